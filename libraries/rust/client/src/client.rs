@@ -1,6 +1,7 @@
 use std::{
     collections::VecDeque,
     error::Error as StdError,
+    future::Future,
     rc::Rc,
     sync::{Arc, Mutex},
 };
@@ -8,13 +9,15 @@ use thiserror::Error;
 
 use solana_sdk::{
     address_lookup_table_account::AddressLookupTableAccount, hash::Hash, instruction::Instruction,
-    pubkey::Pubkey, signature::Signature,
+    packet::PACKET_DATA_SIZE, pubkey::Pubkey, signature::Signature,
+    transaction::VersionedTransaction,
 };
 use spl_associated_token_account::{
     get_associated_token_address, instruction::create_associated_token_account,
 };
 
 use jet_solana_client::{
+    lookup_tables::optimize_lookup_tables,
     rpc::{SolanaRpc, SolanaRpcExtra},
     transaction::{create_unsigned_transaction, ToTransaction},
 };
@@ -164,6 +167,7 @@ impl ClientState {
         }
     }
 
+    /// Send a transaction, using the subset of the lookup tables that makes it smallest
     pub async fn send_with_lookup_tables(
         &self,
         instructions: &[Instruction],
@@ -173,7 +177,7 @@ impl ClientState {
         let tx = create_unsigned_transaction(
             instructions,
             &self.signer(),
-            lookup_tables,
+            &optimize_lookup_tables(instructions, lookup_tables),
             recent_blockhash,
         )
         .map_err(|e| ClientError::Unexpected(format!("compile error: {e:?}")))?;
@@ -190,6 +194,32 @@ impl ClientState {
         Ok(())
     }
 
+    /// Whether the instructions fit in a single transaction when using the lookup tables
+    pub fn fits_in_transaction(
+        &self,
+        instructions: &[Instruction],
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> bool {
+        fits_in_transaction(instructions, &self.signer(), lookup_tables)
+    }
+
+    /// Sign transactions to be sent later, in order, with [send_signed_ordered]
+    pub(crate) async fn sign_ordered(
+        &self,
+        transactions: impl IntoIterator<Item = impl ToTransaction>,
+    ) -> ClientResult<Vec<VersionedTransaction>> {
+        let recent_blockhash = self.get_latest_blockhash().await?;
+        let transactions = transactions
+            .into_iter()
+            .map(|tx| tx.to_transaction(&self.signer(), recent_blockhash))
+            .collect::<Vec<_>>();
+
+        self.wallet
+            .sign_transactions(&transactions)
+            .await
+            .ok_or(ClientError::MissingWallet)
+    }
+
     pub(crate) async fn with_wallet_account(
         &self,
         token: &Pubkey,
@@ -209,3 +239,120 @@ impl ClientState {
         Ok(address)
     }
 }
+
+/// Whether the instructions fit in a single transaction when using the lookup tables
+fn fits_in_transaction(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> bool {
+    let Ok(tx) = create_unsigned_transaction(
+        instructions,
+        payer,
+        &optimize_lookup_tables(instructions, lookup_tables),
+        Hash::default(),
+    ) else {
+        return false;
+    };
+
+    // The transaction is unsigned, so the size of the signatures is added
+    let signatures_size =
+        tx.message.header().num_required_signatures as usize * std::mem::size_of::<Signature>();
+
+    bincode::serialized_size(&tx)
+        .map(|size| size as usize + signatures_size <= PACKET_DATA_SIZE)
+        .unwrap_or(false)
+}
+
+/// Send transactions signed with [ClientState::sign_ordered], waiting for each
+/// to be confirmed before sending the next
+pub(crate) async fn send_signed_ordered(
+    network: &(dyn SolanaRpc + 'static),
+    transactions: &[VersionedTransaction],
+) -> ClientResult<()> {
+    for (index, tx) in transactions.iter().enumerate() {
+        let signature = network.send_transaction(tx).await?;
+        log::info!("submitted transaction #{index}: {signature}");
+
+        network.confirm_transaction_result(signature).await?;
+    }
+
+    Ok(())
+}
+
+/// Run a task in the background, without waiting for it to complete
+///
+/// Outside of a tokio runtime the task can't be spawned, so it is run to
+/// completion before returning instead.
+pub(crate) async fn spawn_or_run(task: impl Future<Output = ()> + Send + 'static) {
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_futures::spawn_local(task);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => {
+            handle.spawn(task);
+        }
+        Err(_) => task.await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::instruction::AccountMeta;
+
+    use super::*;
+
+    fn instruction(accounts: &[Pubkey]) -> Instruction {
+        Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            accounts
+                .iter()
+                .map(|address| AccountMeta::new(*address, false))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn small_transaction_fits() {
+        let accounts = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+        assert!(fits_in_transaction(
+            &[instruction(&accounts)],
+            &Pubkey::new_unique(),
+            &[]
+        ));
+    }
+
+    #[test]
+    fn lookup_tables_make_large_transactions_fit() {
+        let payer = Pubkey::new_unique();
+        let accounts = (0..40).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let instructions = [instruction(&accounts)];
+
+        assert!(!fits_in_transaction(&instructions, &payer, &[]));
+
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: accounts,
+        };
+        assert!(fits_in_transaction(&instructions, &payer, &[table]));
+    }
+
+    #[test]
+    fn too_many_accounts_do_not_fit_even_with_lookup_tables() {
+        let payer = Pubkey::new_unique();
+        let accounts = (0..300).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let instructions = [instruction(&accounts)];
+        let tables = accounts
+            .chunks(256)
+            .map(|addresses| AddressLookupTableAccount {
+                key: Pubkey::new_unique(),
+                addresses: addresses.to_vec(),
+            })
+            .collect::<Vec<_>>();
+
+        assert!(!fits_in_transaction(&instructions, &payer, &tables));
+    }
+}
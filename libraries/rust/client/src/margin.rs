@@ -5,12 +5,14 @@ use std::{collections::HashSet, sync::Arc};
 use wasm_bindgen::prelude::*;
 
 use bytemuck::Zeroable;
+use futures::future::{BoxFuture, FutureExt};
 
 use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
 use spl_associated_token_account::{
     get_associated_token_address, instruction::create_associated_token_account,
 };
 
+use jet_environment::lookup_tables::resolve_lookup_tables;
 use jet_instructions::{
     fixed_term::derive as derive_fixed_term,
     margin::{
@@ -18,25 +20,33 @@ use jet_instructions::{
     },
     margin_pool::{derive_loan_account, derive_margin_pool, MarginPoolIxBuilder},
 };
-use jet_margin::{AccountPosition, MarginAccount, TokenAdmin, TokenConfig, TokenKind, TokenOracle};
+use jet_margin::{
+    AccountPosition, MarginAccount, RiskParameters, TokenAdmin, TokenConfig, TokenKind,
+    TokenOracle, Valuation,
+};
 use jet_margin_pool::{Amount, MarginPool, PoolAction};
 use jet_program_common::Number128;
 use jet_solana_client::rpc::SolanaRpcExtra;
 
 use crate::{
     bail,
-    client::{ClientError, ClientResult, ClientState},
+    client::{send_signed_ordered, spawn_or_run, ClientError, ClientResult, ClientState},
     fixed_term::MarginAccountMarketClient,
     margin_pool::MarginAccountPoolClient,
     state::{
         margin::load_margin_accounts,
         oracles::PriceOracleState,
         tokens::{Mint, TokenAccount},
+        LookupTableCache,
     },
     swaps::MarginAccountSwapsClient,
     JetClient,
 };
 
+use liquidation::{LiquidationPrice, PriceExposure, PriceShock};
+
+pub mod liquidation;
+
 /// Client for interacting with the margin program
 #[derive(Clone)]
 pub struct MarginClient {
@@ -199,22 +209,131 @@ impl MarginAccountClient {
             .unwrap_or_default()
     }
 
+    /// Value this account with the prices and balances from its last refresh,
+    /// regardless of how old the prices are
+    pub fn valuation(&self) -> ClientResult<Valuation> {
+        let (timestamp, risk) = self.valuation_time_and_risk();
+
+        Ok(self.state().valuation(timestamp, &risk)?)
+    }
+
+    /// How the health of this account depends on the price of each underlying token,
+    /// leaving out the same stale positions as the [valuation](Self::valuation)
+    pub fn price_exposures(&self) -> Vec<PriceExposure> {
+        let (timestamp, risk) = self.valuation_time_and_risk();
+        let positions = self.positions_with_token_configs();
+
+        liquidation::price_exposures(
+            positions
+                .iter()
+                .map(|(config, position)| (config.underlying_mint, position)),
+            timestamp,
+            &risk,
+        )
+    }
+
+    /// The time of the last refresh of this account, and risk parameters that accept
+    /// prices of any age
+    fn valuation_time_and_risk(&self) -> (u64, RiskParameters) {
+        let timestamp = self
+            .state()
+            .positions()
+            .flat_map(|p| [p.balance_timestamp, p.price.timestamp])
+            .max()
+            .unwrap_or_default();
+        let risk = RiskParameters {
+            max_price_quote_age: u64::MAX,
+            ..Default::default()
+        };
+
+        (timestamp, risk)
+    }
+
+    /// The price of each underlying token at which this account can be liquidated,
+    /// while the prices of all other tokens stay the same
+    pub fn liquidation_prices(&self) -> ClientResult<Vec<LiquidationPrice>> {
+        let valuation = self.valuation()?;
+
+        Ok(liquidation::liquidation_prices(
+            &valuation,
+            &self.price_exposures(),
+            |token| self.underlying_price(token),
+        ))
+    }
+
+    /// How much of a combined shock to the prices of underlying tokens can be applied
+    /// before this account can be liquidated
+    pub fn liquidation_shock_scale(
+        &self,
+        shocks: &[PriceShock],
+    ) -> ClientResult<Option<Number128>> {
+        let valuation = self.valuation()?;
+
+        Ok(liquidation::liquidation_shock_scale(
+            &valuation,
+            &self.price_exposures(),
+            shocks,
+        ))
+    }
+
     /// Resync the data for this account from the network
     pub async fn sync(&self) -> ClientResult<()> {
         load_margin_accounts(self.client.state(), &[self.address]).await
     }
 
     /// Send a transaction prefixed with refresh instructions for all positions
+    ///
+    /// Any addresses of this account that are not in a known lookup table are added
+    /// to the lookup tables of this account, so later transactions are smaller. The
+    /// tables are extended in the background after the transaction is sent, unless it
+    /// is too large to send without them, or there is no tokio runtime to run it on.
     pub async fn send_with_refresh(&self, instructions: &[Instruction]) -> ClientResult<()> {
         let mut ixs = self.instructions_for_refresh_positions()?;
 
         ixs.extend(instructions.iter().cloned());
 
-        let lookup_tables = self.client.state().lookup_tables.get();
+        let state = self.client.state();
+        let missing = state
+            .lookup_tables
+            .missing_addresses(&ixs, &self.lookup_table_candidates());
+        let lookup_tables = state.lookup_tables.get();
+
+        if !missing.is_empty() && !self.client.fits_in_transaction(&ixs, &lookup_tables) {
+            log::debug!(
+                "extending lookup tables for {} before sending a transaction",
+                self.address
+            );
+            self.extend_lookup_tables(missing).await?;
+
+            return self
+                .client
+                .send_with_lookup_tables(&ixs, &state.lookup_tables.get())
+                .await;
+        }
 
         self.client
             .send_with_lookup_tables(&ixs, &lookup_tables)
-            .await
+            .await?;
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let address = self.address;
+        match self.prepare_lookup_table_extension(missing).await {
+            Ok(Some(extension)) => {
+                spawn_or_run(async move {
+                    if let Err(e) = extension.await {
+                        log::warn!("failed to extend lookup tables for {address}: {e:?}");
+                    }
+                })
+                .await
+            }
+            Ok(None) => (),
+            Err(e) => log::warn!("failed to extend lookup tables for {address}: {e:?}"),
+        }
+
+        Ok(())
     }
 
     /// Close this margin account.
@@ -302,17 +421,25 @@ impl MarginAccountClient {
 
     /// Update lookup tables, creating new tables and adding addresses as necessary
     pub async fn update_lookup_tables(&self) -> ClientResult<()> {
-        let just_created = self.init_lookup_registry().await?;
+        self.extend_lookup_tables(self.lookup_table_candidates())
+            .await
+    }
 
-        if just_created {
-            let slot = self.client.get_slot().await?;
-            self.client.network.wait_for_slot(slot + 1).await?;
+    /// The addresses that belong to this account, which are worth adding to its lookup
+    /// tables: the account, its positions and token accounts, and the token configs
+    /// for them.
+    fn lookup_table_candidates(&self) -> HashSet<Pubkey> {
+        let airspace = self.airspace();
+        let mut accounts = HashSet::from([self.address]);
+
+        if let Some(account) = self.client.state().get::<MarginAccount>(&self.address) {
+            for position in account.positions() {
+                accounts.insert(position.address);
+                accounts.insert(derive_token_config(&airspace, &position.token));
+            }
         }
 
-        // Update existing tables, adding any new tables as necessary
-        let mut accounts = HashSet::new();
-        let state = self.client.state();
-        for token in &state.config.tokens {
+        for token in &self.client.state().config.tokens {
             let ata = get_associated_token_address(&self.address, &token.mint);
             accounts.insert(ata);
             let pool = MarginPoolIxBuilder::new(token.mint);
@@ -321,6 +448,9 @@ impl MarginAccountClient {
                 &pool.deposit_note_mint,
             ));
             accounts.insert(derive_loan_account(&self.address, &pool.loan_note_mint));
+            accounts.insert(derive_token_config(&airspace, &token.mint));
+            accounts.insert(derive_token_config(&airspace, &pool.deposit_note_mint));
+            accounts.insert(derive_token_config(&airspace, &pool.loan_note_mint));
         }
 
         for market in self.client().fixed_term().markets() {
@@ -333,7 +463,36 @@ impl MarginAccountClient {
             accounts.insert(derive_fixed_term::user_underlying_collateral(&margin_user));
         }
 
-        let mut new_accounts = vec![];
+        accounts
+    }
+
+    /// Add addresses to the lookup tables of this account, creating the registry and
+    /// new tables as necessary. The cached lookup tables are updated once the new
+    /// addresses can be used.
+    async fn extend_lookup_tables(&self, accounts: HashSet<Pubkey>) -> ClientResult<()> {
+        match self.prepare_lookup_table_extension(accounts).await? {
+            Some(extension) => extension.await,
+            None => Ok(()),
+        }
+    }
+
+    /// Sign the transactions that add addresses to the lookup tables of this account,
+    /// creating the registry if necessary.
+    ///
+    /// The returned task sends the transactions, and updates the cached lookup tables
+    /// once the new addresses can be used. It doesn't need the wallet, so it can run
+    /// in the background.
+    async fn prepare_lookup_table_extension(
+        &self,
+        accounts: HashSet<Pubkey>,
+    ) -> ClientResult<Option<BoxFuture<'static, ClientResult<()>>>> {
+        let just_created = self.init_lookup_registry().await?;
+
+        if just_created {
+            let slot = self.client.get_slot().await?;
+            self.client.network.wait_for_slot(slot + 1).await?;
+        }
+
         let registry_account = self
             .client
             .network
@@ -341,46 +500,33 @@ impl MarginAccountClient {
             .await?;
         let mut tables = Vec::with_capacity(registry_account.tables.len());
 
-        // If the registry has no tables, add all accounts
-        if registry_account.tables.is_empty() {
-            new_accounts.extend(accounts);
-        } else {
-            for table in &registry_account.tables {
-                if table.discriminator <= 1 {
-                    // Deactivated or deleted
-                    continue;
-                }
-                let lookup_table_account = self
-                    .client()
-                    .client
-                    .network
-                    .get_account(&table.table)
-                    .await?;
-                let Some(lookup_table_account) = lookup_table_account else {
-                    continue;
-                };
-
-                let lookup_table = AddressLookupTable::deserialize(&lookup_table_account.data)?;
-
-                let entry = lookup_table_registry_client::Entry {
-                    discriminator: table.discriminator,
-                    lookup_address: table.table,
-                    addresses: lookup_table.addresses.to_vec(),
-                };
-
-                // Check if there are new accounts
-                for address in &entry.addresses {
-                    if !accounts.contains(address) {
-                        new_accounts.push(*address);
-                    }
-                }
-
-                tables.push(entry);
+        for table in &registry_account.tables {
+            if table.discriminator <= 1 {
+                // Deactivated or deleted
+                continue;
             }
+            let lookup_table_account = self.client.network.get_account(&table.table).await?;
+            let Some(lookup_table_account) = lookup_table_account else {
+                continue;
+            };
+
+            let lookup_table = AddressLookupTable::deserialize(&lookup_table_account.data)?;
+
+            tables.push(Entry {
+                discriminator: table.discriminator,
+                lookup_address: table.table,
+                addresses: lookup_table.addresses.to_vec(),
+            });
         }
 
+        // Only add the accounts that are not in any table yet
+        let mut new_accounts = accounts
+            .into_iter()
+            .filter(|address| !tables.iter().any(|t| t.addresses.contains(address)))
+            .collect::<Vec<_>>();
+
         if new_accounts.is_empty() {
-            return Ok(());
+            return Ok(None);
         }
 
         // For now we are not picky about where addresses are stored, we use
@@ -400,7 +546,7 @@ impl MarginAccountClient {
                         .append_to_lookup_table(entry.lookup_address, &to_add),
                 );
             } else {
-                let slot = self.client().client.get_slot().await?;
+                let slot = self.client.get_slot().await?;
                 let (table_ix, lookup_address) = self.builder.create_lookup_table(slot);
                 append_instructions.push(table_ix);
                 // Add the table to the registry, don't increment index
@@ -412,8 +558,26 @@ impl MarginAccountClient {
             }
         }
 
-        // Submit all instructions
-        self.client.send_ordered(append_instructions).await
+        let transactions = self.client.sign_ordered(append_instructions).await?;
+        let network = self.client.network.clone();
+        let cache = self.client.state().lookup_tables.clone();
+        let address = self.address;
+
+        Ok(Some(
+            async move {
+                send_signed_ordered(network.as_ref(), &transactions).await?;
+
+                // Addresses can only be looked up in the slot after they are added
+                let slot = network.get_slot().await?;
+                network.wait_for_slot(slot + 1).await?;
+
+                let tables = resolve_lookup_tables(network.as_ref(), &address).await?;
+                cache.set(LookupTableCache::DEFAULT_PRIORITY, tables);
+
+                Ok(())
+            }
+            .boxed(),
+        ))
     }
 
     pub(crate) async fn with_deposit_position(
@@ -650,6 +814,19 @@ impl MarginAccountClient {
             .collect()
     }
 
+    fn underlying_price(&self, token: &Pubkey) -> Option<Number128> {
+        let state = self.client.state();
+        let oracle = state
+            .config
+            .tokens
+            .iter()
+            .find(|t| t.mint == *token)?
+            .oracle;
+        let price_state = state.get::<PriceOracleState>(&oracle)?;
+
+        price_state.is_valid.then_some(price_state.price)
+    }
+
     async fn init_lookup_registry(&self) -> ClientResult<bool> {
         let address = self.builder.lookup_table_registry_address();
 
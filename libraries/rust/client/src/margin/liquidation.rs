@@ -0,0 +1,443 @@
+use solana_sdk::pubkey::Pubkey;
+
+use jet_margin::{AccountPosition, RiskParameters, TokenKind, Valuation};
+use jet_program_common::Number128;
+
+/// How the health of a margin account depends on the price of a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceExposure {
+    /// The token that the positions are priced in
+    pub token: Pubkey,
+
+    /// The change in available collateral for each 100% change in the token's price.
+    ///
+    /// This is the collateral value of the positions in the token, less the value and
+    /// required collateral of the claims in the token.
+    pub exposure: Number128,
+}
+
+/// The price of a token at which a margin account can be liquidated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiquidationPrice {
+    /// The token whose price is changed
+    pub token: Pubkey,
+
+    /// The liquidation price as a ratio of the current price
+    pub price_ratio: Number128,
+
+    /// The liquidation price, if the current price of the token is known
+    pub price: Option<Number128>,
+}
+
+/// A relative change in the price of an underlying token, as part of a shock to
+/// several prices
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceShock {
+    /// The underlying token whose price is changed
+    pub token: Pubkey,
+
+    /// The relative change in price, e.g. -0.3 for a 30% drop
+    pub change: Number128,
+}
+
+/// Sum up the exposure of an account to each token, given its positions and the
+/// token each one is priced in.
+///
+/// Positions without a balance, or that are stale at the `timestamp`, are not counted,
+/// as they do not contribute to the account's valuation at the same time.
+pub fn price_exposures<'a>(
+    positions: impl IntoIterator<Item = (Pubkey, &'a AccountPosition)>,
+    timestamp: u64,
+    risk: &RiskParameters,
+) -> Vec<PriceExposure> {
+    let mut exposures: Vec<PriceExposure> = vec![];
+
+    for (token, position) in positions {
+        if position.balance == 0 || is_stale(position, timestamp, risk) {
+            continue;
+        }
+
+        if position.kind() == TokenKind::Claim && position.value_modifier == 0 {
+            // Claims that can't be leveraged make the account unhealthy at any price
+            continue;
+        }
+
+        let exposure = match position.kind() {
+            TokenKind::Collateral | TokenKind::AdapterCollateral => position.collateral_value(),
+            TokenKind::Claim => -(position.value() + position.required_collateral_value()),
+        };
+
+        match exposures.iter_mut().find(|e| e.token == token) {
+            Some(existing) => existing.exposure += exposure,
+            None => exposures.push(PriceExposure { token, exposure }),
+        }
+    }
+
+    exposures
+}
+
+/// Whether the valuation of an account excludes a position, due to an invalid price,
+/// an outdated balance or an outdated price
+fn is_stale(position: &AccountPosition, timestamp: u64, risk: &RiskParameters) -> bool {
+    let balance_age = timestamp.saturating_sub(position.balance_timestamp);
+    let price_quote_age = timestamp.saturating_sub(position.price.timestamp);
+
+    !position.price.is_valid()
+        || (position.max_staleness > 0 && balance_age > position.max_staleness)
+        || price_quote_age > risk.max_price_quote_age
+}
+
+/// Find the price at which `effective_collateral == required_collateral`, as a ratio
+/// of the current price, while the prices of all other tokens stay the same.
+///
+/// Returns `None` if the account can not be liquidated by a change in this price.
+pub fn liquidation_price_ratio(
+    valuation: &Valuation,
+    exposure: &PriceExposure,
+) -> Option<Number128> {
+    if exposure.exposure == Number128::ZERO {
+        return None;
+    }
+
+    let ratio = Number128::ONE - valuation.available_collateral() / exposure.exposure;
+
+    (ratio > Number128::ZERO).then_some(ratio)
+}
+
+/// Find the liquidation price of each token the account is exposed to
+pub fn liquidation_prices(
+    valuation: &Valuation,
+    exposures: &[PriceExposure],
+    current_price: impl Fn(&Pubkey) -> Option<Number128>,
+) -> Vec<LiquidationPrice> {
+    exposures
+        .iter()
+        .filter_map(|exposure| {
+            let price_ratio = liquidation_price_ratio(valuation, exposure)?;
+
+            Some(LiquidationPrice {
+                token: exposure.token,
+                price_ratio,
+                price: current_price(&exposure.token).map(|price| price * price_ratio),
+            })
+        })
+        .collect()
+}
+
+/// Find how much of a combined price shock can be applied before the account can
+/// be liquidated.
+///
+/// A result of `0.5` means the account can be liquidated when each price has
+/// changed by half of its shock, and a result above `1` means the full shock would
+/// leave the account healthy. Returns `None` if scaling the shock in its direction
+/// never makes the account liquidatable.
+pub fn liquidation_shock_scale(
+    valuation: &Valuation,
+    exposures: &[PriceExposure],
+    shocks: &[PriceShock],
+) -> Option<Number128> {
+    let mut sensitivity = Number128::ZERO;
+
+    for shock in shocks {
+        if let Some(exposure) = exposures.iter().find(|e| e.token == shock.token) {
+            sensitivity += exposure.exposure * shock.change;
+        }
+    }
+
+    if sensitivity == Number128::ZERO {
+        return None;
+    }
+
+    let scale = -valuation.available_collateral() / sensitivity;
+    if scale < Number128::ZERO {
+        return None;
+    }
+
+    // Prices can not fall below zero, so a larger shock is not possible
+    let in_range = shocks
+        .iter()
+        .all(|shock| Number128::ONE + shock.change * scale > Number128::ZERO);
+
+    in_range.then_some(scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+    use jet_margin::{Approver, MarginAccount, PositionConfigUpdate, PriceInfo};
+
+    use super::*;
+
+    const NOW: u64 = 1_000;
+
+    struct TestAccount {
+        account: MarginAccount,
+        risk: RiskParameters,
+    }
+
+    impl TestAccount {
+        fn new() -> Self {
+            let mut account = MarginAccount::zeroed();
+            account.initialize(Pubkey::default(), Pubkey::new_unique(), 0, 0);
+
+            Self {
+                account,
+                risk: RiskParameters {
+                    max_price_quote_age: 30,
+                    ..Default::default()
+                },
+            }
+        }
+
+        /// Add a position with a value of `balance` at a price of 1, and the collateral
+        /// weight or max leverage given by `value_modifier`
+        fn add(&mut self, kind: TokenKind, value_modifier: u16, balance: u64) -> Pubkey {
+            self.add_at(kind, value_modifier, balance, NOW, NOW, 0)
+        }
+
+        fn add_at(
+            &mut self,
+            kind: TokenKind,
+            value_modifier: u16,
+            balance: u64,
+            balance_timestamp: u64,
+            price_timestamp: u64,
+            max_staleness: u64,
+        ) -> Pubkey {
+            let mint = Pubkey::new_unique();
+            let adapter = Pubkey::new_unique();
+            let approvals = match kind {
+                TokenKind::Collateral => vec![Approver::MarginAccountAuthority],
+                TokenKind::Claim | TokenKind::AdapterCollateral => {
+                    vec![Approver::MarginAccountAuthority, Approver::Adapter(adapter)]
+                }
+            };
+            self.account
+                .register_position(
+                    PositionConfigUpdate {
+                        mint,
+                        decimals: 0,
+                        address: mint,
+                        airspace: Pubkey::default(),
+                        adapter,
+                        kind,
+                        value_modifier,
+                        max_staleness,
+                    },
+                    &approvals,
+                    &self.risk,
+                )
+                .unwrap();
+            self.account
+                .set_position_price(&mint, &PriceInfo::new_valid(0, 1, price_timestamp))
+                .unwrap();
+            self.account
+                .set_position_balance(&mint, &mint, balance, balance_timestamp)
+                .unwrap();
+
+            mint
+        }
+
+        fn valuation(&self) -> Valuation {
+            self.account.valuation(NOW, &self.risk).unwrap()
+        }
+
+        /// The exposures, with each position priced in its own token
+        fn exposures(&self) -> Vec<PriceExposure> {
+            price_exposures(
+                self.account.positions().map(|p| (p.token, p)),
+                NOW,
+                &self.risk,
+            )
+        }
+    }
+
+    fn n(value: i64) -> Number128 {
+        Number128::from_decimal(value, 0)
+    }
+
+    fn pct(value: i64) -> Number128 {
+        Number128::from_decimal(value, -2)
+    }
+
+    #[test]
+    fn exposures_count_weighted_collateral_and_claims() {
+        let mut test = TestAccount::new();
+        let collateral = test.add(TokenKind::Collateral, 50, 100);
+        let claim = test.add(TokenKind::Claim, 400, 40);
+
+        assert_eq!(
+            vec![
+                PriceExposure {
+                    token: collateral,
+                    exposure: n(50),
+                },
+                PriceExposure {
+                    token: claim,
+                    exposure: -n(50),
+                },
+            ],
+            test.exposures()
+        );
+    }
+
+    #[test]
+    fn exposures_are_summed_by_token() {
+        let mut test = TestAccount::new();
+        let collateral = test.add(TokenKind::Collateral, 100, 100);
+        let claim = test.add(TokenKind::Claim, 400, 40);
+
+        let positions = test.account.positions().map(|p| (collateral, p));
+        let exposures = price_exposures(positions, NOW, &test.risk);
+
+        assert_eq!(
+            vec![PriceExposure {
+                token: collateral,
+                exposure: n(50),
+            }],
+            exposures
+        );
+        assert!(!exposures.iter().any(|e| e.token == claim));
+    }
+
+    #[test]
+    fn exposures_exclude_stale_collateral() {
+        let mut test = TestAccount::new();
+        let fresh = test.add(TokenKind::Collateral, 100, 100);
+        test.add_at(TokenKind::Collateral, 100, 100, NOW - 20, NOW, 10);
+        test.add_at(TokenKind::Collateral, 100, 100, NOW, NOW - 31, 0);
+        test.add(TokenKind::Collateral, 100, 0);
+
+        // Matches the valuation, which also leaves out the stale positions
+        assert_eq!(n(100), test.valuation().weighted_collateral);
+        assert_eq!(
+            vec![PriceExposure {
+                token: fresh,
+                exposure: n(100),
+            }],
+            test.exposures()
+        );
+    }
+
+    #[test]
+    fn liquidation_price_ratio_of_collateral() {
+        let mut test = TestAccount::new();
+        let collateral = test.add(TokenKind::Collateral, 100, 200);
+        test.add(TokenKind::Claim, 400, 100);
+        let valuation = test.valuation();
+
+        // available collateral is 200 - 100 - 25 = 75, so the price can fall by
+        // 75 / 200 before the account can be liquidated
+        let exposure = test.exposures()[0];
+        assert_eq!(collateral, exposure.token);
+        assert_eq!(
+            Some(Number128::ONE - n(75) / n(200)),
+            liquidation_price_ratio(&valuation, &exposure)
+        );
+    }
+
+    #[test]
+    fn liquidation_price_ratio_of_claims() {
+        let mut test = TestAccount::new();
+        test.add(TokenKind::Collateral, 100, 200);
+        test.add(TokenKind::Claim, 400, 100);
+        let valuation = test.valuation();
+
+        // The claim's exposure is -125, so its price can rise by 75 / 125
+        let exposure = test.exposures()[1];
+        assert_eq!(
+            Some(Number128::ONE + n(75) / n(125)),
+            liquidation_price_ratio(&valuation, &exposure)
+        );
+    }
+
+    #[test]
+    fn no_liquidation_price_without_exposure_or_below_zero() {
+        let mut test = TestAccount::new();
+        test.add(TokenKind::Collateral, 100, 200);
+        test.add(TokenKind::Claim, 400, 100);
+        let valuation = test.valuation();
+        let token = Pubkey::new_unique();
+
+        let no_exposure = PriceExposure {
+            token,
+            exposure: Number128::ZERO,
+        };
+        assert_eq!(None, liquidation_price_ratio(&valuation, &no_exposure));
+
+        // More collateral than the exposure can lose, even at a price of zero
+        let small_exposure = PriceExposure {
+            token,
+            exposure: n(50),
+        };
+        assert_eq!(None, liquidation_price_ratio(&valuation, &small_exposure));
+    }
+
+    #[test]
+    fn shock_scale_combines_exposures() {
+        let mut test = TestAccount::new();
+        let collateral = test.add(TokenKind::Collateral, 100, 200);
+        let claim = test.add(TokenKind::Claim, 400, 100);
+        let valuation = test.valuation();
+        let exposures = test.exposures();
+
+        // Collateral falling by 10% and claims rising by 10% loses 20 + 12.5
+        let shocks = [
+            PriceShock {
+                token: collateral,
+                change: -pct(10),
+            },
+            PriceShock {
+                token: claim,
+                change: pct(10),
+            },
+        ];
+        assert_eq!(
+            Some(n(75) / (n(20) + pct(1250))),
+            liquidation_shock_scale(&valuation, &exposures, &shocks)
+        );
+    }
+
+    #[test]
+    fn shock_scale_is_none_when_the_shock_helps_or_is_out_of_range() {
+        let mut test = TestAccount::new();
+        let collateral = test.add(TokenKind::Collateral, 100, 200);
+        test.add(TokenKind::Claim, 400, 100);
+        let valuation = test.valuation();
+        let exposures = test.exposures();
+
+        let rising = [PriceShock {
+            token: collateral,
+            change: pct(10),
+        }];
+        assert_eq!(
+            None,
+            liquidation_shock_scale(&valuation, &exposures, &rising)
+        );
+
+        let falling = PriceShock {
+            token: collateral,
+            change: -pct(10),
+        };
+        assert_eq!(
+            Some(n(75) / n(20)),
+            liquidation_shock_scale(&valuation, &exposures, &[falling])
+        );
+
+        // A token without exposure does not move the account towards liquidation
+        let unrelated = PriceShock {
+            token: Pubkey::new_unique(),
+            change: -pct(50),
+        };
+        assert_eq!(
+            None,
+            liquidation_shock_scale(&valuation, &exposures, &[unrelated])
+        );
+
+        // At a scale of 3.75, the unrelated price would have to fall below zero
+        assert_eq!(
+            None,
+            liquidation_shock_scale(&valuation, &exposures, &[falling, unrelated])
+        );
+    }
+}
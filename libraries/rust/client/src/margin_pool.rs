@@ -48,12 +48,17 @@ impl MarginAccountPoolClient {
         let token_account =
             get_associated_token_address(&self.account.address, &self.builder.token_mint);
 
-        ixns.push(self.account.builder.adapter_invoke(self.builder.deposit(
+        let deposit = self.builder.deposit(
             self.account.address,
             token_account,
             lending_deposit_account,
             TokenChange::shift(amount),
-        )));
+        );
+        ixns.push(
+            self.account
+                .builder
+                .adapter_invoke(self.with_rewards(deposit)),
+        );
 
         self.client.send(&ixns).await
     }
@@ -76,12 +81,12 @@ impl MarginAccountPoolClient {
             get_associated_token_address(&self.client.signer(), &self.builder.token_mint)
         });
 
-        ixns.push(self.builder.deposit(
+        ixns.push(self.with_rewards(self.builder.deposit(
             self.client.signer(),
             token_account,
             lending_deposit_account,
             TokenChange::shift(amount),
-        ));
+        )));
 
         ixns.push(
             self.account
@@ -123,12 +128,17 @@ impl MarginAccountPoolClient {
             Some(value) => TokenChange::shift(value),
         };
 
-        ixns.push(self.account.builder.adapter_invoke(self.builder.withdraw(
+        let withdraw = self.builder.withdraw(
             self.account.address,
             deposit_account,
             deposit_destination,
             change,
-        )));
+        );
+        ixns.push(
+            self.account
+                .builder
+                .adapter_invoke(self.with_rewards(withdraw)),
+        );
 
         self.account.send_with_refresh(&ixns).await
     }
@@ -157,15 +167,13 @@ impl MarginAccountPoolClient {
         };
 
         ixns.extend(self.with_create_loan_account().await?);
-        ixns.push(
-            self.account
-                .builder
-                .adapter_invoke(self.builder.margin_borrow_v2(
-                    self.account.address,
-                    token_account,
-                    amount,
-                )),
-        );
+        ixns.push(self.account.builder.adapter_invoke(
+            self.with_rewards(self.builder.margin_borrow_v2(
+                self.account.address,
+                token_account,
+                amount,
+            )),
+        ));
 
         self.account.send_with_refresh(&ixns).await
     }
@@ -189,15 +197,13 @@ impl MarginAccountPoolClient {
         };
 
         ixns.extend(self.with_create_loan_account().await?);
-        ixns.push(
-            self.account
-                .builder
-                .adapter_invoke(self.builder.margin_borrow_v2(
-                    self.account.address,
-                    destination,
-                    amount,
-                )),
-        );
+        ixns.push(self.account.builder.adapter_invoke(
+            self.with_rewards(self.builder.margin_borrow_v2(
+                self.account.address,
+                destination,
+                amount,
+            )),
+        ));
 
         self.account.send_with_refresh(&ixns).await
     }
@@ -218,15 +224,13 @@ impl MarginAccountPoolClient {
             None => TokenChange::set(0),
             Some(value) => TokenChange::shift(value),
         };
-        let mut instructions =
-            vec![self
-                .account
-                .builder
-                .adapter_invoke(self.builder.margin_repay(
-                    self.account.address,
-                    deposit_account,
-                    change,
-                ))];
+        let mut instructions = vec![self.account.builder.adapter_invoke(
+            self.with_rewards(self.builder.margin_repay(
+                self.account.address,
+                deposit_account,
+                change,
+            )),
+        )];
 
         if amount.is_none() {
             instructions.push(
@@ -277,6 +281,11 @@ impl MarginAccountPoolClient {
         .await
     }
 
+    /// Include the accounts to checkpoint the rewards earned by the margin account
+    fn with_rewards(&self, ix: Instruction) -> Instruction {
+        self.builder.with_rewards(ix, &self.account.address)
+    }
+
     fn instruction_for_refresh(&self) -> ClientResult<Instruction> {
         let token_info = self.client.state().token_info(&self.builder.token_mint)?;
 
@@ -300,8 +309,11 @@ impl MarginAccountPoolClient {
         };
         let mut instructions = vec![
             proxy_ix(
-                self.builder
-                    .repay(*authority, *token_account, loan_account, change),
+                self.builder.with_loan_rewards(
+                    self.builder
+                        .repay(*authority, *token_account, loan_account, change),
+                    &[self.account.address],
+                ),
             ),
             self.account.builder.update_position_balance(loan_account),
         ];
@@ -1,12 +1,15 @@
 use std::{
     any::{Any, TypeId},
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     sync::{Arc, Mutex},
 };
 
 use jet_instructions::airspace::derive_airspace;
 use jet_solana_client::rpc::SolanaRpc;
-use solana_sdk::{address_lookup_table_account::AddressLookupTableAccount, pubkey::Pubkey};
+use solana_sdk::{
+    address_lookup_table_account::AddressLookupTableAccount, instruction::Instruction,
+    pubkey::Pubkey,
+};
 
 use crate::{
     client::ClientResult,
@@ -29,7 +32,7 @@ pub struct AccountStates {
     pub(crate) network: Arc<dyn SolanaRpc>,
     pub(crate) wallet: Pubkey,
     pub(crate) config: StateConfig,
-    pub(crate) lookup_tables: LookupTableCache,
+    pub(crate) lookup_tables: Arc<LookupTableCache>,
     cache: AccountCache,
 }
 
@@ -67,7 +70,7 @@ impl AccountStates {
         log::debug!("loaded state config: {config:#?}");
 
         let cache = AccountCache::default();
-        let lookup_tables = LookupTableCache::default();
+        let lookup_tables = Arc::new(LookupTableCache::default());
 
         Ok(Self {
             config,
@@ -275,14 +278,118 @@ impl LookupTableCache {
         tables.iter().flat_map(|(_, t)| t).cloned().collect()
     }
 
+    /// Add tables with a priority, replacing any cached tables with the same address
     pub fn set(&self, priority: u32, data: impl IntoIterator<Item = AddressLookupTableAccount>) {
         let mut tables = self.tables.lock().unwrap();
 
-        match tables.get_mut(&priority) {
-            Some(t) => t.extend(data),
-            None => {
-                tables.insert(priority, data.into_iter().collect());
+        for table in data {
+            for cached in tables.values_mut() {
+                cached.retain(|t| t.key != table.key);
             }
+
+            tables.entry(priority).or_default().push(table);
         }
     }
+
+    /// The addresses used by the instructions that are not in any of the tables.
+    ///
+    /// Only the `candidates` are considered, so that tables are not filled with
+    /// addresses that are unlikely to be used again. Signers are excluded, as they
+    /// can't be loaded from a lookup table.
+    pub fn missing_addresses(
+        &self,
+        instructions: &[Instruction],
+        candidates: &HashSet<Pubkey>,
+    ) -> HashSet<Pubkey> {
+        let tables = self.tables.lock().unwrap();
+        let known = tables
+            .values()
+            .flatten()
+            .flat_map(|table| &table.addresses)
+            .collect::<HashSet<_>>();
+
+        instructions
+            .iter()
+            .flat_map(|ix| &ix.accounts)
+            .filter(|meta| {
+                !meta.is_signer
+                    && candidates.contains(&meta.pubkey)
+                    && !known.contains(&meta.pubkey)
+            })
+            .map(|meta| meta.pubkey)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::instruction::AccountMeta;
+
+    use super::*;
+
+    fn table(addresses: &[Pubkey]) -> AddressLookupTableAccount {
+        AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: addresses.to_vec(),
+        }
+    }
+
+    #[test]
+    fn missing_addresses_are_candidates_not_in_tables() {
+        let in_table = Pubkey::new_unique();
+        let missing = Pubkey::new_unique();
+        let unrelated = Pubkey::new_unique();
+        let signer = Pubkey::new_unique();
+
+        let cache = LookupTableCache::default();
+        cache.set(LookupTableCache::DEFAULT_PRIORITY, [table(&[in_table])]);
+
+        let instructions = [
+            Instruction::new_with_bytes(
+                Pubkey::new_unique(),
+                &[],
+                vec![
+                    AccountMeta::new(in_table, false),
+                    AccountMeta::new(missing, false),
+                    AccountMeta::new_readonly(signer, true),
+                ],
+            ),
+            Instruction::new_with_bytes(
+                Pubkey::new_unique(),
+                &[],
+                vec![
+                    AccountMeta::new_readonly(missing, false),
+                    AccountMeta::new(unrelated, false),
+                ],
+            ),
+        ];
+        let candidates = HashSet::from([in_table, missing, signer]);
+
+        assert_eq!(
+            HashSet::from([missing]),
+            cache.missing_addresses(&instructions, &candidates)
+        );
+    }
+
+    #[test]
+    fn nothing_is_missing_once_added_to_a_table() {
+        let address = Pubkey::new_unique();
+        let instructions = [Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![AccountMeta::new(address, false)],
+        )];
+        let candidates = HashSet::from([address]);
+
+        let cache = LookupTableCache::default();
+        assert_eq!(
+            HashSet::from([address]),
+            cache.missing_addresses(&instructions, &candidates)
+        );
+
+        cache.set(1, [table(&[address])]);
+        assert!(cache
+            .missing_addresses(&instructions, &candidates)
+            .is_empty());
+    }
 }
@@ -3,12 +3,13 @@ use std::{collections::HashMap, sync::Arc};
 use solana_sdk::pubkey::Pubkey;
 
 use jet_instructions::margin_swap::SwapAccounts;
-use jet_program_common::programs::{OPENBOOK, ORCA_WHIRLPOOL};
+use jet_program_common::programs::{OPENBOOK, ORCA_WHIRLPOOL, PHOENIX};
 
 use super::AccountStates;
 use crate::client::ClientResult;
 
 mod openbook;
+mod phoenix;
 mod whirlpool;
 
 /// Generalized data about a DEX pool/market
@@ -33,6 +34,7 @@ pub async fn sync(states: &AccountStates) -> ClientResult<()> {
 
     whirlpool::load_orca_whirlpools(states, &dexes_for_program(&ORCA_WHIRLPOOL)).await?;
     openbook::load_openbook_markets(states, &dexes_for_program(&OPENBOOK)).await?;
+    phoenix::load_phoenix_markets(states, &dexes_for_program(&PHOENIX)).await?;
 
     Ok(())
 }
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use jet_environment::client_config::DexInfo;
+use jet_instructions::phoenix::PhoenixMarket;
+use jet_program_common::programs::PHOENIX;
+use jet_solana_client::rpc::SolanaRpcExtra;
+
+use crate::{state::AccountStates, ClientResult};
+
+use super::DexState;
+
+pub async fn load_phoenix_markets(states: &AccountStates, markets: &[DexInfo]) -> ClientResult<()> {
+    let market_addrs = markets.iter().map(|m| m.address).collect::<Vec<_>>();
+
+    log::debug!("loading phoenix markets: {market_addrs:?}");
+
+    let market_states = states.network.get_accounts_all(&market_addrs).await?;
+
+    for (address, account_state) in market_addrs.into_iter().zip(market_states) {
+        let Some(account_state) = account_state else {
+            continue;
+        };
+
+        let Some(market) = PhoenixMarket::from_account_data(address, &account_state.data) else {
+            log::warn!("failed to load phoenix market {address}");
+            continue;
+        };
+
+        let swap_accounts = Arc::new(market);
+        let dex_state = DexState {
+            program: PHOENIX,
+            token_a: market.base_mint,
+            token_b: market.quote_mint,
+            swap_a_to_b_accounts: swap_accounts.clone(),
+            swap_b_to_a_accounts: swap_accounts,
+        };
+
+        states.set(&address, dex_state);
+    }
+
+    Ok(())
+}
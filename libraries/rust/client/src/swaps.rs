@@ -40,11 +40,31 @@ impl MarginAccountSwapsClient {
         path: &[SwapStep],
         in_amount: u64,
         minimum_amount_out: u64,
+    ) -> ClientResult<()> {
+        let path = path
+            .iter()
+            .map(|step| (step.clone(), 0))
+            .collect::<Vec<_>>();
+
+        self.route_swap_with_splits(&path, in_amount, minimum_amount_out)
+            .await
+    }
+
+    /// Swap tokens with a given path, where legs can be split between two venues
+    ///
+    /// Each step has the percentage of the leg's input to swap through it. A step
+    /// with a split must be followed by the step for the other venue, with a
+    /// split of zero.
+    pub async fn route_swap_with_splits(
+        &self,
+        path: &[(SwapStep, u8)],
+        in_amount: u64,
+        minimum_amount_out: u64,
     ) -> ClientResult<()> {
         let mut instructions = vec![];
 
-        let source_token = path[0].from_token;
-        let dest_token = path[path.len() - 1].to_token;
+        let source_token = path[0].0.from_token;
+        let dest_token = path[path.len() - 1].0.to_token;
         let mut builder = MarginSwapRouteIxBuilder::new(
             SwapContext::MarginPositions,
             self.account.address(),
@@ -54,7 +74,7 @@ impl MarginAccountSwapsClient {
             minimum_amount_out,
         );
 
-        for step in path {
+        for (step, split) in path {
             self.account
                 .with_deposit_position(&step.to_token, &mut instructions)
                 .await?;
@@ -82,7 +102,7 @@ impl MarginAccountSwapsClient {
                 &dex_state.swap_b_to_a_accounts
             };
 
-            builder.add_swap_leg(swap_accounts.as_ref(), 0)?;
+            builder.add_swap_leg(swap_accounts.as_ref(), *split)?;
         }
 
         builder.finalize()?;
@@ -25,7 +25,10 @@ use jet_solana_client::{
     transaction::TransactionBuilder,
 };
 
-use crate::config::{EnvironmentConfig, TokenDescription};
+use crate::{
+    config::{EnvironmentConfig, TokenDescription},
+    diff::EnvironmentDiff,
+};
 
 pub(crate) mod fixed_term;
 pub(crate) mod global;
@@ -105,6 +108,20 @@ pub struct TokenContext {
     desc: TokenDescription, // TODO: is this whole thing really needed?
 }
 
+impl TokenContext {
+    pub fn mint(&self) -> Pubkey {
+        self.mint
+    }
+
+    pub fn pyth_price(&self) -> Pubkey {
+        self.pyth_price
+    }
+
+    pub fn pyth_product(&self) -> Pubkey {
+        self.pyth_product
+    }
+}
+
 pub struct PlanInstructions {
     pub setup: Vec<Vec<TransactionBuilder>>,
     pub lookup_setup: HashMap<LookupScope, HashSet<Pubkey>>,
@@ -126,6 +143,7 @@ pub struct Builder {
     setup_tx: BTreeMap<SetupPhase, Vec<TransactionBuilder>>,
     propose_tx: Vec<TransactionBuilder>,
     addr_lookup_scopes: HashMap<LookupScope, HashSet<Pubkey>>,
+    pub(crate) diff: EnvironmentDiff,
 }
 
 impl Builder {
@@ -141,6 +159,7 @@ impl Builder {
             setup_tx: BTreeMap::new(),
             propose_tx: vec![],
             addr_lookup_scopes: HashMap::new(),
+            diff: EnvironmentDiff::default(),
             signer,
         })
     }
@@ -162,6 +181,7 @@ impl Builder {
             setup_tx: BTreeMap::new(),
             propose_tx: vec![],
             addr_lookup_scopes: HashMap::new(),
+            diff: EnvironmentDiff::default(),
             signer,
         }
     }
@@ -22,6 +22,8 @@ use jet_solana_client::{rpc::SolanaRpcExtra, transaction::TransactionBuilder};
 
 use crate::config::FixedTermMarketConfig;
 
+use crate::diff::{FieldDiff, ResourceChange, ResourceKind};
+
 use super::{
     margin::configure_margin_token, Builder, BuilderError, LookupScope, NetworkKind, SetupPhase,
     TokenContext,
@@ -60,6 +62,8 @@ pub async fn configure_market_for_token(
         .try_get_anchor_account::<Market>(&market_address)
         .await?;
 
+    record_market_diff(builder, token, config, &market_address, market.as_ref());
+
     let ix_builder = match market {
         None => create_market_for_token(builder, token, config, fee_destination).await?,
         Some(market) => FixedTermIxBuilder::new(
@@ -146,16 +150,73 @@ pub async fn configure_market_for_token(
     Ok(ix_builder)
 }
 
+fn record_market_diff(
+    builder: &mut Builder,
+    token: &TokenContext,
+    config: &FixedTermMarketConfig,
+    address: &Pubkey,
+    market: Option<&Market>,
+) {
+    let change = match market {
+        None => ResourceChange::Create(vec![
+            ("borrow_tenor".to_owned(), config.borrow_tenor.to_string()),
+            ("lend_tenor".to_owned(), config.lend_tenor.to_string()),
+            (
+                "origination_fee".to_owned(),
+                config.origination_fee.to_string(),
+            ),
+            (
+                "min_order_size".to_owned(),
+                config.min_order_size.to_string(),
+            ),
+            ("paused".to_owned(), config.paused.to_string()),
+        ]),
+        Some(market) => {
+            let mut fields = vec![];
+
+            if market.lend_tenor != config.lend_tenor {
+                fields.push(FieldDiff::new(
+                    "lend_tenor",
+                    &market.lend_tenor,
+                    &config.lend_tenor,
+                ));
+            }
+
+            if market.origination_fee != config.origination_fee {
+                fields.push(FieldDiff::new(
+                    "origination_fee",
+                    &market.origination_fee,
+                    &config.origination_fee,
+                ));
+            }
+
+            if market.orderbook_paused.as_bool() != config.paused {
+                fields.push(FieldDiff::new(
+                    "paused",
+                    &market.orderbook_paused.as_bool(),
+                    &config.paused,
+                ));
+            }
+
+            ResourceChange::Update(fields)
+        }
+    };
+
+    builder.diff.record(
+        ResourceKind::FixedTermMarket,
+        format!("{}_{}", token.desc.name, config.borrow_tenor),
+        *address,
+        change,
+    );
+}
+
 async fn configure_cranks_for_market(
     builder: &mut Builder,
     ix_builder: &FixedTermIxBuilder,
     cranks: &[Pubkey],
 ) -> Result<(), BuilderError> {
     for crank in cranks {
-        if builder
-            .account_exists(&ix_builder.crank_authorization(crank))
-            .await?
-        {
+        if crank_authorization_exists(builder, ix_builder, crank).await? {
             continue;
         }
 
@@ -167,9 +228,10 @@ async fn configure_cranks_for_market(
     }
 
     if builder.network == NetworkKind::Localnet && cranks.is_empty() {
-        let authorization = ix_builder.crank_authorization(&builder.proposal_authority());
+        let authority = builder.proposal_authority();
+        let authorization = ix_builder.crank_authorization(&authority);
 
-        if !builder.account_exists(&authorization).await? {
+        if !crank_authorization_exists(builder, ix_builder, &authority).await? {
             builder.propose([test_service::if_not_initialized(
                 authorization,
                 ix_builder.authorize_crank(builder.proposal_authority()),
@@ -180,12 +242,34 @@ async fn configure_cranks_for_market(
     Ok(())
 }
 
+async fn crank_authorization_exists(
+    builder: &mut Builder,
+    ix_builder: &FixedTermIxBuilder,
+    crank: &Pubkey,
+) -> Result<bool, BuilderError> {
+    let authorization = ix_builder.crank_authorization(crank);
+    let exists = builder.account_exists(&authorization).await?;
+
+    builder.diff.record(
+        ResourceKind::CrankPermit,
+        format!("for crank {crank} in market {}", ix_builder.market()),
+        authorization,
+        match exists {
+            true => ResourceChange::Update(vec![]),
+            false => ResourceChange::Create(vec![]),
+        },
+    );
+
+    Ok(exists)
+}
+
 async fn configure_margin_for_market(
     builder: &mut Builder,
     token: &TokenContext,
     market_address: &Pubkey,
     config: &FixedTermMarketConfig,
 ) -> Result<(), BuilderError> {
+    let market_name = format!("{}_{}", token.desc.name, config.borrow_tenor);
     let claims_mint = derive::claims_mint(market_address);
     let ticket_collateral_mint = derive::ticket_collateral_mint(market_address);
     let underlying_collateral_mint = derive::underlying_collateral_mint(market_address);
@@ -205,6 +289,7 @@ async fn configure_margin_for_market(
     if let Some(oracle) = ticket_oracle {
         configure_margin_token(
             builder,
+            &format!("{market_name} ticket"),
             &token.airspace,
             &ticket_mint,
             Some(TokenConfigUpdate {
@@ -221,6 +306,7 @@ async fn configure_margin_for_market(
     // ticket collateral
     configure_margin_token(
         builder,
+        &format!("{market_name} ticket collateral"),
         &token.airspace,
         &ticket_collateral_mint,
         Some(TokenConfigUpdate {
@@ -236,6 +322,7 @@ async fn configure_margin_for_market(
     // token collateral
     configure_margin_token(
         builder,
+        &format!("{market_name} underlying collateral"),
         &token.airspace,
         &underlying_collateral_mint,
         Some(TokenConfigUpdate {
@@ -250,6 +337,7 @@ async fn configure_margin_for_market(
 
     configure_margin_token(
         builder,
+        &format!("{market_name} claims"),
         &token.airspace,
         &claims_mint,
         Some(TokenConfigUpdate {
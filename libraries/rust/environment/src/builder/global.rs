@@ -1,5 +1,8 @@
 use solana_sdk::pubkey::Pubkey;
 
+use jet_airspace::state::Airspace;
+use jet_solana_client::rpc::SolanaRpcExtra;
+
 use jet_instructions::{
     airspace::{derive_governor_id, AirspaceIxBuilder},
     control::{get_control_authority_address, ControlIxBuilder},
@@ -14,7 +17,10 @@ use super::{
     filter_initializers, fixed_term, margin::configure_margin_token, margin_pool, Builder,
     BuilderError, NetworkKind, SetupPhase, TokenContext,
 };
-use crate::config::{AirspaceConfig, EnvironmentConfig, TokenDescription, DEFAULT_MARGIN_ADAPTERS};
+use crate::{
+    config::{AirspaceConfig, EnvironmentConfig, TokenDescription, DEFAULT_MARGIN_ADAPTERS},
+    diff::{FieldDiff, ResourceChange, ResourceKind},
+};
 
 pub async fn configure_environment(
     builder: &mut Builder,
@@ -68,7 +74,14 @@ pub(crate) async fn configure_airspace(
         builder.proposal_authority(),
     );
 
-    if !builder.account_exists(&as_ix.address()).await? {
+    let airspace = builder
+        .interface
+        .try_get_anchor_account::<Airspace>(&as_ix.address())
+        .await?;
+
+    record_airspace_diff(builder, config, &as_ix.address(), airspace.as_ref());
+
+    if airspace.is_none() {
         log::info!("create airspace '{}' as {}", &config.name, as_ix.address());
         builder.propose([as_ix.create(builder.proposal_authority(), config.is_restricted)]);
     }
@@ -91,6 +104,42 @@ pub(crate) async fn configure_airspace(
     Ok(())
 }
 
+fn record_airspace_diff(
+    builder: &mut Builder,
+    config: &AirspaceConfig,
+    address: &Pubkey,
+    airspace: Option<&Airspace>,
+) {
+    let authority = builder.proposal_authority();
+    let change = match airspace {
+        None => ResourceChange::Create(vec![
+            ("authority".to_owned(), authority.to_string()),
+            ("is_restricted".to_owned(), config.is_restricted.to_string()),
+        ]),
+        Some(airspace) => {
+            let mut fields = vec![];
+
+            if airspace.authority != authority {
+                fields.push(FieldDiff::new("authority", &airspace.authority, &authority));
+            }
+
+            if airspace.is_restricted != config.is_restricted {
+                fields.push(FieldDiff::new(
+                    "is_restricted",
+                    &airspace.is_restricted,
+                    &config.is_restricted,
+                ));
+            }
+
+            ResourceChange::Update(fields)
+        }
+    };
+
+    builder
+        .diff
+        .record(ResourceKind::Airspace, &config.name, *address, change);
+}
+
 async fn register_airspace_adapters<'a>(
     builder: &mut Builder,
     airspace: &Pubkey,
@@ -127,6 +176,7 @@ pub async fn configure_tokens<'a>(
         // Set margin config for the token itself
         configure_margin_token(
             builder,
+            &desc.name,
             airspace,
             &token_context.mint,
             Some(TokenConfigUpdate {
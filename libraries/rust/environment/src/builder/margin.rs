@@ -5,15 +5,26 @@ use jet_margin::{TokenConfig, TokenConfigUpdate};
 use jet_solana_client::rpc::SolanaRpcExtra;
 
 use super::{Builder, BuilderError, LookupScope};
+use crate::diff::{diff_fields, list_fields, FieldDiff, ResourceChange, ResourceKind};
 
 pub async fn configure_margin_token(
     builder: &mut Builder,
+    name: &str,
     airspace: &Pubkey,
     mint: &Pubkey,
     config: Option<TokenConfigUpdate>,
 ) -> Result<(), BuilderError> {
     let existing_config = get_token_config(builder, airspace, mint).await?;
 
+    record_token_config_diff(
+        builder,
+        name,
+        airspace,
+        mint,
+        existing_config.as_ref(),
+        &config,
+    );
+
     let should_update = match (existing_config, &config) {
         (None, None) => false,
         (Some(existing), Some(update)) => existing != *update,
@@ -43,3 +54,49 @@ pub async fn get_token_config(
     let address = derive_token_config(airspace, mint);
     Ok(builder.interface.try_get_anchor_account(&address).await?)
 }
+
+fn record_token_config_diff(
+    builder: &mut Builder,
+    name: &str,
+    airspace: &Pubkey,
+    mint: &Pubkey,
+    existing: Option<&TokenConfig>,
+    config: &Option<TokenConfigUpdate>,
+) {
+    let change = match (existing, config) {
+        (None, None) => return,
+        (None, Some(update)) => ResourceChange::Create(list_fields!(
+            update,
+            [
+                underlying_mint,
+                admin,
+                token_kind,
+                value_modifier,
+                max_staleness
+            ]
+        )),
+        (Some(existing), Some(update)) => ResourceChange::Update(diff_fields!(
+            existing,
+            update,
+            [
+                underlying_mint,
+                admin,
+                token_kind,
+                value_modifier,
+                max_staleness
+            ]
+        )),
+        (Some(_), None) => ResourceChange::Update(vec![FieldDiff {
+            field: "config".to_owned(),
+            current: "configured".to_owned(),
+            expected: "removed".to_owned(),
+        }]),
+    };
+
+    builder.diff.record(
+        ResourceKind::TokenConfig,
+        name,
+        derive_token_config(airspace, mint),
+        change,
+    );
+}
@@ -3,10 +3,13 @@ use jet_instructions::{
     margin::{derive_token_config, TokenAdmin, TokenConfigUpdate, TokenKind},
     margin_pool::{derive_margin_pool, MarginPoolIxBuilder, MARGIN_POOL_PROGRAM},
 };
-use jet_margin_pool::MarginPool;
+use jet_margin::TokenConfig;
+use jet_margin_pool::{MarginPool, MarginPoolConfig, PoolFlags};
 use jet_solana_client::rpc::SolanaRpcExtra;
+use solana_sdk::pubkey::Pubkey;
 
 use super::{Builder, BuilderError, LookupScope, TokenContext};
+use crate::diff::{diff_fields, list_fields, FieldDiff, ResourceChange, ResourceKind};
 
 pub(crate) async fn configure_for_token(
     builder: &mut Builder,
@@ -23,7 +26,14 @@ pub(crate) async fn configure_for_token(
     let pool = builder
         .interface
         .try_get_anchor_account::<MarginPool>(&pool_ix.address)
-        .await?;
+        .await?
+        .map(|mut pool| {
+            // rewards are enabled by `configure_rewards`, so aren't part of the config
+            pool.config.flags &= !PoolFlags::REWARDS.bits();
+            pool
+        });
+
+    record_pool_diff(builder, token, pool_config, pool.as_ref());
 
     let mut configure_pool_ixns = vec![];
 
@@ -74,6 +84,21 @@ pub(crate) async fn configure_for_token(
         )
         .await?;
 
+    record_note_config_diff(
+        builder,
+        &format!("{} deposit notes", &token.desc.name),
+        derive_token_config(&token.airspace, &pool_ix.deposit_note_mint),
+        note_configs[0].as_ref(),
+        token.desc.collateral_weight,
+    );
+    record_note_config_diff(
+        builder,
+        &format!("{} loan notes", &token.desc.name),
+        derive_token_config(&token.airspace, &pool_ix.loan_note_mint),
+        note_configs[1].as_ref(),
+        token.desc.max_leverage,
+    );
+
     let should_update_deposit = note_configs[0]
         .as_ref()
         .map(|c| c.value_modifier != token.desc.collateral_weight)
@@ -123,3 +148,99 @@ pub(crate) async fn configure_for_token(
 
     Ok(())
 }
+
+fn record_pool_diff(
+    builder: &mut Builder,
+    token: &TokenContext,
+    config: &MarginPoolConfig,
+    pool: Option<&MarginPool>,
+) {
+    let change = match pool {
+        None => {
+            let mut fields = vec![(
+                "token_price_oracle".to_owned(),
+                token.pyth_price.to_string(),
+            )];
+            fields.extend(list_fields!(
+                config,
+                [
+                    flags,
+                    utilization_rate_1,
+                    utilization_rate_2,
+                    borrow_rate_0,
+                    borrow_rate_1,
+                    borrow_rate_2,
+                    borrow_rate_3,
+                    management_fee_rate,
+                    flash_loan_fee_rate
+                ]
+            ));
+
+            ResourceChange::Create(fields)
+        }
+        Some(pool) => {
+            let mut fields = vec![];
+
+            if pool.token_price_oracle != token.pyth_price {
+                fields.push(FieldDiff::new(
+                    "token_price_oracle",
+                    &pool.token_price_oracle,
+                    &token.pyth_price,
+                ));
+            }
+
+            fields.extend(diff_fields!(
+                pool.config,
+                config,
+                [
+                    flags,
+                    utilization_rate_1,
+                    utilization_rate_2,
+                    borrow_rate_0,
+                    borrow_rate_1,
+                    borrow_rate_2,
+                    borrow_rate_3,
+                    management_fee_rate,
+                    flash_loan_fee_rate,
+                    reserved
+                ]
+            ));
+
+            ResourceChange::Update(fields)
+        }
+    };
+
+    builder.diff.record(
+        ResourceKind::MarginPool,
+        &token.desc.name,
+        derive_margin_pool(&token.airspace, &token.mint),
+        change,
+    );
+}
+
+fn record_note_config_diff(
+    builder: &mut Builder,
+    name: &str,
+    address: Pubkey,
+    existing: Option<&TokenConfig>,
+    value_modifier: u16,
+) {
+    let change = match existing {
+        None => ResourceChange::Create(vec![(
+            "value_modifier".to_owned(),
+            value_modifier.to_string(),
+        )]),
+        Some(existing) => ResourceChange::Update(
+            (existing.value_modifier != value_modifier)
+                .then(|| {
+                    FieldDiff::new("value_modifier", &existing.value_modifier, &value_modifier)
+                })
+                .into_iter()
+                .collect(),
+        ),
+    };
+
+    builder
+        .diff
+        .record(ResourceKind::TokenConfig, name, address, change);
+}
@@ -7,9 +7,11 @@ use jet_instructions::{
         derive_whirlpool, derive_whirlpool_oracle, whirlpool_initialize_fee_tier,
         WhirlpoolIxBuilder,
     },
+    phoenix::{derive_log_authority, derive_vault},
     test_service::{
-        derive_openbook_market, derive_spl_swap_pool, derive_whirlpool_config,
-        openbook_market_create, orca_whirlpool_create_config, saber_swap_pool_create,
+        derive_openbook_market, derive_phoenix_market, derive_spl_swap_pool,
+        derive_whirlpool_config, openbook_market_create, orca_whirlpool_create_config,
+        phoenix_market_allocate, phoenix_market_create, saber_swap_pool_create,
         spl_swap_pool_create,
     },
 };
@@ -53,6 +55,10 @@ pub fn resolve_swap_program(network: NetworkKind, name: &str) -> Result<Pubkey,
         return Ok(ORCA_WHIRLPOOL);
     }
 
+    if name == "phoenix" {
+        return Ok(PHOENIX);
+    }
+
     Err(BuilderError::UnknownSwapProgram(name.to_string()))
 }
 
@@ -73,6 +79,8 @@ pub fn resolve_swap_address(program: &Pubkey, base: &Pubkey, quote: &Pubkey) ->
         Some(derive_openbook_market(program, base, quote, &Pubkey::default()).state)
     } else if *program == ORCA_V2 {
         Some(derive_spl_swap_pool(program, base, quote).state)
+    } else if *program == PHOENIX {
+        Some(derive_phoenix_market(base, quote))
     } else {
         None
     }
@@ -101,6 +109,7 @@ pub async fn create_swap_pools<'a>(
                 create_openbook_market(builder, swap_program, token_a, token_b).await?;
             }
             p if p == "orca-whirlpool" => create_orca_whirlpool(builder, token_a, token_b).await?,
+            p if p == "phoenix" => create_phoenix_market(builder, token_a, token_b).await?,
             p => {
                 log::warn!("ignoring unknown swap program {} {p}", pool.program);
                 continue;
@@ -251,6 +260,48 @@ async fn create_openbook_market(
     Ok(())
 }
 
+async fn create_phoenix_market(
+    builder: &mut Builder,
+    token_a: Pubkey,
+    token_b: Pubkey,
+) -> Result<(), BuilderError> {
+    log::info!("Create Phoenix market for {}/{}", token_a, token_b);
+
+    let market = derive_phoenix_market(&token_a, &token_b);
+
+    if builder.account_exists(&market).await? {
+        return Ok(());
+    }
+
+    builder.register_lookups(
+        LookupScope::Swaps,
+        [
+            market,
+            derive_vault(&market, &token_a),
+            derive_vault(&market, &token_b),
+            derive_log_authority(),
+        ],
+    );
+
+    let (allocate_ix, market_keypair) =
+        phoenix_market_allocate(&builder.payer(), &token_a, &token_b);
+
+    builder.setup(
+        SetupPhase::TokenAccounts,
+        [TransactionBuilder {
+            instructions: vec![allocate_ix],
+            signers: vec![market_keypair],
+        }],
+    );
+
+    builder.setup(
+        SetupPhase::Swaps,
+        [phoenix_market_create(&builder.payer(), &token_a, &token_b)],
+    );
+
+    Ok(())
+}
+
 async fn create_spl_swap_pool(
     builder: &mut Builder,
     swap_program: Pubkey,
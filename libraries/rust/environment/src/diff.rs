@@ -0,0 +1,434 @@
+//! Comparison between the state of an environment on chain, and the configuration
+//! describing what it should be.
+//!
+//! The comparisons are recorded by the [crate::builder::Builder] while it decides which
+//! instructions are needed to apply a config, so the report always reflects the same
+//! logic used to generate the proposed changes.
+
+use std::{
+    collections::HashSet,
+    fmt::{Debug, Display, Formatter},
+};
+
+use solana_sdk::pubkey::Pubkey;
+
+use jet_airspace::state::{Airspace, AirspacePermit};
+use jet_fixed_term::control::state::{CrankAuthorization, Market};
+use jet_instructions::airspace::derive_airspace;
+use jet_margin::TokenConfig;
+use jet_margin_pool::MarginPool;
+use jet_solana_client::rpc::SolanaRpcExtra;
+
+use crate::{
+    builder::{configure_environment, Builder, BuilderError},
+    config::EnvironmentConfig,
+};
+
+/// The types of resources that can be described by an environment config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ResourceKind {
+    Airspace,
+    TokenConfig,
+    MarginPool,
+    FixedTermMarket,
+    CrankPermit,
+    AirspacePermit,
+}
+
+impl Display for ResourceKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ResourceKind::Airspace => "airspace",
+            ResourceKind::TokenConfig => "token config",
+            ResourceKind::MarginPool => "margin pool",
+            ResourceKind::FixedTermMarket => "fixed term market",
+            ResourceKind::CrankPermit => "crank permit",
+            ResourceKind::AirspacePermit => "airspace permit",
+        })
+    }
+}
+
+/// A single field that has a different value on chain than in the config
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    pub field: String,
+    pub current: String,
+    pub expected: String,
+}
+
+impl FieldDiff {
+    pub fn new(field: &str, current: &impl Debug, expected: &impl Debug) -> Self {
+        Self {
+            field: field.to_owned(),
+            current: format!("{current:?}"),
+            expected: format!("{expected:?}"),
+        }
+    }
+}
+
+/// How a resource on chain differs from its config
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceChange {
+    /// The resource does not exist yet, and is configured with these values
+    Create(Vec<(String, String)>),
+
+    /// The resource exists, but some of its fields differ from the config
+    Update(Vec<FieldDiff>),
+
+    /// The resource exists on chain, but is not described by the config
+    Unmanaged,
+}
+
+/// The difference between a config and the chain, for one resource
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceDiff {
+    pub kind: ResourceKind,
+    pub name: String,
+    pub address: Pubkey,
+    pub change: ResourceChange,
+}
+
+/// The differences between a config and the chain, for an entire environment
+#[derive(Debug, Default, Clone)]
+pub struct EnvironmentDiff {
+    /// Every resource that differs from its config
+    pub resources: Vec<ResourceDiff>,
+
+    /// Every resource address that was compared against the config
+    checked: HashSet<Pubkey>,
+}
+
+impl EnvironmentDiff {
+    /// True if the chain state matches the config
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    /// Check if a resource is described by the config
+    pub fn is_checked(&self, address: &Pubkey) -> bool {
+        self.checked.contains(address)
+    }
+
+    /// Record the comparison of a resource against its config. Any updates without
+    /// actual differences in fields are ignored.
+    pub(crate) fn record(
+        &mut self,
+        kind: ResourceKind,
+        name: impl Into<String>,
+        address: Pubkey,
+        change: ResourceChange,
+    ) {
+        self.checked.insert(address);
+
+        if matches!(&change, ResourceChange::Update(fields) if fields.is_empty()) {
+            return;
+        }
+
+        self.resources.push(ResourceDiff {
+            kind,
+            name: name.into(),
+            address,
+            change,
+        });
+    }
+
+    /// Record a resource that exists on chain, but isn't in the config
+    fn record_unmanaged(&mut self, kind: ResourceKind, name: String, address: Pubkey) {
+        if !self.is_checked(&address) {
+            self.record(kind, name, address, ResourceChange::Unmanaged);
+        }
+    }
+}
+
+impl Display for EnvironmentDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no differences between the config and the network");
+        }
+
+        let mut resources = self.resources.iter().collect::<Vec<_>>();
+        resources.sort_by_key(|r| r.kind);
+
+        for resource in resources {
+            let prefix = match &resource.change {
+                ResourceChange::Create(_) => "+",
+                ResourceChange::Update(_) => "~",
+                ResourceChange::Unmanaged => "?",
+            };
+
+            writeln!(
+                f,
+                "{prefix} {} {} ({})",
+                resource.kind, resource.name, resource.address
+            )?;
+
+            match &resource.change {
+                ResourceChange::Create(fields) => {
+                    for (field, value) in fields {
+                        writeln!(f, "      {field}: {value}")?;
+                    }
+                }
+                ResourceChange::Update(fields) => {
+                    for diff in fields {
+                        writeln!(
+                            f,
+                            "      {}: {} -> {}",
+                            diff.field, diff.current, diff.expected
+                        )?;
+                    }
+                }
+                ResourceChange::Unmanaged => {
+                    writeln!(f, "      exists on chain, but is missing from the config")?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Compare the named fields of two values, returning a [FieldDiff] for each one that
+/// is not equal.
+macro_rules! diff_fields {
+    ($current:expr, $expected:expr, [$($field:ident),+ $(,)?]) => {{
+        let mut diffs = vec![];
+        $(
+            if $current.$field != $expected.$field {
+                diffs.push($crate::diff::FieldDiff::new(
+                    stringify!($field),
+                    &$current.$field,
+                    &$expected.$field,
+                ));
+            }
+        )+
+        diffs
+    }};
+}
+
+/// List the named fields of a value, to describe a resource that will be created
+macro_rules! list_fields {
+    ($value:expr, [$($field:ident),+ $(,)?]) => {
+        vec![$((stringify!($field).to_owned(), format!("{:?}", $value.$field))),+]
+    };
+}
+
+pub(crate) use diff_fields;
+pub(crate) use list_fields;
+
+/// Compare the network state against the environment config, including any resources
+/// that exist within the configured airspaces but are not part of the config.
+///
+/// User permits are not described by the config, so they are only reported for
+/// restricted airspaces, where each one is an explicit grant by an issuer.
+pub async fn diff_environment(
+    builder: &mut Builder,
+    config: &EnvironmentConfig,
+) -> Result<EnvironmentDiff, BuilderError> {
+    configure_environment(builder, config).await?;
+
+    let airspaces = config
+        .airspaces
+        .iter()
+        .map(|airspace| derive_airspace(&airspace.name))
+        .collect::<HashSet<_>>();
+
+    let restricted_airspaces = config
+        .airspaces
+        .iter()
+        .filter(|airspace| airspace.is_restricted)
+        .map(|airspace| derive_airspace(&airspace.name))
+        .collect::<HashSet<_>>();
+
+    let interface = builder.interface.clone();
+    let diff = &mut builder.diff;
+
+    // Pools aren't tied to an airspace directly, but their notes need a token config
+    // to be used within an airspace
+    let mut airspace_mints = HashSet::new();
+
+    for (address, airspace) in interface.find_anchor_accounts::<Airspace>().await? {
+        diff.record_unmanaged(
+            ResourceKind::Airspace,
+            format!("with authority {}", airspace.authority),
+            address,
+        );
+    }
+
+    for (address, token_config) in interface.find_anchor_accounts::<TokenConfig>().await? {
+        if airspaces.contains(&token_config.airspace) {
+            airspace_mints.insert(token_config.mint);
+            diff.record_unmanaged(
+                ResourceKind::TokenConfig,
+                format!("for mint {}", token_config.mint),
+                address,
+            );
+        }
+    }
+
+    for (address, pool) in interface.find_anchor_accounts::<MarginPool>().await? {
+        if airspace_mints.contains(&pool.deposit_note_mint)
+            || airspace_mints.contains(&pool.loan_note_mint)
+        {
+            diff.record_unmanaged(
+                ResourceKind::MarginPool,
+                format!("for mint {}", pool.token_mint),
+                address,
+            );
+        }
+    }
+
+    for (address, market) in interface.find_anchor_accounts::<Market>().await? {
+        if airspaces.contains(&market.airspace) {
+            diff.record_unmanaged(
+                ResourceKind::FixedTermMarket,
+                format!(
+                    "for mint {} with tenor {}",
+                    market.underlying_token_mint, market.borrow_tenor
+                ),
+                address,
+            );
+        }
+    }
+
+    for (address, auth) in interface
+        .find_anchor_accounts::<CrankAuthorization>()
+        .await?
+    {
+        if airspaces.contains(&auth.airspace) {
+            diff.record_unmanaged(
+                ResourceKind::CrankPermit,
+                format!("for crank {} in market {}", auth.crank, auth.market),
+                address,
+            );
+        }
+    }
+
+    for (address, permit) in interface.find_anchor_accounts::<AirspacePermit>().await? {
+        if restricted_airspaces.contains(&permit.airspace) {
+            diff.record_unmanaged(
+                ResourceKind::AirspacePermit,
+                format!("for owner {} issued by {}", permit.owner, permit.issuer),
+                address,
+            );
+        }
+    }
+
+    Ok(std::mem::take(diff))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> (EnvironmentDiff, Pubkey, Pubkey, Pubkey) {
+        let (pool, token_config, market) = (
+            Pubkey::new_from_array([1; 32]),
+            Pubkey::new_from_array([2; 32]),
+            Pubkey::new_from_array([3; 32]),
+        );
+        let mut diff = EnvironmentDiff::default();
+
+        diff.record(
+            ResourceKind::MarginPool,
+            "USDC",
+            pool,
+            ResourceChange::Update(vec![FieldDiff::new("borrow_rate_0", &10u16, &20u16)]),
+        );
+        diff.record(
+            ResourceKind::TokenConfig,
+            "SOL",
+            token_config,
+            ResourceChange::Update(vec![]),
+        );
+        diff.record(
+            ResourceKind::FixedTermMarket,
+            "USDC_86400",
+            market,
+            ResourceChange::Create(vec![("borrow_tenor".to_owned(), "86400".to_owned())]),
+        );
+
+        (diff, pool, token_config, market)
+    }
+
+    #[test]
+    fn updates_without_changes_are_checked_but_not_reported() {
+        let (diff, pool, token_config, market) = fixture();
+
+        assert_eq!(2, diff.resources.len());
+        assert!(diff.is_checked(&pool));
+        assert!(diff.is_checked(&token_config));
+        assert!(diff.is_checked(&market));
+        assert!(!diff
+            .resources
+            .iter()
+            .any(|resource| resource.address == token_config));
+    }
+
+    #[test]
+    fn unmanaged_resources_exclude_checked_addresses() {
+        let (mut diff, pool, _, _) = fixture();
+        let permit = Pubkey::new_from_array([4; 32]);
+
+        diff.record_unmanaged(ResourceKind::MarginPool, "for mint".to_owned(), pool);
+        diff.record_unmanaged(ResourceKind::AirspacePermit, "for owner".to_owned(), permit);
+
+        assert_eq!(3, diff.resources.len());
+        assert_eq!(
+            vec![ResourceChange::Unmanaged],
+            diff.resources
+                .iter()
+                .filter(|resource| resource.address == permit)
+                .map(|resource| resource.change.clone())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn report_is_grouped_by_resource_kind() {
+        let (mut diff, _, _, _) = fixture();
+        let permit = Pubkey::new_from_array([4; 32]);
+        diff.record_unmanaged(ResourceKind::AirspacePermit, "for owner".to_owned(), permit);
+
+        let expected = format!(
+            "~ margin pool USDC ({})\n      borrow_rate_0: 10 -> 20\n\
+             + fixed term market USDC_86400 ({})\n      borrow_tenor: 86400\n\
+             ? airspace permit for owner ({})\n      exists on chain, but is missing from the config\n",
+            Pubkey::new_from_array([1; 32]),
+            Pubkey::new_from_array([3; 32]),
+            permit,
+        );
+
+        assert_eq!(expected, diff.to_string());
+    }
+
+    #[test]
+    fn empty_diff_report() {
+        assert_eq!(
+            "no differences between the config and the network\n",
+            EnvironmentDiff::default().to_string()
+        );
+    }
+
+    #[test]
+    fn diff_fields_lists_only_changed_fields() {
+        struct Config {
+            a: u64,
+            b: bool,
+        }
+
+        let current = Config { a: 1, b: true };
+        let expected = Config { a: 2, b: true };
+
+        assert_eq!(
+            vec![FieldDiff::new("a", &1u64, &2u64)],
+            diff_fields!(current, expected, [a, b])
+        );
+        assert_eq!(
+            vec![
+                ("a".to_owned(), "2".to_owned()),
+                ("b".to_owned(), "true".to_owned())
+            ],
+            list_fields!(expected, [a, b])
+        );
+    }
+}
@@ -1,4 +1,5 @@
 pub mod config;
+pub mod diff;
 
 pub mod builder;
 pub mod client_config;
@@ -16,9 +16,16 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use anchor_lang::{InstructionData, ToAccountMetas};
-use solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_program};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
 
-use jet_airspace::seeds::{AIRSPACE, AIRSPACE_PERMIT, AIRSPACE_PERMIT_ISSUER, GOVERNOR_ID};
+use jet_airspace::seeds::{
+    AIRSPACE, AIRSPACE_PERMIT, AIRSPACE_PERMIT_ISSUER, GOVERNOR_ID, TIMELOCK, TIMELOCK_TRANSACTION,
+};
+use jet_airspace::state::PermitTier;
 
 pub use jet_airspace::ID as AIRSPACE_PROGRAM;
 
@@ -204,6 +211,22 @@ impl AirspaceIxBuilder {
     ///
     /// `user` - The address authorized to use the airspace
     pub fn permit_create(&self, user: Pubkey) -> Instruction {
+        self.permit_create_limited(user, 0, PermitTier::default())
+    }
+
+    /// Issue a permit for an address, which expires and limits what it may do in the airspace
+    ///
+    /// # Params
+    ///
+    /// `user` - The address authorized to use the airspace
+    /// `expires_at` - The unix timestamp when the permit expires, or zero to never expire
+    /// `tier` - The limits on what the user may do within the airspace
+    pub fn permit_create_limited(
+        &self,
+        user: Pubkey,
+        expires_at: i64,
+        tier: PermitTier,
+    ) -> Instruction {
         let accounts = jet_airspace::accounts::AirspacePermitCreate {
             airspace: self.address,
             authority: self.authority,
@@ -217,7 +240,35 @@ impl AirspaceIxBuilder {
         Instruction {
             accounts,
             program_id: jet_airspace::ID,
-            data: jet_airspace::instruction::AirspacePermitCreate { owner: user }.data(),
+            data: jet_airspace::instruction::AirspacePermitCreate {
+                owner: user,
+                expires_at,
+                tier,
+            }
+            .data(),
+        }
+    }
+
+    /// Extend the expiry of a previously issued permit
+    ///
+    /// # Params
+    ///
+    /// `user` - The address authorized to use the airspace
+    /// `issuer` - The address that originally issued the permit
+    /// `expires_at` - The new unix timestamp when the permit expires, or zero to never expire
+    pub fn permit_renew(&self, user: Pubkey, issuer: Pubkey, expires_at: i64) -> Instruction {
+        let accounts = jet_airspace::accounts::AirspacePermitRenew {
+            airspace: self.address,
+            authority: self.authority,
+            permit: self.derive_permit(&user),
+            issuer_id: self.derive_issuer_id(&issuer),
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            accounts,
+            program_id: jet_airspace::ID,
+            data: jet_airspace::instruction::AirspacePermitRenew { expires_at }.data(),
         }
     }
 
@@ -240,10 +291,190 @@ impl AirspaceIxBuilder {
         Instruction {
             accounts,
             program_id: jet_airspace::ID,
-            data: jet_airspace::instruction::AirspacePermitCreate { owner: user }.data(),
+            data: jet_airspace::instruction::AirspacePermitRevoke {}.data(),
+        }
+    }
+
+    /// Create a timelock for the airspace, which can then be set as the airspace authority
+    ///
+    /// # Params
+    ///
+    /// `signers` - The addresses allowed to queue, approve and cancel transactions
+    /// `threshold` - The number of signers that must approve a transaction
+    /// `delay` - The number of seconds between queueing and executing a transaction
+    pub fn timelock_create(&self, signers: Vec<Pubkey>, threshold: u8, delay: i64) -> Instruction {
+        let accounts = jet_airspace::accounts::TimelockCreate {
+            payer: self.payer,
+            authority: self.authority,
+            airspace: self.address,
+            timelock: self.derive_timelock(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            accounts,
+            program_id: jet_airspace::ID,
+            data: jet_airspace::instruction::TimelockCreate {
+                signers,
+                threshold,
+                delay,
+            }
+            .data(),
+        }
+    }
+
+    /// Change the configuration of the timelock. The instruction must be signed by the
+    /// timelock, so it needs to be queued with [Self::timelock_queue].
+    ///
+    /// # Params
+    ///
+    /// `signers` - The addresses allowed to queue, approve and cancel transactions
+    /// `threshold` - The number of signers that must approve a transaction
+    /// `delay` - The number of seconds between queueing and executing a transaction
+    pub fn timelock_configure(
+        &self,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+        delay: i64,
+    ) -> Instruction {
+        let accounts = jet_airspace::accounts::TimelockConfigure {
+            timelock: self.derive_timelock(),
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            accounts,
+            program_id: jet_airspace::ID,
+            data: jet_airspace::instruction::TimelockConfigure {
+                signers,
+                threshold,
+                delay,
+            }
+            .data(),
+        }
+    }
+
+    /// Queue an instruction to be signed by the timelock
+    ///
+    /// # Params
+    ///
+    /// `proposer` - The timelock signer queueing the transaction
+    /// `id` - The id for the new transaction, which is the current transaction count
+    ///        of the timelock
+    /// `instruction` - The instruction to be executed by the timelock
+    pub fn timelock_queue(
+        &self,
+        proposer: Pubkey,
+        id: u64,
+        instruction: Instruction,
+    ) -> Instruction {
+        let timelock = self.derive_timelock();
+        let accounts = jet_airspace::accounts::TimelockQueue {
+            payer: self.payer,
+            proposer,
+            timelock,
+            transaction: derive_timelock_transaction(&timelock, id),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            accounts,
+            program_id: jet_airspace::ID,
+            data: jet_airspace::instruction::TimelockQueue {
+                instruction: instruction.into(),
+            }
+            .data(),
         }
     }
 
+    /// Approve a transaction queued in the timelock
+    ///
+    /// # Params
+    ///
+    /// `approver` - The timelock signer approving the transaction
+    /// `id` - The id of the queued transaction
+    pub fn timelock_approve(&self, approver: Pubkey, id: u64) -> Instruction {
+        let timelock = self.derive_timelock();
+        let accounts = jet_airspace::accounts::TimelockApprove {
+            approver,
+            timelock,
+            transaction: derive_timelock_transaction(&timelock, id),
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            accounts,
+            program_id: jet_airspace::ID,
+            data: jet_airspace::instruction::TimelockApprove {}.data(),
+        }
+    }
+
+    /// Cancel a transaction queued in the timelock
+    ///
+    /// # Params
+    ///
+    /// `authority` - The timelock signer cancelling the transaction
+    /// `proposer` - The signer that queued the transaction
+    /// `id` - The id of the queued transaction
+    pub fn timelock_cancel(&self, authority: Pubkey, proposer: Pubkey, id: u64) -> Instruction {
+        let timelock = self.derive_timelock();
+        let accounts = jet_airspace::accounts::TimelockCancel {
+            authority,
+            proposer,
+            timelock,
+            transaction: derive_timelock_transaction(&timelock, id),
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            accounts,
+            program_id: jet_airspace::ID,
+            data: jet_airspace::instruction::TimelockCancel {}.data(),
+        }
+    }
+
+    /// Execute a transaction queued in the timelock
+    ///
+    /// # Params
+    ///
+    /// `proposer` - The signer that queued the transaction
+    /// `id` - The id of the queued transaction
+    /// `instruction` - The instruction that was queued in the transaction
+    pub fn timelock_execute(
+        &self,
+        proposer: Pubkey,
+        id: u64,
+        instruction: &Instruction,
+    ) -> Instruction {
+        let timelock = self.derive_timelock();
+        let mut accounts = jet_airspace::accounts::TimelockExecute {
+            proposer,
+            timelock,
+            transaction: derive_timelock_transaction(&timelock, id),
+        }
+        .to_account_metas(None);
+
+        // the timelock signs within the program, so it isn't a signer of the transaction
+        accounts.extend(instruction.accounts.iter().map(|meta| AccountMeta {
+            is_signer: meta.is_signer && meta.pubkey != timelock,
+            ..meta.clone()
+        }));
+        accounts.push(AccountMeta::new_readonly(instruction.program_id, false));
+
+        Instruction {
+            accounts,
+            program_id: jet_airspace::ID,
+            data: jet_airspace::instruction::TimelockExecute {}.data(),
+        }
+    }
+
+    /// Derive the address of the timelock for the airspace
+    pub fn derive_timelock(&self) -> Pubkey {
+        derive_timelock(&self.address)
+    }
+
     /// Derive the address for the account identifying permit issuers
     pub fn derive_issuer_id(&self, issuer: &Pubkey) -> Pubkey {
         derive_issuer_id(&self.address, issuer)
@@ -282,3 +513,17 @@ pub fn derive_permit(airspace: &Pubkey, user: &Pubkey) -> Pubkey {
     )
     .0
 }
+
+/// Derive the address of the timelock for an airspace
+pub fn derive_timelock(airspace: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[TIMELOCK, airspace.as_ref()], &jet_airspace::ID).0
+}
+
+/// Derive the address of a transaction queued in a timelock
+pub fn derive_timelock_transaction(timelock: &Pubkey, id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[TIMELOCK_TRANSACTION, timelock.as_ref(), &id.to_le_bytes()],
+        &jet_airspace::ID,
+    )
+    .0
+}
@@ -8,6 +8,8 @@ pub mod margin_pool;
 pub mod margin_swap;
 pub mod openbook;
 pub mod orca;
+pub mod phoenix;
+pub mod staking;
 
 /// Instruction builder for the protocol test service
 pub mod test_service;
@@ -28,12 +28,12 @@ use anchor_lang::{system_program, InstructionData};
 
 use jet_margin::instruction as ix_data;
 use jet_margin::program::JetMargin;
-use jet_margin::seeds::{ADAPTER_CONFIG_SEED, PERMIT_SEED, TOKEN_CONFIG_SEED};
+use jet_margin::seeds::{ADAPTER_CONFIG_SEED, PERMIT_SEED, RISK_CONFIG_SEED, TOKEN_CONFIG_SEED};
 use jet_margin::{accounts as ix_account, MarginAccount};
 use jet_program_common::ADDRESS_LOOKUP_REGISTRY_ID;
 
 pub use jet_margin::ID as MARGIN_PROGRAM;
-pub use jet_margin::{TokenAdmin, TokenConfigUpdate, TokenKind, TokenOracle};
+pub use jet_margin::{RiskParameters, TokenAdmin, TokenConfigUpdate, TokenKind, TokenOracle};
 use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
 
 use crate::airspace::derive_permit;
@@ -61,6 +61,10 @@ pub struct MarginIxBuilder {
     /// Key that will sign to authorize changes to the margin account.
     /// - Defaults to owner.
     authority: Option<Pubkey>,
+
+    /// The account was created under a permit with an expiry or tier, so the
+    /// permit is included wherever the margin program checks it.
+    permit_limited: bool,
 }
 
 impl MarginIxBuilder {
@@ -78,6 +82,7 @@ impl MarginIxBuilder {
             address,
             airspace,
             authority: None,
+            permit_limited: false,
         }
     }
 
@@ -89,6 +94,7 @@ impl MarginIxBuilder {
             address,
             airspace,
             authority: None,
+            permit_limited: false,
         }
     }
 
@@ -98,6 +104,12 @@ impl MarginIxBuilder {
         self
     }
 
+    /// Use if the account was created under a permit with an expiry or tier.
+    pub fn with_permit(mut self) -> Self {
+        self.permit_limited = true;
+        self
+    }
+
     /// Use if a different wallet should pay or receive rent instead of the authority.
     pub fn with_payer(mut self, payer: Pubkey) -> Self {
         self.payer = Some(payer);
@@ -136,6 +148,32 @@ impl MarginIxBuilder {
         }
     }
 
+    /// Add the owner's airspace permit to the accounts of an instruction.
+    ///
+    /// Accounts created under a permit with an expiry or tier need to include the permit
+    /// in any instruction that registers a position or borrows.
+    pub fn include_permit(&self, mut ix: Instruction) -> Instruction {
+        let permit = AccountMeta::new_readonly(derive_permit(&self.airspace, &self.owner), false);
+
+        // the risk config has to remain the last account
+        let index = match ix.accounts.last() {
+            Some(last) if last.pubkey == derive_risk_config(&self.airspace) => {
+                ix.accounts.len() - 1
+            }
+            _ => ix.accounts.len(),
+        };
+        ix.accounts.insert(index, permit);
+
+        ix
+    }
+
+    fn include_permit_if_limited(&self, ix: Instruction) -> Instruction {
+        match self.permit_limited {
+            true => self.include_permit(ix),
+            false => ix,
+        }
+    }
+
     /// Get instruction to close account
     pub fn close_account(&self) -> Instruction {
         let accounts = ix_account::CloseAccount {
@@ -277,11 +315,14 @@ impl MarginIxBuilder {
             rent: Rent::id(),
         };
 
-        Instruction {
-            program_id: JetMargin::id(),
-            data: ix_data::RegisterPosition {}.data(),
-            accounts: accounts.to_account_metas(None),
-        }
+        self.include_permit_if_limited(with_risk_config(
+            &self.airspace,
+            Instruction {
+                program_id: JetMargin::id(),
+                data: ix_data::RegisterPosition {}.data(),
+                accounts: accounts.to_account_metas(None),
+            },
+        ))
     }
 
     /// Get instruction to close a position
@@ -362,12 +403,12 @@ impl MarginIxBuilder {
     ///
     /// `adapter_ix` - The instruction to be invoked
     pub fn adapter_invoke(&self, adapter_ix: Instruction) -> Instruction {
-        invoke!(
+        self.include_permit_if_limited(invoke!(
             self.airspace,
             self.address,
             adapter_ix,
             AdapterInvoke { owner: self.owner }
-        )
+        ))
     }
 
     /// Get instruction to invoke through an adapter for permissionless accounting instructions
@@ -402,6 +443,7 @@ impl MarginIxBuilder {
     pub fn liquidate_end(&self, original_liquidator: Option<Pubkey>) -> Instruction {
         let authority = self.authority();
         liquidate_end(
+            self.airspace,
             self.address,
             original_liquidator.unwrap_or(authority),
             authority,
@@ -417,13 +459,13 @@ impl MarginIxBuilder {
     ///
     /// `token_mint` - The mint for the token to be deposited
     pub fn create_deposit_position(&self, token_mint: Pubkey) -> Instruction {
-        create_deposit_position(
+        self.include_permit_if_limited(create_deposit_position(
             self.address,
             self.airspace,
             self.authority(),
             self.payer(),
             token_mint,
-        )
+        ))
     }
 
     /// Transfer tokens into or out of a deposit account associated with the margin account
@@ -457,11 +499,14 @@ impl MarginIxBuilder {
             margin_account: self.address,
         };
 
-        Instruction {
-            program_id: JetMargin::id(),
-            accounts: accounts.to_account_metas(None),
-            data: ix_data::VerifyHealthy.data(),
-        }
+        with_risk_config(
+            &self.airspace,
+            Instruction {
+                program_id: JetMargin::id(),
+                accounts: accounts.to_account_metas(None),
+                data: ix_data::VerifyHealthy.data(),
+            },
+        )
     }
 
     /// Verify that an account is unhealthy
@@ -471,11 +516,14 @@ impl MarginIxBuilder {
             margin_account: self.address,
         };
 
-        Instruction {
-            program_id: JetMargin::id(),
-            accounts: accounts.to_account_metas(None),
-            data: ix_data::VerifyUnhealthy.data(),
-        }
+        with_risk_config(
+            &self.airspace,
+            Instruction {
+                program_id: JetMargin::id(),
+                accounts: accounts.to_account_metas(None),
+                data: ix_data::VerifyUnhealthy.data(),
+            },
+        )
     }
 
     /// Peform an administrative transfer for a position
@@ -581,11 +629,14 @@ pub fn create_deposit_position(
         system_program: system_program::ID,
         rent: Rent::id(),
     };
-    Instruction {
-        program_id: jet_margin::ID,
-        accounts: accounts.to_account_metas(None),
-        data: ix_data::CreateDepositPosition.data(),
-    }
+    with_risk_config(
+        &airspace,
+        Instruction {
+            program_id: jet_margin::ID,
+            accounts: accounts.to_account_metas(None),
+            data: ix_data::CreateDepositPosition.data(),
+        },
+    )
 }
 
 pub fn liquidate_begin(
@@ -604,14 +655,18 @@ pub fn liquidate_begin(
         liquidation,
         system_program: system_program::ID,
     };
-    Instruction {
-        program_id: JetMargin::id(),
-        accounts: accounts.to_account_metas(None),
-        data: jet_margin::instruction::LiquidateBegin {}.data(),
-    }
+    with_risk_config(
+        &airspace,
+        Instruction {
+            program_id: JetMargin::id(),
+            accounts: accounts.to_account_metas(None),
+            data: jet_margin::instruction::LiquidateBegin {}.data(),
+        },
+    )
 }
 
 pub fn liquidate_end(
+    airspace: Pubkey,
     margin_account: Pubkey,
     original_liquidator: Pubkey,
     authority: Pubkey,
@@ -622,11 +677,14 @@ pub fn liquidate_end(
         authority,
         liquidation,
     };
-    Instruction {
-        program_id: JetMargin::id(),
-        accounts: accounts.to_account_metas(None),
-        data: ix_data::LiquidateEnd.data(),
-    }
+    with_risk_config(
+        &airspace,
+        Instruction {
+            program_id: JetMargin::id(),
+            accounts: accounts.to_account_metas(None),
+            data: ix_data::LiquidateEnd.data(),
+        },
+    )
 }
 
 /// Get instruction to refresh the price and balance value for a deposit account
@@ -656,11 +714,14 @@ pub fn refresh_deposit_position(
         });
     }
 
-    Instruction {
-        program_id: JetMargin::id(),
-        data: ix_data::RefreshDepositPosition.data(),
-        accounts,
-    }
+    with_risk_config(
+        airspace,
+        Instruction {
+            program_id: JetMargin::id(),
+            data: ix_data::RefreshDepositPosition.data(),
+            accounts,
+        },
+    )
 }
 
 /// Get instruction to invoke through an adapter
@@ -705,6 +766,21 @@ pub fn liquidator_invoke(
     )
 }
 
+/// Invoke the `keeper_execute` instruction of an adapter on behalf of a margin account
+pub fn keeper_invoke(
+    airspace: Pubkey,
+    keeper: Pubkey,
+    margin_account: Pubkey,
+    adapter_ix: Instruction,
+) -> Instruction {
+    invoke!(
+        airspace,
+        margin_account,
+        adapter_ix,
+        KeeperInvoke { keeper }
+    )
+}
+
 /// Get instruction to invoke through an adapter for permissionless accounting instructions
 ///
 /// # Params
@@ -805,6 +881,24 @@ impl MarginConfigIxBuilder {
         }
     }
 
+    /// Set the risk parameters used for margin accounts in the airspace, or
+    /// revert to the defaults if `None`
+    pub fn configure_risk(&self, params: Option<RiskParameters>) -> Instruction {
+        let accounts = ix_account::ConfigureRisk {
+            authority: self.authority,
+            airspace: self.airspace,
+            payer: self.payer,
+            risk_config: self.derive_risk_config(),
+            system_program: system_program::ID,
+        };
+
+        Instruction {
+            program_id: jet_margin::ID,
+            data: ix_data::ConfigureRisk { params }.data(),
+            accounts: accounts.to_account_metas(None),
+        }
+    }
+
     /// get the accounts to configure a permit
     fn configure_permit(&self, owner: Pubkey) -> ix_account::ConfigurePermit {
         ix_account::ConfigurePermit {
@@ -822,6 +916,11 @@ impl MarginConfigIxBuilder {
         derive_token_config(&self.airspace, token_mint)
     }
 
+    /// Derive address for the risk config account of the airspace
+    pub fn derive_risk_config(&self) -> Pubkey {
+        derive_risk_config(&self.airspace)
+    }
+
     /// Derive address for the config account for a given adapter
     pub fn derive_adapter_config(&self, adapter_program_id: &Pubkey) -> Pubkey {
         derive_adapter_config(&self.airspace, adapter_program_id)
@@ -892,6 +991,23 @@ pub fn derive_adapter_config(airspace: &Pubkey, adapter_program_id: &Pubkey) ->
     .0
 }
 
+/// Derive address for the risk config account of an airspace
+pub fn derive_risk_config(airspace: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[RISK_CONFIG_SEED, airspace.as_ref()], &jet_margin::ID).0
+}
+
+/// Add the risk config of the airspace as the last account of a margin instruction.
+///
+/// The margin program uses the default risk parameters when the config is left out,
+/// or when the airspace hasn't been configured.
+pub fn with_risk_config(airspace: &Pubkey, mut ix: Instruction) -> Instruction {
+    ix.accounts.push(AccountMeta::new_readonly(
+        derive_risk_config(airspace),
+        false,
+    ));
+    ix
+}
+
 /// Derive address for the config account for a given liquidator
 #[deprecated(note = "use derive_margin_permit")]
 pub fn derive_liquidator_config(airspace: &Pubkey, liquidator: &Pubkey) -> Pubkey {
@@ -947,14 +1063,17 @@ macro_rules! invoke {
             }
         }
 
-        Instruction {
-            program_id: JetMargin::id(),
-            data: ix_data::$Instruction {
-                data: $adapter_ix.data,
-            }
-            .data(),
-            accounts,
-        }
+        with_risk_config(
+            &$airspace,
+            Instruction {
+                program_id: JetMargin::id(),
+                data: ix_data::$Instruction {
+                    data: $adapter_ix.data,
+                }
+                .data(),
+                accounts,
+            },
+        )
     }};
 }
 use invoke;
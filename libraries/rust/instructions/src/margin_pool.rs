@@ -17,13 +17,13 @@
 
 use anchor_lang::prelude::{Id, System, ToAccountMetas};
 use anchor_lang::InstructionData;
-use solana_sdk::instruction::Instruction;
+use solana_sdk::instruction::{AccountMeta, Instruction};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::sysvar::{rent::Rent, SysvarId};
 
 use jet_margin_pool::instruction as ix_data;
 use jet_margin_pool::program::JetMarginPool;
-use jet_margin_pool::{accounts as ix_accounts, TokenChange};
+use jet_margin_pool::{accounts as ix_accounts, RewardsConfig, TokenChange};
 
 pub use jet_margin_pool::ID as MARGIN_POOL_PROGRAM;
 
@@ -404,9 +404,10 @@ impl MarginPoolIxBuilder {
         }
     }
 
-    /// Instruction to collect interest and fees
+    /// Instruction to collect interest and fees, which also accrues the rewards
+    /// emitted by the pool if it has any
     pub fn collect(&self, fee_destination: Pubkey) -> Instruction {
-        let accounts = ix_accounts::Collect {
+        let mut accounts = ix_accounts::Collect {
             margin_pool: self.address,
             vault: self.vault,
             fee_destination,
@@ -415,6 +416,8 @@ impl MarginPoolIxBuilder {
         }
         .to_account_metas(None);
 
+        accounts.push(AccountMeta::new(derive_pool_rewards(&self.address), false));
+
         Instruction {
             program_id: jet_margin_pool::ID,
             data: ix_data::Collect.data(),
@@ -422,7 +425,82 @@ impl MarginPoolIxBuilder {
         }
     }
 
-    /// Instruction to transfer a loan between margin accounts
+    /// Instruction to write off the loan of an insolvent margin account
+    pub fn write_off_bad_debt(&self, margin_account: Pubkey) -> Instruction {
+        let accounts = ix_accounts::WriteOffBadDebt {
+            margin_account,
+            margin_pool: self.address,
+            loan_note_mint: self.loan_note_mint,
+            loan_account: derive_loan_account(&margin_account, &self.loan_note_mint),
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+
+        let ix = Instruction {
+            program_id: jet_margin_pool::ID,
+            data: ix_data::WriteOffBadDebt.data(),
+            accounts,
+        };
+
+        self.with_rewards(ix, &margin_account)
+    }
+
+    /// Instruction to borrow tokens with a flash loan, which must be followed by
+    /// a `flash_loan_end` instruction for the same amount in the same transaction
+    ///
+    /// # Params
+    ///
+    /// `destination` - The token account to receive the loan
+    /// `amount` - The amount of tokens to borrow
+    pub fn flash_loan_begin(&self, destination: Pubkey, amount: u64) -> Instruction {
+        let accounts = ix_accounts::FlashLoanBegin {
+            margin_pool: self.address,
+            vault: self.vault,
+            destination,
+            instructions: solana_sdk::sysvar::instructions::ID,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_margin_pool::ID,
+            data: ix_data::FlashLoanBegin { amount }.data(),
+            accounts,
+        }
+    }
+
+    /// Instruction to repay a flash loan along with its fee
+    ///
+    /// # Params
+    ///
+    /// `repayment_source_authority` - The authority for the repayment source tokens
+    /// `repayment_source_account` - The token account to use for repayment
+    /// `amount` - The amount of tokens that were borrowed, excluding the fee
+    pub fn flash_loan_end(
+        &self,
+        repayment_source_authority: Pubkey,
+        repayment_source_account: Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        let accounts = ix_accounts::FlashLoanEnd {
+            repayment_account_authority: repayment_source_authority,
+            margin_pool: self.address,
+            vault: self.vault,
+            repayment_token_account: repayment_source_account,
+            instructions: solana_sdk::sysvar::instructions::ID,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_margin_pool::ID,
+            data: ix_data::FlashLoanEnd { amount }.data(),
+            accounts,
+        }
+    }
+
+    /// Instruction to transfer a loan between margin accounts, including the
+    /// accounts to checkpoint the rewards of both
     pub fn admin_transfer_loan(
         &self,
         source_margin_account: &Pubkey,
@@ -438,12 +516,143 @@ impl MarginPoolIxBuilder {
         }
         .to_account_metas(None);
 
-        Instruction {
+        let ix = Instruction {
             program_id: jet_margin_pool::ID,
             data: ix_data::AdminTransferLoan { amount }.data(),
             accounts,
+        };
+
+        self.with_loan_rewards(ix, &[*source_margin_account, *target_margin_account])
+    }
+
+    /// Instruction to configure the liquidity mining rewards emitted by the pool
+    ///
+    /// # Params
+    ///
+    /// `payer` - The address paying for the rent
+    /// `reward_mint` - The mint for the reward token
+    /// `config` - The rates and end time for the emission of rewards
+    pub fn configure_rewards(
+        &self,
+        payer: Pubkey,
+        reward_mint: Pubkey,
+        config: RewardsConfig,
+    ) -> Instruction {
+        let pool_rewards = derive_pool_rewards(&self.address);
+        let accounts = ix_accounts::ConfigureRewards {
+            authority: jet_program_common::GOVERNOR_ID,
+            margin_pool: self.address,
+            reward_mint,
+            pool_rewards,
+            reward_vault: derive_reward_vault(&pool_rewards),
+            payer,
+            token_program: spl_token::ID,
+            system_program: System::id(),
+            rent: Rent::id(),
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_margin_pool::ID,
+            data: ix_data::ConfigureRewards { config }.data(),
+            accounts,
+        }
+    }
+
+    /// Instruction to start tracking the rewards earned by a margin account
+    ///
+    /// # Params
+    ///
+    /// `margin_account` - The margin account that will earn rewards
+    /// `payer` - The address paying for the rent
+    pub fn register_rewards_position(&self, margin_account: Pubkey, payer: Pubkey) -> Instruction {
+        let pool_rewards = derive_pool_rewards(&self.address);
+        let accounts = ix_accounts::RegisterRewardsPosition {
+            margin_account,
+            margin_pool: self.address,
+            pool_rewards,
+            rewards_position: derive_rewards_position(&pool_rewards, &margin_account),
+            payer,
+            system_program: System::id(),
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_margin_pool::ID,
+            data: ix_data::RegisterRewardsPosition {}.data(),
+            accounts,
+        }
+    }
+
+    /// Instruction to claim the rewards earned by a margin account
+    ///
+    /// # Params
+    ///
+    /// `margin_account` - The margin account that earned the rewards
+    /// `destination` - The token account to receive the rewards
+    pub fn claim_rewards(&self, margin_account: Pubkey, destination: Pubkey) -> Instruction {
+        let pool_rewards = derive_pool_rewards(&self.address);
+        let accounts = ix_accounts::ClaimRewards {
+            margin_account,
+            margin_pool: self.address,
+            pool_rewards,
+            rewards_position: derive_rewards_position(&pool_rewards, &margin_account),
+            reward_vault: derive_reward_vault(&pool_rewards),
+            destination,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_margin_pool::ID,
+            data: ix_data::ClaimRewards {}.data(),
+            accounts,
         }
     }
+
+    /// Include the rewards accounts in an instruction for this pool, so the
+    /// rewards earned by the margin account are checkpointed.
+    ///
+    /// These are required while the pool has rewards configured, and ignored
+    /// otherwise. Applies to `deposit`, `withdraw`, `margin_borrow`,
+    /// `margin_borrow_v2`, `margin_repay` and `margin_refresh_position`.
+    pub fn with_rewards(&self, mut ix: Instruction, margin_account: &Pubkey) -> Instruction {
+        let pool_rewards = derive_pool_rewards(&self.address);
+
+        ix.accounts.push(AccountMeta::new(pool_rewards, false));
+        ix.accounts.push(AccountMeta::new(
+            derive_rewards_position(&pool_rewards, margin_account),
+            false,
+        ));
+
+        ix
+    }
+
+    /// Include the rewards accounts in an instruction for this pool that changes
+    /// loans, along with the margin accounts owning the loans.
+    ///
+    /// Applies to `repay` and `admin_transfer_loan`, with the margin accounts
+    /// in the same order as the loan accounts in the instruction.
+    pub fn with_loan_rewards(
+        &self,
+        mut ix: Instruction,
+        margin_accounts: &[Pubkey],
+    ) -> Instruction {
+        let pool_rewards = derive_pool_rewards(&self.address);
+
+        ix.accounts.push(AccountMeta::new(pool_rewards, false));
+
+        for margin_account in margin_accounts {
+            let position = derive_rewards_position(&pool_rewards, margin_account);
+            ix.accounts.push(AccountMeta::new(position, false));
+        }
+        for margin_account in margin_accounts {
+            ix.accounts
+                .push(AccountMeta::new_readonly(*margin_account, false));
+        }
+
+        ix
+    }
 }
 
 /// Find a loan token account for a margin account and margin pool's loan note mint
@@ -459,3 +668,30 @@ pub fn derive_loan_account(margin_account: &Pubkey, loan_note_mint: &Pubkey) ->
 pub fn derive_margin_pool(_airspace: &Pubkey, token_mint: &Pubkey) -> Pubkey {
     Pubkey::find_program_address(&[token_mint.as_ref()], &jet_margin_pool::ID).0
 }
+
+/// Derive the address of the account tracking the rewards emitted by a pool
+pub fn derive_pool_rewards(margin_pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[margin_pool.as_ref(), b"rewards".as_ref()],
+        &jet_margin_pool::ID,
+    )
+    .0
+}
+
+/// Derive the address of the token account holding a pool's rewards
+pub fn derive_reward_vault(pool_rewards: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[pool_rewards.as_ref(), b"vault".as_ref()],
+        &jet_margin_pool::ID,
+    )
+    .0
+}
+
+/// Derive the address of the account tracking the rewards earned by a margin account
+pub fn derive_rewards_position(pool_rewards: &Pubkey, margin_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[pool_rewards.as_ref(), margin_account.as_ref()],
+        &jet_margin_pool::ID,
+    )
+    .0
+}
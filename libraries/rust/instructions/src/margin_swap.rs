@@ -10,8 +10,10 @@ use spl_associated_token_account::get_associated_token_address;
 use jet_margin_pool::{ChangeKind, TokenChange};
 use jet_margin_swap::{accounts as ix_accounts, SwapRouteDetail, SwapRouteIdentifier};
 use jet_margin_swap::{instruction as ix_data, ROUTE_SWAP_MAX_SPLIT, ROUTE_SWAP_MIN_SPLIT};
+use jet_margin_swap::{seeds::CONDITIONAL_ORDER, OrderAction, OrderTrigger};
 
-use crate::margin::derive_position_token_account;
+use crate::margin::{derive_position_token_account, derive_risk_config};
+use crate::margin_pool::{derive_loan_account, derive_pool_rewards, derive_rewards_position};
 use crate::IxResult;
 use crate::JetIxError;
 use crate::{control::get_control_authority_address, margin_pool::MarginPoolIxBuilder};
@@ -83,7 +85,7 @@ pub fn pool_spl_swap(
     }
     .to_account_metas(None);
 
-    Instruction {
+    let ix = Instruction {
         program_id: jet_margin_swap::ID,
         data: jet_margin_swap::instruction::MarginSwap {
             withdrawal_change_kind,
@@ -92,7 +94,10 @@ pub fn pool_spl_swap(
         }
         .data(),
         accounts,
-    }
+    };
+
+    // passed through to the margin pools for the withdrawal and deposit
+    pool_target.with_rewards(pool_source.with_rewards(ix, margin_account), margin_account)
 }
 
 pub struct SplSwap {
@@ -110,6 +115,131 @@ pub fn derive_spl_swap_authority(program: &Pubkey, pool: &Pubkey) -> Pubkey {
     Pubkey::find_program_address(&[pool.as_ref()], program).0
 }
 
+/// Derive the address of a conditional order of a margin account
+pub fn derive_conditional_order(margin_account: &Pubkey, seed: u16) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            CONDITIONAL_ORDER,
+            margin_account.as_ref(),
+            seed.to_le_bytes().as_ref(),
+        ],
+        &jet_margin_swap::id(),
+    )
+    .0
+}
+
+/// Instruction to create a conditional order, which the caller should wrap with an adapter invoke
+///
+/// # Params
+///
+/// `seed` - A number to distinguish between the orders of the margin account
+/// `price_oracle` - The pyth price account that triggers the order
+/// `keeper_fee` - The lamports paid by the `payer` to the keeper that executes the order
+pub fn create_conditional_order(
+    margin_account: Pubkey,
+    payer: Pubkey,
+    seed: u16,
+    price_oracle: Pubkey,
+    trigger: OrderTrigger,
+    action: OrderAction,
+    keeper_fee: u64,
+) -> Instruction {
+    let accounts = ix_accounts::CreateConditionalOrder {
+        margin_account,
+        payer,
+        order: derive_conditional_order(&margin_account, seed),
+        price_oracle,
+        system_program: solana_sdk::system_program::ID,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: jet_margin_swap::id(),
+        data: ix_data::CreateConditionalOrder {
+            seed,
+            trigger,
+            action,
+            keeper_fee,
+        }
+        .data(),
+        accounts,
+    }
+}
+
+/// Instruction to cancel a conditional order, which the caller should wrap with an adapter invoke
+pub fn cancel_conditional_order(
+    margin_account: Pubkey,
+    order: Pubkey,
+    destination: Pubkey,
+) -> Instruction {
+    let accounts = ix_accounts::CancelConditionalOrder {
+        margin_account,
+        order,
+        destination,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: jet_margin_swap::id(),
+        data: ix_data::CancelConditionalOrder {}.data(),
+        accounts,
+    }
+}
+
+/// Instruction for a keeper to execute a conditional order that repays a pool loan,
+/// which the caller should wrap with a keeper invoke
+///
+/// # Params
+///
+/// `token_mint` - The token of the pool that the order repays
+pub fn keeper_execute_repay(
+    airspace: Pubkey,
+    keeper: Pubkey,
+    margin_account: Pubkey,
+    order: Pubkey,
+    price_oracle: Pubkey,
+    token_mint: Pubkey,
+) -> Instruction {
+    let pool = MarginPoolIxBuilder::new(token_mint);
+
+    let mut accounts = ix_accounts::KeeperExecute {
+        margin_account,
+        keeper,
+        order,
+        price_oracle,
+        risk_config: derive_risk_config(&airspace),
+    }
+    .to_account_metas(None);
+    accounts.extend(
+        ix_accounts::RepayFromDeposit {
+            margin_account,
+            margin_pool: pool.address,
+            loan_note_mint: pool.loan_note_mint,
+            deposit_note_mint: pool.deposit_note_mint,
+            loan_account: derive_loan_account(&margin_account, &pool.loan_note_mint),
+            deposit_account: derive_position_token_account(
+                &margin_account,
+                &pool.deposit_note_mint,
+            ),
+            margin_pool_program: jet_margin_pool::id(),
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None),
+    );
+
+    let ix = Instruction {
+        program_id: jet_margin_swap::id(),
+        data: ix_data::KeeperExecute {
+            swap_routes: Default::default(),
+        }
+        .data(),
+        accounts,
+    };
+
+    // passed through to the margin pool for the repayment
+    pool.with_rewards(ix, &margin_account)
+}
+
 /// Trait to get required information from a swap pool for the [MarginSwapRouteIxBuilder]
 pub trait SwapAccounts {
     /// Convert the pool to a vec of [AccountMeta], takes an optional authority
@@ -140,7 +270,6 @@ pub struct MarginSwapRouteIxBuilder {
     /// The margin account creating the swap
     margin_account: Pubkey,
     /// SPL mint of the left side of the pool
-    #[allow(unused)]
     src_token: Pubkey,
     /// SPL mint of the right side of the pool
     dst_token: Pubkey,
@@ -168,6 +297,8 @@ pub struct MarginSwapRouteIxBuilder {
     swap_context: SwapContext,
     /// Whether this builder has liquidation accounts
     is_liquidation: bool,
+    /// The maximum deviation from the oracle prices allowed for the swap (bps)
+    max_price_deviation: Option<u16>,
 }
 
 impl MarginSwapRouteIxBuilder {
@@ -244,6 +375,7 @@ impl MarginSwapRouteIxBuilder {
             pool_note_mints,
             swap_context,
             is_liquidation: false,
+            max_price_deviation: None,
         }
     }
 
@@ -252,8 +384,9 @@ impl MarginSwapRouteIxBuilder {
         self.is_liquidation
     }
 
-    /// Mark as a swap for the purpose of liquidation
-    pub fn set_liquidation(&mut self) -> IxResult<()> {
+    /// Mark as a swap for the purpose of liquidation, in the airspace of the margin
+    /// account, whose risk config limits the price deviation of the swap
+    pub fn set_liquidation(&mut self, airspace: &Pubkey) -> IxResult<()> {
         if self.is_liquidation {
             return Err(JetIxError::SwapIxError(
                 "A liquidator is already set".to_string(),
@@ -270,17 +403,44 @@ impl MarginSwapRouteIxBuilder {
             ));
         }
 
-        self.account_metas.extend_from_slice(&[AccountMeta {
-            pubkey: get_associated_token_address(&get_control_authority_address(), &self.dst_token),
-            is_signer: false,
-            is_writable: true,
-        }]);
+        self.account_metas.extend_from_slice(&[
+            AccountMeta {
+                pubkey: get_associated_token_address(
+                    &get_control_authority_address(),
+                    &self.dst_token,
+                ),
+                is_signer: false,
+                is_writable: true,
+            },
+            AccountMeta::new_readonly(derive_risk_config(airspace), false),
+        ]);
 
         self.is_liquidation = true;
 
         Ok(())
     }
 
+    /// Reject the swap if the value received is less than the value swapped by more
+    /// than `bps`, based on the prices of the margin account's positions.
+    ///
+    /// Liquidation swaps are always checked, and the tolerance is capped by the program.
+    pub fn set_max_price_deviation(&mut self, bps: u16) -> IxResult<()> {
+        if bps > 10_000 {
+            return Err(JetIxError::SwapIxError(
+                "Price deviation cannot exceed 100%".to_string(),
+            ));
+        }
+        if self.is_finalized {
+            return Err(JetIxError::SwapIxError(
+                "Swap route is already finalized".to_string(),
+            ));
+        }
+
+        self.max_price_deviation = Some(bps);
+
+        Ok(())
+    }
+
     /// Add a swap leg to the route
     pub fn add_swap_leg<T: SwapAccounts + ?Sized>(
         &mut self,
@@ -298,6 +458,10 @@ impl MarginSwapRouteIxBuilder {
         // Run common checks
         self.verify_addition(&src_token, &dst_token, swap_split)?;
 
+        if self.current_route_tokens.is_none() {
+            self.add_pool_rewards_accounts();
+        }
+
         if !self.expects_multi_route {
             // Add source ATA and pool accounts. Add destination only if this is
             // the first part of a split leg.
@@ -344,6 +508,27 @@ impl MarginSwapRouteIxBuilder {
         Ok(())
     }
 
+    /// Add the rewards accounts of the source and destination pools ahead of the
+    /// swap legs, which the swap program passes through to the pools
+    fn add_pool_rewards_accounts(&mut self) {
+        if !matches!(self.swap_context, SwapContext::MarginPool) {
+            return;
+        }
+
+        for token in [self.src_token, self.dst_token] {
+            let pool = MarginPoolIxBuilder::new(token);
+            let pool_rewards = derive_pool_rewards(&pool.address);
+
+            self.account_metas.extend_from_slice(&[
+                AccountMeta::new(pool_rewards, false),
+                AccountMeta::new(
+                    derive_rewards_position(&pool_rewards, &self.margin_account),
+                    false,
+                ),
+            ]);
+        }
+    }
+
     /// Validate and finalize this swap
     pub fn finalize(&mut self) -> IxResult<()> {
         if self.is_finalized {
@@ -404,6 +589,7 @@ impl MarginSwapRouteIxBuilder {
                     minimum_amount_out: self.minimum_amount_out,
                     swap_routes: self.route_details,
                     is_liquidation: self.is_liquidation,
+                    max_price_deviation: self.max_price_deviation,
                 }
                 .data(),
                 SwapContext::MarginPositions => ix_data::RouteSwap {
@@ -411,12 +597,56 @@ impl MarginSwapRouteIxBuilder {
                     minimum_amount_out: self.minimum_amount_out,
                     swap_routes: self.route_details,
                     is_liquidation: self.is_liquidation,
+                    max_price_deviation: self.max_price_deviation,
                 }
                 .data(),
             },
         })
     }
 
+    /// Get the instruction for a keeper to execute a conditional swap order through
+    /// this route, which the caller should wrap with a keeper invoke.
+    ///
+    /// The order always swaps all of its source deposits, so the amounts that the
+    /// builder was created with are not used.
+    pub fn get_keeper_instruction(
+        &self,
+        airspace: Pubkey,
+        keeper: Pubkey,
+        order: Pubkey,
+        price_oracle: Pubkey,
+    ) -> IxResult<Instruction> {
+        if !self.is_finalized {
+            return Err(JetIxError::SwapIxError(
+                "Can only get instruction when the builder is finalized".to_string(),
+            ));
+        }
+        if !matches!(self.swap_context, SwapContext::MarginPool) || self.is_liquidation {
+            return Err(JetIxError::SwapIxError(
+                "Conditional orders can only swap between margin pools".to_string(),
+            ));
+        }
+
+        let mut accounts = ix_accounts::KeeperExecute {
+            margin_account: self.margin_account,
+            keeper,
+            order,
+            price_oracle,
+            risk_config: derive_risk_config(&airspace),
+        }
+        .to_account_metas(None);
+        accounts.extend_from_slice(&self.account_metas);
+
+        Ok(Instruction {
+            program_id: jet_margin_swap::id(),
+            accounts,
+            data: ix_data::KeeperExecute {
+                swap_routes: self.route_details,
+            }
+            .data(),
+        })
+    }
+
     /// Get the pool note mints that are used in the instruction
     pub fn get_pool_note_mints(&self) -> &HashSet<Pubkey> {
         &self.pool_note_mints
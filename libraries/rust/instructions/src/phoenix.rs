@@ -0,0 +1,105 @@
+use anchor_lang::ToAccountMetas;
+use jet_margin_swap::{accounts as ix_accounts, PhoenixMarketHeader, SwapRouteIdentifier};
+use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::margin_swap::SwapAccounts;
+
+pub use jet_program_common::programs::PHOENIX as PHOENIX_PROGRAM;
+
+/// The accounts of a phoenix market that are used for swaps
+#[derive(Debug, Clone, Copy)]
+pub struct PhoenixMarket {
+    /// The market address
+    pub market: Pubkey,
+    /// Base mint
+    pub base_mint: Pubkey,
+    /// Quote mint
+    pub quote_mint: Pubkey,
+    /// The vault holding the base tokens of the market
+    pub base_vault: Pubkey,
+    /// The vault holding the quote tokens of the market
+    pub quote_vault: Pubkey,
+    /// Base atoms per base lot
+    pub base_lot_size: u64,
+    /// Quote atoms per quote lot
+    pub quote_lot_size: u64,
+}
+
+impl PhoenixMarket {
+    /// Read a market from the data of its account, if it is a phoenix market
+    /// that accepts swaps
+    pub fn from_account_data(market: Pubkey, data: &[u8]) -> Option<Self> {
+        let header = PhoenixMarketHeader::load(data)?;
+
+        (header.status == PhoenixMarketHeader::STATUS_ACTIVE).then_some(Self {
+            market,
+            base_mint: header.base_mint,
+            quote_mint: header.quote_mint,
+            base_vault: header.base_vault,
+            quote_vault: header.quote_vault,
+            base_lot_size: header.base_lot_size,
+            quote_lot_size: header.quote_lot_size,
+        })
+    }
+}
+
+impl SwapAccounts for PhoenixMarket {
+    fn to_account_meta(&self, _authority: Pubkey) -> Vec<AccountMeta> {
+        ix_accounts::PhoenixSwapInfo {
+            swap_program: PHOENIX_PROGRAM,
+            log_authority: derive_log_authority(),
+            market: self.market,
+            base_vault: self.base_vault,
+            quote_vault: self.quote_vault,
+        }
+        .to_account_metas(None)
+    }
+
+    fn pool_tokens(&self) -> (Pubkey, Pubkey) {
+        (self.base_mint, self.quote_mint)
+    }
+
+    fn route_type(&self) -> SwapRouteIdentifier {
+        SwapRouteIdentifier::Phoenix
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// The authority that phoenix logs market events with
+pub fn derive_log_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"log"], &PHOENIX_PROGRAM).0
+}
+
+/// The vault of a phoenix market that holds one of its tokens
+pub fn derive_vault(market: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"vault", market.as_ref(), mint.as_ref()],
+        &PHOENIX_PROGRAM,
+    )
+    .0
+}
+
+/// The size of a phoenix market account, which has to be allocated before the
+/// market is initialized.
+///
+/// The market header is followed by the order book, which has a red-black tree
+/// for each of the bids, asks and seats. Every node of the trees has 16 bytes of
+/// registers, followed by its key and value.
+pub fn market_size(bids_size: u64, asks_size: u64, num_seats: u64) -> usize {
+    const HEADER: usize = std::mem::size_of::<PhoenixMarketHeader>();
+    const BOOK_HEADER: usize = 304;
+    const TREE_HEADER: usize = 32;
+    const ORDER_NODE: usize = 16 + 16 + 32;
+    const SEAT_NODE: usize = 16 + 32 + 96;
+
+    let tree = |nodes: u64, node_size: usize| TREE_HEADER + nodes as usize * node_size;
+
+    HEADER
+        + BOOK_HEADER
+        + tree(bids_size, ORDER_NODE)
+        + tree(asks_size, ORDER_NODE)
+        + tree(num_seats, SEAT_NODE)
+}
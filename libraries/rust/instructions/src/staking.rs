@@ -0,0 +1,689 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2022 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::{Id, System};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::sysvar::{rent::Rent, SysvarId};
+
+use jet_staking::{accounts as ix_accounts, instruction as ix_data, seeds, PoolConfig};
+
+use crate::margin::{derive_risk_config, derive_token_config};
+use crate::margin_pool::{derive_loan_account, MarginPoolIxBuilder};
+
+pub use jet_staking::ID as STAKING_PROGRAM;
+
+/// Utility for creating instructions to interact with the staking
+/// program for a specific stake pool.
+#[derive(Clone, Debug)]
+pub struct StakingIxBuilder {
+    /// The seed the pool address is derived from
+    pub seed: String,
+
+    /// The address of the stake pool
+    pub address: Pubkey,
+
+    /// The mint for the tokens being staked
+    pub token_mint: Pubkey,
+
+    /// The token account holding the staked tokens
+    pub vault: Pubkey,
+
+    /// The mint for collateral tokens representing stake owned by margin accounts
+    pub collateral_mint: Pubkey,
+
+    /// The governance realm the pool has voting power in
+    pub governance_realm: Pubkey,
+
+    /// The record of the max voter weight for the realm
+    pub max_voter_weight_record: Pubkey,
+}
+
+impl StakingIxBuilder {
+    /// Create a new builder for a stake pool by deriving its addresses
+    ///
+    /// # Params
+    ///
+    /// `seed` - The seed the pool address is derived from
+    /// `token_mint` - The mint for the tokens being staked
+    /// `governance_realm` - The governance realm the pool has voting power in
+    pub fn new(seed: &str, token_mint: Pubkey, governance_realm: Pubkey) -> Self {
+        let address = derive_stake_pool(seed);
+        let vault =
+            Pubkey::find_program_address(&[seed.as_bytes(), seeds::VAULT], &jet_staking::ID).0;
+        let collateral_mint = Pubkey::find_program_address(
+            &[seed.as_bytes(), seeds::COLLATERAL_MINT],
+            &jet_staking::ID,
+        )
+        .0;
+        let max_voter_weight_record = Pubkey::find_program_address(
+            &[governance_realm.as_ref(), seeds::MAX_VOTE_WEIGHT_RECORD],
+            &jet_staking::ID,
+        )
+        .0;
+
+        Self {
+            seed: seed.to_owned(),
+            address,
+            token_mint,
+            vault,
+            collateral_mint,
+            governance_realm,
+            max_voter_weight_record,
+        }
+    }
+
+    /// Instruction to create the stake pool
+    ///
+    /// # Params
+    ///
+    /// `payer` - The address paying for the rent
+    /// `authority` - The address allowed to manage the pool
+    /// `unbond_period` - The number of seconds unbonding stake takes
+    pub fn init_pool(&self, payer: Pubkey, authority: Pubkey, unbond_period: u64) -> Instruction {
+        let accounts = ix_accounts::InitPool {
+            payer,
+            authority,
+            token_mint: self.token_mint,
+            stake_pool: self.address,
+            max_voter_weight_record: self.max_voter_weight_record,
+            stake_collateral_mint: self.collateral_mint,
+            stake_pool_vault: self.vault,
+            token_program: spl_token::ID,
+            system_program: System::id(),
+            rent: Rent::id(),
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_staking::ID,
+            data: ix_data::InitPool {
+                seed: self.seed.clone(),
+                config: PoolConfig {
+                    unbond_period,
+                    governance_realm: self.governance_realm,
+                },
+            }
+            .data(),
+            accounts,
+        }
+    }
+
+    /// Instruction to create a stake account for an owner
+    ///
+    /// # Params
+    ///
+    /// `owner` - The owner of the stake, which needs a [jet_auth] authentication
+    /// `payer` - The address paying for the rent
+    pub fn init_stake_account(&self, owner: Pubkey, payer: Pubkey) -> Instruction {
+        let stake_account = self.stake_account(&owner);
+        let accounts = ix_accounts::InitStakeAccount {
+            owner,
+            auth: derive_user_authentication(&owner),
+            stake_pool: self.address,
+            stake_account,
+            voter_weight_record: derive_voter_weight_record(&stake_account),
+            payer,
+            system_program: System::id(),
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_staking::ID,
+            data: ix_data::InitStakeAccount {}.data(),
+            accounts,
+        }
+    }
+
+    /// Instruction to add tokens as stake to an account
+    ///
+    /// The lockup for the stake account is always passed, so any lockup bonus
+    /// is kept in its voter weight.
+    ///
+    /// # Params
+    ///
+    /// `owner` - The owner of the stake account
+    /// `payer` - The authority for the tokens being staked
+    /// `payer_token_account` - The token account to take the tokens from
+    /// `amount` - The amount of tokens to stake, or all of them if none
+    pub fn add_stake(
+        &self,
+        owner: &Pubkey,
+        payer: Pubkey,
+        payer_token_account: Pubkey,
+        amount: Option<u64>,
+    ) -> Instruction {
+        let stake_account = self.stake_account(owner);
+        let mut accounts = ix_accounts::AddStake {
+            stake_pool: self.address,
+            stake_pool_vault: self.vault,
+            stake_account,
+            voter_weight_record: derive_voter_weight_record(&stake_account),
+            max_voter_weight_record: self.max_voter_weight_record,
+            payer,
+            payer_token_account,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+
+        accounts.push(AccountMeta::new_readonly(
+            derive_stake_lockup(&stake_account),
+            false,
+        ));
+
+        Instruction {
+            program_id: jet_staking::ID,
+            data: ix_data::AddStake { amount }.data(),
+            accounts,
+        }
+    }
+
+    /// Instruction to lock up the stake in an account for extra voting power
+    ///
+    /// # Params
+    ///
+    /// `owner` - The owner of the stake account
+    /// `payer` - The address paying for the rent
+    /// `lockup_period` - The number of seconds from now to lock the stake for
+    pub fn lock_stake(&self, owner: Pubkey, payer: Pubkey, lockup_period: i64) -> Instruction {
+        let stake_account = self.stake_account(&owner);
+        let accounts = ix_accounts::LockStake {
+            owner,
+            payer,
+            stake_account,
+            stake_lockup: derive_stake_lockup(&stake_account),
+            voter_weight_record: derive_voter_weight_record(&stake_account),
+            system_program: System::id(),
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_staking::ID,
+            data: ix_data::LockStake { lockup_period }.data(),
+            accounts,
+        }
+    }
+
+    /// Instruction to update the voter weight for an account, including any
+    /// lockup bonus
+    pub fn update_voter_weight(&self, owner: &Pubkey) -> Instruction {
+        let stake_account = self.stake_account(owner);
+        let accounts = ix_accounts::UpdateVoterWeight {
+            stake_account,
+            stake_lockup: derive_stake_lockup(&stake_account),
+            voter_weight_record: derive_voter_weight_record(&stake_account),
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_staking::ID,
+            data: ix_data::UpdateVoterWeight {}.data(),
+            accounts,
+        }
+    }
+
+    /// Instruction to unbond stake from an account
+    ///
+    /// # Params
+    ///
+    /// `owner` - The owner of the stake account
+    /// `payer` - The address paying for the rent
+    /// `seed` - A seed to derive the unbonding account from
+    /// `amount` - The amount of tokens to unbond, or all of the stake if none
+    pub fn unbond_stake(
+        &self,
+        owner: Pubkey,
+        payer: Pubkey,
+        seed: u32,
+        amount: Option<u64>,
+    ) -> Instruction {
+        let accounts = self
+            .unbond_accounts(owner, payer, seed)
+            .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_staking::ID,
+            data: ix_data::UnbondStake { seed, amount }.data(),
+            accounts,
+        }
+    }
+
+    /// Instruction to create a stake account owned by a margin account, which
+    /// registers the collateral for its stake as a position
+    ///
+    /// This is invoked through the margin account with `adapter_invoke`, and
+    /// stake is then added with [Self::add_stake].
+    ///
+    /// # Params
+    ///
+    /// `airspace` - The airspace of the margin account
+    /// `margin_account` - The margin account to own the stake
+    /// `payer` - The address paying for the rent
+    pub fn margin_register_stake(
+        &self,
+        airspace: &Pubkey,
+        margin_account: Pubkey,
+        payer: Pubkey,
+    ) -> Instruction {
+        let stake_account = self.stake_account(&margin_account);
+        let accounts = ix_accounts::MarginRegisterStake {
+            margin_account,
+            stake_pool: self.address,
+            stake_account,
+            voter_weight_record: derive_voter_weight_record(&stake_account),
+            stake_collateral_mint: self.collateral_mint,
+            collateral_account: derive_stake_collateral_account(&stake_account),
+            collateral_token_config: derive_token_config(airspace, &self.collateral_mint),
+            payer,
+            token_program: spl_token::ID,
+            system_program: System::id(),
+            rent: Rent::id(),
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_staking::ID,
+            data: ix_data::MarginRegisterStake {}.data(),
+            accounts,
+        }
+    }
+
+    /// Instruction to update the collateral and price for the stake owned by
+    /// a margin account
+    ///
+    /// This is invoked through the margin account with `accounting_invoke`.
+    ///
+    /// # Params
+    ///
+    /// `airspace` - The airspace of the margin account
+    /// `margin_account` - The margin account owning the stake
+    /// `token_price_oracle` - The pyth price for the staked token, as
+    ///                        configured in the airspace
+    pub fn margin_refresh_stake(
+        &self,
+        airspace: &Pubkey,
+        margin_account: Pubkey,
+        token_price_oracle: Pubkey,
+    ) -> Instruction {
+        let stake_account = self.stake_account(&margin_account);
+        let accounts = ix_accounts::MarginRefreshStake {
+            margin_account,
+            stake_pool: self.address,
+            stake_pool_vault: self.vault,
+            stake_account,
+            stake_collateral_mint: self.collateral_mint,
+            collateral_account: derive_stake_collateral_account(&stake_account),
+            token_config: derive_token_config(airspace, &self.token_mint),
+            token_price_oracle,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_staking::ID,
+            data: ix_data::MarginRefreshStake {}.data(),
+            accounts,
+        }
+    }
+
+    /// Instruction to unbond stake owned by a margin account
+    ///
+    /// This is invoked through the margin account with `adapter_invoke`.
+    ///
+    /// # Params
+    ///
+    /// `margin_account` - The margin account owning the stake
+    /// `payer` - The address paying for the rent
+    /// `seed` - A seed to derive the unbonding account from
+    /// `amount` - The amount of tokens to unbond, or all of the stake if none
+    pub fn margin_unbond_stake(
+        &self,
+        margin_account: Pubkey,
+        payer: Pubkey,
+        seed: u32,
+        amount: Option<u64>,
+    ) -> Instruction {
+        let stake_account = self.stake_account(&margin_account);
+        let accounts = ix_accounts::MarginUnbondStake {
+            unbond: self.unbond_accounts(margin_account, payer, seed),
+            stake_collateral_mint: self.collateral_mint,
+            collateral_account: derive_stake_collateral_account(&stake_account),
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_staking::ID,
+            data: ix_data::MarginUnbondStake { seed, amount }.data(),
+            accounts,
+        }
+    }
+
+    /// Instruction to transfer bonded stake out of a margin account being
+    /// liquidated, into another stake account in the pool
+    ///
+    /// This is invoked through the margin account with `liquidator_invoke`.
+    ///
+    /// # Params
+    ///
+    /// `margin_account` - The margin account owning the stake
+    /// `target_owner` - The owner of the stake account to receive the stake
+    /// `shares` - The number of bonded shares to transfer
+    pub fn margin_transfer_stake(
+        &self,
+        margin_account: Pubkey,
+        target_owner: &Pubkey,
+        shares: u64,
+    ) -> Instruction {
+        let source_stake_account = self.stake_account(&margin_account);
+        let target_stake_account = self.stake_account(target_owner);
+        let accounts = ix_accounts::MarginTransferStake {
+            margin_account,
+            stake_pool: self.address,
+            source_stake_account,
+            source_voter_weight_record: derive_voter_weight_record(&source_stake_account),
+            token_owner_record: self.token_owner_record(&margin_account),
+            target_stake_account,
+            target_voter_weight_record: derive_voter_weight_record(&target_stake_account),
+            stake_collateral_mint: self.collateral_mint,
+            collateral_account: derive_stake_collateral_account(&source_stake_account),
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_staking::ID,
+            data: ix_data::MarginTransferStake { shares }.data(),
+            accounts,
+        }
+    }
+
+    /// Instruction to configure the pool as the insurance fund for an airspace
+    ///
+    /// # Params
+    ///
+    /// `authority` - The authority for the stake pool
+    /// `payer` - The address paying for the rent
+    /// `airspace` - The airspace whose bad debt is covered
+    /// `max_claim_bps` - The largest portion of the pool paid out for one claim
+    /// `max_epoch_bps` - The largest portion of the pool paid out in one epoch
+    pub fn configure_insurance(
+        &self,
+        authority: Pubkey,
+        payer: Pubkey,
+        airspace: Pubkey,
+        max_claim_bps: u16,
+        max_epoch_bps: u16,
+    ) -> Instruction {
+        let accounts = ix_accounts::ConfigureInsurance {
+            authority,
+            stake_pool: self.address,
+            insurance_fund: self.insurance_fund(),
+            payer,
+            system_program: System::id(),
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_staking::ID,
+            data: ix_data::ConfigureInsurance {
+                airspace,
+                max_claim_bps,
+                max_epoch_bps,
+            }
+            .data(),
+            accounts,
+        }
+    }
+
+    /// Instruction to write off the loan of an insolvent margin account, and
+    /// record the loss as a claim against the insurance fund
+    ///
+    /// # Params
+    ///
+    /// `authority` - The authority for the airspace
+    /// `airspace` - The airspace the insurance fund covers
+    /// `payer` - The address paying for the rent
+    /// `claim_index` - The number of claims recorded against the fund so far
+    /// `margin_account` - The insolvent margin account
+    /// `pool` - The margin pool the margin account has a loan from
+    /// `pool_token_oracle` - The pyth price for the tokens lent by the pool
+    /// `staked_token_oracle` - The pyth price for the staked token, as
+    ///                         configured in the airspace
+    #[allow(clippy::too_many_arguments)]
+    pub fn record_bad_debt(
+        &self,
+        authority: Pubkey,
+        airspace: Pubkey,
+        payer: Pubkey,
+        claim_index: u64,
+        margin_account: Pubkey,
+        pool: &MarginPoolIxBuilder,
+        pool_token_oracle: Pubkey,
+        staked_token_oracle: Pubkey,
+    ) -> Instruction {
+        let accounts = ix_accounts::RecordBadDebt {
+            authority,
+            airspace,
+            stake_pool: self.address,
+            insurance_fund: self.insurance_fund(),
+            claim: self.claim(claim_index),
+            beneficiary: self.recovery_account(&pool.address),
+            margin_account,
+            margin_pool: pool.address,
+            loan_note_mint: pool.loan_note_mint,
+            loan_account: derive_loan_account(&margin_account, &pool.loan_note_mint),
+            pool_token_mint: pool.token_mint,
+            pool_token_oracle,
+            staked_token_mint: self.token_mint,
+            staked_token_config: derive_token_config(&airspace, &self.token_mint),
+            staked_token_oracle,
+            risk_config: derive_risk_config(&airspace),
+            payer,
+            margin_pool_program: jet_margin_pool::ID,
+            token_program: spl_token::ID,
+            system_program: System::id(),
+            rent: Rent::id(),
+        }
+        .to_account_metas(None);
+
+        let ix = Instruction {
+            program_id: jet_staking::ID,
+            data: ix_data::RecordBadDebt {}.data(),
+            accounts,
+        };
+
+        // passed through to the margin pool for the write-off
+        pool.with_rewards(ix, &margin_account)
+    }
+
+    /// Instruction to pay out a claim against the insurance fund
+    ///
+    /// # Params
+    ///
+    /// `claim_index` - The index of the claim in the insurance fund
+    /// `beneficiary` - The token account recorded to receive the payout
+    pub fn cover_bad_debt(&self, claim_index: u64, beneficiary: Pubkey) -> Instruction {
+        let accounts = ix_accounts::CoverBadDebt {
+            stake_pool: self.address,
+            stake_pool_vault: self.vault,
+            insurance_fund: self.insurance_fund(),
+            claim: self.claim(claim_index),
+            beneficiary,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_staking::ID,
+            data: ix_data::CoverBadDebt {}.data(),
+            accounts,
+        }
+    }
+
+    /// Instruction to withdraw the payouts for bad debt from the recovery
+    /// account for a margin pool
+    ///
+    /// # Params
+    ///
+    /// `authority` - The authority for the stake pool
+    /// `margin_pool` - The margin pool the claims were recorded for
+    /// `token_receiver` - The token account to receive the withdrawn tokens
+    /// `amount` - The amount of tokens to withdraw
+    pub fn withdraw_recovered(
+        &self,
+        authority: Pubkey,
+        margin_pool: Pubkey,
+        token_receiver: Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        let accounts = ix_accounts::WithdrawRecovered {
+            authority,
+            stake_pool: self.address,
+            insurance_fund: self.insurance_fund(),
+            margin_pool,
+            recovery_account: self.recovery_account(&margin_pool),
+            token_receiver,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+
+        Instruction {
+            program_id: jet_staking::ID,
+            data: ix_data::WithdrawRecovered { amount }.data(),
+            accounts,
+        }
+    }
+
+    /// The address of the insurance fund config for the pool
+    pub fn insurance_fund(&self) -> Pubkey {
+        Pubkey::find_program_address(&[self.address.as_ref(), seeds::INSURANCE], &jet_staking::ID).0
+    }
+
+    /// The address of a claim against the insurance fund
+    pub fn claim(&self, claim_index: u64) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                self.insurance_fund().as_ref(),
+                claim_index.to_le_bytes().as_ref(),
+            ],
+            &jet_staking::ID,
+        )
+        .0
+    }
+
+    /// The address of the insurance fund's recovery account for a margin pool,
+    /// which receives the payouts for claims from the margin pool
+    pub fn recovery_account(&self, margin_pool: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                self.insurance_fund().as_ref(),
+                margin_pool.as_ref(),
+                seeds::RECOVERY,
+            ],
+            &jet_staking::ID,
+        )
+        .0
+    }
+
+    /// The address of the stake account for an owner
+    pub fn stake_account(&self, owner: &Pubkey) -> Pubkey {
+        derive_stake_account(&self.address, owner)
+    }
+
+    /// The address of the governance record for an owner's votes in the realm
+    pub fn token_owner_record(&self, owner: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                b"governance".as_ref(),
+                self.governance_realm.as_ref(),
+                self.token_mint.as_ref(),
+                owner.as_ref(),
+            ],
+            &jet_staking::spl_governance::ID,
+        )
+        .0
+    }
+
+    fn unbond_accounts(&self, owner: Pubkey, payer: Pubkey, seed: u32) -> ix_accounts::UnbondStake {
+        let stake_account = self.stake_account(&owner);
+        let token_owner_record = self.token_owner_record(&owner);
+
+        ix_accounts::UnbondStake {
+            owner,
+            payer,
+            stake_account,
+            stake_lockup: derive_stake_lockup(&stake_account),
+            stake_pool: self.address,
+            stake_pool_vault: self.vault,
+            unbonding_account: derive_unbonding_account(&stake_account, seed),
+            voter_weight_record: derive_voter_weight_record(&stake_account),
+            max_voter_weight_record: self.max_voter_weight_record,
+            token_owner_record,
+            system_program: System::id(),
+        }
+    }
+}
+
+/// Derive the address of a stake pool
+pub fn derive_stake_pool(seed: &str) -> Pubkey {
+    Pubkey::find_program_address(&[seed.as_bytes()], &jet_staking::ID).0
+}
+
+/// Derive the address of the stake account for an owner
+pub fn derive_stake_account(stake_pool: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[stake_pool.as_ref(), owner.as_ref()], &jet_staking::ID).0
+}
+
+/// Derive the address of the voter weight record for a stake account
+pub fn derive_voter_weight_record(stake_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[seeds::VOTER_WEIGHT_RECORD, stake_account.as_ref()],
+        &jet_staking::ID,
+    )
+    .0
+}
+
+/// Derive the address of the lockup for a stake account
+pub fn derive_stake_lockup(stake_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[stake_account.as_ref(), seeds::LOCKUP], &jet_staking::ID).0
+}
+
+/// Derive the address of an account recording a request to unbond stake
+pub fn derive_unbonding_account(stake_account: &Pubkey, seed: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[stake_account.as_ref(), seed.to_le_bytes().as_ref()],
+        &jet_staking::ID,
+    )
+    .0
+}
+
+/// Derive the address of the token account holding the collateral tokens for
+/// a stake account owned by a margin account
+pub fn derive_stake_collateral_account(stake_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[stake_account.as_ref(), seeds::COLLATERAL],
+        &jet_staking::ID,
+    )
+    .0
+}
+
+/// Derive the address of the [jet_auth] authentication for a user
+pub fn derive_user_authentication(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[user.as_ref()], &jet_auth::ID).0
+}
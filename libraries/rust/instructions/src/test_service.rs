@@ -17,13 +17,16 @@
 
 use anchor_lang::{InstructionData, ToAccountMetas};
 use jet_fixed_term::seeds;
-use jet_program_common::programs::ORCA_WHIRLPOOL;
+use jet_program_common::programs::{ORCA_WHIRLPOOL, PHOENIX};
 use solana_sdk::{
+    hash::hashv,
     instruction::Instruction,
     pubkey,
     pubkey::Pubkey,
     rent::Rent,
-    system_program,
+    signature::{keypair_from_seed, Keypair},
+    signer::Signer,
+    system_instruction, system_program,
     sysvar::{self, SysvarId},
 };
 
@@ -34,9 +37,11 @@ use jet_test_service::{
         TOKEN_INFO, TOKEN_MINT, TOKEN_PYTH_PRICE, TOKEN_PYTH_PRODUCT,
     },
     OpenBookMarketCancelOrdersParams, OpenBookMarketCreateParams, OpenBookMarketMakeParams,
-    SaberSwapPoolCreateParams,
+    PhoenixMarketCreateParams, SaberSwapPoolCreateParams,
 };
 
+use crate::phoenix;
+
 pub use jet_test_service::{SplSwapPoolCreateParams, TokenCreateParams};
 
 pub use jet_test_service::ID as TEST_SERVICE_PROGRAM;
@@ -421,6 +426,91 @@ pub fn openbook_market_create(
     }
 }
 
+/// The size of the order book of the Phoenix markets created for testing
+const PHOENIX_MARKET_SIZE: (u64, u64, u64) = (512, 512, 128);
+
+/// Create a Phoenix market, with the payer as its authority.
+///
+/// The market account must already be allocated, see [phoenix_market_allocate].
+pub fn phoenix_market_create(
+    payer: &Pubkey,
+    token_base: &Pubkey,
+    token_quote: &Pubkey,
+) -> Instruction {
+    let market = derive_phoenix_market(token_base, token_quote);
+    let (bids_size, asks_size, num_seats) = PHOENIX_MARKET_SIZE;
+
+    let accounts = jet_test_service::accounts::PhoenixMarketCreate {
+        payer: *payer,
+        mint_base: *token_base,
+        mint_quote: *token_quote,
+        info_base: derive_token_info(token_base),
+        info_quote: derive_token_info(token_quote),
+        market,
+        vault_base: phoenix::derive_vault(&market, token_base),
+        vault_quote: phoenix::derive_vault(&market, token_quote),
+        log_authority: phoenix::derive_log_authority(),
+        phoenix_program: PHOENIX,
+        token_program: spl_token::ID,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+
+    Instruction {
+        program_id: jet_test_service::ID,
+        accounts,
+        data: jet_test_service::instruction::PhoenixMarketCreate {
+            params: PhoenixMarketCreateParams {
+                bids_size,
+                asks_size,
+                num_seats,
+                num_quote_lots_per_quote_unit: 1_000_000,
+                tick_size_in_quote_lots_per_base_unit: 1_000,
+                num_base_lots_per_base_unit: 1_000,
+                taker_fee_bps: 5,
+            },
+        }
+        .data(),
+    }
+}
+
+/// Allocate the account for a Phoenix market created by [phoenix_market_create]
+pub fn phoenix_market_allocate(
+    payer: &Pubkey,
+    token_base: &Pubkey,
+    token_quote: &Pubkey,
+) -> (Instruction, Keypair) {
+    let (bids_size, asks_size, num_seats) = PHOENIX_MARKET_SIZE;
+    let size = phoenix::market_size(bids_size, asks_size, num_seats);
+    let market = phoenix_market_keypair(token_base, token_quote);
+
+    (
+        system_instruction::create_account(
+            payer,
+            &market.pubkey(),
+            Rent::default().minimum_balance(size),
+            size as u64,
+            &PHOENIX,
+        ),
+        market,
+    )
+}
+
+/// The keypair for the Phoenix market of a token pair.
+///
+/// Phoenix markets are too large to be PDAs, so the keypair is derived from
+/// the tokens instead, which is only suitable for testing.
+pub fn phoenix_market_keypair(token_base: &Pubkey, token_quote: &Pubkey) -> Keypair {
+    let seed = hashv(&[b"phoenix-market", token_base.as_ref(), token_quote.as_ref()]);
+
+    keypair_from_seed(seed.as_ref()).unwrap()
+}
+
+/// Get the Phoenix market for a token pair
+pub fn derive_phoenix_market(token_base: &Pubkey, token_quote: &Pubkey) -> Pubkey {
+    phoenix_market_keypair(token_base, token_quote).pubkey()
+}
+
 /// Cancel existing Openbook orders
 #[allow(clippy::too_many_arguments)]
 pub fn openbook_market_cancel_orders(
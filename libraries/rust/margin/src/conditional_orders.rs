@@ -0,0 +1,307 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{sync::Arc, time::Duration};
+
+use anchor_lang::AccountDeserialize;
+use anchor_spl::associated_token::get_associated_token_address;
+use anyhow::{Context, Result};
+use jet_client::state::AccountStates;
+use jet_instructions::margin::keeper_invoke;
+use jet_instructions::margin_swap::{keeper_execute_repay, SwapContext};
+use jet_margin_pool::{Amount, MarginPool, PoolAction};
+use jet_margin_swap::{ConditionalOrder, OrderAction};
+use jet_program_common::Number128;
+use jet_simulation::SolanaRpcClient;
+use jet_solana_client::rpc::{AccountFilter, SolanaRpc};
+use pyth_sdk_solana::state::load_price_account;
+use solana_sdk::{
+    address_lookup_table_account::AddressLookupTableAccount,
+    compute_budget::ComputeBudgetInstruction, instruction::Instruction, pubkey::Pubkey,
+    signer::Signer,
+};
+use tokio::sync::RwLock;
+
+use crate::{
+    get_state::{get_anchor_account, get_margin_account},
+    margin_account_ext::MarginAccountExt,
+    refresh::{canonical_position_refresher, position_refresher::PositionRefresher},
+    solana::transaction::{SendTransactionBuilder, TransactionBuilder},
+    swap::router::SwapRouter,
+};
+
+type KeyAccount<T> = (Pubkey, T);
+
+/// Executes the conditional orders of margin accounts once their trigger prices
+/// are reached, earning the keeper fee of each order.
+///
+/// The RPC payer is used as the keeper.
+pub struct ConditionalOrderKeeper {
+    rpc: Arc<dyn SolanaRpcClient>,
+    router: RwLock<SwapRouter>,
+    network: Option<Arc<dyn SolanaRpc>>,
+    lookup_tables: Vec<AddressLookupTableAccount>,
+}
+
+impl ConditionalOrderKeeper {
+    /// Create a keeper with a router that is not refreshed
+    ///
+    /// # Params
+    ///
+    /// `router` - Used to find the swap routes of swap orders
+    /// `lookup_tables` - Lookup tables that can be used to pack the transactions
+    pub fn new(
+        rpc: Arc<dyn SolanaRpcClient>,
+        router: SwapRouter,
+        lookup_tables: Vec<AddressLookupTableAccount>,
+    ) -> Self {
+        Self {
+            rpc,
+            router: RwLock::new(router),
+            network: None,
+            lookup_tables,
+        }
+    }
+
+    /// Create a keeper that routes swaps through the venues known to a client,
+    /// reloading the state of the venues before executing any orders.
+    ///
+    /// # Params
+    ///
+    /// `network` - Used to load the state of the swap venues
+    /// `states` - The client state with the swap venues
+    /// `lookup_tables` - Lookup tables that can be used to pack the transactions
+    pub async fn load(
+        rpc: Arc<dyn SolanaRpcClient>,
+        network: Arc<dyn SolanaRpc>,
+        states: &AccountStates,
+        lookup_tables: Vec<AddressLookupTableAccount>,
+    ) -> Result<Self> {
+        let router = SwapRouter::load(network.as_ref(), states).await?;
+
+        Ok(Self {
+            rpc,
+            router: RwLock::new(router),
+            network: Some(network),
+            lookup_tables,
+        })
+    }
+
+    /// Execute all the orders that have been triggered
+    pub async fn execute_all(&self) {
+        let orders = match self.fetch_orders().await {
+            Ok(orders) => orders,
+            Err(e) => {
+                tracing::warn!("encountered error fetching conditional orders: [{e}]");
+                return;
+            }
+        };
+
+        let mut router_refreshed = false;
+        for (address, order) in orders {
+            match self.is_triggered(&order).await {
+                Ok(true) => (),
+                Ok(false) => continue,
+                Err(e) => {
+                    tracing::warn!("failed to check the trigger of order [{address}]: [{e}]");
+                    continue;
+                }
+            }
+
+            // Quotes are only as accurate as the pool states they are based on
+            if let (Some(network), false) = (&self.network, router_refreshed) {
+                self.router.write().await.refresh(network.as_ref()).await;
+                router_refreshed = true;
+            }
+
+            tracing::debug!(
+                "executing order [{address}] for margin account [{}]",
+                order.margin_account
+            );
+            if let Err(e) = self.execute(address, &order).await {
+                tracing::warn!("encountered error while executing order [{address}]: [{e}]");
+            }
+        }
+    }
+
+    /// Repeatedly execute triggered orders, waiting for `delay` between each attempt
+    pub async fn execute_forever(&self, delay: Duration) {
+        tracing::trace!("starting conditional order keeper loop");
+        loop {
+            self.execute_all().await;
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Whether the current price of the order's oracle has reached the trigger
+    pub async fn is_triggered(&self, order: &ConditionalOrder) -> Result<bool> {
+        let account = self
+            .rpc
+            .get_account(&order.price_oracle)
+            .await?
+            .with_context(|| format!("oracle {} does not exist", order.price_oracle))?;
+        let price = load_price_account(&account.data)?
+            .to_price_feed(&order.price_oracle)
+            .get_price_unchecked();
+
+        Ok(order.is_triggered(Number128::from_decimal(price.price, price.expo)))
+    }
+
+    /// Execute an order, refreshing the positions of its margin account first
+    pub async fn execute(&self, address: Pubkey, order: &ConditionalOrder) -> Result<()> {
+        let keeper = self.rpc.payer().pubkey();
+        let margin_account = get_margin_account(&self.rpc, &order.margin_account).await?;
+
+        let mut transactions = vec![];
+        let action = match order.action {
+            OrderAction::Swap {
+                source_pool,
+                destination_pool,
+                max_slippage_bps,
+            } => {
+                let source_pool = get_anchor_account::<MarginPool>(&self.rpc, &source_pool).await?;
+                let destination_pool =
+                    get_anchor_account::<MarginPool>(&self.rpc, &destination_pool).await?;
+                let amount = source_pool
+                    .convert_amount(
+                        Amount::notes(margin_account.balance(&source_pool.deposit_note_mint)),
+                        PoolAction::Withdraw,
+                    )?
+                    .tokens;
+
+                let route = self
+                    .router
+                    .read()
+                    .await
+                    .find_route(
+                        source_pool.token_mint,
+                        destination_pool.token_mint,
+                        amount,
+                        max_slippage_bps,
+                    )
+                    .with_context(|| {
+                        format!(
+                            "no swap route from {} to {}",
+                            source_pool.token_mint, destination_pool.token_mint
+                        )
+                    })?;
+                let builder = route.builder(SwapContext::MarginPool, order.margin_account, None)?;
+
+                let setup = self
+                    .create_missing_token_accounts(
+                        &order.margin_account,
+                        builder.get_spl_token_mints(),
+                    )
+                    .await?;
+                if !setup.is_empty() {
+                    transactions.push(setup.into());
+                }
+
+                builder.get_keeper_instruction(
+                    margin_account.airspace,
+                    keeper,
+                    address,
+                    order.price_oracle,
+                )?
+            }
+            OrderAction::Repay { pool } => {
+                let pool = get_anchor_account::<MarginPool>(&self.rpc, &pool).await?;
+
+                keeper_execute_repay(
+                    margin_account.airspace,
+                    keeper,
+                    order.margin_account,
+                    address,
+                    order.price_oracle,
+                    pool.token_mint,
+                )
+            }
+        };
+
+        transactions.extend(
+            canonical_position_refresher(self.rpc.clone())
+                .refresh_positions(&margin_account)
+                .await?,
+        );
+        transactions.push(TransactionBuilder::from(vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(800000),
+            keeper_invoke(
+                margin_account.airspace,
+                keeper,
+                order.margin_account,
+                action,
+            ),
+        ]));
+
+        self.rpc
+            .send_and_confirm_condensed_in_order_with_lookup(transactions, &self.lookup_tables)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn fetch_orders(&self) -> Result<Vec<KeyAccount<ConditionalOrder>>> {
+        let orders = self
+            .rpc
+            .get_program_accounts(
+                &jet_margin_swap::ID,
+                vec![AccountFilter::DataSize(ConditionalOrder::SPACE)],
+            )
+            .await?
+            .into_iter()
+            .filter_map(
+                |(k, a)| match ConditionalOrder::try_deserialize(&mut a.data.as_ref()) {
+                    Ok(order) => Some((k, order)),
+                    Err(_) => {
+                        tracing::warn!("failed to deserialize conditional order [{k}]");
+                        None
+                    }
+                },
+            )
+            .collect::<Vec<_>>();
+
+        if !orders.is_empty() {
+            tracing::trace!("found [{}] conditional orders", orders.len());
+        }
+        Ok(orders)
+    }
+
+    /// The swap routes pass through the associated token accounts of the margin account,
+    /// which the keeper pays for if they do not exist yet.
+    async fn create_missing_token_accounts(
+        &self,
+        margin_account: &Pubkey,
+        token_mints: impl IntoIterator<Item = &Pubkey>,
+    ) -> Result<Vec<Instruction>> {
+        let mut instructions = vec![];
+        for token_mint in token_mints {
+            let ata = get_associated_token_address(margin_account, token_mint);
+            if self.rpc.get_account(&ata).await?.is_none() {
+                instructions.push(
+                    spl_associated_token_account::instruction::create_associated_token_account(
+                        &self.rpc.payer().pubkey(),
+                        margin_account,
+                        token_mint,
+                        &spl_token::id(),
+                    ),
+                );
+            }
+        }
+
+        Ok(instructions)
+    }
+}
@@ -1,4 +1,7 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use anchor_lang::AccountDeserialize;
 use futures::future::join_all;
@@ -15,6 +18,7 @@ use jet_solana_client::rpc::AccountFilter;
 use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
 use thiserror::Error;
 
+use super::metrics;
 use crate::solana::transaction::SendTransactionBuilder;
 
 type KeyAccount<T> = (Pubkey, T);
@@ -53,9 +57,13 @@ impl AutoRollServicer {
 
         for res in join_all(users).await {
             match res {
-                Err(e) => tracing::warn!("encountered error while servicing users: [{e}]"),
+                Err(e) => {
+                    tracing::warn!("encountered error while servicing users: [{e}]");
+                    metrics::transaction_failure(&self.ix.market(), "auto-roll", e.kind());
+                }
                 Ok(_) => {
                     tracing::trace!("successfully serviced [{num_users}] users");
+                    metrics::user_serviced(&self.ix.market());
                     continue;
                 }
             }
@@ -65,7 +73,9 @@ impl AutoRollServicer {
     pub async fn service_forever(&self, delay: Duration) {
         tracing::trace!("starting servicer loop");
         loop {
+            let start = Instant::now();
             self.service_all().await;
+            metrics::loop_duration("auto-roll", start);
             tokio::time::sleep(delay).await;
         }
     }
@@ -75,7 +85,9 @@ impl AutoRollServicer {
 
         let mut ixns = vec![];
         self.with_service_loans(&user, &mut ixns).await?;
+        let borrow_orders = ixns.len();
         self.with_service_deposits(&user, &mut ixns).await?;
+        let lend_orders = ixns.len() - borrow_orders;
         if !ixns.is_empty() {
             tracing::debug!(
                 "sending [{}] instructions to service user [{}]",
@@ -83,7 +95,11 @@ impl AutoRollServicer {
                 user.0
             );
         }
-        self.bundle_and_send(ixns).await
+        self.bundle_and_send(ixns).await?;
+
+        metrics::auto_roll_orders(&self.ix.market(), "borrow", borrow_orders);
+        metrics::auto_roll_orders(&self.ix.market(), "lend", lend_orders);
+        Ok(())
     }
 
     async fn with_service_loans(
@@ -311,4 +327,15 @@ pub enum ServicerError {
     #[error("failed to fetch the instruction builder for market: {0}")]
     MissingIxBuilder(Pubkey),
 }
+
+impl ServicerError {
+    /// A short name for the kind of error, suitable for labeling metrics
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ServicerError::Rpc(_) => "rpc",
+            ServicerError::Anchor(_) => "anchor",
+            ServicerError::MissingIxBuilder(_) => "missing_ix_builder",
+        }
+    }
+}
 type Result<T> = std::result::Result<T, ServicerError>;
@@ -1,7 +1,7 @@
 use std::{
     collections::HashMap,
     sync::{Arc, Mutex},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use agnostic_orderbook::state::{
@@ -29,7 +29,7 @@ use tracing::instrument;
 
 use crate::util::no_dupe_queue::AsyncNoDupeQueue;
 
-use super::{FixedTermIxBuilder, OwnedEventQueue};
+use super::{metrics, FixedTermIxBuilder, OwnedEventQueue};
 
 const MAX_EVENTS_PER_TX: usize = 8;
 
@@ -51,6 +51,19 @@ pub enum EventConsumerError {
     Program(String),
 }
 
+impl EventConsumerError {
+    /// A short name for the kind of error, suitable for labeling metrics
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EventConsumerError::Rpc(_) => "rpc",
+            EventConsumerError::InvalidMarketAccount(_) => "invalid_market_account",
+            EventConsumerError::InvalidEventQueue(_) => "invalid_event_queue",
+            EventConsumerError::InvalidUserKey(_) => "invalid_user_key",
+            EventConsumerError::Program(_) => "program",
+        }
+    }
+}
+
 /// Utility for running consume-events for fixed term markets
 pub struct EventConsumer {
     rpc: Arc<dyn SolanaRpcClient>,
@@ -120,9 +133,11 @@ impl EventConsumer {
     /// Start a loop to continuously consume events. Never returns. Logs errors
     pub async fn sync_and_consume_forever(&self, targets: &[Pubkey], delay: Duration) {
         loop {
+            let start = Instant::now();
             if let Err(e) = self.sync_and_consume_all(targets).await {
                 tracing::error!("Error while consuming events: {e:?}");
             }
+            metrics::loop_duration("event-consumer", start);
             tokio::time::sleep(delay).await;
         }
     }
@@ -220,6 +235,10 @@ impl EventConsumer {
             if let Some(account) = account {
                 market_state.queue = account.data;
 
+                if let Ok(pending) = market_state.pending_events() {
+                    metrics::pending_events(&market, pending);
+                }
+
                 tracing::trace!(?market, "sync queue {}", market_state.market.event_queue);
             } else {
                 tracing::error!(?market, "queue account missing");
@@ -253,6 +272,7 @@ impl EventConsumer {
         for result in &results {
             if let Err((market, e)) = result {
                 tracing::error!(?market, "failed consuming events because: {e}",);
+                metrics::transaction_failure(market, "event-consumer", e.kind());
             }
         }
 
@@ -410,6 +430,8 @@ impl MarketState {
         self.pop_events(consume_params.len())?;
 
         rpc.send_and_confirm_transaction(&consume_tx).await?;
+        metrics::events_consumed(&self.market_address, consume_params.len());
+
         if let Some(sink) = self.margin_accounts_to_settle.as_ref() {
             sink.push_many(margin_accounts_to_settle).await;
         }
@@ -0,0 +1,91 @@
+//! Metrics reported by the fixed term crank and servicers.
+//!
+//! These are recorded through the `metrics` facade, so they have no effect unless
+//! the running binary installs a recorder (for example a prometheus exporter).
+
+use std::time::Instant;
+
+use metrics::{describe_counter, describe_gauge, describe_histogram, Unit};
+use solana_sdk::pubkey::Pubkey;
+
+pub const PENDING_EVENTS: &str = "fixed_term_pending_events";
+pub const EVENTS_CONSUMED: &str = "fixed_term_events_consumed_total";
+pub const TRANSACTION_FAILURES: &str = "fixed_term_transaction_failures_total";
+pub const USERS_SERVICED: &str = "fixed_term_auto_roll_users_serviced_total";
+pub const AUTO_ROLL_ORDERS: &str = "fixed_term_auto_roll_orders_total";
+pub const LOOP_DURATION: &str = "fixed_term_loop_duration_seconds";
+
+/// Register descriptions for all the fixed term metrics with the installed recorder
+pub fn describe() {
+    describe_gauge!(
+        PENDING_EVENTS,
+        Unit::Count,
+        "events waiting to be consumed in the market's event queue"
+    );
+    describe_counter!(
+        EVENTS_CONSUMED,
+        Unit::Count,
+        "events consumed from the market's event queue"
+    );
+    describe_counter!(
+        TRANSACTION_FAILURES,
+        Unit::Count,
+        "transactions that failed to be processed, by the kind of error"
+    );
+    describe_counter!(
+        USERS_SERVICED,
+        Unit::Count,
+        "margin users serviced for auto-roll"
+    );
+    describe_counter!(
+        AUTO_ROLL_ORDERS,
+        Unit::Count,
+        "auto-roll orders placed on behalf of users"
+    );
+    describe_histogram!(
+        LOOP_DURATION,
+        Unit::Seconds,
+        "time taken by a single iteration of a service loop"
+    );
+}
+
+pub(crate) fn pending_events(market: &Pubkey, count: usize) {
+    metrics::gauge!(PENDING_EVENTS, count as f64, "market" => market.to_string());
+}
+
+pub(crate) fn events_consumed(market: &Pubkey, count: usize) {
+    metrics::counter!(EVENTS_CONSUMED, count as u64, "market" => market.to_string());
+}
+
+pub(crate) fn transaction_failure(market: &Pubkey, service: &'static str, error: &'static str) {
+    metrics::counter!(
+        TRANSACTION_FAILURES,
+        1,
+        "market" => market.to_string(),
+        "service" => service,
+        "error" => error
+    );
+}
+
+pub(crate) fn user_serviced(market: &Pubkey) {
+    metrics::counter!(USERS_SERVICED, 1, "market" => market.to_string());
+}
+
+pub(crate) fn auto_roll_orders(market: &Pubkey, side: &'static str, count: usize) {
+    if count > 0 {
+        metrics::counter!(
+            AUTO_ROLL_ORDERS,
+            count as u64,
+            "market" => market.to_string(),
+            "side" => side
+        );
+    }
+}
+
+pub(crate) fn loop_duration(service: &'static str, start: Instant) {
+    metrics::histogram!(
+        LOOP_DURATION,
+        start.elapsed().as_secs_f64(),
+        "service" => service
+    );
+}
@@ -4,6 +4,7 @@ pub mod auto_roll_servicer;
 pub mod error;
 pub mod event_consumer;
 mod ix_builder;
+pub mod metrics;
 pub mod settler;
 
 use futures::future::{join_all, try_join_all};
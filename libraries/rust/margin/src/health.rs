@@ -0,0 +1,595 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Project the health of a margin account after a plan of hypothetical actions.
+//!
+//! The simulation applies each action to a copy of the [MarginAccount] and values
+//! it with [MarginAccount::valuation], so the results use the same fixed point math
+//! as the margin program.
+//!
+//! Amounts are in position tokens. Pool deposits and loans are expressed in notes,
+//! and fixed-term orders in the tokens that the order is expected to be filled with.
+//! Price shocks are given for underlying tokens, and change the price of every
+//! position that is priced in that token.
+
+use std::collections::HashMap;
+
+use anyhow::{bail, Context, Result};
+use solana_sdk::pubkey::Pubkey;
+
+use jet_margin::{
+    AccountPosition, Approver, MarginAccount, PositionConfigUpdate, PriceInfo, RiskParameters,
+    TokenKind, Valuation,
+};
+use jet_program_common::Number128;
+
+pub use jet_client::margin::liquidation::PriceShock;
+
+/// An amount of a position token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionAmount {
+    /// The mint of the position token
+    pub token: Pubkey,
+
+    /// The amount of position tokens, in the token's smallest unit
+    pub amount: u64,
+}
+
+/// A hypothetical change to a margin account
+#[derive(Debug, Clone)]
+pub enum SimulatedAction {
+    /// Register a position that later actions can add tokens to
+    RegisterPosition {
+        /// The configuration of the new position
+        config: PositionConfigUpdate,
+        /// The price of the position token
+        price: PriceInfo,
+        /// The underlying token that the position is priced in
+        underlying_mint: Pubkey,
+    },
+
+    /// Borrow from a margin pool, keeping the borrowed tokens as a pool deposit
+    Borrow {
+        /// The loan notes that are issued
+        loan: PositionAmount,
+        /// The deposit notes for the borrowed tokens
+        deposit: PositionAmount,
+    },
+
+    /// Swap the tokens of one position for the tokens of another
+    Swap {
+        /// The tokens that are exchanged
+        source: PositionAmount,
+        /// The tokens that are received
+        target: PositionAmount,
+    },
+
+    /// Borrow from a fixed-term market with an order that is filled
+    FixedTermBorrow {
+        /// The term loan claim, including the interest due at maturity
+        claim: PositionAmount,
+        /// The tokens that are received from the loan
+        proceeds: PositionAmount,
+    },
+
+    /// Lend to a fixed-term market with an order that is filled
+    FixedTermLend {
+        /// The tokens that are lent
+        source: PositionAmount,
+        /// The collateral that is received for the term deposit
+        collateral: PositionAmount,
+    },
+
+    /// Withdraw tokens out of the margin account
+    Withdraw(PositionAmount),
+}
+
+/// The projected health of a margin account
+#[derive(Debug, Clone)]
+pub struct SimulatedHealth {
+    /// The valuation of the account
+    pub valuation: Valuation,
+
+    /// The collateral that is not required to cover the account's claims, which is
+    /// negative if the account would be unhealthy
+    pub available_collateral: Number128,
+
+    /// The ratio of effective collateral to required collateral
+    pub c_ratio: Number128,
+}
+
+impl SimulatedHealth {
+    fn new(valuation: Valuation) -> Self {
+        Self {
+            available_collateral: valuation.available_collateral(),
+            c_ratio: valuation.effective_c_ratio(),
+            valuation,
+        }
+    }
+
+    /// Whether the account would be healthy
+    pub fn is_healthy(&self) -> bool {
+        self.valuation.required_collateral <= self.valuation.effective_collateral
+    }
+}
+
+/// The projected health of a margin account throughout a plan
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    /// The health after any price shocks, before the first action
+    pub initial: SimulatedHealth,
+
+    /// The health after each action, in the order of the plan
+    pub steps: Vec<SimulatedHealth>,
+}
+
+impl Simulation {
+    /// The health after the last action of the plan
+    pub fn last(&self) -> &SimulatedHealth {
+        self.steps.last().unwrap_or(&self.initial)
+    }
+
+    /// The first step where the account would be unhealthy, if any
+    pub fn first_unhealthy_step(&self) -> Option<usize> {
+        self.steps.iter().position(|step| !step.is_healthy())
+    }
+}
+
+/// Simulates hypothetical actions and price changes on a copy of a margin account
+#[derive(Clone)]
+pub struct HealthSimulator {
+    account: MarginAccount,
+    timestamp: u64,
+    risk: RiskParameters,
+    underlying_mints: HashMap<Pubkey, Pubkey>,
+}
+
+impl HealthSimulator {
+    /// Start a simulation from the current state of an account.
+    ///
+    /// The positions should be refreshed recently enough to be valued at `timestamp`.
+    /// Each position is assumed to be priced in its own token, unless its underlying
+    /// token is set with [HealthSimulator::with_underlying_mints].
+    pub fn new(account: &MarginAccount, timestamp: u64, risk: RiskParameters) -> Self {
+        Self {
+            account: *account,
+            timestamp,
+            risk,
+            underlying_mints: HashMap::new(),
+        }
+    }
+
+    /// Set the underlying token that each position token is priced in, such as the
+    /// token of the pool for deposit and loan notes
+    pub fn with_underlying_mints(
+        mut self,
+        mints: impl IntoIterator<Item = (Pubkey, Pubkey)>,
+    ) -> Self {
+        self.underlying_mints.extend(mints);
+        self
+    }
+
+    /// The simulated state of the account
+    pub fn account(&self) -> &MarginAccount {
+        &self.account
+    }
+
+    /// Change the price of the positions priced in an underlying token
+    pub fn with_price_shock(mut self, shock: &PriceShock) -> Result<Self> {
+        self.shock_price(shock)?;
+        Ok(self)
+    }
+
+    /// Change the prices of the positions priced in several underlying tokens
+    pub fn with_price_shocks(mut self, shocks: &[PriceShock]) -> Result<Self> {
+        for shock in shocks {
+            self.shock_price(shock)?;
+        }
+        Ok(self)
+    }
+
+    /// Value the account in its current simulated state
+    pub fn health(&self) -> Result<SimulatedHealth> {
+        let valuation = self
+            .account
+            .valuation(self.timestamp, &self.risk)
+            .context("could not value the simulated margin account")?;
+
+        Ok(SimulatedHealth::new(valuation))
+    }
+
+    /// Apply each action in order, valuing the account after every step
+    pub fn simulate(mut self, actions: &[SimulatedAction]) -> Result<Simulation> {
+        let initial = self.health()?;
+        let mut steps = Vec::with_capacity(actions.len());
+
+        for (index, action) in actions.iter().enumerate() {
+            self.apply(action)
+                .with_context(|| format!("could not simulate action {index}: {action:?}"))?;
+            steps.push(self.health()?);
+        }
+
+        Ok(Simulation { initial, steps })
+    }
+
+    /// Apply a single action to the simulated account, leaving it unchanged if the
+    /// action is invalid
+    pub fn apply(&mut self, action: &SimulatedAction) -> Result<()> {
+        let previous = self.account;
+        let result = self.apply_changes(action);
+
+        if result.is_err() {
+            self.account = previous;
+        }
+
+        result
+    }
+
+    fn apply_changes(&mut self, action: &SimulatedAction) -> Result<()> {
+        match action {
+            SimulatedAction::RegisterPosition {
+                config,
+                price,
+                underlying_mint,
+            } => self.register(config, price, underlying_mint),
+            SimulatedAction::Borrow { loan, deposit } => {
+                self.increase(loan)?;
+                self.increase(deposit)
+            }
+            SimulatedAction::Swap { source, target } => {
+                self.decrease(source)?;
+                self.increase(target)
+            }
+            SimulatedAction::FixedTermBorrow { claim, proceeds } => {
+                self.increase(claim)?;
+                self.increase(proceeds)
+            }
+            SimulatedAction::FixedTermLend { source, collateral } => {
+                self.decrease(source)?;
+                self.increase(collateral)
+            }
+            SimulatedAction::Withdraw(amount) => self.decrease(amount),
+        }
+    }
+
+    fn register(
+        &mut self,
+        config: &PositionConfigUpdate,
+        price: &PriceInfo,
+        underlying_mint: &Pubkey,
+    ) -> Result<()> {
+        if self.account.get_position(&config.mint).is_some() {
+            bail!("position {} is already registered", config.mint);
+        }
+
+        // The margin program only lets adapters register the positions they manage
+        let approvals = match config.kind {
+            TokenKind::Collateral => vec![Approver::MarginAccountAuthority],
+            TokenKind::Claim | TokenKind::AdapterCollateral => vec![
+                Approver::MarginAccountAuthority,
+                Approver::Adapter(config.adapter),
+            ],
+        };
+
+        self.account
+            .register_position(config.clone(), &approvals, &self.risk)?;
+        self.account
+            .set_position_price(&config.mint, price)
+            .map_err(anchor_lang::error::Error::from)?;
+        self.underlying_mints.insert(config.mint, *underlying_mint);
+        self.set_balance(&config.mint, 0)
+    }
+
+    fn increase(&mut self, change: &PositionAmount) -> Result<()> {
+        let balance = self.balance(&change.token)?;
+        let Some(balance) = balance.checked_add(change.amount) else {
+            bail!("balance of position {} overflows", change.token);
+        };
+
+        self.set_balance(&change.token, balance)
+    }
+
+    fn decrease(&mut self, change: &PositionAmount) -> Result<()> {
+        let balance = self.balance(&change.token)?;
+        let Some(balance) = balance.checked_sub(change.amount) else {
+            bail!(
+                "position {} has a balance of {balance}, which is less than {}",
+                change.token,
+                change.amount
+            );
+        };
+
+        self.set_balance(&change.token, balance)
+    }
+
+    fn balance(&self, token: &Pubkey) -> Result<u64> {
+        Ok(self.position(token)?.balance)
+    }
+
+    fn position(&self, token: &Pubkey) -> Result<&AccountPosition> {
+        self.account
+            .get_position(token)
+            .with_context(|| format!("position {token} is not registered"))
+    }
+
+    fn set_balance(&mut self, token: &Pubkey, balance: u64) -> Result<()> {
+        let address = self.position(token)?.address;
+        self.account
+            .set_position_balance(token, &address, balance, self.timestamp)
+            .map_err(anchor_lang::error::Error::from)?;
+
+        Ok(())
+    }
+
+    fn underlying_mint(&self, position: &AccountPosition) -> Pubkey {
+        self.underlying_mints
+            .get(&position.token)
+            .copied()
+            .unwrap_or(position.token)
+    }
+
+    fn shock_price(&mut self, shock: &PriceShock) -> Result<()> {
+        let ratio = Number128::ONE + shock.change;
+        if ratio < Number128::ZERO {
+            bail!("the price of {} cannot drop below zero", shock.token);
+        }
+
+        let shocked: Vec<(Pubkey, PriceInfo)> = self
+            .account
+            .positions()
+            .filter(|position| self.underlying_mint(position) == shock.token)
+            .map(|position| (position.token, position.price))
+            .collect();
+
+        if shocked.is_empty() {
+            bail!("no positions are priced in {}", shock.token);
+        }
+
+        for (token, price) in shocked {
+            let value = (price.value as i128)
+                .checked_mul(ratio.to_i128())
+                .map(|value| value / Number128::ONE.to_i128())
+                .and_then(|value| i64::try_from(value).ok());
+            let Some(value) = value else {
+                bail!("the shocked price of {token} is too large");
+            };
+
+            self.account
+                .set_position_price(&token, &PriceInfo { value, ..price })
+                .map_err(anchor_lang::error::Error::from)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+
+    use super::*;
+
+    const TIMESTAMP: u64 = 1_000_000;
+
+    struct Token {
+        mint: Pubkey,
+        underlying_mint: Pubkey,
+        decimals: u8,
+        kind: TokenKind,
+        value_modifier: u16,
+        price: i64,
+    }
+
+    impl Token {
+        fn new(kind: TokenKind, decimals: u8, value_modifier: u16, price: i64) -> Self {
+            let mint = Pubkey::new_unique();
+            Self {
+                mint,
+                underlying_mint: mint,
+                decimals,
+                kind,
+                value_modifier,
+                price,
+            }
+        }
+
+        fn amount(&self, tokens: u64) -> PositionAmount {
+            PositionAmount {
+                token: self.mint,
+                amount: tokens * 10u64.pow(self.decimals as u32),
+            }
+        }
+
+        fn register(&self) -> SimulatedAction {
+            SimulatedAction::RegisterPosition {
+                config: PositionConfigUpdate {
+                    mint: self.mint,
+                    decimals: self.decimals,
+                    address: Pubkey::new_unique(),
+                    airspace: Pubkey::default(),
+                    adapter: Pubkey::new_unique(),
+                    kind: self.kind,
+                    value_modifier: self.value_modifier,
+                    max_staleness: 0,
+                },
+                price: PriceInfo::new_valid(0, self.price, TIMESTAMP),
+                underlying_mint: self.underlying_mint,
+            }
+        }
+
+        /// A pool note priced in the same underlying token as this one
+        fn note(&self, kind: TokenKind, value_modifier: u16) -> Self {
+            Self {
+                mint: Pubkey::new_unique(),
+                kind,
+                value_modifier,
+                ..*self
+            }
+        }
+    }
+
+    fn pct(value: i64) -> Number128 {
+        Number128::from_decimal(value, -2)
+    }
+
+    fn simulator(positions: &[(&Token, u64)]) -> HealthSimulator {
+        let mut simulator = HealthSimulator::new(
+            &MarginAccount::zeroed(),
+            TIMESTAMP,
+            RiskParameters::default(),
+        );
+        for (token, balance) in positions {
+            simulator.apply(&token.register()).unwrap();
+            simulator.increase(&token.amount(*balance)).unwrap();
+        }
+
+        simulator
+    }
+
+    #[test]
+    fn borrow_then_withdraw() {
+        let usdc = Token::new(TokenKind::Collateral, 6, 100, 1);
+        let sol_deposit = Token::new(TokenKind::AdapterCollateral, 9, 90, 20);
+        let sol_loan = Token::new(TokenKind::Claim, 9, 1000, 20);
+
+        let simulation = simulator(&[(&usdc, 1_000)])
+            .simulate(&[
+                sol_deposit.register(),
+                sol_loan.register(),
+                SimulatedAction::Borrow {
+                    loan: sol_loan.amount(100),
+                    deposit: sol_deposit.amount(100),
+                },
+                SimulatedAction::Withdraw(usdc.amount(900)),
+            ])
+            .unwrap();
+
+        assert_eq!(Number128::MAX, simulation.initial.c_ratio);
+
+        // 1000 USDC and 1800 weighted SOL, less 2000 debt and 200 required for 10x leverage
+        let borrowed = &simulation.steps[2];
+        assert_eq!(
+            Number128::from_decimal(600, 0),
+            borrowed.available_collateral
+        );
+        assert_eq!(Number128::from_decimal(4, 0), borrowed.c_ratio);
+        assert_eq!(Some(3), simulation.first_unhealthy_step());
+        assert_eq!(
+            Number128::from_decimal(-300, 0),
+            simulation.last().available_collateral
+        );
+    }
+
+    #[test]
+    fn price_shock_makes_account_unhealthy() {
+        let sol = Token::new(TokenKind::Collateral, 9, 90, 20);
+        let usdc_loan = Token::new(TokenKind::Claim, 6, 1000, 1);
+        let positions = [(&sol, 100), (&usdc_loan, 1_000)];
+
+        let health = simulator(&positions).health().unwrap();
+        assert_eq!(Number128::from_decimal(8, 0), health.c_ratio);
+        assert!(health.is_healthy());
+
+        let shock = PriceShock {
+            token: sol.mint,
+            change: -pct(50),
+        };
+        let health = simulator(&positions)
+            .with_price_shock(&shock)
+            .unwrap()
+            .health()
+            .unwrap();
+        assert_eq!(
+            Number128::from_decimal(-200, 0),
+            health.available_collateral
+        );
+        assert!(!health.is_healthy());
+    }
+
+    #[test]
+    fn rejects_invalid_actions() {
+        let usdc = Token::new(TokenKind::Collateral, 6, 100, 1);
+        let sol = Token::new(TokenKind::Collateral, 9, 90, 20);
+        let mut simulator = simulator(&[(&usdc, 100)]);
+
+        assert!(simulator
+            .apply(&SimulatedAction::Withdraw(usdc.amount(101)))
+            .is_err());
+        assert!(simulator
+            .apply(&SimulatedAction::Swap {
+                source: usdc.amount(100),
+                target: sol.amount(5),
+            })
+            .is_err());
+        assert!(simulator.apply(&usdc.register()).is_err());
+        assert!(simulator
+            .clone()
+            .with_price_shock(&PriceShock {
+                token: usdc.mint,
+                change: -pct(101),
+            })
+            .is_err());
+        assert!(simulator
+            .clone()
+            .with_price_shock(&PriceShock {
+                token: sol.mint,
+                change: -pct(10),
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn price_shock_applies_to_each_position_in_the_underlying_token() {
+        let usdc = Token::new(TokenKind::Collateral, 6, 100, 1);
+        let sol = Token::new(TokenKind::Collateral, 9, 90, 20);
+        let sol_deposit = sol.note(TokenKind::AdapterCollateral, 90);
+        let sol_loan = sol.note(TokenKind::Claim, 1000);
+        let positions = [(&usdc, 1_000), (&sol_deposit, 100), (&sol_loan, 100)];
+
+        // A rising SOL price adds 180 weighted collateral, and 200 debt with 20
+        // required to cover it
+        let shock = PriceShock {
+            token: sol.mint,
+            change: pct(10),
+        };
+        let health = simulator(&positions)
+            .with_price_shock(&shock)
+            .unwrap()
+            .health()
+            .unwrap();
+        assert_eq!(Number128::from_decimal(560, 0), health.available_collateral);
+
+        // Positions registered before the simulation are priced in their own token
+        // unless their underlying token is given
+        let account = *simulator(&positions).account();
+        let mut loaded = HealthSimulator::new(&account, TIMESTAMP, RiskParameters::default());
+        assert!(loaded.clone().with_price_shock(&shock).is_err());
+
+        loaded = loaded.with_underlying_mints(
+            [&sol_deposit, &sol_loan].map(|note| (note.mint, note.underlying_mint)),
+        );
+        assert_eq!(
+            health.available_collateral,
+            loaded
+                .with_price_shock(&shock)
+                .unwrap()
+                .health()
+                .unwrap()
+                .available_collateral
+        );
+    }
+}
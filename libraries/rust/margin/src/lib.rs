@@ -51,8 +51,12 @@
 
 #![warn(missing_docs)]
 
+/// keeper for conditional orders on margin accounts
+pub mod conditional_orders;
 /// retrieve on-chain state
 pub mod get_state;
+/// Simulate the health of margin accounts after hypothetical actions
+pub mod health;
 /// Instruction builders for programs and adapters supported by the SDK
 pub mod ix_builder;
 /// ease of use for reading a MarginAccount
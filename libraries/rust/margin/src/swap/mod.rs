@@ -25,6 +25,8 @@ use jet_simulation::solana_rpc_api::SolanaRpcClient;
 use solana_sdk::pubkey::Pubkey;
 
 pub mod openbook_swap;
+pub mod phoenix;
+pub mod router;
 pub mod saber_swap;
 pub mod spl_swap;
 pub mod whirlpool;
@@ -162,7 +162,7 @@ impl OpenBookMarket {
 }
 
 #[inline]
-fn pubkey_from_slice(slice: [u64; 4]) -> Pubkey {
+pub(super) fn pubkey_from_slice(slice: [u64; 4]) -> Pubkey {
     let address_bytes: [u8; 32] = bytemuck::cast_slice(&slice).try_into().unwrap();
     Pubkey::from(address_bytes)
 }
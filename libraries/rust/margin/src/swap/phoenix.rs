@@ -0,0 +1,57 @@
+//! The phoenix swap module gets all the markets that can be used in a swap
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use jet_margin_swap::PhoenixMarketHeader;
+use jet_simulation::solana_rpc_api::SolanaRpcClient;
+use jet_solana_client::rpc::{AccountFilter, DataSlice};
+use solana_sdk::pubkey::Pubkey;
+
+pub use jet_instructions::phoenix::PhoenixMarket;
+use jet_instructions::phoenix::PHOENIX_PROGRAM;
+
+/// The offset of the status in a phoenix market header
+const STATUS_OFFSET: usize = 8;
+
+/// Get all active markets that contain pairs of supported mints
+pub async fn get_markets(
+    rpc: &Arc<dyn SolanaRpcClient>,
+    supported_mints: &HashSet<Pubkey>,
+) -> anyhow::Result<HashMap<(Pubkey, Pubkey), PhoenixMarket>> {
+    // Only the headers are needed, the order books make market accounts large
+    let accounts = rpc
+        .get_program_accounts_with_slice(
+            &PHOENIX_PROGRAM,
+            vec![AccountFilter::Memcmp {
+                offset: STATUS_OFFSET,
+                bytes: PhoenixMarketHeader::STATUS_ACTIVE.to_le_bytes().to_vec(),
+            }],
+            DataSlice {
+                offset: 0,
+                length: std::mem::size_of::<PhoenixMarketHeader>(),
+            },
+        )
+        .await?;
+
+    let mut markets = HashMap::new();
+    for (address, account) in accounts {
+        // Seats are also owned by phoenix, but they are too small to be read as markets
+        let Some(market) = PhoenixMarket::from_account_data(address, &account.data) else {
+            continue;
+        };
+        if !supported_mints.contains(&market.base_mint)
+            || !supported_mints.contains(&market.quote_mint)
+        {
+            continue;
+        }
+
+        markets
+            .entry((market.base_mint, market.quote_mint))
+            .or_insert(market);
+    }
+
+    Ok(markets)
+}
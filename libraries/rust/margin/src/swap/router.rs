@@ -0,0 +1,1154 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! The router module finds the best route for a swap through [`jet_margin_swap`].
+//!
+//! Quotes are calculated locally from the state of each pool, so the router
+//! does not need to simulate any transactions. A route has up to 3 legs, and
+//! each leg can be split between two venues, which is the most that the
+//! `route_swap` instruction supports.
+//!
+//! The state for each pool is loaded with [`PoolCurve::load`], or for all the
+//! venues known to a client with [`SwapRouter::load`].
+
+use std::sync::Arc;
+
+use anchor_spl::dex::serum_dex::{fees::FeeTier, state::MarketState};
+use anyhow::{bail, Context};
+use jet_client::{
+    state::{dexes::DexState, AccountStates},
+    swaps::SwapStep,
+};
+use jet_instructions::orca::{derive_tick_array, start_tick_index};
+use jet_margin_pool::TokenChange;
+use jet_margin_swap::{SwapRouteIdentifier, ROUTE_SWAP_MAX_SPLIT, ROUTE_SWAP_MIN_SPLIT};
+use jet_solana_client::rpc::{SolanaRpc, SolanaRpcExtra};
+use jet_static_program_registry::orca_swap_v2::{curve::base::CurveType, state::SwapV1};
+use orca_whirlpool::{
+    math::sqrt_price_from_tick_index,
+    state::{TickArray, Whirlpool, MAX_TICK_INDEX, MIN_TICK_INDEX, TICK_ARRAY_SIZE},
+};
+use saber_client::state::SwapInfo;
+use solana_sdk::{account::Account, account_info::AccountInfo, program_pack::Pack, pubkey::Pubkey};
+
+use crate::ix_builder::{IxResult, MarginSwapRouteIxBuilder, SwapContext};
+
+use super::openbook_swap::pubkey_from_slice;
+
+/// The most legs supported in a single route
+pub const MAX_ROUTE_LEGS: usize = 3;
+
+/// The step between the split percentages tried for each leg
+const SPLIT_STEP: u8 = 10;
+
+/// A price level in an order book
+#[derive(Debug, Clone, Copy)]
+pub struct BookLevel {
+    /// The price, as quote token units per base token unit
+    pub price: f64,
+    /// The quantity available, in base token units
+    pub quantity: u64,
+}
+
+/// An initialized tick in a concentrated liquidity pool
+#[derive(Debug, Clone, Copy)]
+pub struct LiquidityTick {
+    /// The square root of the price at the tick, as a Q64.64
+    pub sqrt_price_x64: u128,
+    /// The change in liquidity when the price moves up across the tick
+    pub liquidity_net: i128,
+}
+
+/// The state of a pool needed to quote swaps locally
+#[derive(Debug, Clone)]
+pub enum PoolCurve {
+    /// A constant product pool, such as spl-token-swap or orca v2
+    ConstantProduct {
+        reserve_a: u64,
+        reserve_b: u64,
+        fee_bps: u16,
+    },
+
+    /// A saber stable swap pool
+    StableSwap {
+        amp: u64,
+        reserve_a: u64,
+        reserve_b: u64,
+        fee_bps: u16,
+    },
+
+    /// An orca whirlpool. Quotes cross the initialized ticks, but only within
+    /// the tick arrays passed to the swap, so larger swaps can't be quoted.
+    ConcentratedLiquidity {
+        sqrt_price_x64: u128,
+        liquidity: u128,
+        /// The fee rate, in hundredths of a basis point
+        fee_rate: u16,
+        /// The initialized ticks in the tick arrays used for swaps, by price
+        ticks: Vec<LiquidityTick>,
+        /// The lowest price a swap can reach with its tick arrays
+        min_sqrt_price_x64: u128,
+        /// The highest price a swap can reach with its tick arrays
+        max_sqrt_price_x64: u128,
+    },
+
+    /// An openbook market, where token A is the base token
+    OrderBook {
+        bids: Vec<BookLevel>,
+        asks: Vec<BookLevel>,
+        fee_bps: u16,
+    },
+}
+
+impl PoolCurve {
+    /// Calculate the output of swapping an amount of tokens, or `None` if the
+    /// pool can't fill the swap
+    pub fn quote(&self, a_to_b: bool, amount_in: u64) -> Option<u64> {
+        let amount_out = match self {
+            PoolCurve::ConstantProduct {
+                reserve_a,
+                reserve_b,
+                fee_bps,
+            } => {
+                let (reserve_in, reserve_out) = ordered(a_to_b, *reserve_a, *reserve_b);
+                constant_product_quote(reserve_in, reserve_out, less_bps(amount_in, *fee_bps))?
+            }
+            PoolCurve::StableSwap {
+                amp,
+                reserve_a,
+                reserve_b,
+                fee_bps,
+            } => {
+                let (reserve_in, reserve_out) = ordered(a_to_b, *reserve_a, *reserve_b);
+                let amount_out = stable_swap_quote(*amp, reserve_in, reserve_out, amount_in)?;
+                less_bps(amount_out, *fee_bps)
+            }
+            PoolCurve::ConcentratedLiquidity {
+                sqrt_price_x64,
+                liquidity,
+                fee_rate,
+                ticks,
+                min_sqrt_price_x64,
+                max_sqrt_price_x64,
+            } => {
+                let fee = amount_in as u128 * *fee_rate as u128 / 1_000_000;
+                let amount_in = amount_in - fee as u64;
+                let limit = match a_to_b {
+                    true => *min_sqrt_price_x64,
+                    false => *max_sqrt_price_x64,
+                };
+
+                concentrated_liquidity_quote(
+                    *sqrt_price_x64,
+                    *liquidity,
+                    ticks,
+                    limit,
+                    a_to_b,
+                    amount_in,
+                )?
+            }
+            PoolCurve::OrderBook {
+                bids,
+                asks,
+                fee_bps,
+            } => match a_to_b {
+                // Selling the base token, with the fee taken from the quote received
+                true => less_bps(sell_into_book(bids, amount_in)?, *fee_bps),
+                // Buying the base token, with the fee taken from the quote spent
+                false => buy_from_book(asks, less_bps(amount_in, *fee_bps))?,
+            },
+        };
+
+        (amount_out > 0).then_some(amount_out)
+    }
+}
+
+fn ordered(a_to_b: bool, reserve_a: u64, reserve_b: u64) -> (u64, u64) {
+    match a_to_b {
+        true => (reserve_a, reserve_b),
+        false => (reserve_b, reserve_a),
+    }
+}
+
+fn less_bps(amount: u64, bps: u16) -> u64 {
+    let bps = std::cmp::min(bps, 10_000);
+
+    (amount as u128 * (10_000 - bps as u128) / 10_000) as u64
+}
+
+fn constant_product_quote(reserve_in: u64, reserve_out: u64, amount_in: u64) -> Option<u64> {
+    let numerator = (reserve_out as u128).checked_mul(amount_in as u128)?;
+    let denominator = (reserve_in as u128).checked_add(amount_in as u128)?;
+
+    u64::try_from(numerator.checked_div(denominator)?).ok()
+}
+
+/// Calculate the stable swap invariant for two tokens
+fn stable_swap_invariant(amp: u64, x: u128, y: u128) -> Option<u128> {
+    let sum = x.checked_add(y)?;
+    if sum == 0 {
+        return Some(0);
+    }
+
+    let amp_n = amp as u128 * 4;
+    let mut d = sum;
+
+    for _ in 0..32 {
+        let mut d_product = d;
+        d_product = d_product.checked_mul(d)?.checked_div(x.checked_mul(2)?)?;
+        d_product = d_product.checked_mul(d)?.checked_div(y.checked_mul(2)?)?;
+
+        let previous = d;
+        let numerator = amp_n
+            .checked_mul(sum)?
+            .checked_add(d_product.checked_mul(2)?)?
+            .checked_mul(d)?;
+        let denominator = amp_n
+            .checked_sub(1)?
+            .checked_mul(d)?
+            .checked_add(d_product.checked_mul(3)?)?;
+        d = numerator.checked_div(denominator)?;
+
+        if d.abs_diff(previous) <= 1 {
+            break;
+        }
+    }
+
+    Some(d)
+}
+
+/// Calculate the balance of the other token to keep the stable swap invariant
+fn stable_swap_balance(amp: u64, x: u128, d: u128) -> Option<u128> {
+    let amp_n = amp as u128 * 4;
+
+    let c = d
+        .checked_mul(d)?
+        .checked_div(x.checked_mul(2)?)?
+        .checked_mul(d)?
+        .checked_div(amp_n.checked_mul(2)?)?;
+    let b = x.checked_add(d.checked_div(amp_n)?)?;
+    let mut y = d;
+
+    for _ in 0..32 {
+        let previous = y;
+        y = y
+            .checked_mul(y)?
+            .checked_add(c)?
+            .checked_div(y.checked_mul(2)?.checked_add(b)?.checked_sub(d)?)?;
+
+        if y.abs_diff(previous) <= 1 {
+            break;
+        }
+    }
+
+    Some(y)
+}
+
+fn stable_swap_quote(amp: u64, reserve_in: u64, reserve_out: u64, amount_in: u64) -> Option<u64> {
+    if amp == 0 || reserve_in == 0 || reserve_out == 0 {
+        return None;
+    }
+
+    let d = stable_swap_invariant(amp, reserve_in as u128, reserve_out as u128)?;
+    let new_reserve_in = (reserve_in as u128).checked_add(amount_in as u128)?;
+    let new_reserve_out = stable_swap_balance(amp, new_reserve_in, d)?;
+
+    u64::try_from((reserve_out as u128).checked_sub(new_reserve_out)?).ok()
+}
+
+/// Swap through the liquidity of each tick range in turn, crossing the
+/// initialized ticks, until the input is used up. Returns `None` if the swap
+/// would move the price past the limit of the tick arrays.
+fn concentrated_liquidity_quote(
+    sqrt_price_x64: u128,
+    liquidity: u128,
+    ticks: &[LiquidityTick],
+    limit_sqrt_price_x64: u128,
+    a_to_b: bool,
+    amount_in: u64,
+) -> Option<u64> {
+    if sqrt_price_x64 == 0 {
+        return None;
+    }
+
+    let to_f64 = |sqrt_price_x64: u128| sqrt_price_x64 as f64 / 2f64.powi(64);
+    let mut sqrt_price = to_f64(sqrt_price_x64);
+    let mut liquidity = liquidity as f64;
+    let mut remaining = amount_in as f64;
+    let mut amount_out = 0.0;
+
+    // The ticks to cross, in the order the price reaches them
+    let mut next_ticks: Box<dyn Iterator<Item = &LiquidityTick>> = match a_to_b {
+        true => Box::new(
+            ticks
+                .iter()
+                .rev()
+                .filter(move |t| t.sqrt_price_x64 <= sqrt_price_x64),
+        ),
+        false => Box::new(
+            ticks
+                .iter()
+                .filter(move |t| t.sqrt_price_x64 > sqrt_price_x64),
+        ),
+    };
+
+    loop {
+        let next = next_ticks.next();
+        let target = to_f64(next.map_or(limit_sqrt_price_x64, |t| t.sqrt_price_x64));
+
+        // The price of A in B decreases as A is added
+        let (needed, filled) = match a_to_b {
+            true => (
+                liquidity * (1.0 / target - 1.0 / sqrt_price),
+                liquidity * (sqrt_price - target),
+            ),
+            false => (
+                liquidity * (target - sqrt_price),
+                liquidity * (1.0 / sqrt_price - 1.0 / target),
+            ),
+        };
+
+        if liquidity > 0.0 && remaining <= needed {
+            let new_sqrt_price = match a_to_b {
+                true => liquidity * sqrt_price / (liquidity + remaining * sqrt_price),
+                false => sqrt_price + remaining / liquidity,
+            };
+            amount_out += match a_to_b {
+                true => liquidity * (sqrt_price - new_sqrt_price),
+                false => liquidity * (1.0 / sqrt_price - 1.0 / new_sqrt_price),
+            };
+
+            return Some(amount_out.max(0.0).floor() as u64);
+        }
+
+        // The price can't move past the tick arrays passed to the swap
+        let tick = next?;
+
+        remaining -= needed.max(0.0);
+        amount_out += filled.max(0.0);
+        sqrt_price = target;
+        liquidity = match a_to_b {
+            true => liquidity - tick.liquidity_net as f64,
+            false => liquidity + tick.liquidity_net as f64,
+        }
+        .max(0.0);
+    }
+}
+
+/// Sell base tokens into the bids, best price first
+fn sell_into_book(bids: &[BookLevel], amount_in: u64) -> Option<u64> {
+    let mut remaining = amount_in;
+    let mut amount_out = 0.0;
+
+    for level in bids {
+        let filled = remaining.min(level.quantity);
+        amount_out += filled as f64 * level.price;
+        remaining -= filled;
+
+        if remaining == 0 {
+            return Some(amount_out.floor() as u64);
+        }
+    }
+
+    None
+}
+
+/// Buy base tokens from the asks with quote tokens, best price first
+fn buy_from_book(asks: &[BookLevel], amount_in: u64) -> Option<u64> {
+    let mut remaining = amount_in as f64;
+    let mut amount_out = 0u64;
+
+    for level in asks {
+        let affordable = (remaining / level.price).floor() as u64;
+        let filled = affordable.min(level.quantity);
+        amount_out += filled;
+        remaining -= filled as f64 * level.price;
+
+        if filled < level.quantity {
+            return Some(amount_out);
+        }
+    }
+
+    None
+}
+
+impl PoolCurve {
+    /// Load the state of a pool from the network, for the venues that can be
+    /// quoted locally. Phoenix markets are not supported yet.
+    ///
+    /// # Params
+    ///
+    /// `rpc` - The network to load the pool from
+    /// `address` - The address of the pool or market
+    /// `dex` - The swap accounts for the pool, such as those loaded by [`jet_client`]
+    pub async fn load(
+        rpc: &(dyn SolanaRpc + 'static),
+        address: &Pubkey,
+        dex: &DexState,
+    ) -> anyhow::Result<Self> {
+        match dex.swap_a_to_b_accounts.route_type() {
+            SwapRouteIdentifier::Spl => Self::load_spl_swap(rpc, address).await,
+            SwapRouteIdentifier::SaberStable => Self::load_saber_swap(rpc, address).await,
+            SwapRouteIdentifier::Whirlpool => Self::load_whirlpool(rpc, address).await,
+            SwapRouteIdentifier::OpenBook => Self::load_openbook(rpc, address, &dex.program).await,
+            other => bail!("swaps through {other:?} can't be quoted locally"),
+        }
+    }
+
+    /// Load a constant product pool from an spl-token-swap compatible program
+    pub async fn load_spl_swap(
+        rpc: &(dyn SolanaRpc + 'static),
+        address: &Pubkey,
+    ) -> anyhow::Result<Self> {
+        let account = rpc
+            .get_account(address)
+            .await?
+            .with_context(|| format!("swap pool {address} not found"))?;
+        let swap = SwapV1::unpack(&account.data[1..])?;
+
+        if swap.swap_curve.curve_type != CurveType::ConstantProduct {
+            bail!("swap pool {address} is not a constant product pool");
+        }
+
+        let fees = &swap.fees;
+        let fee_bps = fraction_bps(fees.trade_fee_numerator, fees.trade_fee_denominator)
+            + fraction_bps(
+                fees.owner_trade_fee_numerator,
+                fees.owner_trade_fee_denominator,
+            );
+
+        Ok(PoolCurve::ConstantProduct {
+            reserve_a: rpc.get_token_account(&swap.token_a).await?.amount,
+            reserve_b: rpc.get_token_account(&swap.token_b).await?.amount,
+            fee_bps,
+        })
+    }
+
+    /// Load a saber stable swap pool
+    ///
+    /// The target amplification is used, so quotes are less accurate while
+    /// the pool's amplification is ramping.
+    pub async fn load_saber_swap(
+        rpc: &(dyn SolanaRpc + 'static),
+        address: &Pubkey,
+    ) -> anyhow::Result<Self> {
+        let account = rpc
+            .get_account(address)
+            .await?
+            .with_context(|| format!("swap pool {address} not found"))?;
+        let swap = SwapInfo::unpack(&account.data)?;
+
+        if swap.is_paused {
+            bail!("swap pool {address} is paused");
+        }
+
+        Ok(PoolCurve::StableSwap {
+            amp: swap.target_amp_factor,
+            reserve_a: rpc.get_token_account(&swap.token_a.reserves).await?.amount,
+            reserve_b: rpc.get_token_account(&swap.token_b.reserves).await?.amount,
+            fee_bps: fraction_bps(
+                swap.fees.trade_fee_numerator,
+                swap.fees.trade_fee_denominator,
+            ),
+        })
+    }
+
+    /// Load an orca whirlpool, with the initialized ticks in the tick arrays
+    /// that swaps in either direction use
+    pub async fn load_whirlpool(
+        rpc: &(dyn SolanaRpc + 'static),
+        address: &Pubkey,
+    ) -> anyhow::Result<Self> {
+        let whirlpool = rpc.get_anchor_account::<Whirlpool>(address).await?;
+        let spacing = whirlpool.tick_spacing;
+
+        // The same tick arrays as the swap accounts, from the lowest price to the highest
+        let start_indexes = (-2..=2)
+            .map(|offset| start_tick_index(whirlpool.tick_current_index, spacing, offset))
+            .collect::<Vec<_>>();
+        let addresses = start_indexes
+            .iter()
+            .map(|start| derive_tick_array(address, *start, spacing))
+            .collect::<Vec<_>>();
+        let size = std::mem::size_of::<TickArray>();
+        let tick_arrays = rpc
+            .get_accounts_all(&addresses)
+            .await?
+            .into_iter()
+            .map(|account| {
+                account
+                    .filter(|account| account.data.len() >= 8 + size)
+                    .map(|account| *bytemuck::from_bytes::<TickArray>(&account.data[8..8 + size]))
+            })
+            .collect::<Vec<_>>();
+
+        // Swaps can only reach the arrays that are next to the current one
+        let current = 2;
+        let lowest = (0..=current)
+            .rev()
+            .take_while(|i| tick_arrays[*i].is_some())
+            .last()
+            .context("the current tick array is not initialized")?;
+        let highest = (current..tick_arrays.len())
+            .take_while(|i| tick_arrays[*i].is_some())
+            .last()
+            .unwrap();
+        let array_width = spacing as i32 * TICK_ARRAY_SIZE;
+
+        let mut ticks = vec![];
+        let reachable = start_indexes.iter().zip(&tick_arrays);
+        for (start, tick_array) in reachable.take(highest + 1).skip(lowest) {
+            // Copied out of the packed account before being borrowed
+            let array_ticks = tick_array.as_ref().unwrap().ticks;
+
+            for (i, tick) in array_ticks.iter().enumerate() {
+                if tick.initialized {
+                    ticks.push(LiquidityTick {
+                        sqrt_price_x64: sqrt_price_from_tick_index(
+                            start + i as i32 * spacing as i32,
+                        ),
+                        liquidity_net: tick.liquidity_net,
+                    });
+                }
+            }
+        }
+
+        Ok(PoolCurve::ConcentratedLiquidity {
+            sqrt_price_x64: whirlpool.sqrt_price,
+            liquidity: whirlpool.liquidity,
+            fee_rate: whirlpool.fee_rate,
+            ticks,
+            min_sqrt_price_x64: sqrt_price_from_tick_index(
+                start_indexes[lowest].max(MIN_TICK_INDEX),
+            ),
+            max_sqrt_price_x64: sqrt_price_from_tick_index(
+                (start_indexes[highest] + array_width).min(MAX_TICK_INDEX),
+            ),
+        })
+    }
+
+    /// Load the order book for an openbook market
+    pub async fn load_openbook(
+        rpc: &(dyn SolanaRpc + 'static),
+        address: &Pubkey,
+        program: &Pubkey,
+    ) -> anyhow::Result<Self> {
+        let mut market_account = rpc
+            .get_account(address)
+            .await?
+            .with_context(|| format!("market {address} not found"))?;
+        let market_info = AccountInfo::from((address, &mut market_account));
+        let market = MarketState::load(&market_info, program)
+            .map_err(|e| anyhow::anyhow!("invalid market {address}: {e:?}"))?;
+
+        // Convert from lots to token units
+        let base_lot_size = market.coin_lot_size;
+        let lot_price = market.pc_lot_size as f64 / base_lot_size as f64;
+        let bids_address = pubkey_from_slice(market.bids);
+        let asks_address = pubkey_from_slice(market.asks);
+
+        let levels = |book: &Pubkey, mut account: Account, bids: bool| -> anyhow::Result<_> {
+            let info = AccountInfo::from((book, &mut account));
+            let mut slab = match bids {
+                true => market.load_bids_mut(&info),
+                false => market.load_asks_mut(&info),
+            }
+            .map_err(|e| anyhow::anyhow!("invalid order book {book}: {e:?}"))?;
+
+            let mut levels = vec![];
+            while let Some(order) = match bids {
+                true => slab.remove_max(),
+                false => slab.remove_min(),
+            } {
+                levels.push(BookLevel {
+                    price: u64::from(order.price()) as f64 * lot_price,
+                    quantity: order.quantity() * base_lot_size,
+                });
+            }
+
+            Ok(levels)
+        };
+
+        let mut books = rpc.get_accounts_all(&[bids_address, asks_address]).await?;
+        let asks = books.pop().flatten().context("market asks not found")?;
+        let bids = books.pop().flatten().context("market bids not found")?;
+
+        Ok(PoolCurve::OrderBook {
+            bids: levels(&bids_address, bids, true)?,
+            asks: levels(&asks_address, asks, false)?,
+            // The fee on 10,000 units of the quote token is the fee in basis points
+            fee_bps: FeeTier::Base.taker_fee(10_000) as u16,
+        })
+    }
+}
+
+fn fraction_bps(numerator: u64, denominator: u64) -> u16 {
+    match denominator {
+        0 => 0,
+        _ => (numerator as u128 * 10_000 / denominator as u128) as u16,
+    }
+}
+
+/// A pool that can be used in a route
+#[derive(Clone)]
+pub struct RoutePool {
+    /// The address of the pool or market
+    pub address: Pubkey,
+    /// The accounts for swapping with the pool
+    pub dex: Arc<DexState>,
+    /// The state used to quote swaps with the pool
+    pub curve: PoolCurve,
+}
+
+impl RoutePool {
+    fn quote(&self, from_token: &Pubkey, amount_in: u64) -> Option<u64> {
+        self.curve.quote(*from_token == self.dex.token_a, amount_in)
+    }
+
+    fn connects(&self, from_token: &Pubkey, to_token: &Pubkey) -> bool {
+        (self.dex.token_a == *from_token && self.dex.token_b == *to_token)
+            || (self.dex.token_b == *from_token && self.dex.token_a == *to_token)
+    }
+
+    fn step(&self, from_token: Pubkey, to_token: Pubkey) -> SwapStep {
+        SwapStep {
+            from_token,
+            to_token,
+            program: self.dex.program,
+            swap_pool: self.address,
+        }
+    }
+}
+
+/// A leg of a route, which swaps through one or two venues
+#[derive(Clone)]
+pub struct RouteLeg {
+    /// The pools used for the leg, with the second one only set for a split
+    pub pools: Vec<RoutePool>,
+    /// The percentage of the input swapped through the first pool if there's
+    /// a split, otherwise zero
+    pub split: u8,
+    /// The token swapped from
+    pub from_token: Pubkey,
+    /// The token swapped to
+    pub to_token: Pubkey,
+    /// The amount of tokens into the leg
+    pub amount_in: u64,
+    /// The expected amount of tokens out of the leg
+    pub amount_out: u64,
+}
+
+impl RouteLeg {
+    /// The steps for each venue in the leg
+    pub fn steps(&self) -> Vec<SwapStep> {
+        self.pools
+            .iter()
+            .map(|pool| pool.step(self.from_token, self.to_token))
+            .collect()
+    }
+}
+
+/// The best route found for a swap
+#[derive(Clone)]
+pub struct SwapRoute {
+    /// The legs of the route, in order
+    pub legs: Vec<RouteLeg>,
+    /// The amount of tokens to swap
+    pub amount_in: u64,
+    /// The expected amount of tokens received
+    pub expected_amount_out: u64,
+    /// The least amount of tokens to accept, after allowing for slippage
+    pub minimum_amount_out: u64,
+}
+
+impl SwapRoute {
+    /// The steps of the route, with the split percentage to use for each of them
+    pub fn steps(&self) -> Vec<(SwapStep, u8)> {
+        self.legs
+            .iter()
+            .flat_map(|leg| {
+                leg.steps()
+                    .into_iter()
+                    .enumerate()
+                    .map(move |(i, step)| (step, if i == 0 { leg.split } else { 0 }))
+            })
+            .collect()
+    }
+
+    /// Create a finalized instruction builder for the route
+    ///
+    /// If `max_price_deviation` (bps) is set, the swap is also checked against the
+    /// prices of the margin account's positions.
+    pub fn builder(
+        &self,
+        swap_context: SwapContext,
+        margin_account: Pubkey,
+        max_price_deviation: Option<u16>,
+    ) -> IxResult<MarginSwapRouteIxBuilder> {
+        let mut builder = MarginSwapRouteIxBuilder::new(
+            swap_context,
+            margin_account,
+            self.legs[0].from_token,
+            self.legs[self.legs.len() - 1].to_token,
+            TokenChange::shift(self.amount_in),
+            self.minimum_amount_out,
+        );
+
+        for leg in &self.legs {
+            for (i, pool) in leg.pools.iter().enumerate() {
+                let accounts = match pool.dex.token_a == leg.from_token {
+                    true => &pool.dex.swap_a_to_b_accounts,
+                    false => &pool.dex.swap_b_to_a_accounts,
+                };
+                let split = if i == 0 { leg.split } else { 0 };
+
+                builder.add_swap_leg(accounts.as_ref(), split)?;
+            }
+        }
+
+        if let Some(bps) = max_price_deviation {
+            builder.set_max_price_deviation(bps)?;
+        }
+        builder.finalize()?;
+
+        Ok(builder)
+    }
+}
+
+/// Finds the best route for a swap, from a set of pools with known state
+#[derive(Default, Clone)]
+pub struct SwapRouter {
+    pools: Vec<RoutePool>,
+}
+
+impl SwapRouter {
+    /// Create a router without any pools
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a router with the venues known to a client, loading the state
+    /// of each one from the network. Venues that can't be quoted locally are
+    /// left out.
+    pub async fn load(
+        rpc: &(dyn SolanaRpc + 'static),
+        states: &AccountStates,
+    ) -> anyhow::Result<Self> {
+        let mut router = Self::new();
+
+        for (address, dex) in states.get_all::<DexState>() {
+            match PoolCurve::load(rpc, &address, &dex).await {
+                Ok(curve) => router.add_pool(address, dex, curve),
+                Err(e) => tracing::debug!("not routing through {address}: {e:?}"),
+            }
+        }
+
+        Ok(router)
+    }
+
+    /// Reload the state of every pool in the router, leaving out any that can no
+    /// longer be quoted
+    pub async fn refresh(&mut self, rpc: &(dyn SolanaRpc + 'static)) {
+        let mut pools = Vec::with_capacity(self.pools.len());
+
+        for pool in self.pools.drain(..) {
+            match PoolCurve::load(rpc, &pool.address, &pool.dex).await {
+                Ok(curve) => pools.push(RoutePool { curve, ..pool }),
+                Err(e) => tracing::debug!("not routing through {}: {e:?}", pool.address),
+            }
+        }
+
+        self.pools = pools;
+    }
+
+    /// Add a pool that can be used for routing
+    pub fn add_pool(&mut self, address: Pubkey, dex: Arc<DexState>, curve: PoolCurve) {
+        self.pools.push(RoutePool {
+            address,
+            dex,
+            curve,
+        });
+    }
+
+    /// Load the state of a pool, and add it to be used for routing
+    pub async fn add_dex(
+        &mut self,
+        rpc: &(dyn SolanaRpc + 'static),
+        address: Pubkey,
+        dex: Arc<DexState>,
+    ) -> anyhow::Result<()> {
+        let curve = PoolCurve::load(rpc, &address, &dex).await?;
+        self.add_pool(address, dex, curve);
+
+        Ok(())
+    }
+
+    /// Find the route with the best output for swapping between two tokens
+    ///
+    /// # Params
+    ///
+    /// `from_token` - The token to swap from
+    /// `to_token` - The token to swap to
+    /// `amount_in` - The amount of tokens to swap
+    /// `slippage_bps` - The slippage allowed from the expected output, used to
+    ///                  calculate the minimum amount out
+    pub fn find_route(
+        &self,
+        from_token: Pubkey,
+        to_token: Pubkey,
+        amount_in: u64,
+        slippage_bps: u16,
+    ) -> Option<SwapRoute> {
+        let mut best: Option<Vec<RouteLeg>> = None;
+
+        for path in self.token_paths(from_token, to_token) {
+            let Some(legs) = self.quote_path(&path, amount_in) else {
+                continue;
+            };
+
+            let amount_out = legs.last().unwrap().amount_out;
+            if best
+                .as_ref()
+                .map_or(true, |b| b.last().unwrap().amount_out < amount_out)
+            {
+                best = Some(legs);
+            }
+        }
+
+        best.map(|legs| {
+            let expected_amount_out = legs.last().unwrap().amount_out;
+
+            SwapRoute {
+                legs,
+                amount_in,
+                expected_amount_out,
+                minimum_amount_out: less_bps(expected_amount_out, slippage_bps),
+            }
+        })
+    }
+
+    /// Find all the sequences of tokens, without repeats, that connect two
+    /// tokens in at most the maximum number of legs
+    fn token_paths(&self, from_token: Pubkey, to_token: Pubkey) -> Vec<Vec<Pubkey>> {
+        let mut paths = vec![];
+        let mut stack = vec![vec![from_token]];
+
+        while let Some(path) = stack.pop() {
+            let current = *path.last().unwrap();
+
+            if current == to_token {
+                paths.push(path);
+                continue;
+            }
+            if path.len() > MAX_ROUTE_LEGS {
+                continue;
+            }
+
+            let mut next_tokens = self
+                .pools
+                .iter()
+                .filter_map(|pool| match current {
+                    t if t == pool.dex.token_a => Some(pool.dex.token_b),
+                    t if t == pool.dex.token_b => Some(pool.dex.token_a),
+                    _ => None,
+                })
+                .filter(|t| !path.contains(t))
+                .collect::<Vec<_>>();
+            next_tokens.sort();
+            next_tokens.dedup();
+
+            for token in next_tokens {
+                let mut next = path.clone();
+                next.push(token);
+                stack.push(next);
+            }
+        }
+
+        paths
+    }
+
+    /// Find the best legs along a sequence of tokens
+    fn quote_path(&self, path: &[Pubkey], amount_in: u64) -> Option<Vec<RouteLeg>> {
+        let mut legs = Vec::with_capacity(path.len() - 1);
+        let mut amount = amount_in;
+
+        for pair in path.windows(2) {
+            let leg = self.best_leg(pair[0], pair[1], amount)?;
+            amount = leg.amount_out;
+            legs.push(leg);
+        }
+
+        Some(legs)
+    }
+
+    /// Find the best single venue, or split between two venues, for a leg
+    fn best_leg(&self, from_token: Pubkey, to_token: Pubkey, amount_in: u64) -> Option<RouteLeg> {
+        let pools = self
+            .pools
+            .iter()
+            .filter(|p| p.connects(&from_token, &to_token))
+            .collect::<Vec<_>>();
+
+        let mut best: Option<(Vec<&RoutePool>, u8, u64)> = None;
+        let mut consider = |venues: Vec<&RoutePool>, split: u8, amount_out: Option<u64>| {
+            if let Some(amount_out) = amount_out {
+                if best.as_ref().map_or(true, |(_, _, b)| *b < amount_out) {
+                    best = Some((venues, split, amount_out));
+                }
+            }
+        };
+
+        for (i, first) in pools.iter().enumerate() {
+            consider(vec![*first], 0, first.quote(&from_token, amount_in));
+
+            for second in &pools[i + 1..] {
+                let mut split = ROUTE_SWAP_MIN_SPLIT;
+                while split <= ROUTE_SWAP_MAX_SPLIT {
+                    let first_in = (amount_in as u128 * split as u128 / 100) as u64;
+                    let amount_out = first
+                        .quote(&from_token, first_in)
+                        .zip(second.quote(&from_token, amount_in - first_in))
+                        .and_then(|(a, b)| a.checked_add(b));
+
+                    consider(vec![*first, *second], split, amount_out);
+                    split += SPLIT_STEP;
+                }
+            }
+        }
+
+        best.map(|(venues, split, amount_out)| RouteLeg {
+            pools: venues.into_iter().cloned().collect(),
+            split,
+            from_token,
+            to_token,
+            amount_in,
+            amount_out,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::swap::spl_swap::SplSwapPool;
+
+    fn dex(token_a: Pubkey, token_b: Pubkey) -> Arc<DexState> {
+        let accounts = Arc::new(SplSwapPool {
+            pool: Pubkey::new_unique(),
+            pool_authority: Pubkey::default(),
+            pool_mint: Pubkey::default(),
+            mint_a: token_a,
+            mint_b: token_b,
+            token_a: Pubkey::default(),
+            token_b: Pubkey::default(),
+            fee_account: Pubkey::default(),
+            program: Pubkey::default(),
+        });
+
+        Arc::new(DexState {
+            program: Pubkey::default(),
+            token_a,
+            token_b,
+            swap_a_to_b_accounts: accounts.clone(),
+            swap_b_to_a_accounts: accounts,
+        })
+    }
+
+    fn constant_product(reserve_a: u64, reserve_b: u64) -> PoolCurve {
+        PoolCurve::ConstantProduct {
+            reserve_a,
+            reserve_b,
+            fee_bps: 30,
+        }
+    }
+
+    #[test]
+    fn constant_product_quotes_include_fees() {
+        let curve = constant_product(1_000_000, 2_000_000);
+
+        assert_eq!(Some(1_992), curve.quote(true, 1_000));
+        assert_eq!(Some(498), curve.quote(false, 1_000));
+    }
+
+    #[test]
+    fn stable_swap_has_low_slippage_when_balanced() {
+        let curve = PoolCurve::StableSwap {
+            amp: 100,
+            reserve_a: 1_000_000_000,
+            reserve_b: 1_000_000_000,
+            fee_bps: 0,
+        };
+
+        let amount_out = curve.quote(true, 1_000_000).unwrap();
+        assert!(amount_out > 999_000 && amount_out <= 1_000_000);
+    }
+
+    #[test]
+    fn order_book_fills_across_levels() {
+        let curve = PoolCurve::OrderBook {
+            bids: vec![
+                BookLevel {
+                    price: 2.0,
+                    quantity: 100,
+                },
+                BookLevel {
+                    price: 1.0,
+                    quantity: 100,
+                },
+            ],
+            asks: vec![BookLevel {
+                price: 4.0,
+                quantity: 100,
+            }],
+            fee_bps: 0,
+        };
+
+        assert_eq!(Some(250), curve.quote(true, 150));
+        assert_eq!(None, curve.quote(true, 250));
+        assert_eq!(Some(25), curve.quote(false, 100));
+    }
+
+    fn sqrt_price_x64(price: f64) -> u128 {
+        (price.sqrt() * 2f64.powi(64)) as u128
+    }
+
+    #[test]
+    fn concentrated_liquidity_crosses_ticks() {
+        let curve = |ticks| PoolCurve::ConcentratedLiquidity {
+            sqrt_price_x64: sqrt_price_x64(1.0),
+            liquidity: 1_000_000_000,
+            fee_rate: 0,
+            ticks,
+            min_sqrt_price_x64: sqrt_price_x64(0.5),
+            max_sqrt_price_x64: sqrt_price_x64(2.0),
+        };
+
+        // the liquidity doubles below the tick, and is gone above the other one
+        let with_ticks = curve(vec![
+            LiquidityTick {
+                sqrt_price_x64: sqrt_price_x64(0.99),
+                liquidity_net: -1_000_000_000,
+            },
+            LiquidityTick {
+                sqrt_price_x64: sqrt_price_x64(1.01),
+                liquidity_net: -1_000_000_000,
+            },
+        ]);
+        let without_ticks = curve(vec![]);
+
+        // small swaps stay within the current tick range
+        assert_eq!(
+            without_ticks.quote(true, 1_000_000),
+            with_ticks.quote(true, 1_000_000)
+        );
+
+        // larger swaps get more from the deeper liquidity below the tick
+        let amount_in = 20_000_000;
+        assert!(with_ticks.quote(true, amount_in) > without_ticks.quote(true, amount_in));
+
+        // and can't buy more than the liquidity up to the empty range
+        let available = with_ticks.quote(false, 4_000_000).unwrap();
+        assert!(available < 5_000_000);
+        assert_eq!(None, with_ticks.quote(false, 10_000_000));
+
+        // nor move the price past the tick arrays
+        assert_eq!(None, without_ticks.quote(true, 1_000_000_000));
+    }
+
+    #[test]
+    fn fees_over_the_whole_amount_are_clamped() {
+        assert_eq!(0, less_bps(1_000, 20_000));
+        assert_eq!(900, less_bps(1_000, 1_000));
+    }
+
+    #[test]
+    fn router_splits_between_equal_pools() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut router = SwapRouter::new();
+        router.add_pool(
+            Pubkey::new_unique(),
+            dex(a, b),
+            constant_product(10_000, 10_000),
+        );
+        router.add_pool(
+            Pubkey::new_unique(),
+            dex(a, b),
+            constant_product(10_000, 10_000),
+        );
+
+        let route = router.find_route(a, b, 5_000, 100).unwrap();
+
+        assert_eq!(1, route.legs.len());
+        assert_eq!(50, route.legs[0].split);
+        assert_eq!(2, route.steps().len());
+        assert!(route.minimum_amount_out < route.expected_amount_out);
+    }
+
+    #[test]
+    fn router_splits_amounts_near_the_limit() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let reserve = u64::MAX / 2;
+        let mut router = SwapRouter::new();
+        router.add_pool(
+            Pubkey::new_unique(),
+            dex(a, b),
+            constant_product(reserve, reserve),
+        );
+        router.add_pool(
+            Pubkey::new_unique(),
+            dex(a, b),
+            constant_product(reserve, reserve),
+        );
+
+        let route = router.find_route(a, b, u64::MAX / 4, 0).unwrap();
+
+        assert_eq!(1, route.legs.len());
+        assert_eq!(50, route.legs[0].split);
+    }
+
+    #[test]
+    fn router_uses_intermediate_token_when_better() {
+        let (a, b, c) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut router = SwapRouter::new();
+        router.add_pool(
+            Pubkey::new_unique(),
+            dex(a, b),
+            constant_product(1_000, 1_000),
+        );
+        router.add_pool(
+            Pubkey::new_unique(),
+            dex(a, c),
+            constant_product(1_000_000, 1_000_000),
+        );
+        router.add_pool(
+            Pubkey::new_unique(),
+            dex(c, b),
+            constant_product(1_000_000, 1_000_000),
+        );
+
+        let route = router.find_route(a, b, 1_000, 0).unwrap();
+
+        assert_eq!(2, route.legs.len());
+        assert_eq!(c, route.legs[0].to_token);
+        assert_eq!(route.expected_amount_out, route.minimum_amount_out);
+    }
+}
@@ -25,6 +25,7 @@ use crate::{
     },
     solana::transaction::TransactionBuilder,
 };
+use jet_airspace::state::PermitTier;
 use jet_margin::{TokenAdmin, TokenConfigUpdate, TokenKind, TokenOracle};
 
 /// Utility for constructing transactions for administrative functions on protocol
@@ -61,6 +62,26 @@ impl AirspaceAdmin {
         vec![self.as_ix.permit_create(user)].into()
     }
 
+    /// Create a permit for a user, which expires and limits what the user may do in this airspace
+    pub fn issue_limited_user_permit(
+        &self,
+        user: Pubkey,
+        expires_at: i64,
+        tier: PermitTier,
+    ) -> TransactionBuilder {
+        vec![self.as_ix.permit_create_limited(user, expires_at, tier)].into()
+    }
+
+    /// Extend the expiry of a previously issued permit for a user
+    pub fn renew_user_permit(
+        &self,
+        user: Pubkey,
+        issuer: Pubkey,
+        expires_at: i64,
+    ) -> TransactionBuilder {
+        vec![self.as_ix.permit_renew(user, issuer, expires_at)].into()
+    }
+
     /// Revoke a previously issued permit for a user, preventing them from continuing to
     /// use airspace resources.
     pub fn revoke_user_permit(&self, user: Pubkey, issuer: Pubkey) -> TransactionBuilder {
@@ -28,12 +28,13 @@ impl MarginInvokeContext {
         let pool = MarginPoolIxBuilder::new(underlying_mint);
         let source_authority = source_authority.unwrap_or(self.margin_account);
         let (target, mut instructions) = self.get_or_create_pool_deposit(underlying_mint, target);
-        instructions.push(self.invoke(pool.deposit(
+        let deposit = pool.deposit(
             source_authority,
             source.or_ata(&source_authority, &underlying_mint),
             target,
             change,
-        )));
+        );
+        instructions.push(self.invoke(pool.with_rewards(deposit, &self.margin_account)));
         instructions
     }
 
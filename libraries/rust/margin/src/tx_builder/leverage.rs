@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anyhow::{bail, Result};
+use jet_margin::Valuation;
+use jet_program_common::Number128;
+
+/// The expected state of a margin account after opening a leveraged position.
+///
+/// Leverage is the ratio of the account's assets to its equity, so opening a
+/// position with 3x leverage borrows twice the account's equity and swaps it into
+/// the target token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeverageEstimate {
+    /// The value that is borrowed to open the position
+    pub borrow_value: Number128,
+
+    /// The equity remaining if the swap loses the maximum slippage
+    pub equity: Number128,
+
+    /// The collateral available after opening the position, which is negative if
+    /// the account would be unhealthy
+    pub available_collateral: Number128,
+}
+
+impl LeverageEstimate {
+    /// Estimate the result of opening a leveraged position
+    ///
+    /// # Params
+    ///
+    /// `valuation` - The current valuation of the margin account
+    /// `leverage` - The ratio of assets to equity after opening the position
+    /// `max_slippage_bps` - The most value that can be lost when swapping the borrowed tokens
+    /// `target_modifier` - The collateral weight of the target token's deposit notes
+    /// `loan_modifier` - The max leverage of the loan notes of the borrowed token
+    pub fn new(
+        valuation: &Valuation,
+        leverage: f64,
+        max_slippage_bps: u16,
+        target_modifier: u16,
+        loan_modifier: u16,
+    ) -> Result<Self> {
+        Self::estimate(
+            &Totals::from(valuation),
+            leverage,
+            max_slippage_bps,
+            target_modifier,
+            loan_modifier,
+        )
+    }
+
+    fn estimate(
+        current: &Totals,
+        leverage: f64,
+        max_slippage_bps: u16,
+        target_modifier: u16,
+        loan_modifier: u16,
+    ) -> Result<Self> {
+        if !leverage.is_finite() || leverage <= 1.0 {
+            bail!("leverage must be greater than 1, got {leverage}");
+        }
+        if current.equity <= Number128::ZERO {
+            bail!("the margin account has no equity to open a leveraged position with");
+        }
+        if loan_modifier == 0 {
+            bail!("the borrowed token cannot be used for leverage");
+        }
+
+        let leverage = Number128::from_decimal((leverage * 10_000.0).round() as i64, -4);
+        let borrow_value = current.equity * (leverage - Number128::ONE);
+        let slippage = borrow_value * Number128::from_bps(max_slippage_bps);
+        let swapped_value = borrow_value - slippage;
+
+        // The borrowed tokens are deposited and then swapped, so the only new collateral
+        // is the target token and the new liability is the loan.
+        let weighted_collateral = current.weighted_collateral
+            + swapped_value * Number128::from_decimal(target_modifier, -2);
+        let liabilities = current.liabilities + borrow_value;
+        let required_collateral =
+            current.required_collateral + borrow_value / Number128::from_decimal(loan_modifier, -2);
+
+        Ok(Self {
+            borrow_value,
+            equity: current.equity - slippage,
+            available_collateral: weighted_collateral - liabilities - required_collateral,
+        })
+    }
+
+    /// Whether the account is expected to remain healthy after opening the position
+    pub fn is_healthy(&self) -> bool {
+        self.available_collateral >= Number128::ZERO
+    }
+}
+
+/// The totals from a [Valuation] that a leveraged position changes
+struct Totals {
+    equity: Number128,
+    liabilities: Number128,
+    required_collateral: Number128,
+    weighted_collateral: Number128,
+}
+
+impl From<&Valuation> for Totals {
+    fn from(valuation: &Valuation) -> Self {
+        Self {
+            equity: valuation.equity,
+            liabilities: valuation.liabilities,
+            required_collateral: valuation.required_collateral,
+            weighted_collateral: valuation.weighted_collateral,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn totals(equity: i64, weighted_collateral: i64) -> Totals {
+        Totals {
+            equity: Number128::from_decimal(equity, 0),
+            liabilities: Number128::ZERO,
+            required_collateral: Number128::ZERO,
+            weighted_collateral: Number128::from_decimal(weighted_collateral, 0),
+        }
+    }
+
+    #[test]
+    fn borrows_equity_times_extra_leverage() {
+        let estimate =
+            LeverageEstimate::estimate(&totals(1_000, 1_000), 3.0, 0, 100, 1_000).unwrap();
+
+        assert_eq!(Number128::from_decimal(2_000, 0), estimate.borrow_value);
+        assert_eq!(Number128::from_decimal(1_000, 0), estimate.equity);
+        // 1000 + 2000 collateral, less 2000 debt and 200 required for 10x max leverage
+        assert_eq!(
+            Number128::from_decimal(800, 0),
+            estimate.available_collateral
+        );
+        assert!(estimate.is_healthy());
+    }
+
+    #[test]
+    fn excessive_leverage_is_unhealthy() {
+        let estimate = LeverageEstimate::estimate(&totals(1_000, 900), 5.0, 100, 90, 500).unwrap();
+
+        assert_eq!(Number128::from_decimal(960, 0), estimate.equity);
+        assert!(!estimate.is_healthy());
+    }
+
+    #[test]
+    fn rejects_invalid_leverage() {
+        assert!(LeverageEstimate::estimate(&totals(1_000, 1_000), 1.0, 0, 100, 1_000).is_err());
+        assert!(LeverageEstimate::estimate(&totals(0, 0), 2.0, 0, 100, 1_000).is_err());
+    }
+}
@@ -23,10 +23,13 @@ mod invoke_context;
 mod invoke_pool;
 /// invoke margin-swap through a margin account.
 mod invoke_swap;
+/// estimate the effect of leveraged positions on an account's health
+mod leverage;
 mod user;
 
 pub use airspace::*;
 pub use invoke_context::*;
 pub use invoke_pool::*;
 pub use invoke_swap::*;
+pub use leverage::*;
 pub use user::*;
@@ -23,7 +23,7 @@ use async_trait::async_trait;
 use jet_instructions::openbook::{close_open_orders, create_open_orders};
 use jet_margin_pool::program::JetMarginPool;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use jet_solana_client::transaction::create_signed_transaction;
 use solana_sdk::address_lookup_table_account::AddressLookupTableAccount;
 use solana_sdk::commitment_config::CommitmentConfig;
@@ -36,12 +36,13 @@ use solana_sdk::transaction::{Transaction, VersionedTransaction};
 
 use anchor_lang::{AccountDeserialize, Id};
 
-use jet_margin::{MarginAccount, TokenKind};
-use jet_margin_pool::{MarginPool, TokenChange};
+use jet_margin::{MarginAccount, RiskConfig, RiskParameters, TokenConfig, TokenKind, Valuation};
+use jet_margin_pool::{Amount, MarginPool, PoolAction, TokenChange};
+use jet_program_common::Number128;
 use jet_simulation::solana_rpc_api::SolanaRpcClient;
 
 use crate::cat;
-use crate::get_state::{get_margin_account, get_token_metadata};
+use crate::get_state::{get_anchor_account, get_margin_account, get_token_metadata};
 use crate::margin_account_ext::MarginAccountExt;
 use crate::refresh::deposit::refresh_deposit_positions;
 use crate::refresh::pool::{
@@ -50,17 +51,18 @@ use crate::refresh::pool::{
 use crate::refresh::position_refresher::{HasMarginAccountAddress, HasRpc, PositionRefresher};
 use crate::solana::pubkey::OrAta;
 use crate::solana::transaction::WithSigner;
+use crate::swap::router::SwapRouter;
 use crate::util::data::Join;
 use crate::{
     ix_builder::*,
     solana::{
         keypair::{clone, KeypairExt},
-        transaction::{SendTransactionBuilder, TransactionBuilder},
+        transaction::{condense, SendTransactionBuilder, TransactionBuilder, MAX_TX_SIZE},
     },
 };
 
 use super::invoke_pool::PoolTargetPosition;
-use super::MarginInvokeContext;
+use super::{LeverageEstimate, MarginInvokeContext};
 
 /// [Transaction] builder for a margin account, which supports invoking adapter
 /// actions signed as the margin account.
@@ -203,6 +205,14 @@ impl MarginTxBuilder {
         self
     }
 
+    /// Creates a variant of the builder for an account created under a permit
+    /// with an expiry or tier, which includes the permit wherever it's checked.
+    pub fn with_permit(mut self) -> Self {
+        self.ix = self.ix.with_permit();
+
+        self
+    }
+
     async fn create_transaction(&self, instructions: &[Instruction]) -> Result<Transaction> {
         let signers = self.signer.as_ref().map(|s| vec![s]).unwrap_or_default();
 
@@ -423,7 +433,10 @@ impl MarginTxBuilder {
         let source = source.or_ata(&authority, token_mint);
         let pool = MarginPoolIxBuilder::new(*token_mint);
         let (position, maybe_create) = self.get_or_create_position(&pool.deposit_note_mint).await?;
-        let inner_ix = pool.deposit(authority, source, position, change);
+        let inner_ix = pool.with_rewards(
+            pool.deposit(authority, source, position, change),
+            &self.ix.address,
+        );
         if let Some(create) = maybe_create {
             instructions.push(create);
             if self.ix.needs_signature(&inner_ix) {
@@ -461,7 +474,10 @@ impl MarginTxBuilder {
             pool.margin_refresh_position(self.ix.address, token_metadata.pyth_price);
         instructions.push(self.ix.accounting_invoke(inner_refresh_loan_ix));
 
-        let inner_borrow_ix = pool.margin_borrow(self.ix.address, deposit_position, change);
+        let inner_borrow_ix = pool.with_rewards(
+            pool.margin_borrow(self.ix.address, deposit_position, change),
+            &self.ix.address,
+        );
 
         instructions.push(self.adapter_invoke_ix(inner_borrow_ix));
         Ok(self.create_transaction_builder(&instructions))
@@ -488,7 +504,10 @@ impl MarginTxBuilder {
             .get_or_create_pool_loan_position(&mut instructions, &pool)
             .await?;
 
-        let inner_repay_ix = pool.margin_repay(self.ix.address, deposit_position, change);
+        let inner_repay_ix = pool.with_rewards(
+            pool.margin_repay(self.ix.address, deposit_position, change),
+            &self.ix.address,
+        );
 
         instructions.push(self.adapter_invoke_ix(inner_repay_ix));
         Ok(self.create_transaction_builder(&instructions))
@@ -513,7 +532,10 @@ impl MarginTxBuilder {
         let source = source.or_ata(&authority, &token_mint);
         let pool = MarginPoolIxBuilder::new(token_mint);
         let loan_notes = derive_loan_account(&self.ix.address, &pool.loan_note_mint);
-        let inner_ix = pool.repay(authority, source, loan_notes, change);
+        let inner_ix = pool.with_loan_rewards(
+            pool.repay(authority, source, loan_notes, change),
+            &[self.ix.address],
+        );
         let wrapped_ix = self.smart_invoke(inner_ix);
 
         self.create_transaction_builder(&[wrapped_ix])
@@ -538,8 +560,10 @@ impl MarginTxBuilder {
             .get_or_push_create_position(&mut instructions, &pool.deposit_note_mint)
             .await?;
 
-        let inner_withdraw_ix =
-            pool.withdraw(self.ix.address, deposit_position, *destination, change);
+        let inner_withdraw_ix = pool.with_rewards(
+            pool.withdraw(self.ix.address, deposit_position, *destination, change),
+            &self.ix.address,
+        );
 
         instructions.push(self.adapter_invoke_ix(inner_withdraw_ix));
         self.create_transaction(&instructions).await
@@ -672,6 +696,191 @@ impl MarginTxBuilder {
         Ok(setup_instructions)
     }
 
+    /// Open a leveraged position in a token by borrowing against the account's equity,
+    /// and swapping the loan into the token.
+    ///
+    /// The amount to borrow is based on the current valuation of the account, and the
+    /// account is checked to remain healthy if the swap loses the maximum slippage.
+    /// The borrow and swap are kept in the same transaction, and any setup and refresh
+    /// instructions are packed into as few transactions as possible.
+    ///
+    /// # Params
+    ///
+    /// `collateral` - The token to borrow, which the account should have deposits of
+    /// `target_token` - The token to open the leveraged position in
+    /// `leverage` - The ratio of the account's assets to its equity after opening the position
+    /// `max_slippage_bps` - The maximum slippage of the swap, also checked against the oracle prices
+    /// `router` - The router used to find the best route for the swap
+    /// `lookup_tables` - Lookup tables that can be used to pack the transactions
+    pub async fn open_leveraged_position(
+        &self,
+        collateral: &Pubkey,
+        target_token: &Pubkey,
+        leverage: f64,
+        max_slippage_bps: u16,
+        router: &SwapRouter,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<Vec<TransactionBuilder>> {
+        let state = self.get_account_state().await?;
+        let collateral_pool = MarginPoolIxBuilder::new(*collateral);
+        let target_pool = MarginPoolIxBuilder::new(*target_token);
+
+        let estimate = LeverageEstimate::new(
+            &self.get_valuation(&state).await?,
+            leverage,
+            max_slippage_bps,
+            self.get_value_modifier(&target_pool.deposit_note_mint)
+                .await?,
+            self.get_value_modifier(&collateral_pool.loan_note_mint)
+                .await?,
+        )?;
+        if !estimate.is_healthy() {
+            bail!("opening a position with {leverage}x leverage would leave the account unhealthy");
+        }
+
+        // The deposit notes of the collateral are priced by the same oracle as the loan
+        let deposit_position = state
+            .get_position(&collateral_pool.deposit_note_mint)
+            .with_context(|| format!("the margin account has no deposits of {collateral}"))?;
+        if !deposit_position.price.is_valid() {
+            bail!("the price of the {collateral} deposits needs to be refreshed");
+        }
+        let borrow_notes = (estimate.borrow_value
+            / Number128::from_decimal(
+                deposit_position.price.value,
+                deposit_position.price.exponent,
+            ))
+        .as_u64(deposit_position.exponent);
+        let borrow_amount = self
+            .get_pool(collateral)
+            .await?
+            .convert_amount(Amount::notes(borrow_notes), PoolAction::Withdraw)?
+            .tokens;
+
+        let route = router
+            .find_route(*collateral, *target_token, borrow_amount, max_slippage_bps)
+            .with_context(|| format!("no swap route from {collateral} to {target_token}"))?;
+        let swap_builder = route.builder(
+            SwapContext::MarginPool,
+            *self.address(),
+            Some(max_slippage_bps),
+        )?;
+
+        let open = cat![
+            self.borrow(collateral, TokenChange::shift(borrow_amount))
+                .await?,
+            self.create_transaction_builder(&[
+                ComputeBudgetInstruction::set_compute_unit_limit(800000),
+                self.adapter_invoke_ix(swap_builder.get_instruction()?),
+            ]),
+        ];
+
+        self.pack_leveraged_transactions(&swap_builder, open, lookup_tables)
+            .await
+    }
+
+    /// Close a leveraged position opened with `open_leveraged_position`, by swapping
+    /// all the deposits of the token back into the borrowed token, and repaying the loan.
+    ///
+    /// # Params
+    ///
+    /// `collateral` - The token that was borrowed to open the position
+    /// `target_token` - The token the leveraged position is in
+    /// `max_slippage_bps` - The maximum slippage of the swap, also checked against the oracle prices
+    /// `router` - The router used to find the best route for the swap
+    /// `lookup_tables` - Lookup tables that can be used to pack the transactions
+    pub async fn close_leveraged_position(
+        &self,
+        collateral: &Pubkey,
+        target_token: &Pubkey,
+        max_slippage_bps: u16,
+        router: &SwapRouter,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<Vec<TransactionBuilder>> {
+        let state = self.get_account_state().await?;
+        let collateral_pool = MarginPoolIxBuilder::new(*collateral);
+        let target_pool = MarginPoolIxBuilder::new(*target_token);
+
+        let position_amount = self
+            .get_pool(target_token)
+            .await?
+            .convert_amount(
+                Amount::notes(state.balance(&target_pool.deposit_note_mint)),
+                PoolAction::Withdraw,
+            )?
+            .tokens;
+        if position_amount == 0 {
+            bail!("the margin account has no deposits of {target_token} to close");
+        }
+
+        let pool = self.get_pool(collateral).await?;
+        let loan = pool
+            .convert_amount(
+                Amount::notes(state.balance(&collateral_pool.loan_note_mint)),
+                PoolAction::Repay,
+            )?
+            .tokens;
+        let deposits = pool
+            .convert_amount(
+                Amount::notes(state.balance(&collateral_pool.deposit_note_mint)),
+                PoolAction::Withdraw,
+            )?
+            .tokens;
+
+        let route = router
+            .find_route(
+                *target_token,
+                *collateral,
+                position_amount,
+                max_slippage_bps,
+            )
+            .with_context(|| format!("no swap route from {target_token} to {collateral}"))?;
+        if deposits.saturating_add(route.minimum_amount_out) < loan {
+            bail!("the swap may not return enough {collateral} to repay the loan");
+        }
+        let swap_builder = route.builder(
+            SwapContext::MarginPool,
+            *self.address(),
+            Some(max_slippage_bps),
+        )?;
+
+        let close = cat![
+            self.create_transaction_builder(&[
+                ComputeBudgetInstruction::set_compute_unit_limit(800000),
+                self.adapter_invoke_ix(swap_builder.get_instruction()?),
+            ]),
+            self.margin_repay(collateral, TokenChange::set(0)).await?,
+        ];
+
+        self.pack_leveraged_transactions(&swap_builder, close, lookup_tables)
+            .await
+    }
+
+    /// Pack the setup and refresh transactions for a leveraged swap, followed by the
+    /// swap itself. The loan and the swap are always sent in the same transaction, so
+    /// the account is never left with only one of them.
+    async fn pack_leveraged_transactions(
+        &self,
+        swap_builder: &MarginSwapRouteIxBuilder,
+        action: TransactionBuilder,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<Vec<TransactionBuilder>> {
+        let payer = self.rpc.payer().pubkey();
+        let action_size = action.fake_encode_with_lookup(&payer, lookup_tables)?.len();
+        if action_size > MAX_TX_SIZE {
+            bail!("the loan and the swap don't fit in one transaction");
+        }
+
+        let mut transactions =
+            vec![self.create_transaction_builder(&self.setup_swap(swap_builder).await?)];
+        transactions.extend(self.refresh_all_pool_positions().await?);
+
+        let mut transactions = condense(&transactions, &payer, lookup_tables)?;
+        transactions.push(action);
+
+        Ok(transactions)
+    }
+
     /// Transaction to begin liquidating user account.
     /// If `refresh_position` is provided, all the margin pools will be refreshed first.
     pub async fn liquidate_begin(&self, refresh_positions: bool) -> Result<Transaction> {
@@ -868,6 +1077,33 @@ impl MarginTxBuilder {
         Ok(MarginPool::try_deserialize(&mut &account.data[..])?)
     }
 
+    async fn get_valuation(&self, state: &MarginAccount) -> Result<Valuation> {
+        let params = match self
+            .rpc
+            .get_account(&derive_risk_config(&self.airspace()))
+            .await?
+        {
+            Some(account) => RiskConfig::try_deserialize(&mut &account.data[..])?.params,
+            None => RiskParameters::default(),
+        };
+        let clock = self.rpc.get_clock().await?;
+
+        state
+            .valuation(clock.unix_timestamp as u64, &params)
+            .context("could not value the margin account, its positions may need a refresh")
+    }
+
+    async fn get_value_modifier(&self, token_mint: &Pubkey) -> Result<u16> {
+        let config: TokenConfig = get_anchor_account(
+            &self.rpc,
+            &derive_token_config(&self.airspace(), token_mint),
+        )
+        .await
+        .with_context(|| format!("token config for {token_mint}"))?;
+
+        Ok(config.value_modifier)
+    }
+
     async fn get_or_push_create_position(
         &self,
         instructions: &mut Vec<Instruction>,
@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Loading of account state captured from a live cluster, so that it can be
+//! reproduced inside a [crate::TestRuntime].
+//!
+//! The expected format is the output of `solana account <ADDRESS> --output json`,
+//! with the account data encoded as base64:
+//!
+//! ```json
+//! {
+//!   "pubkey": "...",
+//!   "account": {
+//!     "lamports": 1000000,
+//!     "data": ["...", "base64"],
+//!     "owner": "...",
+//!     "executable": false,
+//!     "rentEpoch": 0
+//!   }
+//! }
+//! ```
+//!
+//! A file may contain a single account in this format, or a json array of them.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+/// An account as written by the solana cli
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AccountDump {
+    pub pubkey: String,
+    pub account: UiAccount,
+}
+
+impl AccountDump {
+    /// Create a dump of an account, for writing to a file
+    pub fn new(address: &Pubkey, account: &Account) -> Self {
+        Self {
+            pubkey: address.to_string(),
+            account: UiAccount::encode(address, account, UiAccountEncoding::Base64, None, None),
+        }
+    }
+
+    /// Decode the address and account state
+    pub fn decode(&self) -> Result<(Pubkey, Account)> {
+        let address = Pubkey::from_str(&self.pubkey)
+            .with_context(|| format!("invalid address {}", self.pubkey))?;
+        let account = self
+            .account
+            .decode::<Account>()
+            .ok_or_else(|| anyhow!("account {address} is not binary encoded"))?;
+
+        Ok((address, account))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AccountDumpFile {
+    Single(AccountDump),
+    Multiple(Vec<AccountDump>),
+}
+
+/// Parse accounts from the contents of a json dump
+pub fn parse_account_dumps(json: &str) -> Result<Vec<(Pubkey, Account)>> {
+    let dumps = match serde_json::from_str(json)? {
+        AccountDumpFile::Single(dump) => vec![dump],
+        AccountDumpFile::Multiple(dumps) => dumps,
+    };
+
+    dumps.iter().map(AccountDump::decode).collect()
+}
+
+/// Load accounts from a json dump file, or from all the `.json` files in
+/// a directory.
+pub fn load_account_dumps(path: impl AsRef<Path>) -> Result<Vec<(Pubkey, Account)>> {
+    let path = path.as_ref();
+
+    if !path.exists() {
+        bail!("no account dump found at {}", path.display());
+    }
+
+    let files = match path.is_dir() {
+        false => vec![path.to_path_buf()],
+        true => {
+            let mut files = fs::read_dir(path)?
+                .map(|entry| Ok(entry?.path()))
+                .collect::<Result<Vec<PathBuf>>>()?;
+
+            files.retain(|file| file.extension().map(|ext| ext == "json").unwrap_or(false));
+            files.sort();
+            files
+        }
+    };
+
+    let mut accounts = vec![];
+
+    for file in files {
+        let content = fs::read_to_string(&file)?;
+        let parsed = parse_account_dumps(&content)
+            .with_context(|| format!("failed to parse account dump {}", file.display()))?;
+
+        accounts.extend(parsed);
+    }
+
+    Ok(accounts)
+}
+
+/// Write accounts to a json dump file, that can later be loaded with [load_account_dumps]
+pub fn save_account_dumps(path: impl AsRef<Path>, accounts: &[(Pubkey, Account)]) -> Result<()> {
+    let dumps = accounts
+        .iter()
+        .map(|(address, account)| AccountDump::new(address, account))
+        .collect::<Vec<_>>();
+
+    fs::write(path, serde_json::to_string_pretty(&dumps)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_cli_account_output() {
+        let json = r#"{
+            "pubkey": "11111111111111111111111111111112",
+            "account": {
+                "lamports": 1461600,
+                "data": ["AQIDBA==", "base64"],
+                "owner": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                "executable": false,
+                "rentEpoch": 361
+            }
+        }"#;
+
+        let accounts = parse_account_dumps(json).unwrap();
+        let (address, account) = &accounts[0];
+
+        assert_eq!(1, accounts.len());
+        assert_eq!("11111111111111111111111111111112", address.to_string());
+        assert_eq!(1461600, account.lamports);
+        assert_eq!(vec![1, 2, 3, 4], account.data);
+        assert_eq!(spl_token::ID, account.owner);
+    }
+
+    #[test]
+    fn round_trips_multiple_accounts() {
+        let accounts = (0..3u8)
+            .map(|n| {
+                (
+                    Pubkey::new_unique(),
+                    Account {
+                        lamports: n as u64 * 100,
+                        data: vec![n; 16],
+                        owner: Pubkey::new_unique(),
+                        executable: false,
+                        rent_epoch: 0,
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let dumps = accounts
+            .iter()
+            .map(|(address, account)| AccountDump::new(address, account))
+            .collect::<Vec<_>>();
+        let json = serde_json::to_string(&dumps).unwrap();
+
+        assert_eq!(accounts, parse_account_dumps(&json).unwrap());
+    }
+}
@@ -34,9 +34,10 @@ mod log;
 #[doc(hidden)]
 pub mod runtime;
 
+pub mod account_dump;
 pub mod solana_rpc_api;
 
-pub use runtime::{Entrypoint, TestRuntime};
+pub use runtime::{BankSnapshot, Entrypoint, TestRuntime};
 pub use solana_rpc_api::SolanaRpcClient;
 
 pub type EntryFn =
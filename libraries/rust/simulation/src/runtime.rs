@@ -16,7 +16,7 @@ use solana_runtime::{
     bank::{Bank, TransactionLogCollectorFilter},
 };
 use solana_sdk::{
-    account::Account,
+    account::{Account, AccountSharedData, ReadableAccount},
     clock::Clock,
     compute_budget,
     entrypoint::SUCCESS,
@@ -31,7 +31,7 @@ use solana_sdk::{
     pubkey::Pubkey,
     signature::Signature,
     slot_hashes::SlotHashes,
-    sysvar::Sysvar,
+    sysvar::{self, Sysvar},
     transaction::{
         MessageHash, SanitizedTransaction, Transaction, TransactionError, VersionedTransaction,
     },
@@ -49,7 +49,35 @@ pub type Entrypoint = fn(*mut u8) -> u64;
 /// Utility for testing programs with the Solana runtime in-memory
 #[derive(Clone)]
 pub struct TestRuntime {
-    bank: Arc<Bank>,
+    manager: Arc<BankManager>,
+}
+
+/// A copy of the state of every account in a [TestRuntime] at some point in time
+///
+/// Snapshots can be restored any number of times, so a test can return to the
+/// same starting state to explore several different sequences of transactions.
+#[derive(Clone)]
+pub struct BankSnapshot {
+    slot: u64,
+    clock: Clock,
+    accounts: HashMap<Pubkey, AccountSharedData>,
+}
+
+impl BankSnapshot {
+    /// The slot of the bank when the snapshot was taken
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+
+    /// The clock of the bank when the snapshot was taken
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// The state of an account when the snapshot was taken
+    pub fn account(&self, address: &Pubkey) -> Option<Account> {
+        self.accounts.get(address).cloned().map(Account::from)
+    }
 }
 
 lazy_static! {
@@ -160,22 +188,46 @@ impl TestRuntime {
         solana_sdk::program_stubs::set_syscall_stubs(Box::new(LocalRuntimeSyscallStub));
 
         Self {
-            bank: Arc::new(Bank::new_from_parent(
+            manager: Arc::new(BankManager::new(Arc::new(Bank::new_from_parent(
                 &Arc::new(bank),
                 &Pubkey::new_unique(),
                 1,
-            )),
+            )))),
         }
     }
 
     /// Set the state for an account
     pub fn set_account(&self, address: &Pubkey, account: &Account) {
-        self.bank.store_account(address, account)
+        self.manager.bank().store_account(address, account)
     }
 
+    /// Set the state for many accounts, such as those loaded from a cluster
+    /// with [crate::account_dump::load_account_dumps]
+    pub fn set_accounts<'a>(&self, accounts: impl IntoIterator<Item = &'a (Pubkey, Account)>) {
+        let bank = self.manager.bank();
+
+        for (address, account) in accounts {
+            bank.store_account(address, account);
+        }
+    }
+
+    /// Capture the current state of all accounts, to be restored later
+    pub fn snapshot(&self) -> BankSnapshot {
+        self.manager.snapshot()
+    }
+
+    /// Return all accounts and the clock to the state captured in the snapshot
+    pub fn restore(&self, snapshot: &BankSnapshot) {
+        self.manager.restore(snapshot)
+    }
+
+    /// Get an rpc client for this runtime
+    ///
+    /// All clients share the same bank, so any changes made through one client
+    /// (or this runtime) are visible in all the others.
     pub fn rpc(&self) -> TestRuntimeRpcClient {
         TestRuntimeRpcClient {
-            manager: Arc::new(BankManager::new(self.bank.clone())),
+            manager: self.manager.clone(),
         }
     }
 }
@@ -581,6 +633,10 @@ impl BankManager {
         }
     }
 
+    fn bank(&self) -> Arc<Bank> {
+        self.bank.lock().unwrap().clone()
+    }
+
     fn complete_block(&self) {
         let mut bank = self.bank.lock().unwrap();
 
@@ -594,6 +650,70 @@ impl BankManager {
 
         log::bank::info!("new bank at slot {}", bank.slot());
     }
+
+    fn snapshot(&self) -> BankSnapshot {
+        let bank = self.bank();
+        let clock = Clock::clone(&bank.get_sysvar_cache_for_tests().get_clock().unwrap());
+
+        let accounts = bank
+            .get_all_accounts_with_modified_slots()
+            .unwrap()
+            .into_iter()
+            .filter(|(_, account, _)| is_snapshot_account(account))
+            .map(|(address, account, _)| (address, account))
+            .collect::<HashMap<_, _>>();
+
+        log::bank::info!(
+            "snapshot of {} accounts at slot {}",
+            accounts.len(),
+            bank.slot()
+        );
+
+        BankSnapshot {
+            slot: bank.slot(),
+            clock,
+            accounts,
+        }
+    }
+
+    fn restore(&self, snapshot: &BankSnapshot) {
+        // start a new block, so that the restored state doesn't mix with
+        // any changes already recorded for the current slot
+        self.complete_block();
+        let bank = self.bank();
+
+        let current = bank.get_all_accounts_with_modified_slots().unwrap();
+        let mut removed = 0;
+
+        for (address, account, _) in current {
+            if is_snapshot_account(&account) && !snapshot.accounts.contains_key(&address) {
+                // zero lamport accounts are treated as non-existent
+                bank.store_account(&address, &AccountSharedData::default());
+                removed += 1;
+            }
+        }
+
+        for (address, account) in &snapshot.accounts {
+            bank.store_account(address, account);
+        }
+
+        bank.set_sysvar_for_tests(&snapshot.clock);
+        bank.register_recent_blockhash(&Hash::new_unique());
+
+        log::bank::info!(
+            "restored {} accounts (removed {}) from snapshot at slot {} into slot {}",
+            snapshot.accounts.len(),
+            removed,
+            snapshot.slot,
+            bank.slot()
+        );
+    }
+}
+
+/// Sysvars and builtin programs are managed by the bank itself, so they are
+/// left alone when taking or restoring a snapshot
+fn is_snapshot_account(account: &AccountSharedData) -> bool {
+    !account.executable() && *account.owner() != sysvar::ID
 }
 
 #[derive(Clone)]
@@ -603,16 +723,40 @@ pub struct TestRuntimeRpcClient {
 
 impl TestRuntimeRpcClient {
     fn bank(&self) -> Arc<Bank> {
-        self.manager.bank.lock().unwrap().clone()
+        self.manager.bank()
     }
 
     pub fn set_clock(&self, new_clock: &Clock) {
         self.bank().set_sysvar_for_tests(new_clock);
     }
 
+    /// Move the clock forward (or backward) by some number of seconds
+    pub fn warp_clock(&self, seconds: i64) {
+        let bank = self.bank();
+        let mut clock = Clock::clone(&bank.get_sysvar_cache_for_tests().get_clock().unwrap());
+
+        clock.unix_timestamp += seconds;
+        bank.set_sysvar_for_tests(&clock);
+    }
+
     pub fn next_block(&self) {
         self.manager.complete_block();
     }
+
+    /// Set the state for an account
+    pub fn set_account(&self, address: &Pubkey, account: &Account) {
+        self.bank().store_account(address, account)
+    }
+
+    /// Capture the current state of all accounts, to be restored later
+    pub fn snapshot(&self) -> BankSnapshot {
+        self.manager.snapshot()
+    }
+
+    /// Return all accounts and the clock to the state captured in the snapshot
+    pub fn restore(&self, snapshot: &BankSnapshot) {
+        self.manager.restore(snapshot)
+    }
 }
 
 #[async_trait]
@@ -839,4 +983,55 @@ mod test {
 
         assert_eq!(420 * LAMPORTS_PER_SOL, dest_balance);
     }
+
+    #[tokio::test]
+    async fn can_restore_snapshot() {
+        let rt = TestRuntime::new([], []);
+        let rpc = rt.rpc();
+
+        let source_wallet = Keypair::new();
+        let dest_wallet = Keypair::new();
+
+        SolanaRpc::airdrop(&rpc, &source_wallet.pubkey(), 10 * LAMPORTS_PER_SOL)
+            .await
+            .unwrap();
+
+        let snapshot = rt.snapshot();
+        let initial_time = snapshot.clock().unix_timestamp;
+
+        let recent_blockhash = SolanaRpc::get_latest_blockhash(&rpc).await.unwrap();
+        let transfer_tx = system_transaction::transfer(
+            &source_wallet,
+            &dest_wallet.pubkey(),
+            5 * LAMPORTS_PER_SOL,
+            recent_blockhash,
+        );
+
+        SolanaRpc::send_transaction_legacy(&rpc, &transfer_tx)
+            .await
+            .unwrap();
+        rpc.warp_clock(3600);
+
+        assert!(SolanaRpc::get_account(&rpc, &dest_wallet.pubkey())
+            .await
+            .unwrap()
+            .is_some());
+
+        rt.restore(&snapshot);
+
+        let source_balance = SolanaRpc::get_account(&rpc, &source_wallet.pubkey())
+            .await
+            .unwrap()
+            .unwrap()
+            .lamports;
+        let dest_account = SolanaRpc::get_account(&rpc, &dest_wallet.pubkey())
+            .await
+            .unwrap();
+        let slot = SolanaRpc::get_slot(&rpc).await.unwrap();
+        let time = SolanaRpc::get_block_time(&rpc, slot).await.unwrap();
+
+        assert_eq!(10 * LAMPORTS_PER_SOL, source_balance);
+        assert!(dest_account.is_none());
+        assert_eq!(initial_time, time);
+    }
 }
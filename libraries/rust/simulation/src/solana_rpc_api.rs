@@ -33,7 +33,7 @@ use solana_sdk::slot_history::Slot;
 use solana_sdk::transaction::{Transaction, VersionedTransaction};
 use solana_transaction_status::TransactionStatus;
 
-use jet_solana_client::rpc::{AccountFilter, SolanaRpc, SolanaRpcExtra};
+use jet_solana_client::rpc::{AccountFilter, DataSlice, SolanaRpc, SolanaRpcExtra};
 
 use crate::runtime::TestRuntimeRpcClient;
 
@@ -63,6 +63,13 @@ pub trait SolanaRpcClient: Send + Sync {
         filters: Vec<AccountFilter>,
     ) -> Result<Vec<(Pubkey, Account)>>;
 
+    async fn get_program_accounts_with_slice(
+        &self,
+        program_id: &Pubkey,
+        filters: Vec<AccountFilter>,
+        slice: DataSlice,
+    ) -> Result<Vec<(Pubkey, Account)>>;
+
     async fn airdrop(&self, account: &Pubkey, amount: u64) -> Result<()>;
 
     async fn send_and_confirm_transaction(&self, transaction: &Transaction) -> Result<Signature> {
@@ -150,6 +157,18 @@ where
         Ok(self.0.get_program_accounts(program_id, &filters).await?)
     }
 
+    async fn get_program_accounts_with_slice(
+        &self,
+        program_id: &Pubkey,
+        filters: Vec<AccountFilter>,
+        slice: DataSlice,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        Ok(self
+            .0
+            .get_program_accounts_with_slice(program_id, &filters, slice)
+            .await?)
+    }
+
     async fn airdrop(&self, account: &Pubkey, amount: u64) -> Result<()> {
         self.0.airdrop(account, amount).await?;
 
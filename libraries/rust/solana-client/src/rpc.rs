@@ -71,6 +71,13 @@ impl AccountFilter {
     }
 }
 
+/// The part of the data to return for each account in a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataSlice {
+    pub offset: usize,
+    pub length: usize,
+}
+
 /// A type that allows for interacting with a Solana RPC node
 #[async_trait]
 pub trait SolanaRpc: Send + Sync {
@@ -100,6 +107,25 @@ pub trait SolanaRpc: Send + Sync {
         filters: &[AccountFilter],
     ) -> ClientResult<Vec<(Pubkey, Account)>>;
 
+    /// Get the accounts of a program with only a part of their data, which is
+    /// cheaper for large accounts that are searched by a small header
+    async fn get_program_accounts_with_slice(
+        &self,
+        program: &Pubkey,
+        filters: &[AccountFilter],
+        slice: DataSlice,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        let mut accounts = self.get_program_accounts(program, filters).await?;
+
+        for (_, account) in &mut accounts {
+            let start = slice.offset.min(account.data.len());
+            let end = (slice.offset + slice.length).min(account.data.len());
+            account.data = account.data[start..end].to_vec();
+        }
+
+        Ok(accounts)
+    }
+
     async fn get_token_accounts_by_owner(
         &self,
         owner: &Pubkey,
@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use serde_json::json;
 use std::{str::FromStr, sync::Arc};
 
-use solana_account_decoder::UiAccountEncoding;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
 use solana_client::{
     nonblocking::rpc_client::RpcClient,
     rpc_config::{
@@ -23,7 +23,7 @@ use solana_sdk::{
 };
 use spl_token::state::Account as TokenAccount;
 
-use super::{AccountFilter, ClientError, ClientResult, SolanaRpc};
+use super::{AccountFilter, ClientError, ClientResult, DataSlice, SolanaRpc};
 
 /// A wrapper for an RPC client to implement `SolanaRpc` trait
 #[derive(Clone)]
@@ -60,6 +60,44 @@ impl RpcConnection {
             },
         }
     }
+
+    async fn get_program_accounts_sliced(
+        &self,
+        program: &Pubkey,
+        filters: &[AccountFilter],
+        slice: Option<DataSlice>,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        use solana_client::rpc_filter::*;
+
+        let config = RpcProgramAccountsConfig {
+            filters: Some(
+                filters
+                    .iter()
+                    .map(|filter| match filter {
+                        AccountFilter::Memcmp { offset, bytes } => {
+                            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(*offset, bytes.clone()))
+                        }
+                        AccountFilter::DataSize(size) => RpcFilterType::DataSize(*size as u64),
+                    })
+                    .collect(),
+            ),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64Zstd),
+                data_slice: slice.map(|slice| UiDataSliceConfig {
+                    offset: slice.offset,
+                    length: slice.length,
+                }),
+                commitment: Some(CommitmentConfig::processed()),
+                min_context_slot: None,
+            },
+            with_context: None,
+        };
+
+        self.rpc
+            .get_program_accounts_with_config(program, config)
+            .await
+            .map_err(convert_err)
+    }
 }
 
 impl From<RpcClient> for RpcConnection {
@@ -179,33 +217,18 @@ impl SolanaRpc for RpcConnection {
         program: &Pubkey,
         filters: &[AccountFilter],
     ) -> ClientResult<Vec<(Pubkey, solana_sdk::account::Account)>> {
-        use solana_client::rpc_filter::*;
-
-        let config = RpcProgramAccountsConfig {
-            filters: Some(
-                filters
-                    .iter()
-                    .map(|filter| match filter {
-                        AccountFilter::Memcmp { offset, bytes } => {
-                            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(*offset, bytes.clone()))
-                        }
-                        AccountFilter::DataSize(size) => RpcFilterType::DataSize(*size as u64),
-                    })
-                    .collect(),
-            ),
-            account_config: RpcAccountInfoConfig {
-                encoding: Some(UiAccountEncoding::Base64Zstd),
-                data_slice: None,
-                commitment: Some(CommitmentConfig::processed()),
-                min_context_slot: None,
-            },
-            with_context: None,
-        };
+        self.get_program_accounts_sliced(program, filters, None)
+            .await
+    }
 
-        self.rpc
-            .get_program_accounts_with_config(program, config)
+    async fn get_program_accounts_with_slice(
+        &self,
+        program: &Pubkey,
+        filters: &[AccountFilter],
+        slice: DataSlice,
+    ) -> ClientResult<Vec<(Pubkey, solana_sdk::account::Account)>> {
+        self.get_program_accounts_sliced(program, filters, Some(slice))
             .await
-            .map_err(convert_err)
     }
 
     async fn get_token_accounts_by_owner(
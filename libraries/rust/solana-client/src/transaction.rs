@@ -338,7 +338,8 @@ impl WithSigner for Vec<TransactionBuilder> {
     }
 }
 
-const MAX_TX_SIZE: usize = 1232;
+/// The largest transaction that can be sent, compared with the size of the encoded transaction
+pub const MAX_TX_SIZE: usize = 1232;
 
 /// Combines all the instructions within each of the TransactionBuilders into
 /// the smallest possible number of TransactionBuilders that don't violate the
@@ -30,6 +30,20 @@ export const IDL: JetAirspaceIDL = {
       },
       value: 'b"airspace-permit"'
     },
+    {
+      name: "TIMELOCK",
+      type: {
+        defined: "&[u8]"
+      },
+      value: 'b"timelock"'
+    },
+    {
+      name: "TIMELOCK_TRANSACTION",
+      type: {
+        defined: "&[u8]"
+      },
+      value: 'b"timelock-transaction"'
+    },
     {
       name: "GOVERNOR_DEFAULT",
       type: "publicKey",
@@ -125,6 +139,18 @@ export const IDL: JetAirspaceIDL = {
           isSigner: false,
           docs: ["The airspace account to be created"]
         },
+        {
+          name: "governor",
+          isMut: false,
+          isSigner: true,
+          docs: ["The current governor"]
+        },
+        {
+          name: "governorId",
+          isMut: false,
+          isSigner: false,
+          docs: ["The governer identity account"]
+        },
         {
           name: "systemProgram",
           isMut: false,
@@ -266,7 +292,11 @@ export const IDL: JetAirspaceIDL = {
         "# Parameters",
         "",
         "* `owner` - The owner for the new permit, which is the address being allowed to use",
-        "the airspace."
+        "the airspace.",
+        "* `expires_at` - The unix timestamp when the permit stops being valid, or zero if the",
+        "permit never expires.",
+        "* `tier` - The limits on what the owner may do within the airspace. Only the airspace",
+        "authority or an issuer may create a permit with an expiry or tier."
       ],
       accounts: [
         {
@@ -318,6 +348,64 @@ export const IDL: JetAirspaceIDL = {
         {
           name: "owner",
           type: "publicKey"
+        },
+        {
+          name: "expiresAt",
+          type: "i64"
+        },
+        {
+          name: "tier",
+          type: {
+            defined: "PermitTier"
+          }
+        }
+      ]
+    },
+    {
+      name: "airspacePermitRenew",
+      docs: [
+        "Extend the expiry of a previously created permit",
+        "",
+        "# Parameters",
+        "",
+        "* `expires_at` - The new unix timestamp when the permit stops being valid, which must",
+        "be later than the current expiry, or zero to never expire."
+      ],
+      accounts: [
+        {
+          name: "authority",
+          isMut: false,
+          isSigner: true,
+          docs: [
+            "The authority allowed to renew the permit",
+            "",
+            "This must be either the airspace authority, or the regulator that issued the",
+            "permit while it is still licensed to issue permits."
+          ]
+        },
+        {
+          name: "airspace",
+          isMut: false,
+          isSigner: false,
+          docs: ["The airspace the permit is for"]
+        },
+        {
+          name: "issuerId",
+          isMut: false,
+          isSigner: false,
+          docs: ["The identity account for the regulator that issued the permit"]
+        },
+        {
+          name: "permit",
+          isMut: true,
+          isSigner: false,
+          docs: ["The permit to be renewed"]
+        }
+      ],
+      args: [
+        {
+          name: "expiresAt",
+          type: "i64"
         }
       ]
     },
@@ -366,6 +454,241 @@ export const IDL: JetAirspaceIDL = {
         }
       ],
       args: []
+    },
+    {
+      name: "timelockCreate",
+      docs: [
+        "Create a timelock for an airspace",
+        "",
+        "Once created, the airspace authority can be set to the timelock address, so that any",
+        "change requiring the authority has to be queued and approved by the timelock signers,",
+        "and then wait for the delay before it can be executed.",
+        "",
+        "# Parameters",
+        "",
+        "* `signers` - The addresses allowed to queue, approve and cancel transactions.",
+        "* `threshold` - The number of signers that must approve a transaction.",
+        "* `delay` - The number of seconds between queueing and executing a transaction."
+      ],
+      accounts: [
+        {
+          name: "payer",
+          isMut: true,
+          isSigner: true
+        },
+        {
+          name: "authority",
+          isMut: false,
+          isSigner: true,
+          docs: ["The airspace authority"]
+        },
+        {
+          name: "airspace",
+          isMut: false,
+          isSigner: false,
+          docs: ["The airspace the timelock will be used for"]
+        },
+        {
+          name: "timelock",
+          isMut: true,
+          isSigner: false,
+          docs: ["The timelock account to be created"]
+        },
+        {
+          name: "systemProgram",
+          isMut: false,
+          isSigner: false
+        }
+      ],
+      args: [
+        {
+          name: "signers",
+          type: {
+            vec: "publicKey"
+          }
+        },
+        {
+          name: "threshold",
+          type: "u8"
+        },
+        {
+          name: "delay",
+          type: "i64"
+        }
+      ]
+    },
+    {
+      name: "timelockConfigure",
+      docs: [
+        "Change the signers, threshold or delay for a timelock. Must be signed by the",
+        "timelock itself, by executing a queued transaction.",
+        "",
+        "# Parameters",
+        "",
+        "* `signers` - The addresses allowed to queue, approve and cancel transactions.",
+        "* `threshold` - The number of signers that must approve a transaction.",
+        "* `delay` - The number of seconds between queueing and executing a transaction."
+      ],
+      accounts: [
+        {
+          name: "timelock",
+          isMut: true,
+          isSigner: true,
+          docs: [
+            "The timelock to be configured",
+            "",
+            "The timelock must sign for its own changes, so they can only be made by executing",
+            "a transaction queued in the timelock."
+          ]
+        }
+      ],
+      args: [
+        {
+          name: "signers",
+          type: {
+            vec: "publicKey"
+          }
+        },
+        {
+          name: "threshold",
+          type: "u8"
+        },
+        {
+          name: "delay",
+          type: "i64"
+        }
+      ]
+    },
+    {
+      name: "timelockQueue",
+      docs: [
+        "Queue an instruction to be signed by the timelock once the delay has passed",
+        "",
+        "The proposer counts as the first approval for the transaction.",
+        "",
+        "# Parameters",
+        "",
+        "* `instruction` - The instruction to be executed."
+      ],
+      accounts: [
+        {
+          name: "payer",
+          isMut: true,
+          isSigner: true
+        },
+        {
+          name: "proposer",
+          isMut: false,
+          isSigner: true,
+          docs: ["One of the timelock signers"]
+        },
+        {
+          name: "timelock",
+          isMut: true,
+          isSigner: false,
+          docs: ["The timelock to queue the transaction in"]
+        },
+        {
+          name: "transaction",
+          isMut: true,
+          isSigner: false,
+          docs: ["The account to store the queued transaction"]
+        },
+        {
+          name: "systemProgram",
+          isMut: false,
+          isSigner: false
+        }
+      ],
+      args: [
+        {
+          name: "instruction",
+          type: {
+            defined: "TimelockInstruction"
+          }
+        }
+      ]
+    },
+    {
+      name: "timelockApprove",
+      docs: ["Approve a queued transaction"],
+      accounts: [
+        {
+          name: "approver",
+          isMut: false,
+          isSigner: true,
+          docs: ["One of the timelock signers"]
+        },
+        {
+          name: "timelock",
+          isMut: false,
+          isSigner: false,
+          docs: ["The timelock the transaction was queued in"]
+        },
+        {
+          name: "transaction",
+          isMut: true,
+          isSigner: false,
+          docs: ["The transaction to approve"]
+        }
+      ],
+      args: []
+    },
+    {
+      name: "timelockCancel",
+      docs: ["Cancel a queued transaction. Any one of the timelock signers may cancel."],
+      accounts: [
+        {
+          name: "authority",
+          isMut: false,
+          isSigner: true,
+          docs: ["One of the timelock signers"]
+        },
+        {
+          name: "proposer",
+          isMut: true,
+          isSigner: false,
+          docs: ["The address that queued the transaction, which receives its rent"]
+        },
+        {
+          name: "timelock",
+          isMut: false,
+          isSigner: false,
+          docs: ["The timelock the transaction was queued in"]
+        },
+        {
+          name: "transaction",
+          isMut: true,
+          isSigner: false,
+          docs: ["The transaction to cancel"]
+        }
+      ],
+      args: []
+    },
+    {
+      name: "timelockExecute",
+      docs: ["Execute a queued transaction that has enough approvals, and has waited for", "the timelock delay."],
+      accounts: [
+        {
+          name: "proposer",
+          isMut: true,
+          isSigner: false,
+          docs: ["The address that queued the transaction, which receives its rent"]
+        },
+        {
+          name: "timelock",
+          isMut: false,
+          isSigner: false,
+          docs: ["The timelock the transaction was queued in, which signs for the instruction"]
+        },
+        {
+          name: "transaction",
+          isMut: true,
+          isSigner: false,
+          docs: ["The transaction to execute"]
+        }
+      ],
+      args: []
     }
   ],
   accounts: [
@@ -437,6 +760,119 @@ export const IDL: JetAirspaceIDL = {
             name: "issuer",
             docs: ["The issuer of this permit"],
             type: "publicKey"
+          },
+          {
+            name: "expiresAt",
+            docs: [
+              "The unix timestamp after which the permit is no longer valid, or zero if the",
+              "permit never expires."
+            ],
+            type: "i64"
+          },
+          {
+            name: "tier",
+            docs: ["Limits on what the owner may do with the resources in the airspace"],
+            type: {
+              defined: "PermitTier"
+            }
+          }
+        ]
+      }
+    },
+    {
+      name: "Timelock",
+      docs: [
+        "A delay applied to changes made by an airspace authority.",
+        "",
+        "The timelock address is meant to become the airspace authority, so that any change",
+        "signed by the authority (including config changes in other programs) must be queued",
+        "by the timelock signers, and can only be executed after the delay has passed."
+      ],
+      type: {
+        kind: "struct",
+        fields: [
+          {
+            name: "airspace",
+            docs: ["The airspace this timelock is for"],
+            type: "publicKey"
+          },
+          {
+            name: "bumpSeed",
+            docs: ["The bump seed for the timelock address"],
+            type: {
+              array: ["u8", 1]
+            }
+          },
+          {
+            name: "signers",
+            docs: ["The addresses allowed to queue, approve and cancel transactions"],
+            type: {
+              vec: "publicKey"
+            }
+          },
+          {
+            name: "threshold",
+            docs: ["The number of signers that must approve a transaction before it can be executed"],
+            type: "u8"
+          },
+          {
+            name: "delay",
+            docs: ["The number of seconds a transaction must wait after being queued before it can", "be executed."],
+            type: "i64"
+          },
+          {
+            name: "transactionCount",
+            docs: [
+              "The number of transactions that have been queued, used to derive the address",
+              "of the next transaction."
+            ],
+            type: "u64"
+          }
+        ]
+      }
+    },
+    {
+      name: "TimelockTransaction",
+      docs: [
+        "An instruction that has been queued in a timelock, to be executed with the timelock",
+        "address as a signer once the delay has passed."
+      ],
+      type: {
+        kind: "struct",
+        fields: [
+          {
+            name: "timelock",
+            docs: ["The timelock this transaction was queued in"],
+            type: "publicKey"
+          },
+          {
+            name: "id",
+            docs: ["The sequence number for this transaction within the timelock"],
+            type: "u64"
+          },
+          {
+            name: "proposer",
+            docs: ["The signer that queued this transaction, which receives the rent when it is closed"],
+            type: "publicKey"
+          },
+          {
+            name: "eta",
+            docs: ["The unix timestamp after which the transaction may be executed"],
+            type: "i64"
+          },
+          {
+            name: "approvals",
+            docs: ["The timelock signers that have approved this transaction"],
+            type: {
+              vec: "publicKey"
+            }
+          },
+          {
+            name: "instruction",
+            docs: ["The instruction to be executed"],
+            type: {
+              defined: "TimelockInstruction"
+            }
           }
         ]
       }
@@ -459,6 +895,76 @@ export const IDL: JetAirspaceIDL = {
       }
     }
   ],
+  types: [
+    {
+      name: "PermitTier",
+      docs: ["Limits on the use of airspace resources, attached to a permit.", "", "The default tier has no limits."],
+      type: {
+        kind: "struct",
+        fields: [
+          {
+            name: "lendOnly",
+            docs: ["If true, the owner may only lend, and may not take on any liabilities"],
+            type: "bool"
+          },
+          {
+            name: "maxBorrowValue",
+            docs: [
+              "The maximum total value of liabilities (in whole USD) that an account under the",
+              "permit may have after borrowing, or zero if there is no limit."
+            ],
+            type: "u64"
+          }
+        ]
+      }
+    },
+    {
+      name: "TimelockInstruction",
+      docs: ["An instruction stored in a [TimelockTransaction]"],
+      type: {
+        kind: "struct",
+        fields: [
+          {
+            name: "programId",
+            type: "publicKey"
+          },
+          {
+            name: "accounts",
+            type: {
+              vec: {
+                defined: "TimelockAccountMeta"
+              }
+            }
+          },
+          {
+            name: "data",
+            type: "bytes"
+          }
+        ]
+      }
+    },
+    {
+      name: "TimelockAccountMeta",
+      docs: ["An account used by a [TimelockInstruction]"],
+      type: {
+        kind: "struct",
+        fields: [
+          {
+            name: "pubkey",
+            type: "publicKey"
+          },
+          {
+            name: "isSigner",
+            type: "bool"
+          },
+          {
+            name: "isWritable",
+            type: "bool"
+          }
+        ]
+      }
+    }
+  ],
   events: [
     {
       name: "AirspaceCreated",
@@ -547,6 +1053,38 @@ export const IDL: JetAirspaceIDL = {
           name: "owner",
           type: "publicKey",
           index: false
+        },
+        {
+          name: "expiresAt",
+          type: "i64",
+          index: false
+        },
+        {
+          name: "tier",
+          type: {
+            defined: "PermitTier"
+          },
+          index: false
+        }
+      ]
+    },
+    {
+      name: "AirspacePermitRenewed",
+      fields: [
+        {
+          name: "airspace",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "permit",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "expiresAt",
+          type: "i64",
+          index: false
         }
       ]
     },
@@ -564,6 +1102,162 @@ export const IDL: JetAirspaceIDL = {
           index: false
         }
       ]
+    },
+    {
+      name: "TimelockCreated",
+      fields: [
+        {
+          name: "airspace",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "timelock",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "signers",
+          type: {
+            vec: "publicKey"
+          },
+          index: false
+        },
+        {
+          name: "threshold",
+          type: "u8",
+          index: false
+        },
+        {
+          name: "delay",
+          type: "i64",
+          index: false
+        }
+      ]
+    },
+    {
+      name: "TimelockConfigured",
+      fields: [
+        {
+          name: "timelock",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "signers",
+          type: {
+            vec: "publicKey"
+          },
+          index: false
+        },
+        {
+          name: "threshold",
+          type: "u8",
+          index: false
+        },
+        {
+          name: "delay",
+          type: "i64",
+          index: false
+        }
+      ]
+    },
+    {
+      name: "TimelockTransactionQueued",
+      fields: [
+        {
+          name: "timelock",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "transaction",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "id",
+          type: "u64",
+          index: false
+        },
+        {
+          name: "proposer",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "eta",
+          type: "i64",
+          index: false
+        },
+        {
+          name: "instruction",
+          type: {
+            defined: "TimelockInstruction"
+          },
+          index: false
+        }
+      ]
+    },
+    {
+      name: "TimelockTransactionApproved",
+      fields: [
+        {
+          name: "timelock",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "transaction",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "approver",
+          type: "publicKey",
+          index: false
+        }
+      ]
+    },
+    {
+      name: "TimelockTransactionCancelled",
+      fields: [
+        {
+          name: "timelock",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "transaction",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "cancelledBy",
+          type: "publicKey",
+          index: false
+        }
+      ]
+    },
+    {
+      name: "TimelockTransactionExecuted",
+      fields: [
+        {
+          name: "timelock",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "transaction",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "id",
+          type: "u64",
+          index: false
+        }
+      ]
     }
   ],
   errors: [
@@ -571,6 +1265,31 @@ export const IDL: JetAirspaceIDL = {
       code: 707000,
       name: "PermissionDenied",
       msg: "The signer does not have the required permissions to do this"
+    },
+    {
+      code: 707001,
+      name: "InvalidPermitExpiry",
+      msg: "The permit expiry must be in the future, and can only be extended"
+    },
+    {
+      code: 707002,
+      name: "InvalidTimelockConfig",
+      msg: "The timelock signers, threshold or delay are not valid"
+    },
+    {
+      code: 707003,
+      name: "TimelockAlreadyApproved",
+      msg: "The signer already approved this transaction"
+    },
+    {
+      code: 707004,
+      name: "TimelockNotReady",
+      msg: "The timelock delay for this transaction has not passed"
+    },
+    {
+      code: 707005,
+      name: "TimelockNotApproved",
+      msg: "The transaction does not have enough approvals"
     }
   ],
   metadata: {
@@ -496,9 +496,576 @@ export const IDL: JetMarginPoolIDL = {
         }
       ],
       args: []
+    },
+    {
+      name: "writeOffBadDebt",
+      docs: [
+        "Write off the loans of an insolvent margin account",
+        "",
+        "An account is insolvent when it has no collateral left after being",
+        "liquidated, but still has outstanding loan notes. The loan notes are",
+        "burned, with the bad debt absorbed by any uncollected fees first, and",
+        "the remainder lowering the value of the pool's deposit notes.",
+        "",
+        "The margin account's loan position should be updated afterwards with",
+        "the margin program's `update_position_balance`.",
+        "",
+        "# [Accounts](jet_margin_pool::accounts::WriteOffBadDebt)",
+        "",
+        "|     |     |     |",
+        "| --- | --- | --- |",
+        "| **Name** | **Type** | **Description** |",
+        "| `margin_account` | `read_only` | The insolvent margin account with the loan. |",
+        "| `margin_pool` | `writable` | The pool with the outstanding loan. |",
+        "| `loan_note_mint` | `writable` | The mint for the notes representing loans from the pool. |",
+        "| `loan_account` | `writable` | The account with the loan notes to be written off. |",
+        "| `token_program` | `read_only` | The [spl token program](https://spl.solana.com/token). |",
+        "",
+        "# Events",
+        "",
+        "|     |     |",
+        "| --- | --- |",
+        "| **Event Name** | **Description** |",
+        "| [`events::BadDebtWrittenOff`] | The bad debt that was written off. |",
+        "",
+        "The bad debt is also returned, so that a program invoking this can",
+        "record a claim for the loss, such as the staking insurance fund.",
+        ""
+      ],
+      accounts: [
+        {
+          name: "marginAccount",
+          isMut: false,
+          isSigner: false,
+          docs: ["The insolvent margin account with the loan"]
+        },
+        {
+          name: "marginPool",
+          isMut: true,
+          isSigner: false,
+          docs: ["The pool with the outstanding loan"]
+        },
+        {
+          name: "loanNoteMint",
+          isMut: true,
+          isSigner: false,
+          docs: ["The mint for the notes representing loans from the pool"]
+        },
+        {
+          name: "loanAccount",
+          isMut: true,
+          isSigner: false,
+          docs: ["The account with the loan notes to be written off"]
+        },
+        {
+          name: "tokenProgram",
+          isMut: false,
+          isSigner: false
+        }
+      ],
+      args: [],
+      returns: {
+        defined: "BadDebt"
+      }
+    },
+    {
+      name: "flashLoanBegin",
+      docs: [
+        "Lend tokens from the pool's vault, which must be repaid with a fee by a",
+        "`flash_loan_end` instruction later in the same transaction.",
+        "",
+        "The next flash loan instruction of the pool in the transaction must be the",
+        "`flash_loan_end` for the same amount, so flash loans cannot be nested.",
+        "",
+        "# Parameters",
+        "",
+        "* `amount` - The amount of tokens to borrow.",
+        "",
+        "# [Accounts](jet_margin_pool::accounts::FlashLoanBegin)",
+        "",
+        "|     |     |     |",
+        "| --- | --- | --- |",
+        "| **Name** | **Type** | **Description** |",
+        "| `margin_pool` | `read_only` | The pool to borrow from. |",
+        "| `vault` | `writable` | The vault responsible for storing the pool's tokens. |",
+        "| `destination` | `writable` | The token account to receive the loan. |",
+        "| `instructions` | `read_only` | The [instructions sysvar](https://docs.solana.com/developing/runtime-facilities/sysvars#instructions). |",
+        "| `token_program` | `read_only` | The [spl token program](https://spl.solana.com/token). |",
+        "",
+        "# Events",
+        "",
+        "|     |     |",
+        "| --- | --- |",
+        "| **Event Name** | **Description** |",
+        "| [`events::FlashLoanBegin`] | The tokens lent. |",
+        ""
+      ],
+      accounts: [
+        {
+          name: "marginPool",
+          isMut: false,
+          isSigner: false,
+          docs: ["The pool to borrow from"]
+        },
+        {
+          name: "vault",
+          isMut: true,
+          isSigner: false,
+          docs: ["The vault responsible for storing the pool's tokens"]
+        },
+        {
+          name: "destination",
+          isMut: true,
+          isSigner: false,
+          docs: ["The token account to receive the loan"]
+        },
+        {
+          name: "instructions",
+          isMut: false,
+          isSigner: false,
+          docs: ["The instructions of the transaction"]
+        },
+        {
+          name: "tokenProgram",
+          isMut: false,
+          isSigner: false
+        }
+      ],
+      args: [
+        {
+          name: "amount",
+          type: "u64"
+        }
+      ]
+    },
+    {
+      name: "flashLoanEnd",
+      docs: [
+        "Repay a flash loan, along with the fee set by the pool's `flash_loan_fee_rate`,",
+        "which is earned by depositors less the pool's management fee.",
+        "",
+        "# Parameters",
+        "",
+        "* `amount` - The amount of tokens that were borrowed.",
+        "",
+        "# [Accounts](jet_margin_pool::accounts::FlashLoanEnd)",
+        "",
+        "|     |     |     |",
+        "| --- | --- | --- |",
+        "| **Name** | **Type** | **Description** |",
+        "| `repayment_account_authority` | `Signer` | Signing authority for the repaying token account. |",
+        "| `margin_pool` | `writable` | The pool that was borrowed from. |",
+        "| `vault` | `writable` | The vault responsible for storing the pool's tokens. |",
+        "| `repayment_token_account` | `writable` | The token account repaying the loan and fee. |",
+        "| `instructions` | `read_only` | The [instructions sysvar](https://docs.solana.com/developing/runtime-facilities/sysvars#instructions). |",
+        "| `token_program` | `read_only` | The [spl token program](https://spl.solana.com/token). |",
+        "",
+        "# Events",
+        "",
+        "|     |     |",
+        "| --- | --- |",
+        "| **Event Name** | **Description** |",
+        "| [`events::FlashLoanEnd`] | The loan and fee repaid. |",
+        ""
+      ],
+      accounts: [
+        {
+          name: "repaymentAccountAuthority",
+          isMut: false,
+          isSigner: true,
+          docs: ["Signing authority for the repaying token account"]
+        },
+        {
+          name: "marginPool",
+          isMut: true,
+          isSigner: false,
+          docs: ["The pool that was borrowed from"]
+        },
+        {
+          name: "vault",
+          isMut: true,
+          isSigner: false,
+          docs: ["The vault responsible for storing the pool's tokens"]
+        },
+        {
+          name: "repaymentTokenAccount",
+          isMut: true,
+          isSigner: false,
+          docs: ["The token account repaying the loan and fee"]
+        },
+        {
+          name: "instructions",
+          isMut: false,
+          isSigner: false,
+          docs: ["The instructions of the transaction"]
+        },
+        {
+          name: "tokenProgram",
+          isMut: false,
+          isSigner: false
+        }
+      ],
+      args: [
+        {
+          name: "amount",
+          type: "u64"
+        }
+      ]
+    },
+    {
+      name: "configureRewards",
+      docs: [
+        "Administrative function for configuring the liquidity mining rewards",
+        "emitted by a pool, creating the rewards account if needed.",
+        "",
+        "# Parameters",
+        "",
+        "* `config` - The rates and end time for the emission of rewards.",
+        "",
+        "# [Accounts](jet_margin_pool::accounts::ConfigureRewards)",
+        "",
+        "|     |     |     |",
+        "| --- | --- | --- |",
+        "| **Name** | **Type** | **Description** |",
+        "| `authority` | `Signer` | The administrative authority. |",
+        "| `margin_pool` | `writable` | The pool emitting the rewards. |",
+        "| `reward_mint` | `read_only` | The mint for the reward token. |",
+        "| `pool_rewards` | `writable` | The account tracking the rewards emitted by the pool. |",
+        "| `reward_vault` | `writable` | The token account holding the rewards to be claimed. |",
+        "| `payer` | `Signer` | The payer of rent for new accounts. |",
+        "| `token_program` | `read_only` | The [spl token program](https://spl.solana.com/token). |",
+        "| `system_program` | `read_only` | The [system native program](https://docs.solana.com/developing/runtime-facilities/programs#system-program). |",
+        "| `rent` | `read_only` | The [rent sysvar](https://docs.solana.com/developing/runtime-facilities/sysvars#rent). |",
+        "",
+        "# Events",
+        "",
+        "|     |     |",
+        "| --- | --- |",
+        "| **Event Name** | **Description** |",
+        "| [`events::RewardsConfigured`] | The new rewards config. |",
+        ""
+      ],
+      accounts: [
+        {
+          name: "authority",
+          isMut: false,
+          isSigner: true,
+          docs: ["The administrative authority"]
+        },
+        {
+          name: "marginPool",
+          isMut: true,
+          isSigner: false,
+          docs: ["The pool emitting the rewards"]
+        },
+        {
+          name: "rewardMint",
+          isMut: false,
+          isSigner: false,
+          docs: ["The mint for the reward token"]
+        },
+        {
+          name: "poolRewards",
+          isMut: true,
+          isSigner: false,
+          docs: ["The account tracking the rewards emitted by the pool"]
+        },
+        {
+          name: "rewardVault",
+          isMut: true,
+          isSigner: false,
+          docs: ["The token account holding the rewards to be claimed"]
+        },
+        {
+          name: "payer",
+          isMut: true,
+          isSigner: true,
+          docs: ["The payer of rent for new accounts"]
+        },
+        {
+          name: "tokenProgram",
+          isMut: false,
+          isSigner: false
+        },
+        {
+          name: "systemProgram",
+          isMut: false,
+          isSigner: false
+        },
+        {
+          name: "rent",
+          isMut: false,
+          isSigner: false
+        }
+      ],
+      args: [
+        {
+          name: "config",
+          type: {
+            defined: "RewardsConfig"
+          }
+        }
+      ]
+    },
+    {
+      name: "registerRewardsPosition",
+      docs: [
+        "Start tracking the liquidity mining rewards earned by a margin account.",
+        "",
+        "Once registered, the rewards are checkpointed by every instruction that",
+        "changes the notes held by the margin account. While the pool has rewards",
+        "configured, those instructions require the `pool_rewards` account followed",
+        "by the `rewards_position` of each margin account whose notes change as",
+        "their remaining accounts. `repay` and `admin_transfer_loan` also require",
+        "the margin accounts owning the loans after the positions.",
+        "",
+        "# [Accounts](jet_margin_pool::accounts::RegisterRewardsPosition)",
+        "",
+        "|     |     |     |",
+        "| --- | --- | --- |",
+        "| **Name** | **Type** | **Description** |",
+        "| `margin_account` | `read_only` | The margin account that will earn rewards. |",
+        "| `margin_pool` | `read_only` | The pool emitting the rewards. |",
+        "| `pool_rewards` | `writable` | The rewards emitted by the pool. |",
+        "| `rewards_position` | `writable` | The account to track the rewards earned by the margin account. |",
+        "| `payer` | `Signer` | The payer of rent for the new account. |",
+        "| `system_program` | `read_only` | The [system native program](https://docs.solana.com/developing/runtime-facilities/programs#system-program). |",
+        ""
+      ],
+      accounts: [
+        {
+          name: "marginAccount",
+          isMut: false,
+          isSigner: false,
+          docs: ["The margin account that will earn rewards"]
+        },
+        {
+          name: "marginPool",
+          isMut: false,
+          isSigner: false,
+          docs: ["The pool emitting the rewards"]
+        },
+        {
+          name: "poolRewards",
+          isMut: true,
+          isSigner: false,
+          docs: ["The rewards emitted by the pool"]
+        },
+        {
+          name: "rewardsPosition",
+          isMut: true,
+          isSigner: false,
+          docs: ["The account to track the rewards earned by the margin account"]
+        },
+        {
+          name: "payer",
+          isMut: true,
+          isSigner: true,
+          docs: ["The payer of rent for the new account"]
+        },
+        {
+          name: "systemProgram",
+          isMut: false,
+          isSigner: false
+        }
+      ],
+      args: []
+    },
+    {
+      name: "claimRewards",
+      docs: [
+        "Claim the liquidity mining rewards earned by a margin account",
+        "",
+        "# [Accounts](jet_margin_pool::accounts::ClaimRewards)",
+        "",
+        "|     |     |     |",
+        "| --- | --- | --- |",
+        "| **Name** | **Type** | **Description** |",
+        "| `margin_account` | `Signer` | The margin account that earned the rewards. |",
+        "| `margin_pool` | `read_only` | The pool emitting the rewards. |",
+        "| `pool_rewards` | `writable` | The rewards emitted by the pool. |",
+        "| `rewards_position` | `writable` | The account tracking the rewards earned by the margin account. |",
+        "| `reward_vault` | `writable` | The token account holding the rewards to be claimed. |",
+        "| `destination` | `writable` | The account to receive the rewards. |",
+        "| `token_program` | `read_only` | The [spl token program](https://spl.solana.com/token). |",
+        "",
+        "# Events",
+        "",
+        "|     |     |",
+        "| --- | --- |",
+        "| **Event Name** | **Description** |",
+        "| [`events::RewardsClaimed`] | The rewards claimed. |",
+        ""
+      ],
+      accounts: [
+        {
+          name: "marginAccount",
+          isMut: false,
+          isSigner: true,
+          docs: ["The margin account that earned the rewards"]
+        },
+        {
+          name: "marginPool",
+          isMut: false,
+          isSigner: false,
+          docs: ["The pool emitting the rewards"]
+        },
+        {
+          name: "poolRewards",
+          isMut: true,
+          isSigner: false,
+          docs: ["The rewards emitted by the pool"]
+        },
+        {
+          name: "rewardsPosition",
+          isMut: true,
+          isSigner: false,
+          docs: ["The account tracking the rewards earned by the margin account"]
+        },
+        {
+          name: "rewardVault",
+          isMut: true,
+          isSigner: false,
+          docs: ["The token account holding the rewards to be claimed"]
+        },
+        {
+          name: "destination",
+          isMut: true,
+          isSigner: false,
+          docs: ["The account to receive the rewards"]
+        },
+        {
+          name: "tokenProgram",
+          isMut: false,
+          isSigner: false
+        }
+      ],
+      args: []
     }
   ],
   accounts: [
+    {
+      name: "PoolRewards",
+      docs: [
+        "Account tracking the liquidity mining rewards emitted by a pool.",
+        "",
+        "Rewards are tracked as an index of the reward tokens earned per note, which",
+        "is advanced whenever the notes in the pool change."
+      ],
+      type: {
+        kind: "struct",
+        fields: [
+          {
+            name: "marginPool",
+            docs: ["The pool emitting the rewards"],
+            type: "publicKey"
+          },
+          {
+            name: "bump",
+            docs: ["The bump seed used to create this address"],
+            type: {
+              array: ["u8", 1]
+            }
+          },
+          {
+            name: "rewardMint",
+            docs: ["The mint for the reward token"],
+            type: "publicKey"
+          },
+          {
+            name: "rewardVault",
+            docs: ["The token account holding the rewards to be claimed"],
+            type: "publicKey"
+          },
+          {
+            name: "config",
+            docs: ["The current emission parameters"],
+            type: {
+              defined: "RewardsConfig"
+            }
+          },
+          {
+            name: "depositIndex",
+            docs: ["The rewards emitted per deposit note, up to `accrued_until`"],
+            type: {
+              array: ["u8", 24]
+            }
+          },
+          {
+            name: "loanIndex",
+            docs: ["The rewards emitted per loan note, up to `accrued_until`"],
+            type: {
+              array: ["u8", 24]
+            }
+          },
+          {
+            name: "accruedUntil",
+            docs: ["The time the rewards were last accrued up to"],
+            type: "i64"
+          }
+        ]
+      }
+    },
+    {
+      name: "RewardsPosition",
+      docs: ["Account tracking the rewards earned by a margin account from a pool"],
+      type: {
+        kind: "struct",
+        fields: [
+          {
+            name: "poolRewards",
+            docs: ["The rewards being earned"],
+            type: "publicKey"
+          },
+          {
+            name: "marginAccount",
+            docs: ["The margin account earning the rewards"],
+            type: "publicKey"
+          },
+          {
+            name: "depositAccount",
+            docs: ["The token account holding the margin account's deposit notes"],
+            type: "publicKey"
+          },
+          {
+            name: "loanAccount",
+            docs: ["The token account holding the margin account's loan notes"],
+            type: "publicKey"
+          },
+          {
+            name: "depositNotes",
+            docs: ["The deposit notes held at the last checkpoint"],
+            type: "u64"
+          },
+          {
+            name: "loanNotes",
+            docs: ["The loan notes held at the last checkpoint"],
+            type: "u64"
+          },
+          {
+            name: "depositIndex",
+            docs: ["The pool's deposit reward index at the last checkpoint"],
+            type: {
+              array: ["u8", 24]
+            }
+          },
+          {
+            name: "loanIndex",
+            docs: ["The pool's loan reward index at the last checkpoint"],
+            type: {
+              array: ["u8", 24]
+            }
+          },
+          {
+            name: "unclaimed",
+            docs: ["The rewards earned that have not been claimed yet"],
+            type: {
+              array: ["u8", 24]
+            }
+          }
+        ]
+      }
+    },
     {
       name: "marginPool",
       type: {
@@ -613,46 +1180,116 @@ export const IDL: JetMarginPoolIDL = {
         ]
       }
     },
+    {
+      name: "RewardsConfig",
+      docs: ["Parameters for the emission of liquidity mining rewards from a pool"],
+      type: {
+        kind: "struct",
+        fields: [
+          {
+            name: "depositRate",
+            docs: ["The amount of reward tokens emitted per second, shared between all depositors"],
+            type: "u64"
+          },
+          {
+            name: "loanRate",
+            docs: ["The amount of reward tokens emitted per second, shared between all borrowers"],
+            type: "u64"
+          },
+          {
+            name: "endAt",
+            docs: ["The time at which the emission of rewards stops"],
+            type: "i64"
+          }
+        ]
+      }
+    },
+    {
+      name: "BadDebt",
+      docs: ["The result of writing off bad debt from a pool"],
+      type: {
+        kind: "struct",
+        fields: [
+          {
+            name: "notes",
+            docs: ["The loan notes written off"],
+            type: "u64"
+          },
+          {
+            name: "tokens",
+            docs: ["The tokens owed for the loan notes"],
+            type: "u64"
+          },
+          {
+            name: "feesAbsorbed",
+            docs: ["The tokens covered by fees that hadn't been collected yet"],
+            type: "u64"
+          },
+          {
+            name: "depositorLoss",
+            docs: ["The tokens lost by depositors"],
+            type: "u64"
+          }
+        ]
+      }
+    },
     {
       name: "MarginPoolConfig",
+      docs: ["Configuration for a margin pool"],
       type: {
         kind: "struct",
         fields: [
           {
             name: "flags",
+            docs: ["Space for binary settings"],
             type: "u64"
           },
           {
             name: "utilizationRate1",
+            docs: ["The utilization rate at which first regime transitions to second"],
             type: "u16"
           },
           {
             name: "utilizationRate2",
+            docs: ["The utilization rate at which second regime transitions to third"],
             type: "u16"
           },
           {
             name: "borrowRate0",
+            docs: ["The lowest borrow rate"],
             type: "u16"
           },
           {
             name: "borrowRate1",
+            docs: ["The borrow rate at the transition point from first to second regime"],
             type: "u16"
           },
           {
             name: "borrowRate2",
+            docs: ["The borrow rate at the transition point from second to third regime"],
             type: "u16"
           },
           {
             name: "borrowRate3",
+            docs: ["The highest possible borrow rate."],
             type: "u16"
           },
           {
             name: "managementFeeRate",
+            docs: ["The fee rate applied to interest payments collected"],
             type: "u16"
           },
           {
-            name: "managementFeeCollectThreshold",
-            type: "u64"
+            name: "flashLoanFeeRate",
+            docs: ["The fee rate charged on flash loans (bps)"],
+            type: "u16"
+          },
+          {
+            name: "reserved",
+            docs: ["Unused"],
+            type: {
+              array: ["u8", 6]
+            }
           }
         ]
       }
@@ -1092,6 +1729,144 @@ export const IDL: JetMarginPoolIDL = {
           index: false
         }
       ]
+    },
+    {
+      name: "BadDebtWrittenOff",
+      fields: [
+        {
+          name: "marginPool",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "marginAccount",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "loanAccount",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "badDebt",
+          type: {
+            defined: "BadDebt"
+          },
+          index: false
+        },
+        {
+          name: "summary",
+          type: {
+            defined: "MarginPoolSummary"
+          },
+          index: false
+        }
+      ]
+    },
+    {
+      name: "RewardsConfigured",
+      fields: [
+        {
+          name: "marginPool",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "poolRewards",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "rewardMint",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "config",
+          type: {
+            defined: "RewardsConfig"
+          },
+          index: false
+        }
+      ]
+    },
+    {
+      name: "RewardsClaimed",
+      fields: [
+        {
+          name: "marginPool",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "marginAccount",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "destination",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "amount",
+          type: "u64",
+          index: false
+        }
+      ]
+    },
+    {
+      name: "FlashLoanBegin",
+      fields: [
+        {
+          name: "marginPool",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "destination",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "amount",
+          type: "u64",
+          index: false
+        }
+      ]
+    },
+    {
+      name: "FlashLoanEnd",
+      fields: [
+        {
+          name: "marginPool",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "repaymentTokenAccount",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "amount",
+          type: "u64",
+          index: false
+        },
+        {
+          name: "fee",
+          type: "u64",
+          index: false
+        },
+        {
+          name: "summary",
+          type: {
+            defined: "MarginPoolSummary"
+          },
+          index: false
+        }
+      ]
     }
   ],
   errors: [
@@ -1136,6 +1911,41 @@ export const IDL: JetMarginPoolIDL = {
     {
       code: 141108,
       name: "RepaymentExceedsTotalOutstanding"
+    },
+    {
+      code: 141109,
+      name: "ExceedsMaxBorrowUtilRatio",
+      msg: "This borrow pushes the pool util ratio above the limit for new borrows"
+    },
+    {
+      code: 141110,
+      name: "InvalidRewardsAccount",
+      msg: "The rewards accounts do not belong to the pool"
+    },
+    {
+      code: 141111,
+      name: "MissingDepositPosition",
+      msg: "The margin account has no deposit position in the pool"
+    },
+    {
+      code: 141112,
+      name: "AccountNotInsolvent",
+      msg: "The margin account is not insolvent"
+    },
+    {
+      code: 141113,
+      name: "FlashLoanNotRepaid",
+      msg: "A flash loan must be repaid in the same transaction"
+    },
+    {
+      code: 141114,
+      name: "FlashLoanInvokedByProgram",
+      msg: "Flash loans cannot be invoked by other programs"
+    },
+    {
+      code: 141115,
+      name: "MissingRewardsAccounts",
+      msg: "The rewards accounts are required for this pool"
     }
   ]
 }
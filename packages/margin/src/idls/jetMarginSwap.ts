@@ -159,32 +159,474 @@ export const IDL: JetMarginSwapIDL = {
           isSigner: false
         },
         {
-          name: "vaultInto",
+          name: "vaultA",
           isMut: true,
           isSigner: false
         },
         {
-          name: "vaultFrom",
+          name: "vaultB",
           isMut: true,
           isSigner: false
         },
         {
-          name: "adminFeeDestination",
+          name: "adminFeeA",
           isMut: true,
           isSigner: false
         },
+        {
+          name: "adminFeeB",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "swapProgram",
+          isMut: false,
+          isSigner: false,
+          docs: ["The address of the swap program"]
+        }
+      ],
+      args: []
+    },
+    {
+      name: "openbookSwap",
+      accounts: [
+        {
+          name: "market",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "openOrders",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "requestQueue",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "eventQueue",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "marketBids",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "marketAsks",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "baseVault",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "quoteVault",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "quoteMint",
+          isMut: false,
+          isSigner: false
+        },
+        {
+          name: "vaultSigner",
+          isMut: false,
+          isSigner: false
+        },
+        {
+          name: "referrerAccount",
+          isMut: true,
+          isSigner: false,
+          docs: ["The referrer account owned by the control program"]
+        },
+        {
+          name: "dexProgram",
+          isMut: false,
+          isSigner: false,
+          docs: ["The address of the swap program"]
+        },
+        {
+          name: "rent",
+          isMut: false,
+          isSigner: false
+        }
+      ],
+      args: []
+    },
+    {
+      name: "whirlpoolSwap",
+      accounts: [
+        {
+          name: "swapProgram",
+          isMut: false,
+          isSigner: false,
+          docs: ["The address of the swap program"]
+        },
+        {
+          name: "whirlpool",
+          isMut: true,
+          isSigner: false,
+          docs: ["The following accounts relevant to the swap pool used for the exchange"]
+        },
+        {
+          name: "vaultA",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "vaultB",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "tickArray0",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "tickArray1",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "tickArray2",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "oracle",
+          isMut: false,
+          isSigner: false
+        }
+      ],
+      args: []
+    },
+    {
+      name: "phoenixSwap",
+      docs: ["Swap using a Phoenix order book"],
+      accounts: [
         {
           name: "swapProgram",
           isMut: false,
           isSigner: false,
           docs: ["The address of the swap program"]
+        },
+        {
+          name: "logAuthority",
+          isMut: false,
+          isSigner: false,
+          docs: ["The authority that phoenix logs market events with"]
+        },
+        {
+          name: "market",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "baseVault",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "quoteVault",
+          isMut: true,
+          isSigner: false
+        }
+      ],
+      args: []
+    },
+    {
+      name: "initOpenbookOpenOrders",
+      accounts: [
+        {
+          name: "marginAccount",
+          isMut: false,
+          isSigner: true,
+          docs: ["The margin account with the position to close"]
+        },
+        {
+          name: "market",
+          isMut: false,
+          isSigner: false
+        },
+        {
+          name: "payer",
+          isMut: true,
+          isSigner: true,
+          docs: ["The address paying for rent"]
+        },
+        {
+          name: "openOrders",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "dexProgram",
+          isMut: false,
+          isSigner: false
+        },
+        {
+          name: "systemProgram",
+          isMut: false,
+          isSigner: false
+        },
+        {
+          name: "rent",
+          isMut: false,
+          isSigner: false
+        }
+      ],
+      args: []
+    },
+    {
+      name: "closeOpenbookOpenOrders",
+      accounts: [
+        {
+          name: "marginAccount",
+          isMut: false,
+          isSigner: true
+        },
+        {
+          name: "openOrders",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "destination",
+          isMut: true,
+          isSigner: false,
+          docs: ["The destination account to send SOL to"]
+        },
+        {
+          name: "market",
+          isMut: false,
+          isSigner: false
+        },
+        {
+          name: "dexProgram",
+          isMut: false,
+          isSigner: false
+        }
+      ],
+      args: []
+    },
+    {
+      name: "createConditionalOrder",
+      docs: [
+        "Create an order that any keeper may execute on behalf of the margin account",
+        "once the price of an oracle reaches the trigger.",
+        "",
+        "The `keeper_fee` (lamports) is paid by the payer, and is sent to the keeper",
+        "along with the rent of the order when it is executed."
+      ],
+      accounts: [
+        {
+          name: "marginAccount",
+          isMut: false,
+          isSigner: true,
+          docs: ["The margin account that the order is for"]
+        },
+        {
+          name: "payer",
+          isMut: true,
+          isSigner: true,
+          docs: ["The address paying for rent and the keeper fee"]
+        },
+        {
+          name: "order",
+          isMut: true,
+          isSigner: false,
+          docs: ["The order being created"]
+        },
+        {
+          name: "priceOracle",
+          isMut: false,
+          isSigner: false,
+          docs: ["The pyth price account that the order is triggered by"]
+        },
+        {
+          name: "systemProgram",
+          isMut: false,
+          isSigner: false
+        }
+      ],
+      args: [
+        {
+          name: "seed",
+          type: "u16"
+        },
+        {
+          name: "trigger",
+          type: {
+            defined: "OrderTrigger"
+          }
+        },
+        {
+          name: "action",
+          type: {
+            defined: "OrderAction"
+          }
+        },
+        {
+          name: "keeperFee",
+          type: "u64"
+        }
+      ]
+    },
+    {
+      name: "cancelConditionalOrder",
+      docs: ["Cancel a conditional order, returning its lamports to the destination"],
+      accounts: [
+        {
+          name: "marginAccount",
+          isMut: false,
+          isSigner: true,
+          docs: ["The margin account that the order is for"]
+        },
+        {
+          name: "order",
+          isMut: true,
+          isSigner: false,
+          docs: ["The order being cancelled"]
+        },
+        {
+          name: "destination",
+          isMut: true,
+          isSigner: false,
+          docs: ["The destination account to send SOL to"]
+        }
+      ],
+      args: []
+    },
+    {
+      name: "keeperExecute",
+      docs: [
+        "Execute a conditional order whose trigger has been reached.",
+        "",
+        "This is invoked by a keeper through the margin program's `keeper_invoke`, and",
+        "`swap_routes` is only used by swap orders."
+      ],
+      accounts: [
+        {
+          name: "marginAccount",
+          isMut: false,
+          isSigner: true,
+          docs: ["The margin account that the order is for"]
+        },
+        {
+          name: "keeper",
+          isMut: true,
+          isSigner: true,
+          docs: ["The keeper executing the order, which receives its lamports"]
+        },
+        {
+          name: "order",
+          isMut: true,
+          isSigner: false,
+          docs: ["The order being executed"]
+        },
+        {
+          name: "priceOracle",
+          isMut: false,
+          isSigner: false,
+          docs: ["The pyth price account that the order is triggered by"]
+        },
+        {
+          name: "riskConfig",
+          isMut: false,
+          isSigner: false,
+          docs: [
+            "The risk parameters for the airspace of the margin account, which may be",
+            "uninitialized if the airspace uses the defaults"
+          ]
+        }
+      ],
+      args: [
+        {
+          name: "swapRoutes",
+          type: {
+            array: [
+              {
+                defined: "SwapRouteDetail"
+              },
+              3
+            ]
+          }
+        }
+      ]
+    },
+    {
+      name: "routeSwap",
+      docs: [
+        "Route a swap to one or more venues",
+        "",
+        "If `max_price_deviation` (bps) is set, the value of the tokens received is checked",
+        "against the value of the tokens swapped, using the prices of the margin account's",
+        "positions. Liquidation swaps are always checked, with a tolerance of at most the",
+        "airspace's [liquidation_max_price_deviation](jet_margin::RiskParameters).",
+        "",
+        "`is_liquidation` must be set if and only if the margin account is being liquidated."
+      ],
+      accounts: [
+        {
+          name: "marginAccount",
+          isMut: false,
+          isSigner: true,
+          docs: ["The margin account being executed on"]
+        },
+        {
+          name: "tokenProgram",
+          isMut: false,
+          isSigner: false
+        }
+      ],
+      args: [
+        {
+          name: "amountIn",
+          type: "u64"
+        },
+        {
+          name: "minimumAmountOut",
+          type: "u64"
+        },
+        {
+          name: "swapRoutes",
+          type: {
+            array: [
+              {
+                defined: "SwapRouteDetail"
+              },
+              3
+            ]
+          }
+        },
+        {
+          name: "isLiquidation",
+          type: "bool"
+        },
+        {
+          name: "maxPriceDeviation",
+          type: {
+            option: "u16"
+          }
         }
-      ],
-      args: []
+      ]
     },
     {
-      name: "routeSwap",
-      docs: ["Route a swap to one or more venues"],
+      name: "routeSwapPool",
+      docs: [
+        "Route a swap to one or more venues by using margin pools",
+        "",
+        "The oracle price check is the same as in `route_swap`, and compares the value of the",
+        "pool deposit notes withdrawn and deposited."
+      ],
       accounts: [
         {
           name: "marginAccount",
@@ -207,24 +649,6 @@ export const IDL: JetMarginSwapIDL = {
             "The swap is also atomic, and no excess funds would be taken/left in the account."
           ]
         },
-        {
-          name: "transitSourceAccount",
-          isMut: true,
-          isSigner: false,
-          docs: [
-            "Temporary account for moving tokens",
-            "The swap is also atomic, and no excess funds would be taken/left in the account."
-          ]
-        },
-        {
-          name: "transitDestinationAccount",
-          isMut: true,
-          isSigner: false,
-          docs: [
-            "Temporary account for moving tokens",
-            "The swap is also atomic, and no excess funds would be taken/left in the account."
-          ]
-        },
         {
           name: "sourceMarginPool",
           accounts: [
@@ -301,11 +725,138 @@ export const IDL: JetMarginSwapIDL = {
               3
             ]
           }
+        },
+        {
+          name: "isLiquidation",
+          type: "bool"
+        },
+        {
+          name: "maxPriceDeviation",
+          type: {
+            option: "u16"
+          }
         }
       ]
+    },
+    {
+      name: "splTokenSwap",
+      accounts: [
+        {
+          name: "swapPool",
+          isMut: false,
+          isSigner: false
+        },
+        {
+          name: "authority",
+          isMut: false,
+          isSigner: false
+        },
+        {
+          name: "vaultA",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "vaultB",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "tokenMint",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "feeAccount",
+          isMut: true,
+          isSigner: false
+        },
+        {
+          name: "swapProgram",
+          isMut: false,
+          isSigner: false,
+          docs: ["The address of the swap program"]
+        }
+      ],
+      args: []
+    }
+  ],
+  accounts: [
+    {
+      name: "ConditionalOrder",
+      docs: [
+        "An action that a keeper may take on behalf of a margin account once the price",
+        "of an oracle crosses a threshold.",
+        "",
+        "The order is closed when it is executed, and its lamports are paid to the keeper."
+      ],
+      type: {
+        kind: "struct",
+        fields: [
+          {
+            name: "marginAccount",
+            docs: ["The margin account that the order acts on"],
+            type: "publicKey"
+          },
+          {
+            name: "seed",
+            docs: ["The seed used to derive the order address"],
+            type: "u16"
+          },
+          {
+            name: "priceOracle",
+            docs: ["The pyth price account that is checked against the trigger"],
+            type: "publicKey"
+          },
+          {
+            name: "trigger",
+            docs: ["The price that allows the order to be executed"],
+            type: {
+              defined: "OrderTrigger"
+            }
+          },
+          {
+            name: "action",
+            docs: ["What is done when the order is executed"],
+            type: {
+              defined: "OrderAction"
+            }
+          },
+          {
+            name: "keeperFee",
+            docs: ["The lamports paid to the keeper in addition to the rent of the order"],
+            type: "u64"
+          }
+        ]
+      }
     }
   ],
   types: [
+    {
+      name: "OrderTrigger",
+      type: {
+        kind: "struct",
+        fields: [
+          {
+            name: "kind",
+            docs: ["Which side of the price the oracle has to reach"],
+            type: {
+              defined: "TriggerKind"
+            }
+          },
+          {
+            name: "price",
+            docs: ["The trigger price, in the same units as the oracle"],
+            type: "i64"
+          },
+          {
+            name: "exponent",
+            docs: ["The exponent of the trigger price"],
+            type: "i32"
+          }
+        ]
+      }
+    },
     {
       name: "SwapRouteDetail",
       type: {
@@ -334,6 +885,54 @@ export const IDL: JetMarginSwapIDL = {
         ]
       }
     },
+    {
+      name: "TriggerKind",
+      type: {
+        kind: "enum",
+        variants: [
+          {
+            name: "Below"
+          },
+          {
+            name: "Above"
+          }
+        ]
+      }
+    },
+    {
+      name: "OrderAction",
+      type: {
+        kind: "enum",
+        variants: [
+          {
+            name: "Swap",
+            fields: [
+              {
+                name: "source_pool",
+                type: "publicKey"
+              },
+              {
+                name: "destination_pool",
+                type: "publicKey"
+              },
+              {
+                name: "max_slippage_bps",
+                type: "u16"
+              }
+            ]
+          },
+          {
+            name: "Repay",
+            fields: [
+              {
+                name: "pool",
+                type: "publicKey"
+              }
+            ]
+          }
+        ]
+      }
+    },
     {
       name: "SwapRouteIdentifier",
       type: {
@@ -350,6 +949,12 @@ export const IDL: JetMarginSwapIDL = {
           },
           {
             name: "SaberStable"
+          },
+          {
+            name: "OpenBook"
+          },
+          {
+            name: "Phoenix"
           }
         ]
       }
@@ -369,31 +974,236 @@ export const IDL: JetMarginSwapIDL = {
       }
     }
   ],
+  events: [
+    {
+      name: "RouteSwapped",
+      fields: [
+        {
+          name: "marginAccount",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "tokenIn",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "amountIn",
+          type: "u64",
+          index: false
+        },
+        {
+          name: "amountOut",
+          type: "u64",
+          index: false
+        },
+        {
+          name: "liquidationFees",
+          type: "u64",
+          index: false
+        },
+        {
+          name: "routes",
+          type: {
+            array: [
+              {
+                defined: "SwapRouteDetail"
+              },
+              3
+            ]
+          },
+          index: false
+        }
+      ]
+    },
+    {
+      name: "ConditionalOrderCreated",
+      fields: [
+        {
+          name: "marginAccount",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "order",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "priceOracle",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "trigger",
+          type: {
+            defined: "OrderTrigger"
+          },
+          index: false
+        },
+        {
+          name: "action",
+          type: {
+            defined: "OrderAction"
+          },
+          index: false
+        },
+        {
+          name: "keeperFee",
+          type: "u64",
+          index: false
+        }
+      ]
+    },
+    {
+      name: "ConditionalOrderCancelled",
+      fields: [
+        {
+          name: "marginAccount",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "order",
+          type: "publicKey",
+          index: false
+        }
+      ]
+    },
+    {
+      name: "ConditionalOrderExecuted",
+      fields: [
+        {
+          name: "marginAccount",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "order",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "keeper",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "oraclePrice",
+          type: "i64",
+          index: false
+        },
+        {
+          name: "oracleExponent",
+          type: "i32",
+          index: false
+        }
+      ]
+    },
+    {
+      name: "ProtocolSwapFee",
+      fields: [
+        {
+          name: "venue",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "mint",
+          type: "publicKey",
+          index: false
+        },
+        {
+          name: "amount",
+          type: "u64",
+          index: false
+        },
+        {
+          name: "venueIdentifier",
+          type: {
+            defined: "SwapRouteIdentifier"
+          },
+          index: false
+        }
+      ]
+    }
+  ],
   errors: [
     {
-      code: 6000,
+      code: 143000,
       name: "NoSwapTokensWithdrawn",
       msg: "Zero tokens have been withdrawn from a pool for the swap"
     },
     {
-      code: 6001,
+      code: 143001,
       name: "InvalidSwapRoute",
       msg: "An invalid swap route has been provided"
     },
     {
-      code: 6002,
+      code: 143002,
       name: "InvalidSwapRouteParam",
       msg: "An invalid swap route parameter has been provided"
     },
     {
-      code: 6003,
+      code: 143003,
       name: "SlippageExceeded",
       msg: "The swap exceeds the maximum slippage tolerance"
     },
     {
-      code: 6004,
+      code: 143004,
       name: "DisallowedDirectInstruction",
       msg: "The instruction should not be called directly, use route_swap"
+    },
+    {
+      code: 143005,
+      name: "InvalidSplitDestination",
+      msg: "Token swaps having a split should deposit into the same account"
+    },
+    {
+      code: 143006,
+      name: "InvalidLiquidator",
+      msg: "Invalid liquidator on a liquidation swap"
+    },
+    {
+      code: 143007,
+      name: "InvalidFeeDestination",
+      msg: "Invalid fee destination account due to an authority mismatch"
+    },
+    {
+      code: 143008,
+      name: "PriceDeviationExceeded",
+      msg: "The swap output deviates too far from the oracle prices"
+    },
+    {
+      code: 143009,
+      name: "PositionPriceUnavailable",
+      msg: "A valid position price is required to check the swap against the oracle"
+    },
+    {
+      code: 143010,
+      name: "InvalidConditionalOrder",
+      msg: "The conditional order parameters are invalid"
+    },
+    {
+      code: 143011,
+      name: "InvalidOrderOracle",
+      msg: "The conditional order oracle is invalid or stale"
+    },
+    {
+      code: 143012,
+      name: "OrderNotTriggered",
+      msg: "The oracle price has not reached the trigger of the conditional order"
+    },
+    {
+      code: 143013,
+      name: "InvalidOrderAccounts",
+      msg: "The accounts do not match the action of the conditional order"
+    },
+    {
+      code: 143014,
+      name: "InvalidRiskConfig",
+      msg: "The risk config is not the one for the margin account's airspace"
     }
   ]
 }
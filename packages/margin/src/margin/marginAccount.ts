@@ -298,6 +298,15 @@ export class MarginAccount {
     return findDerivedAccount(this.programs.config.marginProgramId, "token-config", this.airspace, tokenMint)
   }
 
+  /**
+   * Derive the address of the risk config account for the airspace.
+   *
+   * The account may not exist, in which case the margin program uses its default risk parameters.
+   */
+  findRiskConfigAddress(): PublicKey {
+    return findDerivedAccount(this.programs.config.marginProgramId, "risk-config", this.airspace)
+  }
+
   /**
    *
    * @param args
@@ -1110,7 +1119,7 @@ export class MarginAccount {
         }
         const issuerId = airspace.derivePermitIssuerId(this.owner)
         const permitIx = await this.programs.airspace.methods
-          .airspacePermitCreate(this.owner)
+          .airspacePermitCreate(this.owner, new BN(0), { lendOnly: false, maxBorrowValue: new BN(0) })
           .accounts({
             payer: this.provider.wallet.publicKey,
             authority: this.owner,
@@ -1413,6 +1422,7 @@ export class MarginAccount {
         rent: SYSVAR_RENT_PUBKEY,
         systemProgram: SystemProgram.programId
       })
+      .remainingAccounts([this.riskConfigAccount()])
       .instruction()
     instructions.push(ix)
     return tokenAccount
@@ -1455,6 +1465,7 @@ export class MarginAccount {
         rent: SYSVAR_RENT_PUBKEY,
         systemProgram: SystemProgram.programId
       })
+      .remainingAccounts([this.riskConfigAccount()])
       .instruction()
 
     instructions.push(ix)
@@ -1655,6 +1666,7 @@ export class MarginAccount {
         marginAccount: this.address,
         liquidation
       })
+      .remainingAccounts([this.riskConfigAccount()])
       .instruction()
     instructions.push(ix)
   }
@@ -1784,7 +1796,8 @@ export class MarginAccount {
   /**
    * prepares arguments for `adapter_invoke`, `account_invoke`, or `liquidator_invoke`
    *
-   * @return {AccountMeta[]} The instruction keys but the margin account is no longer a signer.
+   * @return {AccountMeta[]} The instruction keys but the margin account is no longer a signer,
+   * followed by the risk config for the airspace.
    * @memberof MarginAccount
    */
   private invokeAccounts(adapterInstruction: TransactionInstruction): AccountMeta[] {
@@ -1800,10 +1813,26 @@ export class MarginAccount {
         isWritable: acc.isWritable
       })
     }
+    accounts.push(this.riskConfigAccount())
 
     return accounts
   }
 
+  /**
+   * The risk config for the airspace, as the optional last account of margin instructions that
+   * value or liquidate the account.
+   *
+   * @return {AccountMeta}
+   * @memberof MarginAccount
+   */
+  private riskConfigAccount(): AccountMeta {
+    return {
+      pubkey: this.findRiskConfigAddress(),
+      isSigner: false,
+      isWritable: false
+    }
+  }
+
   /** Create an address lookup table registry account owned by the margin account.
    */
   async initLookupRegistry(): Promise<void> {
@@ -2019,6 +2048,7 @@ export class MarginAccount {
         config,
         priceOracle
       })
+      .remainingAccounts([this.riskConfigAccount()])
       .instruction()
     instructions.push(ix)
   }
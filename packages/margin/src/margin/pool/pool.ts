@@ -1202,6 +1202,8 @@ export class Pool {
    * @param `swapPool` - The SPL swap pool the exchange is taking place in.
    * @param `swapAmount` - The amount being swapped.
    * @param `minAmountOut` - The minimum output amount based on swapAmount and slippage.
+   * @param `maxPriceDeviation` - The largest loss in value (bps) allowed against the oracle prices
+   * of the margin account's positions, if the swap should be checked against them.
    */
   async routeSwap({
     endpoint,
@@ -1214,6 +1216,7 @@ export class Pool {
     swapPaths,
     markets,
     lookupTables,
+    maxPriceDeviation
   }: {
     endpoint: string
     marginAccount: MarginAccount
@@ -1225,6 +1228,7 @@ export class Pool {
     swapPaths: SwapPath[]
     markets: FixedTermMarket[]
     lookupTables: { address: string; data: Uint8Array }[]
+    maxPriceDeviation?: number
   }) {
     assert(marginAccount)
     assert(swapAmount)
@@ -1329,6 +1333,7 @@ export class Pool {
       minimum_out: minAmountOut.lamports.toNumber(),
       margin_pool_deposit_notes: true,
       is_liquidation: false,
+      max_price_deviation: maxPriceDeviation ?? null,
     })
 
     // If there are > 1 instructions, the first ones are setup instructions
@@ -3,8 +3,11 @@
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
+use serde_wasm_bindgen::Serializer;
 use wasm_bindgen::prelude::*;
 
+use crate::JsResult;
+
 pub const MARGIN_ACCOUNT_SETUP_LEVERAGE_FRACTION: f64 = 0.5;
 
 #[derive(Serialize, Deserialize)]
@@ -14,6 +17,15 @@ pub struct MarginAccountValuationInput {
     pub prices: HashMap<String, OraclePrice>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct LiquidationShockInput {
+    pub positions: HashMap<String, MarginPosition>,
+    pub changes: Vec<MarginPosition>,
+    pub prices: HashMap<String, OraclePrice>,
+    /// The relative change in price of each token, e.g. -0.3 for a 30% drop
+    pub shocks: HashMap<String, f64>,
+}
+
 #[wasm_bindgen]
 #[derive(Clone)]
 pub struct MarginAccountValuation {
@@ -74,11 +86,125 @@ impl MarginAccountValuation {
         }
     }
 
+    /// The price of each token at which the account can be liquidated, while the
+    /// prices of all other tokens stay the same. Tokens whose price can not make the
+    /// account liquidatable are left out.
+    #[wasm_bindgen(js_name = liquidationPrices)]
+    pub fn liquidation_prices_js(val: JsValue) -> JsResult {
+        let MarginAccountValuationInput {
+            positions,
+            changes,
+            prices,
+        } = serde_wasm_bindgen::from_value(val)?;
+        let liquidation_prices = Self::liquidation_prices(positions, changes, &prices);
+
+        Ok(liquidation_prices.serialize(&Serializer::json_compatible())?)
+    }
+
+    /// How much of a combined price shock can be applied before the account can be
+    /// liquidated, e.g. 0.5 if the account is liquidatable after half of each price
+    /// change. Returns undefined if the shock never makes the account liquidatable.
+    #[wasm_bindgen(js_name = liquidationShockScale)]
+    pub fn liquidation_shock_scale_js(val: JsValue) -> Result<Option<f64>, JsError> {
+        let LiquidationShockInput {
+            positions,
+            changes,
+            prices,
+            shocks,
+        } = serde_wasm_bindgen::from_value(val)?;
+
+        Ok(Self::liquidation_shock_scale(
+            positions, changes, &prices, &shocks,
+        ))
+    }
+
+    fn liquidation_prices(
+        positions: HashMap<String, MarginPosition>,
+        changes: Vec<MarginPosition>,
+        prices: &HashMap<String, OraclePrice>,
+    ) -> HashMap<String, f64> {
+        let positions = Self::apply_changes(positions, changes);
+        let valuation = Self::value_positions(&positions, prices);
+
+        Self::price_exposures(&positions, prices)
+            .into_iter()
+            .filter_map(|(token, exposure)| {
+                if exposure == 0.0 {
+                    return None;
+                }
+                let ratio = 1.0 - valuation.available_collateral / exposure;
+                if ratio <= 0.0 {
+                    return None;
+                }
+                let price = prices.get(&token)?.price * ratio;
+
+                Some((token, price))
+            })
+            .collect()
+    }
+
+    fn liquidation_shock_scale(
+        positions: HashMap<String, MarginPosition>,
+        changes: Vec<MarginPosition>,
+        prices: &HashMap<String, OraclePrice>,
+        shocks: &HashMap<String, f64>,
+    ) -> Option<f64> {
+        let positions = Self::apply_changes(positions, changes);
+        let valuation = Self::value_positions(&positions, prices);
+        let exposures = Self::price_exposures(&positions, prices);
+
+        let sensitivity: f64 = shocks
+            .iter()
+            .filter_map(|(token, change)| Some(exposures.get(token)? * change))
+            .sum();
+        if sensitivity == 0.0 {
+            return None;
+        }
+
+        let scale = -valuation.available_collateral / sensitivity;
+        // Prices can not fall below zero, so a larger shock is not possible
+        let in_range = shocks.values().all(|change| 1.0 + change * scale > 0.0);
+
+        (scale >= 0.0 && in_range).then_some(scale)
+    }
+
+    /// The change in available collateral for each 100% change in the price of each
+    /// token
+    fn price_exposures(
+        positions: &HashMap<String, MarginPosition>,
+        prices: &HashMap<String, OraclePrice>,
+    ) -> HashMap<String, f64> {
+        let mut exposures = HashMap::new();
+        for position in positions.values() {
+            let Some(price) = prices.get(&position.token) else {
+                continue;
+            };
+            let value = position.balance as f64 * 10.0_f64.powi(position.exponent) * price.price;
+            let exposure = match &position.position_kind {
+                1 | 3 => value * position.value_modifier,
+                2 => -(value + value / position.value_modifier),
+                _ => continue,
+            };
+            *exposures.entry(position.token.clone()).or_default() += exposure;
+        }
+
+        exposures
+    }
+
     fn value(
         positions: HashMap<String, MarginPosition>,
         changes: Vec<MarginPosition>,
         prices: &HashMap<String, OraclePrice>,
     ) -> Self {
+        let updated = Self::apply_changes(positions, changes);
+
+        Self::value_positions(&updated, prices)
+    }
+
+    fn apply_changes(
+        positions: HashMap<String, MarginPosition>,
+        changes: Vec<MarginPosition>,
+    ) -> HashMap<String, MarginPosition> {
         // Update positions with changes
         let mut updated = positions;
         for change in changes {
@@ -89,6 +215,14 @@ impl MarginAccountValuation {
                 updated.insert(change.address.clone(), change);
             }
         }
+
+        updated
+    }
+
+    fn value_positions(
+        updated: &HashMap<String, MarginPosition>,
+        prices: &HashMap<String, OraclePrice>,
+    ) -> Self {
         let mut valuation = MarginAccountValuation {
             assets: 0.0,
             liabilities: 0.0,
@@ -270,4 +404,85 @@ mod tests {
         assert_eq!(valuation.total_positions, 3);
         assert!(valuation.risk_indicator() < 1.0);
     }
+
+    #[test]
+    fn test_liquidation_prices() {
+        // 10 SOL deposited at $20 with a 0.9 collateral weight, and 100 USDC borrowed
+        // with 10x max leverage
+        let positions = HashMap::from_iter([
+            (
+                "SOL-deposit".to_string(),
+                MarginPosition::new(
+                    "SOL-deposit".to_string(),
+                    "SOL".to_string(),
+                    10_000_000_000,
+                    -9,
+                    1,
+                    0.9,
+                ),
+            ),
+            (
+                "USDC-loan".to_string(),
+                MarginPosition::new(
+                    "USDC-loan".to_string(),
+                    "USDC".to_string(),
+                    100_000_000,
+                    -6,
+                    2,
+                    10.0,
+                ),
+            ),
+        ]);
+        let prices = HashMap::from_iter([
+            ("SOL".to_string(), OraclePrice { price: 20.0 }),
+            ("USDC".to_string(), OraclePrice { price: 1.0 }),
+        ]);
+
+        // 180 weighted collateral, less 100 debt and 10 required collateral
+        let liquidation_prices =
+            MarginAccountValuation::liquidation_prices(positions.clone(), vec![], &prices);
+        let sol_price = liquidation_prices["SOL"];
+        let usdc_price = liquidation_prices["USDC"];
+        assert!((sol_price - 110.0 / 9.0).abs() < 1e-9);
+        assert!((usdc_price - 180.0 / 110.0).abs() < 1e-9);
+
+        // The account is liquidated at the SOL price
+        let shocked = HashMap::from_iter([
+            ("SOL".to_string(), OraclePrice { price: sol_price }),
+            ("USDC".to_string(), OraclePrice { price: 1.0 }),
+        ]);
+        let valuation = MarginAccountValuation::value(positions.clone(), vec![], &shocked);
+        assert!(valuation.available_collateral.abs() < 1e-9);
+
+        // A drop in the USDC price reduces the debt, so the combined shock can go further
+        let sol_only = HashMap::from_iter([("SOL".to_string(), -0.5)]);
+        let sol_scale = MarginAccountValuation::liquidation_shock_scale(
+            positions.clone(),
+            vec![],
+            &prices,
+            &sol_only,
+        )
+        .unwrap();
+        assert!((sol_scale - 70.0 / 90.0).abs() < 1e-9);
+
+        let combined = HashMap::from_iter([("SOL".to_string(), -0.5), ("USDC".to_string(), -0.1)]);
+        let combined_scale = MarginAccountValuation::liquidation_shock_scale(
+            positions.clone(),
+            vec![],
+            &prices,
+            &combined,
+        )
+        .unwrap();
+        assert!((combined_scale - 70.0 / 79.0).abs() < 1e-9);
+
+        // A shock that only improves the account's health never liquidates it
+        let shocks = HashMap::from_iter([("SOL".to_string(), 0.5)]);
+        assert!(MarginAccountValuation::liquidation_shock_scale(
+            positions,
+            vec![],
+            &prices,
+            &shocks
+        )
+        .is_none());
+    }
 }
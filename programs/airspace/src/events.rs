@@ -17,6 +17,8 @@
 
 use anchor_lang::prelude::*;
 
+use crate::state::{PermitTier, TimelockInstruction};
+
 #[event]
 pub struct AirspaceCreated {
     pub airspace: Pubkey,
@@ -48,6 +50,15 @@ pub struct AirspacePermitCreated {
     pub airspace: Pubkey,
     pub issuer: Pubkey,
     pub owner: Pubkey,
+    pub expires_at: i64,
+    pub tier: PermitTier,
+}
+
+#[event]
+pub struct AirspacePermitRenewed {
+    pub airspace: Pubkey,
+    pub permit: Pubkey,
+    pub expires_at: i64,
 }
 
 #[event]
@@ -55,3 +66,51 @@ pub struct AirspacePermitRevoked {
     pub airspace: Pubkey,
     pub permit: Pubkey,
 }
+
+#[event]
+pub struct TimelockCreated {
+    pub airspace: Pubkey,
+    pub timelock: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub delay: i64,
+}
+
+#[event]
+pub struct TimelockConfigured {
+    pub timelock: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub delay: i64,
+}
+
+#[event]
+pub struct TimelockTransactionQueued {
+    pub timelock: Pubkey,
+    pub transaction: Pubkey,
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub eta: i64,
+    pub instruction: TimelockInstruction,
+}
+
+#[event]
+pub struct TimelockTransactionApproved {
+    pub timelock: Pubkey,
+    pub transaction: Pubkey,
+    pub approver: Pubkey,
+}
+
+#[event]
+pub struct TimelockTransactionCancelled {
+    pub timelock: Pubkey,
+    pub transaction: Pubkey,
+    pub cancelled_by: Pubkey,
+}
+
+#[event]
+pub struct TimelockTransactionExecuted {
+    pub timelock: Pubkey,
+    pub transaction: Pubkey,
+    pub id: u64,
+}
@@ -20,7 +20,8 @@ use anchor_lang::prelude::*;
 use crate::{
     events::AirspacePermitCreated,
     seeds::{AIRSPACE_PERMIT, AIRSPACE_PERMIT_ISSUER},
-    state::{Airspace, AirspacePermit, AirspacePermitIssuerId},
+    state::{Airspace, AirspacePermit, AirspacePermitIssuerId, PermitTier},
+    AirspaceErrorCode,
 };
 
 #[derive(Accounts)]
@@ -70,6 +71,8 @@ pub struct AirspacePermitCreate<'info> {
 pub fn airspace_permit_create_handler(
     ctx: Context<AirspacePermitCreate>,
     owner: Pubkey,
+    expires_at: i64,
+    tier: PermitTier,
 ) -> Result<()> {
     // First validate that the signer is allowed to create permits
 
@@ -86,6 +89,24 @@ pub fn airspace_permit_create_handler(
         // and the account data being valid to deserialize means the permission was granted
     }
 
+    // In an unrestricted airspace anyone can create a permit for any owner, so only the
+    // airspace authority or an issuer may attach limits to it.
+    let is_limited = expires_at != 0 || tier != PermitTier::default();
+
+    if is_limited
+        && !airspace.is_restricted
+        && airspace.authority != authority.key()
+        && ctx.accounts.issuer_id.data_is_empty()
+    {
+        msg!("only an issuer may create a permit with an expiry or tier");
+        return err!(AirspaceErrorCode::PermissionDenied);
+    }
+
+    if expires_at != 0 && expires_at <= Clock::get()?.unix_timestamp {
+        msg!("the permit would already be expired");
+        return err!(AirspaceErrorCode::InvalidPermitExpiry);
+    }
+
     let permit = &mut ctx.accounts.permit;
 
     permit.airspace = ctx.accounts.airspace.key();
@@ -95,11 +116,15 @@ pub fn airspace_permit_create_handler(
     } else {
         authority.key()
     };
+    permit.expires_at = expires_at;
+    permit.tier = tier;
 
     emit!(AirspacePermitCreated {
         airspace: airspace.key(),
         issuer: permit.issuer,
-        owner: permit.owner
+        owner: permit.owner,
+        expires_at,
+        tier,
     });
 
     Ok(())
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::AirspacePermitRenewed,
+    seeds::AIRSPACE_PERMIT_ISSUER,
+    state::{Airspace, AirspacePermit},
+    AirspaceErrorCode,
+};
+
+#[derive(Accounts)]
+pub struct AirspacePermitRenew<'info> {
+    /// The authority allowed to renew the permit
+    ///
+    /// This must be either the airspace authority, or the regulator that issued the
+    /// permit while it is still licensed to issue permits.
+    authority: Signer<'info>,
+
+    /// The airspace the permit is for
+    airspace: Account<'info, Airspace>,
+
+    /// The identity account for the regulator that issued the permit
+    #[account(seeds = [
+                AIRSPACE_PERMIT_ISSUER,
+                airspace.key().as_ref(),
+                permit.issuer.as_ref()
+              ],
+              bump
+    )]
+    issuer_id: AccountInfo<'info>,
+
+    /// The permit to be renewed
+    #[account(mut, has_one = airspace)]
+    permit: Account<'info, AirspacePermit>,
+}
+
+pub fn airspace_permit_renew_handler(
+    ctx: Context<AirspacePermitRenew>,
+    expires_at: i64,
+) -> Result<()> {
+    let airspace = &ctx.accounts.airspace;
+    let permit = &mut ctx.accounts.permit;
+    let authority = ctx.accounts.authority.key();
+
+    // The issuing regulator may only renew its permits while it still has a license
+    let is_issuer = authority == permit.issuer && !ctx.accounts.issuer_id.data_is_empty();
+
+    if authority != airspace.authority && !is_issuer {
+        return err!(AirspaceErrorCode::PermissionDenied);
+    }
+
+    // Renewal can only extend a permit, shortening it requires a revoke
+    if permit.expires_at == 0 || (expires_at != 0 && expires_at <= permit.expires_at) {
+        msg!(
+            "cannot change expiry from {} to {}",
+            permit.expires_at,
+            expires_at
+        );
+        return err!(AirspaceErrorCode::InvalidPermitExpiry);
+    }
+
+    permit.expires_at = expires_at;
+
+    emit!(AirspacePermitRenewed {
+        airspace: airspace.key(),
+        permit: permit.key(),
+        expires_at,
+    });
+
+    Ok(())
+}
@@ -8,8 +8,16 @@ mod airspace_permit_issuer_create;
 mod airspace_permit_issuer_revoke;
 
 mod airspace_permit_create;
+mod airspace_permit_renew;
 mod airspace_permit_revoke;
 
+mod timelock_approve;
+mod timelock_cancel;
+mod timelock_configure;
+mod timelock_create;
+mod timelock_execute;
+mod timelock_queue;
+
 pub use create_governor_id::*;
 pub use set_governor::*;
 
@@ -20,4 +28,12 @@ pub use airspace_permit_issuer_create::*;
 pub use airspace_permit_issuer_revoke::*;
 
 pub use airspace_permit_create::*;
+pub use airspace_permit_renew::*;
 pub use airspace_permit_revoke::*;
+
+pub use timelock_approve::*;
+pub use timelock_cancel::*;
+pub use timelock_configure::*;
+pub use timelock_create::*;
+pub use timelock_execute::*;
+pub use timelock_queue::*;
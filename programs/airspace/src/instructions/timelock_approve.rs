@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::TimelockTransactionApproved,
+    state::{Timelock, TimelockTransaction},
+    AirspaceErrorCode,
+};
+
+#[derive(Accounts)]
+pub struct TimelockApprove<'info> {
+    /// One of the timelock signers
+    approver: Signer<'info>,
+
+    /// The timelock the transaction was queued in
+    timelock: Account<'info, Timelock>,
+
+    /// The transaction to approve
+    #[account(mut, has_one = timelock)]
+    transaction: Account<'info, TimelockTransaction>,
+}
+
+pub fn timelock_approve_handler(ctx: Context<TimelockApprove>) -> Result<()> {
+    let timelock = &ctx.accounts.timelock;
+    let transaction = &mut ctx.accounts.transaction;
+    let approver = ctx.accounts.approver.key();
+
+    if !timelock.is_signer(&approver) {
+        return err!(AirspaceErrorCode::PermissionDenied);
+    }
+
+    if transaction.approvals.contains(&approver) {
+        return err!(AirspaceErrorCode::TimelockAlreadyApproved);
+    }
+
+    // Approvals from signers that have since been removed don't count towards the
+    // threshold, and can be dropped to make room for the current signers.
+    transaction
+        .approvals
+        .retain(|address| timelock.is_signer(address));
+    transaction.approvals.push(approver);
+
+    emit!(TimelockTransactionApproved {
+        timelock: timelock.key(),
+        transaction: transaction.key(),
+        approver,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::TimelockTransactionCancelled,
+    state::{Timelock, TimelockTransaction},
+    AirspaceErrorCode,
+};
+
+#[derive(Accounts)]
+pub struct TimelockCancel<'info> {
+    /// One of the timelock signers
+    authority: Signer<'info>,
+
+    /// The address that queued the transaction, which receives its rent
+    /// CHECK: verified by the transaction
+    #[account(mut)]
+    proposer: UncheckedAccount<'info>,
+
+    /// The timelock the transaction was queued in
+    timelock: Account<'info, Timelock>,
+
+    /// The transaction to cancel
+    #[account(mut,
+              close = proposer,
+              has_one = timelock,
+              has_one = proposer
+    )]
+    transaction: Account<'info, TimelockTransaction>,
+}
+
+pub fn timelock_cancel_handler(ctx: Context<TimelockCancel>) -> Result<()> {
+    let authority = ctx.accounts.authority.key();
+
+    // Any single signer may cancel a transaction, so that every signer can prevent a
+    // change they object to while it is waiting for the delay.
+    if !ctx.accounts.timelock.is_signer(&authority) {
+        return err!(AirspaceErrorCode::PermissionDenied);
+    }
+
+    emit!(TimelockTransactionCancelled {
+        timelock: ctx.accounts.timelock.key(),
+        transaction: ctx.accounts.transaction.key(),
+        cancelled_by: authority,
+    });
+
+    Ok(())
+}
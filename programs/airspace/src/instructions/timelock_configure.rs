@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+
+use crate::{events::TimelockConfigured, state::Timelock, AirspaceErrorCode};
+
+#[derive(Accounts)]
+pub struct TimelockConfigure<'info> {
+    /// The timelock to be configured
+    ///
+    /// The timelock must sign for its own changes, so they can only be made by executing
+    /// a transaction queued in the timelock.
+    #[account(mut, signer)]
+    timelock: Account<'info, Timelock>,
+}
+
+pub fn timelock_configure_handler(
+    ctx: Context<TimelockConfigure>,
+    signers: Vec<Pubkey>,
+    threshold: u8,
+    delay: i64,
+) -> Result<()> {
+    if !Timelock::validate_config(&signers, threshold, delay) {
+        return err!(AirspaceErrorCode::InvalidTimelockConfig);
+    }
+
+    let timelock = &mut ctx.accounts.timelock;
+
+    timelock.signers = signers.clone();
+    timelock.threshold = threshold;
+    timelock.delay = delay;
+
+    emit!(TimelockConfigured {
+        timelock: timelock.key(),
+        signers,
+        threshold,
+        delay,
+    });
+
+    Ok(())
+}
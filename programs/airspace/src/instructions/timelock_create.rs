@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::TimelockCreated,
+    seeds::TIMELOCK,
+    state::{Airspace, Timelock},
+    AirspaceErrorCode,
+};
+
+#[derive(Accounts)]
+pub struct TimelockCreate<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+
+    /// The airspace authority
+    authority: Signer<'info>,
+
+    /// The airspace the timelock will be used for
+    #[account(has_one = authority)]
+    airspace: Account<'info, Airspace>,
+
+    /// The timelock account to be created
+    #[account(init,
+              seeds = [TIMELOCK, airspace.key().as_ref()],
+              bump,
+              payer = payer,
+              space = Timelock::SIZE
+    )]
+    timelock: Account<'info, Timelock>,
+
+    system_program: Program<'info, System>,
+}
+
+pub fn timelock_create_handler(
+    ctx: Context<TimelockCreate>,
+    signers: Vec<Pubkey>,
+    threshold: u8,
+    delay: i64,
+) -> Result<()> {
+    if !Timelock::validate_config(&signers, threshold, delay) {
+        return err!(AirspaceErrorCode::InvalidTimelockConfig);
+    }
+
+    let airspace = &ctx.accounts.airspace;
+    let timelock = &mut ctx.accounts.timelock;
+
+    timelock.airspace = airspace.key();
+    timelock.bump_seed = [*ctx.bumps.get("timelock").unwrap()];
+    timelock.signers = signers.clone();
+    timelock.threshold = threshold;
+    timelock.delay = delay;
+    timelock.transaction_count = 0;
+
+    emit!(TimelockCreated {
+        airspace: airspace.key(),
+        timelock: timelock.key(),
+        signers,
+        threshold,
+        delay,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, program::invoke_signed},
+};
+
+use crate::{
+    events::TimelockTransactionExecuted,
+    state::{Timelock, TimelockTransaction},
+    AirspaceErrorCode,
+};
+
+#[derive(Accounts)]
+pub struct TimelockExecute<'info> {
+    /// The address that queued the transaction, which receives its rent
+    /// CHECK: verified by the transaction
+    #[account(mut)]
+    proposer: UncheckedAccount<'info>,
+
+    /// The timelock the transaction was queued in, which signs for the instruction
+    timelock: Account<'info, Timelock>,
+
+    /// The transaction to execute
+    #[account(mut,
+              close = proposer,
+              has_one = timelock,
+              has_one = proposer
+    )]
+    transaction: Account<'info, TimelockTransaction>,
+}
+
+pub fn timelock_execute_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, TimelockExecute<'info>>,
+) -> Result<()> {
+    let timelock = &ctx.accounts.timelock;
+    let transaction = &ctx.accounts.transaction;
+
+    if Clock::get()?.unix_timestamp < transaction.eta {
+        msg!(
+            "the transaction cannot be executed until {}",
+            transaction.eta
+        );
+        return err!(AirspaceErrorCode::TimelockNotReady);
+    }
+
+    if transaction.approval_count(timelock) < timelock.threshold as usize {
+        msg!(
+            "the transaction needs {} approvals, but has {}",
+            timelock.threshold,
+            transaction.approval_count(timelock)
+        );
+        return err!(AirspaceErrorCode::TimelockNotApproved);
+    }
+
+    // The accounts required by the instruction, including the program being invoked,
+    // are passed as the remaining accounts.
+    let instruction = Instruction::from(&transaction.instruction);
+    let mut accounts = ctx.remaining_accounts.to_vec();
+    accounts.push(timelock.to_account_info());
+
+    invoke_signed(&instruction, &accounts, &[&timelock.signer_seeds()])?;
+
+    emit!(TimelockTransactionExecuted {
+        timelock: timelock.key(),
+        transaction: transaction.key(),
+        id: transaction.id,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    events::TimelockTransactionQueued,
+    seeds::TIMELOCK_TRANSACTION,
+    state::{Timelock, TimelockInstruction, TimelockTransaction},
+    AirspaceErrorCode,
+};
+
+#[derive(Accounts)]
+#[instruction(instruction: TimelockInstruction)]
+pub struct TimelockQueue<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+
+    /// One of the timelock signers
+    proposer: Signer<'info>,
+
+    /// The timelock to queue the transaction in
+    #[account(mut)]
+    timelock: Account<'info, Timelock>,
+
+    /// The account to store the queued transaction
+    #[account(init,
+              seeds = [
+                TIMELOCK_TRANSACTION,
+                timelock.key().as_ref(),
+                timelock.transaction_count.to_le_bytes().as_ref()
+              ],
+              bump,
+              payer = payer,
+              space = TimelockTransaction::space(&instruction)
+    )]
+    transaction: Account<'info, TimelockTransaction>,
+
+    system_program: Program<'info, System>,
+}
+
+pub fn timelock_queue_handler(
+    ctx: Context<TimelockQueue>,
+    instruction: TimelockInstruction,
+) -> Result<()> {
+    let timelock = &mut ctx.accounts.timelock;
+    let proposer = ctx.accounts.proposer.key();
+
+    if !timelock.is_signer(&proposer) {
+        return err!(AirspaceErrorCode::PermissionDenied);
+    }
+
+    let transaction = &mut ctx.accounts.transaction;
+
+    transaction.timelock = timelock.key();
+    transaction.id = timelock.transaction_count;
+    transaction.proposer = proposer;
+    transaction.eta = Clock::get()?.unix_timestamp + timelock.delay;
+    transaction.approvals = vec![proposer];
+    transaction.instruction = instruction.clone();
+
+    timelock.transaction_count += 1;
+
+    emit!(TimelockTransactionQueued {
+        timelock: timelock.key(),
+        transaction: transaction.key(),
+        id: transaction.id,
+        proposer,
+        eta: transaction.eta,
+        instruction,
+    });
+
+    Ok(())
+}
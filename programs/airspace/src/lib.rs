@@ -28,6 +28,8 @@ use instructions::*;
 pub mod events;
 pub mod state;
 
+use state::{PermitTier, TimelockInstruction};
+
 pub mod seeds {
     use super::constant;
 
@@ -42,6 +44,12 @@ pub mod seeds {
 
     #[constant]
     pub const AIRSPACE_PERMIT: &[u8] = b"airspace-permit";
+
+    #[constant]
+    pub const TIMELOCK: &[u8] = b"timelock";
+
+    #[constant]
+    pub const TIMELOCK_TRANSACTION: &[u8] = b"timelock-transaction";
 }
 
 #[program]
@@ -122,14 +130,102 @@ pub mod jet_airspace {
     ///
     /// * `owner` - The owner for the new permit, which is the address being allowed to use
     ///             the airspace.
-    pub fn airspace_permit_create(ctx: Context<AirspacePermitCreate>, owner: Pubkey) -> Result<()> {
-        instructions::airspace_permit_create_handler(ctx, owner)
+    /// * `expires_at` - The unix timestamp when the permit stops being valid, or zero if the
+    ///                  permit never expires.
+    /// * `tier` - The limits on what the owner may do within the airspace. Only the airspace
+    ///            authority or an issuer may create a permit with an expiry or tier.
+    pub fn airspace_permit_create(
+        ctx: Context<AirspacePermitCreate>,
+        owner: Pubkey,
+        expires_at: i64,
+        tier: PermitTier,
+    ) -> Result<()> {
+        instructions::airspace_permit_create_handler(ctx, owner, expires_at, tier)
+    }
+
+    /// Extend the expiry of a previously created permit
+    ///
+    /// # Parameters
+    ///
+    /// * `expires_at` - The new unix timestamp when the permit stops being valid, which must
+    ///                  be later than the current expiry, or zero to never expire.
+    pub fn airspace_permit_renew(ctx: Context<AirspacePermitRenew>, expires_at: i64) -> Result<()> {
+        instructions::airspace_permit_renew_handler(ctx, expires_at)
     }
 
     /// Revoke a previously created permit
     pub fn airspace_permit_revoke(ctx: Context<AirspacePermitRevoke>) -> Result<()> {
         instructions::airspace_permit_revoke_handler(ctx)
     }
+
+    /// Create a timelock for an airspace
+    ///
+    /// Once created, the airspace authority can be set to the timelock address, so that any
+    /// change requiring the authority has to be queued and approved by the timelock signers,
+    /// and then wait for the delay before it can be executed.
+    ///
+    /// # Parameters
+    ///
+    /// * `signers` - The addresses allowed to queue, approve and cancel transactions.
+    /// * `threshold` - The number of signers that must approve a transaction.
+    /// * `delay` - The number of seconds between queueing and executing a transaction.
+    pub fn timelock_create(
+        ctx: Context<TimelockCreate>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+        delay: i64,
+    ) -> Result<()> {
+        instructions::timelock_create_handler(ctx, signers, threshold, delay)
+    }
+
+    /// Change the signers, threshold or delay for a timelock. Must be signed by the
+    /// timelock itself, by executing a queued transaction.
+    ///
+    /// # Parameters
+    ///
+    /// * `signers` - The addresses allowed to queue, approve and cancel transactions.
+    /// * `threshold` - The number of signers that must approve a transaction.
+    /// * `delay` - The number of seconds between queueing and executing a transaction.
+    pub fn timelock_configure(
+        ctx: Context<TimelockConfigure>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+        delay: i64,
+    ) -> Result<()> {
+        instructions::timelock_configure_handler(ctx, signers, threshold, delay)
+    }
+
+    /// Queue an instruction to be signed by the timelock once the delay has passed
+    ///
+    /// The proposer counts as the first approval for the transaction.
+    ///
+    /// # Parameters
+    ///
+    /// * `instruction` - The instruction to be executed.
+    pub fn timelock_queue(
+        ctx: Context<TimelockQueue>,
+        instruction: TimelockInstruction,
+    ) -> Result<()> {
+        instructions::timelock_queue_handler(ctx, instruction)
+    }
+
+    /// Approve a queued transaction
+    pub fn timelock_approve(ctx: Context<TimelockApprove>) -> Result<()> {
+        instructions::timelock_approve_handler(ctx)
+    }
+
+    /// Cancel a queued transaction. Any one of the timelock signers may cancel.
+    pub fn timelock_cancel(ctx: Context<TimelockCancel>) -> Result<()> {
+        instructions::timelock_cancel_handler(ctx)
+    }
+
+    /// Execute a queued transaction that has enough approvals, and has waited for
+    /// the timelock delay.
+    pub fn timelock_execute<'info>(
+        ctx: Context<'_, '_, '_, 'info, TimelockExecute<'info>>,
+    ) -> Result<()> {
+        instructions::timelock_execute_handler(ctx)
+    }
 }
 
 #[error_code]
@@ -137,4 +233,24 @@ pub enum AirspaceErrorCode {
     /// 707000 - No permissions to do an action
     #[msg("The signer does not have the required permissions to do this")]
     PermissionDenied = 701_000,
+
+    /// 701001 - The permit expiry is not valid
+    #[msg("The permit expiry must be in the future, and can only be extended")]
+    InvalidPermitExpiry,
+
+    /// 701002 - The timelock signers, threshold or delay are not valid
+    #[msg("The timelock signers, threshold or delay are not valid")]
+    InvalidTimelockConfig,
+
+    /// 701003 - The signer already approved the transaction
+    #[msg("The signer already approved this transaction")]
+    TimelockAlreadyApproved,
+
+    /// 701004 - The transaction delay has not passed
+    #[msg("The timelock delay for this transaction has not passed")]
+    TimelockNotReady,
+
+    /// 701005 - The transaction does not have enough approvals
+    #[msg("The transaction does not have enough approvals")]
+    TimelockNotApproved,
 }
@@ -15,7 +15,9 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::instruction::Instruction};
+
+use crate::seeds::TIMELOCK;
 
 macro_rules! declare_account_size {
     ($name:ident, $size:expr) => {
@@ -73,10 +75,198 @@ pub struct AirspacePermit {
 
     /// The issuer of this permit
     pub issuer: Pubkey,
+
+    /// The unix timestamp after which the permit is no longer valid, or zero if the
+    /// permit never expires.
+    pub expires_at: i64,
+
+    /// Limits on what the owner may do with the resources in the airspace
+    pub tier: PermitTier,
 }
 
 declare_account_size!(AirspacePermit, 128);
 
+impl AirspacePermit {
+    /// True if the permit has an expiry that has passed
+    pub fn is_expired(&self, timestamp: i64) -> bool {
+        self.expires_at != 0 && timestamp >= self.expires_at
+    }
+
+    /// True if the permit has an expiry or tier, which needs to be checked when
+    /// using resources under the permit.
+    pub fn is_limited(&self) -> bool {
+        self.expires_at != 0 || self.tier != PermitTier::default()
+    }
+}
+
+/// Limits on the use of airspace resources, attached to a permit.
+///
+/// The default tier has no limits.
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PermitTier {
+    /// If true, the owner may only lend, and may not take on any liabilities
+    pub lend_only: bool,
+
+    /// The maximum total value of liabilities (in whole USD) that an account under the
+    /// permit may have after borrowing, or zero if there is no limit.
+    pub max_borrow_value: u64,
+}
+
+/// The maximum number of signers that can be configured for a timelock
+pub const MAX_TIMELOCK_SIGNERS: usize = 8;
+
+/// A delay applied to changes made by an airspace authority.
+///
+/// The timelock address is meant to become the airspace authority, so that any change
+/// signed by the authority (including config changes in other programs) must be queued
+/// by the timelock signers, and can only be executed after the delay has passed.
+#[account]
+pub struct Timelock {
+    /// The airspace this timelock is for
+    pub airspace: Pubkey,
+
+    /// The bump seed for the timelock address
+    pub bump_seed: [u8; 1],
+
+    /// The addresses allowed to queue, approve and cancel transactions
+    pub signers: Vec<Pubkey>,
+
+    /// The number of signers that must approve a transaction before it can be executed
+    pub threshold: u8,
+
+    /// The number of seconds a transaction must wait after being queued before it can
+    /// be executed.
+    pub delay: i64,
+
+    /// The number of transactions that have been queued, used to derive the address
+    /// of the next transaction.
+    pub transaction_count: u64,
+}
+
+declare_account_size!(Timelock, 320);
+
+impl Timelock {
+    /// The seeds used to sign for the timelock address
+    pub fn signer_seeds(&self) -> [&[u8]; 3] {
+        [TIMELOCK, self.airspace.as_ref(), &self.bump_seed]
+    }
+
+    /// Check if an address is one of the signers for the timelock
+    pub fn is_signer(&self, address: &Pubkey) -> bool {
+        self.signers.contains(address)
+    }
+
+    /// Check that a set of signers and a threshold can be used for a timelock
+    pub fn validate_config(signers: &[Pubkey], threshold: u8, delay: i64) -> bool {
+        let has_duplicates = signers
+            .iter()
+            .enumerate()
+            .any(|(i, signer)| signers[..i].contains(signer));
+
+        !signers.is_empty()
+            && signers.len() <= MAX_TIMELOCK_SIGNERS
+            && !has_duplicates
+            && threshold > 0
+            && threshold as usize <= signers.len()
+            && delay >= 0
+    }
+}
+
+/// An instruction that has been queued in a timelock, to be executed with the timelock
+/// address as a signer once the delay has passed.
+#[account]
+pub struct TimelockTransaction {
+    /// The timelock this transaction was queued in
+    pub timelock: Pubkey,
+
+    /// The sequence number for this transaction within the timelock
+    pub id: u64,
+
+    /// The signer that queued this transaction, which receives the rent when it is closed
+    pub proposer: Pubkey,
+
+    /// The unix timestamp after which the transaction may be executed
+    pub eta: i64,
+
+    /// The timelock signers that have approved this transaction
+    pub approvals: Vec<Pubkey>,
+
+    /// The instruction to be executed
+    pub instruction: TimelockInstruction,
+}
+
+impl TimelockTransaction {
+    /// The space needed to store a transaction with the given instruction
+    pub fn space(instruction: &TimelockInstruction) -> usize {
+        8 + 32
+            + 8
+            + 32
+            + 8
+            + (4 + 32 * MAX_TIMELOCK_SIGNERS)
+            + (32 + 4 + 34 * instruction.accounts.len() + 4 + instruction.data.len())
+    }
+
+    /// The number of approvals from addresses that are currently signers of the timelock
+    pub fn approval_count(&self, timelock: &Timelock) -> usize {
+        self.approvals
+            .iter()
+            .filter(|approver| timelock.is_signer(approver))
+            .count()
+    }
+}
+
+/// An instruction stored in a [TimelockTransaction]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TimelockInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<TimelockAccountMeta>,
+    pub data: Vec<u8>,
+}
+
+/// An account used by a [TimelockInstruction]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimelockAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl From<&TimelockInstruction> for Instruction {
+    fn from(ix: &TimelockInstruction) -> Self {
+        Instruction {
+            program_id: ix.program_id,
+            accounts: ix
+                .accounts
+                .iter()
+                .map(|meta| AccountMeta {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: ix.data.clone(),
+        }
+    }
+}
+
+impl From<Instruction> for TimelockInstruction {
+    fn from(ix: Instruction) -> Self {
+        TimelockInstruction {
+            program_id: ix.program_id,
+            accounts: ix
+                .accounts
+                .into_iter()
+                .map(|meta| TimelockAccountMeta {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: ix.data,
+        }
+    }
+}
+
 /// A global account specifying the current governing address for the protocol
 #[account]
 pub struct GovernorId {
@@ -1,4 +1,4 @@
-use crate::{MarginPool, MarginPoolConfig};
+use crate::{BadDebt, MarginPool, MarginPoolConfig, RewardsConfig};
 use anchor_lang::prelude::*;
 
 #[event]
@@ -97,6 +97,47 @@ pub struct LoanTransferred {
     pub amount: u64,
 }
 
+#[event]
+pub struct BadDebtWrittenOff {
+    pub margin_pool: Pubkey,
+    pub margin_account: Pubkey,
+    pub loan_account: Pubkey,
+    pub bad_debt: BadDebt,
+    pub summary: MarginPoolSummary,
+}
+
+#[event]
+pub struct RewardsConfigured {
+    pub margin_pool: Pubkey,
+    pub pool_rewards: Pubkey,
+    pub reward_mint: Pubkey,
+    pub config: RewardsConfig,
+}
+
+#[event]
+pub struct RewardsClaimed {
+    pub margin_pool: Pubkey,
+    pub margin_account: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FlashLoanBegin {
+    pub margin_pool: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FlashLoanEnd {
+    pub margin_pool: Pubkey,
+    pub repayment_token_account: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub summary: MarginPoolSummary,
+}
+
 /// Common fields from MarginPool for event logging.
 #[derive(AnchorDeserialize, AnchorSerialize, Debug)]
 pub struct MarginPoolSummary {
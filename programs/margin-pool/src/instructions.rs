@@ -15,32 +15,40 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+mod claim_rewards;
 mod close_loan;
 mod collect;
 mod configure;
 mod create_pool;
 mod deposit;
+mod flash_loan;
 mod margin_borrow;
 mod margin_borrow_v2;
 mod margin_refresh_position;
 mod margin_repay;
 mod register_loan;
+mod register_rewards_position;
 mod repay;
 mod withdraw;
+mod write_off_bad_debt;
 
 mod admin;
 
+pub use claim_rewards::*;
 pub use close_loan::*;
 pub use collect::*;
 pub use configure::*;
 pub use create_pool::*;
 pub use deposit::*;
+pub use flash_loan::*;
 pub use margin_borrow::*;
 pub use margin_borrow_v2::*;
 pub use margin_refresh_position::*;
 pub use margin_repay::*;
 pub use register_loan::*;
+pub use register_rewards_position::*;
 pub use repay::*;
 pub use withdraw::*;
+pub use write_off_bad_debt::*;
 
 pub use admin::*;
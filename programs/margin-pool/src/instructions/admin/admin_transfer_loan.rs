@@ -18,14 +18,19 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
+#[cfg(not(feature = "testing"))]
 use jet_program_common::GOVERNOR_ID;
 
-use crate::{events::LoanTransferred, MarginPool};
+use crate::{
+    events::LoanTransferred,
+    rewards::{NoteChange, RewardsAccounts},
+    MarginPool,
+};
 
 #[derive(Accounts)]
 pub struct AdminTransferLoan<'info> {
     /// The administrative authority
-    #[account(address = GOVERNOR_ID)]
+    #[cfg_attr(not(feature = "testing"), account(address = GOVERNOR_ID))]
     pub authority: Signer<'info>,
 
     /// The margin pool with the loan
@@ -56,6 +61,23 @@ impl<'info> AdminTransferLoan<'info> {
 }
 
 pub fn admin_transfer_loan_handler(ctx: Context<AdminTransferLoan>, amount: u64) -> Result<()> {
+    let pool = &ctx.accounts.margin_pool;
+
+    // the loan notes are moved between accounts, so the rewards earned on
+    // them must be checkpointed for both accounts
+    let loan_accounts = [
+        ctx.accounts.source_loan_account.key(),
+        ctx.accounts.target_loan_account.key(),
+    ];
+    let owners = RewardsAccounts::loan_owners(pool, ctx.remaining_accounts, &loan_accounts)?;
+    let mut rewards = RewardsAccounts::load(pool, ctx.remaining_accounts, &owners)?;
+
+    if let Some(rewards) = &mut rewards {
+        rewards.accrue(pool, Clock::get()?.unix_timestamp);
+    }
+
+    let source_notes = ctx.accounts.source_loan_account.amount;
+    let target_notes = ctx.accounts.target_loan_account.amount;
     let source_seeds = ctx.accounts.margin_pool.signer_seeds()?;
 
     token::transfer(
@@ -65,6 +87,14 @@ pub fn admin_transfer_loan_handler(ctx: Context<AdminTransferLoan>, amount: u64)
         amount,
     )?;
 
+    if let Some(mut rewards) = rewards {
+        rewards.update_positions(&[
+            (loan_accounts[0], NoteChange::decrease(source_notes, amount)),
+            (loan_accounts[1], NoteChange::increase(target_notes, amount)),
+        ]);
+        rewards.exit()?;
+    }
+
     emit!(LoanTransferred {
         margin_pool: ctx.accounts.margin_pool.key(),
         source_loan_account: ctx.accounts.source_loan_account.key(),
@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+#[cfg(not(feature = "testing"))]
+use jet_program_common::GOVERNOR_ID;
+
+use crate::{events::RewardsConfigured, MarginPool, PoolFlags, PoolRewards, RewardsConfig};
+
+#[derive(Accounts)]
+pub struct ConfigureRewards<'info> {
+    /// The administrative authority
+    #[cfg_attr(not(feature = "testing"), account(address = GOVERNOR_ID))]
+    pub authority: Signer<'info>,
+
+    /// The pool emitting the rewards
+    #[account(mut)]
+    pub margin_pool: Box<Account<'info, MarginPool>>,
+
+    /// The mint for the reward token
+    pub reward_mint: Box<Account<'info, Mint>>,
+
+    /// The account tracking the rewards emitted by the pool
+    #[account(init_if_needed,
+              seeds = [
+                margin_pool.key().as_ref(),
+                b"rewards".as_ref()
+              ],
+              bump,
+              payer = payer,
+              space = 8 + std::mem::size_of::<PoolRewards>())]
+    pub pool_rewards: Box<Account<'info, PoolRewards>>,
+
+    /// The token account holding the rewards to be claimed
+    #[account(init_if_needed,
+              seeds = [
+                pool_rewards.key().as_ref(),
+                b"vault".as_ref()
+              ],
+              bump,
+              token::mint = reward_mint,
+              token::authority = pool_rewards,
+              payer = payer)]
+    pub reward_vault: Box<Account<'info, TokenAccount>>,
+
+    /// The payer of rent for new accounts
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn configure_rewards_handler(
+    ctx: Context<ConfigureRewards>,
+    config: RewardsConfig,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.margin_pool;
+    let rewards = &mut ctx.accounts.pool_rewards;
+    let clock = Clock::get()?;
+
+    if rewards.margin_pool == Pubkey::default() {
+        rewards.margin_pool = pool.key();
+        rewards.bump[0] = *ctx.bumps.get("pool_rewards").unwrap();
+        rewards.reward_mint = ctx.accounts.reward_mint.key();
+        rewards.reward_vault = ctx.accounts.reward_vault.key();
+    } else {
+        // settle the rewards emitted under the previous config
+        rewards.accrue(pool, clock.unix_timestamp);
+    }
+
+    // nothing is emitted for any time before this change that was outside
+    // the previous config's emission period
+    rewards.accrued_until = clock.unix_timestamp;
+    rewards.config = config;
+
+    // from now on, the pool's notes can only change with the rewards accounts
+    pool.config.flags |= PoolFlags::REWARDS.bits();
+
+    emit!(RewardsConfigured {
+        margin_pool: pool.key(),
+        pool_rewards: rewards.key(),
+        reward_mint: rewards.reward_mint,
+        config,
+    });
+
+    Ok(())
+}
@@ -1,3 +1,5 @@
 mod admin_transfer_loan;
+mod configure_rewards;
 
 pub use admin_transfer_loan::*;
+pub use configure_rewards::*;
@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use jet_margin::MarginAccount;
+
+use crate::{events, MarginPool, PoolRewards, RewardsPosition};
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    /// The margin account that earned the rewards
+    #[account(signer)]
+    pub margin_account: AccountLoader<'info, MarginAccount>,
+
+    /// The pool emitting the rewards
+    pub margin_pool: Account<'info, MarginPool>,
+
+    /// The rewards emitted by the pool
+    #[account(mut,
+              has_one = margin_pool,
+              has_one = reward_vault)]
+    pub pool_rewards: Account<'info, PoolRewards>,
+
+    /// The account tracking the rewards earned by the margin account
+    #[account(mut,
+              has_one = pool_rewards,
+              has_one = margin_account)]
+    pub rewards_position: Account<'info, RewardsPosition>,
+
+    /// The token account holding the rewards to be claimed
+    #[account(mut)]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    /// The account to receive the rewards
+    /// CHECK:
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClaimRewards<'info> {
+    fn transfer_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reward_vault.to_account_info(),
+                to: self.destination.to_account_info(),
+                authority: self.pool_rewards.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn claim_rewards_handler(ctx: Context<ClaimRewards>) -> Result<()> {
+    let pool = &ctx.accounts.margin_pool;
+    let rewards = &mut ctx.accounts.pool_rewards;
+    let position = &mut ctx.accounts.rewards_position;
+
+    rewards.accrue(pool, Clock::get()?.unix_timestamp);
+    position.update_from_margin_account(rewards, &*ctx.accounts.margin_account.load()?);
+
+    // Anything that can't be paid out of the vault remains claimable later
+    let amount = position.claim(ctx.accounts.reward_vault.amount);
+
+    let rewards = &ctx.accounts.pool_rewards;
+    token::transfer(
+        ctx.accounts
+            .transfer_context()
+            .with_signer(&[&rewards.signer_seeds()]),
+        amount,
+    )?;
+
+    emit!(events::RewardsClaimed {
+        margin_pool: pool.key(),
+        margin_account: ctx.accounts.margin_account.key(),
+        destination: ctx.accounts.destination.key(),
+        amount,
+    });
+
+    Ok(())
+}
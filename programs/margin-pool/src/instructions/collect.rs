@@ -20,6 +20,7 @@ use std::ops::Deref;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, MintTo, Token, TokenAccount};
 
+use crate::rewards::RewardsAccounts;
 use crate::{events, state::*, Amount};
 
 #[derive(Accounts)]
@@ -71,6 +72,11 @@ pub fn collect_handler(ctx: Context<Collect>) -> Result<()> {
         return Ok(());
     }
 
+    if let Some(mut rewards) = RewardsAccounts::load(pool, ctx.remaining_accounts, &[])? {
+        rewards.accrue(pool, clock.unix_timestamp);
+        rewards.exit()?;
+    }
+
     let fee_notes = pool.collect_accrued_fees();
     let pool = &ctx.accounts.margin_pool;
 
@@ -43,7 +43,12 @@ pub fn configure_handler(ctx: Context<Configure>, config: Option<MarginPoolConfi
     let pool = &mut ctx.accounts.margin_pool;
 
     if let Some(new_config) = config {
+        // rewards are enabled by `configure_rewards`, and can't be removed
+        // while positions may still be earning them
+        let rewards = pool.config.flags & PoolFlags::REWARDS.bits();
+
         pool.config = new_config;
+        pool.config.flags |= rewards;
     }
 
     if *ctx.accounts.pyth_price.key != Pubkey::default() {
@@ -21,6 +21,7 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{self, MintTo, Token, Transfer};
 use jet_program_common::debug_msg;
 
+use crate::rewards::{NoteChange, RewardsAccounts};
 use crate::{events, state::*, TokenChange};
 use crate::{ChangeKind, ErrorCode};
 
@@ -103,11 +104,15 @@ pub fn deposit_handler(ctx: Context<Deposit>, change_kind: ChangeKind, amount: u
         return Err(ErrorCode::InterestAccrualBehind.into());
     }
 
-    let deposit_amount = pool.calculate_full_amount(
-        token::accessor::amount(&ctx.accounts.destination.to_account_info())?,
-        change,
-        PoolAction::Deposit,
-    )?;
+    let owner = token::accessor::authority(&ctx.accounts.destination.to_account_info())?;
+    let mut rewards = RewardsAccounts::load(pool, ctx.remaining_accounts, &[owner])?;
+    if let Some(rewards) = &mut rewards {
+        rewards.accrue(pool, clock.unix_timestamp);
+    }
+
+    let destination_notes = token::accessor::amount(&ctx.accounts.destination.to_account_info())?;
+    let deposit_amount =
+        pool.calculate_full_amount(destination_notes, change, PoolAction::Deposit)?;
     debug_msg!("Executing deposit {:?}", deposit_amount);
     pool.deposit(&deposit_amount);
 
@@ -123,6 +128,14 @@ pub fn deposit_handler(ctx: Context<Deposit>, change_kind: ChangeKind, amount: u
         deposit_amount.notes,
     )?;
 
+    if let Some(mut rewards) = rewards {
+        rewards.update_positions(&[(
+            ctx.accounts.destination.key(),
+            NoteChange::increase(destination_notes, deposit_amount.notes),
+        )]);
+        rewards.exit()?;
+    }
+
     emit!(events::Deposit {
         margin_pool: ctx.accounts.margin_pool.key(),
         user: ctx.accounts.depositor.key(),
@@ -0,0 +1,250 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_lang::{Discriminator, InstructionData};
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::{events, instruction, ErrorCode, MarginPool};
+
+#[derive(Accounts)]
+pub struct FlashLoanBegin<'info> {
+    /// The pool to borrow from
+    #[account(has_one = vault)]
+    pub margin_pool: Account<'info, MarginPool>,
+
+    /// The vault responsible for storing the pool's tokens
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// The token account to receive the loan
+    /// CHECK: The token program validates the transfer
+    #[account(mut)]
+    pub destination: AccountInfo<'info>,
+
+    /// The instructions of the transaction
+    /// CHECK: The address is checked
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> FlashLoanBegin<'info> {
+    fn transfer_loan_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.vault.to_account_info(),
+                to: self.destination.to_account_info(),
+                authority: self.margin_pool.to_account_info(),
+            },
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct FlashLoanEnd<'info> {
+    /// Signing authority for the repaying token account
+    pub repayment_account_authority: Signer<'info>,
+
+    /// The pool that was borrowed from
+    #[account(mut, has_one = vault)]
+    pub margin_pool: Account<'info, MarginPool>,
+
+    /// The vault responsible for storing the pool's tokens
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// The token account repaying the loan and fee
+    /// CHECK: The token program validates the transfer
+    #[account(mut)]
+    pub repayment_token_account: AccountInfo<'info>,
+
+    /// The instructions of the transaction
+    /// CHECK: The address is checked
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> FlashLoanEnd<'info> {
+    fn transfer_repayment_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.repayment_token_account.to_account_info(),
+                to: self.vault.to_account_info(),
+                authority: self.repayment_account_authority.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn flash_loan_begin_handler(ctx: Context<FlashLoanBegin>, amount: u64) -> Result<()> {
+    let pool = &ctx.accounts.margin_pool;
+    pool.verify_flash_loan(amount)?;
+
+    // The loan is not recorded by the pool, as the matching `flash_loan_end` is
+    // required to transfer the tokens back before the transaction completes.
+    let current = verify_top_level(
+        &ctx.accounts.instructions,
+        instruction::FlashLoanBegin { amount }.data(),
+    )?;
+    match find_flash_loan_instruction(&ctx.accounts.instructions, current, true)? {
+        Some(ix)
+            if is_matching(
+                &ix,
+                instruction::FlashLoanEnd { amount }.data(),
+                &pool.key(),
+            ) => {}
+        _ => {
+            msg!("a flash loan must be followed by a flash_loan_end for the same pool and amount");
+            return err!(ErrorCode::FlashLoanNotRepaid);
+        }
+    }
+
+    let signer = [&pool.signer_seeds()?[..]];
+    token::transfer(
+        ctx.accounts.transfer_loan_context().with_signer(&signer),
+        amount,
+    )?;
+
+    emit!(events::FlashLoanBegin {
+        margin_pool: pool.key(),
+        destination: ctx.accounts.destination.key(),
+        amount,
+    });
+
+    Ok(())
+}
+
+pub fn flash_loan_end_handler(ctx: Context<FlashLoanEnd>, amount: u64) -> Result<()> {
+    let current = verify_top_level(
+        &ctx.accounts.instructions,
+        instruction::FlashLoanEnd { amount }.data(),
+    )?;
+    match find_flash_loan_instruction(&ctx.accounts.instructions, current, false)? {
+        Some(ix)
+            if is_matching(
+                &ix,
+                instruction::FlashLoanBegin { amount }.data(),
+                &ctx.accounts.margin_pool.key(),
+            ) => {}
+        _ => {
+            msg!("there is no flash loan to repay");
+            return err!(ErrorCode::FlashLoanNotRepaid);
+        }
+    }
+
+    let pool = &mut ctx.accounts.margin_pool;
+    let clock = Clock::get()?;
+
+    // Make sure interest accrual is up-to-date
+    if !pool.accrue_interest(clock.unix_timestamp) {
+        msg!("interest accrual is too far behind");
+        return Err(ErrorCode::InterestAccrualBehind.into());
+    }
+
+    let fee = pool.flash_loan_fee(amount);
+    pool.collect_flash_loan_fee(fee);
+
+    token::transfer(
+        ctx.accounts.transfer_repayment_context(),
+        amount.checked_add(fee).unwrap(),
+    )?;
+
+    let pool = &ctx.accounts.margin_pool;
+    emit!(events::FlashLoanEnd {
+        margin_pool: pool.key(),
+        repayment_token_account: ctx.accounts.repayment_token_account.key(),
+        amount,
+        fee,
+        summary: (&pool.clone().into_inner()).into(),
+    });
+
+    Ok(())
+}
+
+/// Check that the instruction being executed is not invoked by another program,
+/// as only the top level instructions are visible to the other half of the loan.
+fn verify_top_level(instructions: &AccountInfo, data: Vec<u8>) -> Result<usize> {
+    let current = load_current_index_checked(instructions)? as usize;
+    let ix = load_instruction_at_checked(current, instructions)?;
+
+    if ix.program_id != crate::ID || ix.data != data {
+        msg!("flash loans cannot be invoked by other programs");
+        return err!(ErrorCode::FlashLoanInvokedByProgram);
+    }
+
+    Ok(current)
+}
+
+/// Find the closest flash loan instruction of this program in the transaction,
+/// searching either forward or backward from the current instruction.
+///
+/// Only the closest one is considered, so that flash loans cannot be nested and
+/// every loan has its own repayment.
+fn find_flash_loan_instruction(
+    instructions: &AccountInfo,
+    current: usize,
+    forward: bool,
+) -> Result<Option<Instruction>> {
+    let mut index = current;
+    loop {
+        index = match (forward, index) {
+            (false, 0) => return Ok(None),
+            (false, index) => index - 1,
+            (true, index) => index + 1,
+        };
+        let ix = match load_instruction_at_checked(index, instructions) {
+            Ok(ix) => ix,
+            Err(_) => return Ok(None),
+        };
+
+        if ix.program_id == crate::ID
+            && (ix
+                .data
+                .starts_with(&instruction::FlashLoanBegin::DISCRIMINATOR)
+                || ix
+                    .data
+                    .starts_with(&instruction::FlashLoanEnd::DISCRIMINATOR))
+        {
+            return Ok(Some(ix));
+        }
+    }
+}
+
+/// Whether an instruction has the expected data, and uses the same pool
+fn is_matching(ix: &Instruction, data: Vec<u8>, margin_pool: &Pubkey) -> bool {
+    // The pool is the first account of flash_loan_begin, and the second of flash_loan_end
+    let pool_index = match data.starts_with(&instruction::FlashLoanBegin::DISCRIMINATOR) {
+        true => 0,
+        false => 1,
+    };
+
+    ix.data == data
+        && ix
+            .accounts
+            .get(pool_index)
+            .map_or(false, |meta| meta.pubkey == *margin_pool)
+}
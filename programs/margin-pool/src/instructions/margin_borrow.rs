@@ -22,6 +22,7 @@ use anchor_spl::token::{self, MintTo, Token, TokenAccount};
 
 use jet_margin::MarginAccount;
 
+use crate::rewards::{NoteChange, RewardsAccounts};
 use crate::{events, state::*, ChangeKind, TokenChange};
 use crate::{Amount, ErrorCode};
 
@@ -105,6 +106,15 @@ pub fn margin_borrow_handler(
         return Err(ErrorCode::InterestAccrualBehind.into());
     }
 
+    let mut rewards = RewardsAccounts::load(
+        pool,
+        ctx.remaining_accounts,
+        &[ctx.accounts.margin_account.key()],
+    )?;
+    if let Some(rewards) = &mut rewards {
+        rewards.accrue(pool, clock.unix_timestamp);
+    }
+
     // First record a borrow of the tokens requested
     let borrow_amount =
         pool.calculate_full_amount(ctx.accounts.loan_account.amount, change, PoolAction::Borrow)?;
@@ -128,6 +138,20 @@ pub fn margin_borrow_handler(
         deposit_amount.notes,
     )?;
 
+    if let Some(mut rewards) = rewards {
+        rewards.update_positions(&[
+            (
+                ctx.accounts.deposit_account.key(),
+                NoteChange::increase(ctx.accounts.deposit_account.amount, deposit_amount.notes),
+            ),
+            (
+                ctx.accounts.loan_account.key(),
+                NoteChange::increase(ctx.accounts.loan_account.amount, borrow_amount.notes),
+            ),
+        ]);
+        rewards.exit()?;
+    }
+
     emit!(events::MarginBorrow {
         margin_pool: ctx.accounts.margin_pool.key(),
         user: ctx.accounts.margin_account.key(),
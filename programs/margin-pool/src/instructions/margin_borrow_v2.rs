@@ -22,6 +22,7 @@ use anchor_spl::token::{self, MintTo, Token, TokenAccount, Transfer};
 
 use jet_margin::MarginAccount;
 
+use crate::rewards::{NoteChange, RewardsAccounts};
 use crate::{events, state::*, ChangeKind, ErrorCode, TokenChange};
 
 #[derive(Accounts)]
@@ -100,6 +101,15 @@ pub fn margin_borrow_v2_handler(ctx: Context<MarginBorrowV2>, amount: u64) -> Re
         return Err(ErrorCode::InterestAccrualBehind.into());
     }
 
+    let mut rewards = RewardsAccounts::load(
+        pool,
+        ctx.remaining_accounts,
+        &[ctx.accounts.margin_account.key()],
+    )?;
+    if let Some(rewards) = &mut rewards {
+        rewards.accrue(pool, clock.unix_timestamp);
+    }
+
     // Record a borrow of the tokens requested
     let borrow_amount =
         pool.calculate_full_amount(ctx.accounts.loan_account.amount, change, PoolAction::Borrow)?;
@@ -118,6 +128,14 @@ pub fn margin_borrow_v2_handler(ctx: Context<MarginBorrowV2>, amount: u64) -> Re
         borrow_amount.tokens,
     )?;
 
+    if let Some(mut rewards) = rewards {
+        rewards.update_positions(&[(
+            ctx.accounts.loan_account.key(),
+            NoteChange::increase(ctx.accounts.loan_account.amount, borrow_amount.notes),
+        )]);
+        rewards.exit()?;
+    }
+
     emit!(events::MarginBorrow {
         margin_pool: ctx.accounts.margin_pool.key(),
         user: ctx.accounts.margin_account.key(),
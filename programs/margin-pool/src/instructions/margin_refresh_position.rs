@@ -19,6 +19,7 @@ use anchor_lang::prelude::*;
 
 use jet_margin::{AdapterResult, MarginAccount, PositionChange, PriceChangeInfo};
 
+use crate::rewards::RewardsAccounts;
 use crate::state::*;
 use crate::ErrorCode;
 
@@ -86,5 +87,20 @@ pub fn margin_refresh_position_handler(ctx: Context<MarginRefreshPosition>) -> R
         },
     )?;
 
+    // no notes are moved, so the rewards are only checkpointed if the accounts are given
+    if ctx.remaining_accounts.is_empty() {
+        return Ok(());
+    }
+
+    if let Some(mut rewards) = RewardsAccounts::load(
+        pool,
+        ctx.remaining_accounts,
+        &[ctx.accounts.margin_account.key()],
+    )? {
+        rewards.accrue(pool, Clock::get()?.unix_timestamp);
+        rewards.update_from_margin_account(&ctx.accounts.margin_account)?;
+        rewards.exit()?;
+    }
+
     Ok(())
 }
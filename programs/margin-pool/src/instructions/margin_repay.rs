@@ -23,6 +23,7 @@ use anchor_spl::token::{self, Burn, Token, TokenAccount};
 use jet_margin::MarginAccount;
 use jet_program_common::debug_msg;
 
+use crate::rewards::{NoteChange, RewardsAccounts};
 use crate::{events, state::*, ChangeKind, TokenChange};
 use crate::{Amount, ErrorCode};
 
@@ -107,6 +108,15 @@ pub fn margin_repay_handler(
         return Err(ErrorCode::InterestAccrualBehind.into());
     }
 
+    let mut rewards = RewardsAccounts::load(
+        pool,
+        ctx.remaining_accounts,
+        &[ctx.accounts.margin_account.key()],
+    )?;
+    if let Some(rewards) = &mut rewards {
+        rewards.accrue(pool, clock.unix_timestamp);
+    }
+
     // Amount the user desires to repay, and the amount of deposit notes equivalent to that repayment.
     let repay_amount =
         pool.calculate_full_amount(ctx.accounts.loan_account.amount, change, PoolAction::Repay)?;
@@ -126,6 +136,20 @@ pub fn margin_repay_handler(
     )?;
     token::burn(ctx.accounts.burn_deposit_context(), withdraw_amount.notes)?;
 
+    if let Some(mut rewards) = rewards {
+        rewards.update_positions(&[
+            (
+                ctx.accounts.deposit_account.key(),
+                NoteChange::decrease(ctx.accounts.deposit_account.amount, withdraw_amount.notes),
+            ),
+            (
+                ctx.accounts.loan_account.key(),
+                NoteChange::decrease(ctx.accounts.loan_account.amount, repay_amount.notes),
+            ),
+        ]);
+        rewards.exit()?;
+    }
+
     emit!(events::MarginRepay {
         margin_pool: pool.key(),
         user: ctx.accounts.margin_account.key(),
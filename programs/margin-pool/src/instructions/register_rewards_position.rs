@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+
+use jet_margin::MarginAccount;
+
+use crate::{ErrorCode, MarginPool, PoolRewards, RewardsPosition};
+
+#[derive(Accounts)]
+pub struct RegisterRewardsPosition<'info> {
+    /// The margin account that will earn rewards
+    pub margin_account: AccountLoader<'info, MarginAccount>,
+
+    /// The pool emitting the rewards
+    pub margin_pool: Account<'info, MarginPool>,
+
+    /// The rewards emitted by the pool
+    #[account(mut, has_one = margin_pool)]
+    pub pool_rewards: Account<'info, PoolRewards>,
+
+    /// The account to track the rewards earned by the margin account
+    #[account(init,
+              seeds = [
+                pool_rewards.key().as_ref(),
+                margin_account.key().as_ref()
+              ],
+              bump,
+              payer = payer,
+              space = 8 + std::mem::size_of::<RewardsPosition>())]
+    pub rewards_position: Account<'info, RewardsPosition>,
+
+    /// The payer of rent for the new account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn register_rewards_position_handler(ctx: Context<RegisterRewardsPosition>) -> Result<()> {
+    let pool = &ctx.accounts.margin_pool;
+    let margin_account = ctx.accounts.margin_account.load()?;
+    let rewards = &mut ctx.accounts.pool_rewards;
+    let position = &mut ctx.accounts.rewards_position;
+
+    position.deposit_account = match margin_account.get_position(&pool.deposit_note_mint) {
+        Some(deposit_position) => deposit_position.address,
+        None => {
+            msg!("the deposit position must be registered before earning rewards");
+            return err!(ErrorCode::MissingDepositPosition);
+        }
+    };
+
+    position.pool_rewards = rewards.key();
+    position.margin_account = ctx.accounts.margin_account.key();
+    position.loan_account = Pubkey::find_program_address(
+        &[
+            ctx.accounts.margin_account.key().as_ref(),
+            pool.loan_note_mint.as_ref(),
+        ],
+        &crate::ID,
+    )
+    .0;
+
+    // Start earning from the current balances of the margin account
+    rewards.accrue(pool, Clock::get()?.unix_timestamp);
+    position.update_from_margin_account(rewards, &margin_account);
+
+    Ok(())
+}
@@ -18,6 +18,7 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Burn, Token, TokenAccount, Transfer};
 
+use crate::rewards::{NoteChange, RewardsAccounts};
 use crate::{events, state::PoolAction, ChangeKind, ErrorCode, MarginPool, TokenChange};
 
 #[derive(Accounts)]
@@ -91,6 +92,16 @@ pub fn repay_handler(ctx: Context<Repay>, change_kind: ChangeKind, amount: u64)
         return Err(ErrorCode::InterestAccrualBehind.into());
     }
 
+    let owners = RewardsAccounts::loan_owners(
+        pool,
+        ctx.remaining_accounts,
+        &[ctx.accounts.loan_account.key()],
+    )?;
+    let mut rewards = RewardsAccounts::load(pool, ctx.remaining_accounts, &owners)?;
+    if let Some(rewards) = &mut rewards {
+        rewards.accrue(pool, clock.unix_timestamp);
+    }
+
     // Amount the user desires to repay
     let repay_amount =
         pool.calculate_full_amount(ctx.accounts.loan_account.amount, change, PoolAction::Repay)?;
@@ -115,6 +126,14 @@ pub fn repay_handler(ctx: Context<Repay>, change_kind: ChangeKind, amount: u64)
         repay_amount.notes,
     )?;
 
+    if let Some(mut rewards) = rewards {
+        rewards.update_positions(&[(
+            ctx.accounts.loan_account.key(),
+            NoteChange::decrease(ctx.accounts.loan_account.amount, repay_amount.notes),
+        )]);
+        rewards.exit()?;
+    }
+
     emit!(events::Repay {
         margin_pool: pool.key(),
         user: ctx.accounts.repayment_account_authority.key(),
@@ -20,6 +20,7 @@ use std::ops::Deref;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Burn, Token, Transfer};
 
+use crate::rewards::{NoteChange, RewardsAccounts};
 use crate::{events, state::*, TokenChange};
 use crate::{ChangeKind, ErrorCode};
 
@@ -99,11 +100,14 @@ pub fn withdraw_handler(
         return Err(ErrorCode::InterestAccrualBehind.into());
     }
 
-    let withdraw_amount = pool.calculate_full_amount(
-        token::accessor::amount(&ctx.accounts.source.to_account_info())?,
-        change,
-        PoolAction::Withdraw,
-    )?;
+    let owner = token::accessor::authority(&ctx.accounts.source.to_account_info())?;
+    let mut rewards = RewardsAccounts::load(pool, ctx.remaining_accounts, &[owner])?;
+    if let Some(rewards) = &mut rewards {
+        rewards.accrue(pool, clock.unix_timestamp);
+    }
+
+    let source_notes = token::accessor::amount(&ctx.accounts.source.to_account_info())?;
+    let withdraw_amount = pool.calculate_full_amount(source_notes, change, PoolAction::Withdraw)?;
     pool.withdraw(&withdraw_amount)?;
 
     let pool = &ctx.accounts.margin_pool;
@@ -115,6 +119,14 @@ pub fn withdraw_handler(
     )?;
     token::burn(ctx.accounts.burn_note_context(), withdraw_amount.notes)?;
 
+    if let Some(mut rewards) = rewards {
+        rewards.update_positions(&[(
+            ctx.accounts.source.key(),
+            NoteChange::decrease(source_notes, withdraw_amount.notes),
+        )]);
+        rewards.exit()?;
+    }
+
     emit!(events::Withdraw {
         margin_pool: ctx.accounts.margin_pool.key(),
         user: ctx.accounts.depositor.key(),
@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Token, TokenAccount};
+
+use jet_margin::{MarginAccount, TokenKind};
+
+use crate::rewards::{NoteChange, RewardsAccounts};
+use crate::{events, BadDebt, ErrorCode, MarginPool};
+
+#[derive(Accounts)]
+pub struct WriteOffBadDebt<'info> {
+    /// The insolvent margin account with the loan
+    pub margin_account: AccountLoader<'info, MarginAccount>,
+
+    /// The pool with the outstanding loan
+    #[account(mut, has_one = loan_note_mint)]
+    pub margin_pool: Account<'info, MarginPool>,
+
+    /// The mint for the notes representing loans from the pool
+    /// CHECK:
+    #[account(mut)]
+    pub loan_note_mint: AccountInfo<'info>,
+
+    /// The account with the loan notes to be written off
+    #[account(mut,
+              seeds = [
+                  margin_account.key().as_ref(),
+                  loan_note_mint.key().as_ref()
+              ],
+              bump)]
+    pub loan_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> WriteOffBadDebt<'info> {
+    fn burn_loan_context(&self) -> CpiContext<'_, '_, '_, 'info, Burn<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Burn {
+                mint: self.loan_note_mint.to_account_info(),
+                from: self.loan_account.to_account_info(),
+                authority: self.margin_pool.to_account_info(),
+            },
+        )
+    }
+
+    /// The account is insolvent if it has debt, and no collateral left to repay it
+    fn verify_insolvent(&self) -> Result<()> {
+        let margin_account = self.margin_account.load()?;
+
+        if self.loan_account.amount == 0 {
+            msg!("the margin account has no loan to write off");
+            return err!(ErrorCode::AccountNotInsolvent);
+        }
+
+        if let Some(position) = margin_account
+            .positions()
+            .find(|p| p.kind() != TokenKind::Claim && p.balance > 0)
+        {
+            msg!(
+                "the margin account still has collateral in {}",
+                position.token
+            );
+            return err!(ErrorCode::AccountNotInsolvent);
+        }
+
+        Ok(())
+    }
+}
+
+pub fn write_off_bad_debt_handler(ctx: Context<WriteOffBadDebt>) -> Result<BadDebt> {
+    ctx.accounts.verify_insolvent()?;
+
+    let pool = &mut ctx.accounts.margin_pool;
+    let clock = Clock::get()?;
+
+    // Make sure interest accrual is up-to-date
+    if !pool.accrue_interest(clock.unix_timestamp) {
+        msg!("interest accrual is too far behind");
+        return Err(ErrorCode::InterestAccrualBehind.into());
+    }
+
+    let mut rewards = RewardsAccounts::load(
+        pool,
+        ctx.remaining_accounts,
+        &[ctx.accounts.margin_account.key()],
+    )?;
+    if let Some(rewards) = &mut rewards {
+        rewards.accrue(pool, clock.unix_timestamp);
+    }
+
+    let loan_notes = ctx.accounts.loan_account.amount;
+    let bad_debt = pool.write_off(loan_notes)?;
+
+    msg!(
+        "Writing off [{} tokens, {} notes] of bad debt",
+        bad_debt.tokens,
+        bad_debt.notes
+    );
+
+    let pool = &ctx.accounts.margin_pool;
+    let signer = [&pool.signer_seeds()?[..]];
+
+    token::burn(
+        ctx.accounts.burn_loan_context().with_signer(&signer),
+        bad_debt.notes,
+    )?;
+
+    if let Some(mut rewards) = rewards {
+        rewards.update_positions(&[(
+            ctx.accounts.loan_account.key(),
+            NoteChange::decrease(loan_notes, bad_debt.notes),
+        )]);
+        rewards.exit()?;
+    }
+
+    emit!(events::BadDebtWrittenOff {
+        margin_pool: pool.key(),
+        margin_account: ctx.accounts.margin_account.key(),
+        loan_account: ctx.accounts.loan_account.key(),
+        bad_debt,
+        summary: (&pool.clone().into_inner()).into(),
+    });
+
+    Ok(bad_debt)
+}
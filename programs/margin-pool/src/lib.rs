@@ -21,11 +21,13 @@
 use anchor_lang::prelude::*;
 
 mod instructions;
+mod rewards;
 mod state;
 mod util;
 use instructions::*;
 
-pub use state::{MarginPool, MarginPoolConfig, PoolAction, PoolFlags};
+pub use rewards::{PoolRewards, RewardsConfig, RewardsPosition};
+pub use state::{BadDebt, FullAmount, MarginPool, MarginPoolConfig, PoolAction, PoolFlags};
 pub mod events;
 
 declare_id!("JPPooLEqRo3NCSx82EdE2VZY5vUaSsgskpZPBHNGVLZ");
@@ -366,10 +368,192 @@ mod jet_margin_pool {
         instructions::close_loan_handler(ctx)
     }
 
-    /// Administrative function for moving loans between accounts
+    /// Write off the loans of an insolvent margin account
+    ///
+    /// An account is insolvent when it has no collateral left after being
+    /// liquidated, but still has outstanding loan notes. The loan notes are
+    /// burned, with the bad debt absorbed by any uncollected fees first, and
+    /// the remainder lowering the value of the pool's deposit notes.
+    ///
+    /// The margin account's loan position should be updated afterwards with
+    /// the margin program's `update_position_balance`.
+    ///
+    /// # [Accounts](jet_margin_pool::accounts::WriteOffBadDebt)
+    ///
+    /// |     |     |     |
+    /// | --- | --- | --- |
+    /// | **Name** | **Type** | **Description** |
+    /// | `margin_account` | `read_only` | The insolvent margin account with the loan. |
+    /// | `margin_pool` | `writable` | The pool with the outstanding loan. |
+    /// | `loan_note_mint` | `writable` | The mint for the notes representing loans from the pool. |
+    /// | `loan_account` | `writable` | The account with the loan notes to be written off. |
+    /// | `token_program` | `read_only` | The [spl token program](https://spl.solana.com/token). |
+    ///
+    /// # Events
+    ///
+    /// |     |     |
+    /// | --- | --- |
+    /// | **Event Name** | **Description** |
+    /// | [`events::BadDebtWrittenOff`] | The bad debt that was written off. |
+    ///
+    /// The bad debt is also returned, so that a program invoking this can
+    /// record a claim for the loss, such as the staking insurance fund.
+    ///
+    pub fn write_off_bad_debt(ctx: Context<WriteOffBadDebt>) -> Result<BadDebt> {
+        instructions::write_off_bad_debt_handler(ctx)
+    }
+
+    /// Lend tokens from the pool's vault, which must be repaid with a fee by a
+    /// `flash_loan_end` instruction later in the same transaction.
+    ///
+    /// The next flash loan instruction of the pool in the transaction must be the
+    /// `flash_loan_end` for the same amount, so flash loans cannot be nested.
+    ///
+    /// # Parameters
+    ///
+    /// * `amount` - The amount of tokens to borrow.
+    ///
+    /// # [Accounts](jet_margin_pool::accounts::FlashLoanBegin)
+    ///
+    /// |     |     |     |
+    /// | --- | --- | --- |
+    /// | **Name** | **Type** | **Description** |
+    /// | `margin_pool` | `read_only` | The pool to borrow from. |
+    /// | `vault` | `writable` | The vault responsible for storing the pool's tokens. |
+    /// | `destination` | `writable` | The token account to receive the loan. |
+    /// | `instructions` | `read_only` | The [instructions sysvar](https://docs.solana.com/developing/runtime-facilities/sysvars#instructions). |
+    /// | `token_program` | `read_only` | The [spl token program](https://spl.solana.com/token). |
+    ///
+    /// # Events
+    ///
+    /// |     |     |
+    /// | --- | --- |
+    /// | **Event Name** | **Description** |
+    /// | [`events::FlashLoanBegin`] | The tokens lent. |
+    ///
+    pub fn flash_loan_begin(ctx: Context<FlashLoanBegin>, amount: u64) -> Result<()> {
+        instructions::flash_loan_begin_handler(ctx, amount)
+    }
+
+    /// Repay a flash loan, along with the fee set by the pool's `flash_loan_fee_rate`,
+    /// which is earned by depositors less the pool's management fee.
+    ///
+    /// # Parameters
+    ///
+    /// * `amount` - The amount of tokens that were borrowed.
+    ///
+    /// # [Accounts](jet_margin_pool::accounts::FlashLoanEnd)
+    ///
+    /// |     |     |     |
+    /// | --- | --- | --- |
+    /// | **Name** | **Type** | **Description** |
+    /// | `repayment_account_authority` | `Signer` | Signing authority for the repaying token account. |
+    /// | `margin_pool` | `writable` | The pool that was borrowed from. |
+    /// | `vault` | `writable` | The vault responsible for storing the pool's tokens. |
+    /// | `repayment_token_account` | `writable` | The token account repaying the loan and fee. |
+    /// | `instructions` | `read_only` | The [instructions sysvar](https://docs.solana.com/developing/runtime-facilities/sysvars#instructions). |
+    /// | `token_program` | `read_only` | The [spl token program](https://spl.solana.com/token). |
+    ///
+    /// # Events
+    ///
+    /// |     |     |
+    /// | --- | --- |
+    /// | **Event Name** | **Description** |
+    /// | [`events::FlashLoanEnd`] | The loan and fee repaid. |
+    ///
+    pub fn flash_loan_end(ctx: Context<FlashLoanEnd>, amount: u64) -> Result<()> {
+        instructions::flash_loan_end_handler(ctx, amount)
+    }
+
+    /// Administrative function for moving loans between accounts, checkpointing
+    /// the rewards of both accounts if the pool has rewards configured
     pub fn admin_transfer_loan(ctx: Context<AdminTransferLoan>, amount: u64) -> Result<()> {
         instructions::admin_transfer_loan_handler(ctx, amount)
     }
+
+    /// Administrative function for configuring the liquidity mining rewards
+    /// emitted by a pool, creating the rewards account if needed.
+    ///
+    /// # Parameters
+    ///
+    /// * `config` - The rates and end time for the emission of rewards.
+    ///
+    /// # [Accounts](jet_margin_pool::accounts::ConfigureRewards)
+    ///
+    /// |     |     |     |
+    /// | --- | --- | --- |
+    /// | **Name** | **Type** | **Description** |
+    /// | `authority` | `Signer` | The administrative authority. |
+    /// | `margin_pool` | `writable` | The pool emitting the rewards. |
+    /// | `reward_mint` | `read_only` | The mint for the reward token. |
+    /// | `pool_rewards` | `writable` | The account tracking the rewards emitted by the pool. |
+    /// | `reward_vault` | `writable` | The token account holding the rewards to be claimed. |
+    /// | `payer` | `Signer` | The payer of rent for new accounts. |
+    /// | `token_program` | `read_only` | The [spl token program](https://spl.solana.com/token). |
+    /// | `system_program` | `read_only` | The [system native program](https://docs.solana.com/developing/runtime-facilities/programs#system-program). |
+    /// | `rent` | `read_only` | The [rent sysvar](https://docs.solana.com/developing/runtime-facilities/sysvars#rent). |
+    ///
+    /// # Events
+    ///
+    /// |     |     |
+    /// | --- | --- |
+    /// | **Event Name** | **Description** |
+    /// | [`events::RewardsConfigured`] | The new rewards config. |
+    ///
+    pub fn configure_rewards(ctx: Context<ConfigureRewards>, config: RewardsConfig) -> Result<()> {
+        instructions::configure_rewards_handler(ctx, config)
+    }
+
+    /// Start tracking the liquidity mining rewards earned by a margin account.
+    ///
+    /// Once registered, the rewards are checkpointed by every instruction that
+    /// changes the notes held by the margin account. While the pool has rewards
+    /// configured, those instructions require the `pool_rewards` account followed
+    /// by the `rewards_position` of each margin account whose notes change as
+    /// their remaining accounts. `repay` and `admin_transfer_loan` also require
+    /// the margin accounts owning the loans after the positions.
+    ///
+    /// # [Accounts](jet_margin_pool::accounts::RegisterRewardsPosition)
+    ///
+    /// |     |     |     |
+    /// | --- | --- | --- |
+    /// | **Name** | **Type** | **Description** |
+    /// | `margin_account` | `read_only` | The margin account that will earn rewards. |
+    /// | `margin_pool` | `read_only` | The pool emitting the rewards. |
+    /// | `pool_rewards` | `writable` | The rewards emitted by the pool. |
+    /// | `rewards_position` | `writable` | The account to track the rewards earned by the margin account. |
+    /// | `payer` | `Signer` | The payer of rent for the new account. |
+    /// | `system_program` | `read_only` | The [system native program](https://docs.solana.com/developing/runtime-facilities/programs#system-program). |
+    ///
+    pub fn register_rewards_position(ctx: Context<RegisterRewardsPosition>) -> Result<()> {
+        instructions::register_rewards_position_handler(ctx)
+    }
+
+    /// Claim the liquidity mining rewards earned by a margin account
+    ///
+    /// # [Accounts](jet_margin_pool::accounts::ClaimRewards)
+    ///
+    /// |     |     |     |
+    /// | --- | --- | --- |
+    /// | **Name** | **Type** | **Description** |
+    /// | `margin_account` | `Signer` | The margin account that earned the rewards. |
+    /// | `margin_pool` | `read_only` | The pool emitting the rewards. |
+    /// | `pool_rewards` | `writable` | The rewards emitted by the pool. |
+    /// | `rewards_position` | `writable` | The account tracking the rewards earned by the margin account. |
+    /// | `reward_vault` | `writable` | The token account holding the rewards to be claimed. |
+    /// | `destination` | `writable` | The account to receive the rewards. |
+    /// | `token_program` | `read_only` | The [spl token program](https://spl.solana.com/token). |
+    ///
+    /// # Events
+    ///
+    /// |     |     |
+    /// | --- | --- |
+    /// | **Event Name** | **Description** |
+    /// | [`events::RewardsClaimed`] | The rewards claimed. |
+    ///
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        instructions::claim_rewards_handler(ctx)
+    }
 }
 
 /// Interface for changing the token value of an account through pool instructions
@@ -499,4 +683,28 @@ pub enum ErrorCode {
     /// 141109 - This borrow pushes the pool util ratio above the limit for new borrows
     #[msg("This borrow pushes the pool util ratio above the limit for new borrows")]
     ExceedsMaxBorrowUtilRatio,
+
+    /// 141110 - The rewards accounts do not belong to the pool
+    #[msg("The rewards accounts do not belong to the pool")]
+    InvalidRewardsAccount,
+
+    /// 141111 - The margin account has no deposit position in the pool
+    #[msg("The margin account has no deposit position in the pool")]
+    MissingDepositPosition,
+
+    /// 141112 - The margin account still has collateral, or has no loan
+    #[msg("The margin account is not insolvent")]
+    AccountNotInsolvent,
+
+    /// 141113 - A flash loan is not matched by a repayment in the same transaction
+    #[msg("A flash loan must be repaid in the same transaction")]
+    FlashLoanNotRepaid,
+
+    /// 141114 - Flash loans can only be top level instructions
+    #[msg("Flash loans cannot be invoked by other programs")]
+    FlashLoanInvokedByProgram,
+
+    /// 141115 - The pool has rewards, and the accounts to track them were not given
+    #[msg("The rewards accounts are required for this pool")]
+    MissingRewardsAccounts,
 }
@@ -0,0 +1,502 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::cmp::min;
+
+use anchor_lang::{prelude::*, solana_program::clock::UnixTimestamp};
+use jet_margin::MarginAccount;
+use jet_program_common::Number;
+
+use crate::{ErrorCode, MarginPool};
+
+/// Parameters for the emission of liquidity mining rewards from a pool
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RewardsConfig {
+    /// The amount of reward tokens emitted per second, shared between all depositors
+    pub deposit_rate: u64,
+
+    /// The amount of reward tokens emitted per second, shared between all borrowers
+    pub loan_rate: u64,
+
+    /// The time at which the emission of rewards stops
+    pub end_at: i64,
+}
+
+/// Account tracking the liquidity mining rewards emitted by a pool.
+///
+/// Rewards are tracked as an index of the reward tokens earned per note, which
+/// is advanced whenever the notes in the pool change.
+#[account]
+#[derive(Default)]
+pub struct PoolRewards {
+    /// The pool emitting the rewards
+    pub margin_pool: Pubkey,
+
+    /// The bump seed used to create this address
+    pub bump: [u8; 1],
+
+    /// The mint for the reward token
+    pub reward_mint: Pubkey,
+
+    /// The token account holding the rewards to be claimed
+    pub reward_vault: Pubkey,
+
+    /// The current emission parameters
+    pub config: RewardsConfig,
+
+    /// The rewards emitted per deposit note, up to `accrued_until`
+    pub deposit_index: [u8; 24],
+
+    /// The rewards emitted per loan note, up to `accrued_until`
+    pub loan_index: [u8; 24],
+
+    /// The time the rewards were last accrued up to
+    pub accrued_until: i64,
+}
+
+impl PoolRewards {
+    pub fn signer_seeds(&self) -> [&[u8]; 3] {
+        [
+            self.margin_pool.as_ref(),
+            b"rewards".as_ref(),
+            self.bump.as_ref(),
+        ]
+    }
+
+    /// Advance the reward indices for the time elapsed since the last accrual.
+    ///
+    /// This must be called before the note totals of the pool change, so that
+    /// the rewards are shared out over the notes that earned them.
+    pub fn accrue(&mut self, pool: &MarginPool, time: UnixTimestamp) {
+        let until = min(time, self.config.end_at);
+
+        if until <= self.accrued_until {
+            return;
+        }
+
+        let elapsed = Number::from((until - self.accrued_until) as u64);
+
+        if pool.deposit_notes > 0 {
+            let emitted = Number::from(self.config.deposit_rate) * elapsed;
+            self.deposit_index =
+                (self.deposit_index() + emitted / Number::from(pool.deposit_notes)).into_bits();
+        }
+
+        if pool.loan_notes > 0 {
+            let emitted = Number::from(self.config.loan_rate) * elapsed;
+            self.loan_index =
+                (self.loan_index() + emitted / Number::from(pool.loan_notes)).into_bits();
+        }
+
+        self.accrued_until = until;
+    }
+
+    pub fn deposit_index(&self) -> Number {
+        Number::from_bits(self.deposit_index)
+    }
+
+    pub fn loan_index(&self) -> Number {
+        Number::from_bits(self.loan_index)
+    }
+}
+
+/// Account tracking the rewards earned by a margin account from a pool
+#[account]
+#[derive(Default)]
+pub struct RewardsPosition {
+    /// The rewards being earned
+    pub pool_rewards: Pubkey,
+
+    /// The margin account earning the rewards
+    pub margin_account: Pubkey,
+
+    /// The token account holding the margin account's deposit notes
+    pub deposit_account: Pubkey,
+
+    /// The token account holding the margin account's loan notes
+    pub loan_account: Pubkey,
+
+    /// The deposit notes held at the last checkpoint
+    pub deposit_notes: u64,
+
+    /// The loan notes held at the last checkpoint
+    pub loan_notes: u64,
+
+    /// The pool's deposit reward index at the last checkpoint
+    pub deposit_index: [u8; 24],
+
+    /// The pool's loan reward index at the last checkpoint
+    pub loan_index: [u8; 24],
+
+    /// The rewards earned that have not been claimed yet
+    pub unclaimed: [u8; 24],
+}
+
+impl RewardsPosition {
+    /// Checkpoint the rewards earned since the last update, and record the
+    /// new note balances.
+    ///
+    /// Rewards are only earned on the lower of the balance recorded at the
+    /// last checkpoint and the balance before this change, so notes moved in
+    /// without a checkpoint don't earn rewards for time they weren't held.
+    pub fn update(
+        &mut self,
+        rewards: &PoolRewards,
+        deposit: Option<NoteChange>,
+        loan: Option<NoteChange>,
+    ) {
+        let deposit = deposit.unwrap_or_else(|| NoteChange::unchanged(self.deposit_notes));
+        let loan = loan.unwrap_or_else(|| NoteChange::unchanged(self.loan_notes));
+
+        let deposit_earned = Number::from(min(self.deposit_notes, deposit.before))
+            * (rewards.deposit_index() - Number::from_bits(self.deposit_index));
+        let loan_earned = Number::from(min(self.loan_notes, loan.before))
+            * (rewards.loan_index() - Number::from_bits(self.loan_index));
+
+        self.unclaimed = (self.unclaimed() + deposit_earned + loan_earned).into_bits();
+        self.deposit_index = rewards.deposit_index;
+        self.loan_index = rewards.loan_index;
+        self.deposit_notes = deposit.after;
+        self.loan_notes = loan.after;
+    }
+
+    /// Checkpoint the rewards using the balances registered in the margin account
+    pub fn update_from_margin_account(&mut self, rewards: &PoolRewards, account: &MarginAccount) {
+        let balance = |token_account: &Pubkey| {
+            account
+                .positions()
+                .find(|p| p.address == *token_account)
+                .map(|p| NoteChange::unchanged(p.balance))
+        };
+
+        let deposit = balance(&self.deposit_account).unwrap_or(NoteChange::unchanged(0));
+        let loan = balance(&self.loan_account).unwrap_or(NoteChange::unchanged(0));
+
+        self.update(rewards, Some(deposit), Some(loan));
+    }
+
+    /// Take up to `max` of the unclaimed rewards, returning the amount taken
+    pub fn claim(&mut self, max: u64) -> u64 {
+        let amount = min(max, self.unclaimed().as_u64(0));
+        self.unclaimed = (self.unclaimed() - Number::from(amount)).into_bits();
+
+        amount
+    }
+
+    pub fn unclaimed(&self) -> Number {
+        Number::from_bits(self.unclaimed)
+    }
+}
+
+/// A change in the notes held in a token account
+#[derive(Debug, Clone, Copy)]
+pub struct NoteChange {
+    pub before: u64,
+    pub after: u64,
+}
+
+impl NoteChange {
+    pub const fn unchanged(notes: u64) -> Self {
+        Self {
+            before: notes,
+            after: notes,
+        }
+    }
+
+    pub const fn increase(before: u64, notes: u64) -> Self {
+        Self {
+            before,
+            after: before + notes,
+        }
+    }
+
+    pub const fn decrease(before: u64, notes: u64) -> Self {
+        Self {
+            before,
+            after: before - notes,
+        }
+    }
+}
+
+/// The accounts used to track rewards, which are passed as the remaining
+/// accounts of instructions that change the notes in a pool.
+///
+/// The first account is the [PoolRewards] for the pool, followed by the address
+/// of the [RewardsPosition] for each margin account whose notes are changing.
+/// A position is only checkpointed once it has been registered, but its address
+/// must always be given so that a registered position can't be left out.
+///
+/// These accounts are required when the pool has rewards configured, so programs
+/// using the pool through CPI need to pass them on, as the margin-swap program does.
+pub struct RewardsAccounts<'info> {
+    pub pool_rewards: Account<'info, PoolRewards>,
+    pub positions: Vec<Account<'info, RewardsPosition>>,
+}
+
+impl<'info> RewardsAccounts<'info> {
+    /// Load the rewards accounts for a change to the notes owned by the given
+    /// margin accounts, if the pool has rewards configured
+    pub fn load(
+        pool: &MarginPool,
+        remaining_accounts: &[AccountInfo<'info>],
+        owners: &[Pubkey],
+    ) -> Result<Option<Self>> {
+        if !pool.has_rewards() {
+            return Ok(None);
+        }
+
+        let pool_rewards = match remaining_accounts.first() {
+            Some(info) => Account::<PoolRewards>::try_from(info)?,
+            None => {
+                msg!("the pool has rewards, so the rewards accounts are required");
+                return err!(ErrorCode::MissingRewardsAccounts);
+            }
+        };
+
+        let expected_address =
+            Pubkey::create_program_address(&pool_rewards.signer_seeds(), &crate::ID)
+                .map_err(|_| error!(ErrorCode::InvalidRewardsAccount))?;
+
+        if pool_rewards.margin_pool != pool.address || pool_rewards.key() != expected_address {
+            msg!("the rewards account is for a different pool");
+            return err!(ErrorCode::InvalidRewardsAccount);
+        }
+
+        let mut positions: Vec<Account<RewardsPosition>> = vec![];
+
+        for (index, owner) in owners.iter().enumerate() {
+            let info = match remaining_accounts.get(1 + index) {
+                Some(info) => info,
+                None => {
+                    msg!("the rewards position for {} is required", owner);
+                    return err!(ErrorCode::MissingRewardsAccounts);
+                }
+            };
+
+            let (expected_address, _) = Pubkey::find_program_address(
+                &[pool_rewards.key().as_ref(), owner.as_ref()],
+                &crate::ID,
+            );
+
+            if info.key() != expected_address {
+                msg!("the rewards position is not for {}", owner);
+                return err!(ErrorCode::InvalidRewardsAccount);
+            }
+
+            // the position is skipped if it hasn't been registered yet
+            if *info.owner == crate::ID && !positions.iter().any(|p| p.key() == info.key()) {
+                positions.push(Account::<RewardsPosition>::try_from(info)?);
+            }
+        }
+
+        Ok(Some(Self {
+            pool_rewards,
+            positions,
+        }))
+    }
+
+    /// Find the margin accounts that own the given loan accounts, for instructions
+    /// where they aren't otherwise known.
+    ///
+    /// The margin accounts follow the rewards positions in the remaining
+    /// accounts, and are only required when the pool has rewards configured.
+    pub fn loan_owners(
+        pool: &MarginPool,
+        remaining_accounts: &[AccountInfo<'info>],
+        loan_accounts: &[Pubkey],
+    ) -> Result<Vec<Pubkey>> {
+        if !pool.has_rewards() {
+            return Ok(vec![]);
+        }
+
+        let first = 1 + loan_accounts.len();
+
+        loan_accounts
+            .iter()
+            .enumerate()
+            .map(|(index, loan_account)| {
+                let owner = match remaining_accounts.get(first + index) {
+                    Some(info) => info.key(),
+                    None => {
+                        msg!("the margin account owning {} is required", loan_account);
+                        return err!(ErrorCode::MissingRewardsAccounts);
+                    }
+                };
+
+                let (expected_address, _) = Pubkey::find_program_address(
+                    &[owner.as_ref(), pool.loan_note_mint.as_ref()],
+                    &crate::ID,
+                );
+
+                if *loan_account != expected_address {
+                    msg!("the loan account does not belong to {}", owner);
+                    return err!(ErrorCode::InvalidRewardsAccount);
+                }
+
+                Ok(owner)
+            })
+            .collect()
+    }
+
+    /// Accrue the rewards emitted by the pool, before its notes change
+    pub fn accrue(&mut self, pool: &MarginPool, time: UnixTimestamp) {
+        self.pool_rewards.accrue(pool, time);
+    }
+
+    /// Checkpoint the rewards of each position for changes to token accounts.
+    ///
+    /// Changes to token accounts not tracked by a position are ignored.
+    pub fn update_positions(&mut self, changes: &[(Pubkey, NoteChange)]) {
+        let change_for = |token_account: &Pubkey| {
+            changes
+                .iter()
+                .find(|(account, _)| account == token_account)
+                .map(|(_, change)| *change)
+        };
+
+        for position in &mut self.positions {
+            let deposit = change_for(&position.deposit_account);
+            let loan = change_for(&position.loan_account);
+
+            position.update(&self.pool_rewards, deposit, loan);
+        }
+    }
+
+    /// Checkpoint the user's rewards using the balances registered in their margin account
+    pub fn update_from_margin_account(
+        &mut self,
+        margin_account: &AccountLoader<MarginAccount>,
+    ) -> Result<()> {
+        for position in &mut self.positions {
+            if position.margin_account != margin_account.key() {
+                msg!("the rewards position is for a different margin account");
+                return err!(ErrorCode::InvalidRewardsAccount);
+            }
+
+            position.update_from_margin_account(&self.pool_rewards, &*margin_account.load()?);
+        }
+
+        Ok(())
+    }
+
+    /// Persist the changes to the rewards accounts
+    pub fn exit(&self) -> Result<()> {
+        self.pool_rewards.exit(&crate::ID)?;
+
+        for position in &self.positions {
+            position.exit(&crate::ID)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(deposit_notes: u64, loan_notes: u64) -> MarginPool {
+        MarginPool {
+            deposit_notes,
+            loan_notes,
+            ..Default::default()
+        }
+    }
+
+    fn rewards() -> PoolRewards {
+        PoolRewards {
+            config: RewardsConfig {
+                deposit_rate: 100,
+                loan_rate: 50,
+                end_at: 1_000,
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rewards_are_shared_by_notes() {
+        let mut rewards = rewards();
+        let mut alice = RewardsPosition::default();
+        let mut bob = RewardsPosition::default();
+
+        alice.update(&rewards, Some(NoteChange::increase(0, 300)), None);
+        bob.update(
+            &rewards,
+            Some(NoteChange::increase(0, 100)),
+            Some(NoteChange::increase(0, 10)),
+        );
+
+        rewards.accrue(&pool(400, 10), 10);
+        alice.update(&rewards, None, None);
+        bob.update(&rewards, None, None);
+
+        assert_eq!(750, alice.claim(u64::MAX));
+        assert_eq!(750, bob.claim(u64::MAX));
+    }
+
+    #[test]
+    fn rewards_stop_at_end() {
+        let mut rewards = rewards();
+        let mut position = RewardsPosition::default();
+
+        position.update(&rewards, Some(NoteChange::increase(0, 1)), None);
+        rewards.accrue(&pool(1, 0), 2_000);
+        position.update(&rewards, None, None);
+
+        assert_eq!(1_000, rewards.accrued_until);
+        assert_eq!(40, position.claim(40));
+        assert_eq!(99_960, position.claim(u64::MAX));
+    }
+
+    #[test]
+    fn unrecorded_notes_earn_nothing() {
+        let mut rewards = rewards();
+        let mut position = RewardsPosition::default();
+
+        position.update(&rewards, Some(NoteChange::increase(0, 10)), None);
+        rewards.accrue(&pool(20, 0), 10);
+
+        // notes were moved in without a checkpoint
+        position.update(&rewards, Some(NoteChange::increase(20, 10)), None);
+        rewards.accrue(&pool(40, 0), 20);
+        position.update(&rewards, Some(NoteChange::unchanged(30)), None);
+
+        assert_eq!(500 + 750, position.claim(u64::MAX));
+    }
+
+    #[test]
+    fn transferred_notes_are_earned_once() {
+        let mut rewards = rewards();
+        let mut alice = RewardsPosition::default();
+        let mut bob = RewardsPosition::default();
+
+        alice.update(&rewards, None, Some(NoteChange::increase(0, 10)));
+        bob.update(&rewards, None, Some(NoteChange::increase(0, 10)));
+        rewards.accrue(&pool(0, 20), 10);
+
+        // alice's loan is moved to bob, with both checkpointed
+        alice.update(&rewards, None, Some(NoteChange::decrease(10, 10)));
+        bob.update(&rewards, None, Some(NoteChange::increase(10, 10)));
+        rewards.accrue(&pool(0, 20), 20);
+        alice.update(&rewards, None, None);
+        bob.update(&rewards, None, None);
+
+        assert_eq!(250, alice.claim(u64::MAX));
+        assert_eq!(250 + 500, bob.claim(u64::MAX));
+    }
+}
@@ -16,7 +16,7 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 use std::cmp::Ordering;
-use std::convert::TryFrom;
+use std::convert::{TryFrom, TryInto};
 
 use anchor_lang::{prelude::*, solana_program::clock::UnixTimestamp};
 use jet_program_common::{Number, BPS_EXPONENT};
@@ -255,6 +255,70 @@ impl MarginPool {
         Ok(())
     }
 
+    /// Write off loan notes that will never be repaid.
+    ///
+    /// The bad debt is absorbed by any uncollected fees first, with the remainder
+    /// being a loss to depositors through a lower deposit note exchange rate.
+    pub fn write_off(&mut self, notes: u64) -> Result<BadDebt> {
+        let amount = self.convert_amount(Amount::notes(notes), PoolAction::Repay)?;
+
+        self.loan_notes = self
+            .loan_notes
+            .checked_sub(amount.notes)
+            .ok_or(ErrorCode::InsufficientLiquidity)?;
+
+        *self.total_borrowed_mut() = self
+            .total_borrowed()
+            .saturating_sub(Number::from(amount.tokens));
+
+        let bad_debt = Number::from(amount.tokens);
+        let uncollected = *self.total_uncollected_fees();
+        let absorbed = std::cmp::min(uncollected, bad_debt);
+
+        *self.total_uncollected_fees_mut() = uncollected - absorbed;
+
+        Ok(BadDebt {
+            notes: amount.notes,
+            tokens: amount.tokens,
+            fees_absorbed: absorbed.as_u64(0),
+            depositor_loss: (bad_debt - absorbed).as_u64_ceil(0),
+        })
+    }
+
+    /// Check that the pool can lend out tokens for a flash loan
+    pub fn verify_flash_loan(&self, amount: u64) -> Result<()> {
+        if !self.flags().contains(PoolFlags::ALLOW_LENDING) {
+            msg!("this pool only allows deposits");
+            return err!(ErrorCode::DepositsOnly);
+        }
+        if amount == 0 {
+            return err!(ErrorCode::InvalidAmount);
+        }
+        if amount > self.deposit_tokens {
+            return err!(ErrorCode::InsufficientLiquidity);
+        }
+
+        Ok(())
+    }
+
+    /// Calculate the fee charged for a flash loan, which is rounded up
+    pub fn flash_loan_fee(&self, amount: u64) -> u64 {
+        let fee = (amount as u128 * self.config.flash_loan_fee_rate as u128 + 9_999) / 10_000;
+
+        fee.try_into().unwrap()
+    }
+
+    /// Record the fee paid for a flash loan.
+    ///
+    /// The fee is earned by depositors, less the management fee, in the same way
+    /// as the interest paid on loans.
+    pub fn collect_flash_loan_fee(&mut self, fee: u64) {
+        let fee_to_collect = Number::from(fee) * Number::from_bps(self.config.management_fee_rate);
+
+        self.deposit_tokens = self.deposit_tokens.checked_add(fee).unwrap();
+        *self.total_uncollected_fees_mut() += fee_to_collect;
+    }
+
     /// Accrue interest charges on outstanding borrows
     ///
     /// Returns true if the interest was fully accumulated, false if it was
@@ -535,6 +599,11 @@ impl MarginPool {
         bytemuck::from_bytes(&self.borrowed_tokens)
     }
 
+    /// Whether the pool has liquidity mining rewards configured
+    pub fn has_rewards(&self) -> bool {
+        self.flags().contains(PoolFlags::REWARDS)
+    }
+
     fn flags(&self) -> PoolFlags {
         PoolFlags::from_bits_truncate(self.config.flags)
     }
@@ -606,6 +675,22 @@ impl RoundingDirection {
     }
 }
 
+/// The result of writing off bad debt from a pool
+#[derive(AnchorDeserialize, AnchorSerialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub struct BadDebt {
+    /// The loan notes written off
+    pub notes: u64,
+
+    /// The tokens owed for the loan notes
+    pub tokens: u64,
+
+    /// The tokens covered by fees that hadn't been collected yet
+    pub fees_absorbed: u64,
+
+    /// The tokens lost by depositors
+    pub depositor_loss: u64,
+}
+
 pub struct PriceResult {
     pub deposit_note_price: i64,
     pub deposit_note_conf: u64,
@@ -643,9 +728,13 @@ pub struct MarginPoolConfig {
     /// The fee rate applied to interest payments collected
     pub management_fee_rate: u16,
 
+    /// The fee rate charged on flash loans (bps)
+    #[cfg_attr(feature = "no-entrypoint", serde(default))]
+    pub flash_loan_fee_rate: u16,
+
     /// Unused
     #[cfg_attr(feature = "no-entrypoint", serde(default))]
-    pub reserved: u64,
+    pub reserved: [u8; 6],
 }
 
 bitflags::bitflags! {
@@ -656,6 +745,12 @@ bitflags::bitflags! {
 
         /// The pool is allowed to lend out deposits for borrowing
         const ALLOW_LENDING = 1 << 1;
+
+        /// The pool emits liquidity mining rewards, so every change to its
+        /// notes must checkpoint the rewards of the accounts holding them.
+        /// This is set by `configure_rewards`, and kept when the pool is
+        /// reconfigured.
+        const REWARDS = 1 << 2;
     }
 }
 
@@ -707,6 +802,68 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_write_off_absorbs_fees_first() -> Result<()> {
+        let mut margin_pool = MarginPool::default();
+        margin_pool.config.flags = PoolFlags::ALLOW_LENDING.bits();
+
+        margin_pool.deposit(&FullAmount {
+            tokens: 1_000_000,
+            notes: 1_000_000,
+        });
+        margin_pool.borrow(&FullAmount {
+            tokens: 500_000,
+            notes: 500_000,
+        })?;
+        *margin_pool.total_uncollected_fees_mut() = Number::from(100_000u64);
+
+        assert_eq!(margin_pool.deposit_note_exchange_rate().as_u64(-1), 9);
+
+        let bad_debt = margin_pool.write_off(200_000)?;
+
+        assert_eq!(
+            bad_debt,
+            BadDebt {
+                notes: 200_000,
+                tokens: 200_000,
+                fees_absorbed: 100_000,
+                depositor_loss: 100_000,
+            }
+        );
+        assert_eq!(margin_pool.loan_notes, 300_000);
+        assert_eq!(margin_pool.total_borrowed().as_u64(0), 300_000);
+        assert_eq!(*margin_pool.total_uncollected_fees(), Number::ZERO);
+        assert_eq!(margin_pool.deposit_note_exchange_rate().as_u64(-1), 8);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_flash_loan_fee_goes_to_depositors() -> Result<()> {
+        let mut margin_pool = MarginPool::default();
+        margin_pool.config.flags = PoolFlags::ALLOW_LENDING.bits();
+        margin_pool.config.management_fee_rate = 10_00;
+        margin_pool.config.flash_loan_fee_rate = 9;
+
+        margin_pool.deposit(&FullAmount {
+            tokens: 1_000_000,
+            notes: 1_000_000,
+        });
+        margin_pool.verify_flash_loan(1_000_000)?;
+        assert!(margin_pool.verify_flash_loan(1_000_001).is_err());
+
+        assert_eq!(margin_pool.flash_loan_fee(1_000_000), 900);
+        assert_eq!(margin_pool.flash_loan_fee(1), 1);
+
+        margin_pool.collect_flash_loan_fee(100_000);
+
+        assert_eq!(margin_pool.deposit_tokens, 1_100_000);
+        assert_eq!(margin_pool.total_uncollected_fees().as_u64(0), 10_000);
+        assert_eq!(margin_pool.deposit_note_exchange_rate().as_u64(-2), 109);
+
+        Ok(())
+    }
+
     #[test]
     fn test_deposit_note_rounding() -> Result<()> {
         let mut margin_pool = MarginPool::default();
@@ -15,18 +15,22 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+mod conditional_order;
 mod margin_spl_swap;
 mod openbook_accounts;
 mod openbook_swap;
 mod orca_whirlpool_swap;
+mod phoenix_swap;
 mod route_swap;
 mod saber_swap;
 mod spl_token_swap;
 
+pub use conditional_order::*;
 pub use margin_spl_swap::*;
 pub use openbook_accounts::*;
 pub use openbook_swap::*;
 pub use orca_whirlpool_swap::*;
+pub use phoenix_swap::*;
 pub use route_swap::*;
 pub use saber_swap::*;
 pub use spl_token_swap::*;
@@ -0,0 +1,419 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anchor_lang::system_program::{self, Transfer};
+use anchor_spl::token::Token;
+use jet_margin::{seeds::RISK_CONFIG_SEED, RiskConfig};
+use jet_margin_pool::{cpi::accounts::MarginRepay, ChangeKind};
+use jet_program_common::Number128;
+use pyth_sdk_solana::Price;
+
+use crate::seeds::CONDITIONAL_ORDER;
+use crate::*;
+
+/// An action that a keeper may take on behalf of a margin account once the price
+/// of an oracle crosses a threshold.
+///
+/// The order is closed when it is executed, and its lamports are paid to the keeper.
+#[account]
+#[derive(Debug)]
+pub struct ConditionalOrder {
+    /// The margin account that the order acts on
+    pub margin_account: Pubkey,
+
+    /// The seed used to derive the order address
+    pub seed: u16,
+
+    /// The pyth price account that is checked against the trigger
+    pub price_oracle: Pubkey,
+
+    /// The price that allows the order to be executed
+    pub trigger: OrderTrigger,
+
+    /// What is done when the order is executed
+    pub action: OrderAction,
+
+    /// The lamports paid to the keeper in addition to the rent of the order
+    pub keeper_fee: u64,
+}
+
+impl ConditionalOrder {
+    /// The space allocated for an order, which fits the largest action
+    pub const SPACE: usize = 8 + 32 + 2 + 32 + 13 + 67 + 8;
+
+    /// Whether the order can be executed at an oracle price
+    pub fn is_triggered(&self, price: Number128) -> bool {
+        let trigger = Number128::from_decimal(self.trigger.price, self.trigger.exponent);
+
+        match self.trigger.kind {
+            TriggerKind::Below => price <= trigger,
+            TriggerKind::Above => price >= trigger,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderTrigger {
+    /// Which side of the price the oracle has to reach
+    pub kind: TriggerKind,
+
+    /// The trigger price, in the same units as the oracle
+    pub price: i64,
+
+    /// The exponent of the trigger price
+    pub exponent: i32,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerKind {
+    /// Triggered when the oracle price is at or below the trigger price (e.g. stop-loss)
+    Below,
+
+    /// Triggered when the oracle price is at or above the trigger price (e.g. take-profit)
+    Above,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderAction {
+    /// Swap all the deposits in a pool into another pool.
+    ///
+    /// The value received is checked against the margin account's position prices,
+    /// and can be at most `max_slippage_bps` less than the value swapped.
+    Swap {
+        source_pool: Pubkey,
+        destination_pool: Pubkey,
+        max_slippage_bps: u16,
+    },
+
+    /// Repay as much of a pool loan as possible from the deposits in the same pool
+    Repay { pool: Pubkey },
+}
+
+impl OrderAction {
+    fn validate(&self) -> Result<()> {
+        match self {
+            OrderAction::Swap {
+                source_pool,
+                destination_pool,
+                max_slippage_bps,
+            } if source_pool == destination_pool || *max_slippage_bps > 10_000 => {
+                err!(crate::ErrorCode::InvalidConditionalOrder)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(seed: u16)]
+pub struct CreateConditionalOrder<'info> {
+    /// The margin account that the order is for
+    #[account(signer)]
+    pub margin_account: AccountLoader<'info, MarginAccount>,
+
+    /// The address paying for rent and the keeper fee
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The order being created
+    #[account(
+        init,
+        seeds = [
+            CONDITIONAL_ORDER,
+            margin_account.key().as_ref(),
+            seed.to_le_bytes().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = ConditionalOrder::SPACE,
+    )]
+    pub order: Account<'info, ConditionalOrder>,
+
+    /// The pyth price account that the order is triggered by
+    /// CHECK: The account is checked to be a price feed
+    pub price_oracle: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelConditionalOrder<'info> {
+    /// The margin account that the order is for
+    #[account(signer)]
+    pub margin_account: AccountLoader<'info, MarginAccount>,
+
+    /// The order being cancelled
+    #[account(mut, has_one = margin_account, close = destination)]
+    pub order: Account<'info, ConditionalOrder>,
+
+    /// The destination account to send SOL to
+    /// CHECK: Account only needs to be able to receive SOL
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct KeeperExecute<'info> {
+    /// The margin account that the order is for
+    #[account(signer)]
+    pub margin_account: AccountLoader<'info, MarginAccount>,
+
+    /// The keeper executing the order, which receives its lamports
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    /// The order being executed
+    #[account(mut, has_one = margin_account, has_one = price_oracle, close = keeper)]
+    pub order: Account<'info, ConditionalOrder>,
+
+    /// The pyth price account that the order is triggered by
+    /// CHECK: The address is checked against the order
+    pub price_oracle: AccountInfo<'info>,
+
+    /// The risk parameters for the airspace of the margin account, which may be
+    /// uninitialized if the airspace uses the defaults
+    /// CHECK: address is verified, and the data is checked when loaded
+    #[account(
+        seeds = [RISK_CONFIG_SEED, margin_account.load()?.airspace.as_ref()],
+        seeds::program = jet_margin::ID,
+        bump
+    )]
+    pub risk_config: AccountInfo<'info>,
+}
+
+/// The accounts to repay a pool loan from deposits in the same pool
+#[derive(Accounts)]
+pub struct RepayFromDeposit<'info> {
+    /// The margin account with the loan
+    #[account(signer)]
+    pub margin_account: AccountLoader<'info, MarginAccount>,
+
+    /// CHECK: The pool program validates the pool accounts
+    #[account(mut)]
+    pub margin_pool: AccountInfo<'info>,
+
+    /// CHECK:
+    #[account(mut)]
+    pub loan_note_mint: AccountInfo<'info>,
+
+    /// CHECK:
+    #[account(mut)]
+    pub deposit_note_mint: AccountInfo<'info>,
+
+    /// CHECK:
+    #[account(mut)]
+    pub loan_account: AccountInfo<'info>,
+
+    /// CHECK:
+    #[account(mut)]
+    pub deposit_account: AccountInfo<'info>,
+
+    pub margin_pool_program: Program<'info, JetMarginPool>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> RepayFromDeposit<'info> {
+    fn repay(&self, rewards_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        jet_margin_pool::cpi::margin_repay(
+            CpiContext::new(
+                self.margin_pool_program.to_account_info(),
+                MarginRepay {
+                    margin_account: self.margin_account.to_account_info(),
+                    margin_pool: self.margin_pool.to_account_info(),
+                    loan_note_mint: self.loan_note_mint.to_account_info(),
+                    deposit_note_mint: self.deposit_note_mint.to_account_info(),
+                    loan_account: self.loan_account.to_account_info(),
+                    deposit_account: self.deposit_account.to_account_info(),
+                    token_program: self.token_program.to_account_info(),
+                },
+            )
+            .with_remaining_accounts(rewards_accounts.to_vec()),
+            ChangeKind::SetTo,
+            0,
+        )
+    }
+}
+
+pub fn create_conditional_order_handler(
+    ctx: Context<CreateConditionalOrder>,
+    seed: u16,
+    trigger: OrderTrigger,
+    action: OrderAction,
+    keeper_fee: u64,
+) -> Result<()> {
+    if trigger.price <= 0 {
+        return err!(crate::ErrorCode::InvalidConditionalOrder);
+    }
+    action.validate()?;
+
+    if pyth_sdk_solana::load_price_feed_from_account_info(&ctx.accounts.price_oracle).is_err() {
+        msg!("the oracle account is not valid");
+        return err!(crate::ErrorCode::InvalidOrderOracle);
+    }
+
+    if keeper_fee > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.order.to_account_info(),
+                },
+            ),
+            keeper_fee,
+        )?;
+    }
+
+    let order = &mut ctx.accounts.order;
+    order.margin_account = ctx.accounts.margin_account.key();
+    order.seed = seed;
+    order.price_oracle = ctx.accounts.price_oracle.key();
+    order.trigger = trigger;
+    order.action = action;
+    order.keeper_fee = keeper_fee;
+
+    emit!(crate::ConditionalOrderCreated {
+        margin_account: order.margin_account,
+        order: order.key(),
+        price_oracle: order.price_oracle,
+        trigger,
+        action,
+        keeper_fee,
+    });
+
+    Ok(())
+}
+
+pub fn cancel_conditional_order_handler(ctx: Context<CancelConditionalOrder>) -> Result<()> {
+    emit!(crate::ConditionalOrderCancelled {
+        margin_account: ctx.accounts.margin_account.key(),
+        order: ctx.accounts.order.key(),
+    });
+
+    Ok(())
+}
+
+/// Execute a conditional order once its trigger price has been reached.
+///
+/// The accounts of the action follow the instruction accounts:
+/// - for a swap, the accounts of `route_swap_pool` followed by the route accounts
+/// - for a repay, the accounts of [RepayFromDeposit] followed by the rewards accounts
+///   of the pool, if it has rewards
+pub fn keeper_execute_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, KeeperExecute<'info>>,
+    swap_routes: [SwapRouteDetail; 3],
+) -> Result<()> {
+    let risk = RiskConfig::load_params(
+        &ctx.accounts.risk_config,
+        &ctx.accounts.margin_account.load()?.airspace,
+    )?;
+    let oracle_price = oracle_price(&ctx.accounts.price_oracle, risk.max_oracle_staleness)?;
+    let price = Number128::from_decimal(oracle_price.price, oracle_price.expo);
+    let order = &ctx.accounts.order;
+
+    if !order.is_triggered(price) {
+        msg!("the oracle price {} has not reached the trigger", price);
+        return err!(crate::ErrorCode::OrderNotTriggered);
+    }
+
+    let mut accounts = ctx.remaining_accounts;
+    let mut bumps = BTreeMap::new();
+    let mut reallocs = BTreeSet::new();
+
+    match order.action {
+        OrderAction::Swap {
+            source_pool,
+            destination_pool,
+            max_slippage_bps,
+        } => {
+            let mut swap_accounts = RouteSwapPool::try_accounts(
+                ctx.program_id,
+                &mut accounts,
+                &[],
+                &mut bumps,
+                &mut reallocs,
+            )?;
+            if swap_accounts.margin_account.key() != order.margin_account
+                || swap_accounts.source_margin_pool.margin_pool.key() != source_pool
+                || swap_accounts.destination_margin_pool.margin_pool.key() != destination_pool
+            {
+                return err!(crate::ErrorCode::InvalidOrderAccounts);
+            }
+
+            // The oracle price check enforces the slippage, so any output is accepted here
+            route_swap_pool_handler(
+                Context::new(ctx.program_id, &mut swap_accounts, accounts, bumps),
+                ChangeKind::SetTo,
+                0,
+                1,
+                swap_routes,
+                false,
+                Some(max_slippage_bps),
+            )?;
+        }
+        OrderAction::Repay { pool } => {
+            let repay_accounts = RepayFromDeposit::try_accounts(
+                ctx.program_id,
+                &mut accounts,
+                &[],
+                &mut bumps,
+                &mut reallocs,
+            )?;
+            if repay_accounts.margin_account.key() != order.margin_account
+                || repay_accounts.margin_pool.key() != pool
+            {
+                return err!(crate::ErrorCode::InvalidOrderAccounts);
+            }
+
+            repay_accounts.repay(accounts)?;
+        }
+    }
+
+    emit!(crate::ConditionalOrderExecuted {
+        margin_account: order.margin_account,
+        order: order.key(),
+        keeper: ctx.accounts.keeper.key(),
+        oracle_price: oracle_price.price,
+        oracle_exponent: oracle_price.expo,
+    });
+
+    Ok(())
+}
+
+/// Get the current price from a pyth oracle, which must have been updated within the
+/// airspace's staleness limit
+fn oracle_price(price_oracle: &AccountInfo, max_staleness: i64) -> Result<Price> {
+    let price_feed = match pyth_sdk_solana::load_price_feed_from_account_info(price_oracle) {
+        Ok(price_feed) => price_feed,
+        Err(e) => {
+            msg!("the oracle account is not valid: {:?}", e);
+            return err!(crate::ErrorCode::InvalidOrderOracle);
+        }
+    };
+
+    let clock = Clock::get()?;
+    match price_feed.get_price_no_older_than(clock.unix_timestamp, max_staleness as u64) {
+        Some(price) => Ok(price),
+        None => {
+            msg!("the oracle price is stale");
+            err!(crate::ErrorCode::InvalidOrderOracle)
+        }
+    }
+}
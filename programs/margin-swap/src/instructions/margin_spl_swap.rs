@@ -75,7 +75,12 @@ pub struct MarginSplSwap<'info> {
 
 impl<'info> MarginSplSwap<'info> {
     #[inline(never)]
-    fn withdraw(&self, change_kind: ChangeKind, amount_in: u64) -> Result<()> {
+    fn withdraw(
+        &self,
+        change_kind: ChangeKind,
+        amount_in: u64,
+        rewards_accounts: Vec<AccountInfo<'info>>,
+    ) -> Result<()> {
         jet_margin_pool::cpi::withdraw(
             CpiContext::new(
                 self.margin_pool_program.to_account_info(),
@@ -88,7 +93,8 @@ impl<'info> MarginSplSwap<'info> {
                     destination: self.transit_source_account.to_account_info(),
                     token_program: self.token_program.to_account_info(),
                 },
-            ),
+            )
+            .with_remaining_accounts(rewards_accounts),
             change_kind,
             amount_in,
         )?;
@@ -97,7 +103,11 @@ impl<'info> MarginSplSwap<'info> {
     }
 
     #[inline(never)]
-    fn deposit_destination(&self, amount: u64) -> Result<()> {
+    fn deposit_destination(
+        &self,
+        amount: u64,
+        rewards_accounts: Vec<AccountInfo<'info>>,
+    ) -> Result<()> {
         jet_margin_pool::cpi::deposit(
             CpiContext::new(
                 self.margin_pool_program.to_account_info(),
@@ -113,7 +123,8 @@ impl<'info> MarginSplSwap<'info> {
                     destination: self.destination_account.to_account_info(),
                     token_program: self.token_program.to_account_info(),
                 },
-            ),
+            )
+            .with_remaining_accounts(rewards_accounts),
             ChangeKind::ShiftBy,
             amount,
         )?;
@@ -122,7 +133,7 @@ impl<'info> MarginSplSwap<'info> {
     }
 
     #[inline(never)]
-    fn deposit_source(&self, amount: u64) -> Result<()> {
+    fn deposit_source(&self, amount: u64, rewards_accounts: Vec<AccountInfo<'info>>) -> Result<()> {
         jet_margin_pool::cpi::deposit(
             CpiContext::new(
                 self.margin_pool_program.to_account_info(),
@@ -135,7 +146,8 @@ impl<'info> MarginSplSwap<'info> {
                     destination: self.source_account.to_account_info(),
                     token_program: self.token_program.to_account_info(),
                 },
-            ),
+            )
+            .with_remaining_accounts(rewards_accounts),
             ChangeKind::ShiftBy,
             amount,
         )?;
@@ -224,8 +236,11 @@ pub struct SwapInfo<'info> {
 /// and after an action.
 /// If either transit account has tokens before the instructions, it should still
 /// have the same tokens after the swap.
-pub fn margin_spl_swap_handler(
-    ctx: Context<MarginSplSwap>,
+///
+/// The remaining accounts are the rewards accounts of the source then destination
+/// pools, for any pools with rewards.
+pub fn margin_spl_swap_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, MarginSplSwap<'info>>,
     withdrawal_change_kind: ChangeKind,
     withdrawal_amount: u64,
     minimum_amount_out: u64,
@@ -239,12 +254,25 @@ pub fn margin_spl_swap_handler(
         ctx.accounts.destination_margin_pool.deposit_note_mint.key(),
     );
 
+    let mut remaining_accounts = ctx.remaining_accounts.iter();
+    let source_rewards = ctx
+        .accounts
+        .source_margin_pool
+        .take_rewards_accounts(&mut remaining_accounts);
+    let destination_rewards = ctx
+        .accounts
+        .destination_margin_pool
+        .take_rewards_accounts(&mut remaining_accounts);
+
     // Get the balance before the withdrawal. The balance should almost always
     // be zero, however it could already have a value.
     let source_opening_balance =
         token::accessor::amount(&ctx.accounts.transit_source_account.to_account_info())?;
-    ctx.accounts
-        .withdraw(withdrawal_change_kind, withdrawal_amount)?;
+    ctx.accounts.withdraw(
+        withdrawal_change_kind,
+        withdrawal_amount,
+        source_rewards.clone(),
+    )?;
     let source_closing_balance =
         token::accessor::amount(&ctx.accounts.transit_source_account.to_account_info())?;
 
@@ -267,7 +295,8 @@ pub fn margin_spl_swap_handler(
     let swap_amount_out = destination_closing_balance
         .checked_sub(destination_opening_balance)
         .unwrap();
-    ctx.accounts.deposit_destination(swap_amount_out)?;
+    ctx.accounts
+        .deposit_destination(swap_amount_out, destination_rewards)?;
 
     // check if there was less required for the min amount out then expected
     // aka if there was unexpected leftover balance in the source account after
@@ -283,7 +312,7 @@ pub fn margin_spl_swap_handler(
     // if there was leftover balance in the source transit account, deposit into the pool
     if leftover_balance_from_source_account > 0 {
         ctx.accounts
-            .deposit_source(leftover_balance_from_source_account)?;
+            .deposit_source(leftover_balance_from_source_account, source_rewards)?;
     }
 
     Ok(())
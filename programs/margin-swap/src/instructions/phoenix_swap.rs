@@ -0,0 +1,190 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use bytemuck::{Pod, Zeroable};
+use jet_program_common::programs::PHOENIX;
+
+use crate::*;
+
+/// The tag of phoenix's `Swap` instruction
+const PHOENIX_SWAP: u8 = 0;
+
+/// The index of the `ImmediateOrCancel` variant of phoenix's `OrderPacket`
+const IMMEDIATE_OR_CANCEL: u8 = 2;
+
+/// The header at the start of every phoenix market account, which is followed by
+/// the order book. Only the header is needed to place a swap.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+pub struct PhoenixMarketHeader {
+    pub discriminant: u64,
+    pub status: u64,
+    pub bids_size: u64,
+    pub asks_size: u64,
+    pub num_seats: u64,
+    pub base_decimals: u32,
+    pub base_vault_bump: u32,
+    pub base_mint: Pubkey,
+    pub base_vault: Pubkey,
+    /// Base atoms per base lot
+    pub base_lot_size: u64,
+    pub quote_decimals: u32,
+    pub quote_vault_bump: u32,
+    pub quote_mint: Pubkey,
+    pub quote_vault: Pubkey,
+    /// Quote atoms per quote lot
+    pub quote_lot_size: u64,
+    pub tick_size_in_quote_atoms_per_base_unit: u64,
+    pub authority: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub market_sequence_number: u64,
+    pub successor: Pubkey,
+    pub raw_base_units_per_base_unit: u32,
+    _padding1: u32,
+    _padding2: [u64; 32],
+}
+
+impl PhoenixMarketHeader {
+    /// The status of a market that accepts swaps
+    pub const STATUS_ACTIVE: u64 = 1;
+
+    /// Read the header from the data of a market account
+    pub fn load(data: &[u8]) -> Option<Self> {
+        data.get(..std::mem::size_of::<Self>())
+            .map(bytemuck::pod_read_unaligned)
+    }
+}
+
+/// The `ImmediateOrCancel` order packet used by phoenix swaps
+#[derive(AnchorSerialize)]
+struct ImmediateOrCancel {
+    side: u8,
+    price_in_ticks: Option<u64>,
+    num_base_lots: u64,
+    num_quote_lots: u64,
+    min_base_lots_to_fill: u64,
+    min_quote_lots_to_fill: u64,
+    self_trade_behavior: u8,
+    match_limit: Option<u64>,
+    client_order_id: u128,
+    use_only_deposited_funds: bool,
+    last_valid_slot: Option<u64>,
+    last_valid_unix_timestamp_in_seconds: Option<u64>,
+}
+
+#[derive(Accounts)]
+pub struct PhoenixSwapInfo<'info> {
+    /// The address of the swap program
+    /// CHECK: The address is checked
+    #[account(address = PHOENIX)]
+    pub swap_program: AccountInfo<'info>,
+
+    /// The authority that phoenix logs market events with
+    /// CHECK: Validated by phoenix
+    pub log_authority: AccountInfo<'info>,
+
+    /// CHECK: The owner is checked, and the market is validated by phoenix
+    #[account(mut, owner = PHOENIX)]
+    pub market: AccountInfo<'info>,
+
+    /// CHECK: Validated by phoenix
+    #[account(mut)]
+    pub base_vault: AccountInfo<'info>,
+
+    /// CHECK: Validated by phoenix
+    #[account(mut)]
+    pub quote_vault: AccountInfo<'info>,
+}
+
+impl<'info> PhoenixSwapInfo<'info> {
+    #[inline(never)]
+    pub fn swap(
+        &self,
+        source: &AccountInfo<'info>,
+        target: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        token_program: &AccountInfo<'info>,
+        amount_in: u64,
+    ) -> Result<()> {
+        let header = PhoenixMarketHeader::load(&self.market.try_borrow_data()?)
+            .ok_or(crate::ErrorCode::InvalidSwapRoute)?;
+
+        // Sell the base token for as much quote as possible, or spend the quote
+        // token on as much base as possible
+        let source_mint = token::accessor::mint(source)?;
+        let (side, num_base_lots, num_quote_lots, base_account, quote_account) =
+            if source_mint == header.base_mint {
+                let base_lots = amount_in.checked_div(header.base_lot_size).unwrap();
+                (1, base_lots, 0, source, target)
+            } else {
+                let quote_lots = amount_in.checked_div(header.quote_lot_size).unwrap();
+                (0, 0, quote_lots, target, source)
+            };
+
+        let order = ImmediateOrCancel {
+            side,
+            price_in_ticks: None,
+            num_base_lots,
+            num_quote_lots,
+            min_base_lots_to_fill: 0,
+            min_quote_lots_to_fill: 0,
+            // Abort, as the margin account never has resting orders to trade against
+            self_trade_behavior: 0,
+            match_limit: None,
+            client_order_id: 0,
+            use_only_deposited_funds: false,
+            last_valid_slot: None,
+            last_valid_unix_timestamp_in_seconds: None,
+        };
+        let mut data = vec![PHOENIX_SWAP, IMMEDIATE_OR_CANCEL];
+        data.extend(order.try_to_vec()?);
+
+        let ix = Instruction {
+            program_id: PHOENIX,
+            accounts: vec![
+                AccountMeta::new_readonly(PHOENIX, false),
+                AccountMeta::new_readonly(self.log_authority.key(), false),
+                AccountMeta::new(self.market.key(), false),
+                AccountMeta::new_readonly(authority.key(), true),
+                AccountMeta::new(base_account.key(), false),
+                AccountMeta::new(quote_account.key(), false),
+                AccountMeta::new(self.base_vault.key(), false),
+                AccountMeta::new(self.quote_vault.key(), false),
+                AccountMeta::new_readonly(token_program.key(), false),
+            ],
+            data,
+        };
+
+        invoke(
+            &ix,
+            &[
+                self.swap_program.to_account_info(),
+                self.log_authority.to_account_info(),
+                self.market.to_account_info(),
+                authority.to_account_info(),
+                base_account.to_account_info(),
+                quote_account.to_account_info(),
+                self.base_vault.to_account_info(),
+                self.quote_vault.to_account_info(),
+                token_program.to_account_info(),
+            ],
+        )?;
+
+        Ok(())
+    }
+}
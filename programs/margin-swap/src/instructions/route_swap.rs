@@ -21,8 +21,9 @@ use std::{
 };
 
 use anchor_spl::token::Token;
+use jet_margin::{seeds::RISK_CONFIG_SEED, RiskConfig};
 use jet_margin_pool::ChangeKind;
-use jet_program_common::CONTROL_AUTHORITY;
+use jet_program_common::{programs::PHOENIX, Number128, CONTROL_AUTHORITY};
 use orca_whirlpool::math::{MAX_SQRT_PRICE_X64, MIN_SQRT_PRICE_X64};
 
 use crate::*;
@@ -62,6 +63,7 @@ impl<'info> RouteSwapPool<'info> {
         change_kind: ChangeKind,
         amount_in: u64,
         destination: &AccountInfo<'info>,
+        rewards_accounts: Vec<AccountInfo<'info>>,
     ) -> Result<()> {
         jet_margin_pool::cpi::withdraw(
             CpiContext::new(
@@ -75,7 +77,8 @@ impl<'info> RouteSwapPool<'info> {
                     destination: destination.to_account_info(),
                     token_program: self.token_program.to_account_info(),
                 },
-            ),
+            )
+            .with_remaining_accounts(rewards_accounts),
             change_kind,
             amount_in,
         )?;
@@ -92,6 +95,7 @@ impl<'info> RouteSwapPool<'info> {
         destination: &AccountInfo<'info>,
         change_kind: ChangeKind,
         amount: u64,
+        rewards_accounts: Vec<AccountInfo<'info>>,
     ) -> Result<()> {
         let margin_pool = &pool_accounts[0];
         let vault = &pool_accounts[1];
@@ -108,7 +112,8 @@ impl<'info> RouteSwapPool<'info> {
                     destination: destination.to_account_info(),
                     token_program: self.token_program.to_account_info(),
                 },
-            ),
+            )
+            .with_remaining_accounts(rewards_accounts),
             change_kind,
             amount,
         )?;
@@ -180,7 +185,11 @@ impl<'info> RouteSwap<'info> {
 /// - associated token account
 /// - accounts of the swap instruction
 ///
-/// Where there are multiple swaps, the above are concatenated to each other
+/// Where there are multiple swaps, the above are concatenated to each other.
+///
+/// The legs are preceded by the liquidation fee destination and the airspace's risk
+/// config for liquidations, and by the rewards accounts of the source then destination
+/// pools for any pools with rewards.
 pub fn route_swap_pool_handler<'info>(
     ctx: Context<'_, '_, '_, 'info, RouteSwapPool<'info>>,
     withdrawal_change_kind: ChangeKind,
@@ -188,11 +197,13 @@ pub fn route_swap_pool_handler<'info>(
     minimum_amount_out: u64,
     swap_routes: [SwapRouteDetail; 3],
     is_liquidation: bool,
+    max_price_deviation: Option<u16>,
 ) -> Result<()> {
     // To protect users, the minimum_amount_out should always be positive.
     // We only check for slippage after all swaps, and some swaps might return 0
     // tokens, so we prevent this by ensuring that we'll compare against > 0.
     assert!(minimum_amount_out > 0);
+    let is_liquidation = verify_liquidation(&ctx.accounts.margin_account, is_liquidation)?;
     // Validate input and find out how many swaps there are
     let valid_swaps = match (
         swap_routes[0].validate()?,
@@ -207,13 +218,30 @@ pub fn route_swap_pool_handler<'info>(
 
     let mut remaining_accounts = ctx.remaining_accounts.iter();
 
-    // If this is a liquidation, the first account is:
+    // If this is a liquidation, the first accounts are:
     // - fee output PDA of the control authority
-    let fee_recipient = if is_liquidation {
-        Some(liquiation_fee_destination(&mut remaining_accounts)?)
+    // - risk config of the airspace, with the limit on the price deviation
+    let (fee_recipient, liquidation_limit) = if is_liquidation {
+        (
+            Some(liquiation_fee_destination(&mut remaining_accounts)?),
+            Some(liquidation_max_price_deviation(
+                &ctx.accounts.margin_account,
+                &mut remaining_accounts,
+            )?),
+        )
     } else {
-        None
+        (None, None)
     };
+    let max_price_deviation = crate::max_price_deviation(max_price_deviation, liquidation_limit);
+    // The rewards accounts of the pools are passed on to the pool program
+    let source_rewards = ctx
+        .accounts
+        .source_margin_pool
+        .take_rewards_accounts(&mut remaining_accounts);
+    let destination_rewards = ctx
+        .accounts
+        .destination_margin_pool
+        .take_rewards_accounts(&mut remaining_accounts);
     let (mut src_transit, dst_transit) = {
         let slice = remaining_accounts.as_slice();
         (
@@ -222,10 +250,16 @@ pub fn route_swap_pool_handler<'info>(
         )
     };
     let source_opening_balance = token::accessor::amount(&src_transit)?;
+    let source_opening_notes = token::accessor::amount(&ctx.accounts.source_account)?;
     // Withdraw from pool into ATA
-    ctx.accounts
-        .withdraw(withdrawal_change_kind, withdrawal_amount, &src_transit)?;
+    ctx.accounts.withdraw(
+        withdrawal_change_kind,
+        withdrawal_amount,
+        &src_transit,
+        source_rewards,
+    )?;
     let source_closing_balance = token::accessor::amount(&src_transit)?;
+    let source_closing_notes = token::accessor::amount(&ctx.accounts.source_account)?;
 
     let destination_opening_balance = token::accessor::amount(dst_transit)?;
 
@@ -304,6 +338,8 @@ pub fn route_swap_pool_handler<'info>(
 
     msg!("output = {}", swap_amount_out);
 
+    let destination_opening_notes = token::accessor::amount(&destination_pool_dep_note)?;
+
     // Deposit into the destination pool
     ctx.accounts.deposit(
         dest_pool_accounts,
@@ -311,8 +347,32 @@ pub fn route_swap_pool_handler<'info>(
         &destination_pool_dep_note,
         ChangeKind::ShiftBy,
         swap_amount_out,
+        destination_rewards,
     )?;
 
+    // The positions of the margin account hold deposit notes, so the notes are
+    // compared instead of the tokens that were swapped.
+    if let Some(max_price_deviation) = max_price_deviation {
+        let destination_closing_notes = token::accessor::amount(&destination_pool_dep_note)?;
+
+        verify_price_deviation(
+            &*ctx.accounts.margin_account.load()?,
+            (
+                &token::accessor::mint(&ctx.accounts.source_account)?,
+                source_opening_notes
+                    .checked_sub(source_closing_notes)
+                    .unwrap(),
+            ),
+            (
+                &token::accessor::mint(&destination_pool_dep_note)?,
+                destination_closing_notes
+                    .checked_sub(destination_opening_notes)
+                    .unwrap(),
+            ),
+            max_price_deviation,
+        )?;
+    }
+
     emit!(crate::RouteSwapped {
         margin_account: ctx.accounts.margin_account.key(),
         token_in,
@@ -331,11 +391,13 @@ pub fn route_swap_handler<'info>(
     minimum_amount_out: u64,
     swap_routes: [SwapRouteDetail; 3],
     is_liquidation: bool,
+    max_price_deviation: Option<u16>,
 ) -> Result<()> {
     // To protect users, the minimum_amount_out should always be positive.
     // We only check for slippage after all swaps, and some swaps might return 0
     // tokens, so we prevent this by ensuring that we'll compare against > 0.
     assert!(amount_in > 0 && minimum_amount_out > 0);
+    let is_liquidation = verify_liquidation(&ctx.accounts.margin_account, is_liquidation)?;
     // Validate input and find out how many swaps there are
     let valid_swaps = match (
         swap_routes[0].validate()?,
@@ -350,14 +412,21 @@ pub fn route_swap_handler<'info>(
 
     let mut remaining_accounts = ctx.remaining_accounts.iter();
 
-    // If this is a liquidation, the first 2 accounts are:
-    // - liquidation account
+    // If this is a liquidation, the first accounts are:
     // - fee output PDA of the control authority
-    let fee_recipient = if is_liquidation {
-        Some(liquiation_fee_destination(&mut remaining_accounts)?)
+    // - risk config of the airspace, with the limit on the price deviation
+    let (fee_recipient, liquidation_limit) = if is_liquidation {
+        (
+            Some(liquiation_fee_destination(&mut remaining_accounts)?),
+            Some(liquidation_max_price_deviation(
+                &ctx.accounts.margin_account,
+                &mut remaining_accounts,
+            )?),
+        )
     } else {
-        None
+        (None, None)
     };
+    let max_price_deviation = crate::max_price_deviation(max_price_deviation, liquidation_limit);
 
     let (mut src_transit, dst_transit) = {
         let slice = remaining_accounts.as_slice();
@@ -419,6 +488,15 @@ pub fn route_swap_handler<'info>(
         return Err(error!(crate::ErrorCode::SlippageExceeded));
     }
 
+    if let Some(max_price_deviation) = max_price_deviation {
+        verify_price_deviation(
+            &*ctx.accounts.margin_account.load()?,
+            (&token_in, amount_in),
+            (&token::accessor::mint(dst_transit)?, swap_amount_out),
+            max_price_deviation,
+        )?;
+    }
+
     emit!(crate::RouteSwapped {
         margin_account: ctx.accounts.margin_account.key(),
         token_in,
@@ -433,6 +511,54 @@ pub fn route_swap_handler<'info>(
     Ok(())
 }
 
+/// Check that the value of the tokens received is not less than the value of the
+/// tokens given up by more than `max_deviation` (bps), using the position prices
+/// of the margin account.
+///
+/// The liquidator chooses the minimum amount out of a liquidation swap, so this
+/// prevents swapping the account's collateral through a manipulated venue.
+fn verify_price_deviation(
+    margin_account: &MarginAccount,
+    (token_in, amount_in): (&Pubkey, u64),
+    (token_out, amount_out): (&Pubkey, u64),
+    max_deviation: u16,
+) -> Result<()> {
+    let value_in = position_value(margin_account, token_in, amount_in)?;
+    let value_out = position_value(margin_account, token_out, amount_out)?;
+
+    let min_value_out = value_in * (Number128::ONE - Number128::from_bps(max_deviation));
+
+    if value_out < min_value_out {
+        msg!(
+            "Value out = {} less than the oracle value in {} with a tolerance of {} bps",
+            value_out,
+            value_in,
+            max_deviation
+        );
+        return Err(error!(crate::ErrorCode::PriceDeviationExceeded));
+    }
+
+    Ok(())
+}
+
+/// The value of an amount of tokens at the price of the margin account's position
+fn position_value(
+    margin_account: &MarginAccount,
+    token: &Pubkey,
+    amount: u64,
+) -> Result<Number128> {
+    let position = match margin_account.get_position(token) {
+        Some(position) if position.price.is_valid() => position,
+        _ => {
+            msg!("No valid position price for {}", token);
+            return Err(error!(crate::ErrorCode::PositionPriceUnavailable));
+        }
+    };
+
+    Ok(Number128::from_decimal(amount, position.exponent)
+        * Number128::from_decimal(position.price.value, position.price.exponent))
+}
+
 /// Execute a swap and return number of tokens swapped and the destination account
 fn exec_swap<'info>(
     margin_account: &AccountInfo<'info>,
@@ -614,6 +740,29 @@ fn exec_swap_split<'info>(
             )?;
             dst_ata_closing = token::accessor::amount(dst_ata)?;
 
+            dst_ata.to_account_info()
+        }
+        SwapRouteIdentifier::Phoenix => {
+            let accounts = remaining_accounts.take(5).cloned().collect::<Vec<_>>();
+            let swap_accounts = PhoenixSwapInfo::try_accounts(
+                &PHOENIX,
+                &mut &accounts[..],
+                &[],
+                &mut scratch.bumps,
+                &mut scratch.reallocs,
+            )?;
+            let dst_ata = next_account_info(remaining_accounts).unwrap();
+            dst_ata_opening = token::accessor::amount(dst_ata)?;
+
+            swap_accounts.swap(
+                src_ata,
+                dst_ata,
+                &authority.to_account_info(),
+                token_program,
+                swap_amount_in,
+            )?;
+            dst_ata_closing = token::accessor::amount(dst_ata)?;
+
             dst_ata.to_account_info()
         }
     };
@@ -621,6 +770,26 @@ fn exec_swap_split<'info>(
     Ok((dst_ata_opening, dst_ata_closing, dst_ata))
 }
 
+/// Swaps are liquidations whenever the margin account is being liquidated, so
+/// a liquidator can't avoid the fee or the price check by claiming otherwise.
+fn verify_liquidation(
+    margin_account: &AccountLoader<MarginAccount>,
+    is_liquidation: bool,
+) -> Result<bool> {
+    let is_liquidating = margin_account.load()?.is_liquidating();
+
+    if is_liquidation != is_liquidating {
+        msg!(
+            "is_liquidation is {} but the account being liquidated is {}",
+            is_liquidation,
+            is_liquidating
+        );
+        return err!(crate::ErrorCode::InvalidSwapRouteParam);
+    }
+
+    Ok(is_liquidating)
+}
+
 /// Validate fee destination for liquidations
 fn liquiation_fee_destination<'info>(
     remaining_accounts: &mut Iter<AccountInfo<'info>>,
@@ -639,6 +808,26 @@ fn liquiation_fee_destination<'info>(
     Ok(fee_destination)
 }
 
+/// Get the airspace's limit on the price deviation of liquidation swaps, from its
+/// risk config
+fn liquidation_max_price_deviation<'info>(
+    margin_account: &AccountLoader<MarginAccount>,
+    remaining_accounts: &mut Iter<AccountInfo<'info>>,
+) -> Result<u16> {
+    let risk_config = next_account_info(remaining_accounts)?;
+    let airspace = margin_account.load()?.airspace;
+
+    let (address, _) =
+        Pubkey::find_program_address(&[RISK_CONFIG_SEED, airspace.as_ref()], &jet_margin::ID);
+    if risk_config.key() != address {
+        return err!(crate::ErrorCode::InvalidRiskConfig);
+    }
+
+    let risk = RiskConfig::load_params(risk_config, &airspace)?;
+
+    Ok(risk.liquidation_max_price_deviation)
+}
+
 /// Scratch space for try_accounts, reused to prevent creating accounts each time
 #[derive(Default)]
 struct Scratch {
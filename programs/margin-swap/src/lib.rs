@@ -46,6 +46,9 @@ pub mod seeds {
 
     #[constant]
     pub const OPENBOOK_OPEN_ORDERS: &[u8] = b"open_orders";
+
+    #[constant]
+    pub const CONDITIONAL_ORDER: &[u8] = b"conditional_order";
 }
 
 /// The fee charged for liquidation swaps (bps)
@@ -55,8 +58,8 @@ pub const LIQUIDATION_FEE: u64 = 3_00;
 mod jet_margin_swap {
     use super::*;
 
-    pub fn margin_swap(
-        ctx: Context<MarginSplSwap>,
+    pub fn margin_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, MarginSplSwap<'info>>,
         withdrawal_change_kind: ChangeKind,
         withdrawal_amount: u64,
         minimum_amount_out: u64,
@@ -70,12 +73,20 @@ mod jet_margin_swap {
     }
 
     /// Route a swap to one or more venues
+    ///
+    /// If `max_price_deviation` (bps) is set, the value of the tokens received is checked
+    /// against the value of the tokens swapped, using the prices of the margin account's
+    /// positions. Liquidation swaps are always checked, with a tolerance of at most the
+    /// airspace's [liquidation_max_price_deviation](jet_margin::RiskParameters).
+    ///
+    /// `is_liquidation` must be set if and only if the margin account is being liquidated.
     pub fn route_swap<'info>(
         ctx: Context<'_, '_, '_, 'info, RouteSwap<'info>>,
         amount_in: u64,
         minimum_amount_out: u64,
         swap_routes: [SwapRouteDetail; 3],
         is_liquidation: bool,
+        max_price_deviation: Option<u16>,
     ) -> Result<()> {
         route_swap_handler(
             ctx,
@@ -83,10 +94,14 @@ mod jet_margin_swap {
             minimum_amount_out,
             swap_routes,
             is_liquidation,
+            max_price_deviation,
         )
     }
 
     /// Route a swap to one or more venues by using margin pools
+    ///
+    /// The oracle price check is the same as in `route_swap`, and compares the value of the
+    /// pool deposit notes withdrawn and deposited.
     pub fn route_swap_pool<'info>(
         ctx: Context<'_, '_, '_, 'info, RouteSwapPool<'info>>,
         withdrawal_change_kind: ChangeKind,
@@ -94,6 +109,7 @@ mod jet_margin_swap {
         minimum_amount_out: u64,
         swap_routes: [SwapRouteDetail; 3],
         is_liquidation: bool,
+        max_price_deviation: Option<u16>,
     ) -> Result<()> {
         route_swap_pool_handler(
             ctx,
@@ -102,6 +118,7 @@ mod jet_margin_swap {
             minimum_amount_out,
             swap_routes,
             is_liquidation,
+            max_price_deviation,
         )
     }
 
@@ -122,6 +139,11 @@ mod jet_margin_swap {
         Err(error!(crate::ErrorCode::DisallowedDirectInstruction))
     }
 
+    /// Swap using a Phoenix order book
+    pub fn phoenix_swap(_ctx: Context<PhoenixSwapInfo>) -> Result<()> {
+        Err(error!(crate::ErrorCode::DisallowedDirectInstruction))
+    }
+
     pub fn init_openbook_open_orders(ctx: Context<InitOpenOrders>) -> Result<()> {
         init_open_orders_handler(ctx)
     }
@@ -129,6 +151,37 @@ mod jet_margin_swap {
     pub fn close_openbook_open_orders(ctx: Context<CloseOpenOrders>) -> Result<()> {
         close_open_orders_handler(ctx)
     }
+
+    /// Create an order that any keeper may execute on behalf of the margin account
+    /// once the price of an oracle reaches the trigger.
+    ///
+    /// The `keeper_fee` (lamports) is paid by the payer, and is sent to the keeper
+    /// along with the rent of the order when it is executed.
+    pub fn create_conditional_order(
+        ctx: Context<CreateConditionalOrder>,
+        seed: u16,
+        trigger: OrderTrigger,
+        action: OrderAction,
+        keeper_fee: u64,
+    ) -> Result<()> {
+        create_conditional_order_handler(ctx, seed, trigger, action, keeper_fee)
+    }
+
+    /// Cancel a conditional order, returning its lamports to the destination
+    pub fn cancel_conditional_order(ctx: Context<CancelConditionalOrder>) -> Result<()> {
+        cancel_conditional_order_handler(ctx)
+    }
+
+    /// Execute a conditional order whose trigger has been reached.
+    ///
+    /// This is invoked by a keeper through the margin program's `keeper_invoke`, and
+    /// `swap_routes` is only used by swap orders.
+    pub fn keeper_execute<'info>(
+        ctx: Context<'_, '_, '_, 'info, KeeperExecute<'info>>,
+        swap_routes: [SwapRouteDetail; 3],
+    ) -> Result<()> {
+        keeper_execute_handler(ctx, swap_routes)
+    }
 }
 
 #[derive(Accounts)]
@@ -146,6 +199,31 @@ pub struct MarginPoolInfo<'info> {
     pub deposit_note_mint: UncheckedAccount<'info>,
 }
 
+impl<'info> MarginPoolInfo<'info> {
+    /// Take the rewards accounts for the pool from the front of the remaining accounts,
+    /// if they're included.
+    ///
+    /// These are the pool's rewards account followed by the margin account's rewards
+    /// position, which the pool requires for any change to the margin account's notes
+    /// while it has rewards configured. They may be included for any pool.
+    fn take_rewards_accounts(
+        &self,
+        remaining_accounts: &mut std::slice::Iter<AccountInfo<'info>>,
+    ) -> Vec<AccountInfo<'info>> {
+        let (pool_rewards, _) = Pubkey::find_program_address(
+            &[self.margin_pool.key().as_ref(), b"rewards".as_ref()],
+            &jet_margin_pool::ID,
+        );
+
+        match remaining_accounts.as_slice().first() {
+            Some(info) if info.key() == pool_rewards => {
+                remaining_accounts.take(2).cloned().collect()
+            }
+            _ => vec![],
+        }
+    }
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Zero tokens have been withdrawn from a pool for the swap")]
@@ -171,6 +249,27 @@ pub enum ErrorCode {
 
     #[msg("Invalid fee destination account due to an authority mismatch")]
     InvalidFeeDestination,
+
+    #[msg("The swap output deviates too far from the oracle prices")]
+    PriceDeviationExceeded,
+
+    #[msg("A valid position price is required to check the swap against the oracle")]
+    PositionPriceUnavailable,
+
+    #[msg("The conditional order parameters are invalid")]
+    InvalidConditionalOrder,
+
+    #[msg("The conditional order oracle is invalid or stale")]
+    InvalidOrderOracle,
+
+    #[msg("The oracle price has not reached the trigger of the conditional order")]
+    OrderNotTriggered,
+
+    #[msg("The accounts do not match the action of the conditional order")]
+    InvalidOrderAccounts,
+
+    #[msg("The risk config is not the one for the margin account's airspace")]
+    InvalidRiskConfig,
 }
 
 #[event]
@@ -183,6 +282,31 @@ pub struct RouteSwapped {
     pub routes: [SwapRouteDetail; 3],
 }
 
+#[event]
+pub struct ConditionalOrderCreated {
+    pub margin_account: Pubkey,
+    pub order: Pubkey,
+    pub price_oracle: Pubkey,
+    pub trigger: OrderTrigger,
+    pub action: OrderAction,
+    pub keeper_fee: u64,
+}
+
+#[event]
+pub struct ConditionalOrderCancelled {
+    pub margin_account: Pubkey,
+    pub order: Pubkey,
+}
+
+#[event]
+pub struct ConditionalOrderExecuted {
+    pub margin_account: Pubkey,
+    pub order: Pubkey,
+    pub keeper: Pubkey,
+    pub oracle_price: i64,
+    pub oracle_exponent: i32,
+}
+
 /// An event to log when the protocol earns a swap fee.
 /// The fees are referral fees such as when placing an OpenBook swap.
 #[event]
@@ -201,6 +325,7 @@ pub enum SwapRouteIdentifier {
     Whirlpool,
     SaberStable,
     OpenBook,
+    Phoenix,
 }
 
 impl Default for SwapRouteIdentifier {
@@ -254,3 +379,13 @@ pub fn liquidation_fee(amount_out: u64) -> u64 {
 
     fee.try_into().unwrap()
 }
+
+/// Get the maximum oracle price deviation (bps) that a swap should be checked against,
+/// if any. Liquidation swaps are always checked, against at most the airspace's limit.
+pub fn max_price_deviation(requested: Option<u16>, liquidation_limit: Option<u16>) -> Option<u16> {
+    match (requested, liquidation_limit) {
+        (Some(bps), Some(limit)) => Some(bps.min(limit)),
+        (None, Some(limit)) => Some(limit),
+        (requested, None) => requested,
+    }
+}
@@ -26,11 +26,11 @@ use jet_program_common::Number128;
 use solana_program::clock::UnixTimestamp;
 
 use crate::{
+    permit,
     syscall::{sys, Sys},
     util::{log_on_error, Require},
     AccountPositionKey, AdapterPositionFlags, Approver, ErrorCode, MarginAccount,
-    PositionConfigUpdate, PriceInfo, SignerSeeds, TokenConfig, MAX_ORACLE_CONFIDENCE,
-    MAX_ORACLE_STALENESS,
+    PositionConfigUpdate, PriceInfo, RiskParameters, SignerSeeds, TokenConfig,
 };
 pub struct InvokeAdapter<'a, 'info> {
     /// The margin account to proxy an action for
@@ -45,6 +45,9 @@ pub struct InvokeAdapter<'a, 'info> {
     /// The transaction was signed by the authority of the margin account.
     /// Thus, the invocation should be signed by the margin account.
     pub signed: bool,
+
+    /// The risk parameters for the airspace of the margin account
+    pub risk: &'a RiskParameters,
 }
 
 impl InvokeAdapter<'_, '_> {
@@ -106,8 +109,12 @@ pub struct PriceChangeInfo {
 }
 
 impl PriceChangeInfo {
-    pub fn try_into(self, unix_timestamp: UnixTimestamp) -> Result<PriceInfo> {
-        let max_confidence = Number128::from_bps(MAX_ORACLE_CONFIDENCE);
+    pub fn try_into(
+        self,
+        unix_timestamp: UnixTimestamp,
+        risk: &RiskParameters,
+    ) -> Result<PriceInfo> {
+        let max_confidence = Number128::from_bps(risk.max_oracle_confidence);
 
         let twap = Number128::from_decimal(self.twap, self.exponent);
         let confidence = Number128::from_decimal(self.confidence, self.exponent);
@@ -122,7 +129,7 @@ impl PriceChangeInfo {
                 msg!("price confidence exceeding max");
                 PriceInfo::new_invalid()
             }
-            (_, publish_time) if (unix_timestamp - publish_time) > MAX_ORACLE_STALENESS => {
+            (_, publish_time) if (unix_timestamp - publish_time) > risk.max_oracle_staleness => {
                 msg!(
                     "price timestamp is too old/stale. published: {}, now: {}",
                     publish_time,
@@ -157,9 +164,9 @@ impl TryFrom<pyth_sdk::PriceFeed> for PriceChangeInfo {
 /// * `signed` - sign with the margin account
 pub fn invoke(ctx: &InvokeAdapter, data: Vec<u8>) -> Result<()> {
     let signer = ctx.margin_account.load()?.signer_seeds_owned();
+    let account_infos = permit::without_permit(&*ctx.margin_account.load()?, ctx.accounts);
 
-    let accounts = ctx
-        .accounts
+    let accounts = account_infos
         .iter()
         .map(|info| AccountMeta {
             pubkey: info.key(),
@@ -180,9 +187,9 @@ pub fn invoke(ctx: &InvokeAdapter, data: Vec<u8>) -> Result<()> {
 
     ctx.margin_account.load_mut()?.invocation.start();
     if ctx.signed {
-        program::invoke_signed(&instruction, ctx.accounts, &[&signer.signer_seeds()])?;
+        program::invoke_signed(&instruction, &account_infos, &[&signer.signer_seeds()])?;
     } else {
-        program::invoke(&instruction, ctx.accounts)?;
+        program::invoke(&instruction, &account_infos)?;
     }
     ctx.margin_account.load_mut()?.invocation.end();
 
@@ -245,7 +252,9 @@ fn apply_changes(ctx: &InvokeAdapter, mint: Pubkey, changes: Vec<PositionChange>
         match change {
             PositionChange::Price(px) => {
                 if let Some(pos) = position {
-                    pos.set_price(&px.try_into(sys().unix_timestamp() as UnixTimestamp)?)?;
+                    pos.set_price(
+                        &px.try_into(sys().unix_timestamp() as UnixTimestamp, ctx.risk)?,
+                    )?;
                 }
             }
             PositionChange::Flags(flags, true) => position.require_mut()?.flags |= flags,
@@ -262,6 +271,7 @@ fn apply_changes(ctx: &InvokeAdapter, mint: Pubkey, changes: Vec<PositionChange>
                         &mut margin_account,
                         ctx.accounts,
                         ctx.adapter_result_approvals().as_slice(),
+                        ctx.risk,
                         mint,
                         token_account,
                     )?);
@@ -290,6 +300,7 @@ fn register_position(
     margin_account: &mut MarginAccount,
     remaining_accounts: &[AccountInfo],
     approvals: &[Approver],
+    risk: &RiskParameters,
     mint_address: Pubkey,
     token_account_address: Pubkey,
 ) -> Result<AccountPositionKey> {
@@ -322,6 +333,15 @@ fn register_position(
         return err!(ErrorCode::PositionNotRegisterable);
     }
 
+    if let Some(config) = &token_config {
+        permit::verify_can_register(
+            margin_account,
+            remaining_accounts,
+            config.token_kind,
+            sys().unix_timestamp() as i64,
+        )?;
+    }
+
     let key = match token_config {
         Some(config) => margin_account.register_position(
             PositionConfigUpdate::new_from_config(
@@ -331,6 +351,7 @@ fn register_position(
                 config.adapter_program().unwrap_or_default(),
             ),
             approvals,
+            risk,
         )?,
         // TODO: remove backwards compat
         None => {
@@ -405,6 +426,7 @@ mod test {
             adapter_program: &adapter,
             accounts: &[],
             signed: true,
+            risk: &RiskParameters::default(),
         };
 
         for change in all_change_types() {
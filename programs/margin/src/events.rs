@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::{Liquidation, Permissions, TokenConfigUpdate, Valuation};
+use crate::{Liquidation, Permissions, RiskParameters, TokenConfigUpdate, Valuation};
 
 #[event]
 pub struct AccountCreated {
@@ -43,6 +43,16 @@ pub struct AdapterInvokeBegin {
 #[event]
 pub struct AdapterInvokeEnd {}
 
+#[event]
+pub struct KeeperInvokeBegin {
+    pub margin_account: Pubkey,
+    pub adapter_program: Pubkey,
+    pub keeper: Pubkey,
+}
+
+#[event]
+pub struct KeeperInvokeEnd {}
+
 #[event]
 pub struct LiquidationBegun {
     pub margin_account: Pubkey,
@@ -88,6 +98,12 @@ pub struct TokenConfigured {
     pub mint: Pubkey,
 }
 
+#[event]
+pub struct RiskConfigured {
+    pub airspace: Pubkey,
+    pub params: Option<RiskParameters>,
+}
+
 #[event]
 pub struct AdapterConfigured {
     pub airspace: Pubkey,
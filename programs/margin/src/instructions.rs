@@ -20,6 +20,7 @@ mod adapter_invoke;
 mod close_account;
 mod close_position;
 mod create_account;
+mod keeper_invoke;
 mod liquidate_begin;
 mod liquidate_end;
 mod liquidator_invoke;
@@ -38,6 +39,7 @@ pub use adapter_invoke::*;
 pub use close_account::*;
 pub use close_position::*;
 pub use create_account::*;
+pub use keeper_invoke::*;
 pub use liquidate_begin::*;
 pub use liquidate_end::*;
 pub use liquidator_invoke::*;
@@ -18,7 +18,7 @@
 use anchor_lang::prelude::*;
 
 use crate::adapter::{self, InvokeAdapter};
-use crate::{events, AdapterConfig, ErrorCode, MarginAccount};
+use crate::{events, AdapterConfig, ErrorCode, MarginAccount, RiskConfig};
 
 #[derive(Accounts)]
 pub struct AccountingInvoke<'info> {
@@ -41,6 +41,11 @@ pub fn accounting_invoke_handler<'info>(
     ctx: Context<'_, '_, '_, 'info, AccountingInvoke<'info>>,
     data: Vec<u8>,
 ) -> Result<()> {
+    let (risk, remaining_accounts) = RiskConfig::split_params(
+        &ctx.accounts.margin_account.load()?.airspace,
+        ctx.remaining_accounts,
+    );
+
     emit!(events::AccountingInvokeBegin {
         margin_account: ctx.accounts.margin_account.key(),
         adapter_program: ctx.accounts.adapter_program.key(),
@@ -50,8 +55,9 @@ pub fn accounting_invoke_handler<'info>(
         &InvokeAdapter {
             margin_account: &ctx.accounts.margin_account,
             adapter_program: &ctx.accounts.adapter_program,
-            accounts: ctx.remaining_accounts,
+            accounts: remaining_accounts,
             signed: false,
+            risk: &risk,
         },
         data,
     )?;
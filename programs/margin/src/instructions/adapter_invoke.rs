@@ -19,7 +19,7 @@ use anchor_lang::prelude::*;
 
 use crate::adapter::{self, InvokeAdapter};
 use crate::syscall::{sys, Sys};
-use crate::{events, AdapterConfig, ErrorCode, MarginAccount};
+use crate::{events, permit, AdapterConfig, ErrorCode, MarginAccount, RiskConfig};
 
 #[derive(Accounts)]
 pub struct AdapterInvoke<'info> {
@@ -50,6 +50,18 @@ pub fn adapter_invoke_handler<'info>(
         return Err(ErrorCode::Liquidating.into());
     }
 
+    let (risk, remaining_accounts) = RiskConfig::split_params(
+        &ctx.accounts.margin_account.load()?.airspace,
+        ctx.remaining_accounts,
+    );
+
+    // accounts limited by their permit need to be checked for any new borrowing,
+    // which is detected from claim balances so that stale prices don't block repayment
+    let claims_before = {
+        let account = ctx.accounts.margin_account.load()?;
+        (account.permit_limited != 0).then(|| account.claim_balances())
+    };
+
     emit!(events::AdapterInvokeBegin {
         margin_account: ctx.accounts.margin_account.key(),
         adapter_program: ctx.accounts.adapter_program.key(),
@@ -59,19 +71,29 @@ pub fn adapter_invoke_handler<'info>(
         &InvokeAdapter {
             margin_account: &ctx.accounts.margin_account,
             adapter_program: &ctx.accounts.adapter_program,
-            accounts: ctx.remaining_accounts,
+            accounts: remaining_accounts,
             signed: true,
+            risk: &risk,
         },
         data,
     )?;
 
     emit!(events::AdapterInvokeEnd {});
 
-    ctx.accounts
-        .margin_account
-        .load()?
-        .valuation(sys().unix_timestamp())?
-        .verify_healthy()?;
+    let account = ctx.accounts.margin_account.load()?;
+    let valuation = account.valuation(sys().unix_timestamp(), &risk)?;
+
+    if let Some(claims_before) = claims_before {
+        permit::verify_can_borrow(
+            &account,
+            remaining_accounts,
+            &claims_before,
+            valuation.liabilities,
+            sys().unix_timestamp() as i64,
+        )?;
+    }
+
+    valuation.verify_healthy()?;
 
     Ok(())
 }
@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::{prelude::*, AccountsClose};
+
+use jet_airspace::state::Airspace;
+
+use crate::{events::RiskConfigured, seeds::RISK_CONFIG_SEED, RiskConfig, RiskParameters};
+
+#[derive(Accounts)]
+pub struct ConfigureRisk<'info> {
+    /// The authority allowed to make changes to configuration
+    pub authority: Signer<'info>,
+
+    /// The airspace being modified
+    #[account(has_one = authority)]
+    pub airspace: Account<'info, Airspace>,
+
+    /// The payer for any rent costs, if required
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The config account to be modified
+    #[account(init_if_needed,
+              seeds = [
+                RISK_CONFIG_SEED,
+                airspace.key().as_ref(),
+              ],
+              bump,
+              payer = payer,
+              space = RiskConfig::SPACE,
+    )]
+    pub risk_config: Account<'info, RiskConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn configure_risk_handler(
+    ctx: Context<ConfigureRisk>,
+    params: Option<RiskParameters>,
+) -> Result<()> {
+    let config = &mut ctx.accounts.risk_config;
+
+    emit!(RiskConfigured {
+        airspace: ctx.accounts.airspace.key(),
+        params,
+    });
+
+    let params = match params {
+        Some(params) => params,
+        None => return config.close(ctx.accounts.payer.to_account_info()),
+    };
+
+    params.validate()?;
+
+    config.airspace = ctx.accounts.airspace.key();
+    config.params = params;
+
+    Ok(())
+}
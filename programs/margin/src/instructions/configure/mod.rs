@@ -1,9 +1,11 @@
 mod configure_account_airspace;
 mod configure_adapter;
 mod configure_permit;
+mod configure_risk;
 mod configure_token;
 
 pub use configure_account_airspace::*;
 pub use configure_adapter::*;
 pub use configure_permit::*;
+pub use configure_risk::*;
 pub use configure_token::*;
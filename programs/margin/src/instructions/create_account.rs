@@ -55,6 +55,10 @@ pub fn create_account_handler(ctx: Context<CreateAccount>, seed: u16) -> Result<
         *ctx.bumps.get("margin_account").unwrap(),
     );
 
+    if ctx.accounts.permit.is_limited() {
+        account.permit_limited = 1;
+    }
+
     emit!(events::AccountCreated {
         margin_account: ctx.accounts.margin_account.key(),
         owner: ctx.accounts.owner.key(),
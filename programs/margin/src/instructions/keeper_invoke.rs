@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::convert::TryInto;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+
+use crate::adapter::{self, InvokeAdapter};
+use crate::syscall::{sys, Sys};
+use crate::{
+    events, AdapterConfig, ErrorCode, MarginAccount, RiskConfig, KEEPER_EXECUTE_INSTRUCTION,
+};
+
+#[derive(Accounts)]
+pub struct KeeperInvoke<'info> {
+    /// The keeper executing an action on behalf of the margin account
+    pub keeper: Signer<'info>,
+
+    /// The margin account to proxy an action for
+    #[account(mut)]
+    pub margin_account: AccountLoader<'info, MarginAccount>,
+
+    /// The program to be invoked
+    /// CHECK:
+    pub adapter_program: AccountInfo<'info>,
+
+    /// The metadata about the proxy program
+    #[account(has_one = adapter_program,
+              constraint = adapter_config.airspace == margin_account.load()?.airspace @ ErrorCode::WrongAirspace
+    )]
+    pub adapter_config: Account<'info, AdapterConfig>,
+}
+
+pub fn keeper_invoke_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, KeeperInvoke<'info>>,
+    data: Vec<u8>,
+) -> Result<()> {
+    if ctx.accounts.margin_account.load()?.liquidator != Pubkey::default() {
+        msg!("account is being liquidated");
+        return Err(ErrorCode::Liquidating.into());
+    }
+
+    // The adapter is responsible for checking that the owner authorized the action
+    if data.get(..8) != Some(&keeper_execute_discriminator()[..]) {
+        msg!(
+            "keepers may only invoke the {} instruction",
+            KEEPER_EXECUTE_INSTRUCTION
+        );
+        return err!(ErrorCode::UnauthorizedKeeperInstruction);
+    }
+
+    let (risk, remaining_accounts) = RiskConfig::split_params(
+        &ctx.accounts.margin_account.load()?.airspace,
+        ctx.remaining_accounts,
+    );
+    // Compared without prices, so only the check after the action needs them to be fresh
+    let claims_before = ctx.accounts.margin_account.load()?.claim_balances();
+
+    emit!(events::KeeperInvokeBegin {
+        margin_account: ctx.accounts.margin_account.key(),
+        adapter_program: ctx.accounts.adapter_program.key(),
+        keeper: ctx.accounts.keeper.key(),
+    });
+
+    adapter::invoke(
+        &InvokeAdapter {
+            margin_account: &ctx.accounts.margin_account,
+            adapter_program: &ctx.accounts.adapter_program,
+            accounts: remaining_accounts,
+            signed: true,
+            risk: &risk,
+        },
+        data,
+    )?;
+
+    emit!(events::KeeperInvokeEnd {});
+
+    let margin_account = ctx.accounts.margin_account.load()?;
+    if margin_account.claims_increased_since(&claims_before) {
+        msg!("the keeper action increased the claims against the account");
+        return err!(ErrorCode::KeeperIncreasedLiabilities);
+    }
+
+    let valuation = margin_account.valuation(sys().unix_timestamp(), &risk)?;
+    valuation.verify_healthy()?;
+
+    Ok(())
+}
+
+/// The anchor discriminator of the instruction that keepers may invoke
+fn keeper_execute_discriminator() -> [u8; 8] {
+    let preimage = format!("global:{}", KEEPER_EXECUTE_INSTRUCTION);
+
+    hash(preimage.as_bytes()).to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
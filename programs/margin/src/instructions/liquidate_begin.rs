@@ -22,8 +22,8 @@ use jet_program_common::Number128;
 use crate::{
     events,
     syscall::{sys, Sys},
-    ErrorCode, Liquidation, LiquidationState, MarginAccount, Permissions, Permit, Valuation,
-    LIQUIDATION_MAX_EQUITY_LOSS_CONSTANT, LIQUIDATION_MAX_EQUITY_LOSS_PROPORTION_BPS,
+    ErrorCode, Liquidation, LiquidationState, MarginAccount, Permissions, Permit, RiskConfig,
+    RiskParameters, Valuation,
 };
 
 #[derive(Accounts)]
@@ -68,9 +68,10 @@ pub fn liquidate_begin_handler(ctx: Context<LiquidateBegin>) -> Result<()> {
     let liquidator = ctx.accounts.liquidator.key();
     let mut account = ctx.accounts.margin_account.load_mut()?;
     let timestamp = sys().unix_timestamp();
+    let (risk, _) = RiskConfig::split_params(&account.airspace, ctx.remaining_accounts);
 
     // verify the account is subject to liquidation
-    account.valuation(timestamp)?.verify_unhealthy()?;
+    account.valuation(timestamp, &risk)?.verify_unhealthy()?;
 
     // verify not already being liquidated
     match account.liquidator {
@@ -91,8 +92,8 @@ pub fn liquidate_begin_handler(ctx: Context<LiquidateBegin>) -> Result<()> {
         }
     }
 
-    let valuation = account.valuation(timestamp)?;
-    let max_equity_loss = max_equity_loss(&valuation);
+    let valuation = account.valuation(timestamp, &risk)?;
+    let max_equity_loss = max_equity_loss(&valuation, &risk);
 
     let liquidation_state = LiquidationState {
         liquidator,
@@ -112,10 +113,8 @@ pub fn liquidate_begin_handler(ctx: Context<LiquidateBegin>) -> Result<()> {
     Ok(())
 }
 
-pub fn max_equity_loss(valuation: &Valuation) -> Number128 {
-    const M: Number128 =
-        Number128::const_from_bps(LIQUIDATION_MAX_EQUITY_LOSS_PROPORTION_BPS as i128);
-    const B: Number128 =
-        Number128::const_from_decimal(LIQUIDATION_MAX_EQUITY_LOSS_CONSTANT as i128, 0);
-    M * valuation.liabilities + B
+pub fn max_equity_loss(valuation: &Valuation, risk: &RiskParameters) -> Number128 {
+    let m = Number128::from_bps(risk.liquidation_max_equity_loss_proportion_bps);
+    let b = Number128::from_decimal(risk.liquidation_max_equity_loss_constant, 0);
+    m * valuation.liabilities + b
 }
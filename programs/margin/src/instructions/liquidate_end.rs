@@ -18,7 +18,7 @@
 use anchor_lang::prelude::*;
 
 use crate::events;
-use crate::{ErrorCode, LiquidationState, MarginAccount, LIQUIDATION_TIMEOUT};
+use crate::{ErrorCode, LiquidationState, MarginAccount, RiskConfig};
 
 #[derive(Accounts)]
 pub struct LiquidateEnd<'info> {
@@ -45,13 +45,14 @@ pub struct LiquidateEnd<'info> {
 pub fn liquidate_end_handler(ctx: Context<LiquidateEnd>) -> Result<()> {
     let mut account = ctx.accounts.margin_account.load_mut()?;
     let start_time = ctx.accounts.liquidation.load()?.state.start_time();
+    let (risk, _) = RiskConfig::split_params(&account.airspace, ctx.remaining_accounts);
 
-    let timed_out = Clock::get()?.unix_timestamp - start_time >= LIQUIDATION_TIMEOUT;
+    let timed_out = Clock::get()?.unix_timestamp - start_time >= risk.liquidation_timeout;
 
     if (account.liquidator != ctx.accounts.authority.key()) && !timed_out {
         msg!(
             "Only the liquidator may end the liquidation before the timeout of {} seconds",
-            LIQUIDATION_TIMEOUT
+            risk.liquidation_timeout
         );
         return Err(ErrorCode::UnauthorizedLiquidator.into());
     }
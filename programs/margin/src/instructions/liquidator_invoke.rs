@@ -20,7 +20,8 @@ use anchor_lang::prelude::*;
 use crate::adapter::{self, InvokeAdapter};
 use crate::syscall::{sys, Sys};
 use crate::{
-    events, AdapterConfig, ErrorCode, Liquidation, LiquidationState, MarginAccount, Valuation,
+    events, AdapterConfig, ErrorCode, Liquidation, LiquidationState, MarginAccount, RiskConfig,
+    RiskParameters, Valuation,
 };
 
 #[derive(Accounts)]
@@ -55,7 +56,11 @@ pub fn liquidator_invoke_handler<'info>(
     data: Vec<u8>,
 ) -> Result<()> {
     let margin_account = &ctx.accounts.margin_account;
-    let start_value = margin_account.load()?.valuation(sys().unix_timestamp())?;
+    let (risk, remaining_accounts) =
+        RiskConfig::split_params(&margin_account.load()?.airspace, ctx.remaining_accounts);
+    let start_value = margin_account
+        .load()?
+        .valuation(sys().unix_timestamp(), &risk)?;
 
     emit!(events::LiquidatorInvokeBegin {
         margin_account: ctx.accounts.margin_account.key(),
@@ -67,8 +72,9 @@ pub fn liquidator_invoke_handler<'info>(
         &InvokeAdapter {
             margin_account: &ctx.accounts.margin_account,
             adapter_program: &ctx.accounts.adapter_program,
-            accounts: ctx.remaining_accounts,
+            accounts: remaining_accounts,
             signed: true,
+            risk: &risk,
         },
         data,
     )?;
@@ -78,6 +84,7 @@ pub fn liquidator_invoke_handler<'info>(
         &*ctx.accounts.margin_account.load()?,
         liquidation,
         start_value,
+        &risk,
     )?;
 
     emit!(events::LiquidatorInvokeEnd {
@@ -92,8 +99,9 @@ fn update_and_verify_liquidation(
     margin_account: &MarginAccount,
     liquidation: &mut Liquidation,
     start_value: Valuation,
+    risk: &RiskParameters,
 ) -> Result<Valuation> {
-    let end_value = margin_account.valuation(sys().unix_timestamp())?;
+    let end_value = margin_account.valuation(sys().unix_timestamp(), risk)?;
 
     *liquidation.equity_loss_mut() += start_value.equity - end_value.equity;
 
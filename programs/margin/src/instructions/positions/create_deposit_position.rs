@@ -21,7 +21,11 @@ use anchor_spl::{
     token::{Mint, Token, TokenAccount},
 };
 
-use crate::{Approver, ErrorCode, MarginAccount, PositionConfigUpdate, TokenConfig};
+use crate::{
+    permit,
+    syscall::{sys, Sys},
+    Approver, ErrorCode, MarginAccount, PositionConfigUpdate, RiskConfig, TokenConfig,
+};
 
 #[derive(Accounts)]
 pub struct CreateDepositPosition<'info> {
@@ -64,6 +68,15 @@ pub fn create_deposit_position_handler(ctx: Context<CreateDepositPosition>) -> R
     let position_token = &ctx.accounts.mint;
     let address = ctx.accounts.token_account.key();
     account.verify_authority(ctx.accounts.authority.key())?;
+    let (risk, remaining_accounts) =
+        RiskConfig::split_params(&account.airspace, ctx.remaining_accounts);
+
+    permit::verify_can_register(
+        &account,
+        remaining_accounts,
+        config.token_kind,
+        sys().unix_timestamp() as i64,
+    )?;
 
     account.register_position(
         PositionConfigUpdate::new_from_config(
@@ -73,6 +86,7 @@ pub fn create_deposit_position_handler(ctx: Context<CreateDepositPosition>) -> R
             config.adapter_program().unwrap_or_default(),
         ),
         &[Approver::MarginAccountAuthority],
+        &risk,
     )?;
 
     Ok(())
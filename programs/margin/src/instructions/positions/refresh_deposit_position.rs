@@ -21,7 +21,7 @@ use solana_program::clock::UnixTimestamp;
 
 use crate::{
     syscall::{sys, Sys},
-    ErrorCode, MarginAccount, PriceChangeInfo, TokenConfig, TokenOracle,
+    ErrorCode, MarginAccount, PriceChangeInfo, RiskConfig, TokenConfig, TokenOracle,
 };
 
 #[derive(Accounts)]
@@ -43,6 +43,8 @@ pub struct RefreshDepositPosition<'info> {
 pub fn refresh_deposit_position_handler(ctx: Context<RefreshDepositPosition>) -> Result<()> {
     let mut margin_account = ctx.accounts.margin_account.load_mut()?;
     let config = &ctx.accounts.config;
+    let (risk, remaining_accounts) =
+        RiskConfig::split_params(&margin_account.airspace, ctx.remaining_accounts);
 
     match config.oracle() {
         Some(TokenOracle::Pyth { price, .. }) => {
@@ -74,7 +76,7 @@ pub fn refresh_deposit_position_handler(ctx: Context<RefreshDepositPosition>) ->
                 publish_time: price_obj.publish_time,
             };
 
-            if let Some(position_token_account) = ctx.remaining_accounts.first() {
+            if let Some(position_token_account) = remaining_accounts.first() {
                 let balance = token::accessor::amount(position_token_account)?;
 
                 margin_account.set_position_balance(
@@ -87,7 +89,7 @@ pub fn refresh_deposit_position_handler(ctx: Context<RefreshDepositPosition>) ->
 
             margin_account.set_position_price(
                 &config.mint,
-                &price_info.try_into(sys().unix_timestamp() as UnixTimestamp)?,
+                &price_info.try_into(sys().unix_timestamp() as UnixTimestamp, &risk)?,
             )?;
         }
 
@@ -18,7 +18,11 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 
-use crate::{Approver, ErrorCode, MarginAccount, PositionConfigUpdate, TokenConfig};
+use crate::{
+    permit,
+    syscall::{sys, Sys},
+    Approver, ErrorCode, MarginAccount, PositionConfigUpdate, RiskConfig, TokenConfig,
+};
 
 #[derive(Accounts)]
 pub struct RegisterPosition<'info> {
@@ -66,6 +70,15 @@ pub fn register_position_handler(ctx: Context<RegisterPosition>) -> Result<()> {
     let position_token = &ctx.accounts.position_token_mint;
     let address = ctx.accounts.token_account.key();
     account.verify_authority(ctx.accounts.authority.key())?;
+    let (risk, remaining_accounts) =
+        RiskConfig::split_params(&account.airspace, ctx.remaining_accounts);
+
+    permit::verify_can_register(
+        &account,
+        remaining_accounts,
+        config.token_kind,
+        sys().unix_timestamp() as i64,
+    )?;
 
     account.register_position(
         PositionConfigUpdate::new_from_config(
@@ -77,6 +90,7 @@ pub fn register_position_handler(ctx: Context<RegisterPosition>) -> Result<()> {
                 .ok_or_else(|| error!(ErrorCode::InvalidConfig))?,
         ),
         &[Approver::MarginAccountAuthority],
+        &risk,
     )?;
 
     Ok(())
@@ -20,7 +20,7 @@ use anchor_lang::prelude::*;
 use crate::{
     events,
     syscall::{sys, Sys},
-    MarginAccount,
+    MarginAccount, RiskConfig,
 };
 
 #[derive(Accounts)]
@@ -31,9 +31,10 @@ pub struct VerifyHealthy<'info> {
 
 pub fn verify_healthy_handler(ctx: Context<VerifyHealthy>) -> Result<()> {
     let account = ctx.accounts.margin_account.load()?;
+    let (risk, _) = RiskConfig::split_params(&account.airspace, ctx.remaining_accounts);
 
     account
-        .valuation(sys().unix_timestamp())?
+        .valuation(sys().unix_timestamp(), &risk)?
         .verify_healthy()?;
 
     emit!(events::VerifiedHealthy {
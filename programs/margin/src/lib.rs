@@ -29,6 +29,7 @@ pub mod seeds;
 
 mod adapter;
 mod instructions;
+mod permit;
 mod state;
 pub mod syscall;
 /// Utilities used only in this crate
@@ -41,7 +42,16 @@ pub use util::Invocation;
 pub use adapter::{AdapterResult, PositionChange, PriceChangeInfo};
 pub use instructions::TokenConfigUpdate;
 
-/// The maximum confidence deviation allowed for an oracle price.
+/// The name of the only adapter instruction that can be invoked with `keeper_invoke`.
+///
+/// Adapters implementing this instruction must verify that the owner of the margin
+/// account authorized the action, since keepers can call it with any arguments.
+pub const KEEPER_EXECUTE_INSTRUCTION: &str = "keeper_execute";
+
+/// The default maximum confidence deviation allowed for an oracle price.
+///
+/// This and the other risk limits below can be set for each airspace with a
+/// [RiskConfig], and these values only apply to airspaces without one.
 ///
 /// The confidence is measured as the percent of the confidence interval
 /// value provided by the oracle as compared to the weighted average value
@@ -75,6 +85,11 @@ pub const LIQUIDATION_MAX_EQUITY_LOSS_CONSTANT: u64 = 1;
 #[constant]
 pub const LIQUIDATION_TIMEOUT: UnixTimestamp = 60;
 
+/// The maximum loss in value against the oracle prices allowed for liquidation swaps
+/// (bps). This includes the liquidation fee.
+#[constant]
+pub const LIQUIDATION_MAX_PRICE_DEVIATION: u16 = 5_00;
+
 /// The maximum number of positions that a user can register.
 /// This may be exceeded by a liquidator.
 pub const MAX_USER_POSITIONS: u64 = 24;
@@ -364,6 +379,46 @@ pub mod jet_margin {
         accounting_invoke_handler(ctx, data)
     }
 
+    /// Perform an action by invoking an adapter on behalf of the owner, without their signature.
+    ///
+    /// This allows anyone to act as a keeper that executes actions which the owner
+    /// authorized ahead of time, such as conditional orders. Only the adapter instruction
+    /// named [KEEPER_EXECUTE_INSTRUCTION] may be invoked, and the adapter must verify the
+    /// authorization. The action may not increase the balance of any claim against the
+    /// account, and the account must be healthy afterwards.
+    ///
+    /// All extra accounts passed in are used as the input accounts when invoking
+    /// the provided adapter porgram.
+    ///
+    /// # Parameters
+    ///
+    /// * `data` - The instruction data to pass to the adapter program
+    ///
+    /// # [Accounts](jet_margin::accounts::KeeperInvoke)
+    ///
+    /// |     |     |     |
+    /// | --- | --- | --- |
+    /// | **Name** | **Type** | **Description** |
+    /// | `keeper` | `signer` | The keeper executing the action. |
+    /// | `margin_account` | `writable` | The margin account to proxy an action for. |
+    /// | `adapter_program` | `read_only` | The program to be invoked. |
+    /// | `adapter_config` | `read_only` | The configuration of the adapter in the account's airspace. |
+    ///
+    /// # Events
+    ///
+    /// |     |     |
+    /// | --- | --- |
+    /// | **Event Name** | **Description** |
+    /// | [`events::KeeperInvokeBegin`] | Marks the start of the keeper invocation. |
+    /// | [`events::PositionEvent`] _(Note that each single event represents a different adapter position)_ | The [PositionEvent](events::PositionEvent) marks the change in position. |
+    /// | [`events::KeeperInvokeEnd`] | Marks the ending of the keeper invocation. |
+    pub fn keeper_invoke<'info>(
+        ctx: Context<'_, '_, '_, 'info, KeeperInvoke<'info>>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        keeper_invoke_handler(ctx, data)
+    }
+
     /// Begin liquidating an account
     ///
     /// The account will enter a state preventing the owner from taking any action,
@@ -397,7 +452,7 @@ pub mod jet_margin {
     /// End the liquidation state for an account
     ///
     /// Normally must be signed by the liquidator that started the liquidation state. Can be
-    /// signed by anyone after the [timeout period](jet_margin::LIQUIDATION_TIMEOUT) has elapsed.
+    /// signed by anyone after the [timeout period](RiskParameters::liquidation_timeout) has elapsed.
     ///
     /// # [Accounts](jet_margin::accounts::LiquidateEnd)
     ///
@@ -508,6 +563,25 @@ pub mod jet_margin {
         configure_permit(ctx, is_liquidator, Permissions::LIQUIDATE)
     }
 
+    /// Set the risk parameters for all margin accounts in an airspace.
+    ///
+    /// Airspaces without any configured parameters use the defaults, which are the
+    /// program-wide constants. Changing the parameters requires the airspace authority
+    /// to sign.
+    ///
+    /// The account storing the configuration will be funded if not already. If a `None` is provided as
+    /// the updated configuration, then the account will be defunded and the defaults apply again.
+    ///
+    /// Instructions that value or liquidate a margin account take the config as an optional
+    /// last remaining account, which is not passed on to adapters. The defaults are used if
+    /// it's not provided.
+    pub fn configure_risk(
+        ctx: Context<ConfigureRisk>,
+        params: Option<RiskParameters>,
+    ) -> Result<()> {
+        configure_risk_handler(ctx, params)
+    }
+
     /// Configure an account to join the default airspace
     ///
     /// This can be used to migrate margin accounts existing before the introduction of airspaces
@@ -577,6 +651,14 @@ pub enum ErrorCode {
     #[msg("the current instruction was not directly invoked by the margin program")]
     IndirectInvocation,
 
+    /// 141004
+    #[msg("keepers may only invoke the keeper_execute instruction of an adapter")]
+    UnauthorizedKeeperInstruction,
+
+    /// 141005
+    #[msg("keepers are not allowed to increase the claims against an account")]
+    KeeperIncreasedLiabilities,
+
     /// 141010 - Account cannot record any additional positions
     #[msg("account cannot record any additional positions")]
     MaxPositions = 135_010,
@@ -683,6 +765,18 @@ pub enum ErrorCode {
     /// 141061
     #[msg("the permit is not owned by the current user")]
     PermitNotOwned = 135_061,
+
+    /// 141062
+    #[msg("the airspace permit for the account owner must be provided")]
+    PermitRequired = 135_062,
+
+    /// 141063
+    #[msg("the airspace permit has expired")]
+    PermitExpired = 135_063,
+
+    /// 141064
+    #[msg("the airspace permit tier does not allow this")]
+    PermitTierExceeded = 135_064,
 }
 
 /// Writes the result of position changes from an adapter invocation.
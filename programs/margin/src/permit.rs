@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Enforcement of the expiry and tier of the airspace permit that a margin account
+//! was created under.
+//!
+//! Accounts created under a permit without any limits never need to provide it again.
+//! Otherwise the permit must be passed in the remaining accounts of any instruction
+//! that registers a position or may increase the account's liabilities.
+//! When invoking an adapter, the permit is checked by the margin program but not passed
+//! on to the adapter.
+
+use anchor_lang::prelude::*;
+use jet_airspace::state::AirspacePermit;
+use jet_program_common::Number128;
+
+use crate::{ErrorCode, MarginAccount, TokenKind};
+
+/// Load the account as the permit for the margin account, if it is one
+fn load_permit(margin_account: &MarginAccount, info: &AccountInfo) -> Option<AirspacePermit> {
+    if info.owner != &jet_airspace::ID {
+        return None;
+    }
+
+    // permits are unique for each owner in an airspace, so a matching permit
+    // must be the one the account was created under
+    Account::<AirspacePermit>::try_from(info)
+        .ok()
+        .filter(|permit| {
+            permit.airspace == margin_account.airspace && permit.owner == margin_account.owner
+        })
+        .map(Account::into_inner)
+}
+
+/// Find the permit for the margin account within a list of accounts
+fn find_permit(margin_account: &MarginAccount, accounts: &[AccountInfo]) -> Result<AirspacePermit> {
+    for info in accounts {
+        if let Some(permit) = load_permit(margin_account, info) {
+            return Ok(permit);
+        }
+    }
+
+    msg!("the airspace permit for the account owner must be provided");
+    err!(ErrorCode::PermitRequired)
+}
+
+/// The accounts to pass on to an adapter, which excludes the permit since it's only
+/// provided for the margin program to check
+pub fn without_permit<'info>(
+    margin_account: &MarginAccount,
+    accounts: &[AccountInfo<'info>],
+) -> Vec<AccountInfo<'info>> {
+    accounts
+        .iter()
+        .filter(|info| {
+            margin_account.permit_limited == 0 || load_permit(margin_account, info).is_none()
+        })
+        .cloned()
+        .collect()
+}
+
+fn verify_not_expired(permit: &AirspacePermit, timestamp: i64) -> Result<()> {
+    if permit.is_expired(timestamp) {
+        msg!("the permit expired at {}", permit.expires_at);
+        return err!(ErrorCode::PermitExpired);
+    }
+
+    Ok(())
+}
+
+/// Check that the account's permit allows registering a position of the given kind
+pub fn verify_can_register(
+    margin_account: &MarginAccount,
+    accounts: &[AccountInfo],
+    kind: TokenKind,
+    timestamp: i64,
+) -> Result<()> {
+    if margin_account.permit_limited == 0 {
+        return Ok(());
+    }
+
+    let permit = find_permit(margin_account, accounts)?;
+    verify_not_expired(&permit, timestamp)?;
+
+    if kind == TokenKind::Claim && permit.tier.lend_only {
+        msg!("the permit only allows lending");
+        return err!(ErrorCode::PermitTierExceeded);
+    }
+
+    Ok(())
+}
+
+/// Check that the account's permit allows any new borrowing since the claim
+/// balances were taken
+pub fn verify_can_borrow(
+    margin_account: &MarginAccount,
+    accounts: &[AccountInfo],
+    claims_before: &[(Pubkey, u64)],
+    liabilities_after: Number128,
+    timestamp: i64,
+) -> Result<()> {
+    if margin_account.permit_limited == 0 || !margin_account.claims_increased_since(claims_before) {
+        return Ok(());
+    }
+
+    let permit = find_permit(margin_account, accounts)?;
+    verify_not_expired(&permit, timestamp)?;
+
+    let tier = &permit.tier;
+    let exceeds_limit = tier.max_borrow_value != 0
+        && liabilities_after > Number128::from_decimal(tier.max_borrow_value, 0);
+
+    if tier.lend_only || exceeds_limit {
+        msg!(
+            "liabilities of {} exceed the permit tier {:?}",
+            liabilities_after,
+            tier
+        );
+        return err!(ErrorCode::PermitTierExceeded);
+    }
+
+    Ok(())
+}
@@ -31,3 +31,6 @@ pub const LIQUIDATOR_CONFIG_SEED: &[u8] = PERMIT_SEED;
 
 #[constant]
 pub const PERMIT_SEED: &[u8] = b"permit";
+
+#[constant]
+pub const RISK_CONFIG_SEED: &[u8] = b"risk-config";
@@ -30,7 +30,7 @@ use std::result::Result;
 use crate::{
     syscall::{sys, Sys},
     util::{Invocation, Require},
-    ErrorCode, TokenKind, MAX_PRICE_QUOTE_AGE, MAX_USER_POSITIONS,
+    ErrorCode, RiskParameters, TokenKind,
 };
 
 mod positions;
@@ -53,7 +53,11 @@ pub struct MarginAccount {
     /// Must normally be zeroed, except during an invocation.
     pub invocation: Invocation,
 
-    pub reserved0: [u8; 3],
+    /// Non-zero if the account was created under a permit with an expiry or tier, in which
+    /// case the permit must be provided whenever the account registers positions or borrows.
+    pub permit_limited: u8,
+
+    pub reserved0: [u8; 2],
 
     /// The owner of this account, which generally has to sign for any changes to it
     pub owner: Pubkey,
@@ -90,6 +94,7 @@ impl std::fmt::Debug for MarginAccount {
         acc.field("version", &self.version)
             .field("bump_seed", &self.bump_seed)
             .field("user_seed", &self.user_seed)
+            .field("permit_limited", &self.permit_limited)
             .field("reserved0", &self.reserved0)
             .field("invocation", &self.invocation)
             .field("owner", &self.owner)
@@ -158,6 +163,7 @@ impl MarginAccount {
 
     pub fn initialize(&mut self, airspace: Pubkey, owner: Pubkey, seed: u16, bump_seed: u8) {
         self.version = MARGIN_ACCOUNT_VERSION;
+        self.permit_limited = 0;
         self.airspace = airspace;
         self.owner = owner;
         self.bump_seed = [bump_seed];
@@ -173,14 +179,41 @@ impl MarginAccount {
             .filter(|p| p.address != Pubkey::default())
     }
 
+    /// Get the balance of each claim against this account
+    pub fn claim_balances(&self) -> Vec<(Pubkey, u64)> {
+        self.positions()
+            .filter(|p| p.kind() == TokenKind::Claim)
+            .map(|p| (p.token, p.balance))
+            .collect()
+    }
+
+    /// Check if any claim against this account has grown since the balances were taken.
+    ///
+    /// Unlike a valuation, this doesn't depend on prices, so it can be checked
+    /// for accounts with stale positions.
+    pub fn claims_increased_since(&self, claims_before: &[(Pubkey, u64)]) -> bool {
+        self.positions()
+            .filter(|p| p.kind() == TokenKind::Claim)
+            .any(|p| {
+                let before = claims_before
+                    .iter()
+                    .find(|(token, _)| *token == p.token)
+                    .map(|(_, balance)| *balance)
+                    .unwrap_or_default();
+
+                p.balance > before
+            })
+    }
+
     /// Register the space for a new position into this account
     #[allow(clippy::too_many_arguments)]
     pub fn register_position(
         &mut self,
         config: PositionConfigUpdate,
         approvals: &[Approver],
+        risk: &RiskParameters,
     ) -> AnchorResult<AccountPositionKey> {
-        if !self.is_liquidating() && self.position_list().length >= MAX_USER_POSITIONS {
+        if !self.is_liquidating() && self.position_list().length >= risk.max_user_positions {
             return err!(ErrorCode::MaxPositions);
         }
         if self.airspace != config.airspace {
@@ -353,7 +386,7 @@ impl MarginAccount {
         Ok(())
     }
 
-    pub fn valuation(&self, timestamp: u64) -> AnchorResult<Valuation> {
+    pub fn valuation(&self, timestamp: u64, risk: &RiskParameters) -> AnchorResult<Valuation> {
         let mut past_due = false;
         let mut liabilities = Number128::ZERO;
         let mut required_collateral = Number128::ZERO;
@@ -376,7 +409,7 @@ impl MarginAccount {
                 } else if position.max_staleness > 0 && balance_age > position.max_staleness {
                     // outdated balance
                     Some(ErrorCode::OutdatedBalance)
-                } else if price_quote_age > MAX_PRICE_QUOTE_AGE {
+                } else if price_quote_age > risk.max_price_quote_age {
                     // outdated price
                     Some(ErrorCode::OutdatedPrice)
                 } else {
@@ -627,7 +660,8 @@ mod tests {
             version: 1,
             bump_seed: [0],
             user_seed: [0; 2],
-            reserved0: [0; 3],
+            permit_limited: 0,
+            reserved0: [0; 2],
             owner: Pubkey::default(),
             airspace: Pubkey::default(),
             liquidator: Pubkey::default(),
@@ -638,7 +672,8 @@ mod tests {
             version: 1,
             bump_seed: [0],
             user_seed: [0, 0],
-            reserved0: [0, 0, 0],
+            permit_limited: 0,
+            reserved0: [0, 0],
             invocation: Invocation {
                 caller_heights: BitSet(0b10010111)
             },
@@ -666,6 +701,7 @@ mod tests {
                 max_staleness: 1000,
             },
             approvals,
+            &RiskParameters::default(),
         )
         .unwrap();
         let position = "AccountPosition {
@@ -699,7 +735,8 @@ mod tests {
             version: 1,
             bump_seed: [0],
             user_seed: [0; 2],
-            reserved0: [0; 3],
+            permit_limited: 0,
+            reserved0: [0; 2],
             owner: Pubkey::default(),
             airspace: Pubkey::default(),
             liquidator: Pubkey::default(),
@@ -786,7 +823,8 @@ mod tests {
             version: 1,
             bump_seed: [0],
             user_seed: [0; 2],
-            reserved0: [0; 3],
+            permit_limited: 0,
+            reserved0: [0; 2],
             owner: Pubkey::new_unique(),
             airspace: Pubkey::default(),
             liquidator: Pubkey::default(),
@@ -798,7 +836,9 @@ mod tests {
             .set_position_balance(&pos, &pos, 1, ARBITRARY_TIME)
             .unwrap();
 
-        assert!(margin_account.valuation(ARBITRARY_TIME).is_err());
+        assert!(margin_account
+            .valuation(ARBITRARY_TIME, &RiskParameters::default())
+            .is_err());
     }
 
     #[test]
@@ -807,7 +847,8 @@ mod tests {
             version: 1,
             bump_seed: [0],
             user_seed: [0; 2],
-            reserved0: [0; 3],
+            permit_limited: 0,
+            reserved0: [0; 2],
             owner: Pubkey::new_unique(),
             airspace: Pubkey::default(),
             liquidator: Pubkey::default(),
@@ -820,7 +861,9 @@ mod tests {
         margin_account
             .set_position_balance(&pos, &pos, 1, ARBITRARY_TIME)
             .unwrap();
-        let valuation = margin_account.valuation(ARBITRARY_TIME).unwrap();
+        let valuation = margin_account
+            .valuation(ARBITRARY_TIME, &RiskParameters::default())
+            .unwrap();
         assert_eq!(valuation.effective_collateral, Number128::ZERO);
         assert_eq!(valuation.equity, Number128::ZERO);
 
@@ -836,7 +879,9 @@ mod tests {
                 },
             )
             .unwrap();
-        let valuation = margin_account.valuation(ARBITRARY_TIME).unwrap();
+        let valuation = margin_account
+            .valuation(ARBITRARY_TIME, &RiskParameters::default())
+            .unwrap();
         assert_eq!(valuation.effective_collateral, Number128::ONE * 100);
         assert_eq!(valuation.equity, Number128::ONE);
     }
@@ -849,7 +894,8 @@ mod tests {
             version: 1,
             bump_seed: [0],
             user_seed: [0; 2],
-            reserved0: [0; 3],
+            permit_limited: 0,
+            reserved0: [0; 2],
             owner: Pubkey::new_unique(),
             airspace: Pubkey::default(),
             liquidator: Pubkey::default(),
@@ -879,6 +925,7 @@ mod tests {
                     max_staleness: 0,
                 },
                 user_approval,
+                &RiskParameters::default(),
             )
             .unwrap();
 
@@ -895,6 +942,7 @@ mod tests {
                     max_staleness: 0,
                 },
                 adapter_approval,
+                &RiskParameters::default(),
             )
             .unwrap();
 
@@ -911,6 +959,7 @@ mod tests {
                     max_staleness: 0,
                 },
                 user_approval,
+                &RiskParameters::default(),
             )
             .unwrap();
 
@@ -945,6 +994,7 @@ mod tests {
                     max_staleness: 100,
                 },
                 user_approval,
+                &RiskParameters::default(),
             )
             .unwrap();
         assert_eq!(margin_account.positions().count(), 2);
@@ -962,6 +1012,7 @@ mod tests {
                     max_staleness: 100,
                 },
                 user_approval,
+                &RiskParameters::default(),
             )
             .unwrap();
         assert_eq!(margin_account.positions().count(), 3);
@@ -986,6 +1037,68 @@ mod tests {
         assert_eq!(margin_account.positions, [0; 7432].into());
     }
 
+    #[test]
+    fn claims_increased_ignores_collateral_and_repayment() {
+        let margin_address = Pubkey::new_unique();
+        let adapter = Pubkey::new_unique();
+        let mut margin_account = MarginAccount {
+            version: 1,
+            bump_seed: [0],
+            user_seed: [0; 2],
+            permit_limited: 0,
+            reserved0: [0; 2],
+            owner: Pubkey::new_unique(),
+            airspace: Pubkey::default(),
+            liquidator: Pubkey::default(),
+            invocation: Invocation::default(),
+            positions: [0; 7432].into(),
+        };
+        let (collateral, collateral_address) = create_position_input(&margin_address);
+        let (claim, claim_address) = create_position_input(&margin_address);
+
+        for (mint, address, kind) in [
+            (collateral, collateral_address, TokenKind::Collateral),
+            (claim, claim_address, TokenKind::Claim),
+        ] {
+            margin_account
+                .register_position(
+                    PositionConfigUpdate {
+                        mint,
+                        decimals: 6,
+                        airspace: Default::default(),
+                        address,
+                        adapter,
+                        kind,
+                        value_modifier: 0,
+                        max_staleness: 0,
+                    },
+                    &[Approver::MarginAccountAuthority, Approver::Adapter(adapter)],
+                    &RiskParameters::default(),
+                )
+                .unwrap();
+        }
+        margin_account
+            .set_position_balance(&claim, &claim_address, 100, ARBITRARY_TIME)
+            .unwrap();
+
+        let before = margin_account.claim_balances();
+        assert_eq!(vec![(claim, 100)], before);
+
+        margin_account
+            .set_position_balance(&collateral, &collateral_address, 500, ARBITRARY_TIME)
+            .unwrap();
+        margin_account
+            .set_position_balance(&claim, &claim_address, 50, ARBITRARY_TIME)
+            .unwrap();
+        assert!(!margin_account.claims_increased_since(&before));
+
+        margin_account
+            .set_position_balance(&claim, &claim_address, 101, ARBITRARY_TIME)
+            .unwrap();
+        assert!(margin_account.claims_increased_since(&before));
+        assert!(margin_account.claims_increased_since(&[]));
+    }
+
     #[test]
     fn registering_adapter_collateral_requires_adapter_and_owner_approval() {
         let margin_address = Pubkey::new_unique();
@@ -994,7 +1107,8 @@ mod tests {
             version: 1,
             bump_seed: [0],
             user_seed: [0; 2],
-            reserved0: [0; 3],
+            permit_limited: 0,
+            reserved0: [0; 2],
             owner: Pubkey::new_unique(),
             airspace: Pubkey::default(),
             liquidator: Pubkey::default(),
@@ -1019,6 +1133,7 @@ mod tests {
                     max_staleness: 0,
                 },
                 &[],
+                &RiskParameters::default(),
             )
             .unwrap_err();
         margin_account
@@ -1034,6 +1149,7 @@ mod tests {
                     max_staleness: 0,
                 },
                 &[Approver::MarginAccountAuthority],
+                &RiskParameters::default(),
             )
             .unwrap_err();
         margin_account
@@ -1049,6 +1165,7 @@ mod tests {
                     max_staleness: 0,
                 },
                 &[Approver::Adapter(adapter)],
+                &RiskParameters::default(),
             )
             .unwrap_err();
         margin_account
@@ -1064,6 +1181,7 @@ mod tests {
                     max_staleness: 0,
                 },
                 &[Approver::MarginAccountAuthority, Approver::Adapter(adapter)],
+                &RiskParameters::default(),
             )
             .unwrap();
     }
@@ -1076,7 +1194,8 @@ mod tests {
             version: 1,
             bump_seed: [0],
             user_seed: [0; 2],
-            reserved0: [0; 3],
+            permit_limited: 0,
+            reserved0: [0; 2],
             owner: Pubkey::new_unique(),
             airspace: Pubkey::default(),
             liquidator: Pubkey::default(),
@@ -1098,6 +1217,7 @@ mod tests {
                     max_staleness: 0,
                 },
                 &[Approver::MarginAccountAuthority, Approver::Adapter(adapter)],
+                &RiskParameters::default(),
             )
             .unwrap();
     }
@@ -1108,7 +1228,8 @@ mod tests {
             version: 1,
             bump_seed: [0],
             user_seed: [0; 2],
-            reserved0: [0; 3],
+            permit_limited: 0,
+            reserved0: [0; 2],
             owner: Pubkey::default(),
             airspace: Pubkey::default(),
             liquidator: Pubkey::default(),
@@ -1128,7 +1249,7 @@ mod tests {
         assert_healthy(&acc);
         // but when past due, the account is unhealthy
         acc.get_position_mut(&claim).require().unwrap().flags |= AdapterPositionFlags::PAST_DUE;
-        acc.valuation(ARBITRARY_TIME)
+        acc.valuation(ARBITRARY_TIME, &RiskParameters::default())
             .unwrap()
             .verify_unhealthy()
             .unwrap();
@@ -1165,28 +1286,29 @@ mod tests {
                 max_staleness: 0,
             },
             &approvals,
+            &RiskParameters::default(),
         )?;
 
         Ok(key)
     }
 
     fn assert_unhealthy(acc: &MarginAccount) {
-        acc.valuation(ARBITRARY_TIME)
+        acc.valuation(ARBITRARY_TIME, &RiskParameters::default())
             .unwrap()
             .verify_healthy()
             .unwrap_err();
-        acc.valuation(ARBITRARY_TIME)
+        acc.valuation(ARBITRARY_TIME, &RiskParameters::default())
             .unwrap()
             .verify_unhealthy()
             .unwrap();
     }
 
     fn assert_healthy(acc: &MarginAccount) {
-        acc.valuation(ARBITRARY_TIME)
+        acc.valuation(ARBITRARY_TIME, &RiskParameters::default())
             .unwrap()
             .verify_healthy()
             .unwrap();
-        acc.valuation(ARBITRARY_TIME)
+        acc.valuation(ARBITRARY_TIME, &RiskParameters::default())
             .unwrap()
             .verify_unhealthy()
             .unwrap_err();
@@ -1316,7 +1438,8 @@ mod tests {
             version: 1,
             bump_seed: [0],
             user_seed: [0; 2],
-            reserved0: [0; 3],
+            permit_limited: 0,
+            reserved0: [0; 2],
             owner: Pubkey::default(),
             airspace: Pubkey::default(),
             liquidator: Pubkey::default(),
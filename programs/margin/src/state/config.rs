@@ -19,7 +19,12 @@ use anchor_lang::prelude::*;
 use bitflags::bitflags;
 use bytemuck::Contiguous;
 
-use crate::{ErrorCode, TokenConfigUpdate};
+use crate::{
+    seeds::RISK_CONFIG_SEED, ErrorCode, TokenConfigUpdate, LIQUIDATION_MAX_EQUITY_LOSS_CONSTANT,
+    LIQUIDATION_MAX_EQUITY_LOSS_PROPORTION_BPS, LIQUIDATION_MAX_PRICE_DEVIATION,
+    LIQUIDATION_TIMEOUT, MAX_ORACLE_CONFIDENCE, MAX_ORACLE_STALENESS, MAX_PRICE_QUOTE_AGE,
+    MAX_USER_POSITIONS,
+};
 
 /// Description of the token's usage
 #[derive(AnchorSerialize, AnchorDeserialize, Contiguous, Eq, PartialEq, Clone, Copy, Debug)]
@@ -149,6 +154,164 @@ pub enum TokenAdmin {
     Adapter(Pubkey),
 }
 
+/// Risk parameters that apply to every margin account within an airspace
+#[account]
+#[derive(Debug, Eq, PartialEq)]
+pub struct RiskConfig {
+    /// The airspace these parameters apply to
+    pub airspace: Pubkey,
+
+    /// The parameters in effect for the airspace
+    pub params: RiskParameters,
+}
+
+impl RiskConfig {
+    pub const SPACE: usize = 8 + std::mem::size_of::<Self>();
+
+    /// Load the parameters for an airspace from its config account, using the default
+    /// parameters if the airspace has not been configured.
+    ///
+    /// The address of the account must already be verified.
+    pub fn load_params(info: &AccountInfo, airspace: &Pubkey) -> Result<RiskParameters> {
+        if info.data_is_empty() {
+            return Ok(RiskParameters::default());
+        }
+
+        let config = Account::<RiskConfig>::try_from(info)?;
+
+        if config.airspace != *airspace {
+            return err!(ErrorCode::WrongAirspace);
+        }
+
+        Ok(config.params)
+    }
+
+    /// Split the risk config for an airspace off the end of an instruction's remaining
+    /// accounts, returning its parameters along with the other accounts.
+    ///
+    /// The config is an optional trailing account, so that instructions keep the same
+    /// accounts as before risk parameters could be configured. The default parameters
+    /// are used when it's not provided, or when its address is given for an airspace
+    /// that hasn't been configured.
+    pub fn split_params<'a, 'info>(
+        airspace: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> (RiskParameters, &'a [AccountInfo<'info>]) {
+        if let Some((last, others)) = accounts.split_last() {
+            if let Some(params) = Self::load(airspace, last) {
+                return (params, others);
+            }
+
+            if last.data_is_empty() && last.key() == Self::address(airspace) {
+                return (RiskParameters::default(), others);
+            }
+        }
+
+        (RiskParameters::default(), accounts)
+    }
+
+    /// The address of the risk config for an airspace
+    fn address(airspace: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[RISK_CONFIG_SEED, airspace.as_ref()], &crate::ID).0
+    }
+
+    /// Load the account as the risk config for the airspace, if it is one
+    fn load(airspace: &Pubkey, info: &AccountInfo) -> Option<RiskParameters> {
+        if info.owner != &crate::ID {
+            return None;
+        }
+
+        // configs are only created at the address derived from their airspace, so
+        // a config with a matching airspace must be the one for the airspace
+        Account::<RiskConfig>::try_from(info)
+            .ok()
+            .filter(|config| config.airspace == *airspace)
+            .map(|config| config.params)
+    }
+}
+
+/// Limits used when valuing and liquidating margin accounts.
+///
+/// The defaults are the program-wide constants that applied before these could be
+/// configured for each airspace.
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Eq, PartialEq, Clone, Copy)]
+pub struct RiskParameters {
+    /// The maximum confidence deviation allowed for an oracle price, as the ratio of
+    /// the confidence interval to the average price (in bps)
+    pub max_oracle_confidence: u16,
+
+    /// The maximum number of seconds since the last price was published by an oracle,
+    /// before rejecting the price as too stale
+    pub max_oracle_staleness: i64,
+
+    /// The maximum age (seconds) to allow for a quoted price for a position
+    pub max_price_quote_age: u64,
+
+    /// The maximum amount of equity that can be lost during liquidation, as a proportion
+    /// of the account's liabilities (in bps)
+    pub liquidation_max_equity_loss_proportion_bps: u16,
+
+    /// The dollar value that is always allowed to be lost during liquidation
+    pub liquidation_max_equity_loss_constant: u64,
+
+    /// The maximum duration (seconds) of a liquidation before another user may cancel it
+    pub liquidation_timeout: i64,
+
+    /// The maximum loss in value against the oracle prices allowed for liquidation swaps,
+    /// including the liquidation fee (in bps)
+    pub liquidation_max_price_deviation: u16,
+
+    /// The maximum number of positions that a user can register
+    pub max_user_positions: u64,
+}
+
+impl Default for RiskParameters {
+    fn default() -> Self {
+        Self {
+            max_oracle_confidence: MAX_ORACLE_CONFIDENCE,
+            max_oracle_staleness: MAX_ORACLE_STALENESS,
+            max_price_quote_age: MAX_PRICE_QUOTE_AGE,
+            liquidation_max_equity_loss_proportion_bps: LIQUIDATION_MAX_EQUITY_LOSS_PROPORTION_BPS,
+            liquidation_max_equity_loss_constant: LIQUIDATION_MAX_EQUITY_LOSS_CONSTANT,
+            liquidation_timeout: LIQUIDATION_TIMEOUT,
+            liquidation_max_price_deviation: LIQUIDATION_MAX_PRICE_DEVIATION,
+            max_user_positions: MAX_USER_POSITIONS,
+        }
+    }
+}
+
+impl RiskParameters {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_oracle_confidence == 0
+            || self.max_oracle_staleness <= 0
+            || self.max_price_quote_age == 0
+            || self.liquidation_timeout <= 0
+            || self.liquidation_max_price_deviation == 0
+        {
+            msg!("oracle and liquidation limits must be positive");
+            return err!(ErrorCode::InvalidConfig);
+        }
+
+        if self.liquidation_max_equity_loss_proportion_bps > 10_000 {
+            msg!("the max equity loss cannot exceed the liabilities");
+            return err!(ErrorCode::InvalidConfig);
+        }
+
+        if self.liquidation_max_price_deviation > 10_000 {
+            msg!("the max price deviation cannot exceed the value swapped");
+            return err!(ErrorCode::InvalidConfig);
+        }
+
+        // the position list has space for 32 positions
+        if self.max_user_positions == 0 || self.max_user_positions > 32 {
+            msg!("the max positions must be between 1 and 32");
+            return err!(ErrorCode::InvalidConfig);
+        }
+
+        Ok(())
+    }
+}
+
 /// Configuration enabling a signer to execute permissioned actions
 #[account]
 #[derive(Default, Debug, Eq, PartialEq)]
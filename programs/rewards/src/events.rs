@@ -52,6 +52,45 @@ pub struct AirdropClosed {
     pub vault_amount: u64,
 }
 
+// MERKLE AIRDROPS
+
+#[event]
+pub struct MerkleAirdropCreated {
+    pub airdrop: Pubkey,
+    pub authority: Pubkey,
+    pub token_mint: Pubkey,
+    pub params: AirdropCreateParams,
+}
+
+#[event]
+pub struct MerkleAirdropFinalized {
+    pub airdrop: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub reward_total: u64,
+    pub recipients_total: u64,
+
+    pub vault_balance: u64,
+}
+
+#[event]
+pub struct MerkleAirdropClaimed {
+    pub airdrop: Pubkey,
+    pub recipient: Pubkey,
+    pub index: u64,
+    pub claimed_amount: u64,
+    pub remaining_amount: u64,
+
+    pub vault_balance: u64,
+}
+
+#[event]
+pub struct MerkleAirdropClosed {
+    pub airdrop: Pubkey,
+
+    /// The amount of tokens transferred out of the award vault.
+    pub vault_amount: u64,
+}
+
 // AWARDS
 
 #[event]
@@ -80,6 +119,10 @@ pub struct AwardReleased {
 pub struct AwardRevoked {
     pub award: Pubkey,
 
+    /// The amount that had vested, and was released to the stake account
+    /// before revoking.
+    pub vested_amount: u64,
+
     pub unreleased_amount: u64,
     pub total_released: u64,
 
@@ -4,6 +4,11 @@ mod airdrop_close;
 mod airdrop_create;
 mod airdrop_finalize;
 
+mod merkle_airdrop_claim;
+mod merkle_airdrop_close;
+mod merkle_airdrop_create;
+mod merkle_airdrop_finalize;
+
 mod distribution_close;
 mod distribution_create;
 mod distribution_release;
@@ -19,6 +24,11 @@ pub use airdrop_close::*;
 pub use airdrop_create::*;
 pub use airdrop_finalize::*;
 
+pub use merkle_airdrop_claim::*;
+pub use merkle_airdrop_close::*;
+pub use merkle_airdrop_create::*;
+pub use merkle_airdrop_finalize::*;
+
 pub use distribution_close::*;
 pub use distribution_create::*;
 pub use distribution_release::*;
@@ -24,6 +24,9 @@ pub struct AwardCreateParams {
 
     /// Time distribution is completed at
     pub end_at: u64,
+
+    /// The schedule for distributing the tokens
+    pub kind: DistributionKind,
 }
 
 #[derive(Accounts)]
@@ -105,7 +108,8 @@ pub fn award_create_handler(ctx: Context<AwardCreate>, params: AwardCreateParams
     award.distributed = 0;
     award.begin_at = params.begin_at;
     award.end_at = params.end_at;
-    award.kind = DistributionKind::Linear;
+    award.kind = params.kind;
+    award.validate()?;
 
     let award = &ctx.accounts.award;
 
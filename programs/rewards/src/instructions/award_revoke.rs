@@ -4,6 +4,8 @@ use anchor_spl::token::{self, CloseAccount, Token, TokenAccount};
 
 use crate::events::AwardRevoked;
 use crate::state::*;
+use jet_staking::cpi::accounts::AddStake;
+use jet_staking::program::JetStaking;
 
 #[derive(Accounts)]
 pub struct AwardRevoke<'info> {
@@ -11,7 +13,8 @@ pub struct AwardRevoke<'info> {
     #[account(mut,
               close = receiver,
               has_one = authority,
-              has_one = vault)]
+              has_one = vault,
+              has_one = stake_account)]
     pub award: Account<'info, Award>,
 
     /// The vault for the award
@@ -31,10 +34,52 @@ pub struct AwardRevoke<'info> {
     /// The authority with permission to close the award
     pub authority: Signer<'info>,
 
+    /// The account to transfer the vested tokens to
+    /// CHECK:
+    #[account(mut)]
+    pub stake_account: UncheckedAccount<'info>,
+
+    /// The voter weight for the stake account
+    /// CHECK:
+    #[account(mut)]
+    pub voter_weight_record: AccountInfo<'info>,
+
+    /// The max voter weight
+    /// CHECK:
+    #[account(mut)]
+    pub max_voter_weight_record: AccountInfo<'info>,
+
+    /// The stake pool the account is part of
+    /// CHECK:
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+
+    /// The token vault for the pool
+    /// CHECK:
+    #[account(mut)]
+    pub stake_pool_vault: UncheckedAccount<'info>,
+
+    pub staking_program: Program<'info, JetStaking>,
     pub token_program: Program<'info, Token>,
 }
 
 impl<'info> AwardRevoke<'info> {
+    fn add_stake_context(&self) -> CpiContext<'_, '_, '_, 'info, AddStake<'info>> {
+        CpiContext::new(
+            self.staking_program.to_account_info(),
+            AddStake {
+                stake_pool: self.stake_pool.to_account_info(),
+                stake_pool_vault: self.stake_pool_vault.to_account_info(),
+                stake_account: self.stake_account.to_account_info(),
+                voter_weight_record: self.voter_weight_record.to_account_info(),
+                max_voter_weight_record: self.max_voter_weight_record.to_account_info(),
+                payer: self.award.to_account_info(),
+                payer_token_account: self.vault.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            },
+        )
+    }
+
     fn transfer_remaining_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
         CpiContext::new(
             self.token_program.to_account_info(),
@@ -59,8 +104,24 @@ impl<'info> AwardRevoke<'info> {
 }
 
 pub fn award_revoke_handler(ctx: Context<AwardRevoke>) -> Result<()> {
+    let award = &mut ctx.accounts.award;
+    let clock = Clock::get()?;
+
+    // Tokens that have already vested still belong to the recipient, so they
+    // are released before reclaiming the unvested balance.
+    let vested_amount = award.distribute(clock.unix_timestamp as u64);
     let award = &ctx.accounts.award;
 
+    if vested_amount > 0 {
+        jet_staking::cpi::add_stake(
+            ctx.accounts
+                .add_stake_context()
+                .with_signer(&[&award.signer_seeds()]),
+            Some(vested_amount),
+        )?;
+        ctx.accounts.vault.reload()?;
+    }
+
     let vault_amount = ctx.accounts.vault.amount;
     let total_released = award.distributed;
     let unreleased_amount = award.target_amount - total_released;
@@ -81,6 +142,7 @@ pub fn award_revoke_handler(ctx: Context<AwardRevoke>) -> Result<()> {
     emit!(AwardRevoked {
         award: award.key(),
 
+        vested_amount,
         unreleased_amount,
         total_released,
 
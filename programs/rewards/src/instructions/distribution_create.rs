@@ -24,6 +24,9 @@ pub struct DistributionCreateParams {
 
     /// Time distribution is completed at
     pub end_at: u64,
+
+    /// The schedule for distributing the tokens
+    pub kind: DistributionKind,
 }
 
 #[derive(Accounts)]
@@ -108,7 +111,8 @@ pub fn distribution_create_handler(
     distribution.target_amount = params.amount;
     distribution.begin_at = params.begin_at;
     distribution.end_at = params.end_at;
-    distribution.kind = DistributionKind::Linear;
+    distribution.kind = params.kind;
+    distribution.validate()?;
 
     let distribution = &ctx.accounts.distribution;
 
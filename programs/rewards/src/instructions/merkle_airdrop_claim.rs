@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use jet_staking::cpi::accounts::AddStake;
+use jet_staking::program::JetStaking;
+
+use crate::ErrorCode;
+use crate::{events, state::*};
+
+#[derive(Debug, AnchorDeserialize, AnchorSerialize)]
+pub struct MerkleAirdropClaimParams {
+    /// The position of the recipient in the tree
+    pub index: u64,
+
+    /// The amount of tokens the recipient is entitled to
+    pub amount: u64,
+
+    /// The hashes needed to prove the recipient is in the tree
+    pub proof: Vec<[u8; 32]>,
+}
+
+#[derive(Accounts)]
+pub struct MerkleAirdropClaim<'info> {
+    /// The airdrop to claim from
+    #[account(mut,
+              has_one = stake_pool,
+              has_one = reward_vault)]
+    pub airdrop: AccountLoader<'info, MerkleAirdrop>,
+
+    /// The token account to claim the rewarded tokens from
+    /// CHECK:
+    #[account(mut)]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    /// The address entitled to the airdrop, which must sign to claim
+    pub recipient: Signer<'info>,
+
+    /// The stake pool to deposit stake into
+    /// CHECK:
+    #[account(mut)]
+    pub stake_pool: AccountInfo<'info>,
+
+    /// The stake pool token vault
+    /// CHECK:
+    #[account(mut)]
+    pub stake_pool_vault: UncheckedAccount<'info>,
+
+    /// The account to own the stake being deposited
+    /// CHECK:
+    #[account(mut)]
+    pub stake_account: AccountInfo<'info>,
+
+    /// The voter weight for the stake account
+    /// CHECK:
+    #[account(mut)]
+    pub voter_weight_record: AccountInfo<'info>,
+
+    /// The max voter weight
+    /// CHECK:
+    #[account(mut)]
+    pub max_voter_weight_record: AccountInfo<'info>,
+
+    pub staking_program: Program<'info, JetStaking>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> MerkleAirdropClaim<'info> {
+    fn add_stake_context(&self) -> CpiContext<'_, '_, '_, 'info, AddStake<'info>> {
+        CpiContext::new(
+            self.staking_program.to_account_info(),
+            AddStake {
+                stake_pool: self.stake_pool.to_account_info(),
+                stake_pool_vault: self.stake_pool_vault.to_account_info(),
+                stake_account: self.stake_account.to_account_info(),
+                voter_weight_record: self.voter_weight_record.to_account_info(),
+                max_voter_weight_record: self.max_voter_weight_record.to_account_info(),
+                payer: self.reward_vault.to_account_info(),
+                payer_token_account: self.reward_vault.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn merkle_airdrop_claim_handler(
+    ctx: Context<MerkleAirdropClaim>,
+    params: MerkleAirdropClaimParams,
+) -> Result<()> {
+    let info = ctx.accounts.airdrop.to_account_info();
+    let mut data = info.try_borrow_mut_data()?;
+    let (airdrop, bitmap) = MerkleAirdrop::from_data_mut(&mut data);
+    let clock = Clock::get()?;
+
+    if airdrop.expire_at <= clock.unix_timestamp {
+        msg!("this airdrop is expired");
+        return Err(ErrorCode::AirdropExpired.into());
+    }
+
+    airdrop.claim(
+        bitmap,
+        &ctx.accounts.recipient.key(),
+        params.index,
+        params.amount,
+        &params.proof,
+    )?;
+
+    jet_staking::cpi::add_stake(
+        ctx.accounts
+            .add_stake_context()
+            .with_signer(&[&airdrop.signer_seeds()]),
+        Some(params.amount),
+    )?;
+
+    emit!(events::MerkleAirdropClaimed {
+        airdrop: airdrop.address,
+        recipient: ctx.accounts.recipient.key(),
+        index: params.index,
+        claimed_amount: params.amount,
+        remaining_amount: airdrop.reward_total - airdrop.claimed_amount,
+
+        vault_balance: ctx.accounts.reward_vault.amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, TokenAccount};
+
+use crate::{events, state::*};
+
+#[derive(Debug, AnchorDeserialize, AnchorSerialize)]
+pub struct MerkleAirdropFinalizeParams {
+    /// The root of the merkle tree containing all the recipients
+    pub merkle_root: [u8; 32],
+
+    /// The total amount of tokens that can be claimed by all the recipients
+    pub reward_total: u64,
+
+    /// The number of recipients in the tree
+    pub recipients_total: u64,
+}
+
+#[derive(Accounts)]
+pub struct MerkleAirdropFinalize<'info> {
+    /// The airdrop to finalize
+    #[account(mut,
+              has_one = authority,
+              has_one = reward_vault)]
+    pub airdrop: AccountLoader<'info, MerkleAirdrop>,
+
+    /// The token account holding the reward tokens to be distributed
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    /// The authority to make changes to the airdrop, which must sign
+    pub authority: Signer<'info>,
+}
+
+pub fn merkle_airdrop_finalize_handler(
+    ctx: Context<MerkleAirdropFinalize>,
+    params: MerkleAirdropFinalizeParams,
+) -> Result<()> {
+    let info = ctx.accounts.airdrop.to_account_info();
+    let mut data = info.try_borrow_mut_data()?;
+    let (airdrop, bitmap) = MerkleAirdrop::from_data_mut(&mut data);
+    let vault_balance = token::accessor::amount(&ctx.accounts.reward_vault.to_account_info())?;
+
+    airdrop.finalize(
+        bitmap,
+        vault_balance,
+        params.merkle_root,
+        params.reward_total,
+        params.recipients_total,
+    )?;
+
+    emit!(events::MerkleAirdropFinalized {
+        airdrop: airdrop.address,
+        merkle_root: params.merkle_root,
+        reward_total: params.reward_total,
+        recipients_total: params.recipients_total,
+
+        vault_balance,
+    });
+
+    Ok(())
+}
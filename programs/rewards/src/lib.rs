@@ -60,6 +60,41 @@ pub mod jet_rewards {
         instructions::airdrop_claim_handler(ctx)
     }
 
+    /// Initialize a new account to manage an airdrop, where the recipients are
+    /// committed to as a merkle root instead of being stored in the account.
+    ///
+    /// The account must be allocated with enough space for the claim bitmap,
+    /// see [state::MerkleAirdrop::space].
+    pub fn merkle_airdrop_create(
+        ctx: Context<MerkleAirdropCreate>,
+        params: AirdropCreateParams,
+    ) -> Result<()> {
+        instructions::merkle_airdrop_create_handler(ctx, params)
+    }
+
+    /// Commit the merkle root and totals for an airdrop, allowing recipients
+    /// to claim their tokens.
+    pub fn merkle_airdrop_finalize(
+        ctx: Context<MerkleAirdropFinalize>,
+        params: MerkleAirdropFinalizeParams,
+    ) -> Result<()> {
+        instructions::merkle_airdrop_finalize_handler(ctx, params)
+    }
+
+    /// Close and delete a merkle airdrop account.
+    pub fn merkle_airdrop_close(ctx: Context<MerkleAirdropClose>) -> Result<()> {
+        instructions::merkle_airdrop_close_handler(ctx)
+    }
+
+    /// Claim of tokens from a merkle airdrop as a recipient, with a proof
+    /// of the recipient's entry in the tree.
+    pub fn merkle_airdrop_claim(
+        ctx: Context<MerkleAirdropClaim>,
+        params: MerkleAirdropClaimParams,
+    ) -> Result<()> {
+        instructions::merkle_airdrop_claim_handler(ctx, params)
+    }
+
     /// Initialize a new token distribution
     pub fn distribution_create(
         ctx: Context<DistributionCreate>,
@@ -118,6 +153,12 @@ mod error {
         DistributionNotEnded,
 
         AwardNotFullyVested,
+
+        AirdropAlreadyClaimed,
+        InvalidMerkleProof,
+        AirdropInsufficientSpace,
+
+        InvalidDistributionSchedule,
     }
 }
 
@@ -1,7 +1,9 @@
 mod airdrop;
 mod award;
 mod distribution;
+mod merkle_airdrop;
 
 pub use airdrop::*;
 pub use award::*;
 pub use distribution::*;
+pub use merkle_airdrop::*;
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::ErrorCode;
+
 #[account]
 #[derive(Default)]
 pub struct Distribution {
@@ -56,10 +58,40 @@ impl std::ops::DerefMut for Distribution {
     }
 }
 
-#[derive(Default, AnchorDeserialize, AnchorSerialize, Clone, Copy)]
+#[derive(Default, AnchorDeserialize, AnchorSerialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DistributionKind {
+    /// Tokens are distributed at a constant rate between the begin and end times
     #[default]
     Linear,
+
+    /// Nothing is distributed before the cliff, at which point the tokens that
+    /// would have been distributed linearly since the beginning are released,
+    /// with the rest continuing to be distributed linearly.
+    Cliff {
+        /// The time of the cliff
+        cliff_at: u64,
+    },
+
+    /// Tokens are released in equal tranches at the end of each period,
+    /// starting from the begin time.
+    Step {
+        /// The length of each period, in seconds
+        period: u64,
+    },
+
+    /// The rate of distribution decays exponentially, halving after each
+    /// half-life, with anything remaining released at the end time.
+    Exponential {
+        /// The time for the rate of distribution to halve, in seconds
+        half_life: u64,
+    },
+}
+
+impl DistributionKind {
+    /// The most bytes used by any kind of distribution
+    pub const fn space() -> usize {
+        1 + 8
+    }
 }
 
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, Default)]
@@ -82,7 +114,27 @@ pub struct TokenDistribution {
 
 impl TokenDistribution {
     pub fn space() -> usize {
-        8 * 4 + 1
+        8 * 4 + DistributionKind::space()
+    }
+
+    /// Check that the schedule for the distribution is valid
+    pub fn validate(&self) -> Result<()> {
+        let valid = self.begin_at <= self.end_at
+            && match self.kind {
+                DistributionKind::Linear => true,
+                DistributionKind::Cliff { cliff_at } => {
+                    self.begin_at <= cliff_at && cliff_at <= self.end_at
+                }
+                DistributionKind::Step { period } => period > 0,
+                DistributionKind::Exponential { half_life } => half_life > 0,
+            };
+
+        if !valid {
+            msg!("invalid distribution schedule: {:?}", self.kind);
+            return err!(ErrorCode::InvalidDistributionSchedule);
+        }
+
+        Ok(())
     }
 
     pub fn distribute(&mut self, timestamp: u64) -> u64 {
@@ -95,9 +147,21 @@ impl TokenDistribution {
     pub fn distributed_amount(&self, timestamp: u64) -> u64 {
         match self.kind {
             DistributionKind::Linear => self.distributed_amount_linear(timestamp),
+            DistributionKind::Cliff { cliff_at } => {
+                self.distributed_amount_cliff(timestamp, cliff_at)
+            }
+            DistributionKind::Step { period } => self.distributed_amount_step(timestamp, period),
+            DistributionKind::Exponential { half_life } => {
+                self.distributed_amount_exponential(timestamp, half_life)
+            }
         }
     }
 
+    /// The amount of tokens not yet distributed as of the timestamp
+    pub fn undistributed_amount(&self, timestamp: u64) -> u64 {
+        self.target_amount - self.distributed_amount(timestamp)
+    }
+
     fn distributed_amount_linear(&self, timestamp: u64) -> u64 {
         let range = std::cmp::max(1, self.end_at.checked_sub(self.begin_at).unwrap()) as u128;
         let remaining = self.end_at.saturating_sub(timestamp) as u128;
@@ -110,6 +174,48 @@ impl TokenDistribution {
 
         distributed as u64
     }
+
+    fn distributed_amount_cliff(&self, timestamp: u64, cliff_at: u64) -> u64 {
+        match timestamp < cliff_at {
+            true => 0,
+            false => self.distributed_amount_linear(timestamp),
+        }
+    }
+
+    fn distributed_amount_step(&self, timestamp: u64, period: u64) -> u64 {
+        if timestamp >= self.end_at {
+            return self.target_amount;
+        }
+
+        let range = self.end_at - self.begin_at;
+        // rounded up without overflowing for very long periods
+        let total_steps = range / period + u64::from(range % period != 0);
+        let total_steps = std::cmp::max(1, total_steps) as u128;
+        let steps = (timestamp.saturating_sub(self.begin_at) / period) as u128;
+
+        (self.target_amount as u128 * steps / total_steps) as u64
+    }
+
+    fn distributed_amount_exponential(&self, timestamp: u64, half_life: u64) -> u64 {
+        if timestamp >= self.end_at {
+            return self.target_amount;
+        }
+
+        let elapsed = timestamp.saturating_sub(self.begin_at);
+        let halvings = elapsed / half_life;
+
+        if halvings >= 64 {
+            return self.target_amount;
+        }
+
+        // The remaining amount halves with each half-life, and is interpolated
+        // linearly within the current half-life.
+        let remaining = (self.target_amount >> halvings) as u128;
+        let fraction = (elapsed % half_life) as u128;
+        let remaining = remaining - remaining * fraction / (2 * half_life as u128);
+
+        self.target_amount - remaining as u64
+    }
 }
 
 #[cfg(test)]
@@ -143,4 +249,85 @@ mod test {
         let now = 1642721054;
         assert_eq!(dist.target_amount, dist.distributed_amount(now));
     }
+
+    fn schedule(kind: DistributionKind) -> TokenDistribution {
+        TokenDistribution {
+            distributed: 0,
+            target_amount: 1_200,
+            begin_at: 1_000,
+            end_at: 2_200,
+            kind,
+        }
+    }
+
+    #[test]
+    fn cliff_distribution() {
+        let dist = schedule(DistributionKind::Cliff { cliff_at: 1_300 });
+        dist.validate().unwrap();
+
+        assert_eq!(0, dist.distributed_amount(1_299));
+        assert_eq!(300, dist.distributed_amount(1_300));
+        assert_eq!(600, dist.distributed_amount(1_600));
+        assert_eq!(1_200, dist.distributed_amount(2_200));
+
+        schedule(DistributionKind::Cliff { cliff_at: 2_201 })
+            .validate()
+            .unwrap_err();
+    }
+
+    #[test]
+    fn step_distribution() {
+        let dist = schedule(DistributionKind::Step { period: 500 });
+        dist.validate().unwrap();
+
+        assert_eq!(0, dist.distributed_amount(1_499));
+        assert_eq!(400, dist.distributed_amount(1_500));
+        assert_eq!(800, dist.distributed_amount(2_199));
+        assert_eq!(1_200, dist.distributed_amount(2_200));
+
+        schedule(DistributionKind::Step { period: 0 })
+            .validate()
+            .unwrap_err();
+    }
+
+    #[test]
+    fn step_distribution_with_long_period() {
+        let dist = TokenDistribution {
+            distributed: 0,
+            target_amount: 1_200,
+            begin_at: 0,
+            end_at: u64::MAX,
+            kind: DistributionKind::Step { period: u64::MAX },
+        };
+
+        assert_eq!(0, dist.distributed_amount(u64::MAX - 1));
+        assert_eq!(1_200, dist.distributed_amount(u64::MAX));
+    }
+
+    #[test]
+    fn exponential_distribution() {
+        let dist = schedule(DistributionKind::Exponential { half_life: 200 });
+        dist.validate().unwrap();
+
+        assert_eq!(0, dist.distributed_amount(1_000));
+        assert_eq!(300, dist.distributed_amount(1_100));
+        assert_eq!(600, dist.distributed_amount(1_200));
+        assert_eq!(900, dist.distributed_amount(1_400));
+        assert_eq!(1_200, dist.distributed_amount(2_200));
+
+        let mut last = 0;
+        for timestamp in (1_000..2_200).step_by(7) {
+            let distributed = dist.distributed_amount(timestamp);
+            assert!(distributed >= last);
+            last = distributed;
+        }
+    }
+
+    #[test]
+    fn undistributed_amount_is_unvested() {
+        let dist = schedule(DistributionKind::Step { period: 300 });
+
+        assert_eq!(900, dist.undistributed_amount(1_300));
+        assert_eq!(0, dist.undistributed_amount(2_200));
+    }
 }
@@ -0,0 +1,311 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+
+use jet_program_common::pod::PodBytes;
+
+use crate::ErrorCode;
+
+/// Prefix for hashing the leaves of the tree, so they can't be confused with inner nodes
+const LEAF_PREFIX: &[u8] = &[0];
+
+/// Prefix for hashing the inner nodes of the tree
+const NODE_PREFIX: &[u8] = &[1];
+
+/// An airdrop where the recipients are committed to as the root of a merkle tree,
+/// rather than being stored in the account.
+///
+/// The account data following this struct is a bitmap, with one bit per recipient
+/// index to mark the recipients that have already claimed.
+#[account(zero_copy)]
+pub struct MerkleAirdrop {
+    /// The address of this account
+    pub address: Pubkey,
+
+    /// The token account containing the tokens to be distributed
+    /// as the airdrop reward
+    pub reward_vault: Pubkey,
+
+    /// The address allowed to make changes to the airdrop metadata
+    /// before finalizing.
+    pub authority: Pubkey,
+
+    /// The time at which this airdrop expires, and can no longer be claimed
+    pub expire_at: i64,
+
+    /// The stake pool that rewards are staked into when claimed
+    pub stake_pool: Pubkey,
+
+    /// Settings for airdrops
+    pub flags: u64,
+
+    /// A short descriptive text for the airdrop
+    pub short_desc: [u8; 32],
+
+    /// A longer descriptive text for the airdrop
+    pub long_desc: PodBytes<255>,
+
+    /// The bump seed for the reward vault
+    pub vault_bump: [u8; 1],
+
+    /// The root of the merkle tree containing all the recipients
+    pub merkle_root: [u8; 32],
+
+    /// The total amount of reward tokens that are claimable by recipients
+    pub reward_total: u64,
+
+    /// The total number of airdrop recipients
+    pub recipients_total: u64,
+
+    /// The amount of reward tokens claimed so far
+    pub claimed_amount: u64,
+
+    /// The number of recipients that have claimed so far
+    pub claimed_total: u64,
+
+    /// Marker to indicate when the airdrop has been finalized
+    /// from further edits
+    pub finalized: u64,
+}
+
+impl MerkleAirdrop {
+    /// The size of the account needed to track claims for a number of recipients
+    pub fn space(recipients_total: u64) -> usize {
+        8 + std::mem::size_of::<Self>() + Self::bitmap_len(recipients_total)
+    }
+
+    fn bitmap_len(recipients_total: u64) -> usize {
+        ((recipients_total + 7) / 8) as usize
+    }
+
+    /// Split the data for an airdrop account into the airdrop state and the claimed bitmap
+    pub fn from_data_mut(data: &mut [u8]) -> (&mut Self, &mut [u8]) {
+        let (header, bitmap) = data[8..].split_at_mut(std::mem::size_of::<Self>());
+        (bytemuck::from_bytes_mut(header), bitmap)
+    }
+
+    pub fn finalize(
+        &mut self,
+        bitmap: &[u8],
+        vault_balance: u64,
+        merkle_root: [u8; 32],
+        reward_total: u64,
+        recipients_total: u64,
+    ) -> Result<()> {
+        if self.finalized > 0 {
+            return Err(ErrorCode::AirdropFinal.into());
+        }
+
+        if vault_balance < reward_total {
+            return Err(ErrorCode::AirdropInsufficientRewardBalance.into());
+        }
+
+        if bitmap.len() < Self::bitmap_len(recipients_total) {
+            msg!(
+                "the airdrop account needs {} bytes for {} recipients",
+                Self::space(recipients_total),
+                recipients_total
+            );
+            return Err(ErrorCode::AirdropInsufficientSpace.into());
+        }
+
+        self.merkle_root = merkle_root;
+        self.reward_total = reward_total;
+        self.recipients_total = recipients_total;
+        self.finalized = 1;
+
+        Ok(())
+    }
+
+    pub fn claim(
+        &mut self,
+        bitmap: &mut [u8],
+        recipient: &Pubkey,
+        index: u64,
+        amount: u64,
+        proof: &[[u8; 32]],
+    ) -> Result<()> {
+        if self.finalized != 1 {
+            msg!("cannot claim from an unfinalized airdrop");
+            return Err(ErrorCode::AirdropNotFinal.into());
+        }
+
+        if index >= self.recipients_total {
+            return Err(ErrorCode::RecipientNotFound.into());
+        }
+
+        let (byte, bit) = ((index / 8) as usize, 1u8 << (index % 8));
+
+        if bitmap[byte] & bit != 0 {
+            return Err(ErrorCode::AirdropAlreadyClaimed.into());
+        }
+
+        if !verify_proof(proof, self.merkle_root, leaf_hash(index, recipient, amount)) {
+            return Err(ErrorCode::InvalidMerkleProof.into());
+        }
+
+        bitmap[byte] |= bit;
+        self.claimed_total += 1;
+        self.claimed_amount = self.claimed_amount.checked_add(amount).unwrap();
+
+        if self.claimed_amount > self.reward_total {
+            msg!("claims exceed the airdrop total");
+            return Err(ErrorCode::AirdropInsufficientRewardBalance.into());
+        }
+
+        Ok(())
+    }
+
+    pub fn signer_seeds(&self) -> [&[u8]; 3] {
+        [self.address.as_ref(), b"vault".as_ref(), &self.vault_bump]
+    }
+}
+
+impl std::fmt::Debug for MerkleAirdrop {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("MerkleAirdrop")
+            .field("address", &self.address)
+            .field("reward_vault", &self.reward_vault)
+            .field("authority", &self.authority)
+            .field("stake_pool", &self.stake_pool)
+            .field("expire_at", &(self.expire_at))
+            .field("flags", &self.flags)
+            .field("reward_total", &self.reward_total)
+            .field("recipients_total", &self.recipients_total)
+            .field("claimed_amount", &self.claimed_amount)
+            .finish()
+    }
+}
+
+/// The hash of a recipient's entry in the merkle tree
+pub fn leaf_hash(index: u64, recipient: &Pubkey, amount: u64) -> [u8; 32] {
+    keccak::hashv(&[
+        LEAF_PREFIX,
+        &index.to_le_bytes(),
+        recipient.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .0
+}
+
+/// The hash of an inner node in the merkle tree, where the children are sorted so
+/// that proofs don't need to include the position of each node.
+pub fn node_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    keccak::hashv(&[NODE_PREFIX, left, right]).0
+}
+
+pub fn verify_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    proof
+        .iter()
+        .fold(leaf, |node, sibling| node_hash(&node, sibling))
+        == root
+}
+
+/// Builds the merkle tree for a set of airdrop recipients, to generate the root
+/// and the proofs needed for each recipient to claim.
+#[cfg(any(test, feature = "cli"))]
+pub struct MerkleTree {
+    /// Each level of the tree, starting from the leaves
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+#[cfg(any(test, feature = "cli"))]
+impl MerkleTree {
+    /// Build a tree for the recipients, where each recipient's index is its
+    /// position in the list.
+    pub fn new(recipients: &[(Pubkey, u64)]) -> Self {
+        let leaves = recipients
+            .iter()
+            .enumerate()
+            .map(|(index, (recipient, amount))| leaf_hash(index as u64, recipient, *amount))
+            .collect::<Vec<_>>();
+        let mut levels = vec![leaves];
+
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => node_hash(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+
+        Self { levels }
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.levels
+            .last()
+            .unwrap()
+            .first()
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn proof(&self, mut index: usize) -> Vec<[u8; 32]> {
+        let mut proof = vec![];
+
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+
+        proof
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn proofs_verify_for_each_recipient() {
+        let recipients = (0..13)
+            .map(|i| (Pubkey::new_unique(), i * 100))
+            .collect::<Vec<_>>();
+        let tree = MerkleTree::new(&recipients);
+
+        for (index, (recipient, amount)) in recipients.iter().enumerate() {
+            let leaf = leaf_hash(index as u64, recipient, *amount);
+            assert!(verify_proof(&tree.proof(index), tree.root(), leaf));
+
+            let wrong_amount = leaf_hash(index as u64, recipient, *amount + 1);
+            assert!(!verify_proof(&tree.proof(index), tree.root(), wrong_amount));
+        }
+    }
+
+    #[test]
+    fn claims_are_tracked_in_bitmap() {
+        let recipients = (0..10)
+            .map(|_| (Pubkey::new_unique(), 100))
+            .collect::<Vec<_>>();
+        let tree = MerkleTree::new(&recipients);
+
+        let mut data = vec![0u8; MerkleAirdrop::space(recipients.len() as u64)];
+        let (airdrop, bitmap) = MerkleAirdrop::from_data_mut(&mut data);
+        airdrop
+            .finalize(bitmap, 1000, tree.root(), 1000, recipients.len() as u64)
+            .unwrap();
+
+        let (recipient, amount) = recipients[9];
+        airdrop
+            .claim(bitmap, &recipient, 9, amount, &tree.proof(9))
+            .unwrap();
+        airdrop
+            .claim(bitmap, &recipient, 9, amount, &tree.proof(9))
+            .unwrap_err();
+        airdrop
+            .claim(bitmap, &recipients[8].0, 8, amount, &tree.proof(9))
+            .unwrap_err();
+
+        assert_eq!(0b10, bitmap[1]);
+        assert_eq!(100, airdrop.claimed_amount);
+    }
+}
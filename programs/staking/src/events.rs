@@ -39,6 +39,16 @@ pub struct StakeAdded {
     pub max_voter_weight: u64,
 }
 
+#[event]
+pub struct StakeLockedUp {
+    pub stake_pool: Pubkey,
+    pub stake_account: Pubkey,
+    pub owner: Pubkey,
+    pub locked_until: i64,
+
+    pub voter_weight: u64,
+}
+
 #[event]
 pub struct StakeUnbonded {
     pub stake_pool: Pubkey,
@@ -94,6 +104,65 @@ pub struct BondedWithdrawn {
     pub pool_note: StakePoolNote,
 }
 
+#[event]
+pub struct StakeTransferred {
+    pub stake_pool: Pubkey,
+    pub source_stake_account: Pubkey,
+    pub target_stake_account: Pubkey,
+    pub margin_account: Pubkey,
+
+    pub transferred_shares: u64,
+
+    pub source_note: StakeAccountNote,
+    pub target_note: StakeAccountNote,
+}
+
+#[event]
+pub struct InsuranceConfigured {
+    pub stake_pool: Pubkey,
+    pub insurance_fund: Pubkey,
+    pub airspace: Pubkey,
+    pub max_claim_bps: u16,
+    pub max_epoch_bps: u16,
+}
+
+#[event]
+pub struct BadDebtRecorded {
+    pub stake_pool: Pubkey,
+    pub insurance_fund: Pubkey,
+    pub claim: Pubkey,
+    pub beneficiary: Pubkey,
+    pub source: Pubkey,
+    pub margin_account: Pubkey,
+    pub debt_mint: Pubkey,
+    pub debt_amount: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct BadDebtCovered {
+    pub stake_pool: Pubkey,
+    pub insurance_fund: Pubkey,
+    pub claim: Pubkey,
+    pub beneficiary: Pubkey,
+
+    pub claimed_amount: u64,
+    pub covered_amount: u64,
+    pub uncovered_amount: u64,
+
+    pub pool_note: StakePoolNote,
+}
+
+#[event]
+pub struct RecoveredWithdrawn {
+    pub stake_pool: Pubkey,
+    pub insurance_fund: Pubkey,
+    pub margin_pool: Pubkey,
+    pub recovery_account: Pubkey,
+
+    pub withdrawn_amount: u64,
+}
+
 #[event]
 pub struct StakeAccountClosed {
     pub stake_account: Pubkey,
@@ -3,19 +3,41 @@ mod init_stake_account;
 
 mod add_stake;
 mod cancel_unbond;
+mod lock_stake;
+mod margin_refresh_stake;
+mod margin_register_stake;
+mod margin_transfer_stake;
+mod margin_unbond_stake;
 mod unbond_stake;
+mod update_voter_weight;
 mod withdraw_bonded;
 mod withdraw_unbonded;
 
 mod close_stake_account;
 
+mod configure_insurance;
+mod cover_bad_debt;
+mod record_bad_debt;
+mod withdraw_recovered;
+
 pub use init_pool::*;
 pub use init_stake_account::*;
 
 pub use add_stake::*;
 pub use cancel_unbond::*;
+pub use lock_stake::*;
+pub use margin_refresh_stake::*;
+pub use margin_register_stake::*;
+pub use margin_transfer_stake::*;
+pub use margin_unbond_stake::*;
 pub use unbond_stake::*;
+pub use update_voter_weight::*;
 pub use withdraw_bonded::*;
 pub use withdraw_unbonded::*;
 
 pub use close_stake_account::*;
+
+pub use configure_insurance::*;
+pub use cover_bad_debt::*;
+pub use record_bad_debt::*;
+pub use withdraw_recovered::*;
@@ -55,7 +55,16 @@ impl<'info> AddStake<'info> {
 }
 
 /// handler handler
+///
+/// The lockup for the stake account can be passed as the only remaining account,
+/// so the voter weight keeps its lockup bonus. It's optional so that programs
+/// unaware of lockups can still add stake, in which case the bonus is restored
+/// by the next `update_voter_weight`.
 pub fn add_stake_handler(ctx: Context<AddStake>, amount: Option<u64>) -> Result<()> {
+    let lockup = match ctx.remaining_accounts.first() {
+        Some(info) => StakeLockup::load_for(&ctx.accounts.stake_account.key(), info)?,
+        None => None,
+    };
     let stake_pool = &mut ctx.accounts.stake_pool;
     let stake_account = &mut ctx.accounts.stake_account;
     let voter_weight = &mut ctx.accounts.voter_weight_record;
@@ -69,7 +78,11 @@ pub fn add_stake_handler(ctx: Context<AddStake>, amount: Option<u64>) -> Result<
     };
 
     let full_amount = stake_pool.deposit(stake_account, token_amount);
-    stake_account.update_voter_weight_record(voter_weight);
+    stake_account.update_voter_weight_record_with_lockup(
+        voter_weight,
+        lockup.as_ref(),
+        &Clock::get()?,
+    );
     stake_pool.update_max_vote_weight_record(max_weight);
 
     let stake_pool = &ctx.accounts.stake_pool;
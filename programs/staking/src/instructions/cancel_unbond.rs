@@ -45,7 +45,13 @@ pub struct CancelUnbond<'info> {
     pub unbonding_account: Account<'info, UnbondingAccount>,
 }
 
+/// The lockup for the stake account can be passed as the only remaining account,
+/// so the voter weight keeps its lockup bonus.
 pub fn cancel_unbond_handler(ctx: Context<CancelUnbond>) -> Result<()> {
+    let lockup = match ctx.remaining_accounts.first() {
+        Some(info) => StakeLockup::load_for(&ctx.accounts.stake_account.key(), info)?,
+        None => None,
+    };
     let stake_pool = &mut ctx.accounts.stake_pool;
     let stake_account = &mut ctx.accounts.stake_account;
     let unbonding_account = &mut ctx.accounts.unbonding_account;
@@ -55,7 +61,11 @@ pub fn cancel_unbond_handler(ctx: Context<CancelUnbond>) -> Result<()> {
     stake_pool.update_vault(ctx.accounts.stake_pool_vault.amount);
     let cancelled_amount = stake_pool.rebond(stake_account, unbonding_account);
 
-    stake_account.update_voter_weight_record(voter_weight);
+    stake_account.update_voter_weight_record_with_lockup(
+        voter_weight,
+        lockup.as_ref(),
+        &Clock::get()?,
+    );
     stake_pool.update_max_vote_weight_record(max_weight);
 
     emit!(UnbondCancelled {
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::events::InsuranceConfigured;
+use crate::seeds;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct ConfigureInsurance<'info> {
+    /// The authority for the stake pool
+    pub authority: Signer<'info>,
+
+    /// The stake pool to provide the insurance
+    #[account(has_one = authority)]
+    pub stake_pool: Account<'info, StakePool>,
+
+    /// The insurance fund configuration for the pool
+    #[account(init_if_needed,
+              seeds = [
+                  stake_pool.key().as_ref(),
+                  seeds::INSURANCE
+              ],
+              bump,
+              payer = payer,
+              space = 8 + std::mem::size_of::<InsuranceFund>())]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    /// The payer for rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn configure_insurance_handler(
+    ctx: Context<ConfigureInsurance>,
+    airspace: Pubkey,
+    max_claim_bps: u16,
+    max_epoch_bps: u16,
+) -> Result<()> {
+    let insurance_fund = &mut ctx.accounts.insurance_fund;
+
+    insurance_fund.stake_pool = ctx.accounts.stake_pool.key();
+    insurance_fund.airspace = airspace;
+    insurance_fund.max_claim_bps = max_claim_bps;
+    insurance_fund.max_epoch_bps = max_epoch_bps;
+    insurance_fund.validate()?;
+
+    emit!(InsuranceConfigured {
+        stake_pool: insurance_fund.stake_pool,
+        insurance_fund: insurance_fund.key(),
+        airspace,
+        max_claim_bps,
+        max_epoch_bps,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::events::{BadDebtCovered, Note};
+use crate::state::*;
+use crate::ErrorCode;
+
+#[derive(Accounts)]
+pub struct CoverBadDebt<'info> {
+    /// The stake pool providing the insurance
+    #[account(mut,
+              has_one = stake_pool_vault)]
+    pub stake_pool: Account<'info, StakePool>,
+
+    /// The stake pool token vault
+    #[account(mut)]
+    pub stake_pool_vault: Account<'info, TokenAccount>,
+
+    /// The insurance fund the claim is against
+    #[account(mut,
+              has_one = stake_pool)]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    /// The claim to be covered
+    #[account(mut,
+              has_one = insurance_fund,
+              has_one = beneficiary)]
+    pub claim: Account<'info, BadDebtClaim>,
+
+    /// The recovery account recorded to receive the tokens covering the debt
+    #[account(mut)]
+    pub beneficiary: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CoverBadDebt<'info> {
+    fn transfer_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.stake_pool_vault.to_account_info(),
+                to: self.beneficiary.to_account_info(),
+                authority: self.stake_pool.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn cover_bad_debt_handler(ctx: Context<CoverBadDebt>) -> Result<()> {
+    let stake_pool = &mut ctx.accounts.stake_pool;
+    let insurance_fund = &mut ctx.accounts.insurance_fund;
+    let claim = &mut ctx.accounts.claim;
+
+    if claim.covered_at != 0 {
+        return err!(ErrorCode::ClaimAlreadyCovered);
+    }
+
+    let clock = Clock::get()?;
+    stake_pool.update_vault(ctx.accounts.stake_pool_vault.amount);
+
+    let max_cover = insurance_fund.claim_limit(stake_pool.vault_amount, clock.epoch);
+    if max_cover == 0 {
+        msg!("the insurance fund has paid out all it can for this epoch");
+        return err!(ErrorCode::InsuranceLimitReached);
+    }
+
+    let covered = stake_pool.cover_bad_debt(claim.amount, max_cover);
+
+    claim.covered = covered;
+    claim.uncovered = claim.amount - covered;
+    claim.covered_at = clock.unix_timestamp;
+    insurance_fund.record_covered(covered);
+
+    let stake_pool = &ctx.accounts.stake_pool;
+    token::transfer(
+        ctx.accounts
+            .transfer_context()
+            .with_signer(&[&stake_pool.signer_seeds()]),
+        covered,
+    )?;
+
+    emit!(BadDebtCovered {
+        stake_pool: stake_pool.key(),
+        insurance_fund: ctx.accounts.insurance_fund.key(),
+        claim: ctx.accounts.claim.key(),
+        beneficiary: ctx.accounts.beneficiary.key(),
+
+        claimed_amount: ctx.accounts.claim.amount,
+        covered_amount: covered,
+        uncovered_amount: ctx.accounts.claim.uncovered,
+
+        pool_note: stake_pool.note(),
+    });
+
+    Ok(())
+}
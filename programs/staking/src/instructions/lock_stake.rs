@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::events::StakeLockedUp;
+use crate::seeds;
+use crate::spl_addin::VoterWeightRecord;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct LockStake<'info> {
+    /// The owner of the stake account
+    pub owner: Signer<'info>,
+
+    /// The payer for rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The account owning the stake to be locked
+    #[account(has_one = owner,
+              has_one = voter_weight_record)]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    /// The lockup for the stake account
+    #[account(init_if_needed,
+              seeds = [
+                  stake_account.key().as_ref(),
+                  seeds::LOCKUP
+              ],
+              bump,
+              payer = payer,
+              space = 8 + std::mem::size_of::<StakeLockup>())]
+    pub stake_lockup: Account<'info, StakeLockup>,
+
+    /// The voter weight to be updated
+    #[account(mut)]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn lock_stake_handler(ctx: Context<LockStake>, lockup_period: i64) -> Result<()> {
+    let stake_account = &ctx.accounts.stake_account;
+    let lockup = &mut ctx.accounts.stake_lockup;
+    let voter_weight = &mut ctx.accounts.voter_weight_record;
+    let clock = Clock::get()?;
+
+    lockup.stake_account = stake_account.key();
+    lockup.extend(clock.unix_timestamp, lockup_period)?;
+
+    stake_account.update_voter_weight_record_with_lockup(voter_weight, Some(&**lockup), &clock);
+
+    emit!(StakeLockedUp {
+        stake_pool: stake_account.stake_pool,
+        stake_account: stake_account.key(),
+        owner: stake_account.owner,
+        locked_until: lockup.locked_until,
+
+        voter_weight: voter_weight.voter_weight,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use jet_margin::{AdapterResult, MarginAccount, PositionChange, TokenConfig};
+
+use crate::margin::{self, StakeCollateral};
+use crate::seeds;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct MarginRefreshStake<'info> {
+    /// The margin account owning the stake
+    pub margin_account: AccountLoader<'info, MarginAccount>,
+
+    /// The stake pool the stake is in
+    #[account(has_one = stake_pool_vault,
+              has_one = stake_collateral_mint)]
+    pub stake_pool: Box<Account<'info, StakePool>>,
+
+    /// The stake pool token vault
+    pub stake_pool_vault: Box<Account<'info, TokenAccount>>,
+
+    /// The stake account owned by the margin account
+    #[account(has_one = stake_pool,
+              constraint = stake_account.owner == margin_account.key())]
+    pub stake_account: Box<Account<'info, StakeAccount>>,
+
+    /// The mint for the collateral tokens representing the bonded stake
+    #[account(mut)]
+    pub stake_collateral_mint: Box<Account<'info, Mint>>,
+
+    /// The token account holding the collateral tokens for the stake
+    #[account(mut,
+              seeds = [
+                  stake_account.key().as_ref(),
+                  seeds::COLLATERAL
+              ],
+              bump)]
+    pub collateral_account: Box<Account<'info, TokenAccount>>,
+
+    /// The margin config for the staked token, which identifies its oracle
+    pub token_config: Box<Account<'info, TokenConfig>>,
+
+    /// The pyth price account for the staked token
+    /// CHECK: verified against the token config
+    pub token_price_oracle: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn margin_refresh_stake_handler(ctx: Context<MarginRefreshStake>) -> Result<()> {
+    let margin_account = ctx.accounts.margin_account.load()?;
+
+    margin::verify_token_oracle(
+        &margin_account,
+        &ctx.accounts.stake_pool,
+        &ctx.accounts.token_config,
+        &ctx.accounts.token_price_oracle,
+    )?;
+
+    // Any tokens added to the vault since the pool was last updated are credited
+    // to the bonded shares, so they need to be included in the price.
+    let mut stake_pool = (**ctx.accounts.stake_pool).clone();
+    stake_pool.update_vault(ctx.accounts.stake_pool_vault.amount);
+
+    let price = margin::bonded_share_price(&stake_pool, &ctx.accounts.token_price_oracle)?;
+
+    StakeCollateral {
+        stake_pool: &ctx.accounts.stake_pool,
+        stake_collateral_mint: &ctx.accounts.stake_collateral_mint,
+        collateral_account: &ctx.accounts.collateral_account,
+        token_program: &ctx.accounts.token_program,
+    }
+    .sync(&ctx.accounts.stake_account)?;
+
+    // Tell the margin program what the current price is
+    jet_margin::write_adapter_result(
+        &margin_account,
+        &AdapterResult {
+            position_changes: vec![(
+                ctx.accounts.stake_collateral_mint.key(),
+                vec![PositionChange::Price(price)],
+            )],
+        },
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use jet_margin::{AdapterResult, MarginAccount, PositionChange};
+
+use crate::events::StakeAccountCreated;
+use crate::seeds;
+use crate::spl_addin::VoterWeightRecord;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct MarginRegisterStake<'info> {
+    /// The margin account to own the stake
+    #[account(signer)]
+    pub margin_account: AccountLoader<'info, MarginAccount>,
+
+    /// The stake pool to create an account with
+    #[account(has_one = stake_collateral_mint)]
+    pub stake_pool: Box<Account<'info, StakePool>>,
+
+    /// The new stake account, owned by the margin account
+    #[account(
+        init,
+        payer = payer,
+        seeds = [
+            stake_pool.key().as_ref(),
+            margin_account.key().as_ref()
+        ],
+        bump,
+        space = 8 + std::mem::size_of::<StakeAccount>(),
+    )]
+    pub stake_account: Box<Account<'info, StakeAccount>>,
+
+    /// The voter weight record to be created for this stake
+    #[account(init,
+              seeds = [
+                  seeds::VOTER_WEIGHT_RECORD,
+                  stake_account.key().as_ref()
+              ],
+              bump,
+              payer = payer,
+              space = 8 + std::mem::size_of::<VoterWeightRecord>())]
+    pub voter_weight_record: Box<Account<'info, VoterWeightRecord>>,
+
+    /// The mint for the collateral tokens representing the bonded stake
+    pub stake_collateral_mint: Box<Account<'info, Mint>>,
+
+    /// The token account to hold the collateral tokens, which is registered
+    /// as a position in the margin account
+    #[account(init,
+              seeds = [
+                  stake_account.key().as_ref(),
+                  seeds::COLLATERAL
+              ],
+              bump,
+              payer = payer,
+              token::mint = stake_collateral_mint,
+              token::authority = stake_pool)]
+    pub collateral_account: Box<Account<'info, TokenAccount>>,
+
+    /// This will be required for margin to register the position,
+    /// so requiring it here makes it easier for clients to ensure
+    /// that it will be sent.
+    ///
+    /// CHECK:
+    pub collateral_token_config: AccountInfo<'info>,
+
+    /// The address that will pay for the rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn margin_register_stake_handler(ctx: Context<MarginRegisterStake>) -> Result<()> {
+    let pool = &ctx.accounts.stake_pool;
+    let account = &mut ctx.accounts.stake_account;
+    let voter_weight = &mut ctx.accounts.voter_weight_record;
+
+    account.owner = ctx.accounts.margin_account.key();
+    account.stake_pool = pool.key();
+    account.voter_weight_record = voter_weight.key();
+
+    voter_weight.realm = pool.governance_realm;
+    voter_weight.governing_token_mint = pool.token_mint;
+
+    account.update_voter_weight_record(voter_weight);
+
+    jet_margin::write_adapter_result(
+        &*ctx.accounts.margin_account.load()?,
+        &AdapterResult {
+            position_changes: vec![(
+                ctx.accounts.stake_collateral_mint.key(),
+                vec![PositionChange::Register(
+                    ctx.accounts.collateral_account.key(),
+                )],
+            )],
+        },
+    )?;
+
+    emit!(StakeAccountCreated {
+        stake_pool: ctx.accounts.stake_pool.key(),
+        stake_account: ctx.accounts.stake_account.key(),
+        owner: ctx.accounts.margin_account.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use jet_margin::MarginAccount;
+
+use crate::events::{Note, StakeTransferred};
+use crate::margin::{self, StakeCollateral};
+use crate::seeds;
+use crate::spl_addin::VoterWeightRecord;
+use crate::state::*;
+use crate::ErrorCode;
+
+#[derive(Accounts)]
+pub struct MarginTransferStake<'info> {
+    /// The margin account being liquidated
+    #[account(signer)]
+    pub margin_account: AccountLoader<'info, MarginAccount>,
+
+    /// The stake pool the stake is in
+    #[account(has_one = stake_collateral_mint)]
+    pub stake_pool: Box<Account<'info, StakePool>>,
+
+    /// The stake account owned by the margin account
+    #[account(mut,
+              has_one = stake_pool,
+              constraint = source_stake_account.owner == margin_account.key())]
+    pub source_stake_account: Box<Account<'info, StakeAccount>>,
+
+    /// The voter weight for the margin account's stake
+    #[account(mut,
+              address = source_stake_account.voter_weight_record)]
+    pub source_voter_weight_record: Box<Account<'info, VoterWeightRecord>>,
+
+    /// The governance record for the margin account's votes, which may not exist
+    /// CHECK: verified to be the margin account's record
+    pub token_owner_record: AccountInfo<'info>,
+
+    /// The stake account to receive the stake
+    #[account(mut, has_one = stake_pool)]
+    pub target_stake_account: Box<Account<'info, StakeAccount>>,
+
+    /// The voter weight for the receiving stake account
+    #[account(mut,
+              address = target_stake_account.voter_weight_record)]
+    pub target_voter_weight_record: Box<Account<'info, VoterWeightRecord>>,
+
+    /// The mint for the collateral tokens representing the bonded stake
+    #[account(mut)]
+    pub stake_collateral_mint: Box<Account<'info, Mint>>,
+
+    /// The token account holding the collateral tokens for the margin account's stake
+    #[account(mut,
+              seeds = [
+                  source_stake_account.key().as_ref(),
+                  seeds::COLLATERAL
+              ],
+              bump)]
+    pub collateral_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn margin_transfer_stake_handler(ctx: Context<MarginTransferStake>, shares: u64) -> Result<()> {
+    if !ctx.accounts.margin_account.load()?.is_liquidating() {
+        return err!(ErrorCode::NotLiquidating);
+    }
+
+    margin::verify_no_outstanding_votes(
+        &ctx.accounts.stake_pool,
+        &ctx.accounts.margin_account.key(),
+        &ctx.accounts.token_owner_record,
+    )?;
+
+    let source = &mut ctx.accounts.source_stake_account;
+    let target = &mut ctx.accounts.target_stake_account;
+
+    // Liquidations ignore any lockup, since the stake stays bonded
+    source.unbond(shares, 0)?;
+    target.deposit(shares);
+
+    source.update_voter_weight_record(&mut ctx.accounts.source_voter_weight_record);
+    target.update_voter_weight_record(&mut ctx.accounts.target_voter_weight_record);
+
+    StakeCollateral {
+        stake_pool: &ctx.accounts.stake_pool,
+        stake_collateral_mint: &ctx.accounts.stake_collateral_mint,
+        collateral_account: &ctx.accounts.collateral_account,
+        token_program: &ctx.accounts.token_program,
+    }
+    .sync(&ctx.accounts.source_stake_account)?;
+
+    emit!(StakeTransferred {
+        stake_pool: ctx.accounts.stake_pool.key(),
+        source_stake_account: ctx.accounts.source_stake_account.key(),
+        target_stake_account: ctx.accounts.target_stake_account.key(),
+        margin_account: ctx.accounts.margin_account.key(),
+
+        transferred_shares: shares,
+
+        source_note: ctx.accounts.source_stake_account.note(),
+        target_note: ctx.accounts.target_stake_account.note(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::margin::StakeCollateral;
+use crate::seeds;
+use crate::ErrorCode;
+
+use super::UnbondStake;
+
+#[derive(Accounts)]
+#[instruction(seed: u32)]
+pub struct MarginUnbondStake<'info> {
+    /// The accounts to unbond the stake, where the owner is the margin account
+    #[account(constraint = *unbond.owner.owner == jet_margin::ID @ ErrorCode::NotMarginOwnedStake,
+              constraint = unbond.stake_pool.stake_collateral_mint == stake_collateral_mint.key())]
+    pub unbond: UnbondStake<'info>,
+
+    /// The mint for the collateral tokens representing the bonded stake
+    #[account(mut)]
+    pub stake_collateral_mint: Box<Account<'info, Mint>>,
+
+    /// The token account holding the collateral tokens for the stake
+    #[account(mut,
+              seeds = [
+                  unbond.stake_account.key().as_ref(),
+                  seeds::COLLATERAL
+              ],
+              bump)]
+    pub collateral_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn margin_unbond_stake_handler(
+    ctx: Context<MarginUnbondStake>,
+    _seed: u32,
+    amount: Option<u64>,
+) -> Result<()> {
+    ctx.accounts.unbond.unbond(amount)?;
+
+    // The margin program updates the position balance from the collateral
+    // account after this returns, and checks the account is still healthy.
+    StakeCollateral {
+        stake_pool: &ctx.accounts.unbond.stake_pool,
+        stake_collateral_mint: &ctx.accounts.stake_collateral_mint,
+        collateral_account: &ctx.accounts.collateral_account,
+        token_program: &ctx.accounts.token_program,
+    }
+    .sync(&ctx.accounts.unbond.stake_account)?;
+
+    Ok(())
+}
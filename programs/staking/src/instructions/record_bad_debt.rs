@@ -0,0 +1,239 @@
+use std::convert::TryInto;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use pyth_sdk_solana::Price;
+
+use jet_airspace::state::Airspace;
+use jet_margin::{seeds::RISK_CONFIG_SEED, MarginAccount, RiskConfig, TokenConfig};
+use jet_margin_pool::cpi::accounts::WriteOffBadDebt;
+use jet_margin_pool::program::JetMarginPool;
+use jet_margin_pool::MarginPool;
+
+use crate::events::BadDebtRecorded;
+use crate::margin;
+use crate::seeds;
+use crate::state::*;
+use crate::ErrorCode;
+
+#[derive(Accounts)]
+pub struct RecordBadDebt<'info> {
+    /// The authority for the airspace the debt was incurred in
+    pub authority: Signer<'info>,
+
+    /// The airspace covered by the insurance fund
+    #[account(has_one = authority)]
+    pub airspace: Box<Account<'info, Airspace>>,
+
+    /// The stake pool providing the insurance
+    pub stake_pool: Box<Account<'info, StakePool>>,
+
+    /// The insurance fund to claim against
+    #[account(mut,
+              has_one = stake_pool,
+              has_one = airspace)]
+    pub insurance_fund: Box<Account<'info, InsuranceFund>>,
+
+    /// The record of the bad debt
+    #[account(init,
+              seeds = [
+                  insurance_fund.key().as_ref(),
+                  insurance_fund.claim_count.to_le_bytes().as_ref()
+              ],
+              bump,
+              payer = payer,
+              space = 8 + std::mem::size_of::<BadDebtClaim>())]
+    pub claim: Box<Account<'info, BadDebtClaim>>,
+
+    /// The insurance fund's recovery account for the margin pool, which receives
+    /// the tokens covering the debt until they're returned to the depositors
+    #[account(init_if_needed,
+              seeds = [
+                  insurance_fund.key().as_ref(),
+                  margin_pool.key().as_ref(),
+                  seeds::RECOVERY
+              ],
+              bump,
+              payer = payer,
+              token::mint = staked_token_mint,
+              token::authority = stake_pool)]
+    pub beneficiary: Box<Account<'info, TokenAccount>>,
+
+    /// The insolvent margin account, which must be in the insured airspace
+    #[account(constraint = margin_account.load()?.airspace == airspace.key() @ ErrorCode::WrongAirspace)]
+    pub margin_account: AccountLoader<'info, MarginAccount>,
+
+    /// The pool with the loan to be written off
+    #[account(mut,
+              has_one = loan_note_mint,
+              constraint = margin_pool.token_mint == pool_token_mint.key(),
+              constraint = margin_pool.token_price_oracle == pool_token_oracle.key() @ ErrorCode::InvalidOracle)]
+    pub margin_pool: Box<Account<'info, MarginPool>>,
+
+    /// The mint for the notes representing loans from the pool
+    /// CHECK: the margin pool program checks the mint
+    #[account(mut)]
+    pub loan_note_mint: AccountInfo<'info>,
+
+    /// The account with the loan notes to be written off
+    /// CHECK: the margin pool program checks the account
+    #[account(mut)]
+    pub loan_account: AccountInfo<'info>,
+
+    /// The mint for the tokens lent by the pool
+    pub pool_token_mint: Box<Account<'info, Mint>>,
+
+    /// The price oracle for the tokens lent by the pool
+    /// CHECK: the address is checked against the margin pool
+    pub pool_token_oracle: AccountInfo<'info>,
+
+    /// The mint for the staked token
+    #[account(address = stake_pool.token_mint)]
+    pub staked_token_mint: Box<Account<'info, Mint>>,
+
+    /// The margin program's config for the staked token in the airspace
+    pub staked_token_config: Box<Account<'info, TokenConfig>>,
+
+    /// The price oracle for the staked token
+    /// CHECK: the address is checked against the token config
+    pub staked_token_oracle: AccountInfo<'info>,
+
+    /// The margin program's risk parameters for the airspace, which may be
+    /// uninitialized if the airspace uses the defaults
+    /// CHECK: address is verified, and the data is checked when loaded
+    #[account(seeds = [RISK_CONFIG_SEED, airspace.key().as_ref()],
+              seeds::program = jet_margin::ID,
+              bump)]
+    pub risk_config: AccountInfo<'info>,
+
+    /// The payer for rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub margin_pool_program: Program<'info, JetMarginPool>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> RecordBadDebt<'info> {
+    fn write_off_context(&self) -> CpiContext<'_, '_, '_, 'info, WriteOffBadDebt<'info>> {
+        CpiContext::new(
+            self.margin_pool_program.to_account_info(),
+            WriteOffBadDebt {
+                margin_account: self.margin_account.to_account_info(),
+                margin_pool: self.margin_pool.to_account_info(),
+                loan_note_mint: self.loan_note_mint.to_account_info(),
+                loan_account: self.loan_account.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            },
+        )
+    }
+
+    /// Value an amount of the pool's tokens in units of the staked token
+    fn value_in_staked_tokens(&self, amount: u64) -> Result<u64> {
+        let risk = RiskConfig::load_params(&self.risk_config, &self.airspace.key())?;
+        let pool_token_price = oracle_price(&self.pool_token_oracle, risk.max_oracle_staleness)?;
+        let staked_token_price =
+            oracle_price(&self.staked_token_oracle, risk.max_oracle_staleness)?;
+
+        let quote = pool_token_price
+            .get_price_in_quote(
+                &staked_token_price,
+                -(self.staked_token_mint.decimals as i32),
+            )
+            .filter(|quote| quote.price > 0)
+            .ok_or_else(|| error!(ErrorCode::InvalidOracle))?;
+
+        let value =
+            amount as u128 * quote.price as u128 / 10u128.pow(self.pool_token_mint.decimals as u32);
+
+        Ok(value.try_into().unwrap())
+    }
+}
+
+/// Write off the loan of an insolvent margin account, and record the loss to
+/// the pool's depositors as bad debt to be covered by the insurance fund.
+///
+/// The claim can only be recorded along with the write-off, so each claim is
+/// for a loss that actually happened in the airspace, and is only made once.
+///
+/// The claim is valued in the staked token at the time it's recorded, and paid
+/// out later to the recovery account for the margin pool.
+pub fn record_bad_debt_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, RecordBadDebt<'info>>,
+) -> Result<()> {
+    let staked_token_config = &ctx.accounts.staked_token_config;
+    margin::verify_token_oracle(
+        &*ctx.accounts.margin_account.load()?,
+        &ctx.accounts.stake_pool,
+        staked_token_config,
+        &ctx.accounts.staked_token_oracle,
+    )?;
+
+    // any rewards accounts for the margin pool are passed through
+    let bad_debt = jet_margin_pool::cpi::write_off_bad_debt(
+        ctx.accounts
+            .write_off_context()
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+    )?
+    .get();
+
+    if bad_debt.depositor_loss == 0 {
+        msg!("the loan was covered by uncollected fees, so there is nothing to claim");
+        return err!(ErrorCode::InvalidAmount);
+    }
+
+    let amount = ctx
+        .accounts
+        .value_in_staked_tokens(bad_debt.depositor_loss)?;
+
+    let insurance_fund = &mut ctx.accounts.insurance_fund;
+    let claim = &mut ctx.accounts.claim;
+
+    claim.insurance_fund = insurance_fund.key();
+    claim.beneficiary = ctx.accounts.beneficiary.key();
+    claim.source = ctx.accounts.margin_pool.key();
+    claim.margin_account = ctx.accounts.margin_account.key();
+    claim.debt_mint = ctx.accounts.margin_pool.token_mint;
+    claim.debt_amount = bad_debt.depositor_loss;
+    claim.amount = amount;
+    claim.recorded_at = Clock::get()?.unix_timestamp;
+
+    insurance_fund.claim_count = insurance_fund.claim_count.checked_add(1).unwrap();
+
+    emit!(BadDebtRecorded {
+        stake_pool: insurance_fund.stake_pool,
+        insurance_fund: insurance_fund.key(),
+        claim: claim.key(),
+        beneficiary: claim.beneficiary,
+        source: claim.source,
+        margin_account: claim.margin_account,
+        debt_mint: claim.debt_mint,
+        debt_amount: claim.debt_amount,
+        amount,
+    });
+
+    Ok(())
+}
+
+/// Get the current price from a pyth oracle, which must have been updated within the
+/// airspace's staleness limit
+fn oracle_price(oracle: &AccountInfo, max_staleness: i64) -> Result<Price> {
+    let price_feed = match pyth_sdk_solana::load_price_feed_from_account_info(oracle) {
+        Ok(price_feed) => price_feed,
+        Err(e) => {
+            msg!("the oracle account is not valid: {:?}", e);
+            return err!(ErrorCode::InvalidOracle);
+        }
+    };
+
+    let clock = Clock::get()?;
+    match price_feed.get_price_no_older_than(clock.unix_timestamp, max_staleness as u64) {
+        Some(price) => Ok(price),
+        None => {
+            msg!("the oracle price is stale");
+            err!(ErrorCode::InvalidOracle)
+        }
+    }
+}
@@ -4,6 +4,7 @@ use anchor_spl::token::TokenAccount;
 use spl_governance::state::token_owner_record::TokenOwnerRecordV2;
 
 use crate::events::{Note, StakeUnbonded};
+use crate::seeds;
 use crate::spl_addin::{MaxVoterWeightRecord, VoterWeightRecord};
 use crate::state::*;
 use crate::ErrorCode;
@@ -25,6 +26,15 @@ pub struct UnbondStake<'info> {
               has_one = voter_weight_record)]
     pub stake_account: Box<Account<'info, StakeAccount>>,
 
+    /// The lockup for the stake account, which may not exist
+    /// CHECK: loaded if it has been created
+    #[account(seeds = [
+                  stake_account.key().as_ref(),
+                  seeds::LOCKUP
+              ],
+              bump)]
+    pub stake_lockup: AccountInfo<'info>,
+
     /// The stake pool to be unbonded from
     #[account(mut,
               has_one = stake_pool_vault,
@@ -84,50 +94,71 @@ impl<'info> UnbondStake<'info> {
 
         Ok(record)
     }
-}
 
-pub fn unbond_stake_handler(
-    ctx: Context<UnbondStake>,
-    _seed: u32,
-    amount: Option<u64>,
-) -> Result<()> {
-    let gov_owner_record = ctx.accounts.read_token_owner_record()?;
-    let stake_pool = &mut ctx.accounts.stake_pool;
-    let stake_account = &mut ctx.accounts.stake_account;
-    let unbonding_account = &mut ctx.accounts.unbonding_account;
-    let voter_weight = &mut ctx.accounts.voter_weight_record;
-    let max_weight = &mut ctx.accounts.max_voter_weight_record;
-    let clock = Clock::get()?;
+    /// Unbond the stake, updating the voter weights for the change
+    pub fn unbond(&mut self, amount: Option<u64>) -> Result<()> {
+        let gov_owner_record = self.read_token_owner_record()?;
+        let lockup = StakeLockup::load(&self.stake_lockup)?;
+        let stake_pool = &mut self.stake_pool;
+        let stake_account = &mut self.stake_account;
+        let unbonding_account = &mut self.unbonding_account;
+        let voter_weight = &mut self.voter_weight_record;
+        let max_weight = &mut self.max_voter_weight_record;
+        let clock = Clock::get()?;
+
+        // User can't have any outstanding votes at the time of unbond
+        gov_owner_record
+            .assert_can_withdraw_governing_tokens()
+            .map_err(|_| error!(ErrorCode::OutstandingVotes))?;
+
+        // Locked stake can't be unbonded until the lockup ends
+        if let Some(lockup) = lockup
+            .as_ref()
+            .filter(|l| l.is_locked(clock.unix_timestamp))
+        {
+            msg!("the stake is locked until {}", lockup.locked_until);
+            return err!(ErrorCode::StakeLocked);
+        }
 
-    // User can't have any outstanding votes at the time of unbond
-    gov_owner_record
-        .assert_can_withdraw_governing_tokens()
-        .map_err(|_| error!(ErrorCode::OutstandingVotes))?;
+        unbonding_account.stake_account = stake_account.key();
+        unbonding_account.unbonded_at = clock.unix_timestamp + stake_pool.unbond_period;
 
-    unbonding_account.stake_account = stake_account.key();
-    unbonding_account.unbonded_at = clock.unix_timestamp + stake_pool.unbond_period;
+        stake_pool.update_vault(self.stake_pool_vault.amount);
+        let unbonded_amount = stake_pool.unbond(stake_account, unbonding_account, amount)?;
 
-    stake_pool.update_vault(ctx.accounts.stake_pool_vault.amount);
-    let unbonded_amount = stake_pool.unbond(stake_account, unbonding_account, amount)?;
+        stake_account.update_voter_weight_record_with_lockup(voter_weight, lockup.as_ref(), &clock);
+        stake_pool.update_max_vote_weight_record(max_weight);
 
-    stake_account.update_voter_weight_record(voter_weight);
-    stake_pool.update_max_vote_weight_record(max_weight);
+        emit!(StakeUnbonded {
+            stake_pool: stake_pool.key(),
+            stake_account: stake_account.key(),
+            unbonding_account: unbonding_account.key(),
+            owner: self.owner.key(),
 
-    emit!(StakeUnbonded {
-        stake_pool: stake_pool.key(),
-        stake_account: stake_account.key(),
-        unbonding_account: unbonding_account.key(),
-        owner: ctx.accounts.owner.key(),
+            unbonded_amount,
+            unbonded_at: unbonding_account.unbonded_at,
 
-        unbonded_amount,
-        unbonded_at: unbonding_account.unbonded_at,
+            pool_note: stake_pool.note(),
+            account_note: stake_account.note(),
 
-        pool_note: stake_pool.note(),
-        account_note: stake_account.note(),
+            voter_weight: voter_weight.voter_weight,
+            max_voter_weight: max_weight.max_voter_weight,
+        });
 
-        voter_weight: voter_weight.voter_weight,
-        max_voter_weight: max_weight.max_voter_weight,
-    });
+        Ok(())
+    }
+}
+
+pub fn unbond_stake_handler(
+    ctx: Context<UnbondStake>,
+    _seed: u32,
+    amount: Option<u64>,
+) -> Result<()> {
+    // Stake used as margin collateral has to be unbonded through the margin
+    // account, so the collateral can be updated with it
+    if *ctx.accounts.owner.owner == jet_margin::ID {
+        return err!(ErrorCode::MarginOwnedStake);
+    }
 
-    Ok(())
+    ctx.accounts.unbond(amount)
 }
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::seeds;
+use crate::spl_addin::VoterWeightRecord;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct UpdateVoterWeight<'info> {
+    /// The account owning the stake
+    #[account(has_one = voter_weight_record)]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    /// The lockup for the stake account, which may not exist
+    /// CHECK: loaded if it has been created
+    #[account(seeds = [
+                  stake_account.key().as_ref(),
+                  seeds::LOCKUP
+              ],
+              bump)]
+    pub stake_lockup: AccountInfo<'info>,
+
+    /// The voter weight to be updated
+    #[account(mut)]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+}
+
+pub fn update_voter_weight_handler(ctx: Context<UpdateVoterWeight>) -> Result<()> {
+    let lockup = StakeLockup::load(&ctx.accounts.stake_lockup)?;
+
+    ctx.accounts
+        .stake_account
+        .update_voter_weight_record_with_lockup(
+            &mut ctx.accounts.voter_weight_record,
+            lockup.as_ref(),
+            &Clock::get()?,
+        );
+
+    Ok(())
+}
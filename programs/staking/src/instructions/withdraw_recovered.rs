@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::events::RecoveredWithdrawn;
+use crate::seeds;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct WithdrawRecovered<'info> {
+    /// The authority for the stake pool
+    pub authority: Signer<'info>,
+
+    /// The stake pool providing the insurance
+    #[account(has_one = authority)]
+    pub stake_pool: Account<'info, StakePool>,
+
+    /// The insurance fund that paid out the claims
+    #[account(has_one = stake_pool)]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    /// The margin pool the claims were recorded for
+    /// CHECK: only used to find the recovery account
+    pub margin_pool: AccountInfo<'info>,
+
+    /// The recovery account holding the payouts for the margin pool
+    #[account(mut,
+              seeds = [
+                  insurance_fund.key().as_ref(),
+                  margin_pool.key().as_ref(),
+                  seeds::RECOVERY
+              ],
+              bump)]
+    pub recovery_account: Account<'info, TokenAccount>,
+
+    /// The receiver for the withdrawn tokens
+    /// CHECK:
+    #[account(mut)]
+    pub token_receiver: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> WithdrawRecovered<'info> {
+    fn transfer_context(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.recovery_account.to_account_info(),
+                to: self.token_receiver.to_account_info(),
+                authority: self.stake_pool.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn withdraw_recovered_handler(ctx: Context<WithdrawRecovered>, amount: u64) -> Result<()> {
+    let stake_pool = &ctx.accounts.stake_pool;
+    token::transfer(
+        ctx.accounts
+            .transfer_context()
+            .with_signer(&[&stake_pool.signer_seeds()]),
+        amount,
+    )?;
+
+    emit!(RecoveredWithdrawn {
+        stake_pool: stake_pool.key(),
+        insurance_fund: ctx.accounts.insurance_fund.key(),
+        margin_pool: ctx.accounts.margin_pool.key(),
+        recovery_account: ctx.accounts.recovery_account.key(),
+
+        withdrawn_amount: amount,
+    });
+
+    Ok(())
+}
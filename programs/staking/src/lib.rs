@@ -7,7 +7,8 @@ declare_id!("JPLockxtkngHkaQT5AuRYow3HyUv5qWzmhwsCPd653n");
 
 pub mod events;
 mod instructions;
-mod spl_addin;
+mod margin;
+pub mod spl_addin;
 pub mod state;
 
 pub use instructions::PoolConfig;
@@ -16,12 +17,24 @@ use instructions::*;
 pub mod seeds {
     use super::constant;
 
+    #[constant]
+    pub const COLLATERAL: &[u8] = b"collateral";
+
     #[constant]
     pub const COLLATERAL_MINT: &[u8] = b"collateral-mint";
 
+    #[constant]
+    pub const INSURANCE: &[u8] = b"insurance";
+
+    #[constant]
+    pub const LOCKUP: &[u8] = b"lockup";
+
     #[constant]
     pub const MAX_VOTE_WEIGHT_RECORD: &[u8] = b"max-vote-weight-record";
 
+    #[constant]
+    pub const RECOVERY: &[u8] = b"recovery";
+
     #[constant]
     pub const VAULT: &[u8] = b"vault";
 
@@ -55,16 +68,92 @@ pub mod jet_staking {
     /// # Params
     ///
     /// * `amount` - The amount of tokens to transfer to the stake pool
+    ///
+    /// The lockup for the stake account may be passed as a remaining account,
+    /// to keep its bonus in the voter weight.
     pub fn add_stake(ctx: Context<AddStake>, amount: Option<u64>) -> Result<()> {
         instructions::add_stake_handler(ctx, amount)
     }
 
     /// Unbond stake from an account, allowing it to be withdrawn
+    ///
+    /// The `stake_lockup` account is required, even if the stake account has
+    /// never been locked up, so that locked stake can't be unbonded.
     pub fn unbond_stake(ctx: Context<UnbondStake>, seed: u32, amount: Option<u64>) -> Result<()> {
         instructions::unbond_stake_handler(ctx, seed, amount)
     }
 
+    /// Lock up the stake in an account, in exchange for extra voting power
+    ///
+    /// Locked stake can't be unbonded until the lockup ends. The voting power
+    /// bonus decays as the lockup approaches its end, so the voter weight has
+    /// to be refreshed with `update_voter_weight` before it's used.
+    ///
+    /// The bonus isn't included in the pool's max voter weight, which remains
+    /// the bonded stake, so locked up stake counts for more towards a quorum.
+    ///
+    /// # Params
+    ///
+    /// * `lockup_period` - The number of seconds from now to lock the stake for,
+    ///                     which can't end earlier than any existing lockup.
+    pub fn lock_stake(ctx: Context<LockStake>, lockup_period: i64) -> Result<()> {
+        instructions::lock_stake_handler(ctx, lockup_period)
+    }
+
+    /// Update the voter weight for an account, including any lockup bonus
+    pub fn update_voter_weight(ctx: Context<UpdateVoterWeight>) -> Result<()> {
+        instructions::update_voter_weight_handler(ctx)
+    }
+
+    /// Create a stake account owned by a margin account, registering the
+    /// bonded stake as collateral in the margin account
+    ///
+    /// The bonded shares are represented by collateral tokens, which are
+    /// valued by the margin program at the price of the staked token.
+    /// This must be invoked through the margin account with `adapter_invoke`,
+    /// and stake can then be added with `add_stake` as usual.
+    pub fn margin_register_stake(ctx: Context<MarginRegisterStake>) -> Result<()> {
+        instructions::margin_register_stake_handler(ctx)
+    }
+
+    /// Update the collateral and price for the stake owned by a margin account
+    ///
+    /// This should be invoked through the margin account with `accounting_invoke`
+    pub fn margin_refresh_stake(ctx: Context<MarginRefreshStake>) -> Result<()> {
+        instructions::margin_refresh_stake_handler(ctx)
+    }
+
+    /// Unbond stake owned by a margin account, removing it from the collateral
+    ///
+    /// This must be invoked through the margin account with `adapter_invoke`,
+    /// and the account has to remain healthy without the unbonded stake.
+    pub fn margin_unbond_stake(
+        ctx: Context<MarginUnbondStake>,
+        seed: u32,
+        amount: Option<u64>,
+    ) -> Result<()> {
+        instructions::margin_unbond_stake_handler(ctx, seed, amount)
+    }
+
+    /// Transfer bonded stake out of a margin account being liquidated
+    ///
+    /// This must be invoked through the margin account with `liquidator_invoke`.
+    /// The stake remains bonded in the receiving account, and any lockup on the
+    /// margin account's stake doesn't apply. The margin account can't have any
+    /// outstanding votes, the same as when unbonding.
+    ///
+    /// # Params
+    ///
+    /// * `shares` - The number of bonded shares to transfer, which is the same
+    ///              as the amount of collateral tokens
+    pub fn margin_transfer_stake(ctx: Context<MarginTransferStake>, shares: u64) -> Result<()> {
+        instructions::margin_transfer_stake_handler(ctx, shares)
+    }
+
     /// Cancel a previous request to unbond stake
+    ///
+    /// The lockup for the stake account may be passed as a remaining account,
+    /// to keep its bonus in the voter weight.
     pub fn cancel_unbond(ctx: Context<CancelUnbond>) -> Result<()> {
         instructions::cancel_unbond_handler(ctx)
     }
@@ -79,6 +168,63 @@ pub mod jet_staking {
         instructions::withdraw_bonded_handler(ctx, amount)
     }
 
+    /// Configure a stake pool as the insurance fund for an airspace
+    ///
+    /// # Params
+    ///
+    /// * `airspace` - The airspace whose authority can record bad debt to be covered
+    /// * `max_claim_bps` - The largest portion of the pool, in basis points, that
+    ///                     can be paid out for any single claim
+    /// * `max_epoch_bps` - The largest portion of the pool, in basis points, that
+    ///                     can be paid out for all claims in one epoch
+    pub fn configure_insurance(
+        ctx: Context<ConfigureInsurance>,
+        airspace: Pubkey,
+        max_claim_bps: u16,
+        max_epoch_bps: u16,
+    ) -> Result<()> {
+        instructions::configure_insurance_handler(ctx, airspace, max_claim_bps, max_epoch_bps)
+    }
+
+    /// Write off the loan of an insolvent margin account in the insured airspace,
+    /// and record the loss to the margin pool's depositors as a claim against
+    /// the insurance fund
+    ///
+    /// The loss is valued in the staked token using the prices from the margin
+    /// pool's oracle, and the oracle the airspace has configured for the staked
+    /// token. Any rewards accounts for the margin pool are passed as remaining
+    /// accounts.
+    ///
+    /// The claim is paid out to the insurance fund's recovery account for the
+    /// margin pool, rather than an account chosen by the airspace authority.
+    /// Since the claim is fixed in the staked token when it's recorded, the
+    /// depositors bear any change in the staked token's price until the payout
+    /// is sold for the pool's token.
+    pub fn record_bad_debt<'info>(
+        ctx: Context<'_, '_, '_, 'info, RecordBadDebt<'info>>,
+    ) -> Result<()> {
+        instructions::record_bad_debt_handler(ctx)
+    }
+
+    /// Pay out a recorded bad debt claim from the stake pool
+    ///
+    /// The payout is limited by the insurance fund's claim and epoch limits, with
+    /// any remainder recorded on the claim. It dilutes both bonded and unbonding
+    /// stake.
+    pub fn cover_bad_debt(ctx: Context<CoverBadDebt>) -> Result<()> {
+        instructions::cover_bad_debt_handler(ctx)
+    }
+
+    /// Withdraw the payouts for bad debt claims from the recovery account for a
+    /// margin pool, by the authority for the stake pool
+    ///
+    /// The authority sells the withdrawn tokens for the margin pool's token, and
+    /// returns the proceeds to the depositors that took the loss. This is the
+    /// same trust the authority has to withdraw bonded stake.
+    pub fn withdraw_recovered(ctx: Context<WithdrawRecovered>, amount: u64) -> Result<()> {
+        instructions::withdraw_recovered_handler(ctx, amount)
+    }
+
     /// Close out the stake account, return any rent
     pub fn close_stake_account(ctx: Context<CloseStakeAccount>) -> Result<()> {
         instructions::close_stake_account_handler(ctx)
@@ -99,6 +245,16 @@ mod error {
         NotYetUnbonded,
         StakeRemaining,
         InvalidAmount,
+        InvalidLockup,
+        StakeLocked,
+        MarginOwnedStake,
+        NotMarginOwnedStake,
+        InvalidOracle,
+        NotLiquidating,
+        InvalidInsuranceConfig,
+        ClaimAlreadyCovered,
+        WrongAirspace,
+        InsuranceLimitReached,
     }
 }
 
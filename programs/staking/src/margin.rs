@@ -0,0 +1,142 @@
+use std::cmp::Ordering;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount};
+
+use jet_margin::{MarginAccount, PriceChangeInfo, TokenConfig, TokenOracle};
+use spl_governance::state::token_owner_record::{
+    get_token_owner_record_address, TokenOwnerRecordV2,
+};
+
+use crate::state::*;
+use crate::ErrorCode;
+
+/// The accounts used to keep the collateral tokens for a margin owned stake
+/// account in sync with its bonded shares.
+///
+/// The collateral token account is the position registered with the margin
+/// account, so its balance is what the margin program values.
+pub struct StakeCollateral<'a, 'info> {
+    pub stake_pool: &'a Account<'info, StakePool>,
+    pub stake_collateral_mint: &'a Account<'info, Mint>,
+    pub collateral_account: &'a Account<'info, TokenAccount>,
+    pub token_program: &'a Program<'info, Token>,
+}
+
+impl<'a, 'info> StakeCollateral<'a, 'info> {
+    /// Mint or burn collateral tokens, so the balance matches the bonded shares
+    /// in the stake account
+    pub fn sync(&self, stake_account: &StakeAccount) -> Result<()> {
+        let current = self.collateral_account.amount;
+        let target = stake_account.bonded_shares;
+        let signer_seeds = self.stake_pool.signer_seeds();
+
+        match target.cmp(&current) {
+            Ordering::Greater => token::mint_to(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    MintTo {
+                        mint: self.stake_collateral_mint.to_account_info(),
+                        to: self.collateral_account.to_account_info(),
+                        authority: self.stake_pool.to_account_info(),
+                    },
+                )
+                .with_signer(&[&signer_seeds]),
+                target - current,
+            ),
+            Ordering::Less => token::burn(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    Burn {
+                        mint: self.stake_collateral_mint.to_account_info(),
+                        from: self.collateral_account.to_account_info(),
+                        authority: self.stake_pool.to_account_info(),
+                    },
+                )
+                .with_signer(&[&signer_seeds]),
+                current - target,
+            ),
+            Ordering::Equal => Ok(()),
+        }
+    }
+}
+
+/// Check that the oracle is the one configured in the margin program for the
+/// staked token, within the airspace of the margin account
+pub fn verify_token_oracle(
+    margin_account: &MarginAccount,
+    stake_pool: &StakePool,
+    token_config: &TokenConfig,
+    oracle: &AccountInfo,
+) -> Result<()> {
+    if token_config.mint != stake_pool.token_mint
+        || token_config.airspace != margin_account.airspace
+    {
+        msg!("the token config is not for the staked token in this airspace");
+        return err!(ErrorCode::InvalidOracle);
+    }
+
+    match token_config.oracle() {
+        Some(TokenOracle::Pyth { price, .. }) if price == oracle.key() => Ok(()),
+        _ => {
+            msg!("the oracle is not configured for the staked token");
+            err!(ErrorCode::InvalidOracle)
+        }
+    }
+}
+
+/// Calculate the price of a bonded share, from the price of the staked token
+pub fn bonded_share_price(stake_pool: &StakePool, oracle: &AccountInfo) -> Result<PriceChangeInfo> {
+    let token_oracle = match pyth_sdk_solana::load_price_feed_from_account_info(oracle) {
+        Ok(pf) => pf,
+        Err(e) => {
+            msg!("the oracle account is not valid: {:?}", e);
+            return err!(ErrorCode::InvalidOracle);
+        }
+    };
+
+    // The margin program checks the validity of the price
+    let price = token_oracle.get_price_unchecked();
+    let ema = token_oracle.get_ema_price_unchecked();
+
+    Ok(PriceChangeInfo {
+        publish_time: price.publish_time,
+        exponent: price.expo,
+        value: stake_pool.bonded.share_value(price.price),
+        confidence: stake_pool.bonded.share_value(price.conf as i64) as u64,
+        twap: stake_pool.bonded.share_value(ema.price),
+    })
+}
+
+/// Check that the margin account has no outstanding votes, so its stake can be
+/// moved to another account without being voted with twice
+///
+/// The margin account may never have created a token owner record, in which
+/// case it can't have voted.
+pub fn verify_no_outstanding_votes(
+    stake_pool: &StakePool,
+    margin_account: &Pubkey,
+    token_owner_record: &AccountInfo,
+) -> Result<()> {
+    let expected = get_token_owner_record_address(
+        &crate::spl_governance::ID,
+        &stake_pool.governance_realm,
+        &stake_pool.token_mint,
+        margin_account,
+    );
+
+    if *token_owner_record.key != expected {
+        return err!(ErrorCode::InvalidTokenOwnerRecord);
+    }
+
+    if token_owner_record.data_is_empty() {
+        return Ok(());
+    }
+
+    spl_governance_tools::account::get_account_data::<TokenOwnerRecordV2>(
+        &crate::spl_governance::ID,
+        token_owner_record,
+    )?
+    .assert_can_withdraw_governing_tokens()
+    .map_err(|_| error!(ErrorCode::OutstandingVotes))
+}
@@ -3,12 +3,28 @@ use anchor_lang::prelude::*;
 use serde::ser::{Serialize, SerializeStruct, Serializer};
 use std::convert::TryInto;
 
+use crate::seeds;
 use crate::spl_addin::{MaxVoterWeightRecord, VoterWeightAction, VoterWeightRecord};
 use crate::ErrorCode;
 
 const INIT_TOKEN_SCALE: u64 = 1_000_000_000;
 const INIT_SHARE_SCALE: u64 = 10_000_000_000;
 
+/// The longest period that stake can be locked up for
+pub const MAX_LOCKUP_PERIOD: i64 = 4 * 365 * 24 * 60 * 60;
+
+/// The extra voting power, as a multiple of the stake, given to stake locked
+/// up for the maximum period. The bonus decays linearly as the lockup expires.
+pub const MAX_LOCKUP_BONUS: u64 = 3;
+
+/// The largest portion of the pool, in basis points, that can be used to cover
+/// a single bad debt claim
+pub const MAX_CLAIM_BPS: u16 = 3_000;
+
+/// The largest portion of the pool, in basis points, that can be used to cover
+/// all of the bad debt claims paid out in one epoch
+pub const MAX_EPOCH_CLAIM_BPS: u16 = 5_000;
+
 /// Pool of tokens with shared ownership by all shareholders. Tokens by default are
 /// bonded and subject to airdrops (spl token transfer) or dilutions (withdraw_bonded).
 ///
@@ -151,6 +167,18 @@ impl StakePool {
         self.vault_amount = self.vault_amount.checked_sub(amount).unwrap();
     }
 
+    /// Cover some bad debt from the pool, up to the limit allowed by the insurance fund.
+    /// Both bonded and unbonding shares are diluted, the same as `withdraw_bonded`.
+    ///
+    /// Returns the amount of tokens covered, which should be transferred out of the vault.
+    pub fn cover_bad_debt(&mut self, amount: u64, max_cover: u64) -> u64 {
+        let covered = std::cmp::min(amount, max_cover);
+
+        self.withdraw_bonded(covered);
+
+        covered
+    }
+
     /// Cancel an unbonding account and restore the tokens to the bonded pool.
     /// Redeems unbonding shares and issues bonded shares.
     pub fn rebond(&mut self, account: &mut StakeAccount, record: &UnbondingAccount) -> FullAmount {
@@ -160,6 +188,13 @@ impl StakePool {
         amount
     }
 
+    /// Sets the max voter weight to the bonded stake, without any lockup bonus.
+    ///
+    /// The bonus decays over time, so a max including it would have to assume all
+    /// of the stake was locked up for the maximum period, and quorums would need
+    /// four times the votes. Instead, locked up stake counts for more than its
+    /// share of the max, and governance fits the max to the votes cast if they
+    /// exceed it.
     pub fn update_max_vote_weight_record(&self, max_record: &mut MaxVoterWeightRecord) {
         max_record.max_voter_weight = self.bonded.shares;
         max_record.max_voter_weight_expiry = None;
@@ -271,6 +306,15 @@ impl SharedTokenPool {
         self.tokens = self.tokens.checked_sub(full_amount.token_amount).unwrap();
     }
 
+    /// Converts a value for a single token into the value for a single share,
+    /// at the current exchange rate of the pool.
+    pub fn share_value(&self, token_value: i64) -> i64 {
+        let amount = self.amount();
+        let value = token_value as i128 * amount.all_tokens as i128 / amount.all_shares as i128;
+
+        value.try_into().unwrap()
+    }
+
     /// Deposit tokens without creating any shares
     pub fn donate(&mut self, tokens: u64) {
         self.tokens = self.tokens.checked_add(tokens).unwrap();
@@ -443,11 +487,33 @@ impl StakeAccount {
         self.unbonding_shares = self.unbonding_shares.checked_sub(shares).unwrap();
     }
 
+    /// Sets the voter weight to the bonded stake, without any lockup bonus.
+    ///
+    /// This weight never decreases over time, so it doesn't expire.
     pub fn update_voter_weight_record(&self, record: &mut VoterWeightRecord) {
         record.owner = self.owner;
         record.voter_weight = self.bonded_shares;
+        record.voter_weight_expiry = None;
         record.weight_action = Some(VoterWeightAction::CastVote);
     }
+
+    /// Sets the voter weight including the bonus for any lockup.
+    ///
+    /// The bonus decays over time, so the weight expires at the end of the
+    /// current slot, and has to be updated in the same transaction it's used.
+    pub fn update_voter_weight_record_with_lockup(
+        &self,
+        record: &mut VoterWeightRecord,
+        lockup: Option<&StakeLockup>,
+        clock: &Clock,
+    ) {
+        self.update_voter_weight_record(record);
+
+        if let Some(lockup) = lockup.filter(|l| l.is_locked(clock.unix_timestamp)) {
+            record.voter_weight = lockup.voting_power(self.bonded_shares, clock.unix_timestamp);
+            record.voter_weight_expiry = Some(clock.slot);
+        }
+    }
 }
 
 #[cfg(feature = "cli")]
@@ -494,6 +560,203 @@ impl Serialize for UnbondingAccount {
     }
 }
 
+/// A commitment to keep the stake in an account bonded until some time in
+/// the future, in exchange for extra voting power.
+#[account]
+#[derive(Default, Debug)]
+pub struct StakeLockup {
+    /// The stake account that's locked up
+    pub stake_account: Pubkey,
+
+    /// The time the stake can be unbonded after
+    pub locked_until: i64,
+}
+
+impl StakeLockup {
+    /// The address of the lockup for a stake account
+    pub fn address(stake_account: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[stake_account.as_ref(), seeds::LOCKUP], &crate::ID).0
+    }
+
+    /// Load the lockup for a stake account, if one has been created
+    pub fn load(info: &AccountInfo) -> Result<Option<Self>> {
+        if *info.owner != crate::ID || info.data_is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self::try_deserialize(&mut &info.data.borrow()[..])?))
+    }
+
+    /// Load the lockup for a stake account from an account that hasn't been
+    /// checked to be the lockup's address
+    pub fn load_for(stake_account: &Pubkey, info: &AccountInfo) -> Result<Option<Self>> {
+        if *info.key != Self::address(stake_account) {
+            msg!("{} is not the lockup for {}", info.key, stake_account);
+            return err!(ErrorCode::InvalidLockup);
+        }
+
+        Self::load(info)
+    }
+
+    /// Extend the lockup, which can never end earlier than previously committed
+    pub fn extend(&mut self, now: i64, lockup_period: i64) -> Result<()> {
+        if lockup_period <= 0 || lockup_period > MAX_LOCKUP_PERIOD {
+            msg!(
+                "lockup period must be at most {} seconds",
+                MAX_LOCKUP_PERIOD
+            );
+            return err!(ErrorCode::InvalidLockup);
+        }
+
+        let locked_until = now + lockup_period;
+
+        if locked_until < self.locked_until {
+            msg!("the stake is already locked until {}", self.locked_until);
+            return err!(ErrorCode::InvalidLockup);
+        }
+
+        self.locked_until = locked_until;
+        Ok(())
+    }
+
+    pub fn is_locked(&self, now: i64) -> bool {
+        self.locked_until > now
+    }
+
+    /// The voting power for some stake, with the bonus for the time remaining
+    /// in the lockup
+    pub fn voting_power(&self, shares: u64, now: i64) -> u64 {
+        let remaining = self
+            .locked_until
+            .saturating_sub(now)
+            .clamp(0, MAX_LOCKUP_PERIOD);
+        let bonus = (shares as u128 * MAX_LOCKUP_BONUS as u128 * remaining as u128)
+            / MAX_LOCKUP_PERIOD as u128;
+
+        shares.saturating_add(bonus as u64)
+    }
+}
+
+/// The configuration for a stake pool to act as an insurance backstop,
+/// covering bad debt in the margin system for an airspace.
+#[account]
+#[derive(Default, Debug)]
+pub struct InsuranceFund {
+    /// The stake pool providing the insurance
+    pub stake_pool: Pubkey,
+
+    /// The airspace whose authority can record bad debt against the fund
+    pub airspace: Pubkey,
+
+    /// The largest portion of the pool, in basis points, covered for any one claim
+    pub max_claim_bps: u16,
+
+    /// The largest portion of the pool, in basis points, covered for all the
+    /// claims paid out in one epoch
+    pub max_epoch_bps: u16,
+
+    /// The number of claims that have been recorded
+    pub claim_count: u64,
+
+    /// The total tokens paid out for all claims
+    pub total_covered: u64,
+
+    /// The epoch when a claim was last paid out
+    pub epoch: u64,
+
+    /// The tokens paid out for claims during `epoch`
+    pub epoch_covered: u64,
+}
+
+impl InsuranceFund {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_claim_bps == 0 || self.max_claim_bps > MAX_CLAIM_BPS {
+            msg!("the claim limit must be at most {} bps", MAX_CLAIM_BPS);
+            return err!(ErrorCode::InvalidInsuranceConfig);
+        }
+
+        if self.max_epoch_bps < self.max_claim_bps || self.max_epoch_bps > MAX_EPOCH_CLAIM_BPS {
+            msg!(
+                "the epoch limit must be between the claim limit and {} bps",
+                MAX_EPOCH_CLAIM_BPS
+            );
+            return err!(ErrorCode::InvalidInsuranceConfig);
+        }
+
+        Ok(())
+    }
+
+    /// The most that can be paid out for the next claim, limited by both the
+    /// per claim and the per epoch limits
+    ///
+    /// The epoch limit is a portion of the pool as it was before any claims
+    /// were paid out in the epoch.
+    pub fn claim_limit(&mut self, vault_amount: u64, epoch: u64) -> u64 {
+        if epoch != self.epoch {
+            self.epoch = epoch;
+            self.epoch_covered = 0;
+        }
+
+        let max_claim = bps_of(vault_amount, self.max_claim_bps);
+        let max_epoch = bps_of(
+            vault_amount.saturating_add(self.epoch_covered),
+            self.max_epoch_bps,
+        );
+
+        std::cmp::min(max_claim, max_epoch.saturating_sub(self.epoch_covered))
+    }
+
+    /// Record tokens paid out for a claim in the current epoch
+    pub fn record_covered(&mut self, covered: u64) {
+        self.epoch_covered = self.epoch_covered.checked_add(covered).unwrap();
+        self.total_covered = self.total_covered.checked_add(covered).unwrap();
+    }
+}
+
+fn bps_of(amount: u64, bps: u16) -> u64 {
+    (amount as u128 * bps as u128 / 10_000) as u64
+}
+
+/// A record of bad debt to be covered by an insurance fund
+#[account]
+#[derive(Default, Debug)]
+pub struct BadDebtClaim {
+    /// The insurance fund that will cover the debt
+    pub insurance_fund: Pubkey,
+
+    /// The insurance fund's recovery account for the margin pool, which receives
+    /// the tokens covering the debt
+    pub beneficiary: Pubkey,
+
+    /// The margin pool where the bad debt was written off
+    pub source: Pubkey,
+
+    /// The insolvent margin account whose loan was written off
+    pub margin_account: Pubkey,
+
+    /// The mint for the tokens lent by the margin pool
+    pub debt_mint: Pubkey,
+
+    /// The loss to depositors in the margin pool, in units of `debt_mint`
+    pub debt_amount: u64,
+
+    /// The value of the debt, in units of the staked token
+    pub amount: u64,
+
+    /// The amount of tokens that were paid out for this claim
+    pub covered: u64,
+
+    /// The amount of the claim that was left over after the payout, because
+    /// of the insurance fund's limits
+    pub uncovered: u64,
+
+    /// The time the debt was recorded
+    pub recorded_at: i64,
+
+    /// The time the claim was covered, or zero if it hasn't been yet
+    pub covered_at: i64,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -672,4 +935,95 @@ mod tests {
         assert_eq!(125, down.share_amount); // 125 has no fractions
         assert_eq!(100, down.token_amount);
     }
+
+    #[test]
+    fn lockup_bonus_decays() {
+        let mut lockup = StakeLockup::default();
+        lockup.extend(0, MAX_LOCKUP_PERIOD).unwrap();
+
+        assert_eq!(4_000, lockup.voting_power(1_000, 0));
+        assert_eq!(2_500, lockup.voting_power(1_000, MAX_LOCKUP_PERIOD / 2));
+        assert_eq!(1_000, lockup.voting_power(1_000, MAX_LOCKUP_PERIOD));
+        assert!(!lockup.is_locked(MAX_LOCKUP_PERIOD));
+    }
+
+    #[test]
+    fn lockup_cannot_be_shortened() {
+        let mut lockup = StakeLockup::default();
+        lockup.extend(0, 1_000).unwrap();
+
+        lockup.extend(100, 800).unwrap_err();
+        lockup.extend(100, 900).unwrap();
+        lockup.extend(100, MAX_LOCKUP_PERIOD + 1).unwrap_err();
+        lockup.extend(100, 0).unwrap_err();
+
+        assert_eq!(1_000, lockup.locked_until);
+    }
+
+    #[test]
+    fn share_value_follows_exchange_rate() {
+        let mut pool = StakePool::default();
+        let mut user = StakeAccount::default();
+
+        // an empty pool uses the initial scale of 10 shares per token
+        assert_eq!(100, pool.bonded.share_value(1_000));
+
+        pool.deposit(&mut user, 1_000);
+        assert_eq!(100, pool.bonded.share_value(1_000));
+
+        // rewards increase the value of each share
+        pool.update_vault(2_000);
+        assert_eq!(200, pool.bonded.share_value(1_000));
+    }
+
+    #[test]
+    fn bad_debt_cover_is_capped() {
+        let mut pool = StakePool::default();
+        let mut user_a = StakeAccount::default();
+        let mut user_b = StakeAccount::default();
+        let mut unbonding = UnbondingAccount::default();
+
+        pool.deposit(&mut user_a, 6_000);
+        pool.deposit(&mut user_b, 4_000);
+        pool.unbond(&mut user_b, &mut unbonding, None).unwrap();
+
+        assert_eq!(1_000, pool.cover_bad_debt(5_000, 1_000));
+        assert_eq!(9_000, pool.vault_amount);
+
+        // both bonded and unbonding holders are diluted
+        assert_eq!(5_400, pool.bonded.tokens);
+        assert_eq!(3_600, pool.unbonding.tokens);
+
+        assert_eq!(500, pool.cover_bad_debt(500, 1_000));
+        assert_eq!(8_500, pool.vault_amount);
+    }
+
+    #[test]
+    fn insurance_claims_are_limited_per_epoch() {
+        let mut fund = InsuranceFund {
+            max_claim_bps: 1_000,
+            max_epoch_bps: 2_500,
+            ..Default::default()
+        };
+        fund.validate().unwrap();
+
+        // only 10% of the pool for a single claim
+        assert_eq!(1_000, fund.claim_limit(10_000, 1));
+        fund.record_covered(1_000);
+        assert_eq!(900, fund.claim_limit(9_000, 1));
+        fund.record_covered(900);
+
+        // only 25% of the pool as it was at the start of the epoch
+        assert_eq!(600, fund.claim_limit(8_100, 1));
+        fund.record_covered(600);
+        assert_eq!(0, fund.claim_limit(7_500, 1));
+
+        // the next epoch starts over
+        assert_eq!(750, fund.claim_limit(7_500, 2));
+        assert_eq!(2_500, fund.total_covered);
+
+        // the epoch limit can't be below the claim limit
+        fund.max_epoch_bps = 500;
+        assert!(fund.validate().is_err());
+    }
 }
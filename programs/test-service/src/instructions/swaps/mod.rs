@@ -2,6 +2,7 @@ mod openbook_market_cancel_orders;
 mod openbook_market_create;
 mod openbook_market_make;
 mod orca_whirlpool_create_config;
+mod phoenix_market_create;
 mod saber_swap_pool_balance;
 mod saber_swap_pool_create;
 mod spl_swap_pool_balance;
@@ -12,6 +13,7 @@ pub use openbook_market_cancel_orders::*;
 pub use openbook_market_create::*;
 pub use openbook_market_make::*;
 pub use orca_whirlpool_create_config::*;
+pub use phoenix_market_create::*;
 pub use saber_swap_pool_balance::*;
 pub use saber_swap_pool_create::*;
 pub use spl_swap_pool_balance::*;
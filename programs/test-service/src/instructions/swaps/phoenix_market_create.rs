@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+use anchor_spl::token::{Mint, Token};
+use jet_program_common::programs::PHOENIX;
+
+use crate::state::TokenInfo;
+
+/// The tag of phoenix's `InitializeMarket` instruction
+const PHOENIX_INITIALIZE_MARKET: u8 = 100;
+
+/// The tag of phoenix's `ChangeMarketStatus` instruction
+const PHOENIX_CHANGE_MARKET_STATUS: u8 = 103;
+
+/// Phoenix's `MarketStatus::Active`
+const PHOENIX_MARKET_ACTIVE: u8 = 1;
+
+#[derive(AnchorDeserialize, AnchorSerialize, Debug, Clone, Eq, PartialEq)]
+pub struct PhoenixMarketCreateParams {
+    pub bids_size: u64,
+    pub asks_size: u64,
+    pub num_seats: u64,
+    pub num_quote_lots_per_quote_unit: u64,
+    pub tick_size_in_quote_lots_per_base_unit: u64,
+    pub num_base_lots_per_base_unit: u64,
+    pub taker_fee_bps: u16,
+}
+
+/// Phoenix's `InitializeParams`
+#[derive(AnchorSerialize)]
+struct InitializeParams {
+    bids_size: u64,
+    asks_size: u64,
+    num_seats: u64,
+    num_quote_lots_per_quote_unit: u64,
+    tick_size_in_quote_lots_per_base_unit: u64,
+    num_base_lots_per_base_unit: u64,
+    taker_fee_bps: u16,
+    fee_collector: Pubkey,
+    raw_base_units_per_base_unit: Option<u32>,
+}
+
+#[derive(Accounts)]
+pub struct PhoenixMarketCreate<'info> {
+    /// Pays for the vaults, and becomes the authority of the market
+    #[account(mut)]
+    payer: Signer<'info>,
+
+    mint_base: Box<Account<'info, Mint>>,
+
+    mint_quote: Box<Account<'info, Mint>>,
+
+    #[account(constraint = info_base.mint == mint_base.key())]
+    info_base: Box<Account<'info, TokenInfo>>,
+
+    #[account(constraint = info_quote.mint == mint_quote.key())]
+    info_quote: Box<Account<'info, TokenInfo>>,
+
+    /// The market, which is too large to be created here, so it must already be
+    /// allocated for phoenix
+    #[account(mut, owner = PHOENIX)]
+    market: AccountInfo<'info>,
+
+    #[account(mut)]
+    vault_base: AccountInfo<'info>,
+
+    #[account(mut)]
+    vault_quote: AccountInfo<'info>,
+
+    log_authority: AccountInfo<'info>,
+
+    #[account(address = PHOENIX)]
+    phoenix_program: AccountInfo<'info>,
+
+    token_program: Program<'info, Token>,
+    system_program: Program<'info, System>,
+}
+
+pub fn phoenix_market_create_handler(
+    ctx: Context<PhoenixMarketCreate>,
+    params: PhoenixMarketCreateParams,
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    let mut data = vec![PHOENIX_INITIALIZE_MARKET];
+    data.extend(
+        InitializeParams {
+            bids_size: params.bids_size,
+            asks_size: params.asks_size,
+            num_seats: params.num_seats,
+            num_quote_lots_per_quote_unit: params.num_quote_lots_per_quote_unit,
+            tick_size_in_quote_lots_per_base_unit: params.tick_size_in_quote_lots_per_base_unit,
+            num_base_lots_per_base_unit: params.num_base_lots_per_base_unit,
+            taker_fee_bps: params.taker_fee_bps,
+            fee_collector: accounts.payer.key(),
+            raw_base_units_per_base_unit: None,
+        }
+        .try_to_vec()?,
+    );
+
+    invoke(
+        &Instruction {
+            program_id: PHOENIX,
+            accounts: vec![
+                AccountMeta::new_readonly(PHOENIX, false),
+                AccountMeta::new_readonly(accounts.log_authority.key(), false),
+                AccountMeta::new(accounts.market.key(), false),
+                AccountMeta::new(accounts.payer.key(), true),
+                AccountMeta::new_readonly(accounts.mint_base.key(), false),
+                AccountMeta::new_readonly(accounts.mint_quote.key(), false),
+                AccountMeta::new(accounts.vault_base.key(), false),
+                AccountMeta::new(accounts.vault_quote.key(), false),
+                AccountMeta::new_readonly(accounts.system_program.key(), false),
+                AccountMeta::new_readonly(accounts.token_program.key(), false),
+            ],
+            data,
+        },
+        &[
+            accounts.phoenix_program.to_account_info(),
+            accounts.log_authority.to_account_info(),
+            accounts.market.to_account_info(),
+            accounts.payer.to_account_info(),
+            accounts.mint_base.to_account_info(),
+            accounts.mint_quote.to_account_info(),
+            accounts.vault_base.to_account_info(),
+            accounts.vault_quote.to_account_info(),
+            accounts.system_program.to_account_info(),
+            accounts.token_program.to_account_info(),
+        ],
+    )?;
+
+    // New markets only accept post-only orders, so they are activated to allow swaps
+    invoke(
+        &Instruction {
+            program_id: PHOENIX,
+            accounts: vec![
+                AccountMeta::new_readonly(PHOENIX, false),
+                AccountMeta::new_readonly(accounts.log_authority.key(), false),
+                AccountMeta::new(accounts.market.key(), false),
+                AccountMeta::new_readonly(accounts.payer.key(), true),
+            ],
+            data: vec![PHOENIX_CHANGE_MARKET_STATUS, PHOENIX_MARKET_ACTIVE],
+        },
+        &[
+            accounts.phoenix_program.to_account_info(),
+            accounts.log_authority.to_account_info(),
+            accounts.market.to_account_info(),
+            accounts.payer.to_account_info(),
+        ],
+    )?;
+
+    Ok(())
+}
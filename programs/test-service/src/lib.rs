@@ -30,7 +30,8 @@ use instructions::*;
 
 pub use instructions::{
     OpenBookMarketCancelOrdersParams, OpenBookMarketCreateParams, OpenBookMarketMakeParams,
-    SaberSwapPoolCreateParams, SplSwapPoolCreateParams, TokenCreateParams,
+    PhoenixMarketCreateParams, SaberSwapPoolCreateParams, SplSwapPoolCreateParams,
+    TokenCreateParams,
 };
 
 declare_id!("JPTSApMSqCHBww7vDhpaSmzipTV3qPg6vxub4qneKoy");
@@ -191,4 +192,12 @@ pub mod jet_test_service {
     ) -> Result<()> {
         orca_whirlpool_create_config_handler(ctx, authority, default_fee_rate)
     }
+
+    /// Create a Phoenix market, with the payer as its authority
+    pub fn phoenix_market_create(
+        ctx: Context<PhoenixMarketCreate>,
+        params: PhoenixMarketCreateParams,
+    ) -> Result<()> {
+        phoenix_market_create_handler(ctx, params)
+    }
 }
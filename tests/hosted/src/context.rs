@@ -172,7 +172,12 @@ impl TestDefault for TestContextSetupInfo {
 }
 
 impl TestContextSetupInfo {
-    fn to_config(&self, airspace_name: &str, payer: Pubkey, crank: Pubkey) -> EnvironmentConfig {
+    pub fn to_config(
+        &self,
+        airspace_name: &str,
+        payer: Pubkey,
+        crank: Pubkey,
+    ) -> EnvironmentConfig {
         let tokens = self
             .tokens
             .iter()
@@ -2,6 +2,7 @@
 //! See token.rs for token-specific administration.
 
 use anyhow::Result;
+use jet_airspace::state::PermitTier;
 use jet_client::config::JetAppConfig;
 use jet_client::NetworkKind;
 use jet_environment::builder::{
@@ -16,7 +17,7 @@ use solana_sdk::signature::{Keypair, Signature, Signer};
 
 use crate::actions::Token;
 use crate::margin::MarginUser;
-use jet_environment::config::{FixedTermMarketConfig, TokenDescription};
+use jet_environment::config::{EnvironmentConfig, FixedTermMarketConfig, TokenDescription};
 
 use jet_margin_sdk::solana::transaction::{InverseSendTransactionBuilder, TransactionBuilderExt};
 use jet_simulation::Keygen;
@@ -37,11 +38,7 @@ impl MarginTestContext {
             self.solana.rpc.payer().pubkey(),
             self.crank.pubkey(),
         );
-        let mut builder = self.env_builder();
-        configure_environment(&mut builder, &env_config)
-            .await
-            .unwrap();
-        self.execute_plan(builder.build()).await?;
+        self.apply_environment(&env_config).await?;
 
         let app_config = JetAppConfig::from_env_config(
             env_config,
@@ -54,6 +51,16 @@ impl MarginTestContext {
         Ok(app_config)
     }
 
+    /// Apply an environment config, creating or updating everything it describes
+    pub async fn apply_environment(&self, env_config: &EnvironmentConfig) -> Result<()> {
+        let mut builder = self.env_builder();
+        configure_environment(&mut builder, env_config)
+            .await
+            .unwrap();
+
+        self.execute_plan(builder.build()).await
+    }
+
     pub(super) async fn execute_plan(&self, plan: PlanInstructions) -> Result<()> {
         for setup in plan.setup {
             setup.send_and_confirm_condensed(&self.solana.rpc).await?;
@@ -93,6 +100,28 @@ impl MarginTestContext {
             .await
     }
 
+    pub async fn issue_limited_permit(
+        &self,
+        user: Pubkey,
+        expires_at: i64,
+        tier: PermitTier,
+    ) -> Result<Signature> {
+        self.airspace_ix()
+            .permit_create_limited(user, expires_at, tier)
+            .with_signer(&self.airspace_authority)
+            .send_and_confirm(&self.solana.rpc)
+            .await
+    }
+
+    pub async fn renew_permit(&self, user: Pubkey, expires_at: i64) -> Result<Signature> {
+        let issuer = self.airspace_ix().address();
+        self.airspace_ix()
+            .permit_renew(user, issuer, expires_at)
+            .with_signer(&self.airspace_authority)
+            .send_and_confirm(&self.solana.rpc)
+            .await
+    }
+
     pub async fn create_margin_user(&self, sol_amount: u64) -> Result<MarginUser> {
         let wallet = self.solana.create_wallet(sol_amount).await?;
         self.issue_permit(wallet.pubkey()).await?;
@@ -94,7 +94,8 @@ impl TestDefault for MarginPoolConfig {
             utilization_rate_2: 20,
             management_fee_rate: 10,
             flags: PoolFlags::ALLOW_LENDING.bits(),
-            reserved: 0,
+            flash_loan_fee_rate: 9,
+            reserved: [0; 6],
         }
     }
 }
@@ -5,11 +5,13 @@ pub mod fixed_term;
 pub mod load;
 pub mod margin;
 pub mod openbook;
+pub mod phoenix;
 pub mod pricing;
 pub mod runtime;
 pub mod saber_swap;
 pub mod setup_helper;
 pub mod spl_swap;
+pub mod staking;
 pub mod test_user;
 pub mod tokens;
 pub mod util;
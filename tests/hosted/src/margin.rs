@@ -384,6 +384,13 @@ impl MarginUser {
         }
     }
 
+    /// Include the permit in instructions that check it, for an account created
+    /// under a permit with an expiry or tier
+    pub fn with_permit(mut self) -> Self {
+        self.tx = self.tx.with_permit();
+        self
+    }
+
     pub fn owner(&self) -> &Pubkey {
         self.tx.owner()
     }
@@ -0,0 +1,246 @@
+use anchor_lang::AnchorSerialize;
+use anyhow::{Context, Error};
+use async_trait::async_trait;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_program;
+
+use jet_instructions::phoenix::{derive_log_authority, PhoenixMarket, PHOENIX_PROGRAM};
+use jet_instructions::test_service::{
+    derive_phoenix_market, phoenix_market_allocate, phoenix_market_create, token_register,
+    TokenCreateParams,
+};
+use jet_simulation::send_and_confirm;
+
+use crate::runtime::SolanaTestContext;
+use crate::tokens::TokenManager;
+
+/// The tag of phoenix's `PlaceLimitOrder` instruction
+const PLACE_LIMIT_ORDER: u8 = 2;
+
+/// The tag of phoenix's `ChangeSeatStatus` instruction
+const CHANGE_SEAT_STATUS: u8 = 104;
+
+/// The tag of phoenix's `RequestSeatAuthorized` instruction
+const REQUEST_SEAT_AUTHORIZED: u8 = 105;
+
+/// Phoenix's `SeatApprovalStatus::Approved`
+const SEAT_APPROVED: u8 = 1;
+
+/// Phoenix's `OrderPacket::PostOnly` variant
+const POST_ONLY: u8 = 0;
+
+/// The side of a phoenix order
+#[derive(Debug, Clone, Copy)]
+pub enum PhoenixSide {
+    Bid = 0,
+    Ask = 1,
+}
+
+/// Phoenix's `OrderPacket::PostOnly`
+#[derive(AnchorSerialize)]
+struct PostOnly {
+    side: u8,
+    price_in_ticks: u64,
+    num_base_lots: u64,
+    client_order_id: u128,
+    reject_post_only: bool,
+    use_only_deposited_funds: bool,
+    last_valid_slot: Option<u64>,
+    last_valid_unix_timestamp_in_seconds: Option<u64>,
+    fail_silently_on_insufficient_funds: bool,
+}
+
+/// A trader that can place resting orders on a phoenix market
+pub struct PhoenixMaker {
+    pub wallet: Keypair,
+    pub base_account: Pubkey,
+    pub quote_account: Pubkey,
+}
+
+#[async_trait]
+pub trait PhoenixMarketConfig: Sized {
+    /// Create an active market, with the payer as its authority.
+    ///
+    /// Requires the phoenix program, which is only loaded on localnet.
+    async fn configure(
+        ctx: &SolanaTestContext,
+        base_mint: Pubkey,
+        quote_mint: Pubkey,
+    ) -> Result<Self, Error>;
+
+    /// Create a trader with an approved seat and funded token accounts
+    async fn add_maker(
+        &self,
+        ctx: &SolanaTestContext,
+        base_amount: u64,
+        quote_amount: u64,
+    ) -> Result<PhoenixMaker, Error>;
+
+    /// Place a resting order, priced in ticks and sized in base lots
+    async fn place_post_only(
+        &self,
+        ctx: &SolanaTestContext,
+        maker: &PhoenixMaker,
+        side: PhoenixSide,
+        price_in_ticks: u64,
+        num_base_lots: u64,
+    ) -> Result<(), Error>;
+}
+
+#[async_trait]
+impl PhoenixMarketConfig for PhoenixMarket {
+    async fn configure(
+        ctx: &SolanaTestContext,
+        base_mint: Pubkey,
+        quote_mint: Pubkey,
+    ) -> Result<Self, Error> {
+        let payer = ctx.rpc.payer().pubkey();
+        let token_manager = TokenManager::new(ctx.clone());
+
+        // The test service only creates markets for tokens it knows about
+        let mut register = vec![];
+        for mint in [base_mint, quote_mint] {
+            let decimals = token_manager.get_mint(&mint).await?.decimals;
+            register.push(token_register(
+                &payer,
+                mint,
+                &TokenCreateParams {
+                    symbol: mint.to_string()[..4].to_owned(),
+                    name: mint.to_string(),
+                    decimals,
+                    authority: payer,
+                    oracle_authority: payer,
+                    max_amount: u64::MAX,
+                    source_symbol: String::new(),
+                    price_ratio: 1.0,
+                },
+            ));
+        }
+        send_and_confirm(&ctx.rpc, &register, &[]).await?;
+
+        let (allocate, market_keypair) = phoenix_market_allocate(&payer, &base_mint, &quote_mint);
+        send_and_confirm(&ctx.rpc, &[allocate], &[&market_keypair]).await?;
+        send_and_confirm(
+            &ctx.rpc,
+            &[phoenix_market_create(&payer, &base_mint, &quote_mint)],
+            &[],
+        )
+        .await?;
+
+        let market = derive_phoenix_market(&base_mint, &quote_mint);
+        let account = ctx
+            .rpc
+            .get_account(&market)
+            .await?
+            .context("phoenix market was not created")?;
+
+        PhoenixMarket::from_account_data(market, &account.data)
+            .context("phoenix market is not active")
+    }
+
+    async fn add_maker(
+        &self,
+        ctx: &SolanaTestContext,
+        base_amount: u64,
+        quote_amount: u64,
+    ) -> Result<PhoenixMaker, Error> {
+        let payer = ctx.rpc.payer().pubkey();
+        let token_manager = TokenManager::new(ctx.clone());
+        let wallet = ctx.create_wallet(1).await?;
+        let seat = derive_seat(&self.market, &wallet.pubkey());
+
+        let request_seat = Instruction {
+            program_id: PHOENIX_PROGRAM,
+            accounts: vec![
+                AccountMeta::new_readonly(PHOENIX_PROGRAM, false),
+                AccountMeta::new_readonly(derive_log_authority(), false),
+                AccountMeta::new(self.market, false),
+                AccountMeta::new_readonly(payer, true),
+                AccountMeta::new(payer, true),
+                AccountMeta::new_readonly(wallet.pubkey(), false),
+                AccountMeta::new(seat, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data: vec![REQUEST_SEAT_AUTHORIZED],
+        };
+        let approve_seat = Instruction {
+            program_id: PHOENIX_PROGRAM,
+            accounts: vec![
+                AccountMeta::new_readonly(PHOENIX_PROGRAM, false),
+                AccountMeta::new_readonly(derive_log_authority(), false),
+                AccountMeta::new(self.market, false),
+                AccountMeta::new_readonly(payer, true),
+                AccountMeta::new(seat, false),
+            ],
+            data: vec![CHANGE_SEAT_STATUS, SEAT_APPROVED],
+        };
+        send_and_confirm(&ctx.rpc, &[request_seat, approve_seat], &[]).await?;
+
+        let base_account = token_manager
+            .create_account_funded(&self.base_mint, &wallet.pubkey(), base_amount)
+            .await?;
+        let quote_account = token_manager
+            .create_account_funded(&self.quote_mint, &wallet.pubkey(), quote_amount)
+            .await?;
+
+        Ok(PhoenixMaker {
+            wallet,
+            base_account,
+            quote_account,
+        })
+    }
+
+    async fn place_post_only(
+        &self,
+        ctx: &SolanaTestContext,
+        maker: &PhoenixMaker,
+        side: PhoenixSide,
+        price_in_ticks: u64,
+        num_base_lots: u64,
+    ) -> Result<(), Error> {
+        let order = PostOnly {
+            side: side as u8,
+            price_in_ticks,
+            num_base_lots,
+            client_order_id: 0,
+            reject_post_only: true,
+            use_only_deposited_funds: false,
+            last_valid_slot: None,
+            last_valid_unix_timestamp_in_seconds: None,
+            fail_silently_on_insufficient_funds: false,
+        };
+        let mut data = vec![PLACE_LIMIT_ORDER, POST_ONLY];
+        data.extend(order.try_to_vec()?);
+
+        let place_order = Instruction {
+            program_id: PHOENIX_PROGRAM,
+            accounts: vec![
+                AccountMeta::new_readonly(PHOENIX_PROGRAM, false),
+                AccountMeta::new_readonly(derive_log_authority(), false),
+                AccountMeta::new(self.market, false),
+                AccountMeta::new_readonly(maker.wallet.pubkey(), true),
+                AccountMeta::new_readonly(derive_seat(&self.market, &maker.wallet.pubkey()), false),
+                AccountMeta::new(maker.base_account, false),
+                AccountMeta::new(maker.quote_account, false),
+                AccountMeta::new(self.base_vault, false),
+                AccountMeta::new(self.quote_vault, false),
+                AccountMeta::new_readonly(spl_token::ID, false),
+            ],
+            data,
+        };
+        send_and_confirm(&ctx.rpc, &[place_order], &[&maker.wallet]).await?;
+
+        Ok(())
+    }
+}
+
+/// The seat that allows a trader to place orders on a market
+pub fn derive_seat(market: &Pubkey, trader: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"seat", market.as_ref(), trader.as_ref()],
+        &PHOENIX_PROGRAM,
+    )
+    .0
+}
@@ -4,9 +4,13 @@ use solana_sdk::entrypoint::ProgramResult;
 use solana_sdk::native_token::LAMPORTS_PER_SOL;
 use solana_sdk::signature::Keypair;
 use solana_sdk::signer::Signer;
+use std::path::Path;
 use std::sync::Arc;
 
-use jet_simulation::{runtime::TestRuntimeRpcClient, solana_rpc_api::SolanaRpcClient};
+use jet_simulation::{
+    account_dump::load_account_dumps, runtime::TestRuntimeRpcClient,
+    solana_rpc_api::SolanaRpcClient, BankSnapshot,
+};
 use jet_static_program_registry::{orca_swap_v1, orca_swap_v2, spl_token_swap_v2};
 
 pub use jet_simulation::{DeterministicKeygen, Keygen, RandomKeygen};
@@ -78,6 +82,39 @@ impl SolanaTestContext {
 
         Ok(wallet)
     }
+
+    /// The simulated runtime, if the test is not running against a localnet
+    pub fn test_runtime(&self) -> Option<&TestRuntimeRpcClient> {
+        self.rpc.as_any().downcast_ref::<TestRuntimeRpcClient>()
+    }
+
+    /// Capture the state of all accounts in the simulated runtime
+    pub fn snapshot(&self) -> BankSnapshot {
+        self.test_runtime()
+            .expect("snapshots require the simulated runtime")
+            .snapshot()
+    }
+
+    /// Return the simulated runtime to the state captured by a snapshot
+    pub fn restore(&self, snapshot: &BankSnapshot) {
+        self.test_runtime()
+            .expect("snapshots require the simulated runtime")
+            .restore(snapshot)
+    }
+
+    /// Load accounts dumped from a cluster into the simulated runtime, such as
+    /// the margin account and pools involved in an incident.
+    pub fn load_account_dumps(&self, path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+        let rt = self
+            .test_runtime()
+            .ok_or_else(|| anyhow::anyhow!("account dumps require the simulated runtime"))?;
+
+        for (address, account) in load_account_dumps(path)? {
+            rt.set_account(&address, &account);
+        }
+
+        Ok(())
+    }
 }
 
 fn init_runtime(payer: Keypair) -> (Arc<dyn SolanaRpcClient>, Arc<dyn SolanaRpc>) {
@@ -104,6 +141,7 @@ fn simulation_runtime() -> TestRuntimeRpcClient {
         jet_airspace,
         jet_margin_pool,
         jet_margin_swap,
+        jet_staking,
         (spl_token::ID, spl_token::processor::Processor::process),
         (
             orca_swap_v1::id(),
@@ -0,0 +1,233 @@
+use std::sync::Arc;
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, AnchorSerialize};
+use anyhow::{anyhow, Result};
+use solana_sdk::account::Account;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use spl_governance::state::enums::GovernanceAccountType;
+use spl_governance::state::token_owner_record::TokenOwnerRecordV2;
+
+use jet_auth::UserAuthentication;
+use jet_instructions::margin::MarginConfigIxBuilder;
+use jet_instructions::staking::{
+    derive_stake_lockup, derive_user_authentication, StakingIxBuilder,
+};
+use jet_margin::{TokenAdmin, TokenConfigUpdate, TokenKind};
+use jet_simulation::send_and_confirm;
+use jet_staking::spl_addin::{MaxVoterWeightRecord, VoterWeightRecord};
+use jet_staking::state::{BadDebtClaim, InsuranceFund, StakeAccount, StakeLockup};
+
+use crate::context::MarginTestContext;
+
+/// A stake pool in the simulated runtime
+///
+/// The accounts from the auth and governance programs that staking depends on
+/// are written directly into the runtime, so these tests don't run on localnet.
+pub struct TestStakePool {
+    ctx: Arc<MarginTestContext>,
+    pub ix: StakingIxBuilder,
+}
+
+impl TestStakePool {
+    /// Create a pool for a new token, in a new governance realm
+    pub async fn new(ctx: &Arc<MarginTestContext>, seed: &str, unbond_period: u64) -> Result<Self> {
+        let token_mint = ctx.tokens().create_token(9, None, None).await?;
+
+        Self::with_token(ctx, seed, token_mint, unbond_period).await
+    }
+
+    /// Create a pool for an existing token, in a new governance realm
+    pub async fn with_token(
+        ctx: &Arc<MarginTestContext>,
+        seed: &str,
+        token_mint: Pubkey,
+        unbond_period: u64,
+    ) -> Result<Self> {
+        let realm = ctx.solana.generate_key().pubkey();
+        let ix = StakingIxBuilder::new(seed, token_mint, realm);
+        let payer = ctx.payer().pubkey();
+
+        send_and_confirm(
+            &ctx.rpc(),
+            &[ix.init_pool(payer, payer, unbond_period)],
+            &[],
+        )
+        .await?;
+
+        Ok(Self {
+            ctx: ctx.clone(),
+            ix,
+        })
+    }
+
+    /// Create a stake account for a new user, who is authenticated and has
+    /// no votes in the realm
+    pub async fn create_user(&self) -> Result<Keypair> {
+        let owner = self.ctx.solana.create_wallet(10).await?;
+        let mut auth = vec![];
+        UserAuthentication {
+            owner: owner.pubkey(),
+            complete: true,
+            allowed: true,
+        }
+        .try_serialize(&mut auth)?;
+
+        self.set_account(
+            &derive_user_authentication(&owner.pubkey()),
+            jet_auth::ID,
+            auth,
+        );
+        self.set_votes(&owner.pubkey(), 0)?;
+
+        send_and_confirm(
+            &self.ctx.rpc(),
+            &[self
+                .ix
+                .init_stake_account(owner.pubkey(), self.ctx.payer().pubkey())],
+            &[&owner],
+        )
+        .await?;
+
+        Ok(owner)
+    }
+
+    /// Stake newly minted tokens in the user's account
+    pub async fn add_stake(&self, owner: &Keypair, amount: u64) -> Result<()> {
+        self.add_stake_to(&owner.pubkey(), amount).await
+    }
+
+    /// Stake newly minted tokens in the stake account for any owner, such as
+    /// a margin account
+    pub async fn add_stake_to(&self, owner: &Pubkey, amount: u64) -> Result<()> {
+        let payer = self.ctx.payer().pubkey();
+        let source = self
+            .ctx
+            .tokens()
+            .create_account_funded(&self.ix.token_mint, &payer, amount)
+            .await?;
+
+        send_and_confirm(
+            &self.ctx.rpc(),
+            &[self.ix.add_stake(owner, payer, source, Some(amount))],
+            &[],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Allow margin accounts in the test airspace to use stake as collateral
+    pub async fn register_margin_collateral(&self) -> Result<()> {
+        let config_ix = MarginConfigIxBuilder::new(
+            self.ctx.airspace,
+            self.ctx.payer().pubkey(),
+            Some(self.ctx.airspace_authority.pubkey()),
+        );
+
+        send_and_confirm(
+            &self.ctx.rpc(),
+            &[
+                config_ix.configure_adapter(jet_staking::ID, true),
+                config_ix.configure_token(
+                    self.ix.collateral_mint,
+                    Some(TokenConfigUpdate {
+                        underlying_mint: self.ix.token_mint,
+                        admin: TokenAdmin::Adapter(jet_staking::ID),
+                        token_kind: TokenKind::AdapterCollateral,
+                        value_modifier: 1_00,
+                        max_staleness: 0,
+                    }),
+                ),
+            ],
+            &[&self.ctx.airspace_authority],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record the number of votes an owner has cast in the realm, which
+    /// haven't been relinquished yet
+    pub fn set_votes(&self, owner: &Pubkey, unrelinquished_votes_count: u32) -> Result<()> {
+        let record = TokenOwnerRecordV2 {
+            account_type: GovernanceAccountType::TokenOwnerRecordV2,
+            realm: self.ix.governance_realm,
+            governing_token_mint: self.ix.token_mint,
+            governing_token_owner: *owner,
+            governing_token_deposit_amount: 0,
+            unrelinquished_votes_count,
+            total_votes_count: unrelinquished_votes_count,
+            outstanding_proposal_count: 0,
+            reserved: [0; 7],
+            governance_delegate: None,
+            reserved_v2: [0; 128],
+        };
+
+        self.set_account(
+            &self.ix.token_owner_record(owner),
+            jet_staking::spl_governance::ID,
+            record.try_to_vec()?,
+        );
+
+        Ok(())
+    }
+
+    pub async fn stake_account(&self, owner: &Pubkey) -> Result<StakeAccount> {
+        self.load(&self.ix.stake_account(owner)).await
+    }
+
+    pub async fn lockup(&self, owner: &Pubkey) -> Result<StakeLockup> {
+        self.load(&derive_stake_lockup(&self.ix.stake_account(owner)))
+            .await
+    }
+
+    pub async fn voter_weight(&self, owner: &Pubkey) -> Result<u64> {
+        let stake_account = self.stake_account(owner).await?;
+        let record: VoterWeightRecord = self.load(&stake_account.voter_weight_record).await?;
+
+        Ok(record.voter_weight)
+    }
+
+    pub async fn max_voter_weight(&self) -> Result<u64> {
+        let record: MaxVoterWeightRecord = self.load(&self.ix.max_voter_weight_record).await?;
+
+        Ok(record.max_voter_weight)
+    }
+
+    pub async fn insurance_fund(&self) -> Result<InsuranceFund> {
+        self.load(&self.ix.insurance_fund()).await
+    }
+
+    pub async fn claim(&self, claim_index: u64) -> Result<BadDebtClaim> {
+        self.load(&self.ix.claim(claim_index)).await
+    }
+
+    async fn load<T: AccountDeserialize>(&self, address: &Pubkey) -> Result<T> {
+        let account = self
+            .ctx
+            .rpc()
+            .get_account(address)
+            .await?
+            .ok_or_else(|| anyhow!("account {address} does not exist"))?;
+
+        Ok(T::try_deserialize(&mut &account.data[..])?)
+    }
+
+    fn set_account(&self, address: &Pubkey, owner: Pubkey, data: Vec<u8>) {
+        self.ctx
+            .solana
+            .test_runtime()
+            .expect("staking tests require the simulated runtime")
+            .set_account(
+                address,
+                &Account {
+                    lamports: LAMPORTS_PER_SOL,
+                    data,
+                    owner,
+                    ..Account::default()
+                },
+            );
+    }
+}
@@ -187,7 +187,7 @@ impl TestUser {
             1,
         );
         if is_liquidation {
-            swap_builder.set_liquidation()?;
+            swap_builder.set_liquidation(&self.ctx.airspace)?;
         }
         swap_builder.add_swap_leg(pool, 0)?;
         swap_builder.finalize()?;
@@ -12,9 +12,12 @@ feature_groups! {
         mod load;
         mod pool_overpayment;
         mod rounding;
+        mod timelock;
         mod sanity;
         mod route_swap;
         mod swap;
+        mod permits;
+        mod risk_config;
         mod pools;
         mod client_fixed_term;
         mod whirlpool;
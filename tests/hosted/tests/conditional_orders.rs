@@ -0,0 +1,167 @@
+use anyhow::Result;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+
+use hosted_tests::{
+    margin_test_context,
+    setup_helper::{setup_token, setup_user},
+    test_user::TestUser,
+};
+use jet_instructions::margin::keeper_invoke;
+use jet_instructions::margin_pool::{derive_loan_account, MarginPoolIxBuilder};
+use jet_instructions::margin_swap::{
+    cancel_conditional_order, create_conditional_order, derive_conditional_order,
+    keeper_execute_repay,
+};
+use jet_margin_sdk::tokens::TokenPrice;
+use jet_margin_swap::{OrderAction, OrderTrigger, TriggerKind};
+use jet_simulation::{assert_custom_program_error, send_and_confirm};
+
+const ONE_USDC: u64 = 1_000_000;
+const ONE_TSOL: u64 = 1_000_000_000;
+const KEEPER_FEE: u64 = 1_000_000;
+
+/// Create an order that repays the user's TSOL loan once TSOL is worth at least $2
+async fn create_repay_order(user: &TestUser, tsol: &Pubkey) -> Result<Pubkey> {
+    let margin = &user.user;
+    let margin_account = *margin.address();
+    let oracle = user.ctx.tokens().derive_oracle(tsol).price;
+
+    send_and_confirm(
+        &user.ctx.rpc(),
+        &[margin.tx.ix.adapter_invoke(create_conditional_order(
+            margin_account,
+            margin.signer.pubkey(),
+            0,
+            oracle,
+            OrderTrigger {
+                kind: TriggerKind::Above,
+                price: 200_000_000,
+                exponent: -8,
+            },
+            OrderAction::Repay {
+                pool: MarginPoolIxBuilder::new(*tsol).address,
+            },
+            KEEPER_FEE,
+        ))],
+        &[&margin.signer],
+    )
+    .await?;
+
+    Ok(derive_conditional_order(&margin_account, 0))
+}
+
+/// A keeper can repay a loan with a conditional order once the oracle price
+/// reaches the trigger, and is paid for it
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+#[cfg_attr(feature = "localnet", ignore = "does not run on localnet")]
+async fn keeper_repays_loan_once_the_order_is_triggered() -> Result<()> {
+    let ctx = margin_test_context!();
+    let usdc = setup_token(&ctx, 6, 1_00, 4_00, 1.0).await?;
+    let tsol = setup_token(&ctx, 9, 95, 4_00, 1.0).await?;
+
+    setup_user(&ctx, vec![(tsol, 0, 1_000 * ONE_TSOL)]).await?;
+    let user = setup_user(&ctx, vec![(usdc, 0, 1_000 * ONE_USDC)]).await?;
+    let margin_account = *user.user.address();
+    user.borrow(&tsol, 10 * ONE_TSOL).await?;
+
+    let order = create_repay_order(&user, &tsol).await?;
+    let keeper = ctx.solana.create_wallet(1).await?;
+    let oracle = ctx.tokens().derive_oracle(&tsol).price;
+    let execute = keeper_invoke(
+        ctx.airspace,
+        keeper.pubkey(),
+        margin_account,
+        keeper_execute_repay(
+            ctx.airspace,
+            keeper.pubkey(),
+            margin_account,
+            order,
+            oracle,
+            tsol,
+        ),
+    );
+
+    // the price hasn't reached the trigger yet
+    let result = send_and_confirm(&ctx.rpc(), &[execute.clone()], &[&keeper]).await;
+    assert_custom_program_error(jet_margin_swap::ErrorCode::OrderNotTriggered, result);
+
+    ctx.tokens()
+        .set_price(
+            &tsol,
+            &TokenPrice {
+                exponent: -8,
+                price: 250_000_000,
+                confidence: 1_000_000,
+                twap: 250_000_000,
+            },
+        )
+        .await?;
+    user.user.refresh_all_pool_positions().await?;
+
+    let keeper_lamports = ctx
+        .rpc()
+        .get_account(&keeper.pubkey())
+        .await?
+        .unwrap()
+        .lamports;
+    send_and_confirm(&ctx.rpc(), &[execute], &[&keeper]).await?;
+
+    let loan_account = derive_loan_account(
+        &margin_account,
+        &MarginPoolIxBuilder::new(tsol).loan_note_mint,
+    );
+    assert_eq!(0, ctx.tokens().get_balance(&loan_account).await?);
+    assert!(ctx.rpc().get_account(&order).await?.is_none());
+
+    let keeper_account = ctx.rpc().get_account(&keeper.pubkey()).await?.unwrap();
+    assert!(keeper_account.lamports > keeper_lamports + KEEPER_FEE / 2);
+
+    Ok(())
+}
+
+/// Keepers can't invoke any adapter instruction other than the one that checks
+/// the owner authorized the action, such as cancelling an order to take its lamports
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+#[cfg_attr(feature = "localnet", ignore = "does not run on localnet")]
+async fn keeper_can_only_invoke_keeper_execute() -> Result<()> {
+    let ctx = margin_test_context!();
+    let usdc = setup_token(&ctx, 6, 1_00, 4_00, 1.0).await?;
+    let tsol = setup_token(&ctx, 9, 95, 4_00, 1.0).await?;
+
+    setup_user(&ctx, vec![(tsol, 0, 1_000 * ONE_TSOL)]).await?;
+    let user = setup_user(&ctx, vec![(usdc, 0, 1_000 * ONE_USDC)]).await?;
+    let margin_account = *user.user.address();
+    user.borrow(&tsol, 10 * ONE_TSOL).await?;
+
+    let order = create_repay_order(&user, &tsol).await?;
+    let keeper = ctx.solana.create_wallet(1).await?;
+
+    let cancel = keeper_invoke(
+        ctx.airspace,
+        keeper.pubkey(),
+        margin_account,
+        cancel_conditional_order(margin_account, order, keeper.pubkey()),
+    );
+    let result = send_and_confirm(&ctx.rpc(), &[cancel], &[&keeper]).await;
+
+    assert_custom_program_error(jet_margin::ErrorCode::UnauthorizedKeeperInstruction, result);
+    assert!(ctx.rpc().get_account(&order).await?.is_some());
+
+    // the owner can still cancel it
+    send_and_confirm(
+        &ctx.rpc(),
+        &[user.user.tx.ix.adapter_invoke(cancel_conditional_order(
+            margin_account,
+            order,
+            user.user.signer.pubkey(),
+        ))],
+        &[&user.user.signer],
+    )
+    .await?;
+    assert!(ctx.rpc().get_account(&order).await?.is_none());
+
+    Ok(())
+}
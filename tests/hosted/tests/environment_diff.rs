@@ -0,0 +1,164 @@
+use solana_sdk::signature::Signer;
+
+use hosted_tests::{
+    context::{MarginTestContext, TestContextSetupInfo},
+    environment::TestToken,
+    fn_name_and_try_num,
+};
+use jet_environment::{
+    config::EnvironmentConfig,
+    diff::{diff_environment, EnvironmentDiff, ResourceChange, ResourceKind},
+};
+use jet_instructions::airspace::derive_airspace;
+
+async fn setup(
+    name: &str,
+    is_restricted: bool,
+) -> anyhow::Result<(MarginTestContext, TestContextSetupInfo)> {
+    let setup = TestContextSetupInfo {
+        is_restricted,
+        tokens: vec![
+            TestToken::with_pool("TSOL").into(),
+            TestToken::with_pool("USDC").into(),
+        ],
+        dexes: vec![],
+    };
+    let ctx = MarginTestContext::new(name).await?.and_init(&setup).await?;
+
+    Ok((ctx, setup))
+}
+
+fn config_for(ctx: &MarginTestContext, setup: &TestContextSetupInfo) -> EnvironmentConfig {
+    setup.to_config(&ctx.airspace_name, ctx.payer().pubkey(), ctx.crank.pubkey())
+}
+
+async fn diff(ctx: &MarginTestContext, config: &EnvironmentConfig) -> EnvironmentDiff {
+    diff_environment(&mut ctx.env_builder(), config)
+        .await
+        .unwrap()
+}
+
+fn unmanaged(diff: &EnvironmentDiff, kind: ResourceKind) -> usize {
+    diff.resources
+        .iter()
+        .filter(|r| r.kind == kind && r.change == ResourceChange::Unmanaged)
+        .count()
+}
+
+/// An environment that was just applied from a config has no differences
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+async fn applied_config_has_no_diff() -> anyhow::Result<()> {
+    let (ctx, setup) = setup(&fn_name_and_try_num!(), false).await?;
+    let diff = diff(&ctx, &config_for(&ctx, &setup)).await;
+
+    assert!(diff.is_empty(), "{diff}");
+
+    Ok(())
+}
+
+/// Changing a configured value is reported as an update to that resource only
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+async fn changed_values_are_updates() -> anyhow::Result<()> {
+    let (ctx, setup) = setup(&fn_name_and_try_num!(), false).await?;
+    let mut config = config_for(&ctx, &setup);
+    let token = &mut config.airspaces[0].tokens[0];
+
+    token.collateral_weight += 1;
+    token.margin_pool.as_mut().unwrap().management_fee_rate += 1;
+
+    let diff = diff(&ctx, &config).await;
+    let updated = diff
+        .resources
+        .iter()
+        .filter_map(|r| match &r.change {
+            ResourceChange::Update(fields) => Some((r.kind, fields)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(diff.resources.len(), updated.len(), "{diff}");
+    assert!(updated
+        .iter()
+        .any(|(kind, fields)| *kind == ResourceKind::TokenConfig
+            && fields.iter().any(|f| f.field == "value_modifier")));
+    assert!(updated
+        .iter()
+        .any(|(kind, fields)| *kind == ResourceKind::MarginPool
+            && fields.iter().any(|f| f.field == "management_fee_rate")));
+
+    Ok(())
+}
+
+/// Resources for a token removed from the config remain on chain as unmanaged
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+async fn removed_token_is_unmanaged() -> anyhow::Result<()> {
+    let (ctx, setup) = setup(&fn_name_and_try_num!(), false).await?;
+    let mut config = config_for(&ctx, &setup);
+    config.airspaces[0].tokens.pop();
+
+    let diff = diff(&ctx, &config).await;
+
+    assert_eq!(1, unmanaged(&diff, ResourceKind::MarginPool), "{diff}");
+    assert!(unmanaged(&diff, ResourceKind::TokenConfig) > 0, "{diff}");
+    assert!(diff
+        .resources
+        .iter()
+        .all(|r| r.change == ResourceChange::Unmanaged));
+
+    Ok(())
+}
+
+/// Another airspace shows up as unmanaged, but its pools and token configs
+/// belong to that airspace and are not reported
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+async fn other_airspace_resources_are_ignored() -> anyhow::Result<()> {
+    let (ctx, setup) = setup(&fn_name_and_try_num!(), false).await?;
+    let config = config_for(&ctx, &setup);
+
+    let other_name = format!(
+        "{}.b",
+        &ctx.airspace_name[..ctx.airspace_name.len().min(22)]
+    );
+    let other = setup.to_config(&other_name, ctx.payer().pubkey(), ctx.crank.pubkey());
+    ctx.apply_environment(&other).await?;
+
+    let diff = diff(&ctx, &config).await;
+
+    assert!(diff
+        .resources
+        .iter()
+        .any(|r| r.kind == ResourceKind::Airspace
+            && r.address == derive_airspace(&other_name)
+            && r.change == ResourceChange::Unmanaged));
+    assert_eq!(0, unmanaged(&diff, ResourceKind::TokenConfig), "{diff}");
+    assert_eq!(0, unmanaged(&diff, ResourceKind::MarginPool), "{diff}");
+
+    Ok(())
+}
+
+/// Permits issued in a restricted airspace are not part of the config
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+async fn restricted_airspace_permits_are_reported() -> anyhow::Result<()> {
+    let (ctx, setup) = setup(&fn_name_and_try_num!(), true).await?;
+    let config = config_for(&ctx, &setup);
+
+    let user = ctx.create_wallet(1).await?;
+    ctx.issue_permit(user.pubkey()).await?;
+
+    let diff = diff(&ctx, &config).await;
+    let permits = diff
+        .resources
+        .iter()
+        .filter(|r| r.kind == ResourceKind::AirspacePermit)
+        .collect::<Vec<_>>();
+
+    assert_eq!(1, permits.len(), "{diff}");
+    assert!(permits[0].name.contains(&user.pubkey().to_string()));
+
+    Ok(())
+}
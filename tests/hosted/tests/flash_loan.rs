@@ -0,0 +1,156 @@
+use anyhow::Result;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+use hosted_tests::{
+    context::MarginTestContext,
+    margin_test_context,
+    setup_helper::{setup_token, setup_user},
+};
+use jet_instructions::margin_pool::MarginPoolIxBuilder;
+use jet_instructions::test_service::if_not_initialized;
+use jet_margin_pool::ErrorCode;
+use jet_simulation::{assert_custom_program_error, send_and_confirm};
+
+const ONE_USDC: u64 = 1_000_000;
+const LOAN: u64 = 100 * ONE_USDC;
+
+/// The fee set in the pool config used by the tests
+const FEE: u64 = LOAN * 9 / 10_000;
+
+struct Borrower {
+    wallet: Keypair,
+    token_account: Pubkey,
+}
+
+impl Borrower {
+    async fn new(ctx: &MarginTestContext, mint: &Pubkey) -> Result<Self> {
+        let wallet = ctx.solana.create_wallet(1).await?;
+        let token_account = ctx
+            .tokens()
+            .create_account_funded(mint, &wallet.pubkey(), 10 * ONE_USDC)
+            .await?;
+
+        Ok(Self {
+            wallet,
+            token_account,
+        })
+    }
+
+    fn begin(&self, pool: &MarginPoolIxBuilder, amount: u64) -> Instruction {
+        pool.flash_loan_begin(self.token_account, amount)
+    }
+
+    fn end(&self, pool: &MarginPoolIxBuilder, amount: u64) -> Instruction {
+        pool.flash_loan_end(self.wallet.pubkey(), self.token_account, amount)
+    }
+}
+
+/// A flash loan repaid in the same transaction pays its fee to the pool
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+#[cfg_attr(feature = "localnet", ignore = "does not run on localnet")]
+async fn flash_loan_is_repaid_with_fee() -> Result<()> {
+    let ctx = margin_test_context!();
+    let usdc = setup_token(&ctx, 6, 1_00, 4_00, 1.0).await?;
+    setup_user(&ctx, vec![(usdc, 0, 1_000 * ONE_USDC)]).await?;
+
+    let pool = MarginPoolIxBuilder::new(usdc);
+    let borrower = Borrower::new(&ctx, &usdc).await?;
+    let vault_before = ctx.tokens().get_balance(&pool.vault).await?;
+
+    send_and_confirm(
+        &ctx.rpc(),
+        &[borrower.begin(&pool, LOAN), borrower.end(&pool, LOAN)],
+        &[&borrower.wallet],
+    )
+    .await?;
+
+    assert_eq!(
+        vault_before + FEE,
+        ctx.tokens().get_balance(&pool.vault).await?
+    );
+    assert_eq!(
+        10 * ONE_USDC - FEE,
+        ctx.tokens().get_balance(&borrower.token_account).await?
+    );
+
+    Ok(())
+}
+
+/// Every flash loan has to be followed by its own repayment, for the same pool
+/// and amount
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+#[cfg_attr(feature = "localnet", ignore = "does not run on localnet")]
+async fn flash_loan_must_be_repaid() -> Result<()> {
+    let ctx = margin_test_context!();
+    let usdc = setup_token(&ctx, 6, 1_00, 4_00, 1.0).await?;
+    let usdt = setup_token(&ctx, 6, 1_00, 4_00, 1.0).await?;
+    setup_user(
+        &ctx,
+        vec![(usdc, 0, 1_000 * ONE_USDC), (usdt, 0, 1_000 * ONE_USDC)],
+    )
+    .await?;
+
+    let pool = MarginPoolIxBuilder::new(usdc);
+    let other_pool = MarginPoolIxBuilder::new(usdt);
+    let borrower = Borrower::new(&ctx, &usdc).await?;
+
+    let attempts = [
+        // never repaid
+        vec![borrower.begin(&pool, LOAN)],
+        // repaying less than the loan
+        vec![borrower.begin(&pool, LOAN), borrower.end(&pool, LOAN - 1)],
+        // repaying another pool
+        vec![borrower.begin(&pool, LOAN), borrower.end(&other_pool, LOAN)],
+        // a second loan sharing the repayment of the first
+        vec![
+            borrower.begin(&pool, LOAN),
+            borrower.begin(&pool, LOAN),
+            borrower.end(&pool, LOAN),
+        ],
+        // repaying without a loan
+        vec![borrower.end(&pool, LOAN)],
+    ];
+
+    for instructions in attempts {
+        let result = send_and_confirm(&ctx.rpc(), &instructions, &[&borrower.wallet]).await;
+        assert_custom_program_error(ErrorCode::FlashLoanNotRepaid, result);
+    }
+
+    assert_eq!(
+        10 * ONE_USDC,
+        ctx.tokens().get_balance(&borrower.token_account).await?
+    );
+
+    Ok(())
+}
+
+/// Flash loans can't be taken by another program, since the loan instructions
+/// can only find each other at the top level of the transaction
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+#[cfg_attr(feature = "localnet", ignore = "does not run on localnet")]
+async fn flash_loan_cannot_be_invoked_by_program() -> Result<()> {
+    let ctx = margin_test_context!();
+    let usdc = setup_token(&ctx, 6, 1_00, 4_00, 1.0).await?;
+    setup_user(&ctx, vec![(usdc, 0, 1_000 * ONE_USDC)]).await?;
+
+    let pool = MarginPoolIxBuilder::new(usdc);
+    let borrower = Borrower::new(&ctx, &usdc).await?;
+
+    // the test service invokes the loan, since the checked account doesn't exist
+    let invoke_begin = if_not_initialized(Pubkey::new_unique(), borrower.begin(&pool, LOAN));
+    let result = send_and_confirm(
+        &ctx.rpc(),
+        &[invoke_begin, borrower.end(&pool, LOAN)],
+        &[&borrower.wallet],
+    )
+    .await;
+
+    assert_custom_program_error(ErrorCode::FlashLoanInvokedByProgram, result);
+
+    Ok(())
+}
@@ -0,0 +1,160 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anchor_lang::AccountDeserialize;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+use hosted_tests::{
+    context::MarginTestContext,
+    margin_test_context,
+    setup_helper::{setup_user, tokens},
+    test_user::{TestUser, ONE},
+};
+use jet_airspace::state::{AirspacePermit, PermitTier};
+
+/// Accounts created under a permit with an expiry or tier are marked, so the
+/// margin program knows to check the permit later on.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+async fn limited_permit_marks_account() -> anyhow::Result<()> {
+    let ctx = margin_test_context!();
+
+    let unlimited = ctx.create_wallet(10).await?;
+    let limited = ctx.create_wallet(10).await?;
+    let tier = PermitTier {
+        lend_only: true,
+        max_borrow_value: 0,
+    };
+
+    ctx.issue_permit(unlimited.pubkey()).await?;
+    ctx.issue_limited_permit(limited.pubkey(), 0, tier).await?;
+
+    let unlimited_user = ctx.margin_client().user(&unlimited, 0).created().await?;
+    let limited_user = ctx.margin_client().user(&limited, 0).created().await?;
+
+    let unlimited_account = ctx
+        .margin_client()
+        .get_account(unlimited_user.address())
+        .await?;
+    let limited_account = ctx
+        .margin_client()
+        .get_account(limited_user.address())
+        .await?;
+
+    assert_eq!(0, unlimited_account.permit_limited);
+    assert_eq!(1, limited_account.permit_limited);
+
+    Ok(())
+}
+
+/// Permits can be extended by the issuer, but never shortened
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+async fn renew_permit() -> anyhow::Result<()> {
+    let ctx = margin_test_context!();
+
+    let wallet = ctx.create_wallet(10).await?;
+    let now = ctx.rpc().get_clock().await?.unix_timestamp;
+    let expires_at = now + 3600;
+
+    ctx.issue_limited_permit(wallet.pubkey(), expires_at, PermitTier::default())
+        .await?;
+
+    ctx.renew_permit(wallet.pubkey(), expires_at - 1)
+        .await
+        .unwrap_err();
+    ctx.renew_permit(wallet.pubkey(), expires_at + 3600).await?;
+
+    let permit_address = ctx.airspace_ix().derive_permit(&wallet.pubkey());
+    let permit = ctx.rpc().get_account(&permit_address).await?.unwrap();
+    let permit = AirspacePermit::try_deserialize(&mut &permit.data[..])?;
+
+    assert_eq!(expires_at + 3600, permit.expires_at);
+    assert!(!permit.is_expired(now));
+
+    Ok(())
+}
+
+/// Create a user whose account is limited by its permit, along with a lender so
+/// that there is liquidity to borrow.
+async fn limited_user(
+    ctx: &Arc<MarginTestContext>,
+    expires_at: i64,
+    tier: PermitTier,
+) -> anyhow::Result<(TestUser, [Pubkey; 2])> {
+    let ([usdc, tsol], _, _) = tokens(ctx).await?;
+    setup_user(ctx, vec![(usdc, 0, 1_000 * ONE)]).await?;
+
+    let wallet = ctx.create_wallet(10).await?;
+    ctx.issue_limited_permit(wallet.pubkey(), expires_at, tier)
+        .await?;
+
+    let user = TestUser {
+        ctx: ctx.clone(),
+        user: ctx
+            .margin_client()
+            .user(&wallet, 0)
+            .with_permit()
+            .created()
+            .await?,
+        mint_to_token_account: HashMap::new(),
+    };
+    user.deposit(&tsol, 1_000 * ONE).await?;
+
+    Ok((user, [usdc, tsol]))
+}
+
+/// Accounts with a lend-only permit can deposit, but not borrow
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+async fn lend_only_permit_blocks_borrow() -> anyhow::Result<()> {
+    let ctx = margin_test_context!();
+    let tier = PermitTier {
+        lend_only: true,
+        max_borrow_value: 0,
+    };
+    let (user, [usdc, _]) = limited_user(&ctx, 0, tier).await?;
+
+    user.borrow(&usdc, 10 * ONE).await.unwrap_err();
+
+    Ok(())
+}
+
+/// Accounts can't borrow once their permit has expired
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+#[cfg_attr(feature = "localnet", ignore = "does not run on localnet")]
+async fn expired_permit_is_rejected() -> anyhow::Result<()> {
+    let ctx = margin_test_context!();
+    let now = ctx.rpc().get_clock().await?.unix_timestamp;
+    let (user, [usdc, _]) = limited_user(&ctx, now + 60, PermitTier::default()).await?;
+
+    user.borrow(&usdc, 10 * ONE).await?;
+
+    let mut clock = ctx.rpc().get_clock().await?;
+    clock.unix_timestamp = now + 61;
+    ctx.rpc().set_clock(clock).await?;
+
+    user.borrow(&usdc, 10 * ONE).await.unwrap_err();
+
+    Ok(())
+}
+
+/// Borrowing is allowed up to the value limit of the permit tier, and loans
+/// can still be repaid
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+async fn max_borrow_value_is_enforced() -> anyhow::Result<()> {
+    let ctx = margin_test_context!();
+    let tier = PermitTier {
+        lend_only: false,
+        max_borrow_value: 100,
+    };
+    let (user, [usdc, _]) = limited_user(&ctx, 0, tier).await?;
+
+    user.borrow(&usdc, 50 * ONE).await?;
+    user.borrow(&usdc, 60 * ONE).await.unwrap_err();
+    user.margin_repay(&usdc, 10 * ONE).await?;
+    user.borrow(&usdc, 40 * ONE).await?;
+
+    Ok(())
+}
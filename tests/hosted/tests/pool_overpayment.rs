@@ -23,7 +23,8 @@ const DEFAULT_POOL_CONFIG: MarginPoolConfig = MarginPoolConfig {
     utilization_rate_2: 20,
     management_fee_rate: 10,
     flags: PoolFlags::ALLOW_LENDING.bits(),
-    reserved: 0,
+    flash_loan_fee_rate: 0,
+    reserved: [0; 6],
 };
 
 struct TestEnv {
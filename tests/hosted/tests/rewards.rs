@@ -0,0 +1,230 @@
+use anchor_lang::AccountDeserialize;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::Signer};
+
+use hosted_tests::{
+    context::MarginTestContext,
+    margin_test_context,
+    setup_helper::{setup_user, tokens},
+    test_user::ONE,
+};
+use jet_instructions::margin_pool::{
+    derive_loan_account, derive_pool_rewards, derive_rewards_position, MarginPoolIxBuilder,
+};
+use jet_margin_pool::{PoolRewards, RewardsConfig, RewardsPosition, TokenChange};
+
+const LOAN_RATE: u64 = 1_000;
+
+/// Send an administrative instruction, signed by the payer in place of the governor
+async fn send_as_admin(ctx: &MarginTestContext, mut ix: Instruction) -> anyhow::Result<()> {
+    ix.accounts[0].pubkey = ctx.payer().pubkey();
+    jet_simulation::send_and_confirm(&ctx.rpc(), &[ix], &[]).await?;
+
+    Ok(())
+}
+
+async fn unclaimed(
+    ctx: &MarginTestContext,
+    pool: &MarginPoolIxBuilder,
+    margin_account: &Pubkey,
+) -> anyhow::Result<u64> {
+    let address = derive_rewards_position(&derive_pool_rewards(&pool.address), margin_account);
+    let account = ctx.rpc().get_account(&address).await?.unwrap();
+    let position = RewardsPosition::try_deserialize(&mut &account.data[..])?;
+
+    Ok(position.unclaimed().as_u64(0))
+}
+
+async fn accrued_until(ctx: &MarginTestContext, pool: &MarginPoolIxBuilder) -> anyhow::Result<u64> {
+    let address = derive_pool_rewards(&pool.address);
+    let account = ctx.rpc().get_account(&address).await?.unwrap();
+    let rewards = PoolRewards::try_deserialize(&mut &account.data[..])?;
+
+    Ok(rewards.accrued_until as u64)
+}
+
+async fn warp(ctx: &MarginTestContext, seconds: i64) -> anyhow::Result<()> {
+    let mut clock = ctx.rpc().get_clock().await?;
+    clock.unix_timestamp += seconds;
+    ctx.rpc().set_clock(clock).await?;
+
+    Ok(())
+}
+
+/// Rewards on a loan moved between two margin accounts are earned by each
+/// account only while it holds the loan, so they can't be claimed twice
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+#[cfg_attr(feature = "localnet", ignore = "does not run on localnet")]
+async fn transferred_loan_earns_rewards_once() -> anyhow::Result<()> {
+    let ctx = margin_test_context!();
+    let ([usdc, tsol], _, _) = tokens(&ctx).await?;
+    let pool = MarginPoolIxBuilder::new(usdc);
+
+    setup_user(&ctx, vec![(usdc, 0, 10_000 * ONE)]).await?;
+    let alice = setup_user(&ctx, vec![(tsol, 0, 1_000 * ONE)]).await?;
+    let bob = setup_user(&ctx, vec![(tsol, 0, 1_000 * ONE)]).await?;
+
+    let reward_mint = ctx.tokens().create_token(9, None, None).await?;
+    let config = RewardsConfig {
+        deposit_rate: 0,
+        loan_rate: LOAN_RATE,
+        end_at: i64::MAX,
+    };
+    send_as_admin(
+        &ctx,
+        pool.configure_rewards(ctx.payer().pubkey(), reward_mint, config),
+    )
+    .await?;
+
+    // the rewards accounts are passed along by the sdk, so borrowing still
+    // works before the positions are registered
+    alice.borrow(&usdc, 100 * ONE).await?;
+    bob.borrow(&usdc, 100 * ONE).await?;
+
+    for user in [&alice, &bob] {
+        jet_simulation::send_and_confirm(
+            &ctx.rpc(),
+            &[pool.register_rewards_position(*user.user.address(), ctx.payer().pubkey())],
+            &[],
+        )
+        .await?;
+    }
+
+    let registered_at = accrued_until(&ctx, &pool).await?;
+
+    // both hold half of the loans, then alice's loan is moved to bob
+    warp(&ctx, 100).await?;
+    let alice_loan = ctx
+        .tokens()
+        .get_balance(&derive_loan_account(
+            alice.user.address(),
+            &pool.loan_note_mint,
+        ))
+        .await?;
+    send_as_admin(
+        &ctx,
+        pool.admin_transfer_loan(alice.user.address(), bob.user.address(), alice_loan),
+    )
+    .await?;
+    let transferred_at = accrued_until(&ctx, &pool).await?;
+
+    // bob holds all of the loans, and a transfer back checkpoints both again
+    warp(&ctx, 100).await?;
+    send_as_admin(
+        &ctx,
+        pool.admin_transfer_loan(bob.user.address(), alice.user.address(), 1),
+    )
+    .await?;
+
+    let checked_at = accrued_until(&ctx, &pool).await?;
+
+    let shared = LOAN_RATE * (transferred_at - registered_at) / 2;
+    let held_by_bob = LOAN_RATE * (checked_at - transferred_at);
+    let alice_rewards = unclaimed(&ctx, &pool, alice.user.address()).await?;
+    let bob_rewards = unclaimed(&ctx, &pool, bob.user.address()).await?;
+
+    assert!(held_by_bob > 0);
+    assert!(alice_rewards.abs_diff(shared) <= 1, "{alice_rewards}");
+    assert!(
+        bob_rewards.abs_diff(shared + held_by_bob) <= 1,
+        "{bob_rewards}"
+    );
+    assert!(alice_rewards + bob_rewards <= LOAN_RATE * (checked_at - registered_at));
+
+    Ok(())
+}
+
+/// Once a pool has rewards, its notes can't be moved without the rewards accounts
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+async fn rewards_accounts_are_required() -> anyhow::Result<()> {
+    let ctx = margin_test_context!();
+    let ([usdc, tsol], _, _) = tokens(&ctx).await?;
+    let pool = MarginPoolIxBuilder::new(usdc);
+
+    setup_user(&ctx, vec![(usdc, 0, 10_000 * ONE)]).await?;
+    let alice = setup_user(&ctx, vec![(tsol, 0, 1_000 * ONE)]).await?;
+    let bob = setup_user(&ctx, vec![(tsol, 0, 1_000 * ONE)]).await?;
+
+    alice.borrow(&usdc, 100 * ONE).await?;
+    bob.borrow(&usdc, 100 * ONE).await?;
+
+    let reward_mint = ctx.tokens().create_token(9, None, None).await?;
+    let config = RewardsConfig {
+        deposit_rate: 0,
+        loan_rate: LOAN_RATE,
+        end_at: i64::MAX,
+    };
+    send_as_admin(
+        &ctx,
+        pool.configure_rewards(ctx.payer().pubkey(), reward_mint, config),
+    )
+    .await?;
+
+    let mut transfer = pool.admin_transfer_loan(alice.user.address(), bob.user.address(), 1);
+    let with_rewards = transfer.clone();
+
+    // without the rewards accounts
+    transfer.accounts.truncate(5);
+    send_as_admin(&ctx, transfer).await.unwrap_err();
+
+    // with a position that isn't the one for the margin account
+    let mut wrong_position = with_rewards.clone();
+    wrong_position.accounts.swap(6, 7);
+    send_as_admin(&ctx, wrong_position).await.unwrap_err();
+
+    send_as_admin(&ctx, with_rewards).await?;
+
+    Ok(())
+}
+
+/// Swaps into and out of a pool with rewards pass the rewards accounts on, so
+/// the deposits keep earning rewards
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+#[cfg_attr(feature = "localnet", ignore = "does not run on localnet")]
+async fn swap_through_pool_with_rewards() -> anyhow::Result<()> {
+    const DEPOSIT_RATE: u64 = 1_000;
+
+    let ctx = margin_test_context!();
+    let ([usdc, tsol], swaps, _) = tokens(&ctx).await?;
+    let pool = MarginPoolIxBuilder::new(usdc);
+
+    let alice = setup_user(&ctx, vec![(usdc, 0, 100 * ONE), (tsol, 0, 100 * ONE)]).await?;
+
+    let reward_mint = ctx.tokens().create_token(9, None, None).await?;
+    let config = RewardsConfig {
+        deposit_rate: DEPOSIT_RATE,
+        loan_rate: 0,
+        end_at: i64::MAX,
+    };
+    send_as_admin(
+        &ctx,
+        pool.configure_rewards(ctx.payer().pubkey(), reward_mint, config),
+    )
+    .await?;
+    jet_simulation::send_and_confirm(
+        &ctx.rpc(),
+        &[pool.register_rewards_position(*alice.user.address(), ctx.payer().pubkey())],
+        &[],
+    )
+    .await?;
+
+    // the pool with rewards is the destination
+    warp(&ctx, 100).await?;
+    alice
+        .swap(&swaps, &tsol, &usdc, TokenChange::shift(ONE), false)
+        .await?;
+    let into_pool = unclaimed(&ctx, &pool, alice.user.address()).await?;
+    assert!(into_pool > 0);
+
+    // the pool with rewards is the source
+    warp(&ctx, 100).await?;
+    alice
+        .swap(&swaps, &usdc, &tsol, TokenChange::shift(ONE), false)
+        .await?;
+    let out_of_pool = unclaimed(&ctx, &pool, alice.user.address()).await?;
+    assert!(out_of_pool > into_pool, "{into_pool} {out_of_pool}");
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use anchor_lang::AccountDeserialize;
+use solana_sdk::signature::Signer;
+
+use hosted_tests::margin_test_context;
+use jet_margin::{RiskConfig, RiskParameters};
+use jet_margin_sdk::solana::transaction::{InverseSendTransactionBuilder, TransactionBuilderExt};
+use jet_solana_client::transaction::WithSigner;
+
+/// The airspace authority can override the default risk parameters, which are
+/// then applied to the margin accounts in the airspace.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+async fn airspace_risk_parameters() -> anyhow::Result<()> {
+    let ctx = margin_test_context!();
+    let config_ix = ctx.margin_config_ix();
+    let risk_config = config_ix.derive_risk_config();
+
+    // invalid parameters are rejected
+    config_ix
+        .configure_risk(Some(RiskParameters {
+            max_user_positions: 0,
+            ..Default::default()
+        }))
+        .with_signer(&ctx.airspace_authority)
+        .send_and_confirm(&ctx.rpc())
+        .await
+        .unwrap_err();
+
+    let params = RiskParameters {
+        max_user_positions: 1,
+        ..Default::default()
+    };
+    config_ix
+        .configure_risk(Some(params))
+        .with_signer(&ctx.airspace_authority)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+
+    let account = ctx.rpc().get_account(&risk_config).await?.unwrap();
+    let config = RiskConfig::try_deserialize(&mut &account.data[..])?;
+    assert_eq!(ctx.airspace, config.airspace);
+    assert_eq!(params, config.params);
+
+    // the position limit for the airspace is enforced
+    let usdc = ctx.basic_token(1.0).await?.0;
+    let tsol = ctx.basic_token(10.0).await?.0;
+    let btc = ctx.basic_token(100.0).await?.0;
+    let user = ctx.create_margin_user(10).await?;
+
+    ctx.register_deposit_position(usdc.mint, user.auth())
+        .send_and_confirm_condensed_in_order(&ctx.rpc())
+        .await?;
+    ctx.register_deposit_position(tsol.mint, user.auth())
+        .send_and_confirm_condensed_in_order(&ctx.rpc())
+        .await
+        .unwrap_err();
+
+    // the config is an optional last account, and the defaults apply without it
+    let mut register = ctx.register_deposit_position(btc.mint, user.auth());
+    let register_ix = register
+        .last_mut()
+        .unwrap()
+        .instructions
+        .last_mut()
+        .unwrap();
+    assert_eq!(risk_config, register_ix.accounts.pop().unwrap().pubkey);
+    register
+        .send_and_confirm_condensed_in_order(&ctx.rpc())
+        .await?;
+
+    // reverting to the defaults closes the config
+    config_ix
+        .configure_risk(None)
+        .with_signer(&ctx.airspace_authority)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+    assert!(ctx.rpc().get_account(&risk_config).await?.is_none());
+
+    // the address of the config can still be given once it's closed
+    ctx.register_deposit_position(tsol.mint, user.auth())
+        .send_and_confirm_condensed_in_order(&ctx.rpc())
+        .await?;
+
+    Ok(())
+}
@@ -8,7 +8,12 @@ use jet_margin_sdk::{
     ix_builder::{MarginPoolIxBuilder, MarginSwapRouteIxBuilder, SwapAccounts, SwapContext},
     lookup_tables::LookupTable,
     refresh::position_refresher::PositionRefresher,
-    swap::{openbook_swap::OpenBookMarket, saber_swap::SaberSwapPool, spl_swap::SplSwapPool},
+    swap::{
+        openbook_swap::OpenBookMarket,
+        phoenix::{self, PhoenixMarket},
+        saber_swap::SaberSwapPool,
+        spl_swap::SplSwapPool,
+    },
     tokens::TokenPrice,
     tx_builder::TokenDepositsConfig,
 };
@@ -22,6 +27,7 @@ use hosted_tests::{
     context::MarginTestContext,
     margin::{MarginPoolSetupInfo, MarginUser},
     margin_test_context,
+    phoenix::{PhoenixMarketConfig, PhoenixSide},
     saber_swap::SaberSwapPoolConfig,
     spl_swap::SwapPoolConfig,
 };
@@ -43,7 +49,8 @@ const DEFAULT_POOL_CONFIG: MarginPoolConfig = MarginPoolConfig {
     utilization_rate_2: 20,
     management_fee_rate: 10,
     flags: PoolFlags::ALLOW_LENDING.bits(),
-    reserved: 0,
+    flash_loan_fee_rate: 0,
+    reserved: [0; 6],
 };
 
 struct TestEnv {
@@ -656,3 +663,39 @@ async fn route_saber_swap() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+// Phoenix is not linked into the simulation, so the market only exists on a
+// localnet that loads deps/phoenix.so.
+#[cfg_attr(not(feature = "localnet"), ignore = "phoenix only runs on localnet")]
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+async fn route_phoenix_swap() -> anyhow::Result<()> {
+    let ctx = margin_test_context!();
+    let env = setup_environment(&ctx).await?;
+
+    let market = PhoenixMarket::configure(&ctx.solana, env.msol, env.tsol).await?;
+
+    // Ticks are 0.001 TSOL per MSOL, and base lots are 0.001 MSOL
+    let maker = market
+        .add_maker(&ctx.solana, 20 * ONE_MSOL, 20 * ONE_TSOL)
+        .await?;
+    market
+        .place_post_only(&ctx.solana, &maker, PhoenixSide::Bid, 1_050, 10_000)
+        .await?;
+    market
+        .place_post_only(&ctx.solana, &maker, PhoenixSide::Ask, 1_070, 10_000)
+        .await?;
+
+    // Check that the market can be found
+    let mut supported_mints = HashSet::new();
+    supported_mints.insert(env.msol);
+    supported_mints.insert(env.tsol);
+
+    let markets = phoenix::get_markets(&ctx.rpc(), &supported_mints).await?;
+    assert_eq!(markets.len(), 1);
+    assert_eq!(markets[&(env.msol, env.tsol)].market, market.market);
+
+    single_leg_swap(&ctx, &env, market).await?;
+
+    Ok(())
+}
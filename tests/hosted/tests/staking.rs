@@ -0,0 +1,369 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+
+use hosted_tests::{
+    context::MarginTestContext,
+    margin_test_context,
+    setup_helper::{setup_token, setup_user},
+    staking::TestStakePool,
+    test_user::{TestLiquidator, TestUser, ONE},
+};
+use jet_instructions::margin_pool::MarginPoolIxBuilder;
+use jet_instructions::staking::derive_stake_collateral_account;
+use jet_margin_pool::TokenChange;
+use jet_margin_sdk::tokens::TokenPrice;
+use jet_simulation::send_and_confirm;
+use jet_staking::state::{MAX_LOCKUP_BONUS, MAX_LOCKUP_PERIOD};
+
+const ONE_USDC: u64 = 1_000_000;
+
+/// Stake locked up for the longest period votes with the largest bonus, which
+/// isn't included in the max voter weight, and adding stake keeps the bonus
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+#[cfg_attr(feature = "localnet", ignore = "does not run on localnet")]
+async fn locked_stake_votes_with_bonus() -> Result<()> {
+    let ctx = margin_test_context!();
+    let pool = TestStakePool::new(&ctx, "locked-stake-bonus", 0).await?;
+    let user = pool.create_user().await?;
+    let owner = user.pubkey();
+
+    pool.add_stake(&user, 1_000 * ONE).await?;
+    send_and_confirm(
+        &ctx.rpc(),
+        &[pool
+            .ix
+            .lock_stake(owner, ctx.payer().pubkey(), MAX_LOCKUP_PERIOD)],
+        &[&user],
+    )
+    .await?;
+
+    let shares = pool.stake_account(&owner).await?.bonded_shares;
+    let max_voter_weight = pool.max_voter_weight().await?;
+
+    assert_eq!(shares, max_voter_weight);
+    assert_eq!(
+        shares * (1 + MAX_LOCKUP_BONUS),
+        pool.voter_weight(&owner).await?
+    );
+
+    pool.add_stake(&user, 1_000 * ONE).await?;
+
+    let shares = pool.stake_account(&owner).await?.bonded_shares;
+    let now = ctx.rpc().get_clock().await?.unix_timestamp;
+    let expected = pool.lockup(&owner).await?.voting_power(shares, now);
+
+    assert!(expected > shares);
+    assert_eq!(expected, pool.voter_weight(&owner).await?);
+    assert_eq!(shares, pool.max_voter_weight().await?);
+
+    // the lockup passed in has to be the one for the stake account
+    let source = ctx
+        .tokens()
+        .create_account_funded(&pool.ix.token_mint, &owner, ONE)
+        .await?;
+    let mut add_stake = pool.ix.add_stake(&owner, owner, source, Some(ONE));
+    add_stake.accounts.last_mut().unwrap().pubkey = Pubkey::new_unique();
+
+    send_and_confirm(&ctx.rpc(), &[add_stake], &[&user])
+        .await
+        .unwrap_err();
+
+    Ok(())
+}
+
+/// Locked stake can't be unbonded until the lockup ends
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+#[cfg_attr(feature = "localnet", ignore = "does not run on localnet")]
+async fn locked_stake_cannot_be_unbonded() -> Result<()> {
+    let ctx = margin_test_context!();
+    let pool = TestStakePool::new(&ctx, "locked-stake-unbond", 0).await?;
+    let user = pool.create_user().await?;
+    let owner = user.pubkey();
+    let payer = ctx.payer().pubkey();
+
+    pool.add_stake(&user, 1_000 * ONE).await?;
+    send_and_confirm(
+        &ctx.rpc(),
+        &[pool.ix.lock_stake(owner, payer, 1_000)],
+        &[&user],
+    )
+    .await?;
+
+    let unbond = pool.ix.unbond_stake(owner, payer, 0, None);
+
+    send_and_confirm(&ctx.rpc(), &[unbond.clone()], &[&user])
+        .await
+        .unwrap_err();
+
+    let mut clock = ctx.rpc().get_clock().await?;
+    clock.unix_timestamp += 1_000;
+    ctx.rpc().set_clock(clock).await?;
+
+    send_and_confirm(&ctx.rpc(), &[unbond], &[&user]).await?;
+
+    assert_eq!(0, pool.stake_account(&owner).await?.bonded_shares);
+    assert_eq!(0, pool.voter_weight(&owner).await?);
+    assert_eq!(0, pool.max_voter_weight().await?);
+
+    Ok(())
+}
+
+/// Stake owned by a margin account is valued as collateral, and can be moved
+/// out by a liquidator once the margin account has no outstanding votes
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+#[cfg_attr(feature = "localnet", ignore = "does not run on localnet")]
+async fn margin_stake_is_liquidated_without_outstanding_votes() -> Result<()> {
+    let ctx = margin_test_context!();
+    let usdc = setup_token(&ctx, 6, 1_00, 4_00, 1.0).await?;
+    let staked = setup_token(&ctx, 9, 1_00, 4_00, 1.0).await?;
+    let pool = TestStakePool::with_token(&ctx, "margin-stake", staked, 0).await?;
+    pool.register_margin_collateral().await?;
+
+    setup_user(&ctx, vec![(usdc, 0, 10_000 * ONE_USDC)]).await?;
+    let user = setup_user(&ctx, vec![]).await?;
+    let margin = &user.user;
+    let margin_account = *margin.address();
+    let oracle = ctx.tokens().derive_oracle(&staked).price;
+    let collateral_account =
+        derive_stake_collateral_account(&pool.ix.stake_account(&margin_account));
+
+    send_and_confirm(
+        &ctx.rpc(),
+        &[margin.tx.ix.adapter_invoke(pool.ix.margin_register_stake(
+            &ctx.airspace,
+            margin_account,
+            margin.signer.pubkey(),
+        ))],
+        &[&margin.signer],
+    )
+    .await?;
+    pool.add_stake_to(&margin_account, 1_000 * ONE).await?;
+
+    let refresh = margin.tx.ix.accounting_invoke(pool.ix.margin_refresh_stake(
+        &ctx.airspace,
+        margin_account,
+        oracle,
+    ));
+    send_and_confirm(&ctx.rpc(), &[refresh.clone()], &[]).await?;
+
+    let shares = pool.stake_account(&margin_account).await?.bonded_shares;
+    assert_eq!(shares, ctx.tokens().get_balance(&collateral_account).await?);
+
+    // borrow against the stake, then the staked token loses most of its value
+    user.borrow_to_wallet(&usdc, 500 * ONE_USDC).await?;
+    ctx.tokens()
+        .set_price(
+            &staked,
+            &TokenPrice {
+                exponent: -8,
+                price: 10_000_000,
+                confidence: 100_000,
+                twap: 10_000_000,
+            },
+        )
+        .await?;
+    send_and_confirm(&ctx.rpc(), &[refresh], &[]).await?;
+
+    let liquidator = TestLiquidator::new(&ctx).await?;
+    let liquidation = liquidator.begin(margin, true).await?;
+    let receiver = pool.create_user().await?;
+    let transferred = shares / 200;
+    let transfer = liquidation
+        .user
+        .tx
+        .ix
+        .liquidator_invoke(pool.ix.margin_transfer_stake(
+            margin_account,
+            &receiver.pubkey(),
+            transferred,
+        ));
+
+    // the stake can't be voted with again after it's moved
+    pool.set_votes(&margin_account, 1)?;
+    send_and_confirm(&ctx.rpc(), &[transfer.clone()], &[&liquidation.user.signer])
+        .await
+        .unwrap_err();
+
+    pool.set_votes(&margin_account, 0)?;
+    send_and_confirm(&ctx.rpc(), &[transfer], &[&liquidation.user.signer]).await?;
+
+    assert_eq!(
+        transferred,
+        pool.stake_account(&receiver.pubkey()).await?.bonded_shares
+    );
+    assert_eq!(
+        shares - transferred,
+        ctx.tokens().get_balance(&collateral_account).await?
+    );
+
+    Ok(())
+}
+
+/// Liquidate all of the collateral from a margin account, after its value
+/// collapsed, so its loans can only be written off
+async fn lose_collateral(
+    ctx: &Arc<MarginTestContext>,
+    user: &TestUser,
+    tsol: Pubkey,
+) -> Result<()> {
+    let liquidation = TestLiquidator::new(ctx)
+        .await?
+        .begin(&user.user, true)
+        .await?;
+    let destination = ctx
+        .tokens()
+        .create_account(&tsol, &ctx.payer().pubkey())
+        .await?;
+
+    liquidation
+        .user
+        .withdraw(&tsol, &destination, TokenChange::set(0))
+        .await?;
+
+    Ok(())
+}
+
+/// Bad debt is claimed against the insurance fund when a loan is written off,
+/// and the payouts are limited for each claim and for each epoch
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+#[cfg_attr(feature = "localnet", ignore = "does not run on localnet")]
+async fn insurance_covers_written_off_debt_up_to_limits() -> Result<()> {
+    let ctx = margin_test_context!();
+    let usdc = setup_token(&ctx, 6, 1_00, 4_00, 1.0).await?;
+    let tsol = setup_token(&ctx, 9, 95, 4_00, 100.0).await?;
+    let staked = setup_token(&ctx, 9, 1_00, 4_00, 1.0).await?;
+    let usdc_pool = MarginPoolIxBuilder::new(usdc);
+    let usdc_oracle = ctx.tokens().derive_oracle(&usdc).price;
+    let staked_oracle = ctx.tokens().derive_oracle(&staked).price;
+    let payer = ctx.payer().pubkey();
+
+    let pool = TestStakePool::with_token(&ctx, "insurance", staked, 0).await?;
+    let staker = pool.create_user().await?;
+    pool.add_stake(&staker, 4_000 * ONE).await?;
+
+    // 10% of the pool for each claim, and 10% of the pool each epoch
+    send_and_confirm(
+        &ctx.rpc(),
+        &[pool
+            .ix
+            .configure_insurance(payer, payer, ctx.airspace, 1_000, 1_000)],
+        &[],
+    )
+    .await?;
+
+    setup_user(&ctx, vec![(usdc, 0, 10_000 * ONE_USDC)]).await?;
+    let beneficiary = pool.ix.recovery_account(&usdc_pool.address);
+
+    let record = |claim_index, user: &TestUser| {
+        pool.ix.record_bad_debt(
+            ctx.airspace_authority.pubkey(),
+            ctx.airspace,
+            payer,
+            claim_index,
+            *user.user.address(),
+            &usdc_pool,
+            usdc_oracle,
+            staked_oracle,
+        )
+    };
+
+    // a claim can't be recorded for an account that is still solvent
+    let solvent = setup_user(&ctx, vec![(tsol, 0, 1_000 * ONE)]).await?;
+    solvent.borrow_to_wallet(&usdc, 100 * ONE_USDC).await?;
+    send_and_confirm(
+        &ctx.rpc(),
+        &[record(0, &solvent)],
+        &[&ctx.airspace_authority],
+    )
+    .await
+    .unwrap_err();
+
+    let first = setup_user(&ctx, vec![(tsol, 0, 1_000 * ONE)]).await?;
+    let second = setup_user(&ctx, vec![(tsol, 0, 1_000 * ONE)]).await?;
+    first.borrow_to_wallet(&usdc, 500 * ONE_USDC).await?;
+    second.borrow_to_wallet(&usdc, 100 * ONE_USDC).await?;
+
+    ctx.tokens()
+        .set_price(
+            &tsol,
+            &TokenPrice {
+                exponent: -8,
+                price: 10_000,
+                confidence: 100,
+                twap: 10_000,
+            },
+        )
+        .await?;
+    lose_collateral(&ctx, &first, tsol).await?;
+    lose_collateral(&ctx, &second, tsol).await?;
+
+    for (claim_index, user) in [&first, &second].into_iter().enumerate() {
+        send_and_confirm(
+            &ctx.rpc(),
+            &[record(claim_index as u64, user)],
+            &[&ctx.airspace_authority],
+        )
+        .await?;
+    }
+
+    // the claim is the loss to depositors, valued in the staked token
+    let claim = pool.claim(0).await?;
+    assert_eq!(usdc_pool.address, claim.source);
+    assert_eq!(*first.user.address(), claim.margin_account);
+    assert!(claim.debt_amount >= 500 * ONE_USDC);
+    assert_eq!(claim.debt_amount * 1_000, claim.amount);
+
+    // the first payout is limited to 10% of the pool
+    send_and_confirm(&ctx.rpc(), &[pool.ix.cover_bad_debt(0, beneficiary)], &[]).await?;
+
+    let claim = pool.claim(0).await?;
+    assert_eq!(400 * ONE, claim.covered);
+    assert_eq!(claim.amount - 400 * ONE, claim.uncovered);
+    assert_eq!(400 * ONE, ctx.tokens().get_balance(&beneficiary).await?);
+
+    // and nothing more can be paid out until the next epoch
+    let cover_second = pool.ix.cover_bad_debt(1, beneficiary);
+    send_and_confirm(&ctx.rpc(), &[cover_second.clone()], &[])
+        .await
+        .unwrap_err();
+
+    let mut clock = ctx.rpc().get_clock().await?;
+    clock.epoch += 1;
+    ctx.rpc().set_clock(clock).await?;
+
+    send_and_confirm(&ctx.rpc(), &[cover_second], &[]).await?;
+
+    let claim = pool.claim(1).await?;
+    assert_eq!(claim.amount, claim.covered);
+    assert_eq!(0, claim.uncovered);
+    assert_eq!(
+        400 * ONE + claim.covered,
+        pool.insurance_fund().await?.total_covered
+    );
+
+    // the payouts can only be withdrawn by the authority for the stake pool
+    let receiver = ctx.tokens().create_account(&staked, &payer).await?;
+    let total_covered = ctx.tokens().get_balance(&beneficiary).await?;
+    let withdraw = |authority| {
+        pool.ix
+            .withdraw_recovered(authority, usdc_pool.address, receiver, total_covered)
+    };
+
+    let other = ctx.solana.create_wallet(1).await?;
+    send_and_confirm(&ctx.rpc(), &[withdraw(other.pubkey())], &[&other])
+        .await
+        .unwrap_err();
+    send_and_confirm(&ctx.rpc(), &[withdraw(payer)], &[]).await?;
+
+    assert_eq!(total_covered, ctx.tokens().get_balance(&receiver).await?);
+    assert_eq!(0, ctx.tokens().get_balance(&beneficiary).await?);
+
+    Ok(())
+}
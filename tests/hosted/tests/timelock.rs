@@ -0,0 +1,370 @@
+use anchor_lang::AccountDeserialize;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use hosted_tests::{context::MarginTestContext, margin_test_context};
+use jet_airspace::state::{Airspace, Timelock};
+use jet_instructions::airspace::{derive_timelock_transaction, AirspaceIxBuilder};
+use jet_margin_sdk::solana::transaction::{SendTransactionBuilder, TransactionBuilderExt};
+use jet_solana_client::transaction::WithSigner;
+
+async fn get_account<T: AccountDeserialize>(ctx: &MarginTestContext, address: &Pubkey) -> T {
+    let account = ctx.rpc().get_account(address).await.unwrap().unwrap();
+    T::try_deserialize(&mut &account.data[..]).unwrap()
+}
+
+async fn execute(
+    ctx: &MarginTestContext,
+    airspace_ix: &AirspaceIxBuilder,
+    proposer: Pubkey,
+    id: u64,
+    instruction: &Instruction,
+) -> anyhow::Result<()> {
+    ctx.rpc()
+        .send_and_confirm(vec![airspace_ix.timelock_execute(proposer, id, instruction)].into())
+        .await?;
+
+    Ok(())
+}
+
+async fn approve_and_execute(
+    ctx: &MarginTestContext,
+    airspace_ix: &AirspaceIxBuilder,
+    approver: &Keypair,
+    proposer: Pubkey,
+    id: u64,
+    instruction: &Instruction,
+) -> anyhow::Result<()> {
+    [
+        airspace_ix.timelock_approve(approver.pubkey(), id),
+        airspace_ix.timelock_execute(proposer, id, instruction),
+    ]
+    .as_slice()
+    .with_signer(approver)
+    .send_and_confirm(&ctx.rpc())
+    .await?;
+
+    Ok(())
+}
+
+/// Changes made through the timelock must be queued, and can be cancelled while
+/// waiting for the delay.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+async fn timelock_authority_changes() -> anyhow::Result<()> {
+    let ctx = margin_test_context!();
+    let authority = &ctx.airspace_authority;
+    let airspace_ix = ctx.airspace_ix();
+    let timelock = airspace_ix.derive_timelock();
+
+    // create a timelock with no delay, and hand the airspace over to it
+    airspace_ix
+        .timelock_create(vec![authority.pubkey()], 1, 0)
+        .with_signer(authority)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+    airspace_ix
+        .set_authority(timelock)
+        .with_signer(authority)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+
+    // the previous authority can no longer make changes directly
+    airspace_ix
+        .set_authority(authority.pubkey())
+        .with_signer(authority)
+        .send_and_confirm(&ctx.rpc())
+        .await
+        .unwrap_err();
+
+    // add a delay to the timelock, through the timelock
+    let configure = airspace_ix.timelock_configure(vec![authority.pubkey()], 1, 3600);
+    airspace_ix
+        .timelock_queue(authority.pubkey(), 0, configure.clone())
+        .with_signer(authority)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+    ctx.rpc()
+        .send_and_confirm(
+            vec![airspace_ix.timelock_execute(authority.pubkey(), 0, &configure)].into(),
+        )
+        .await?;
+
+    let timelock_state = get_account::<Timelock>(&ctx, &timelock).await;
+    assert_eq!(3600, timelock_state.delay);
+    assert_eq!(1, timelock_state.transaction_count);
+
+    // changes to the airspace authority now have to wait for the delay
+    let set_authority =
+        AirspaceIxBuilder::new_from_address(ctx.airspace, ctx.payer().pubkey(), timelock)
+            .set_authority(authority.pubkey());
+    airspace_ix
+        .timelock_queue(authority.pubkey(), 1, set_authority.clone())
+        .with_signer(authority)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+    ctx.rpc()
+        .send_and_confirm(
+            vec![airspace_ix.timelock_execute(authority.pubkey(), 1, &set_authority)].into(),
+        )
+        .await
+        .unwrap_err();
+
+    airspace_ix
+        .timelock_cancel(authority.pubkey(), authority.pubkey(), 1)
+        .with_signer(authority)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+
+    let airspace = get_account::<Airspace>(&ctx, &ctx.airspace).await;
+    assert_eq!(timelock, airspace.authority);
+
+    Ok(())
+}
+
+/// A transaction can only be executed once it has approvals from as many signers as
+/// the threshold, and each signer only counts once.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+async fn timelock_threshold_requires_approvals() -> anyhow::Result<()> {
+    let ctx = margin_test_context!();
+    let authority = &ctx.airspace_authority;
+    let airspace_ix = ctx.airspace_ix();
+    let timelock = airspace_ix.derive_timelock();
+    let second = Keypair::new();
+    let outsider = Keypair::new();
+    let signers = vec![authority.pubkey(), second.pubkey()];
+
+    airspace_ix
+        .timelock_create(signers.clone(), 2, 0)
+        .with_signer(authority)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+
+    let configure = airspace_ix.timelock_configure(signers, 1, 0);
+    airspace_ix
+        .timelock_queue(authority.pubkey(), 0, configure.clone())
+        .with_signer(authority)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+
+    // the proposer's approval is not enough
+    execute(&ctx, &airspace_ix, authority.pubkey(), 0, &configure)
+        .await
+        .unwrap_err();
+
+    // the proposer can't approve a second time, and others can't approve at all
+    airspace_ix
+        .timelock_approve(authority.pubkey(), 0)
+        .with_signer(authority)
+        .send_and_confirm(&ctx.rpc())
+        .await
+        .unwrap_err();
+    airspace_ix
+        .timelock_approve(outsider.pubkey(), 0)
+        .with_signer(&outsider)
+        .send_and_confirm(&ctx.rpc())
+        .await
+        .unwrap_err();
+
+    approve_and_execute(
+        &ctx,
+        &airspace_ix,
+        &second,
+        authority.pubkey(),
+        0,
+        &configure,
+    )
+    .await?;
+
+    let timelock_state = get_account::<Timelock>(&ctx, &timelock).await;
+    assert_eq!(1, timelock_state.threshold);
+
+    Ok(())
+}
+
+/// Approvals from a signer stop counting once the signer is removed from the timelock,
+/// and the removed signer can't approve any more.
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+async fn timelock_removed_signer_approvals_do_not_count() -> anyhow::Result<()> {
+    let ctx = margin_test_context!();
+    let authority = &ctx.airspace_authority;
+    let airspace_ix = ctx.airspace_ix();
+    let timelock = airspace_ix.derive_timelock();
+    let removed = Keypair::new();
+    let remaining = Keypair::new();
+
+    airspace_ix
+        .timelock_create(
+            vec![authority.pubkey(), removed.pubkey(), remaining.pubkey()],
+            2,
+            0,
+        )
+        .with_signer(authority)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+
+    let remove_signer =
+        airspace_ix.timelock_configure(vec![authority.pubkey(), remaining.pubkey()], 2, 0);
+    let add_delay =
+        airspace_ix.timelock_configure(vec![authority.pubkey(), remaining.pubkey()], 2, 60);
+
+    for (id, instruction) in [&remove_signer, &add_delay].into_iter().enumerate() {
+        airspace_ix
+            .timelock_queue(authority.pubkey(), id as u64, instruction.clone())
+            .with_signer(authority)
+            .send_and_confirm(&ctx.rpc())
+            .await?;
+    }
+
+    // enough approvals for the second transaction, while the signer is still there
+    airspace_ix
+        .timelock_approve(removed.pubkey(), 1)
+        .with_signer(&removed)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+
+    approve_and_execute(
+        &ctx,
+        &airspace_ix,
+        &remaining,
+        authority.pubkey(),
+        0,
+        &remove_signer,
+    )
+    .await?;
+
+    execute(&ctx, &airspace_ix, authority.pubkey(), 1, &add_delay)
+        .await
+        .unwrap_err();
+    airspace_ix
+        .timelock_approve(removed.pubkey(), 1)
+        .with_signer(&removed)
+        .send_and_confirm(&ctx.rpc())
+        .await
+        .unwrap_err();
+
+    approve_and_execute(
+        &ctx,
+        &airspace_ix,
+        &remaining,
+        authority.pubkey(),
+        1,
+        &add_delay,
+    )
+    .await?;
+
+    let timelock_state = get_account::<Timelock>(&ctx, &timelock).await;
+    assert_eq!(
+        vec![authority.pubkey(), remaining.pubkey()],
+        timelock_state.signers
+    );
+    assert_eq!(60, timelock_state.delay);
+
+    Ok(())
+}
+
+/// A transaction can't be executed until the delay has passed since it was queued
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+#[cfg_attr(feature = "localnet", ignore = "does not run on localnet")]
+async fn timelock_executes_after_delay() -> anyhow::Result<()> {
+    let ctx = margin_test_context!();
+    let authority = &ctx.airspace_authority;
+    let airspace_ix = ctx.airspace_ix();
+    let timelock = airspace_ix.derive_timelock();
+    let second = Keypair::new();
+    let signers = vec![authority.pubkey(), second.pubkey()];
+
+    airspace_ix
+        .timelock_create(signers.clone(), 1, 3600)
+        .with_signer(authority)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+
+    let configure = airspace_ix.timelock_configure(signers, 2, 3600);
+    airspace_ix
+        .timelock_queue(authority.pubkey(), 0, configure.clone())
+        .with_signer(authority)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+
+    execute(&ctx, &airspace_ix, authority.pubkey(), 0, &configure)
+        .await
+        .unwrap_err();
+
+    // approvals beyond the threshold are allowed, but don't skip the delay
+    airspace_ix
+        .timelock_approve(second.pubkey(), 0)
+        .with_signer(&second)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+
+    let mut clock = ctx.rpc().get_clock().await?;
+    clock.unix_timestamp += 3600;
+    ctx.rpc().set_clock(clock).await?;
+
+    execute(&ctx, &airspace_ix, authority.pubkey(), 0, &configure).await?;
+
+    let timelock_state = get_account::<Timelock>(&ctx, &timelock).await;
+    assert_eq!(2, timelock_state.threshold);
+
+    Ok(())
+}
+
+/// A cancelled transaction is closed, so it can't be executed even with enough approvals
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(feature = "localnet"), serial_test::serial)]
+async fn timelock_cancelled_transaction_is_not_executed() -> anyhow::Result<()> {
+    let ctx = margin_test_context!();
+    let authority = &ctx.airspace_authority;
+    let airspace_ix = ctx.airspace_ix();
+    let timelock = airspace_ix.derive_timelock();
+    let second = Keypair::new();
+    let outsider = Keypair::new();
+    let signers = vec![authority.pubkey(), second.pubkey()];
+
+    airspace_ix
+        .timelock_create(signers.clone(), 1, 0)
+        .with_signer(authority)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+
+    let configure = airspace_ix.timelock_configure(signers, 2, 0);
+    airspace_ix
+        .timelock_queue(authority.pubkey(), 0, configure.clone())
+        .with_signer(authority)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+
+    // only the timelock signers can cancel, including ones that didn't propose it
+    airspace_ix
+        .timelock_cancel(outsider.pubkey(), authority.pubkey(), 0)
+        .with_signer(&outsider)
+        .send_and_confirm(&ctx.rpc())
+        .await
+        .unwrap_err();
+    airspace_ix
+        .timelock_cancel(second.pubkey(), authority.pubkey(), 0)
+        .with_signer(&second)
+        .send_and_confirm(&ctx.rpc())
+        .await?;
+
+    execute(&ctx, &airspace_ix, authority.pubkey(), 0, &configure)
+        .await
+        .unwrap_err();
+
+    let timelock_state = get_account::<Timelock>(&ctx, &timelock).await;
+    assert_eq!(1, timelock_state.threshold);
+    assert!(ctx
+        .rpc()
+        .get_account(&derive_timelock_transaction(&timelock, 0))
+        .await?
+        .is_none());
+
+    Ok(())
+}
@@ -0,0 +1,10 @@
+#![no_main]
+
+use jet_math_fuzz::pool::PoolScenario;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|scenario: PoolScenario| {
+    if let Err(violation) = scenario.run() {
+        panic!("{violation}\n{scenario:#?}");
+    }
+});
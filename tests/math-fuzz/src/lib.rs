@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Harness for property based and fuzz testing of the accounting math in the
+//! margin pool and fixed term programs.
+//!
+//! The [pool::PoolHarness] replays a sequence of user actions against a
+//! [jet_margin_pool::MarginPool] in the same way the program instructions do,
+//! and checks after every step that:
+//!
+//! - notes are never worth more than the tokens exchanged for them
+//! - rounding of every conversion favors the pool
+//! - note supplies match the balances held by users
+//! - no arithmetic panics, given a valid pool configuration
+//!
+//! The same operations are driven by proptest (see the `tests` directory) and
+//! by the cargo-fuzz targets in the `fuzz` directory, which need the `fuzzing`
+//! feature to derive `Arbitrary`.
+
+pub mod pool;
+pub mod strategy;
+
+use std::fmt::{Display, Formatter};
+
+/// Describes an invariant that did not hold after some operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation(pub String);
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invariant violated: {}", self.0)
+    }
+}
+
+impl std::error::Error for Violation {}
+
+/// Return a [Violation] if the condition does not hold
+#[macro_export]
+macro_rules! ensure {
+    ($cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            return Err($crate::Violation(format!($($arg)+)));
+        }
+    };
+}
@@ -0,0 +1,363 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use jet_margin_pool::{Amount, FullAmount, MarginPool, MarginPoolConfig, PoolAction, PoolFlags};
+use jet_program_common::Number;
+
+use crate::{ensure, Violation};
+
+/// The number of distinct users interacting with the pool
+pub const MAX_USERS: usize = 4;
+
+/// The largest amount of tokens involved in a single operation
+pub const MAX_TOKEN_AMOUNT: u64 = 1_000_000_000_000;
+
+/// The longest period of time that passes between two operations. Longer
+/// periods cannot be accrued in a single instruction.
+pub const MAX_ACCRUAL_SECONDS: u32 = 7 * 24 * 60 * 60;
+
+/// The largest borrow rate that can be configured, for which interest can
+/// be compounded.
+pub const MAX_BORROW_RATE_BPS: u16 = 20_000;
+
+/// An action taken by a user (or the fee collector) against the pool
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub enum PoolOp {
+    Deposit { user: u8, tokens: u64 },
+    Withdraw { user: u8, amount: OpAmount },
+    Borrow { user: u8, tokens: u64 },
+    Repay { user: u8, amount: OpAmount },
+    Accrue { seconds: u32 },
+    CollectFees,
+}
+
+/// The amount for an action that burns notes held by a user
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub enum OpAmount {
+    /// A fixed number of tokens
+    Tokens(u64),
+
+    /// A proportion of the notes held by the user, in basis points
+    NotesBps(u16),
+}
+
+/// Unvalidated pool configuration, which is normalized into a configuration
+/// that the pool authority could actually set.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct RawPoolConfig {
+    pub utilization_rates: [u16; 2],
+    pub borrow_rates: [u16; 4],
+    pub management_fee_rate: u16,
+}
+
+impl RawPoolConfig {
+    /// Utilization rates must be strictly between 0 and 100%, and the borrow
+    /// rates must be non-decreasing, for the interest rate curve to be defined.
+    pub fn normalize(&self) -> MarginPoolConfig {
+        let utilization_rate_1 = 1 + self.utilization_rates[0] % 9_999;
+        let utilization_rate_2 =
+            utilization_rate_1 + self.utilization_rates[1] % (10_000 - utilization_rate_1);
+
+        let mut borrow_rates = self.borrow_rates.map(|r| r % (MAX_BORROW_RATE_BPS + 1));
+        borrow_rates.sort_unstable();
+
+        MarginPoolConfig {
+            flags: PoolFlags::ALLOW_LENDING.bits(),
+            utilization_rate_1,
+            utilization_rate_2,
+            borrow_rate_0: borrow_rates[0],
+            borrow_rate_1: borrow_rates[1],
+            borrow_rate_2: borrow_rates[2],
+            borrow_rate_3: borrow_rates[3],
+            management_fee_rate: self.management_fee_rate % 10_001,
+            flash_loan_fee_rate: 0,
+            reserved: [0; 6],
+        }
+    }
+}
+
+/// A sequence of operations against a pool, as generated by a fuzzer
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "fuzzing", derive(arbitrary::Arbitrary))]
+pub struct PoolScenario {
+    pub config: RawPoolConfig,
+    pub ops: Vec<PoolOp>,
+}
+
+impl PoolScenario {
+    /// Apply all the operations, stopping at the first violation
+    pub fn run(&self) -> Result<PoolHarness, Violation> {
+        let mut harness = PoolHarness::new(self.config.normalize());
+
+        for op in &self.ops {
+            harness.apply(op)?;
+        }
+
+        Ok(harness)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct UserNotes {
+    deposit_notes: u64,
+    loan_notes: u64,
+}
+
+/// Replays operations against a margin pool and checks its invariants
+#[derive(Debug, Clone)]
+pub struct PoolHarness {
+    pub pool: MarginPool,
+    pub time: i64,
+    pub applied: usize,
+    pub rejected: usize,
+    users: [UserNotes; MAX_USERS],
+    fee_notes: u64,
+}
+
+impl PoolHarness {
+    pub fn new(config: MarginPoolConfig) -> Self {
+        let pool = MarginPool {
+            config,
+            ..Default::default()
+        };
+
+        Self {
+            pool,
+            time: 0,
+            applied: 0,
+            rejected: 0,
+            users: Default::default(),
+            fee_notes: 0,
+        }
+    }
+
+    /// Apply an operation, and verify the invariants of the pool.
+    ///
+    /// Operations that the program would reject are discarded without
+    /// changing the state, as their transaction would fail.
+    pub fn apply(&mut self, op: &PoolOp) -> Result<(), Violation> {
+        let applied = match *op {
+            PoolOp::Deposit { user, tokens } => self.deposit(user_index(user), tokens)?,
+            PoolOp::Withdraw { user, amount } => self.withdraw(user_index(user), amount)?,
+            PoolOp::Borrow { user, tokens } => self.borrow(user_index(user), tokens)?,
+            PoolOp::Repay { user, amount } => self.repay(user_index(user), amount)?,
+            PoolOp::Accrue { seconds } => self.accrue(seconds % (MAX_ACCRUAL_SECONDS + 1))?,
+            PoolOp::CollectFees => self.collect_fees(),
+        };
+
+        match applied {
+            true => self.applied += 1,
+            false => self.rejected += 1,
+        }
+
+        self.check_pool(op)
+    }
+
+    fn deposit(&mut self, user: usize, tokens: u64) -> Result<bool, Violation> {
+        let rate = self.pool.deposit_note_exchange_rate();
+        let amount = match self
+            .pool
+            .convert_amount(Amount::tokens(token_amount(tokens)), PoolAction::Deposit)
+        {
+            Ok(amount) => amount,
+            Err(_) => return Ok(false),
+        };
+
+        ensure!(
+            (Number::from(amount.notes) * rate).as_u64(0) <= amount.tokens,
+            "deposit minted notes worth more than the tokens deposited: {amount:?} at {rate}"
+        );
+
+        self.pool.deposit(&amount);
+        self.users[user].deposit_notes += amount.notes;
+
+        Ok(true)
+    }
+
+    fn withdraw(&mut self, user: usize, amount: OpAmount) -> Result<bool, Violation> {
+        let rate = self.pool.deposit_note_exchange_rate();
+        let held = self.users[user].deposit_notes;
+        let amount = match self.note_amount(amount, held, PoolAction::Withdraw) {
+            Some(amount) if amount.notes <= held => amount,
+            _ => return Ok(false),
+        };
+
+        ensure!(
+            amount.tokens <= (Number::from(amount.notes) * rate).as_u64_ceil(0),
+            "withdrawal paid out more tokens than the notes burned are worth: {amount:?} at {rate}"
+        );
+
+        if !self.transact(|pool| pool.withdraw(&amount)) {
+            return Ok(false);
+        }
+
+        self.users[user].deposit_notes -= amount.notes;
+        Ok(true)
+    }
+
+    fn borrow(&mut self, user: usize, tokens: u64) -> Result<bool, Violation> {
+        let rate = self.pool.loan_note_exchange_rate();
+        let amount = match self
+            .pool
+            .convert_amount(Amount::tokens(token_amount(tokens)), PoolAction::Borrow)
+        {
+            Ok(amount) => amount,
+            Err(_) => return Ok(false),
+        };
+
+        ensure!(
+            (Number::from(amount.notes) * rate).as_u64_ceil(0) >= amount.tokens,
+            "borrow minted loan notes worth less than the tokens lent: {amount:?} at {rate}"
+        );
+
+        if !self.transact(|pool| pool.borrow(&amount)) {
+            return Ok(false);
+        }
+
+        self.users[user].loan_notes += amount.notes;
+        Ok(true)
+    }
+
+    fn repay(&mut self, user: usize, amount: OpAmount) -> Result<bool, Violation> {
+        let rate = self.pool.loan_note_exchange_rate();
+        let held = self.users[user].loan_notes;
+        let amount = match self.note_amount(amount, held, PoolAction::Repay) {
+            Some(amount) if amount.notes <= held => amount,
+            _ => return Ok(false),
+        };
+
+        ensure!(
+            amount.tokens >= (Number::from(amount.notes) * rate).as_u64(0),
+            "repayment burned loan notes worth more than the tokens repaid: {amount:?} at {rate}"
+        );
+
+        if !self.transact(|pool| pool.repay(&amount)) {
+            return Ok(false);
+        }
+
+        self.users[user].loan_notes -= amount.notes;
+        Ok(true)
+    }
+
+    fn accrue(&mut self, seconds: u32) -> Result<bool, Violation> {
+        let deposit_rate = self.pool.deposit_note_exchange_rate();
+        let loan_rate = self.pool.loan_note_exchange_rate();
+
+        self.time += seconds as i64;
+
+        ensure!(
+            self.pool.accrue_interest(self.time),
+            "could not accrue interest over {seconds} seconds"
+        );
+        ensure!(
+            self.pool.deposit_note_exchange_rate() >= deposit_rate,
+            "deposit note value decreased from {deposit_rate} while accruing interest"
+        );
+        ensure!(
+            self.pool.loan_note_exchange_rate() >= loan_rate,
+            "loan note value decreased from {loan_rate} while accruing interest"
+        );
+
+        Ok(true)
+    }
+
+    fn collect_fees(&mut self) -> bool {
+        self.fee_notes += self.pool.collect_accrued_fees();
+        true
+    }
+
+    fn note_amount(&self, amount: OpAmount, held: u64, action: PoolAction) -> Option<FullAmount> {
+        let amount = match amount {
+            OpAmount::Tokens(tokens) => Amount::tokens(token_amount(tokens)),
+            OpAmount::NotesBps(bps) => {
+                let notes = held as u128 * (bps % 10_001) as u128 / 10_000;
+                Amount::notes(notes as u64)
+            }
+        };
+
+        self.pool.convert_amount(amount, action).ok()
+    }
+
+    /// Run a change to the pool, reverting it if the program would return
+    /// an error.
+    fn transact(
+        &mut self,
+        change: impl FnOnce(&mut MarginPool) -> anchor_lang::Result<()>,
+    ) -> bool {
+        let mut pool = self.pool.clone();
+
+        match change(&mut pool) {
+            Ok(()) => {
+                self.pool = pool;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn check_pool(&self, op: &PoolOp) -> Result<(), Violation> {
+        let user_deposit_notes = self.users.iter().map(|u| u.deposit_notes).sum::<u64>();
+        let user_loan_notes = self.users.iter().map(|u| u.loan_notes).sum::<u64>();
+
+        ensure!(
+            user_deposit_notes + self.fee_notes == self.pool.deposit_notes,
+            "after {op:?} the pool has {} deposit notes, but users hold {}",
+            self.pool.deposit_notes,
+            user_deposit_notes + self.fee_notes
+        );
+        ensure!(
+            user_loan_notes == self.pool.loan_notes,
+            "after {op:?} the pool has {} loan notes, but users hold {}",
+            self.pool.loan_notes,
+            user_loan_notes
+        );
+
+        // The pool values its notes at no less than one token while it is
+        // (almost) empty, so the backing can only be checked after that.
+        let total_value = *self.pool.total_borrowed() + Number::from(self.pool.deposit_tokens);
+        let fees = *self.pool.total_uncollected_fees();
+
+        ensure!(
+            fees <= total_value,
+            "after {op:?} uncollected fees {fees} exceed the pool value {total_value}"
+        );
+
+        if total_value >= Number::ONE && self.pool.deposit_notes > 0 {
+            let note_value =
+                Number::from(self.pool.deposit_notes) * self.pool.deposit_note_exchange_rate();
+
+            ensure!(
+                note_value <= total_value - fees,
+                "after {op:?} deposit notes are worth {note_value}, more than the pool holds {}",
+                total_value - fees
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn user_index(user: u8) -> usize {
+    user as usize % MAX_USERS
+}
+
+fn token_amount(tokens: u64) -> u64 {
+    tokens % (MAX_TOKEN_AMOUNT + 1)
+}
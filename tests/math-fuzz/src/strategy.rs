@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//
+// Copyright (C) 2023 JET PROTOCOL HOLDINGS, LLC.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Proptest strategies for generating pool scenarios
+
+use proptest::{collection::vec, prelude::*};
+
+use crate::pool::{OpAmount, PoolOp, PoolScenario, RawPoolConfig, MAX_TOKEN_AMOUNT};
+
+/// Token amounts across several orders of magnitude, so that small amounts
+/// where rounding matters most are generated as often as large ones.
+pub fn token_amount() -> impl Strategy<Value = u64> {
+    prop_oneof![
+        0u64..100,
+        100u64..1_000_000,
+        1_000_000u64..=MAX_TOKEN_AMOUNT,
+    ]
+}
+
+pub fn op_amount() -> impl Strategy<Value = OpAmount> {
+    prop_oneof![
+        token_amount().prop_map(OpAmount::Tokens),
+        (0u16..=10_000).prop_map(OpAmount::NotesBps),
+    ]
+}
+
+pub fn pool_op() -> impl Strategy<Value = PoolOp> {
+    prop_oneof![
+        3 => (any::<u8>(), token_amount()).prop_map(|(user, tokens)| PoolOp::Deposit { user, tokens }),
+        2 => (any::<u8>(), op_amount()).prop_map(|(user, amount)| PoolOp::Withdraw { user, amount }),
+        3 => (any::<u8>(), token_amount()).prop_map(|(user, tokens)| PoolOp::Borrow { user, tokens }),
+        2 => (any::<u8>(), op_amount()).prop_map(|(user, amount)| PoolOp::Repay { user, amount }),
+        2 => any::<u32>().prop_map(|seconds| PoolOp::Accrue { seconds }),
+        1 => Just(PoolOp::CollectFees),
+    ]
+}
+
+pub fn pool_config() -> impl Strategy<Value = RawPoolConfig> {
+    (any::<[u16; 2]>(), any::<[u16; 4]>(), any::<u16>()).prop_map(
+        |(utilization_rates, borrow_rates, management_fee_rate)| RawPoolConfig {
+            utilization_rates,
+            borrow_rates,
+            management_fee_rate,
+        },
+    )
+}
+
+pub fn pool_scenario(max_ops: usize) -> impl Strategy<Value = PoolScenario> {
+    (pool_config(), vec(pool_op(), 1..max_ops))
+        .prop_map(|(config, ops)| PoolScenario { config, ops })
+}
@@ -0,0 +1,87 @@
+use jet_fixed_term::{
+    margin::origination_fee::{borrow_order_qty, loan_to_disburse, FEE_UNIT},
+    orderbook::state::{
+        fp32_div, fp32_mul_ceil, fp32_mul_floor, quote_from_base, RoundingDirection,
+    },
+};
+use proptest::{collection::vec, prelude::*};
+
+/// Much larger than any origination fee that would be charged in practice
+const MAX_ORIGINATION_FEE: u64 = FEE_UNIT / 10;
+
+/// The largest request for which the order including the fee does not overflow
+fn max_borrowable(fee: u64) -> u64 {
+    (FEE_UNIT as u128 * (u64::MAX - 1) as u128 / (FEE_UNIT as u128 + fee as u128)) as u64
+}
+
+fn request_and_fee() -> impl Strategy<Value = (u64, u64)> {
+    (0..=MAX_ORIGINATION_FEE).prop_flat_map(|fee| (0..=max_borrowable(fee), Just(fee)))
+}
+
+proptest! {
+    #[test]
+    fn origination_fee_is_never_undercharged((request, fee) in request_and_fee()) {
+        let order = borrow_order_qty(request, fee);
+        let disbursed = loan_to_disburse(order, fee);
+
+        prop_assert!(order >= request);
+        prop_assert!(disbursed <= request);
+
+        // the borrower pays at least the fee on what they received
+        let fee_paid = (order - disbursed) as u128;
+        prop_assert!(fee_paid >= disbursed as u128 * fee as u128 / FEE_UNIT as u128);
+    }
+
+    #[test]
+    fn partial_fills_disburse_the_request(
+        (request, fee) in request_and_fee(),
+        splits in vec(1u64..=10_000, 1..10),
+    ) {
+        let order = borrow_order_qty(request, fee);
+        let mut remaining = order;
+        let mut disbursed = 0u64;
+        let mut fills = 0;
+
+        for split in splits {
+            let fill = (remaining as u128 * split as u128 / 10_000) as u64;
+            remaining -= fill;
+            disbursed += loan_to_disburse(fill, fee);
+            fills += 1;
+        }
+        disbursed += loan_to_disburse(remaining, fee);
+        fills += 1;
+
+        // each fill may round down by less than one token
+        prop_assert!(disbursed <= request);
+        prop_assert!(request - disbursed < fills);
+    }
+
+    #[test]
+    fn quotes_round_in_the_requested_direction(base: u64, price in 1u64..(1 << 40)) {
+        let exact = base as u128 * price as u128;
+        let floor = exact >> 32;
+        let ceil = (exact + (1 << 32) - 1) >> 32;
+
+        match u64::try_from(ceil) {
+            Ok(ceil) => {
+                prop_assert_eq!(Some(floor as u64), fp32_mul_floor(base, price));
+                prop_assert_eq!(Some(ceil), fp32_mul_ceil(base, price));
+                prop_assert_eq!(ceil, quote_from_base(base, price, RoundingDirection::Up).unwrap());
+                prop_assert_eq!(
+                    floor as u64,
+                    quote_from_base(base, price, RoundingDirection::Down).unwrap()
+                );
+            }
+            Err(_) => prop_assert_eq!(None, fp32_mul_ceil(base, price)),
+        }
+    }
+
+    #[test]
+    fn fp32_round_trip_does_not_create_value(base in 0u64..(1 << 48), price in 1u64..(1 << 40)) {
+        if let Some(quote) = fp32_mul_floor(base, price) {
+            if let Some(round_trip) = fp32_div(quote, price) {
+                prop_assert!(round_trip <= base);
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use jet_margin_pool::{Amount, MarginPool, PoolAction, PoolFlags};
+use jet_program_common::Number;
+use proptest::prelude::*;
+
+use jet_math_fuzz::strategy::{pool_config, pool_scenario, token_amount};
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn random_operations_preserve_pool_invariants(scenario in pool_scenario(64)) {
+        if let Err(violation) = scenario.run() {
+            return Err(TestCaseError::fail(violation.to_string()));
+        }
+    }
+
+    #[test]
+    fn conversions_round_in_favor_of_the_pool(
+        deposit_tokens in token_amount(),
+        borrowed in token_amount(),
+        deposit_rate_bps in 10_000u64..40_000,
+        loan_rate_bps in 10_000u64..40_000,
+        tokens in token_amount(),
+    ) {
+        let deposit_notes = (deposit_tokens + borrowed) * 10_000 / deposit_rate_bps;
+        let loan_notes = borrowed * 10_000 / loan_rate_bps;
+
+        let mut pool = MarginPool {
+            deposit_tokens,
+            deposit_notes,
+            loan_notes,
+            borrowed_tokens: Number::from(borrowed).into_bits(),
+            ..Default::default()
+        };
+        pool.config.flags = PoolFlags::ALLOW_LENDING.bits();
+
+        let deposit_rate = pool.deposit_note_exchange_rate();
+        let loan_rate = pool.loan_note_exchange_rate();
+
+        for action in [PoolAction::Deposit, PoolAction::Withdraw, PoolAction::Borrow, PoolAction::Repay] {
+            let rate = match action {
+                PoolAction::Deposit | PoolAction::Withdraw => deposit_rate,
+                PoolAction::Borrow | PoolAction::Repay => loan_rate,
+            };
+
+            // An error is an acceptable outcome, since no value is exchanged
+            let Ok(amount) = pool.convert_amount(Amount::tokens(tokens), action) else {
+                continue;
+            };
+            let notes_value = Number::from(amount.notes) * rate;
+
+            match action {
+                // the user receives notes, which must not be worth more than they paid
+                PoolAction::Deposit => prop_assert!(notes_value.as_u64(0) <= amount.tokens),
+                // the user takes tokens, and must burn or owe at least as much
+                PoolAction::Withdraw | PoolAction::Borrow => {
+                    prop_assert!(notes_value.as_u64_ceil(0) >= amount.tokens)
+                }
+                // the user pays tokens, and must not cancel more debt than they paid
+                PoolAction::Repay => prop_assert!(notes_value.as_u64(0) <= amount.tokens),
+            }
+        }
+    }
+
+    #[test]
+    fn interest_accrual_never_decreases_note_values(
+        config in pool_config(),
+        deposit in 1_000_000u64..=1_000_000_000_000,
+        borrow_bps in 0u64..9_500,
+        periods in proptest::collection::vec(0i64..=604_800, 1..32),
+    ) {
+        let pool = &mut MarginPool {
+            config: config.normalize(),
+            ..Default::default()
+        };
+        let deposit = pool.convert_amount(Amount::tokens(deposit), PoolAction::Deposit).unwrap();
+        pool.deposit(&deposit);
+
+        let borrow = pool
+            .convert_amount(Amount::tokens(deposit.tokens * borrow_bps / 10_000), PoolAction::Borrow);
+        if let Ok(borrow) = borrow {
+            pool.borrow(&borrow).unwrap();
+        }
+
+        let mut time = 0;
+        for period in periods {
+            let deposit_rate = pool.deposit_note_exchange_rate();
+            let loan_rate = pool.loan_note_exchange_rate();
+
+            time += period;
+            prop_assert!(pool.accrue_interest(time));
+            prop_assert!(pool.deposit_note_exchange_rate() >= deposit_rate);
+            prop_assert!(pool.loan_note_exchange_rate() >= loan_rate);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use jet_program_common::{Fp32, Number, Number128};
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn number_conversions_bracket_the_value(a: u64, b in 1u64..) {
+        let quotient = Number::from(a) / Number::from(b);
+
+        let floor = quotient.as_u64(0);
+        let rounded = quotient.as_u64_rounded(0);
+        let ceil = quotient.as_u64_ceil(0);
+
+        prop_assert_eq!(a / b, floor);
+        prop_assert!(floor <= rounded && rounded <= ceil);
+        prop_assert!(ceil - floor <= 1);
+    }
+
+    #[test]
+    fn number_multiplication_never_rounds_up(
+        a in 0u64..(1 << 40),
+        b in 0u64..(1 << 40),
+        exponent in -15i32..=0,
+    ) {
+        let scaled = Number::from_decimal(b, exponent);
+        let product = Number::from(a) * scaled;
+        let exact = a as u128 * b as u128 / 10u128.pow(exponent.unsigned_abs());
+
+        prop_assert!(product.as_u128(0) <= exact);
+    }
+
+    #[test]
+    fn number_round_trips_integers(a: u64) {
+        prop_assert_eq!(a, Number::from(a).as_u64(0));
+        prop_assert_eq!(a, Number::from(a).as_u64_ceil(0));
+        prop_assert_eq!(Number::from(a), Number::from_bits(Number::from(a).into_bits()));
+    }
+
+    #[test]
+    fn number128_arithmetic_matches_integers(
+        a in -1_000_000_000_000i64..1_000_000_000_000,
+        b in -1_000_000_000_000i64..1_000_000_000_000,
+    ) {
+        let x = Number128::from_decimal(a, 0);
+        let y = Number128::from_decimal(b, 0);
+
+        prop_assert_eq!(Number128::from_decimal(a as i128 + b as i128, 0), x + y);
+        prop_assert_eq!(Number128::from_decimal(a as i128 - b as i128, 0), x - y);
+        prop_assert_eq!(x, x * Number128::ONE);
+        prop_assert_eq!(Number128::from_decimal((a as i128).abs(), 0), x.abs());
+
+        if b != 0 {
+            // division truncates towards zero, like integer division
+            prop_assert_eq!(Number128::from_decimal(a as i128 / b as i128, 0).to_i128() / 10_000_000_000,
+                (x / y).to_i128() / 10_000_000_000);
+        }
+    }
+
+    #[test]
+    fn fp32_decimal_conversions_bracket_the_value(a in 0u64..(1 << 48), b in 1u64..(1 << 32)) {
+        let value = Fp32::from(a) / b;
+
+        let floor = value.as_decimal_u64().unwrap();
+        let ceil = value.as_decimal_u64_ceil().unwrap();
+
+        prop_assert_eq!(a / b, floor);
+        prop_assert_eq!((a + b - 1) / b, ceil);
+        prop_assert_eq!(Some(a), Fp32::from(a).as_decimal_u64());
+    }
+}
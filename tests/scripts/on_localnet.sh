@@ -20,6 +20,7 @@ WHIRLPOOL_PID=whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc
 SBR_PID=SSwpkEEcbUqx4vtoEByFjSkhKdCT862DNVb52nZg1UZ
 OPNBK_PID=srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX
 LKPRG_PID=LTR8xXcSrEDsCbTWPY4JmJREFdMz4uYh65uajkVjzru
+PHOENIX_PID=PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY
 
 CTRL_SO=target/deploy/jet_control.so
 MRGN_SO=target/deploy/jet_margin.so
@@ -36,6 +37,7 @@ WHIRLPOOL_SO=deps/whirlpool.so
 SBRSWAP_SO=deps/saber_stable_swap.so
 OPNBK_SO=deps/openbook.so
 LKPRG_SO=deps/lookup_table_registry.so
+PHOENIX_SO=deps/phoenix.so
 
 PROGRAM_FEATURES='testing'
 TEST_FEATURES="${BATCH:-batch_all},localnet"
@@ -94,6 +96,7 @@ start-validator() {
         --bpf-program $SBR_PID $SBRSWAP_SO \
         --bpf-program $OPNBK_PID $OPNBK_SO \
         --bpf-program $LKPRG_PID $LKPRG_SO \
+        --bpf-program $PHOENIX_PID $PHOENIX_SO \
         --quiet \
         $@ &
     VALIDATOR_PID=$!
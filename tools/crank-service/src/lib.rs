@@ -1,15 +1,17 @@
 use std::{
+    net::SocketAddr,
     path::{Path, PathBuf},
     sync::Arc,
 };
 
 use anyhow::Result;
 use clap::Parser;
+use metrics_exporter_prometheus::PrometheusBuilder;
 
 use solana_cli_config::{Config as SolanaConfig, CONFIG_FILE as SOLANA_CONFIG_FILE};
 use solana_sdk::{pubkey::Pubkey, signature::read_keypair_file};
 
-use jet_margin_sdk::fixed_term::Crank;
+use jet_margin_sdk::fixed_term::{metrics, Crank};
 
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
 use tracing_subscriber::{prelude::*, EnvFilter};
@@ -37,6 +39,11 @@ pub struct CliOpts {
     /// Log file
     #[clap(long, short = 'l')]
     pub log_path: Option<PathBuf>,
+
+    /// The address to serve prometheus metrics from (e.g. 0.0.0.0:9090)
+    /// Metrics are not exported if not set
+    #[clap(long, env = "METRICS_ADDRESS")]
+    pub metrics_address: Option<SocketAddr>,
 }
 
 pub async fn run(opts: CliOpts) -> Result<()> {
@@ -68,6 +75,15 @@ pub async fn run(opts: CliOpts) -> Result<()> {
 
     tracing_subscriber::registry().with(log_layers).init();
 
+    if let Some(address) = opts.metrics_address {
+        PrometheusBuilder::new()
+            .with_http_listener(address)
+            .install()?;
+        metrics::describe();
+
+        tracing::info!("serving metrics at {address}");
+    }
+
     let solana_config =
         SolanaConfig::load(SOLANA_CONFIG_FILE.as_ref().unwrap()).unwrap_or_default();
     let keypair = read_keypair_file(
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use jet_environment::{builder::token_context, config::EnvironmentConfig};
+use jet_margin_sdk::{
+    ix_builder::{
+        derive_airspace, derive_governor_id, derive_token_config,
+        fixed_term::derive::market_from_tenor, get_control_authority_address, MarginPoolIxBuilder,
+    },
+    jet_airspace, jet_control,
+    jet_fixed_term::{self, control::state::Market},
+    jet_margin::{self, TokenConfig},
+    jet_margin_pool::{self, MarginPool},
+    jet_margin_swap, jet_metadata,
+};
+use jet_solana_client::rpc::SolanaRpcExtra;
+
+use crate::{
+    client::Client,
+    governance::{
+        JET_CUSTODY_GOVERNANCE, JET_DAO_GOVERNANCE, JET_ENG_GOVERNANCE, JET_ENG_TREASURY,
+        JET_GOVERNANCE_PROGRAM, JET_STAKING_PROGRAM,
+    },
+};
+
+/// Human readable names for addresses, so that instructions can be described in
+/// terms of the resources they affect.
+#[derive(Default)]
+pub struct AccountLabels {
+    labels: HashMap<Pubkey, String>,
+}
+
+impl AccountLabels {
+    /// Collect labels for well known programs and accounts, for the resources described
+    /// in an environment config (if available), and for the resources found on chain.
+    pub async fn load(client: &Client, config: Option<&EnvironmentConfig>) -> Result<Self> {
+        let mut labels = Self::default();
+
+        labels.add_well_known();
+
+        if let Some(config) = config {
+            labels.add_config(config)?;
+        }
+
+        labels.add_network_state(client).await?;
+
+        Ok(labels)
+    }
+
+    /// Get the label for an address, if one is known
+    pub fn get(&self, address: &Pubkey) -> Option<&str> {
+        self.labels.get(address).map(|s| s.as_str())
+    }
+
+    /// Describe an address by its label, falling back to the address itself
+    pub fn name(&self, address: &Pubkey) -> String {
+        match self.get(address) {
+            Some(label) => label.to_owned(),
+            None => address.to_string(),
+        }
+    }
+
+    /// Describe an address by its label, followed by the address
+    pub fn describe(&self, address: &Pubkey) -> String {
+        match self.get(address) {
+            Some(label) => format!("{label} ({address})"),
+            None => address.to_string(),
+        }
+    }
+
+    fn insert(&mut self, address: Pubkey, label: impl Into<String>) {
+        // labels from the config take precedence over those derived from chain state
+        self.labels.entry(address).or_insert_with(|| label.into());
+    }
+
+    fn add_well_known(&mut self) {
+        for (address, label) in [
+            (jet_margin::ID, "margin program"),
+            (jet_margin_pool::ID, "margin-pool program"),
+            (jet_margin_swap::ID, "margin-swap program"),
+            (jet_fixed_term::ID, "fixed-term program"),
+            (jet_control::ID, "control program"),
+            (jet_metadata::ID, "metadata program"),
+            (jet_airspace::ID, "airspace program"),
+            (JET_STAKING_PROGRAM, "staking program"),
+            (JET_GOVERNANCE_PROGRAM, "governance program"),
+            (JET_DAO_GOVERNANCE, "dao governance"),
+            (JET_ENG_GOVERNANCE, "eng governance"),
+            (JET_ENG_TREASURY, "eng treasury"),
+            (JET_CUSTODY_GOVERNANCE, "custody governance"),
+            (get_control_authority_address(), "control authority"),
+            (derive_governor_id(), "airspace governor"),
+        ] {
+            self.insert(address, label);
+        }
+    }
+
+    fn add_config(&mut self, config: &EnvironmentConfig) -> Result<()> {
+        let oracle_authority = config.oracle_authority.unwrap_or_default();
+
+        for airspace in &config.airspaces {
+            let airspace_address = derive_airspace(&airspace.name);
+            self.insert(airspace_address, format!("airspace {}", airspace.name));
+
+            for desc in &airspace.tokens {
+                let token =
+                    token_context(config.network, &airspace_address, oracle_authority, desc)?;
+                let mint = token.mint();
+                let symbol = &desc.symbol;
+
+                self.insert(mint, format!("{symbol} mint"));
+                self.insert(token.pyth_price(), format!("{symbol} pyth price"));
+                self.insert(token.pyth_product(), format!("{symbol} pyth product"));
+                self.insert(
+                    derive_token_config(&airspace_address, &mint),
+                    format!("token-config {symbol}"),
+                );
+
+                if desc.margin_pool.is_some() {
+                    self.add_pool(&airspace_address, &mint, symbol);
+                }
+
+                for market in &desc.fixed_term_markets {
+                    self.insert(
+                        market_from_tenor(&airspace_address, &mint, market.borrow_tenor),
+                        format!("fixed-term {symbol}_{}", market.borrow_tenor),
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_pool(&mut self, airspace: &Pubkey, mint: &Pubkey, symbol: &str) {
+        let pool = MarginPoolIxBuilder::new(*mint);
+
+        self.insert(pool.address, format!("margin-pool {symbol}"));
+        self.insert(pool.vault, format!("margin-pool {symbol} vault"));
+        self.insert(
+            pool.deposit_note_mint,
+            format!("margin-pool {symbol} deposit notes"),
+        );
+        self.insert(
+            pool.loan_note_mint,
+            format!("margin-pool {symbol} loan notes"),
+        );
+        self.insert(
+            derive_token_config(airspace, &pool.deposit_note_mint),
+            format!("token-config {symbol} deposit notes"),
+        );
+        self.insert(
+            derive_token_config(airspace, &pool.loan_note_mint),
+            format!("token-config {symbol} loan notes"),
+        );
+    }
+
+    async fn add_network_state(&mut self, client: &Client) -> Result<()> {
+        let rpc = client.network_interface();
+
+        for (address, pool) in rpc.find_anchor_accounts::<MarginPool>().await? {
+            let symbol = self.name(&pool.token_mint);
+            let symbol = symbol.trim_end_matches(" mint").to_owned();
+
+            self.insert(address, format!("margin-pool {symbol}"));
+            self.insert(pool.vault, format!("margin-pool {symbol} vault"));
+            self.insert(
+                pool.deposit_note_mint,
+                format!("margin-pool {symbol} deposit notes"),
+            );
+            self.insert(
+                pool.loan_note_mint,
+                format!("margin-pool {symbol} loan notes"),
+            );
+        }
+
+        for (address, market) in rpc.find_anchor_accounts::<Market>().await? {
+            let symbol = self.name(&market.underlying_token_mint);
+            let symbol = symbol.trim_end_matches(" mint");
+
+            self.insert(
+                address,
+                format!("fixed-term {symbol}_{}", market.borrow_tenor),
+            );
+        }
+
+        for (address, config) in rpc.find_anchor_accounts::<TokenConfig>().await? {
+            let name = self.name(&config.mint);
+            let name = name
+                .trim_start_matches("margin-pool ")
+                .trim_end_matches(" mint");
+
+            self.insert(address, format!("token-config {name}"));
+        }
+
+        Ok(())
+    }
+}
@@ -2,8 +2,9 @@ use std::path::PathBuf;
 
 use anchor_lang::prelude::Pubkey;
 use anyhow::{bail, Result};
-use jet_environment::builder::{
-    configure_environment, Builder, ProposalContext, ProposalExecution,
+use jet_environment::{
+    builder::{configure_environment, Builder, ProposalContext, ProposalExecution},
+    diff::diff_environment,
 };
 use jet_program_common::{GOVERNOR_DEVNET, GOVERNOR_MAINNET};
 use jet_solana_client::util::keypair::clone;
@@ -19,6 +20,7 @@ pub async fn process_apply(
     config_path: PathBuf,
     proposal: Option<Pubkey>,
     proposal_option: u8,
+    diff: bool,
 ) -> Result<Plan> {
     let config = jet_environment::config::read_env_config_dir(&config_path)?;
 
@@ -28,6 +30,18 @@ pub async fn process_apply(
         NetworkKind::Localnet => client.signer()?,
     };
 
+    if diff {
+        let mut builder = Builder::new(
+            client.network_interface(),
+            client.signer_ref()?,
+            ProposalExecution::Direct { authority },
+        )
+        .await?;
+
+        print!("{}", diff_environment(&mut builder, &config).await?);
+        return Ok(Plan::default());
+    }
+
     let proposal_execution = match (client.network_kind, proposal) {
         (NetworkKind::Localnet, None) => ProposalExecution::Direct { authority },
         (_, None) => bail!("must target a proposal for effecting changes on public networks"),
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use anyhow::Result;
 use solana_sdk::pubkey::Pubkey;
 use spl_governance::state::proposal::get_proposal_address;
@@ -172,7 +174,16 @@ pub async fn process_proposal_clear_instructions(
     Ok(plan.build())
 }
 
-pub async fn process_proposal_inspect(client: &Client, proposal_address: Pubkey) -> Result<Plan> {
-    crate::governance::inspect_proposal_instructions(client, proposal_address).await?;
+pub async fn process_proposal_inspect(
+    client: &Client,
+    proposal_address: Pubkey,
+    config_path: Option<PathBuf>,
+) -> Result<Plan> {
+    let config = config_path
+        .map(|path| jet_environment::config::read_env_config_dir(&path))
+        .transpose()?;
+
+    crate::governance::inspect_proposal_instructions(client, proposal_address, config.as_ref())
+        .await?;
     Ok(Plan::default())
 }
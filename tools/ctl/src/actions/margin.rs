@@ -12,11 +12,11 @@ use comfy_table::{presets::UTF8_FULL, Table};
 use futures::FutureExt;
 use jet_margin_sdk::{
     ix_builder::{
-        derive_margin_permit, derive_token_config, get_metadata_address, MarginConfigIxBuilder,
-        MarginIxBuilder, MarginPoolIxBuilder,
+        derive_margin_permit, derive_risk_config, derive_token_config, get_metadata_address,
+        MarginConfigIxBuilder, MarginIxBuilder, MarginPoolIxBuilder,
     },
     jet_airspace::state::Airspace,
-    jet_margin::{self, MarginAccount, PriceInfo, Valuation},
+    jet_margin::{self, MarginAccount, PriceInfo, RiskConfig, RiskParameters, Valuation},
     jet_margin_pool::{self, MarginPool},
     jet_metadata::{self},
 };
@@ -330,6 +330,7 @@ pub async fn process_list_top_accounts(
     limit: usize,
 ) -> Result<Plan> {
     let mut all_user_accounts = get_all_accounts(client, &airspace).await?;
+    let risk = get_risk_params(client, &airspace).await?;
 
     let refresh_account_tasks = all_user_accounts.iter_mut().map(|(address, account)| {
         refresh_account_positions(client, account).map(|result| (*address, result))
@@ -365,6 +366,7 @@ pub async fn process_list_top_accounts(
                         .duration_since(UNIX_EPOCH)
                         .unwrap()
                         .as_secs(),
+                    &risk,
                 )
                 .unwrap(),
         });
@@ -412,6 +414,19 @@ async fn get_all_accounts(
         .collect())
 }
 
+async fn get_risk_params(client: &Client, airspace: &Pubkey) -> Result<RiskParameters> {
+    let address = derive_risk_config(airspace);
+
+    if !client.account_exists(&address).await? {
+        return Ok(RiskParameters::default());
+    }
+
+    Ok(client
+        .read_anchor_account::<RiskConfig>(&address)
+        .await?
+        .params)
+}
+
 pub async fn process_inspect(client: &Client, addresses: Vec<Pubkey>) -> Result<Plan> {
     for address in addresses {
         let account = client
@@ -419,8 +434,9 @@ pub async fn process_inspect(client: &Client, addresses: Vec<Pubkey>) -> Result<
             .await?;
         println!("{address:#?}");
         println!("{account:#?}");
+        let risk = get_risk_params(client, &account.airspace).await?;
         if let Some(oldest_price) = account.positions().map(|p| p.price.timestamp).min() {
-            print!("{:#?}", account.valuation(oldest_price)?);
+            print!("{:#?}", account.valuation(oldest_price, &risk)?);
             let dt: DateTime<Local> = (UNIX_EPOCH + Duration::from_secs(oldest_price)).into();
             println!("   priced_at: {}", dt.to_rfc2822());
         }
@@ -45,6 +45,9 @@ pub struct MarginPoolParameters {
 
     #[clap(long)]
     pub management_fee_rate: Option<u16>,
+
+    #[clap(long)]
+    pub flash_loan_fee_rate: Option<u16>,
 }
 
 #[derive(Debug, Parser, Deserialize)]
@@ -295,6 +298,7 @@ fn override_pool_config_with_options(
         borrow_rate_2,
         borrow_rate_3,
         management_fee_rate,
+        flash_loan_fee_rate,
     } = margin_pool;
 
     let orig_params = config.parameters.unwrap();
@@ -308,6 +312,7 @@ fn override_pool_config_with_options(
     override_field!(overridden, params, borrow_rate_2);
     override_field!(overridden, params, borrow_rate_3);
     override_field!(overridden, params, management_fee_rate);
+    override_field!(overridden, params, flash_loan_fee_rate);
 
     if orig_params == *params {
         config.parameters = None;
@@ -12,6 +12,8 @@ use borsh::BorshDeserialize;
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
 
+use crate::account_labels::AccountLabels;
+
 pub struct ParsedInstruction {
     pub program: Pubkey,
     pub name: String,
@@ -20,6 +22,11 @@ pub struct ParsedInstruction {
 }
 
 impl ParsedInstruction {
+    /// Format the instruction with human readable names for the accounts it uses
+    pub fn labeled<'a>(&'a self, labels: &'a AccountLabels) -> LabeledInstruction<'a> {
+        LabeledInstruction { ix: self, labels }
+    }
+
     pub fn account_map(&self) -> HashMap<String, Pubkey> {
         let mut map = HashMap::new();
         for account in &self.accounts {
@@ -163,6 +170,20 @@ impl DataValue {
         }
     }
 
+    pub fn try_as_bool(&self) -> Result<bool> {
+        match self {
+            DataValue::Bool(b) => Ok(*b),
+            _ => bail!("unexpected variant: {}", self.variant_name()),
+        }
+    }
+
+    pub fn try_as_public_key(&self) -> Result<Pubkey> {
+        match self {
+            DataValue::PublicKey(key) => Ok(*key),
+            _ => bail!("unexpected variant: {}", self.variant_name()),
+        }
+    }
+
     pub fn try_as_enum_tuple(&self) -> Result<(&String, &Vec<DataValue>)> {
         match self {
             DataValue::EnumTuple(name, items) => Ok((name, items)),
@@ -426,22 +447,48 @@ impl<'a> IdlReader<'a> {
 impl Debug for ParsedInstruction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct(&self.name)
-            .field("accounts", &DisplayParsedAccounts(&self.accounts))
+            .field("accounts", &DisplayParsedAccounts(&self.accounts, None))
             .field("data", &self.data)
             .finish()
     }
 }
 
-struct DisplayParsedAccounts<'a>(&'a Vec<ParsedAccountInput>);
+pub struct LabeledInstruction<'a> {
+    ix: &'a ParsedInstruction,
+    labels: &'a AccountLabels,
+}
+
+impl<'a> Debug for LabeledInstruction<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(&format!(
+            "{} ({})",
+            self.ix.name,
+            self.labels.name(&self.ix.program)
+        ))
+        .field(
+            "accounts",
+            &DisplayParsedAccounts(&self.ix.accounts, Some(self.labels)),
+        )
+        .field("data", &self.ix.data)
+        .finish()
+    }
+}
+
+struct DisplayParsedAccounts<'a>(&'a Vec<ParsedAccountInput>, Option<&'a AccountLabels>);
 
 impl<'a> Debug for DisplayParsedAccounts<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut struct_fmt = f.debug_struct("");
 
         for parsed in self.0 {
-            match parsed {
-                ParsedAccountInput::Account(name, key) => struct_fmt.field(name, key),
-                ParsedAccountInput::Group(name, keys) => struct_fmt.field(name, &Self(keys)),
+            match (parsed, self.1) {
+                (ParsedAccountInput::Account(name, key), None) => struct_fmt.field(name, key),
+                (ParsedAccountInput::Account(name, key), Some(labels)) => {
+                    struct_fmt.field(name, &DisplayLabel(labels.describe(key)))
+                }
+                (ParsedAccountInput::Group(name, keys), labels) => {
+                    struct_fmt.field(name, &Self(keys, labels))
+                }
             };
         }
 
@@ -449,6 +496,14 @@ impl<'a> Debug for DisplayParsedAccounts<'a> {
     }
 }
 
+struct DisplayLabel(String);
+
+impl Debug for DisplayLabel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 fn find_type_in_idl<'a>(idl: &'a Idl, name: &str) -> Option<&'a IdlTypeDefinitionTy> {
     idl.types
         .iter()
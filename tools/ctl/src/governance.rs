@@ -28,7 +28,10 @@ use spl_governance::state::{
     token_owner_record::{get_token_owner_record_address, get_token_owner_record_data},
 };
 
+use jet_environment::config::EnvironmentConfig;
+
 use crate::{
+    account_labels::AccountLabels,
     anchor_ix_parser::{AnchorParser, ParsedAccountInput, ParsedInstruction},
     client::{Client, NetworkKind, Plan, TransactionEntry},
     ix_inspectors::{all_inspectors, inspect},
@@ -119,17 +122,22 @@ pub async fn convert_plan_to_proposal(
 pub async fn inspect_proposal_instructions(
     client: &Client,
     proposal_address: Pubkey,
+    config: Option<&EnvironmentConfig>,
 ) -> Result<()> {
     let proposal: ProposalV2 = get_borsh_account(client.rpc(), &proposal_address)
         .await
         .with_context(|| "getting proposal")?;
     let mut anchor_parser = AnchorParser::new(client.rpc());
+    let labels = AccountLabels::load(client, config).await?;
 
     for default_program in DEFAULT_IDLS {
         anchor_parser.load_idl(default_program).await?;
     }
 
-    println!("transactions will have authority: {}", proposal.governance);
+    println!(
+        "transactions will have authority: {}",
+        labels.describe(&proposal.governance)
+    );
 
     for (opt_index, option) in proposal.options.iter().enumerate() {
         println!("transactions for option {}:", option.label);
@@ -168,8 +176,8 @@ pub async fn inspect_proposal_instructions(
 
                     let parsed =
                         try_parse_instruction(client, &mut anchor_parser, instruction).await?;
-                    println!("{parsed:#?}");
-                    inspect(client, &parsed, all_inspectors()).await;
+                    println!("{:#?}", parsed.labeled(&labels));
+                    inspect(client, &labels, &parsed, all_inspectors()).await;
                 }
             } else {
                 println!("tx #{tx_index} not found, likely was removed: {tx_address}");
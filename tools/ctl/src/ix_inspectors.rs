@@ -1,27 +1,45 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display};
 
 use anchor_lang::{prelude::Pubkey, AccountDeserialize};
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use futures::try_join;
+use heck::ToSnakeCase;
 use jet_margin_sdk::{
     jet_control,
-    jet_margin_pool::MarginPool,
+    jet_margin::{self, TokenConfig, TokenKind},
+    jet_margin_pool::{MarginPool, PoolFlags},
     jet_metadata::{PositionTokenMetadata, TokenMetadata},
 };
+use solana_sdk::bpf_loader_upgradeable;
 
-use crate::{anchor_ix_parser::ParsedInstruction, client::Client};
+use crate::{
+    account_labels::AccountLabels,
+    anchor_ix_parser::{DataValue, ParsedInstruction},
+    client::Client,
+};
 
 pub fn all_inspectors() -> Vec<IxInspector> {
-    vec![ConfigureMarginPoolInspector.wrap()]
+    vec![
+        ConfigureMarginPoolInspector.wrap(),
+        ConfigureTokenInspector.wrap(),
+        ConfigurePermitInspector.wrap(),
+        ConfigureAdapterInspector.wrap(),
+        ProgramLoaderInspector.wrap(),
+    ]
 }
 
-pub async fn inspect(client: &Client, ix: &ParsedInstruction, inspectors: Vec<IxInspector>) {
+pub async fn inspect(
+    client: &Client,
+    labels: &AccountLabels,
+    ix: &ParsedInstruction,
+    inspectors: Vec<IxInspector>,
+) {
     let mut ran_one = false;
     for inspector in inspectors {
         if inspector.matches(ix) {
             ran_one = true;
-            if let Err(e) = inspector.run(client, ix).await {
+            if let Err(e) = inspector.run(client, labels, ix).await {
                 eprintln!("failed to run {}: {e:#?}", inspector.name());
             }
         }
@@ -69,7 +87,12 @@ pub trait CustomIxInspector {
                 .unwrap_or(true)
     }
 
-    async fn run(&self, client: &Client, ix: &ParsedInstruction) -> Result<()>;
+    async fn run(
+        &self,
+        client: &Client,
+        labels: &AccountLabels,
+        ix: &ParsedInstruction,
+    ) -> Result<()>;
 
     fn wrap(self) -> IxInspector
     where
@@ -92,7 +115,12 @@ impl CustomIxInspector for ConfigureMarginPoolInspector {
     fn instruction_name(&self) -> Option<String> {
         Some(String::from("configureMarginPool"))
     }
-    async fn run(&self, client: &Client, ix: &ParsedInstruction) -> Result<()> {
+    async fn run(
+        &self,
+        client: &Client,
+        labels: &AccountLabels,
+        ix: &ParsedInstruction,
+    ) -> Result<()> {
         let accounts = ix.account_map();
         let (pool, token_metadata, deposit_metadata, loan_metadata) = try_join!(
             account_by_name::<MarginPool>(client, &accounts, "marginPool"),
@@ -100,6 +128,8 @@ impl CustomIxInspector for ConfigureMarginPoolInspector {
             account_by_name::<PositionTokenMetadata>(client, &accounts, "depositMetadata"),
             account_by_name::<PositionTokenMetadata>(client, &accounts, "loanMetadata"),
         )?;
+        let resource = labels.name(address_by_name(&accounts, "marginPool")?);
+
         println!("\nExisting state that this instruction affects:\n");
         println!("{pool:#?}");
         println!("{deposit_metadata:#?}");
@@ -107,17 +137,23 @@ impl CustomIxInspector for ConfigureMarginPoolInspector {
         println!(
             "\nKnown changes that will be triggered by this instruction (may not be exhaustive):\n"
         );
-        for (name, ctx, current_value) in [
-            ("tokenMint", "pool", &pool.token_mint),
-            ("pythProduct", "token", &token_metadata.pyth_product),
-            ("pythPrice", "token", &token_metadata.pyth_price),
-            ("pythPrice", "pool", &pool.token_price_oracle),
+
+        for (name, current_value) in [
+            ("tokenMint", &pool.token_mint),
+            ("pythProduct", &token_metadata.pyth_product),
+            ("pythPrice", &token_metadata.pyth_price),
+            ("pythPrice", &pool.token_price_oracle),
         ] {
-            let addr = accounts
-                .get(name)
-                .context("could not find account {name} in this instruction.")?;
+            let addr = address_by_name(&accounts, name)?;
             if addr != current_value {
-                println!(">>> {name} in {ctx}: {current_value} --> {addr}");
+                print_change(
+                    &resource,
+                    name,
+                    labels.name(current_value),
+                    labels.name(addr),
+                    "",
+                );
+                print_risk(format!("changes the {name} used by {resource}"));
             }
         }
         for top in ix.data.try_as_struct()? {
@@ -128,23 +164,67 @@ impl CustomIxInspector for ConfigureMarginPoolInspector {
                     if name == "tokenKind"
                         && value.try_as_enum_tuple()?.0 != &format!("{:#?}", kind)
                     {
-                        println!(">>> tokenKind: {:#?} --> {value:#?}", kind);
+                        print_change(
+                            &resource,
+                            "token_kind",
+                            format!("{kind:?}"),
+                            format!("{value:?}"),
+                            "",
+                        );
                     }
-                    for (name_to_find, prior) in [
-                        ("collateralWeight", deposit_metadata.value_modifier as u128),
-                        ("maxLeverage", loan_metadata.value_modifier as u128),
-                        ("flags", pool.config.flags as u128),
-                        ("utilizationRate1", pool.config.utilization_rate_1 as u128),
-                        ("utilizationRate2", pool.config.utilization_rate_2 as u128),
-                        ("borrowRate0", pool.config.borrow_rate_0 as u128),
-                        ("borrowRate1", pool.config.borrow_rate_1 as u128),
-                        ("borrowRate2", pool.config.borrow_rate_2 as u128),
-                        ("borrowRate3", pool.config.borrow_rate_3 as u128),
-                        ("managementFeeRate", pool.config.management_fee_rate as u128),
-                        ("reserved", pool.config.reserved as u128),
+                    for (name_to_find, prior, unit) in [
+                        (
+                            "collateralWeight",
+                            deposit_metadata.value_modifier as u128,
+                            "",
+                        ),
+                        ("maxLeverage", loan_metadata.value_modifier as u128, ""),
+                        ("flags", pool.config.flags as u128, ""),
+                        (
+                            "utilizationRate1",
+                            pool.config.utilization_rate_1 as u128,
+                            " bps",
+                        ),
+                        (
+                            "utilizationRate2",
+                            pool.config.utilization_rate_2 as u128,
+                            " bps",
+                        ),
+                        ("borrowRate0", pool.config.borrow_rate_0 as u128, " bps"),
+                        ("borrowRate1", pool.config.borrow_rate_1 as u128, " bps"),
+                        ("borrowRate2", pool.config.borrow_rate_2 as u128, " bps"),
+                        ("borrowRate3", pool.config.borrow_rate_3 as u128, " bps"),
+                        (
+                            "managementFeeRate",
+                            pool.config.management_fee_rate as u128,
+                            " bps",
+                        ),
+                        (
+                            "flashLoanFeeRate",
+                            pool.config.flash_loan_fee_rate as u128,
+                            " bps",
+                        ),
                     ] {
-                        if name == name_to_find && value.try_as_integer_unsigned()? != prior {
-                            println!(">>> {name}: {prior:#?} --> {value:#?}",);
+                        if name != name_to_find {
+                            continue;
+                        }
+
+                        let new = value.try_as_integer_unsigned()?;
+                        if new == prior {
+                            continue;
+                        }
+
+                        if name == "flags" {
+                            print_pool_flags_change(&resource, prior as u64, new as u64);
+                            continue;
+                        }
+
+                        print_change(&resource, &name.to_snake_case(), prior, new, unit);
+
+                        if name == "collateralWeight" && new < prior {
+                            print_risk(format!(
+                                "lowers the collateral weight of {resource}, which may make accounts liquidatable"
+                            ));
                         }
                     }
                 }
@@ -155,14 +235,288 @@ impl CustomIxInspector for ConfigureMarginPoolInspector {
     }
 }
 
+/// Prints the changes to a margin token config
+pub struct ConfigureTokenInspector;
+#[async_trait(?Send)]
+impl CustomIxInspector for ConfigureTokenInspector {
+    fn program_id(&self) -> Option<Pubkey> {
+        Some(jet_margin::ID)
+    }
+    fn instruction_name(&self) -> Option<String> {
+        Some(String::from("configureToken"))
+    }
+    async fn run(
+        &self,
+        client: &Client,
+        labels: &AccountLabels,
+        ix: &ParsedInstruction,
+    ) -> Result<()> {
+        let accounts = ix.account_map();
+        let existing = try_account_by_name::<TokenConfig>(client, &accounts, "tokenConfig").await?;
+        let resource = labels.name(address_by_name(&accounts, "tokenConfig")?);
+        let update = field_by_name(ix.data.try_as_struct()?, "update")?.try_as_optional()?;
+
+        println!(
+            "\nKnown changes that will be triggered by this instruction (may not be exhaustive):\n"
+        );
+
+        let update = match (update, &existing) {
+            (None, None) => return Ok(()),
+            (None, Some(_)) => {
+                println!(">>> {resource}: removed");
+                print_risk(format!(
+                    "removes {resource}, positions in this token will no longer be valued"
+                ));
+                return Ok(());
+            }
+            (Some(update), _) => update.try_as_struct()?,
+        };
+
+        let Some(existing) = existing else {
+            println!(">>> {resource}: created");
+
+            for (name, value) in update {
+                println!("    {}: {value:?}", name.to_snake_case());
+            }
+
+            return Ok(());
+        };
+
+        let value_modifier = field_by_name(update, "valueModifier")?.try_as_integer_unsigned()?;
+        let max_staleness = field_by_name(update, "maxStaleness")?.try_as_integer_unsigned()?;
+        let token_kind = field_by_name(update, "tokenKind")?.try_as_enum_tuple()?.0;
+        let admin = format!("{:?}", field_by_name(update, "admin")?);
+        let underlying = field_by_name(update, "underlyingMint")?.try_as_public_key()?;
+
+        if existing.underlying_mint != underlying {
+            print_change(
+                &resource,
+                "underlying_mint",
+                labels.name(&existing.underlying_mint),
+                labels.name(&underlying),
+                "",
+            );
+            print_risk(format!("changes the underlying token for {resource}"));
+        }
+        if format!("{:?}", existing.token_kind) != *token_kind {
+            print_change(
+                &resource,
+                "token_kind",
+                format!("{:?}", existing.token_kind),
+                token_kind,
+                "",
+            );
+        }
+        if format!("{:?}", existing.admin) != admin {
+            print_change(
+                &resource,
+                "admin",
+                format!("{:?}", existing.admin),
+                &admin,
+                "",
+            );
+            print_risk(format!(
+                "changes the oracle or administrator for {resource}"
+            ));
+        }
+        if existing.value_modifier as u128 != value_modifier {
+            print_change(
+                &resource,
+                "value_modifier",
+                existing.value_modifier,
+                value_modifier,
+                "",
+            );
+
+            if value_modifier < existing.value_modifier as u128 {
+                let description = match existing.token_kind {
+                    TokenKind::Claim => "max leverage",
+                    _ => "collateral weight",
+                };
+
+                print_risk(format!(
+                    "lowers the {description} of {resource}, which may make accounts liquidatable"
+                ));
+            }
+        }
+        if existing.max_staleness as u128 != max_staleness {
+            print_change(
+                &resource,
+                "max_staleness",
+                existing.max_staleness,
+                max_staleness,
+                " seconds",
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Prints the permissions granted or revoked for an address
+pub struct ConfigurePermitInspector;
+#[async_trait(?Send)]
+impl CustomIxInspector for ConfigurePermitInspector {
+    fn program_id(&self) -> Option<Pubkey> {
+        Some(jet_margin::ID)
+    }
+    fn instruction_name(&self) -> Option<String> {
+        Some(String::from("configureLiquidator"))
+    }
+    async fn run(
+        &self,
+        _client: &Client,
+        labels: &AccountLabels,
+        ix: &ParsedInstruction,
+    ) -> Result<()> {
+        let accounts = ix.account_map();
+        let owner = labels.describe(address_by_name(&accounts, "owner")?);
+        let airspace = labels.name(address_by_name(&accounts, "airspace")?);
+        let is_liquidator =
+            field_by_name(ix.data.try_as_struct()?, "isLiquidator")?.try_as_bool()?;
+
+        match is_liquidator {
+            true => print_risk(format!(
+                "grants the LIQUIDATE permit to {owner} in {airspace}"
+            )),
+            false => println!(">>> revokes the LIQUIDATE permit from {owner} in {airspace}"),
+        }
+
+        Ok(())
+    }
+}
+
+/// Prints the adapter programs being added or removed from an airspace
+pub struct ConfigureAdapterInspector;
+#[async_trait(?Send)]
+impl CustomIxInspector for ConfigureAdapterInspector {
+    fn program_id(&self) -> Option<Pubkey> {
+        Some(jet_margin::ID)
+    }
+    fn instruction_name(&self) -> Option<String> {
+        Some(String::from("configureAdapter"))
+    }
+    async fn run(
+        &self,
+        _client: &Client,
+        labels: &AccountLabels,
+        ix: &ParsedInstruction,
+    ) -> Result<()> {
+        let accounts = ix.account_map();
+        let adapter = labels.describe(address_by_name(&accounts, "adapterProgram")?);
+        let airspace = labels.name(address_by_name(&accounts, "airspace")?);
+        let is_adapter = field_by_name(ix.data.try_as_struct()?, "isAdapter")?.try_as_bool()?;
+
+        match is_adapter {
+            true => print_risk(format!(
+                "allows {adapter} to act as an adapter for margin accounts in {airspace}"
+            )),
+            false => println!(">>> removes {adapter} as an adapter in {airspace}"),
+        }
+
+        Ok(())
+    }
+}
+
+/// Prints the programs being upgraded or transferred
+pub struct ProgramLoaderInspector;
+#[async_trait(?Send)]
+impl CustomIxInspector for ProgramLoaderInspector {
+    fn program_id(&self) -> Option<Pubkey> {
+        Some(bpf_loader_upgradeable::ID)
+    }
+    async fn run(
+        &self,
+        _client: &Client,
+        labels: &AccountLabels,
+        ix: &ParsedInstruction,
+    ) -> Result<()> {
+        let accounts = ix.account_map();
+
+        match ix.name.as_str() {
+            "Upgrade Program" => print_risk(format!(
+                "upgrades {} with the code in buffer {}",
+                labels.describe(address_by_name(&accounts, "Program")?),
+                address_by_name(&accounts, "Buffer")?
+            )),
+            "Set Program Authority" => print_risk(format!(
+                "changes the upgrade authority of program data {} to {}",
+                address_by_name(&accounts, "Program Data")?,
+                labels.describe(address_by_name(&accounts, "New Authority")?)
+            )),
+            _ => (),
+        }
+
+        Ok(())
+    }
+}
+
+/// Print a single change to some resource
+fn print_change(resource: &str, field: &str, current: impl Display, new: impl Display, unit: &str) {
+    println!(">>> {resource}: {field} {current} -> {new}{unit}");
+}
+
+/// Print a warning about a change that deserves special attention from reviewers
+fn print_risk(message: impl Display) {
+    println!("!!! RISK: {message}");
+}
+
+fn print_pool_flags_change(resource: &str, prior: u64, new: u64) {
+    let prior = PoolFlags::from_bits_truncate(prior);
+    let new = PoolFlags::from_bits_truncate(new);
+
+    print_change(
+        resource,
+        "flags",
+        format!("{prior:?}"),
+        format!("{new:?}"),
+        "",
+    );
+
+    if new.contains(PoolFlags::DISABLED) && !prior.contains(PoolFlags::DISABLED) {
+        print_risk(format!(
+            "disables {resource}, freezing all funds in the pool"
+        ));
+    }
+
+    if prior.contains(PoolFlags::ALLOW_LENDING) && !new.contains(PoolFlags::ALLOW_LENDING) {
+        print_risk(format!("disables borrowing from {resource}"));
+    }
+}
+
+fn field_by_name<'a>(fields: &'a [(String, DataValue)], name: &str) -> Result<&'a DataValue> {
+    fields
+        .iter()
+        .find_map(|(n, value)| (n == name).then_some(value))
+        .context(format!("could not find field with name: {name}"))
+}
+
+/// Find the address of an account by name, using a map of names to pubkeys
+fn address_by_name<'a>(map: &'a HashMap<String, Pubkey>, name: &str) -> Result<&'a Pubkey> {
+    map.get(name)
+        .context(format!("could not find account with name: {name}"))
+}
+
 /// Download anchor account by name, using a map of names to pubkeys
 async fn account_by_name<T: AccountDeserialize>(
     client: &Client,
     map: &HashMap<String, Pubkey>,
     name: &str,
 ) -> Result<T> {
-    let addr = map
-        .get(name)
-        .context(format!("could not find account with name: {name}"))?;
+    let addr = address_by_name(map, name)?;
     client.read_anchor_account::<T>(addr).await
 }
+
+/// Download anchor account by name, if it exists
+async fn try_account_by_name<T: AccountDeserialize>(
+    client: &Client,
+    map: &HashMap<String, Pubkey>,
+    name: &str,
+) -> Result<Option<T>> {
+    let addr = address_by_name(map, name)?;
+
+    match client.account_exists(addr).await? {
+        true => Ok(Some(client.read_anchor_account::<T>(addr).await?)),
+        false => Ok(None),
+    }
+}
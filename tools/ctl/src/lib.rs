@@ -16,6 +16,7 @@ pub mod app_config;
 pub mod client;
 pub mod config;
 
+mod account_labels;
 mod anchor_ix_parser;
 mod governance;
 mod ix_inspectors;
@@ -76,6 +77,11 @@ pub enum ProposalsCommand {
     Inspect {
         /// The address of the proposal to be inspected
         proposal_address: Pubkey,
+
+        /// The path to the environment config, used to give names to the
+        /// accounts used by the proposal
+        #[clap(long)]
+        config: Option<PathBuf>,
     },
 
     /// Execute instructions on an approved proposal
@@ -292,6 +298,11 @@ pub enum Command {
     Apply {
         /// The path to the configuration to be applied
         config_path: PathBuf,
+
+        /// Only report how the network state differs from the config, without
+        /// making any changes
+        #[clap(long)]
+        diff: bool,
     },
 
     /// Generate the client app config file
@@ -368,12 +379,13 @@ pub async fn run(opts: CliOpts) -> Result<()> {
             )
             .await?
         }
-        Command::Apply { config_path } => {
+        Command::Apply { config_path, diff } => {
             actions::apply::process_apply(
                 &client,
                 config_path,
                 opts.target_proposal,
                 opts.target_proposal_option,
+                diff,
             )
             .await?
         }
@@ -433,9 +445,10 @@ pub async fn run(opts: CliOpts) -> Result<()> {
 
 async fn run_proposals_command(client: &Client, command: ProposalsCommand) -> Result<Plan> {
     match command {
-        ProposalsCommand::Inspect { proposal_address } => {
-            actions::governance::process_proposal_inspect(client, proposal_address).await
-        }
+        ProposalsCommand::Inspect {
+            proposal_address,
+            config,
+        } => actions::governance::process_proposal_inspect(client, proposal_address, config).await,
         ProposalsCommand::Clear { proposal_address } => {
             actions::governance::process_proposal_clear_instructions(client, proposal_address).await
         }
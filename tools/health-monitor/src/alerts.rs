@@ -0,0 +1,444 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+
+use jet_margin::{AccountPosition, ErrorCode, MarginAccount, RiskParameters, Valuation};
+
+/// A notable change in the health of a margin account
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Alert {
+    /// The margin account the alert is about
+    pub margin_account: String,
+
+    /// The unix timestamp when the alert was raised
+    pub timestamp: i64,
+
+    #[serde(flatten)]
+    pub kind: AlertKind,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "alert", rename_all = "snake_case")]
+pub enum AlertKind {
+    /// The effective c-ratio fell below a threshold
+    CRatioBelow { threshold: f64, c_ratio: f64 },
+
+    /// The effective c-ratio recovered above a threshold
+    CRatioAbove { threshold: f64, c_ratio: f64 },
+
+    /// A position can no longer be valued until it is refreshed
+    StalePosition { token: String, reason: String },
+
+    /// A term loan is about to mature, or is already past due
+    TermLoanMaturity {
+        market: String,
+        maturity: i64,
+        seconds_remaining: i64,
+    },
+}
+
+/// Remembers what was already alerted for each account, so that alerts are only
+/// raised when something changes.
+pub struct AlertTracker {
+    /// c-ratio thresholds, from highest to lowest
+    thresholds: Vec<f64>,
+
+    /// Seconds before a term loan matures to alert about it
+    maturity_warning: i64,
+
+    /// The number of thresholds each account is below
+    levels: HashMap<Pubkey, usize>,
+
+    /// The stale positions of each account
+    stale: HashMap<Pubkey, HashSet<Pubkey>>,
+
+    /// The maturity of the last term loan alerted for each fixed term user
+    maturities: HashMap<Pubkey, i64>,
+}
+
+impl AlertTracker {
+    pub fn new(mut thresholds: Vec<f64>, maturity_warning: i64) -> Self {
+        thresholds.sort_by(|a, b| b.total_cmp(a));
+        thresholds.dedup();
+
+        Self {
+            thresholds,
+            maturity_warning,
+            levels: HashMap::new(),
+            stale: HashMap::new(),
+            maturities: HashMap::new(),
+        }
+    }
+
+    /// Check the positions and c-ratio of a margin account
+    pub fn check_account(
+        &mut self,
+        address: &Pubkey,
+        account: &MarginAccount,
+        now: i64,
+        risk: &RiskParameters,
+    ) -> Vec<AlertKind> {
+        // Avoid treating positions as updated in the future when the local clock is behind
+        let timestamp = account
+            .positions()
+            .flat_map(|p| [p.balance_timestamp, p.price.timestamp])
+            .fold(now.max(0) as u64, u64::max);
+
+        let mut alerts = self.check_stale_positions(address, account, timestamp, risk);
+
+        match account.valuation(timestamp, risk) {
+            Ok(valuation) => alerts.extend(self.check_c_ratio(address, &valuation)),
+            Err(e) => log::debug!("could not value account {address}: {e:?}"),
+        }
+
+        alerts
+    }
+
+    /// Check the next unpaid term loan of a fixed term user
+    pub fn check_term_loan(
+        &mut self,
+        user: &Pubkey,
+        market: &Pubkey,
+        maturity: Option<i64>,
+        now: i64,
+    ) -> Option<AlertKind> {
+        let Some(maturity) = maturity else {
+            self.maturities.remove(user);
+            return None;
+        };
+
+        let seconds_remaining = maturity - now;
+        if seconds_remaining > self.maturity_warning || self.maturities.get(user) == Some(&maturity)
+        {
+            return None;
+        }
+
+        self.maturities.insert(*user, maturity);
+
+        Some(AlertKind::TermLoanMaturity {
+            market: market.to_string(),
+            maturity,
+            seconds_remaining,
+        })
+    }
+
+    fn check_stale_positions(
+        &mut self,
+        address: &Pubkey,
+        account: &MarginAccount,
+        timestamp: u64,
+        risk: &RiskParameters,
+    ) -> Vec<AlertKind> {
+        let previous = self.stale.remove(address).unwrap_or_default();
+        let mut current = HashSet::new();
+        let mut alerts = vec![];
+
+        for position in account.positions() {
+            let Some(reason) = stale_reason(position, timestamp, risk) else {
+                continue;
+            };
+
+            if !previous.contains(&position.token) {
+                alerts.push(AlertKind::StalePosition {
+                    token: position.token.to_string(),
+                    reason: format!("{reason:?}"),
+                });
+            }
+            current.insert(position.token);
+        }
+
+        if !current.is_empty() {
+            self.stale.insert(*address, current);
+        }
+
+        alerts
+    }
+
+    fn check_c_ratio(&mut self, address: &Pubkey, valuation: &Valuation) -> Option<AlertKind> {
+        let c_ratio = valuation.effective_c_ratio().as_f64();
+        let level = self.thresholds.iter().filter(|t| c_ratio < **t).count();
+        let previous = self.levels.insert(*address, level).unwrap_or(0);
+
+        match level.cmp(&previous) {
+            std::cmp::Ordering::Greater => Some(AlertKind::CRatioBelow {
+                threshold: self.thresholds[level - 1],
+                c_ratio,
+            }),
+            std::cmp::Ordering::Less => Some(AlertKind::CRatioAbove {
+                threshold: self.thresholds[level],
+                c_ratio,
+            }),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+/// The reason the margin program would not count a position when valuing an account
+fn stale_reason(
+    position: &AccountPosition,
+    timestamp: u64,
+    risk: &RiskParameters,
+) -> Option<ErrorCode> {
+    if position.balance == 0 {
+        return None;
+    }
+
+    let balance_age = timestamp - position.balance_timestamp;
+    let price_quote_age = timestamp - position.price.timestamp;
+
+    if !position.price.is_valid() {
+        Some(ErrorCode::InvalidPrice)
+    } else if position.max_staleness > 0 && balance_age > position.max_staleness {
+        Some(ErrorCode::OutdatedBalance)
+    } else if price_quote_age > risk.max_price_quote_age {
+        Some(ErrorCode::OutdatedPrice)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use bytemuck::Zeroable;
+    use jet_margin::{Approver, PositionConfigUpdate, PriceInfo, TokenKind};
+
+    use super::*;
+
+    pub const NOW: i64 = 1_000;
+
+    /// A margin account with a collateral position and a claim of 100, both priced at 1
+    pub struct TestAccount {
+        pub address: Pubkey,
+        pub account: MarginAccount,
+        pub collateral: Pubkey,
+        pub claim: Pubkey,
+    }
+
+    impl TestAccount {
+        /// With no max leverage on the claim, the c-ratio is `(collateral - 100) / 100`
+        pub fn new(collateral: u64) -> Self {
+            let mut test = Self {
+                address: Pubkey::new_unique(),
+                account: MarginAccount::zeroed(),
+                collateral: Pubkey::new_unique(),
+                claim: Pubkey::new_unique(),
+            };
+            test.account
+                .initialize(Pubkey::default(), Pubkey::new_unique(), 0, 0);
+            test.register(test.collateral, TokenKind::Collateral, 10);
+            test.register(test.claim, TokenKind::Claim, 0);
+            test.set_balance(test.collateral, collateral, NOW);
+            test.set_balance(test.claim, 100, NOW);
+
+            test
+        }
+
+        fn register(&mut self, mint: Pubkey, kind: TokenKind, max_staleness: u64) {
+            let adapter = Pubkey::new_unique();
+            let approvals = match kind {
+                TokenKind::Collateral => vec![Approver::MarginAccountAuthority],
+                _ => vec![Approver::MarginAccountAuthority, Approver::Adapter(adapter)],
+            };
+
+            self.account
+                .register_position(
+                    PositionConfigUpdate {
+                        mint,
+                        decimals: 0,
+                        address: mint,
+                        airspace: Pubkey::default(),
+                        adapter,
+                        kind,
+                        value_modifier: 100,
+                        max_staleness,
+                    },
+                    &approvals,
+                    &RiskParameters::default(),
+                )
+                .unwrap();
+        }
+
+        /// Set the balance of a position, with a price that is current at `timestamp`
+        pub fn set_balance(&mut self, mint: Pubkey, balance: u64, timestamp: i64) {
+            self.account
+                .set_position_price(&mint, &PriceInfo::new_valid(0, 1, timestamp as u64))
+                .unwrap();
+            self.account
+                .set_position_balance(&mint, &mint, balance, timestamp as u64)
+                .unwrap();
+        }
+    }
+
+    fn tracker() -> AlertTracker {
+        AlertTracker::new(vec![1.1, 1.5, 1.25, 1.5], 100)
+    }
+
+    fn check(tracker: &mut AlertTracker, test: &TestAccount, now: i64) -> Vec<AlertKind> {
+        tracker.check_account(
+            &test.address,
+            &test.account,
+            now,
+            &RiskParameters::default(),
+        )
+    }
+
+    fn crossed_below(alerts: &[AlertKind]) -> Vec<f64> {
+        alerts
+            .iter()
+            .filter_map(|alert| match alert {
+                AlertKind::CRatioBelow { threshold, .. } => Some(*threshold),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn crossed_above(alerts: &[AlertKind]) -> Vec<f64> {
+        alerts
+            .iter()
+            .filter_map(|alert| match alert {
+                AlertKind::CRatioAbove { threshold, .. } => Some(*threshold),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn stale(alerts: Vec<AlertKind>) -> Vec<AlertKind> {
+        alerts
+            .into_iter()
+            .filter(|alert| matches!(alert, AlertKind::StalePosition { .. }))
+            .collect()
+    }
+
+    #[test]
+    fn thresholds_are_sorted_from_highest() {
+        assert_eq!(vec![1.5, 1.25, 1.1], tracker().thresholds);
+    }
+
+    #[test]
+    fn healthy_account_raises_no_alerts() {
+        let mut tracker = tracker();
+        let test = TestAccount::new(300);
+
+        assert_eq!(Vec::<AlertKind>::new(), check(&mut tracker, &test, NOW));
+    }
+
+    #[test]
+    fn c_ratio_alerts_only_when_crossing_a_threshold() {
+        let mut tracker = tracker();
+        let mut test = TestAccount::new(240);
+
+        let alerts = check(&mut tracker, &test, NOW);
+        assert_eq!(vec![1.5], crossed_below(&alerts));
+        assert_eq!(
+            vec![AlertKind::CRatioBelow {
+                threshold: 1.5,
+                c_ratio: 1.4,
+            }],
+            alerts
+        );
+
+        // Still between the same thresholds
+        test.set_balance(test.collateral, 230, NOW);
+        assert!(check(&mut tracker, &test, NOW).is_empty());
+
+        // Past two more thresholds at once, which alerts about the lowest one
+        test.set_balance(test.collateral, 205, NOW);
+        assert_eq!(vec![1.1], crossed_below(&check(&mut tracker, &test, NOW)));
+
+        test.set_balance(test.collateral, 215, NOW);
+        assert_eq!(vec![1.1], crossed_above(&check(&mut tracker, &test, NOW)));
+
+        test.set_balance(test.collateral, 300, NOW);
+        assert_eq!(vec![1.5], crossed_above(&check(&mut tracker, &test, NOW)));
+        assert!(check(&mut tracker, &test, NOW).is_empty());
+    }
+
+    #[test]
+    fn accounts_are_tracked_separately() {
+        let mut tracker = tracker();
+        let first = TestAccount::new(240);
+        let second = TestAccount::new(240);
+
+        assert_eq!(vec![1.5], crossed_below(&check(&mut tracker, &first, NOW)));
+        assert_eq!(vec![1.5], crossed_below(&check(&mut tracker, &second, NOW)));
+    }
+
+    #[test]
+    fn stale_position_alerts_once_until_refreshed() {
+        let mut tracker = tracker();
+        let mut test = TestAccount::new(300);
+        let stale_alert = AlertKind::StalePosition {
+            token: test.collateral.to_string(),
+            reason: format!("{:?}", ErrorCode::OutdatedBalance),
+        };
+
+        // The collateral balance may only be 10 seconds old
+        assert!(check(&mut tracker, &test, NOW + 10).is_empty());
+        assert_eq!(
+            vec![stale_alert.clone()],
+            stale(check(&mut tracker, &test, NOW + 11))
+        );
+        assert!(stale(check(&mut tracker, &test, NOW + 20)).is_empty());
+
+        test.set_balance(test.collateral, 300, NOW + 20);
+        test.set_balance(test.claim, 100, NOW + 20);
+        assert!(stale(check(&mut tracker, &test, NOW + 20)).is_empty());
+        assert_eq!(
+            vec![stale_alert],
+            stale(check(&mut tracker, &test, NOW + 31))
+        );
+    }
+
+    #[test]
+    fn stale_price_is_reported() {
+        let mut tracker = tracker();
+        let test = TestAccount::new(300);
+        let max_age = RiskParameters::default().max_price_quote_age as i64;
+
+        let alerts = check(&mut tracker, &test, NOW + max_age + 1);
+        assert!(alerts.contains(&AlertKind::StalePosition {
+            token: test.claim.to_string(),
+            reason: format!("{:?}", ErrorCode::OutdatedPrice),
+        }));
+    }
+
+    #[test]
+    fn term_loan_alerts_once_per_maturity() {
+        let mut tracker = tracker();
+        let user = Pubkey::new_unique();
+        let market = Pubkey::new_unique();
+        let maturity_alert = |maturity: i64, now: i64| AlertKind::TermLoanMaturity {
+            market: market.to_string(),
+            maturity,
+            seconds_remaining: maturity - now,
+        };
+
+        assert_eq!(None, tracker.check_term_loan(&user, &market, None, NOW));
+        assert_eq!(
+            None,
+            tracker.check_term_loan(&user, &market, Some(NOW + 101), NOW)
+        );
+        assert_eq!(
+            Some(maturity_alert(NOW + 100, NOW)),
+            tracker.check_term_loan(&user, &market, Some(NOW + 100), NOW)
+        );
+        assert_eq!(
+            None,
+            tracker.check_term_loan(&user, &market, Some(NOW + 100), NOW + 200)
+        );
+
+        // The next loan is already past due
+        assert_eq!(
+            Some(maturity_alert(NOW + 150, NOW + 200)),
+            tracker.check_term_loan(&user, &market, Some(NOW + 150), NOW + 200)
+        );
+
+        // Once repaid, a new loan with the same maturity is alerted again
+        assert_eq!(None, tracker.check_term_loan(&user, &market, None, NOW));
+        assert_eq!(
+            Some(maturity_alert(NOW + 100, NOW)),
+            tracker.check_term_loan(&user, &market, Some(NOW + 100), NOW)
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{bail, Result};
+use clap::Parser;
+
+use solana_clap_utils::input_validators::normalize_to_url_if_moniker;
+use solana_sdk::pubkey::Pubkey;
+
+use jet_client::{config::JetAppConfig, state::AccountStates};
+use jet_instructions::airspace::derive_airspace;
+use jet_solana_client::rpc::{native::RpcConnection, SolanaRpc};
+
+mod alerts;
+mod monitor;
+mod sinks;
+
+pub use alerts::{Alert, AlertKind, AlertTracker};
+pub use monitor::HealthMonitor;
+pub use sinks::{AlertSink, FileSink, StdoutSink, WebhookSink};
+
+static LOCALNET_URL: &str = "http://127.0.0.1:8899";
+
+#[derive(Parser, Debug)]
+pub struct CliOpts {
+    /// The filepath to the app config file with airspace and token information
+    #[clap(long, short = 'c')]
+    pub config_path: PathBuf,
+
+    /// The airspace of the margin accounts to monitor
+    #[clap(long, short = 'a', default_value = "default")]
+    pub airspace: String,
+
+    /// A margin account to monitor, which can be repeated.
+    /// All accounts in the airspace are monitored if none are given.
+    #[clap(long = "account", short = 'm')]
+    pub accounts: Vec<Pubkey>,
+
+    /// The rpc endpoint
+    /// Defaults to localhost
+    #[clap(long, short = 'u', env = "RPC_URL")]
+    pub url: Option<String>,
+
+    /// The interval to check the health of the accounts
+    #[clap(long, short = 'i', default_value = "30s")]
+    pub interval: humantime::Duration,
+
+    /// An effective c-ratio to alert on when an account crosses it, which can be repeated
+    #[clap(long = "threshold", short = 't', default_values = &["1.5", "1.25", "1.1"])]
+    pub thresholds: Vec<f64>,
+
+    /// How long before a term loan matures to alert about it
+    #[clap(long, default_value = "1day")]
+    pub maturity_warning: humantime::Duration,
+
+    /// Print alerts as JSON lines to stdout.
+    /// This is the default when no other sink is configured.
+    #[clap(long)]
+    pub stdout: bool,
+
+    /// Post each alert as JSON to a webhook
+    #[clap(long, env = "ALERT_WEBHOOK_URL")]
+    pub webhook_url: Option<String>,
+
+    /// Append alerts as JSON lines to a file
+    #[clap(long)]
+    pub alert_file: Option<PathBuf>,
+}
+
+pub async fn run(opts: CliOpts) -> Result<()> {
+    if opts.thresholds.iter().any(|t| !t.is_finite() || *t <= 0.0) {
+        bail!("c-ratio thresholds must be positive numbers");
+    }
+
+    let url = normalize_to_url_if_moniker(opts.url.as_deref().unwrap_or(LOCALNET_URL));
+    let rpc = Arc::new(RpcConnection::new(&url)) as Arc<dyn SolanaRpc>;
+    let app_config = read_config(&opts.config_path)?;
+    let airspace = derive_airspace(&opts.airspace);
+    let states = AccountStates::new(rpc.clone(), Pubkey::default(), app_config, opts.airspace)?;
+
+    let mut sinks: Vec<Box<dyn AlertSink>> = vec![];
+
+    if let Some(url) = opts.webhook_url {
+        sinks.push(Box::new(WebhookSink::new(url)));
+    }
+    if let Some(path) = opts.alert_file {
+        sinks.push(Box::new(FileSink::new(path)));
+    }
+    if opts.stdout || sinks.is_empty() {
+        sinks.push(Box::new(StdoutSink));
+    }
+
+    let interval = Duration::from(opts.interval);
+    let tracker = AlertTracker::new(
+        opts.thresholds,
+        Duration::from(opts.maturity_warning).as_secs() as i64,
+    );
+    let accounts = (!opts.accounts.is_empty()).then_some(opts.accounts);
+    let mut monitor = HealthMonitor::new(rpc, airspace, states, accounts, tracker, sinks).await?;
+
+    loop {
+        if let Err(e) = monitor.check().await {
+            log::error!("failed to check account health: {e:?}");
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+fn read_config(path: impl AsRef<Path>) -> Result<JetAppConfig> {
+    let app_json = std::fs::read_to_string(path)?;
+
+    Ok(serde_json::from_str(&app_json)?)
+}
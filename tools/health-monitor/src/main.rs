@@ -0,0 +1,19 @@
+use clap::Parser;
+use jet_health_monitor::{run, CliOpts};
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+
+    let opts = CliOpts::parse();
+
+    if let Err(e) = run(opts).await {
+        eprintln!("error: ");
+
+        for err in e.chain() {
+            eprintln!("{err}");
+        }
+
+        eprintln!("{}", e.backtrace());
+    }
+}
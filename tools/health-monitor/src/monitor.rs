@@ -0,0 +1,160 @@
+use std::sync::Arc;
+
+use anchor_lang::{AccountDeserialize, Discriminator};
+use anyhow::Result;
+use solana_sdk::{account::ReadableAccount, pubkey::Pubkey};
+
+use jet_client::state::{
+    fixed_term::{sync_user_accounts, UserState},
+    margin::load_margin_accounts,
+    AccountStates,
+};
+use jet_instructions::margin::derive_risk_config;
+use jet_margin::{MarginAccount, RiskConfig, RiskParameters};
+use jet_solana_client::rpc::{AccountFilter, SolanaRpc, SolanaRpcExtra};
+
+use crate::{Alert, AlertKind, AlertSink, AlertTracker};
+
+/// The offset of the airspace in the margin account data, after the discriminator,
+/// version, seeds, invocation, permit flag, reserved bytes and owner
+const MARGIN_ACCOUNT_AIRSPACE_OFFSET: usize = 8 + 40;
+
+/// Checks the health of margin accounts and sends alerts about them
+pub struct HealthMonitor {
+    rpc: Arc<dyn SolanaRpc>,
+    airspace: Pubkey,
+    states: AccountStates,
+    accounts: Option<Vec<Pubkey>>,
+    risk: RiskParameters,
+    tracker: AlertTracker,
+    sinks: Vec<Box<dyn AlertSink>>,
+}
+
+impl HealthMonitor {
+    /// Create a monitor for a list of accounts, or all accounts in the airspace if
+    /// `accounts` is `None`
+    pub async fn new(
+        rpc: Arc<dyn SolanaRpc>,
+        airspace: Pubkey,
+        states: AccountStates,
+        accounts: Option<Vec<Pubkey>>,
+        tracker: AlertTracker,
+        sinks: Vec<Box<dyn AlertSink>>,
+    ) -> Result<Self> {
+        let risk = match rpc
+            .try_get_anchor_account::<RiskConfig>(&derive_risk_config(&airspace))
+            .await?
+        {
+            Some(config) => config.params,
+            None => RiskParameters::default(),
+        };
+
+        Ok(Self {
+            rpc,
+            airspace,
+            states,
+            accounts,
+            risk,
+            tracker,
+            sinks,
+        })
+    }
+
+    /// Load the latest state of the accounts and send alerts for any changes in
+    /// their health
+    pub async fn check(&mut self) -> Result<()> {
+        self.states.sync_all().await?;
+
+        let accounts = match &self.accounts {
+            Some(accounts) => {
+                load_margin_accounts(&self.states, accounts).await?;
+                accounts.clone()
+            }
+            None => self.load_airspace_accounts().await?,
+        };
+
+        sync_user_accounts(&self.states).await?;
+
+        let now = self.states.get_current_time();
+        let mut alerts = vec![];
+
+        for address in &accounts {
+            let Some(account) = self.states.get::<MarginAccount>(address) else {
+                log::warn!("margin account {address} does not exist");
+                continue;
+            };
+
+            let kinds = self
+                .tracker
+                .check_account(address, &account, now, &self.risk);
+            alerts.extend(kinds.into_iter().map(|kind| alert(address, now, kind)));
+        }
+
+        for (user_address, user) in self.states.get_all::<UserState>() {
+            let margin_account = user.margin_account();
+            if !accounts.contains(&margin_account) {
+                continue;
+            }
+
+            let maturity = user
+                .debt()
+                .next_term_loan_to_repay()
+                .map(|_| user.debt().next_term_loan_maturity());
+            let kind = self
+                .tracker
+                .check_term_loan(&user_address, &user.market(), maturity, now);
+            alerts.extend(kind.map(|kind| alert(&margin_account, now, kind)));
+        }
+
+        for alert in &alerts {
+            for sink in &self.sinks {
+                if let Err(e) = sink.send(alert).await {
+                    log::error!("failed to send alert {alert:?}: {e:?}");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn load_airspace_accounts(&self) -> Result<Vec<Pubkey>> {
+        let accounts = self
+            .rpc
+            .get_program_accounts(
+                &jet_margin::ID,
+                &[
+                    AccountFilter::Memcmp {
+                        offset: 0,
+                        bytes: MarginAccount::discriminator().to_vec(),
+                    },
+                    AccountFilter::Memcmp {
+                        offset: MARGIN_ACCOUNT_AIRSPACE_OFFSET,
+                        bytes: self.airspace.to_bytes().to_vec(),
+                    },
+                ],
+            )
+            .await?;
+
+        let mut addresses = Vec::with_capacity(accounts.len());
+
+        for (address, account) in accounts {
+            match MarginAccount::try_deserialize(&mut account.data()) {
+                Ok(state) => {
+                    self.states.set(&address, state);
+                    addresses.push(address);
+                }
+                Err(e) => log::warn!("invalid margin account {address}: {e:?}"),
+            }
+        }
+
+        Ok(addresses)
+    }
+}
+
+fn alert(margin_account: &Pubkey, timestamp: i64, kind: AlertKind) -> Alert {
+    Alert {
+        margin_account: margin_account.to_string(),
+        timestamp,
+        kind,
+    }
+}
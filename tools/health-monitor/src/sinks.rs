@@ -0,0 +1,145 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::Alert;
+
+/// A destination for alerts
+#[async_trait]
+pub trait AlertSink: Send + Sync {
+    async fn send(&self, alert: &Alert) -> Result<()>;
+}
+
+/// Prints each alert as a line of JSON to stdout
+pub struct StdoutSink;
+
+#[async_trait]
+impl AlertSink for StdoutSink {
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        println!("{}", serde_json::to_string(alert)?);
+        Ok(())
+    }
+}
+
+/// Appends each alert as a line of JSON to a file, which is useful for local testing
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+#[async_trait]
+impl AlertSink for FileSink {
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        writeln!(file, "{}", serde_json::to_string(alert)?)?;
+        Ok(())
+    }
+}
+
+/// Posts each alert as JSON to a webhook
+pub struct WebhookSink {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookSink {
+    pub fn new(url: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+        }
+    }
+}
+
+#[async_trait]
+impl AlertSink for WebhookSink {
+    async fn send(&self, alert: &Alert) -> Result<()> {
+        self.client
+            .post(&self.url)
+            .json(alert)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jet_margin::RiskParameters;
+    use serde_json::{json, Value};
+
+    use crate::alerts::tests::{TestAccount, NOW};
+    use crate::AlertTracker;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn file_sink_receives_tracked_alerts() -> Result<()> {
+        let path = std::env::temp_dir().join(format!(
+            "jet-health-monitor-{}.jsonl",
+            solana_sdk::pubkey::Pubkey::new_unique()
+        ));
+        let sink = FileSink::new(path.clone());
+        let mut tracker = AlertTracker::new(vec![1.5], 0);
+        let mut test = TestAccount::new(240);
+
+        for collateral in [240, 230, 300] {
+            test.set_balance(test.collateral, collateral, NOW);
+
+            for kind in tracker.check_account(
+                &test.address,
+                &test.account,
+                NOW,
+                &RiskParameters::default(),
+            ) {
+                let alert = Alert {
+                    margin_account: test.address.to_string(),
+                    timestamp: NOW,
+                    kind,
+                };
+                sink.send(&alert).await?;
+            }
+        }
+
+        let contents = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        let lines = contents
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<Value>, _>>()?;
+
+        assert_eq!(
+            vec![
+                json!({
+                    "margin_account": test.address.to_string(),
+                    "timestamp": NOW,
+                    "alert": "c_ratio_below",
+                    "threshold": 1.5,
+                    "c_ratio": 1.4,
+                }),
+                json!({
+                    "margin_account": test.address.to_string(),
+                    "timestamp": NOW,
+                    "alert": "c_ratio_above",
+                    "threshold": 1.5,
+                    "c_ratio": 2.0,
+                }),
+            ],
+            lines
+        );
+
+        Ok(())
+    }
+}
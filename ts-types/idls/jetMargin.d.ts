@@ -192,6 +192,7 @@ type JetMarginIDL = {
           "| `authority` | `signer` | The authority that can change the margin account. |",
           "| `payer` | `signer` | The address paying for rent. |",
           "| `margin_account` | `writable` |  The margin account to register position type with. |",
+          "| `risk_config` | `read_only` | The risk parameters for the airspace, if configured. |",
           "| `position_token_mint` | `read_only` | The mint for the position token being registered. |",
           "| `metadata` | `read_only` | The metadata account that references the correct oracle for the token. |",
           "| `token_account` | `writable` | The token account to store hold the position assets in the custody of the margin account. |",
@@ -387,6 +388,7 @@ type JetMarginIDL = {
           "| --- | --- | --- |",
           "| **Name** | **Type** | **Description** |",
           "| `margin_account` | `read_only` | The account to verify the health of. |",
+          "| `risk_config` | `read_only` | The risk parameters for the airspace, if configured. |",
           "",
           "# Events",
           "",
@@ -433,6 +435,7 @@ type JetMarginIDL = {
           "| **Name** | **Type** | **Description** |",
           "| `owner` | `signer` | The authority that owns the margin account. |",
           "| `margin_account` | `writable` | The margin account to proxy an action for. |",
+          "| `risk_config` | `read_only` | The risk parameters for the airspace, if configured. |",
           "| `adapter_program` | `read_only` | The program to be invoked. |",
           "| `adapter_metadata` | `read_only` | The metadata about the proxy program. |",
           "",
@@ -503,6 +506,7 @@ type JetMarginIDL = {
           "| --- | --- | --- |",
           "| **Name** | **Type** |  **Description** |",
           "| `margin_account` | `writable` | The margin account to proxy an action for. |",
+          "| `risk_config` | `read_only` | The risk parameters for the airspace, if configured. |",
           "| `adapter_program` | `read_only` | The program to be invoked. |",
           "| `adapter_metadata` | `read_only` | The metadata about the proxy program. |",
           "",
@@ -542,6 +546,76 @@ type JetMarginIDL = {
           }
         ]
       },
+      {
+        name: "keeperInvoke"
+        docs: [
+          "Perform an action by invoking an adapter on behalf of the owner, without their signature.",
+          "",
+          "This allows anyone to act as a keeper that executes actions which the owner",
+          "authorized ahead of time, such as conditional orders. Only the adapter instruction",
+          "named [KEEPER_EXECUTE_INSTRUCTION] may be invoked, and the adapter must verify the",
+          "authorization. The action may not increase the balance of any claim against the",
+          "account, and the account must be healthy afterwards.",
+          "",
+          "All extra accounts passed in are used as the input accounts when invoking",
+          "the provided adapter porgram.",
+          "",
+          "# Parameters",
+          "",
+          "* `data` - The instruction data to pass to the adapter program",
+          "",
+          "# [Accounts](jet_margin::accounts::KeeperInvoke)",
+          "",
+          "|     |     |     |",
+          "| --- | --- | --- |",
+          "| **Name** | **Type** | **Description** |",
+          "| `keeper` | `signer` | The keeper executing the action. |",
+          "| `margin_account` | `writable` | The margin account to proxy an action for. |",
+          "| `adapter_program` | `read_only` | The program to be invoked. |",
+          "| `adapter_config` | `read_only` | The configuration of the adapter in the account's airspace. |",
+          "",
+          "# Events",
+          "",
+          "|     |     |",
+          "| --- | --- |",
+          "| **Event Name** | **Description** |",
+          "| [`events::KeeperInvokeBegin`] | Marks the start of the keeper invocation. |",
+          "| [`events::PositionEvent`] _(Note that each single event represents a different adapter position)_ | The [PositionEvent](events::PositionEvent) marks the change in position. |",
+          "| [`events::KeeperInvokeEnd`] | Marks the ending of the keeper invocation. |"
+        ]
+        accounts: [
+          {
+            name: "keeper"
+            isMut: false
+            isSigner: true
+            docs: ["The keeper executing an action on behalf of the margin account"]
+          },
+          {
+            name: "marginAccount"
+            isMut: true
+            isSigner: false
+            docs: ["The margin account to proxy an action for"]
+          },
+          {
+            name: "adapterProgram"
+            isMut: false
+            isSigner: false
+            docs: ["The program to be invoked"]
+          },
+          {
+            name: "adapterConfig"
+            isMut: false
+            isSigner: false
+            docs: ["The metadata about the proxy program"]
+          }
+        ]
+        args: [
+          {
+            name: "data"
+            type: "bytes"
+          }
+        ]
+      },
       {
         name: "liquidateBegin"
         docs: [
@@ -559,6 +633,7 @@ type JetMarginIDL = {
           "| --- | --- | --- |",
           "| **Name** | **Type** | **Description** |",
           "| `margin_account` | `writable` | The account in need of liquidation. |",
+          "| `risk_config` | `read_only` | The risk parameters for the airspace, if configured. |",
           "| `payer` | `signer` | The address paying rent. |",
           "| `liquidator` | `signer` | The liquidator account performing the liquidation. |",
           "| `liquidator_metadata` | `read_only` | The metadata describing the liquidator. |",
@@ -626,6 +701,7 @@ type JetMarginIDL = {
           "| **Name** | **Type** | **Description** |",
           "| `authority` | `signer` | The pubkey calling the instruction to end liquidation. |",
           "| `margin_account` | `writable` | The account in need of liquidation. |",
+          "| `risk_config` | `read_only` | The risk parameters for the airspace, if configured. |",
           "| `liquidation` | `writable` | The account to persist the state of liquidation. |",
           "",
           "# Events",
@@ -676,6 +752,7 @@ type JetMarginIDL = {
           "| `liquidator` | `signer` | The liquidator processing the margin account. |",
           "| `liquidation` | `writable` | The account to persist the state of liquidation. |",
           "| `margin_account` | `writable` | The margin account to proxy an action for. |",
+          "| `risk_config` | `read_only` | The risk parameters for the airspace, if configured. |",
           "| `adapter_program` | `read_only` | The program to be invoked. |",
           "| `adapter_metadata` | `read_only` | The metadata about the proxy program. |",
           "",
@@ -957,6 +1034,60 @@ type JetMarginIDL = {
           }
         ]
       },
+      {
+        name: "configureRisk"
+        docs: [
+          "Set the risk parameters for all margin accounts in an airspace.",
+          "",
+          "Airspaces without any configured parameters use the defaults, which are the",
+          "program-wide constants. Changing the parameters requires the airspace authority",
+          "to sign.",
+          "",
+          "The account storing the configuration will be funded if not already. If a `None` is provided as",
+          "the updated configuration, then the account will be defunded and the defaults apply again."
+        ]
+        accounts: [
+          {
+            name: "authority"
+            isMut: false
+            isSigner: true
+            docs: ["The authority allowed to make changes to configuration"]
+          },
+          {
+            name: "airspace"
+            isMut: false
+            isSigner: false
+            docs: ["The airspace being modified"]
+          },
+          {
+            name: "payer"
+            isMut: true
+            isSigner: true
+            docs: ["The payer for any rent costs, if required"]
+          },
+          {
+            name: "riskConfig"
+            isMut: true
+            isSigner: false
+            docs: ["The config account to be modified"]
+          },
+          {
+            name: "systemProgram"
+            isMut: false
+            isSigner: false
+          }
+        ]
+        args: [
+          {
+            name: "params"
+            type: {
+              option: {
+                defined: "RiskParameters"
+              }
+            }
+          }
+        ]
+      },
       {
         name: "configureAdapter"
         docs: [
@@ -1534,6 +1665,27 @@ type JetMarginIDL = {
           ]
         }
       },
+      {
+        name: "riskConfig"
+        docs: ["Risk parameters that apply to every margin account within an airspace"]
+        type: {
+          kind: "struct"
+          fields: [
+            {
+              name: "airspace"
+              docs: ["The airspace these parameters apply to"]
+              type: "publicKey"
+            },
+            {
+              name: "params"
+              docs: ["The parameters in effect for the airspace"]
+              type: {
+                defined: "RiskParameters"
+              }
+            }
+          ]
+        }
+      },
       {
         name: "Permit"
         docs: ["Configuration enabling a signer to execute permissioned actions"]
@@ -1702,6 +1854,72 @@ type JetMarginIDL = {
           ]
         }
       },
+      {
+        name: "RiskParameters"
+        docs: [
+          "Limits used when valuing and liquidating margin accounts.",
+          "",
+          "The defaults are the program-wide constants that applied before these could be",
+          "configured for each airspace."
+        ]
+        type: {
+          kind: "struct"
+          fields: [
+            {
+              name: "maxOracleConfidence"
+              docs: [
+                "The maximum confidence deviation allowed for an oracle price, as the ratio of",
+                "the confidence interval to the average price (in bps)"
+              ]
+              type: "u16"
+            },
+            {
+              name: "maxOracleStaleness"
+              docs: [
+                "The maximum number of seconds since the last price was published by an oracle,",
+                "before rejecting the price as too stale"
+              ]
+              type: "i64"
+            },
+            {
+              name: "maxPriceQuoteAge"
+              docs: ["The maximum age (seconds) to allow for a quoted price for a position"]
+              type: "u64"
+            },
+            {
+              name: "liquidationMaxEquityLossProportionBps"
+              docs: [
+                "The maximum amount of equity that can be lost during liquidation, as a proportion",
+                "of the account's liabilities (in bps)"
+              ]
+              type: "u16"
+            },
+            {
+              name: "liquidationMaxEquityLossConstant"
+              docs: ["The dollar value that is always allowed to be lost during liquidation"]
+              type: "u64"
+            },
+            {
+              name: "liquidationTimeout"
+              docs: ["The maximum duration (seconds) of a liquidation before another user may cancel it"]
+              type: "i64"
+            },
+            {
+              name: "liquidationMaxPriceDeviation"
+              docs: [
+                "The maximum loss in value against the oracle prices allowed for liquidation swaps,",
+                "including the liquidation fee (in bps)"
+              ]
+              type: "u16"
+            },
+            {
+              name: "maxUserPositions"
+              docs: ["The maximum number of positions that a user can register"]
+              type: "u64"
+            }
+          ]
+        }
+      },
       {
         name: "AdapterPositionFlags"
         type: {
@@ -2216,6 +2434,30 @@ type JetMarginIDL = {
         name: "AdapterInvokeEnd"
         fields: []
       },
+      {
+        name: "KeeperInvokeBegin"
+        fields: [
+          {
+            name: "marginAccount"
+            type: "publicKey"
+            index: false
+          },
+          {
+            name: "adapterProgram"
+            type: "publicKey"
+            index: false
+          },
+          {
+            name: "keeper"
+            type: "publicKey"
+            index: false
+          }
+        ]
+      },
+      {
+        name: "KeeperInvokeEnd"
+        fields: []
+      },
       {
         name: "LiquidationBegun"
         fields: [
@@ -2363,6 +2605,25 @@ type JetMarginIDL = {
           }
         ]
       },
+      {
+        name: "RiskConfigured"
+        fields: [
+          {
+            name: "airspace"
+            type: "publicKey"
+            index: false
+          },
+          {
+            name: "params"
+            type: {
+              option: {
+                defined: "RiskParameters"
+              }
+            }
+            index: false
+          }
+        ]
+      },
       {
         name: "AdapterConfigured"
         fields: [
@@ -2426,6 +2687,16 @@ type JetMarginIDL = {
         name: "IndirectInvocation"
         msg: "the current instruction was not directly invoked by the margin program"
       },
+      {
+        code: 141004
+        name: "UnauthorizedKeeperInstruction"
+        msg: "keepers may only invoke the keeper_execute instruction of an adapter"
+      },
+      {
+        code: 141005
+        name: "KeeperIncreasedLiabilities"
+        msg: "keepers are not allowed to increase the claims against an account"
+      },
       {
         code: 141010
         name: "MaxPositions"